@@ -0,0 +1,264 @@
+use serde_json::Value;
+use std::path::Path;
+
+/// Which columns of a capture hold what
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns {
+    /// Controller output
+    pub output: String,
+    /// Process value
+    pub process_value: String,
+    /// Only rows whose `controller_id` matches, if set
+    pub controller_id: Option<String>,
+}
+
+/// A recording of one loop, sampled at a fixed rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub outputs: Vec<f64>,
+    pub process_values: Vec<f64>,
+    /// Seconds between samples
+    pub dt: f64,
+    /// Last `setpoint` in the capture, if it has that column
+    pub setpoint: Option<f64>,
+}
+
+/// One row, as name and value pairs
+type Row = Vec<(String, String)>;
+
+impl Capture {
+    /// Load a CSV file with a header row, or JSON lines if the file ends in
+    /// `.jsonl` or `.ndjson`
+    ///
+    /// Both `pidgeon-cli simulate --csv` output and `FileSink` captures load
+    /// as-is. `dt` comes from `dt_override`, else the median of a `dt` column,
+    /// else the median step of a `time` column in seconds.
+    pub fn load(path: &Path, columns: &Columns, dt_override: Option<f64>) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let json_lines = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("jsonl" | "ndjson")
+        );
+        let rows = if json_lines {
+            parse_json_lines(&contents)
+        } else {
+            parse_csv(&contents)
+        };
+        rows.and_then(|rows| Self::from_rows(&rows, columns, dt_override))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn from_rows(
+        rows: &[Row],
+        columns: &Columns,
+        dt_override: Option<f64>,
+    ) -> Result<Self, String> {
+        let rows: Vec<&Row> = rows
+            .iter()
+            .filter(|row| match &columns.controller_id {
+                Some(id) => field(row, "controller_id") == Some(id.as_str()),
+                None => true,
+            })
+            .collect();
+        if rows.is_empty() {
+            return Err(match &columns.controller_id {
+                Some(id) => format!("no rows for controller '{}'", id),
+                None => "no rows".to_string(),
+            });
+        }
+
+        let number = |row: &Row, line: usize, name: &str| -> Result<Option<f64>, String> {
+            match field(row, name) {
+                None => Ok(None),
+                Some(text) => text
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|n: &f64| n.is_finite())
+                    .map(Some)
+                    .ok_or_else(|| format!("row {}: {} '{}' is not a number", line, name, text)),
+            }
+        };
+        let required = |row: &Row, line: usize, name: &str| {
+            number(row, line, name)?.ok_or_else(|| format!("row {}: no '{}' column", line, name))
+        };
+
+        let mut capture = Capture {
+            outputs: Vec::with_capacity(rows.len()),
+            process_values: Vec::with_capacity(rows.len()),
+            dt: 0.0,
+            setpoint: None,
+        };
+        let mut dts = Vec::new();
+        let mut times = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            let line = i + 1;
+            capture.outputs.push(required(row, line, &columns.output)?);
+            capture
+                .process_values
+                .push(required(row, line, &columns.process_value)?);
+            dts.extend(number(row, line, "dt")?);
+            times.extend(number(row, line, "time")?);
+            if let Some(setpoint) = number(row, line, "setpoint")? {
+                capture.setpoint = Some(setpoint);
+            }
+        }
+
+        let steps: Vec<f64> = times.windows(2).map(|w| w[1] - w[0]).collect();
+        capture.dt = match dt_override {
+            Some(dt) => dt,
+            None if !dts.is_empty() => median(dts),
+            None if !steps.is_empty() => median(steps),
+            None => return Err("no 'dt' or 'time' column; pass --dt".to_string()),
+        };
+        if !capture.dt.is_finite() || capture.dt <= 0.0 {
+            return Err(format!(
+                "sample interval {} is not a positive number",
+                capture.dt
+            ));
+        }
+        Ok(capture)
+    }
+}
+
+fn field<'a>(row: &'a Row, name: &str) -> Option<&'a str> {
+    row.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<Row>, String> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header = split_csv_line(lines.next().ok_or("the file is empty")?);
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let fields = split_csv_line(line);
+            if fields.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} fields but the header has {}",
+                    i + 1,
+                    fields.len(),
+                    header.len()
+                ));
+            }
+            Ok(header.iter().cloned().zip(fields).collect())
+        })
+        .collect()
+}
+
+/// Split one CSV line, honouring double-quoted fields with `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        let current = fields.last_mut().expect("fields is never empty");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => current.push(c),
+        }
+    }
+    fields
+}
+
+fn parse_json_lines(contents: &str) -> Result<Vec<Row>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let Value::Object(object) = serde_json::from_str(line)
+                .map_err(|e| format!("line {} is not JSON: {}", i + 1, e))?
+            else {
+                return Err(format!("line {} is not a JSON object", i + 1));
+            };
+            Ok(object
+                .into_iter()
+                .map(|(key, value)| {
+                    let text = match value {
+                        Value::String(text) => text,
+                        other => other.to_string(),
+                    };
+                    (key, text)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Columns {
+        Columns {
+            output: "output".to_string(),
+            process_value: "process_value".to_string(),
+            controller_id: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_with_time_column() {
+        let csv = "time,setpoint,process_value,output\n\
+                   0,5,1.0,10\n0.5,5,1.5,10\n1.0,6,2.0,12\n";
+        let rows = parse_csv(csv).unwrap();
+        let capture = Capture::from_rows(&rows, &columns(), None).unwrap();
+        assert_eq!(capture.outputs, vec![10.0, 10.0, 12.0]);
+        assert_eq!(capture.process_values, vec![1.0, 1.5, 2.0]);
+        assert_eq!(capture.dt, 0.5);
+        assert_eq!(capture.setpoint, Some(6.0));
+
+        let capture = Capture::from_rows(&rows, &columns(), Some(0.1)).unwrap();
+        assert_eq!(capture.dt, 0.1);
+    }
+
+    #[test]
+    fn test_file_sink_rows_filtered_by_controller() {
+        let csv = "controller_id,process_value,output,dt\n\
+                   \"oven, top\",1,2,0.1\nfan,9,9,0.5\n\"oven, top\",3,4,0.1\n";
+        let rows = parse_csv(csv).unwrap();
+        let mut columns = columns();
+        columns.controller_id = Some("oven, top".to_string());
+        let capture = Capture::from_rows(&rows, &columns, None).unwrap();
+        assert_eq!(capture.process_values, vec![1.0, 3.0]);
+        assert_eq!(capture.dt, 0.1);
+
+        columns.controller_id = Some("pump".to_string());
+        assert!(Capture::from_rows(&rows, &columns, None).is_err());
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let jsonl = r#"{"controller_id": "a", "process_value": 1.0, "output": 2.0, "dt": 0.2}
+
+{"controller_id": "a", "process_value": 1.5, "output": 2.0, "dt": 0.2}"#;
+        let rows = parse_json_lines(jsonl).unwrap();
+        let capture = Capture::from_rows(&rows, &columns(), None).unwrap();
+        assert_eq!(capture.process_values, vec![1.0, 1.5]);
+        assert_eq!(capture.dt, 0.2);
+    }
+
+    #[test]
+    fn test_rejects_bad_captures() {
+        let load = |csv: &str| Capture::from_rows(&parse_csv(csv)?, &columns(), None);
+        assert!(load("process_value,output\n1,2\n").is_err());
+        assert!(load("process_value,output,dt\n1,x,0.1\n").is_err());
+        assert!(load("process_value,dt\n1,0.1\n").is_err());
+        assert!(load("process_value,output,dt\n1,2\n").is_err());
+        assert!(load("process_value,output,dt\n1,2,0\n").is_err());
+        assert!(load("").is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use clap::Args;
+use pidgeon::{FopdtModel, PidError, PidGains, TuningRule};
+use std::fmt::Write as _;
+use std::process::ExitCode;
+
+/// `pidgeon-cli gains` flags
+#[derive(Debug, Clone, Args)]
+pub struct GainsArgs {
+    /// Process model as k=<gain>,tau=<time constant>,theta=<dead time>, times
+    /// in seconds; theta defaults to 0
+    #[arg(long, value_parser = parse_model)]
+    pub model: FopdtModel,
+    /// Tuning rule: simc, simc-pid, lambda, zn-pi, zn-pid, cohen-coon-pi, or
+    /// cohen-coon-pid [default: every rule]
+    #[arg(long)]
+    pub rule: Option<TuningRule>,
+    /// Print the gains as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Parse `k=2,tau=30,theta=5`; `gain`, `time_constant`, and `dead_time` are
+/// accepted as well
+pub fn parse_model(s: &str) -> Result<FopdtModel, String> {
+    let (mut gain, mut time_constant, mut dead_time) = (None, None, None);
+    for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", pair))?;
+        let value: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", value.trim()))?;
+        let slot = match key.trim() {
+            "k" | "gain" => &mut gain,
+            "tau" | "time_constant" => &mut time_constant,
+            "theta" | "dead_time" => &mut dead_time,
+            other => return Err(format!("unknown model parameter '{}'", other)),
+        };
+        if slot.replace(value).is_some() {
+            return Err(format!("'{}' is given twice", key.trim()));
+        }
+    }
+    let gain = gain.ok_or("missing k")?;
+    let time_constant = time_constant.ok_or("missing tau")?;
+    FopdtModel::new(gain, time_constant, dead_time.unwrap_or(0.0)).map_err(|e| match e {
+        PidError::InvalidParameter(reason) => reason.to_string(),
+        other => other.to_string(),
+    })
+}
+
+/// Standard-form gain `Kc`, integral time `Ti`, and derivative time `Td` of
+/// parallel-form `gains`; `Ti` is infinite without integral action
+pub fn standard_form(gains: &PidGains) -> (f64, f64, f64) {
+    let ti = if gains.ki == 0.0 {
+        f64::INFINITY
+    } else {
+        gains.kp / gains.ki
+    };
+    (gains.kp, ti, gains.kd / gains.kp)
+}
+
+/// Gains from each of `rules` in both forms, or why a rule does not apply
+pub fn table(model: &FopdtModel, rules: &[TuningRule]) -> String {
+    let mut text = format!(
+        "{:<16}{:>14}{:>14}{:>14}{:>14}{:>14}{:>14}\n",
+        "rule", "kp", "ki", "kd", "Kc", "Ti (s)", "Td (s)"
+    );
+    for rule in rules {
+        let _ = match rule.gains(model) {
+            Ok(g) => {
+                let (kc, ti, td) = standard_form(&g);
+                writeln!(
+                    text,
+                    "{:<16}{:>14.6}{:>14.6}{:>14.6}{:>14.6}{:>14.3}{:>14.3}",
+                    rule.name(),
+                    g.kp,
+                    g.ki,
+                    g.kd,
+                    kc,
+                    ti,
+                    td
+                )
+            }
+            Err(e) => writeln!(text, "{:<16}{}", rule.name(), e),
+        };
+    }
+    text
+}
+
+/// Human-readable gains from one rule
+fn report(model: &FopdtModel, rule: TuningRule, gains: &PidGains) -> String {
+    let (kc, ti, td) = standard_form(gains);
+    let mut text = format!(
+        "{} for k={}, tau={} s, theta={} s\n",
+        rule, model.gain, model.time_constant, model.dead_time
+    );
+    let _ = writeln!(text, "parallel form");
+    let _ = writeln!(text, "  {:<16}{:.6}", "kp", gains.kp);
+    let _ = writeln!(text, "  {:<16}{:.6}", "ki", gains.ki);
+    let _ = writeln!(text, "  {:<16}{:.6}", "kd", gains.kd);
+    let _ = writeln!(text, "standard form");
+    let _ = writeln!(text, "  {:<16}{:.6}", "Kc", kc);
+    let _ = writeln!(text, "  {:<16}{:.3} s", "Ti", ti);
+    let _ = writeln!(text, "  {:<16}{:.3} s", "Td", td);
+    text
+}
+
+/// Gains from each of `rules` as JSON; rules that do not apply carry an
+/// `error` instead, and an infinite `Ti` is null
+fn json(model: &FopdtModel, rules: &[TuningRule]) -> serde_json::Value {
+    let entries = rules.iter().map(|rule| match rule.gains(model) {
+        Ok(g) => {
+            let (kc, ti, td) = standard_form(&g);
+            serde_json::json!({
+                "rule": rule.name(),
+                "kp": g.kp,
+                "ki": g.ki,
+                "kd": g.kd,
+                "kc": kc,
+                "ti": ti.is_finite().then_some(ti),
+                "td": td,
+            })
+        }
+        Err(e) => serde_json::json!({ "rule": rule.name(), "error": e.to_string() }),
+    });
+    serde_json::Value::Array(entries.collect())
+}
+
+/// Run the `gains` command
+pub fn run(args: GainsArgs) -> Result<ExitCode, String> {
+    let rules = match args.rule {
+        Some(rule) => vec![rule],
+        None => TuningRule::ALL.to_vec(),
+    };
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json(&args.model, &rules)).map_err(|e| e.to_string())?
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+    match args.rule {
+        Some(rule) => {
+            let gains = rule
+                .gains(&args.model)
+                .map_err(|e| format!("cannot apply {}: {}", rule, e))?;
+            print!("{}", report(&args.model, rule, &gains));
+        }
+        None => print!("{}", table(&args.model, &rules)),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: GainsArgs,
+    }
+
+    #[test]
+    fn test_parse_model() {
+        let model = parse_model("k=2,tau=30,theta=5").unwrap();
+        assert_eq!(model, FopdtModel::new(2.0, 30.0, 5.0).unwrap());
+        let model = parse_model(" time_constant = 8, gain=-0.5 ").unwrap();
+        assert_eq!(model, FopdtModel::new(-0.5, 8.0, 0.0).unwrap());
+
+        assert!(parse_model("tau=30").unwrap_err().contains("missing k"));
+        assert!(parse_model("k=2,tau=30,k=3").unwrap_err().contains("twice"));
+        assert!(parse_model("k=2,tau=30,zeta=1")
+            .unwrap_err()
+            .contains("zeta"));
+        assert!(parse_model("k=2,tau").is_err());
+        assert!(parse_model("k=two,tau=30").is_err());
+        assert!(parse_model("k=0,tau=30").unwrap_err().contains("gain"));
+    }
+
+    #[test]
+    fn test_standard_form_round_trips() {
+        let gains = PidGains::from_standard(1.8, 10.0, 2.5);
+        let (kc, ti, td) = standard_form(&gains);
+        assert!((kc - 1.8).abs() < 1e-12);
+        assert!((ti - 10.0).abs() < 1e-12);
+        assert!((td - 2.5).abs() < 1e-12);
+
+        let (_, ti, td) = standard_form(&PidGains {
+            kp: 2.0,
+            ki: 0.0,
+            kd: 0.0,
+        });
+        assert!(ti.is_infinite());
+        assert_eq!(td, 0.0);
+    }
+
+    #[test]
+    fn test_report_and_table() {
+        let model = parse_model("k=2,tau=30,theta=5").unwrap();
+        let gains = TuningRule::ZieglerNicholsPid.gains(&model).unwrap();
+        // Kc = 1.2·τ/(k·θ), Ti = 2θ, Td = θ/2
+        let text = report(&model, TuningRule::ZieglerNicholsPid, &gains);
+        assert!(text.contains("Kc              3.600000"), "{}", text);
+        assert!(text.contains("Ti              10.000 s"), "{}", text);
+        assert!(text.contains("Td              2.500 s"), "{}", text);
+        assert!(text.contains("kd              9.000000"), "{}", text);
+
+        let no_dead_time = parse_model("k=2,tau=30").unwrap();
+        let text = table(&no_dead_time, &TuningRule::ALL);
+        assert_eq!(text.lines().count(), TuningRule::ALL.len() + 1);
+        assert!(text.contains("dead time"), "{}", text);
+        assert!(text.contains("simc                  5.000000"), "{}", text);
+    }
+
+    #[test]
+    fn test_json() {
+        let model = parse_model("k=2,tau=30").unwrap();
+        let value = json(&model, &[TuningRule::SimcPi, TuningRule::ZieglerNicholsPi]);
+        assert_eq!(value[0]["rule"], "simc");
+        assert!(value[0]["ti"].is_number());
+        assert!(value[0]["error"].is_null());
+        assert!(value[1]["error"].is_string());
+
+        let value = json(&model, &[TuningRule::LambdaPi]);
+        assert_eq!(value[0]["td"], 0.0);
+        assert!(value[0]["kd"].is_number());
+    }
+
+    #[test]
+    fn test_args() {
+        let args =
+            Cli::parse_from(["gains", "--model", "k=2,tau=30,theta=5", "--rule", "zn-pid"]).args;
+        assert_eq!(args.rule, Some(TuningRule::ZieglerNicholsPid));
+        assert_eq!(args.model.dead_time, 5.0);
+        assert_eq!(
+            Cli::parse_from(["gains", "--model", "k=1,tau=1"]).args.rule,
+            None
+        );
+
+        assert!(Cli::try_parse_from(["gains"]).is_err());
+        assert!(Cli::try_parse_from(["gains", "--model", "k=1"]).is_err());
+        assert!(Cli::try_parse_from(["gains", "--model", "k=1,tau=1", "--rule", "pid"]).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! `pidgeon-cli`: command-line tools for pidgeon PID controllers.
+//!
+//! `simulate` runs a controller against a simulated plant described in a
+//! TOML scenario, with setpoint changes, disturbances and measurement noise,
+//! then reports metrics and optionally writes CSV and a plot. Limits in the
+//! scenario turn it into a pass/fail check, so a tune can be evaluated in CI
+//! and gain sweeps can be scripted.
+//!
+//! `sweep` simulates a scenario for every combination in a grid of gains,
+//! optionally as Monte-Carlo runs over noise seeds, and ranks them by a
+//! metric. With the default `parallel` feature the runs are spread over every
+//! core with rayon.
+//!
+//! `tune` fits a first-order-plus-dead-time model to a recorded step test
+//! and turns it into a `ControllerConfig` with one of pidgeon's tuning rules.
+//!
+//! `gains` applies the tuning rules to a process model given on the command
+//! line and prints the gains in parallel and standard form, for quick
+//! what-if comparisons.
+//!
+//! `replay` re-publishes a recorded debug capture to Iggy or MQTT with its
+//! original pacing, so Pidgeoneer can be developed and demoed without live
+//! controllers.
+//!
+//! `serial` attaches to firmware speaking pidgeon's `serial` line protocol,
+//! prints its telemetry, and sends gain and setpoint changes typed at the
+//! prompt, for tuning on the bench.
+//!
+//! `watch` follows the debug stream on Iggy or MQTT and draws live sparklines
+//! and window statistics per controller in the terminal, for machines reached
+//! over SSH where the Pidgeoneer dashboard is out of reach. It needs the
+//! default `tui` feature.
+//!
+//! ```bash
+//! pidgeon-cli simulate fermenter.toml --csv run.csv --plot run.png
+//! pidgeon-cli sweep fermenter.toml --kp 10:100:19 --ki 0.005:0.05:10 --rank overshoot
+//! pidgeon-cli tune --input step_test.csv --rule simc --write controller.toml
+//! pidgeon-cli gains --model k=2,tau=30,theta=5 --rule zn-pid
+//! pidgeon-cli replay oven_debug.jsonl --to iggy://localhost:8090 --loop
+//! pidgeon-cli serial /dev/ttyACM0 --baud 115200 --csv bench.csv
+//! pidgeon-cli watch --from mqtt://broker.local --controller-id oven
+//! ```
+
+mod capture;
+mod gains;
+mod plant;
+mod plot;
+mod port;
+mod publish;
+mod replay;
+mod scenario;
+mod serial;
+mod simulate;
+#[cfg(feature = "tui")]
+mod subscribe;
+mod sweep;
+mod tune;
+#[cfg(feature = "tui")]
+mod watch;
+
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+/// Command-line flags
+#[derive(Debug, Parser)]
+#[command(
+    name = "pidgeon-cli",
+    about = "Simulate, tune, replay, watch, and bench-tune pidgeon PID controllers"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run a scenario against a simulated plant and report how the tune did;
+    /// exits with status 1 if a limit is exceeded
+    Simulate(simulate::SimulateArgs),
+    /// Simulate a scenario over a grid of gains, optionally several times
+    /// with different noise, and rank the results
+    Sweep(sweep::SweepArgs),
+    /// Identify the process from a recorded step test and suggest a
+    /// controller config
+    Tune(tune::TuneArgs),
+    /// Apply tuning rules to a process model and print the gains in parallel
+    /// and standard form
+    Gains(gains::GainsArgs),
+    /// Re-publish a recorded debug capture to Iggy or MQTT with its original
+    /// pacing
+    Replay(replay::ReplayArgs),
+    /// Attach to a controller over a serial port to watch its telemetry and
+    /// change gains interactively
+    Serial(serial::SerialArgs),
+    /// Follow the debug stream on Iggy or MQTT with live charts and
+    /// statistics in the terminal
+    #[cfg(feature = "tui")]
+    Watch(watch::WatchArgs),
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Simulate(args) => simulate::run(args),
+        Command::Sweep(args) => sweep::run(args),
+        Command::Tune(args) => tune::run(args),
+        Command::Gains(args) => gains::run(args),
+        Command::Replay(args) => replay::run(args),
+        Command::Serial(args) => serial::run(args),
+        #[cfg(feature = "tui")]
+        Command::Watch(args) => watch::run(args),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::scenario::PlantConfig;
+use std::collections::VecDeque;
+
+/// A [`PlantConfig`] being simulated at a fixed step
+pub struct Plant {
+    config: PlantConfig,
+    dt: f64,
+    /// Inputs not yet felt, oldest first; as long as the dead time in steps
+    delay: VecDeque<f64>,
+    value: f64,
+    /// Rate of change of the process value, for second-order plants
+    velocity: f64,
+}
+
+impl Plant {
+    /// Start `config` at rest, stepping `dt` seconds at a time
+    pub fn new(config: &PlantConfig, dt: f64) -> Self {
+        let value = match *config {
+            PlantConfig::FirstOrder { ambient, .. } => ambient,
+            PlantConfig::Integrating { initial, .. } | PlantConfig::SecondOrder { initial, .. } => {
+                initial
+            }
+        };
+        let delay_steps = (config.dead_time() / dt).round() as usize;
+        Plant {
+            config: config.clone(),
+            dt,
+            delay: vec![0.0; delay_steps].into(),
+            value,
+            velocity: 0.0,
+        }
+    }
+
+    /// Current process value
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Apply `input` for one step and return the new process value
+    pub fn step(&mut self, input: f64) -> f64 {
+        self.delay.push_back(input);
+        let input = self.delay.pop_front().unwrap_or(input);
+        let dt = self.dt;
+        match self.config {
+            PlantConfig::FirstOrder {
+                gain,
+                time_constant,
+                ambient,
+                ..
+            } => {
+                // Exact for an input held over the step
+                let target = ambient + gain * input;
+                self.value = target + (self.value - target) * (-dt / time_constant).exp();
+            }
+            PlantConfig::Integrating { gain, .. } => {
+                self.value += gain * input * dt;
+            }
+            PlantConfig::SecondOrder {
+                mass,
+                damping,
+                stiffness,
+                ..
+            } => {
+                // Semi-implicit Euler
+                let acceleration =
+                    (input - damping * self.velocity - stiffness * self.value) / mass;
+                self.velocity += acceleration * dt;
+                self.value += self.velocity * dt;
+            }
+        }
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_order_delays_then_settles() {
+        let config = PlantConfig::FirstOrder {
+            gain: 2.0,
+            time_constant: 5.0,
+            dead_time: 1.0,
+            ambient: 20.0,
+        };
+        let mut plant = Plant::new(&config, 0.1);
+        for _ in 0..10 {
+            assert_eq!(plant.step(10.0), 20.0);
+        }
+        // One time constant after the dead time: 63% of the way there
+        for _ in 0..50 {
+            plant.step(10.0);
+        }
+        assert!((plant.value() - (20.0 + 20.0 * (1.0 - (-1.0f64).exp()))).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_integrating_ramps() {
+        let config = PlantConfig::Integrating {
+            gain: 0.5,
+            dead_time: 0.0,
+            initial: 1.0,
+        };
+        let mut plant = Plant::new(&config, 0.1);
+        for _ in 0..100 {
+            plant.step(2.0);
+        }
+        assert!((plant.value() - 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_second_order_rests_at_force_over_stiffness() {
+        let config = PlantConfig::SecondOrder {
+            mass: 1.0,
+            damping: 2.0,
+            stiffness: 4.0,
+            dead_time: 0.0,
+            initial: 0.0,
+        };
+        let mut plant = Plant::new(&config, 0.01);
+        for _ in 0..10_000 {
+            plant.step(8.0);
+        }
+        assert!((plant.value() - 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::simulate::Sample;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::ops::Range;
+use std::path::Path;
+
+/// Plot size in pixels
+const SIZE: (u32, u32) = (1200, 800);
+
+/// Picks one value out of each sample
+type Field = fn(&Sample) -> f64;
+
+/// Plot the process value and setpoint above the controller output, in the
+/// format named by the file extension (`svg`, or `png` with the `png`
+/// feature)
+pub fn draw(path: &Path, title: &str, samples: &[Sample]) -> Result<(), String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    // Each backend has its own error type, so the arms agree on a string
+    let result = match extension.as_deref() {
+        Some("svg") => draw_on(
+            SVGBackend::new(path, SIZE).into_drawing_area(),
+            title,
+            samples,
+        )
+        .map_err(|e| e.to_string()),
+        #[cfg(feature = "png")]
+        Some("png") => draw_on(
+            BitMapBackend::new(path, SIZE).into_drawing_area(),
+            title,
+            samples,
+        )
+        .map_err(|e| e.to_string()),
+        _ => {
+            return Err(format!(
+                "cannot plot to {}: the file must end in {}",
+                path.display(),
+                if cfg!(feature = "png") {
+                    ".svg or .png"
+                } else {
+                    ".svg (PNG needs the png feature)"
+                }
+            ))
+        }
+    };
+    result.map_err(|e| format!("failed to plot to {}: {}", path.display(), e))
+}
+
+fn draw_on<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    samples: &[Sample],
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 24))?;
+    let (upper, lower) = root.split_vertically(root.dim_in_pixel().1 * 3 / 5);
+    let time = 0.0..samples
+        .last()
+        .map_or(1.0, |s| s.time.max(f64::MIN_POSITIVE));
+
+    let mut chart = ChartBuilder::on(&upper)
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            time.clone(),
+            padded(
+                samples
+                    .iter()
+                    .flat_map(|s| [s.setpoint, s.process_value, s.measurement]),
+            ),
+        )?;
+    chart.configure_mesh().y_desc("process value").draw()?;
+    let series: [(&str, RGBColor, u32, Field); 3] = [
+        ("measurement", RGBColor(180, 180, 180), 1, |s| s.measurement),
+        ("setpoint", RED, 2, |s| s.setpoint),
+        ("process value", BLUE, 2, |s| s.process_value),
+    ];
+    for (label, color, width, value) in series {
+        chart
+            .draw_series(LineSeries::new(
+                samples.iter().map(|s| (s.time, value(s))),
+                color.stroke_width(width),
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::LowerRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    let mut chart = ChartBuilder::on(&lower)
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(time, padded(samples.iter().map(|s| s.output)))?;
+    chart
+        .configure_mesh()
+        .x_desc("time (s)")
+        .y_desc("output")
+        .draw()?;
+    chart.draw_series(LineSeries::new(
+        samples.iter().map(|s| (s.time, s.output)),
+        GREEN.stroke_width(2),
+    ))?;
+
+    root.present()
+}
+
+/// The range of `values` with 5% to spare on each side
+fn padded(values: impl Iterator<Item = f64>) -> Range<f64> {
+    let (min, max) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max {
+        return 0.0..1.0;
+    }
+    let margin = ((max - min) * 0.05).max(1e-9 * max.abs().max(1.0));
+    (min - margin)..(max + margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Sample> {
+        (0..50)
+            .map(|i| {
+                let time = i as f64 * 0.1;
+                Sample {
+                    time,
+                    setpoint: 1.0,
+                    process_value: 1.0 - (-time).exp(),
+                    measurement: 1.0 - (-time).exp(),
+                    output: (-time).exp(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draws_every_supported_format() {
+        let dir = std::env::temp_dir();
+        let mut names = vec!["pidgeon-cli-plot.svg"];
+        if cfg!(feature = "png") {
+            names.push("pidgeon-cli-plot.png");
+        }
+        for name in names {
+            let path = dir.join(name);
+            draw(&path, "step", &samples()).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_extension() {
+        let path = std::env::temp_dir().join("pidgeon-cli-plot.gif");
+        let error = draw(&path, "step", &samples()).unwrap_err();
+        assert!(error.contains(".svg"), "{}", error);
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::path::Path;
+
+/// Open a serial device for reading and writing in raw 8N1 mode at `baud`
+#[cfg(unix)]
+pub fn open(path: &Path, baud: u32) -> Result<File, String> {
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let fail = |e: io::Error| format!("{}: {}", path.display(), e);
+    let speed = speed(baud).ok_or_else(|| format!("unsupported baud rate {}", baud))?;
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(path)
+        .map_err(fail)?;
+    let fd = file.as_raw_fd();
+
+    // SAFETY: all-zero is a valid `termios`, filled in by `tcgetattr`.
+    let mut tio: libc::termios = unsafe { std::mem::zeroed() };
+    // SAFETY: `fd` is open for the lifetime of `file` and `tio` is valid.
+    if unsafe { libc::tcgetattr(fd, &mut tio) } != 0 {
+        return Err(fail(io::Error::last_os_error()));
+    }
+    // SAFETY: `tio` is a valid `termios`.
+    unsafe {
+        libc::cfmakeraw(&mut tio);
+        libc::cfsetispeed(&mut tio, speed);
+        libc::cfsetospeed(&mut tio, speed);
+    }
+    // No modem control, blocking reads of at least one byte
+    tio.c_cflag |= libc::CLOCAL | libc::CREAD;
+    tio.c_cflag &= !(libc::CSTOPB | libc::CRTSCTS);
+    tio.c_cc[libc::VMIN] = 1;
+    tio.c_cc[libc::VTIME] = 0;
+    // SAFETY: `fd` is open and `tio` is a valid `termios`.
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &tio) } != 0 {
+        return Err(fail(io::Error::last_os_error()));
+    }
+    // SAFETY: `fd` is open; drops whatever arrived before the port was set up.
+    unsafe { libc::tcflush(fd, libc::TCIFLUSH) };
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+pub fn open(path: &Path, _baud: u32) -> Result<File, String> {
+    Err(format!(
+        "{}: serial ports are only supported on Unix",
+        path.display()
+    ))
+}
+
+#[cfg(unix)]
+fn speed(baud: u32) -> Option<libc::speed_t> {
+    Some(match baud {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        #[cfg(target_os = "linux")]
+        460800 => libc::B460800,
+        #[cfg(target_os = "linux")]
+        921600 => libc::B921600,
+        _ => return None,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_rejects_missing_device_and_bad_baud() {
+        assert!(open(Path::new("/dev/pidgeon-none"), 115200).is_err());
+        assert!(open(Path::new("/dev/null"), 12345)
+            .unwrap_err()
+            .contains("baud"));
+        // Not a terminal
+        assert!(open(Path::new("/dev/null"), 115200).is_err());
+        assert_eq!(speed(9600), Some(libc::B9600));
+    }
+}
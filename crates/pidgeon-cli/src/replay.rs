@@ -0,0 +1,328 @@
+use crate::publish::{Publisher, Target};
+use clap::Args;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Gap between passes when the capture has no two distinct timestamps
+const DEFAULT_GAP_MS: u64 = 1000;
+
+/// `pidgeon-cli replay` flags
+#[derive(Debug, Clone, Args)]
+pub struct ReplayArgs {
+    /// JSON-lines capture, as written by a `FileSink`
+    pub capture: PathBuf,
+    /// Where to publish: iggy://[user:password@]host:port[/stream[/topic]] or
+    /// mqtt://[user:password@]host[:port][/topic/prefix]
+    #[arg(long)]
+    pub to: Target,
+    /// Playback speed; 2 replays twice as fast as recorded
+    #[arg(long, default_value_t = 1.0)]
+    pub speed: f64,
+    /// Start over at the end of the capture, until interrupted
+    #[arg(long = "loop")]
+    pub repeat: bool,
+    /// Only replay samples from this controller
+    #[arg(long)]
+    pub controller_id: Option<String>,
+    /// Publish the recorded timestamps instead of shifting them to the present
+    #[arg(long, conflicts_with = "repeat")]
+    pub keep_timestamps: bool,
+}
+
+/// One recorded sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub controller_id: String,
+    /// Every field, as recorded
+    pub fields: Map<String, Value>,
+}
+
+impl Record {
+    /// The sample as JSON, stamped with `timestamp`
+    pub fn payload(&self, timestamp: u64) -> Vec<u8> {
+        let mut fields = self.fields.clone();
+        fields.insert("timestamp".to_string(), timestamp.into());
+        Value::Object(fields).to_string().into_bytes()
+    }
+}
+
+/// Parse JSON lines into records ordered by timestamp, keeping only
+/// `controller_id`'s if given
+pub fn parse_records(contents: &str, controller_id: Option<&str>) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = i + 1;
+        let Value::Object(fields) = serde_json::from_str(line)
+            .map_err(|e| format!("line {} is not JSON: {}", line_number, e))?
+        else {
+            return Err(format!("line {} is not a JSON object", line_number));
+        };
+        let timestamp = fields
+            .get("timestamp")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| format!("line {}: no integer 'timestamp'", line_number))?;
+        let id = fields
+            .get("controller_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("line {}: no 'controller_id'", line_number))?
+            .to_string();
+        if controller_id.is_some_and(|wanted| wanted != id) {
+            continue;
+        }
+        records.push(Record {
+            timestamp,
+            controller_id: id,
+            fields,
+        });
+    }
+
+    if records.is_empty() {
+        return Err(match controller_id {
+            Some(id) => format!("no samples from controller '{}'", id),
+            None => "no samples".to_string(),
+        });
+    }
+    // Captures from several processes may be concatenated
+    records.sort_by_key(|record| record.timestamp);
+    Ok(records)
+}
+
+/// When each record goes out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Playback {
+    /// Timestamp of the first record
+    first: u64,
+    /// Recorded milliseconds from the start of one pass to the next: the
+    /// capture's span plus its typical sample interval
+    period: u64,
+    speed: f64,
+}
+
+impl Playback {
+    /// Playback of `records`, which must be sorted and not empty
+    pub fn new(records: &[Record], speed: f64) -> Self {
+        let first = records[0].timestamp;
+        let last = records[records.len() - 1].timestamp;
+        let mut steps: Vec<u64> = records
+            .windows(2)
+            .map(|pair| pair[1].timestamp - pair[0].timestamp)
+            .filter(|&step| step > 0)
+            .collect();
+        steps.sort_unstable();
+        let gap = steps
+            .get(steps.len() / 2)
+            .copied()
+            .unwrap_or(DEFAULT_GAP_MS);
+        Playback {
+            first,
+            period: last - first + gap,
+            speed,
+        }
+    }
+
+    /// Time from the start of the replay until `record` goes out on pass
+    /// `pass`, counting from 0
+    pub fn delay(&self, record: &Record, pass: u64) -> Duration {
+        let recorded = record.timestamp - self.first + pass * self.period;
+        Duration::from_secs_f64(recorded as f64 / 1000.0 / self.speed)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Publish `records` with their recorded pacing until done or interrupted,
+/// returning how many were sent
+async fn replay(
+    publisher: &mut Publisher,
+    records: &[Record],
+    args: &ReplayArgs,
+) -> Result<u64, String> {
+    let playback = Playback::new(records, args.speed);
+    let start = tokio::time::Instant::now();
+    let start_millis = now_millis();
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    let mut sent = 0;
+    for pass in 0.. {
+        for record in records {
+            let delay = playback.delay(record, pass);
+            tokio::select! {
+                _ = tokio::time::sleep_until(start + delay) => {}
+                _ = &mut shutdown => return Ok(sent),
+            }
+            let timestamp = if args.keep_timestamps {
+                record.timestamp
+            } else {
+                start_millis + delay.as_millis() as u64
+            };
+            publisher
+                .send(&record.controller_id, record.payload(timestamp))
+                .await?;
+            sent += 1;
+        }
+        if !args.repeat {
+            break;
+        }
+    }
+    Ok(sent)
+}
+
+/// Run the `replay` command
+pub fn run(args: ReplayArgs) -> Result<ExitCode, String> {
+    if !args.speed.is_finite() || args.speed <= 0.0 {
+        return Err(format!("speed {} is not a positive number", args.speed));
+    }
+    let contents = std::fs::read_to_string(&args.capture)
+        .map_err(|e| format!("failed to read {}: {}", args.capture.display(), e))?;
+    let records = parse_records(&contents, args.controller_id.as_deref())
+        .map_err(|e| format!("{}: {}", args.capture.display(), e))?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("failed to start the runtime: {}", e))?;
+    runtime.block_on(async {
+        let mut publisher = Publisher::connect(&args.to).await?;
+        let controllers: BTreeSet<&str> = records
+            .iter()
+            .map(|record| record.controller_id.as_str())
+            .collect();
+        let span = records[records.len() - 1].timestamp - records[0].timestamp;
+        println!(
+            "replaying {} samples from {} over {:.1} s at {}x to {}",
+            records.len(),
+            controllers.into_iter().collect::<Vec<_>>().join(", "),
+            span as f64 / 1000.0 / args.speed,
+            args.speed,
+            args.to
+        );
+
+        let result = replay(&mut publisher, &records, &args).await;
+        publisher.close().await;
+        println!("published {} samples", result?);
+        Ok(ExitCode::SUCCESS)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::path::Path;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: ReplayArgs,
+    }
+
+    fn line(timestamp: u64, controller_id: &str) -> String {
+        format!(
+            r#"{{"timestamp": {}, "controller_id": "{}", "output": 1.5}}"#,
+            timestamp, controller_id
+        )
+    }
+
+    #[test]
+    fn test_records_sorted_and_filtered() {
+        let contents = [
+            line(1_000, "oven"),
+            String::new(),
+            line(1_500, "fan"),
+            line(1_200, "oven"),
+        ]
+        .join("\n");
+        let records = parse_records(&contents, None).unwrap();
+        let order: Vec<u64> = records.iter().map(|r| r.timestamp).collect();
+        assert_eq!(order, vec![1_000, 1_200, 1_500]);
+
+        let records = parse_records(&contents, Some("oven")).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(parse_records(&contents, Some("pump")).is_err());
+    }
+
+    #[test]
+    fn test_rejects_bad_lines() {
+        assert!(parse_records("", None).is_err());
+        assert!(parse_records("[1, 2]", None).is_err());
+        assert!(parse_records(r#"{"controller_id": "oven"}"#, None).is_err());
+        assert!(parse_records(r#"{"timestamp": -1, "controller_id": "a"}"#, None).is_err());
+        assert!(parse_records(r#"{"timestamp": 5}"#, None).is_err());
+    }
+
+    #[test]
+    fn test_payload_keeps_fields_and_restamps() {
+        let records = parse_records(&line(1_000, "oven"), None).unwrap();
+        let payload: Value = serde_json::from_slice(&records[0].payload(42)).unwrap();
+        assert_eq!(payload["timestamp"], 42);
+        assert_eq!(payload["controller_id"], "oven");
+        assert_eq!(payload["output"], 1.5);
+    }
+
+    #[test]
+    fn test_playback_paces_and_loops() {
+        let contents = [1_000, 1_100, 1_200, 1_400]
+            .map(|t| line(t, "oven"))
+            .join("\n");
+        let records = parse_records(&contents, None).unwrap();
+
+        let playback = Playback::new(&records, 1.0);
+        assert_eq!(playback.delay(&records[0], 0), Duration::ZERO);
+        assert_eq!(playback.delay(&records[3], 0), Duration::from_millis(400));
+        // The next pass starts one typical interval after the last sample
+        assert_eq!(playback.delay(&records[0], 1), Duration::from_millis(500));
+        assert_eq!(playback.delay(&records[2], 2), Duration::from_millis(1_200));
+
+        let playback = Playback::new(&records, 4.0);
+        assert_eq!(playback.delay(&records[3], 0), Duration::from_millis(100));
+
+        let single = parse_records(&line(7, "oven"), None).unwrap();
+        let playback = Playback::new(&single, 1.0);
+        assert_eq!(playback.delay(&single[0], 3), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_args() {
+        let args = Cli::parse_from(["replay", "run.jsonl", "--to", "mqtt://localhost"]).args;
+        assert_eq!(args.speed, 1.0);
+        assert!(!args.repeat);
+        let args = Cli::parse_from(["replay", "run.jsonl", "--to", "iggy://h:1", "--loop"]).args;
+        assert!(args.repeat);
+
+        assert!(Cli::try_parse_from(["replay", "run.jsonl"]).is_err());
+        assert!(Cli::try_parse_from(["replay", "run.jsonl", "--to", "http://h"]).is_err());
+        assert!(Cli::try_parse_from([
+            "replay",
+            "run.jsonl",
+            "--to",
+            "iggy://h:1",
+            "--loop",
+            "--keep-timestamps"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_example_capture_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/oven_debug.jsonl");
+        let contents = std::fs::read_to_string(path).unwrap();
+        let records = parse_records(&contents, None).unwrap();
+        assert!(records.iter().all(|r| r.controller_id == "oven"));
+        let playback = Playback::new(&records, 1.0);
+        assert_eq!(playback.delay(&records[1], 0), Duration::from_millis(1_000));
+    }
+}
@@ -0,0 +1,453 @@
+use pidgeon::ControllerConfig;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Longest simulation accepted, in steps, so a typo in `dt` cannot fill the disk
+pub const MAX_STEPS: usize = 10_000_000;
+
+/// A closed-loop experiment: a controller, a plant, and what happens to them
+///
+/// Loaded from TOML:
+///
+/// ```toml
+/// dt = 1.0
+/// duration = 3600.0
+///
+/// [controller]
+/// kp = 25.0
+/// ki = 0.02
+/// setpoint = 19.5
+/// min_output = 0.0
+/// max_output = 100.0
+///
+/// [plant]
+/// type = "first-order"
+/// gain = 0.08
+/// time_constant = 900.0
+/// dead_time = 30.0
+/// ambient = 17.0
+///
+/// [[setpoint]]
+/// time = 1800.0
+/// value = 21.0
+///
+/// [[disturbance]]
+/// start = 2400.0
+/// value = -40.0
+///
+/// [limits]
+/// max_overshoot = 0.5
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    /// Shown in reports and plot titles [default: the file name]
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Seconds per control step
+    pub dt: f64,
+    /// Seconds to simulate
+    pub duration: f64,
+    /// Controller parameters, validated like [`ControllerConfig::builder`];
+    /// its `setpoint` holds until the first setpoint change
+    pub controller: ControllerConfig,
+    /// The process being controlled
+    pub plant: PlantConfig,
+    /// Setpoint changes, in any order
+    #[serde(default)]
+    pub setpoint: Vec<SetpointChange>,
+    /// Disturbances, which may overlap
+    #[serde(default)]
+    pub disturbance: Vec<Disturbance>,
+    /// Measurement noise, if any
+    #[serde(default)]
+    pub noise: Option<Noise>,
+    /// Error within which a step counts as settled [default: 2% of the step]
+    #[serde(default)]
+    pub settling_band: Option<f64>,
+    /// Pass/fail criteria
+    #[serde(default)]
+    pub limits: Limits,
+}
+
+/// Process models, selected by `type`
+///
+/// Every model can have a `dead_time`, in seconds, before the input is felt.
+/// It is rounded to a whole number of steps.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PlantConfig {
+    /// Heating, flow, and most other self-regulating processes: settles at
+    /// `ambient + gain × input` with time constant `time_constant`
+    FirstOrder {
+        gain: f64,
+        time_constant: f64,
+        #[serde(default)]
+        dead_time: f64,
+        /// Process value with no input, and at the start
+        #[serde(default)]
+        ambient: f64,
+    },
+    /// Tank levels and positions under velocity control: the process value
+    /// changes at `gain × input` per second and never settles by itself
+    Integrating {
+        gain: f64,
+        #[serde(default)]
+        dead_time: f64,
+        /// Process value at the start
+        #[serde(default)]
+        initial: f64,
+    },
+    /// A mass on a spring and damper driven by a force, such as a motor
+    /// positioning a load
+    SecondOrder {
+        mass: f64,
+        #[serde(default)]
+        damping: f64,
+        #[serde(default)]
+        stiffness: f64,
+        #[serde(default)]
+        dead_time: f64,
+        /// Position at the start
+        #[serde(default)]
+        initial: f64,
+    },
+}
+
+/// A new setpoint from `time` on
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SetpointChange {
+    /// Seconds from the start
+    pub time: f64,
+    /// New setpoint
+    pub value: f64,
+    /// Seconds to ramp from the previous setpoint [default: a step]
+    #[serde(default)]
+    pub ramp: f64,
+}
+
+/// Something pushing the process off the setpoint
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Disturbance {
+    /// Seconds from the start
+    pub start: f64,
+    /// Seconds from the start at which it stops [default: never]
+    #[serde(default)]
+    pub end: Option<f64>,
+    /// Where it acts
+    #[serde(default)]
+    pub kind: DisturbanceKind,
+    /// Size, in output units for `load` and process units for `measurement`
+    pub value: f64,
+}
+
+/// Where a [`Disturbance`] acts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DisturbanceKind {
+    /// Added to the controller output before it reaches the plant, like heat
+    /// lost through an open lid
+    #[default]
+    Load,
+    /// Added to the measurement the controller sees, like a sensor offset;
+    /// the real process value is unaffected
+    Measurement,
+}
+
+/// Gaussian noise added to every measurement
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Noise {
+    /// Standard deviation, in process units
+    pub std_dev: f64,
+    /// Seed, so runs are repeatable [default: 0]
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// Criteria a run must meet; unset ones are not checked
+// A misspelled limit would silently pass, so unknown names are errors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    /// Largest overshoot past any setpoint step, in process units
+    pub max_overshoot: Option<f64>,
+    /// Longest time for any step to settle, in seconds; a step that never
+    /// settles fails
+    pub max_settling_time: Option<f64>,
+    /// Largest integral of absolute error
+    pub max_iae: Option<f64>,
+    /// Largest fraction of steps spent with the output at a limit, 0 to 1
+    pub max_saturation: Option<f64>,
+}
+
+impl Scenario {
+    /// Load and check a scenario file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let mut scenario: Scenario = toml::from_str(&contents)
+            .map_err(|e| format!("invalid scenario in {}: {}", path.display(), e))?;
+        scenario
+            .prepare()
+            .map_err(|e| format!("invalid scenario in {}: {}", path.display(), e))?;
+        if scenario.name.is_none() {
+            scenario.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned());
+        }
+        Ok(scenario)
+    }
+
+    /// Number of control steps the scenario runs for
+    pub fn steps(&self) -> usize {
+        (self.duration / self.dt).round() as usize
+    }
+
+    /// Setpoint in effect at `time`
+    pub fn setpoint_at(&self, time: f64) -> f64 {
+        let mut current = self.controller.setpoint();
+        for change in &self.setpoint {
+            if time < change.time {
+                break;
+            }
+            current = if change.ramp > 0.0 && time < change.time + change.ramp {
+                current + (change.value - current) * (time - change.time) / change.ramp
+            } else {
+                change.value
+            };
+        }
+        current
+    }
+
+    /// Sum of the disturbances of `kind` active at `time`
+    pub fn disturbance_at(&self, kind: DisturbanceKind, time: f64) -> f64 {
+        self.disturbance
+            .iter()
+            .filter(|d| d.kind == kind && time >= d.start && d.end.is_none_or(|end| time < end))
+            .map(|d| d.value)
+            .sum()
+    }
+
+    /// Catch mistakes before simulating, and put the setpoint changes in order
+    fn prepare(&mut self) -> Result<(), String> {
+        if !self.dt.is_finite() || self.dt <= 0.0 {
+            return Err("dt must be a positive number".to_string());
+        }
+        if !self.duration.is_finite() || self.duration < self.dt {
+            return Err("duration must be at least one dt".to_string());
+        }
+        if self.steps() > MAX_STEPS {
+            return Err(format!(
+                "duration / dt is {} steps; the limit is {}",
+                self.steps(),
+                MAX_STEPS
+            ));
+        }
+        self.plant.validate()?;
+        for change in &self.setpoint {
+            if !change.time.is_finite() || !change.value.is_finite() {
+                return Err("setpoint time and value must be finite numbers".to_string());
+            }
+            if !change.ramp.is_finite() || change.ramp < 0.0 {
+                return Err("setpoint ramp must be a non-negative number".to_string());
+            }
+        }
+        self.setpoint.sort_by(|a, b| a.time.total_cmp(&b.time));
+        for disturbance in &self.disturbance {
+            let end = disturbance.end.unwrap_or(f64::INFINITY);
+            if !disturbance.start.is_finite() || !disturbance.value.is_finite() || end.is_nan() {
+                return Err("disturbance start, end, and value must be numbers".to_string());
+            }
+            if end <= disturbance.start {
+                return Err(format!(
+                    "disturbance starting at {} s ends before it starts",
+                    disturbance.start
+                ));
+            }
+        }
+        if let Some(noise) = &self.noise {
+            if !noise.std_dev.is_finite() || noise.std_dev < 0.0 {
+                return Err("noise std_dev must be a non-negative number".to_string());
+            }
+        }
+        if let Some(band) = self.settling_band {
+            if !band.is_finite() || band <= 0.0 {
+                return Err("settling_band must be a positive number".to_string());
+            }
+        }
+        let limits = [
+            self.limits.max_overshoot,
+            self.limits.max_settling_time,
+            self.limits.max_iae,
+            self.limits.max_saturation,
+        ];
+        if limits.into_iter().flatten().any(|l| l.is_nan() || l < 0.0) {
+            return Err("limits must be non-negative numbers".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl PlantConfig {
+    /// Seconds before the plant feels its input
+    pub fn dead_time(&self) -> f64 {
+        match *self {
+            PlantConfig::FirstOrder { dead_time, .. }
+            | PlantConfig::Integrating { dead_time, .. }
+            | PlantConfig::SecondOrder { dead_time, .. } => dead_time,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let dead_time = self.dead_time();
+        if !dead_time.is_finite() || dead_time < 0.0 {
+            return Err("plant dead_time must be a non-negative number".to_string());
+        }
+        match *self {
+            PlantConfig::FirstOrder {
+                gain,
+                time_constant,
+                ambient,
+                ..
+            } => {
+                if !gain.is_finite() || !ambient.is_finite() {
+                    return Err("plant gain and ambient must be finite numbers".to_string());
+                }
+                if !time_constant.is_finite() || time_constant <= 0.0 {
+                    return Err("plant time_constant must be a positive number".to_string());
+                }
+            }
+            PlantConfig::Integrating { gain, initial, .. } => {
+                if !gain.is_finite() || !initial.is_finite() {
+                    return Err("plant gain and initial must be finite numbers".to_string());
+                }
+            }
+            PlantConfig::SecondOrder {
+                mass,
+                damping,
+                stiffness,
+                initial,
+                ..
+            } => {
+                if !mass.is_finite() || mass <= 0.0 {
+                    return Err("plant mass must be a positive number".to_string());
+                }
+                if !damping.is_finite()
+                    || damping < 0.0
+                    || !stiffness.is_finite()
+                    || stiffness < 0.0
+                {
+                    return Err(
+                        "plant damping and stiffness must be non-negative numbers".to_string()
+                    );
+                }
+                if !initial.is_finite() {
+                    return Err("plant initial must be a finite number".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Result<Scenario, String> {
+        let mut scenario: Scenario = toml::from_str(toml).map_err(|e| e.to_string())?;
+        scenario.prepare()?;
+        Ok(scenario)
+    }
+
+    const BASE: &str = r#"
+        dt = 0.1
+        duration = 10.0
+        [controller]
+        kp = 1.0
+        setpoint = 5.0
+        min_output = -10.0
+        max_output = 10.0
+        [plant]
+        type = "first-order"
+        gain = 1.0
+        time_constant = 2.0
+    "#;
+
+    #[test]
+    fn test_example_scenario_loads() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/fermenter.toml");
+        let scenario = Scenario::load(&path).unwrap();
+        assert_eq!(scenario.name.as_deref(), Some("fermenter"));
+        assert_eq!(scenario.steps(), 14400);
+        assert_eq!(scenario.plant.dead_time(), 30.0);
+        assert_eq!(scenario.limits.max_overshoot, Some(0.5));
+    }
+
+    #[test]
+    fn test_setpoint_profile() {
+        let scenario = parse(&format!(
+            "{}{}",
+            BASE,
+            r#"
+            [[setpoint]]
+            time = 6.0
+            value = 0.0
+            [[setpoint]]
+            time = 2.0
+            value = 9.0
+            ramp = 2.0
+            "#
+        ))
+        .unwrap();
+        assert_eq!(scenario.setpoint_at(0.0), 5.0);
+        assert_eq!(scenario.setpoint_at(2.0), 5.0);
+        assert_eq!(scenario.setpoint_at(3.0), 7.0);
+        assert_eq!(scenario.setpoint_at(4.0), 9.0);
+        assert_eq!(scenario.setpoint_at(6.0), 0.0);
+    }
+
+    #[test]
+    fn test_disturbances_add_up_while_active() {
+        let scenario = parse(&format!(
+            "{}{}",
+            BASE,
+            r#"
+            [[disturbance]]
+            start = 1.0
+            end = 3.0
+            value = 2.0
+            [[disturbance]]
+            start = 2.0
+            value = -0.5
+            [[disturbance]]
+            start = 0.0
+            kind = "measurement"
+            value = 0.1
+            "#
+        ))
+        .unwrap();
+        assert_eq!(scenario.disturbance_at(DisturbanceKind::Load, 0.5), 0.0);
+        assert_eq!(scenario.disturbance_at(DisturbanceKind::Load, 2.5), 1.5);
+        assert_eq!(scenario.disturbance_at(DisturbanceKind::Load, 3.0), -0.5);
+        assert_eq!(
+            scenario.disturbance_at(DisturbanceKind::Measurement, 9.0),
+            0.1
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_scenarios() {
+        assert!(parse(BASE).is_ok());
+        assert!(parse(&BASE.replace("dt = 0.1", "dt = 0.0")).is_err());
+        assert!(parse(&BASE.replace("time_constant = 2.0", "time_constant = -1.0")).is_err());
+        assert!(parse(&BASE.replace("first-order", "fourth-order")).is_err());
+        // Infinite default limits
+        assert!(parse(&BASE.replace("min_output = -10.0", "")).is_err());
+        assert!(parse(&format!("{}\n[limits]\nmax_overshot = 1.0", BASE)).is_err());
+        assert!(parse(&format!(
+            "{}\n[[disturbance]]\nstart = 5.0\nend = 4.0\nvalue = 1.0",
+            BASE
+        ))
+        .is_err());
+    }
+}
@@ -0,0 +1,287 @@
+use crate::port;
+use clap::Args;
+use pidgeon::{PidError, SerialMessage};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const HELP: &str = "\
+commands, sent to the controller:
+  kp <value>    set the proportional gain
+  ki <value>    set the integral gain
+  kd <value>    set the derivative gain
+  sp <value>    move the setpoint
+  get           show the gains
+  reset         clear the controller's state
+local:
+  watch         toggle telemetry printing
+  help          show this list
+  quit          leave (Ctrl-D works too)
+";
+
+/// `pidgeon-cli serial` flags
+#[derive(Debug, Clone, Args)]
+pub struct SerialArgs {
+    /// Serial device the controller is on, such as /dev/ttyACM0
+    pub port: PathBuf,
+    /// Baud rate
+    #[arg(long, default_value_t = 115200)]
+    pub baud: u32,
+    /// Seconds between printed telemetry lines; 0 prints every one
+    #[arg(long, default_value_t = 1.0)]
+    pub interval: f64,
+    /// Start with telemetry printing off; `watch` turns it on
+    #[arg(long)]
+    pub quiet: bool,
+    /// Record every telemetry line to this CSV file, ready for `tune`
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+}
+
+/// A line typed at the prompt
+#[derive(Debug, Clone, PartialEq)]
+enum Input {
+    Send(SerialMessage),
+    Watch,
+    Help,
+    Quit,
+}
+
+fn reason(e: PidError) -> String {
+    match e {
+        PidError::InvalidParameter(reason) => reason.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_input(line: &str) -> Result<Option<Input>, String> {
+    let line = line.trim();
+    match line {
+        "" => Ok(None),
+        "watch" => Ok(Some(Input::Watch)),
+        "help" | "?" => Ok(Some(Input::Help)),
+        "quit" | "exit" => Ok(Some(Input::Quit)),
+        _ => match line.parse::<SerialMessage>() {
+            Ok(message) if message.is_command() => Ok(Some(Input::Send(message))),
+            Ok(_) => Err(format!("'{}' is sent by controllers, not to them", line)),
+            Err(e) => Err(format!("{}; type help for commands", reason(e))),
+        },
+    }
+}
+
+/// Turns lines from the controller into what to print and record
+struct Monitor<W: Write> {
+    start: Instant,
+    /// Least time between printed telemetry lines
+    interval: Duration,
+    last_printed: Option<Instant>,
+    csv: Option<W>,
+}
+
+impl<W: Write> Monitor<W> {
+    fn new(start: Instant, interval: Duration, mut csv: Option<W>) -> io::Result<Self> {
+        if let Some(csv) = &mut csv {
+            writeln!(csv, "time,setpoint,process_value,output,error")?;
+        }
+        Ok(Monitor {
+            start,
+            interval,
+            last_printed: None,
+            csv,
+        })
+    }
+
+    /// What to print for a line received at `now`, if anything
+    fn line(&mut self, line: &str, now: Instant, watching: bool) -> io::Result<Option<String>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let text = match line.parse::<SerialMessage>() {
+            Ok(SerialMessage::Telemetry {
+                process_value,
+                setpoint,
+                output,
+                error,
+            }) => {
+                let time = now.duration_since(self.start).as_secs_f64();
+                if let Some(csv) = &mut self.csv {
+                    writeln!(
+                        csv,
+                        "{:.3},{},{},{},{}",
+                        time, setpoint, process_value, output, error
+                    )?;
+                }
+                let due = self
+                    .last_printed
+                    .is_none_or(|last| now.duration_since(last) >= self.interval);
+                if !watching || !due {
+                    return Ok(None);
+                }
+                self.last_printed = Some(now);
+                format!(
+                    "{:>10.3} s  pv {}  sp {}  out {}  err {}",
+                    time, process_value, setpoint, output, error
+                )
+            }
+            Ok(SerialMessage::Gains { kp, ki, kd }) => {
+                format!("gains  kp {}  ki {}  kd {}", kp, ki, kd)
+            }
+            Ok(SerialMessage::Rejected) => "rejected; nothing changed".to_string(),
+            // Firmware log output and echoed commands
+            _ => format!("< {}", line),
+        };
+        Ok(Some(text))
+    }
+}
+
+fn send(port: &mut File, message: &SerialMessage) -> Result<(), String> {
+    port.write_all(format!("{}\n", message).as_bytes())
+        .map_err(|e| format!("failed to write to the port: {}", e))
+}
+
+/// Run the `serial` command
+pub fn run(args: SerialArgs) -> Result<ExitCode, String> {
+    if !args.interval.is_finite() || args.interval < 0.0 {
+        return Err(format!(
+            "interval {} is not a non-negative number",
+            args.interval
+        ));
+    }
+    let mut port = port::open(&args.port, args.baud)?;
+    let incoming = port
+        .try_clone()
+        .map_err(|e| format!("{}: {}", args.port.display(), e))?;
+    let csv = match &args.csv {
+        Some(path) => {
+            Some(LineWriter::new(File::create(path).map_err(|e| {
+                format!("failed to create {}: {}", path.display(), e)
+            })?))
+        }
+        None => None,
+    };
+    let mut monitor = Monitor::new(Instant::now(), Duration::from_secs_f64(args.interval), csv)
+        .map_err(|e| format!("failed to write the CSV: {}", e))?;
+
+    let watching = Arc::new(AtomicBool::new(!args.quiet));
+    let watch = Arc::clone(&watching);
+    let name = args.port.display().to_string();
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(incoming);
+        let mut bytes = Vec::new();
+        loop {
+            bytes.clear();
+            match reader.read_until(b'\n', &mut bytes) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("error: failed to read {}: {}", name, e);
+                    return;
+                }
+            }
+            let line = String::from_utf8_lossy(&bytes);
+            match monitor.line(&line, Instant::now(), watch.load(Ordering::Relaxed)) {
+                Ok(Some(text)) => println!("{}", text),
+                Ok(None) => {}
+                Err(e) => eprintln!("error: failed to write the CSV: {}", e),
+            }
+        }
+        eprintln!("error: {} closed", name);
+    });
+
+    println!(
+        "connected to {} at {} baud; type help for commands",
+        args.port.display(),
+        args.baud
+    );
+    send(&mut port, &SerialMessage::GetGains)?;
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("failed to read the terminal: {}", e))?;
+        match parse_input(&line) {
+            Ok(None) => {}
+            Ok(Some(Input::Send(message))) => send(&mut port, &message)?,
+            Ok(Some(Input::Watch)) => {
+                let on = !watching.fetch_xor(true, Ordering::Relaxed);
+                println!("telemetry {}", if on { "on" } else { "off" });
+            }
+            Ok(Some(Input::Help)) => print!("{}", HELP),
+            Ok(Some(Input::Quit)) => break,
+            Err(e) => eprintln!("error: {}", e),
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pidgeon::Gain;
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(parse_input("  "), Ok(None));
+        assert_eq!(
+            parse_input("kp 2.5\n"),
+            Ok(Some(Input::Send(SerialMessage::SetGain {
+                gain: Gain::Kp,
+                value: 2.5,
+            })))
+        );
+        assert_eq!(
+            parse_input("reset"),
+            Ok(Some(Input::Send(SerialMessage::Reset)))
+        );
+        assert_eq!(parse_input("watch"), Ok(Some(Input::Watch)));
+        assert_eq!(parse_input("exit"), Ok(Some(Input::Quit)));
+
+        assert!(parse_input("gains 1 2 3")
+            .unwrap_err()
+            .contains("controllers"));
+        assert!(parse_input("kp").unwrap_err().contains("missing"));
+        assert!(parse_input("go faster").is_err());
+    }
+
+    #[test]
+    fn test_monitor_throttles_and_records() {
+        let start = Instant::now();
+        let mut monitor = Monitor::new(start, Duration::from_secs(1), Some(Vec::new())).unwrap();
+        let at = |ms| start + Duration::from_millis(ms);
+        let tel = "tel 48.5 50 3 1.5\r\n";
+
+        let first = monitor.line(tel, at(100), true).unwrap().unwrap();
+        assert!(
+            first.contains("pv 48.5") && first.contains("err 1.5"),
+            "{}",
+            first
+        );
+        assert_eq!(monitor.line(tel, at(600), true).unwrap(), None);
+        assert!(monitor.line(tel, at(1100), true).unwrap().is_some());
+        assert_eq!(monitor.line(tel, at(2500), false).unwrap(), None);
+
+        assert_eq!(
+            monitor.line("gains 2 0.5 0\n", at(2600), false).unwrap(),
+            Some("gains  kp 2  ki 0.5  kd 0".to_string())
+        );
+        assert!(monitor
+            .line("nak", at(2700), false)
+            .unwrap()
+            .unwrap()
+            .starts_with("rejected"));
+        assert_eq!(
+            monitor.line("booted v1.2\n", at(2800), false).unwrap(),
+            Some("< booted v1.2".to_string())
+        );
+        assert_eq!(monitor.line("\n", at(2900), true).unwrap(), None);
+
+        let csv = String::from_utf8(monitor.csv.take().unwrap()).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0], "time,setpoint,process_value,output,error");
+        assert_eq!(rows[1], "0.100,50,48.5,3,1.5");
+        assert_eq!(rows[4], "2.500,50,48.5,3,1.5");
+    }
+}
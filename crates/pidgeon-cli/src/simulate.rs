@@ -0,0 +1,444 @@
+use crate::plant::Plant;
+use crate::plot;
+use crate::scenario::{DisturbanceKind, Limits, Scenario};
+use clap::Args;
+use pidgeon::PidController;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Settling band, as a fraction of the step, when the scenario sets none
+const DEFAULT_SETTLING_FRACTION: f64 = 0.02;
+
+/// `pidgeon-cli simulate` flags
+#[derive(Debug, Clone, Args)]
+pub struct SimulateArgs {
+    /// Scenario file (TOML)
+    pub scenario: PathBuf,
+    /// Write every step to this CSV file
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+    /// Plot the run to this file; the extension picks SVG or PNG
+    #[arg(long)]
+    pub plot: Option<PathBuf>,
+    /// Print the metrics as JSON instead of text
+    #[arg(long)]
+    pub json: bool,
+    /// Override the scenario's proportional gain
+    #[arg(long)]
+    pub kp: Option<f64>,
+    /// Override the scenario's integral gain
+    #[arg(long)]
+    pub ki: Option<f64>,
+    /// Override the scenario's derivative gain
+    #[arg(long)]
+    pub kd: Option<f64>,
+}
+
+/// One control step of a run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Seconds from the start
+    pub time: f64,
+    /// Setpoint in effect
+    pub setpoint: f64,
+    /// True process value
+    pub process_value: f64,
+    /// What the controller saw: the process value plus noise and measurement
+    /// disturbances
+    pub measurement: f64,
+    /// Controller output, before load disturbances
+    pub output: f64,
+}
+
+/// How well a run went
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Integral of absolute error
+    pub iae: f64,
+    /// Integral of squared error
+    pub ise: f64,
+    /// Largest overshoot past any setpoint step, in process units
+    pub max_overshoot: f64,
+    /// Longest time a step took to settle, of the steps that did; `None`
+    /// without setpoint steps
+    pub settling_time: Option<f64>,
+    /// Setpoint steps
+    pub steps: usize,
+    /// Setpoint steps still outside the settling band when the next began
+    pub unsettled_steps: usize,
+    /// Fraction of control steps with the output at a limit
+    pub saturation: f64,
+    /// Total output movement, a proxy for actuator wear
+    pub output_travel: f64,
+}
+
+/// Run `scenario` from start to finish
+pub fn simulate(scenario: &Scenario) -> Result<Vec<Sample>, String> {
+    let mut controller = PidController::new(scenario.controller.clone());
+    let mut plant = Plant::new(&scenario.plant, scenario.dt);
+    let mut noise = scenario
+        .noise
+        .map(|noise| (StdRng::seed_from_u64(noise.seed), noise.std_dev));
+
+    let steps = scenario.steps();
+    let mut samples = Vec::with_capacity(steps);
+    for k in 0..steps {
+        let time = k as f64 * scenario.dt;
+        let setpoint = scenario.setpoint_at(time);
+        if setpoint != controller.setpoint() {
+            controller
+                .set_setpoint(setpoint)
+                .map_err(|e| format!("at {} s: {}", time, e))?;
+        }
+        let process_value = plant.value();
+        let mut measurement =
+            process_value + scenario.disturbance_at(DisturbanceKind::Measurement, time);
+        if let Some((rng, std_dev)) = &mut noise {
+            measurement += *std_dev * gaussian(rng);
+        }
+        let output = controller
+            .compute(measurement, scenario.dt)
+            .map_err(|e| format!("at {} s: {}", time, e))?;
+        samples.push(Sample {
+            time,
+            setpoint,
+            process_value,
+            measurement,
+            output,
+        });
+        plant.step(output + scenario.disturbance_at(DisturbanceKind::Load, time));
+    }
+    Ok(samples)
+}
+
+/// Standard normal sample, by the Box-Muller transform
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+/// Score a run of `scenario`
+///
+/// A setpoint step runs from one setpoint change (or the start, if the
+/// process begins away from the setpoint) to the next, or until a
+/// disturbance starts, so disturbance rejection does not count against it.
+/// Its overshoot is the furthest the process value passes the setpoint in
+/// the direction of the step, and it has settled once the error stays inside
+/// the settling band for the rest of the step.
+pub fn metrics(scenario: &Scenario, samples: &[Sample]) -> Metrics {
+    let dt = scenario.dt;
+    let mut metrics = Metrics {
+        iae: 0.0,
+        ise: 0.0,
+        max_overshoot: 0.0,
+        settling_time: None,
+        steps: 0,
+        unsettled_steps: 0,
+        saturation: 0.0,
+        output_travel: 0.0,
+    };
+    let (min_output, max_output) = (
+        scenario.controller.min_output(),
+        scenario.controller.max_output(),
+    );
+    let mut saturated = 0;
+    for (k, sample) in samples.iter().enumerate() {
+        let error = sample.setpoint - sample.process_value;
+        metrics.iae += error.abs() * dt;
+        metrics.ise += error * error * dt;
+        if sample.output <= min_output || sample.output >= max_output {
+            saturated += 1;
+        }
+        if k > 0 {
+            metrics.output_travel += (sample.output - samples[k - 1].output).abs();
+        }
+    }
+    metrics.saturation = saturated as f64 / samples.len().max(1) as f64;
+
+    // A step starts wherever the setpoint starts moving to a new value
+    let mut starts = vec![0];
+    starts.extend(
+        scenario
+            .setpoint
+            .iter()
+            .map(|change| (change.time / dt).ceil() as usize)
+            .filter(|&k| k > 0 && k < samples.len()),
+    );
+    starts.dedup();
+    let disturbances: Vec<usize> = scenario
+        .disturbance
+        .iter()
+        .map(|d| (d.start / dt).ceil() as usize)
+        .collect();
+    for (i, &start) in starts.iter().enumerate() {
+        let next = starts.get(i + 1).copied().unwrap_or(samples.len());
+        let end = disturbances
+            .iter()
+            .copied()
+            .filter(|&k| k > start)
+            .fold(next, usize::min);
+        let step = &samples[start..end];
+        let (Some(first), Some(last)) = (step.first(), step.last()) else {
+            continue;
+        };
+        let size = last.setpoint - first.process_value;
+        let band = scenario
+            .settling_band
+            .unwrap_or(DEFAULT_SETTLING_FRACTION * size.abs());
+        if size.abs() <= band || band <= 0.0 {
+            continue;
+        }
+        metrics.steps += 1;
+
+        let direction = size.signum();
+        for sample in step {
+            let overshoot = (sample.process_value - sample.setpoint) * direction;
+            metrics.max_overshoot = metrics.max_overshoot.max(overshoot);
+        }
+        match step
+            .iter()
+            .rposition(|s| (s.setpoint - s.process_value).abs() > band)
+        {
+            Some(outside) if outside == step.len() - 1 => metrics.unsettled_steps += 1,
+            outside => {
+                let settled_at = outside.map_or(first.time, |k| step[k + 1].time);
+                let time = settled_at - first.time;
+                metrics.settling_time = Some(metrics.settling_time.map_or(time, |t| t.max(time)));
+            }
+        }
+    }
+    metrics
+}
+
+/// Describe each limit `metrics` exceeds
+pub fn check_limits(limits: &Limits, metrics: &Metrics) -> Vec<String> {
+    let mut failures = Vec::new();
+    if let Some(max) = limits.max_overshoot {
+        if metrics.max_overshoot > max {
+            failures.push(format!(
+                "overshoot {:.4} exceeds {}",
+                metrics.max_overshoot, max
+            ));
+        }
+    }
+    if let Some(max) = limits.max_settling_time {
+        if metrics.unsettled_steps > 0 {
+            failures.push(format!(
+                "{} of {} setpoint steps never settled",
+                metrics.unsettled_steps, metrics.steps
+            ));
+        }
+        if let Some(time) = metrics.settling_time.filter(|&time| time > max) {
+            failures.push(format!("settling time {} s exceeds {} s", time, max));
+        }
+    }
+    if let Some(max) = limits.max_iae {
+        if metrics.iae > max {
+            failures.push(format!("IAE {:.4} exceeds {}", metrics.iae, max));
+        }
+    }
+    if let Some(max) = limits.max_saturation {
+        if metrics.saturation > max {
+            failures.push(format!(
+                "saturation {:.1}% exceeds {:.1}%",
+                metrics.saturation * 100.0,
+                max * 100.0
+            ));
+        }
+    }
+    failures
+}
+
+/// Write `samples` as CSV with a header row
+pub fn write_csv(path: &Path, samples: &[Sample]) -> Result<(), String> {
+    let write = || -> std::io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "time,setpoint,process_value,measurement,output")?;
+        for s in samples {
+            writeln!(
+                file,
+                "{},{},{},{},{}",
+                s.time, s.setpoint, s.process_value, s.measurement, s.output
+            )?;
+        }
+        file.flush()
+    };
+    write().map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Human-readable summary of a run
+fn report(name: &str, scenario: &Scenario, metrics: &Metrics) -> String {
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "{}: {} s in {} steps of {} s",
+        name,
+        scenario.duration,
+        scenario.steps(),
+        scenario.dt
+    );
+    let settling = match metrics.settling_time {
+        Some(time) if metrics.unsettled_steps == 0 => format!("{} s", time),
+        Some(time) => format!("{} s ({} never settled)", time, metrics.unsettled_steps),
+        None if metrics.unsettled_steps > 0 => "never".to_string(),
+        None => "-".to_string(),
+    };
+    let rows = [
+        ("IAE", format!("{:.4}", metrics.iae)),
+        ("ISE", format!("{:.4}", metrics.ise)),
+        ("setpoint steps", metrics.steps.to_string()),
+        ("max overshoot", format!("{:.4}", metrics.max_overshoot)),
+        ("settling time", settling),
+        ("saturation", format!("{:.1}%", metrics.saturation * 100.0)),
+        ("output travel", format!("{:.4}", metrics.output_travel)),
+    ];
+    for (label, value) in rows {
+        let _ = writeln!(text, "  {:<16}{}", label, value);
+    }
+    text
+}
+
+/// Run the `simulate` command
+pub fn run(args: SimulateArgs) -> Result<ExitCode, String> {
+    let mut scenario = Scenario::load(&args.scenario)?;
+    // Applied through a controller so overrides are validated like the file
+    let mut controller = PidController::new(scenario.controller.clone());
+    let invalid = |e| format!("invalid gain override: {}", e);
+    if let Some(kp) = args.kp {
+        controller.set_kp(kp).map_err(invalid)?;
+    }
+    if let Some(ki) = args.ki {
+        controller.set_ki(ki).map_err(invalid)?;
+    }
+    if let Some(kd) = args.kd {
+        controller.set_kd(kd).map_err(invalid)?;
+    }
+    scenario.controller = controller.config().clone();
+
+    let samples = simulate(&scenario)?;
+    let metrics = metrics(&scenario, &samples);
+    let failures = check_limits(&scenario.limits, &metrics);
+    let name = scenario.name.clone().unwrap_or_default();
+
+    if let Some(path) = &args.csv {
+        write_csv(path, &samples)?;
+    }
+    if let Some(path) = &args.plot {
+        plot::draw(path, &name, &samples)?;
+    }
+
+    if args.json {
+        let json = serde_json::json!({
+            "scenario": name,
+            "metrics": metrics,
+            "passed": failures.is_empty(),
+            "failures": failures,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?
+        );
+    } else {
+        print!("{}", report(&name, &scenario, &metrics));
+        for failure in &failures {
+            println!("FAIL: {}", failure);
+        }
+    }
+    Ok(if failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::PlantConfig;
+    use pidgeon::ControllerConfig;
+
+    fn scenario(kp: f64, ki: f64) -> Scenario {
+        Scenario {
+            name: None,
+            dt: 0.1,
+            duration: 60.0,
+            controller: ControllerConfig::builder()
+                .with_kp(kp)
+                .with_ki(ki)
+                .with_setpoint(10.0)
+                .with_output_limits(-100.0, 100.0)
+                .build()
+                .unwrap(),
+            plant: PlantConfig::FirstOrder {
+                gain: 1.0,
+                time_constant: 2.0,
+                dead_time: 0.5,
+                ambient: 0.0,
+            },
+            setpoint: Vec::new(),
+            disturbance: Vec::new(),
+            noise: None,
+            settling_band: None,
+            limits: Limits::default(),
+        }
+    }
+
+    #[test]
+    fn test_closed_loop_reaches_setpoint() {
+        let scenario = scenario(1.0, 0.5);
+        let samples = simulate(&scenario).unwrap();
+        assert_eq!(samples.len(), 600);
+        assert!((samples.last().unwrap().process_value - 10.0).abs() < 0.01);
+
+        let metrics = metrics(&scenario, &samples);
+        assert_eq!(metrics.steps, 1);
+        assert_eq!(metrics.unsettled_steps, 0);
+        let settling = metrics.settling_time.unwrap();
+        assert!(settling > 1.0 && settling < 30.0, "{}", settling);
+        assert!(metrics.iae > 0.0 && metrics.ise > 0.0);
+    }
+
+    #[test]
+    fn test_aggressive_tune_fails_limits() {
+        let mut gentle = scenario(1.0, 0.5);
+        gentle.limits.max_overshoot = Some(0.5);
+        let mut aggressive = gentle.clone();
+        aggressive.controller = scenario(6.0, 6.0).controller;
+
+        let m = metrics(&gentle, &simulate(&gentle).unwrap());
+        assert!(check_limits(&gentle.limits, &m).is_empty(), "{:?}", m);
+        let m = metrics(&aggressive, &simulate(&aggressive).unwrap());
+        assert!(m.max_overshoot > 0.5);
+        assert_eq!(check_limits(&aggressive.limits, &m).len(), 1);
+    }
+
+    #[test]
+    fn test_proportional_only_never_settles() {
+        // Offset of 10 / (1 + Kp) is far outside the band
+        let mut scenario = scenario(1.0, 0.0);
+        scenario.limits.max_settling_time = Some(60.0);
+        let metrics = metrics(&scenario, &simulate(&scenario).unwrap());
+        assert_eq!(metrics.unsettled_steps, 1);
+        assert_eq!(metrics.settling_time, None);
+        assert_eq!(check_limits(&scenario.limits, &metrics).len(), 1);
+    }
+
+    #[test]
+    fn test_noise_is_repeatable() {
+        let mut scenario = scenario(1.0, 0.5);
+        scenario.noise = Some(crate::scenario::Noise {
+            std_dev: 0.1,
+            seed: 3,
+        });
+        let first = simulate(&scenario).unwrap();
+        assert_eq!(first, simulate(&scenario).unwrap());
+        assert!(first.iter().any(|s| s.measurement != s.process_value));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::publish::Target;
+
+/// A connection samples are received on
+///
+/// Takes the same URLs as [`Publisher`](crate::publish::Publisher): an Iggy
+/// target is read from its stream and topic, an MQTT one from every topic
+/// under its prefix, or only the given controllers' topics.
+pub enum Subscriber {
+    #[cfg(feature = "iggy")]
+    Iggy(iggy_subscriber::IggySubscriber),
+    #[cfg(feature = "mqtt")]
+    Mqtt(Box<mqtt_subscriber::MqttSubscriber>),
+}
+
+impl Subscriber {
+    /// Connect to `target`, failing if it cannot be reached; only samples
+    /// published from now on are received
+    #[cfg_attr(not(feature = "mqtt"), allow(unused_variables))]
+    pub async fn connect(target: &Target, controller_ids: &[String]) -> Result<Self, String> {
+        match target {
+            #[cfg(feature = "iggy")]
+            Target::Iggy { .. } => iggy_subscriber::IggySubscriber::connect(target)
+                .await
+                .map(Subscriber::Iggy),
+            #[cfg(feature = "mqtt")]
+            Target::Mqtt { .. } => mqtt_subscriber::MqttSubscriber::connect(target, controller_ids)
+                .await
+                .map(|subscriber| Subscriber::Mqtt(Box::new(subscriber))),
+            #[allow(unreachable_patterns)]
+            _ => Err(format!(
+                "pidgeon-cli was built without the '{}' feature",
+                match target {
+                    Target::Iggy { .. } => "iggy",
+                    Target::Mqtt { .. } => "mqtt",
+                }
+            )),
+        }
+    }
+
+    /// Wait for the next payloads; an error is worth showing but the
+    /// connection keeps trying, so call again after one
+    pub async fn next(&mut self) -> Result<Vec<Vec<u8>>, String> {
+        match *self {
+            #[cfg(feature = "iggy")]
+            Subscriber::Iggy(ref mut subscriber) => subscriber.next().await,
+            #[cfg(feature = "mqtt")]
+            Subscriber::Mqtt(ref mut subscriber) => subscriber.next().await,
+        }
+    }
+}
+
+#[cfg(feature = "iggy")]
+mod iggy_subscriber {
+    use super::Target;
+    use iggy::client::{Client, MessageClient, UserClient};
+    use iggy::clients::client::IggyClient;
+    use iggy::consumer::Consumer;
+    use iggy::identifier::Identifier;
+    use iggy::messages::poll_messages::PollingStrategy;
+    use iggy::utils::timestamp::IggyTimestamp;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    /// Messages fetched per poll
+    const BATCH: u32 = 100;
+    /// Wait before polling again when nothing was waiting
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+    /// Wait before polling again after an error
+    const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+    pub struct IggySubscriber {
+        client: IggyClient,
+        stream: Identifier,
+        topic: Identifier,
+        /// Named after the process, so the offsets of Pidgeoneer and other
+        /// viewers are left alone
+        consumer: Consumer,
+        /// From now at first, then wherever the last poll stopped
+        strategy: PollingStrategy,
+    }
+
+    impl IggySubscriber {
+        /// Log in and start reading at the present
+        pub async fn connect(target: &Target) -> Result<Self, String> {
+            let Target::Iggy {
+                address,
+                username,
+                password,
+                stream,
+                topic,
+            } = target
+            else {
+                unreachable!("not an Iggy target");
+            };
+            let fail = |what: &str, e: iggy::error::IggyError| {
+                format!("failed to {} at {}: {}", what, target, e)
+            };
+
+            let connection_string = format!("iggy://{}:{}@{}", username, password, address);
+            let client = IggyClient::from_connection_string(&connection_string)
+                .map_err(|e| fail("create a client", e))?;
+            client.connect().await.map_err(|e| fail("connect", e))?;
+            client
+                .login_user(username, password)
+                .await
+                .map_err(|e| fail("log in", e))?;
+            let consumer_id =
+                Identifier::numeric(std::process::id()).map_err(|e| fail("name a consumer", e))?;
+            Ok(IggySubscriber {
+                client,
+                stream: Identifier::from_str(stream).map_err(|e| fail("find the stream", e))?,
+                topic: Identifier::from_str(topic).map_err(|e| fail("find the topic", e))?,
+                consumer: Consumer::new(consumer_id),
+                strategy: PollingStrategy::timestamp(IggyTimestamp::now()),
+            })
+        }
+
+        pub async fn next(&mut self) -> Result<Vec<Vec<u8>>, String> {
+            loop {
+                let polled = match self
+                    .client
+                    .poll_messages(
+                        &self.stream,
+                        &self.topic,
+                        None,
+                        &self.consumer,
+                        &self.strategy,
+                        BATCH,
+                        true,
+                    )
+                    .await
+                {
+                    Ok(polled) => polled,
+                    Err(e) => {
+                        tokio::time::sleep(RETRY_DELAY).await;
+                        return Err(format!("failed to poll Iggy: {}", e));
+                    }
+                };
+                // Offsets are committed as they are read
+                self.strategy = PollingStrategy::next();
+                if polled.messages.is_empty() {
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+                return Ok(polled
+                    .messages
+                    .into_iter()
+                    .map(|message| message.payload.to_vec())
+                    .collect());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+mod mqtt_subscriber {
+    use super::Target;
+    use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+    use std::time::Duration;
+
+    /// Requests buffered between the client handle and the event loop
+    const REQUEST_CAPACITY: usize = 16;
+    /// Interval between keepalive pings
+    const KEEP_ALIVE: Duration = Duration::from_secs(30);
+    /// Wait before reconnecting after a connection error
+    const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+    /// Largest accepted payload
+    const MAX_PACKET_SIZE: usize = 1024 * 1024;
+
+    pub struct MqttSubscriber {
+        client: AsyncClient,
+        event_loop: EventLoop,
+        /// Topic filters, renewed on every reconnect
+        topics: Vec<String>,
+        target: String,
+    }
+
+    impl MqttSubscriber {
+        /// Connect and subscribe, waiting for the broker to accept before
+        /// returning
+        pub async fn connect(target: &Target, controller_ids: &[String]) -> Result<Self, String> {
+            let Target::Mqtt {
+                host,
+                port,
+                credentials,
+                prefix,
+            } = target
+            else {
+                unreachable!("not an MQTT target");
+            };
+            let client_id = format!("pidgeon-cli-watch-{}", std::process::id());
+            let mut options = MqttOptions::new(client_id, host, *port);
+            options.set_keep_alive(KEEP_ALIVE);
+            options.set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE);
+            if let Some((user, password)) = credentials {
+                options.set_credentials(user, password);
+            }
+            let (client, mut event_loop) = AsyncClient::new(options, REQUEST_CAPACITY);
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => break,
+                    Ok(_) => {}
+                    Err(e) => return Err(format!("failed to connect to {}: {}", target, e)),
+                }
+            }
+
+            let topics = if controller_ids.is_empty() {
+                vec![format!("{}/#", prefix)]
+            } else {
+                controller_ids
+                    .iter()
+                    .map(|id| Target::mqtt_topic(prefix, id))
+                    .collect()
+            };
+            let subscriber = MqttSubscriber {
+                client,
+                event_loop,
+                topics,
+                target: target.to_string(),
+            };
+            subscriber.subscribe()?;
+            Ok(subscriber)
+        }
+
+        fn subscribe(&self) -> Result<(), String> {
+            for topic in &self.topics {
+                self.client
+                    .try_subscribe(topic, QoS::AtMostOnce)
+                    .map_err(|e| format!("failed to subscribe to {}: {}", topic, e))?;
+            }
+            Ok(())
+        }
+
+        pub async fn next(&mut self) -> Result<Vec<Vec<u8>>, String> {
+            loop {
+                match self.event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        return Ok(vec![publish.payload.to_vec()]);
+                    }
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => self.subscribe()?,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                        return Err(format!("MQTT connection to {} failed: {}", self.target, e));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use crate::scenario::Scenario;
+use crate::simulate::{check_limits, metrics, simulate, Metrics};
+use clap::{Args, ValueEnum};
+use pidgeon::PidController;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Most gain combinations one sweep will try
+pub const MAX_POINTS: usize = 1_000_000;
+
+/// `pidgeon-cli sweep` flags
+#[derive(Debug, Clone, Args)]
+pub struct SweepArgs {
+    /// Scenario file (TOML)
+    pub scenario: PathBuf,
+    /// Proportional gains to try: a value, a list like 1,2,4, or
+    /// start:stop:count [default: the scenario's]
+    #[arg(long, value_parser = parse_values)]
+    pub kp: Option<Values>,
+    /// Integral gains to try, in the same forms as --kp [default: the
+    /// scenario's]
+    #[arg(long, value_parser = parse_values)]
+    pub ki: Option<Values>,
+    /// Derivative gains to try, in the same forms as --kp [default: the
+    /// scenario's]
+    #[arg(long, value_parser = parse_values)]
+    pub kd: Option<Values>,
+    /// Monte-Carlo runs per combination, each with the next noise seed after
+    /// the scenario's; needs [noise] in the scenario
+    #[arg(long, default_value_t = 1)]
+    pub seeds: u64,
+    /// Metric to rank by; combinations meeting every limit in every run come
+    /// first
+    #[arg(long, value_enum, default_value_t = Rank::Iae)]
+    pub rank: Rank,
+    /// Combinations to print
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+    /// Write every combination, ranked, to this CSV file
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+    /// Print the ranked combinations as JSON
+    #[arg(long)]
+    pub json: bool,
+    /// Worker threads [default: one per core]
+    #[cfg(feature = "parallel")]
+    #[arg(long)]
+    pub threads: Option<usize>,
+}
+
+/// Gains to try for one term
+#[derive(Debug, Clone, PartialEq)]
+pub struct Values(pub Vec<f64>);
+
+/// Parse `2`, `1,2,4`, or `start:stop:count` (both ends included)
+pub fn parse_values(s: &str) -> Result<Values, String> {
+    let number = |text: &str| -> Result<f64, String> {
+        match text.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(value),
+            _ => Err(format!("'{}' is not a finite number", text.trim())),
+        }
+    };
+    let values = match s.split(':').collect::<Vec<_>>()[..] {
+        [start, stop, count] => {
+            let (start, stop) = (number(start)?, number(stop)?);
+            let count: usize = count
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not a count", count.trim()))?;
+            match count {
+                0 => return Err("a range needs a count of at least 1".to_string()),
+                1 => vec![start],
+                _ => (0..count)
+                    .map(|i| start + (stop - start) * i as f64 / (count - 1) as f64)
+                    .collect(),
+            }
+        }
+        [list] => list.split(',').map(number).collect::<Result<_, _>>()?,
+        _ => return Err(format!("expected start:stop:count, got '{}'", s)),
+    };
+    Ok(Values(values))
+}
+
+/// Metrics a sweep can rank by, lowest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Rank {
+    /// Integral of absolute error
+    Iae,
+    /// Integral of squared error
+    Ise,
+    /// Largest overshoot
+    Overshoot,
+    /// Longest settling time
+    Settling,
+}
+
+/// How one gain combination did across its runs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Outcome {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    /// Runs with different noise seeds
+    pub runs: usize,
+    /// Runs that met every limit
+    pub passed: usize,
+    /// Mean over the runs
+    pub iae: f64,
+    /// Mean over the runs
+    pub ise: f64,
+    /// Worst over the runs
+    pub max_overshoot: f64,
+    /// Worst over the runs; `None` if a run had a step that never settled,
+    /// or there were no setpoint steps
+    pub settling_time: Option<f64>,
+    /// Mean over the runs
+    pub saturation: f64,
+}
+
+impl Outcome {
+    fn key(&self, rank: Rank) -> f64 {
+        match rank {
+            Rank::Iae => self.iae,
+            Rank::Ise => self.ise,
+            Rank::Overshoot => self.max_overshoot,
+            Rank::Settling => self.settling_time.unwrap_or(f64::INFINITY),
+        }
+    }
+}
+
+/// Every combination of the gains to try, with `scenario`'s gain for any
+/// term not given
+pub fn grid(
+    scenario: &Scenario,
+    kp: Option<&Values>,
+    ki: Option<&Values>,
+    kd: Option<&Values>,
+) -> Result<Vec<[f64; 3]>, String> {
+    let config = &scenario.controller;
+    let kp = kp.map_or(vec![config.kp()], |v| v.0.clone());
+    let ki = ki.map_or(vec![config.ki()], |v| v.0.clone());
+    let kd = kd.map_or(vec![config.kd()], |v| v.0.clone());
+    let points = kp.len() * ki.len() * kd.len();
+    if points > MAX_POINTS {
+        return Err(format!(
+            "{} gain combinations is more than the {} allowed",
+            points, MAX_POINTS
+        ));
+    }
+    let mut grid = Vec::with_capacity(points);
+    for &p in &kp {
+        for &i in &ki {
+            for &d in &kd {
+                grid.push([p, i, d]);
+            }
+        }
+    }
+    Ok(grid)
+}
+
+/// Run `scenario` with `gains` once per seed and combine the results
+pub fn evaluate(
+    scenario: &Scenario,
+    [kp, ki, kd]: [f64; 3],
+    seeds: u64,
+) -> Result<Outcome, String> {
+    // Applied through a controller so the gains are validated like `simulate`'s
+    let mut controller = PidController::new(scenario.controller.clone());
+    let invalid = |e| format!("invalid gains kp={} ki={} kd={}: {}", kp, ki, kd, e);
+    controller.set_kp(kp).map_err(invalid)?;
+    controller.set_ki(ki).map_err(invalid)?;
+    controller.set_kd(kd).map_err(invalid)?;
+    let mut scenario = scenario.clone();
+    scenario.controller = controller.config().clone();
+
+    let base_seed = scenario.noise.map_or(0, |noise| noise.seed);
+    let mut runs: Vec<Metrics> = Vec::new();
+    let mut passed = 0;
+    for offset in 0..seeds {
+        if let Some(noise) = &mut scenario.noise {
+            noise.seed = base_seed.wrapping_add(offset);
+        }
+        let samples = simulate(&scenario)?;
+        let m = metrics(&scenario, &samples);
+        if check_limits(&scenario.limits, &m).is_empty() {
+            passed += 1;
+        }
+        runs.push(m);
+    }
+
+    let mean = |field: fn(&Metrics) -> f64| runs.iter().map(field).sum::<f64>() / runs.len() as f64;
+    let settling_time = if runs.iter().any(|m| m.unsettled_steps > 0) {
+        None
+    } else {
+        runs.iter().filter_map(|m| m.settling_time).reduce(f64::max)
+    };
+    Ok(Outcome {
+        kp,
+        ki,
+        kd,
+        runs: runs.len(),
+        passed,
+        iae: mean(|m| m.iae),
+        ise: mean(|m| m.ise),
+        max_overshoot: runs.iter().map(|m| m.max_overshoot).fold(0.0, f64::max),
+        settling_time,
+        saturation: mean(|m| m.saturation),
+    })
+}
+
+/// Evaluate every point of `grid`, in parallel with the `parallel` feature;
+/// results are in grid order either way
+pub fn evaluate_all(
+    scenario: &Scenario,
+    grid: &[[f64; 3]],
+    seeds: u64,
+) -> Result<Vec<Outcome>, String> {
+    #[cfg(feature = "parallel")]
+    let points = grid.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let points = grid.iter();
+    points
+        .map(|&gains| evaluate(scenario, gains, seeds))
+        .collect()
+}
+
+/// Sort `outcomes` best first: those passing every run, then by `rank`
+pub fn rank(outcomes: &mut [Outcome], rank: Rank) {
+    outcomes.sort_by(|a, b| {
+        let all_passed = |o: &Outcome| o.passed == o.runs;
+        all_passed(b)
+            .cmp(&all_passed(a))
+            .then(a.key(rank).total_cmp(&b.key(rank)))
+    });
+}
+
+/// The best `top` of ranked `outcomes` as a table
+fn table(outcomes: &[Outcome], top: usize) -> String {
+    let mut text = format!(
+        "{:>12}{:>12}{:>12}{:>14}{:>14}{:>12}{:>12}{:>10}\n",
+        "kp", "ki", "kd", "IAE", "ISE", "overshoot", "settling", "passed"
+    );
+    for o in outcomes.iter().take(top) {
+        let settling = o
+            .settling_time
+            .map_or("-".to_string(), |time| format!("{} s", time));
+        let _ = writeln!(
+            text,
+            "{:>12.6}{:>12.6}{:>12.6}{:>14.4}{:>14.4}{:>12.4}{:>12}{:>10}",
+            o.kp,
+            o.ki,
+            o.kd,
+            o.iae,
+            o.ise,
+            o.max_overshoot,
+            settling,
+            format!("{}/{}", o.passed, o.runs)
+        );
+    }
+    text
+}
+
+/// Write ranked `outcomes` as CSV with a header row
+fn write_csv(path: &Path, outcomes: &[Outcome]) -> Result<(), String> {
+    let write = || -> std::io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(
+            file,
+            "kp,ki,kd,runs,passed,iae,ise,max_overshoot,settling_time,saturation"
+        )?;
+        for o in outcomes {
+            let settling = o.settling_time.map_or(String::new(), |t| t.to_string());
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{},{},{},{}",
+                o.kp,
+                o.ki,
+                o.kd,
+                o.runs,
+                o.passed,
+                o.iae,
+                o.ise,
+                o.max_overshoot,
+                settling,
+                o.saturation
+            )?;
+        }
+        file.flush()
+    };
+    write().map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Run the `sweep` command
+pub fn run(args: SweepArgs) -> Result<ExitCode, String> {
+    let scenario = Scenario::load(&args.scenario)?;
+    if args.seeds == 0 {
+        return Err("--seeds must be at least 1".to_string());
+    }
+    if args.seeds > 1 && scenario.noise.is_none() {
+        return Err(
+            "--seeds needs [noise] in the scenario; without it every run is the same".to_string(),
+        );
+    }
+    let grid = grid(
+        &scenario,
+        args.kp.as_ref(),
+        args.ki.as_ref(),
+        args.kd.as_ref(),
+    )?;
+
+    #[cfg(feature = "parallel")]
+    let mut outcomes = {
+        let mut pool = rayon::ThreadPoolBuilder::new();
+        if let Some(threads) = args.threads {
+            pool = pool.num_threads(threads);
+        }
+        pool.build()
+            .map_err(|e| format!("failed to start worker threads: {}", e))?
+            .install(|| evaluate_all(&scenario, &grid, args.seeds))?
+    };
+    #[cfg(not(feature = "parallel"))]
+    let mut outcomes = evaluate_all(&scenario, &grid, args.seeds)?;
+    rank(&mut outcomes, args.rank);
+
+    if let Some(path) = &args.csv {
+        write_csv(path, &outcomes)?;
+    }
+    if args.json {
+        let top: Vec<&Outcome> = outcomes.iter().take(args.top).collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&top).map_err(|e| e.to_string())?
+        );
+    } else {
+        println!(
+            "{}: {} combinations x {} runs",
+            scenario.name.as_deref().unwrap_or_default(),
+            grid.len(),
+            args.seeds
+        );
+        print!("{}", table(&outcomes, args.top));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::{Limits, Noise, PlantConfig};
+    use clap::Parser;
+    use pidgeon::ControllerConfig;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: SweepArgs,
+    }
+
+    fn scenario() -> Scenario {
+        Scenario {
+            name: None,
+            dt: 0.1,
+            duration: 60.0,
+            controller: ControllerConfig::builder()
+                .with_kp(1.0)
+                .with_ki(0.5)
+                .with_setpoint(10.0)
+                .with_output_limits(-100.0, 100.0)
+                .build()
+                .unwrap(),
+            plant: PlantConfig::FirstOrder {
+                gain: 1.0,
+                time_constant: 2.0,
+                dead_time: 0.5,
+                ambient: 0.0,
+            },
+            setpoint: Vec::new(),
+            disturbance: Vec::new(),
+            noise: None,
+            settling_band: None,
+            limits: Limits {
+                max_overshoot: Some(0.5),
+                ..Limits::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse_values("2.5"), Ok(Values(vec![2.5])));
+        assert_eq!(parse_values("1, 2,4"), Ok(Values(vec![1.0, 2.0, 4.0])));
+        assert_eq!(
+            parse_values("0:1:5"),
+            Ok(Values(vec![0.0, 0.25, 0.5, 0.75, 1.0]))
+        );
+        assert_eq!(parse_values("3:9:1"), Ok(Values(vec![3.0])));
+
+        assert!(parse_values("0:1:0").unwrap_err().contains("at least 1"));
+        assert!(parse_values("0:1").is_err());
+        assert!(parse_values("1,x").unwrap_err().contains("'x'"));
+        assert!(parse_values("inf").is_err());
+        assert!(parse_values("0:1:-2").is_err());
+    }
+
+    #[test]
+    fn test_grid_defaults_to_scenario_gains() {
+        let scenario = scenario();
+        let kp = parse_values("1,2,3").unwrap();
+        let kd = parse_values("0:0.1:2").unwrap();
+        let points = grid(&scenario, Some(&kp), None, Some(&kd)).unwrap();
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0], [1.0, 0.5, 0.0]);
+        assert_eq!(points[5], [3.0, 0.5, 0.1]);
+
+        let huge = parse_values("0:1:1000").unwrap();
+        assert!(grid(&scenario, Some(&huge), Some(&huge), Some(&huge)).is_err());
+    }
+
+    #[test]
+    fn test_sweep_ranks_passing_tunes_first() {
+        let scenario = scenario();
+        let grid = grid(
+            &scenario,
+            Some(&parse_values("1,6").unwrap()),
+            Some(&parse_values("0.5,6").unwrap()),
+            None,
+        )
+        .unwrap();
+        let mut outcomes = evaluate_all(&scenario, &grid, 1).unwrap();
+        let sequential: Vec<Outcome> = grid
+            .iter()
+            .map(|&gains| evaluate(&scenario, gains, 1).unwrap())
+            .collect();
+        assert_eq!(outcomes, sequential);
+
+        rank(&mut outcomes, Rank::Iae);
+        assert_eq!(outcomes.len(), 4);
+        let passing = outcomes.iter().take_while(|o| o.passed == o.runs).count();
+        assert!(passing > 0 && passing < 4, "{:?}", outcomes);
+        assert!(outcomes[..passing]
+            .windows(2)
+            .all(|pair| pair[0].iae <= pair[1].iae));
+        // The aggressive tune overshoots
+        let aggressive = outcomes
+            .iter()
+            .find(|o| o.kp == 6.0 && o.ki == 6.0)
+            .unwrap();
+        assert_eq!(aggressive.passed, 0);
+        assert!(aggressive.max_overshoot > 0.5);
+
+        let text = table(&outcomes, 2);
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.contains("1/1"), "{}", text);
+    }
+
+    #[test]
+    fn test_monte_carlo_runs_vary_the_seed() {
+        let mut scenario = scenario();
+        scenario.noise = Some(Noise {
+            std_dev: 0.5,
+            seed: 7,
+        });
+        let one = evaluate(&scenario, [1.0, 0.5, 0.0], 1).unwrap();
+        let many = evaluate(&scenario, [1.0, 0.5, 0.0], 8).unwrap();
+        assert_eq!(many.runs, 8);
+        assert_ne!(one.iae, many.iae);
+        assert_eq!(evaluate(&scenario, [1.0, 0.5, 0.0], 8).unwrap(), many);
+    }
+
+    #[test]
+    fn test_args() {
+        let args = Cli::parse_from([
+            "sweep", "run.toml", "--kp", "1:10:10", "--ki", "0.1,0.2", "--rank", "settling",
+            "--seeds", "4",
+        ])
+        .args;
+        assert_eq!(args.kp.unwrap().0.len(), 10);
+        assert_eq!(args.ki, Some(Values(vec![0.1, 0.2])));
+        assert_eq!(args.kd, None);
+        assert_eq!(args.rank, Rank::Settling);
+        assert_eq!(args.seeds, 4);
+        assert_eq!(args.top, 10);
+
+        assert!(Cli::try_parse_from(["sweep", "run.toml", "--kp", "1:2"]).is_err());
+        assert!(Cli::try_parse_from(["sweep", "run.toml", "--rank", "speed"]).is_err());
+    }
+}
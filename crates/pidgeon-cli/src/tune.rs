@@ -0,0 +1,303 @@
+use crate::capture::{Capture, Columns};
+use crate::gains;
+use clap::Args;
+use pidgeon::{ControllerConfig, FopdtModel, TuneRecord, TuningRule};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Fit below which the model, and so the gains, deserve suspicion
+const POOR_FIT: f64 = 0.8;
+
+/// `pidgeon-cli tune` flags
+#[derive(Debug, Clone, Args)]
+pub struct TuneArgs {
+    /// Step-test capture: CSV with a header row, or JSON lines (.jsonl)
+    #[arg(long, short)]
+    pub input: PathBuf,
+    /// Tuning rule: simc, simc-pid, lambda, zn-pi, zn-pid, cohen-coon-pi, or
+    /// cohen-coon-pid
+    #[arg(long, default_value = "simc")]
+    pub rule: TuningRule,
+    /// Seconds between samples [default: from a `dt` or `time` column]
+    #[arg(long)]
+    pub dt: Option<f64>,
+    /// Column holding the controller output
+    #[arg(long, default_value = "output")]
+    pub output_column: String,
+    /// Column holding the process value
+    #[arg(long, default_value = "process_value")]
+    pub pv_column: String,
+    /// Only use rows with this `controller_id`
+    #[arg(long)]
+    pub controller_id: Option<String>,
+    /// Setpoint of the written config [default: the capture's last setpoint, or 0]
+    #[arg(long, allow_hyphen_values = true)]
+    pub setpoint: Option<f64>,
+    /// Lower output limit [default: the lowest recorded output]
+    #[arg(long, allow_hyphen_values = true)]
+    pub min_output: Option<f64>,
+    /// Upper output limit [default: the highest recorded output]
+    #[arg(long, allow_hyphen_values = true)]
+    pub max_output: Option<f64>,
+    /// Write the config to this file, as JSON if it ends in `.json` and TOML
+    /// otherwise
+    #[arg(long, short)]
+    pub write: Option<PathBuf>,
+    /// Write the tune's provenance (model, rule, gains, fingerprint of the
+    /// capture, time) to this file, as JSON if it ends in `.json` and TOML
+    /// otherwise
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+    /// Also list the gains every rule gives
+    #[arg(long)]
+    pub compare: bool,
+}
+
+impl From<&TuneArgs> for Columns {
+    fn from(args: &TuneArgs) -> Self {
+        Columns {
+            output: args.output_column.clone(),
+            process_value: args.pv_column.clone(),
+            controller_id: args.controller_id.clone(),
+        }
+    }
+}
+
+/// Build the config `args.rule` suggests for the process in `capture`, with
+/// the record of how it was made
+pub fn tune(capture: &Capture, args: &TuneArgs) -> Result<(TuneRecord, ControllerConfig), String> {
+    let rule = args.rule;
+    let identification =
+        FopdtModel::identify(&capture.outputs, &capture.process_values, capture.dt)
+            .map_err(|e| format!("cannot identify the process: {}", e))?;
+    let record = TuneRecord::from_identification(
+        identification,
+        rule,
+        &capture.outputs,
+        &capture.process_values,
+        capture.dt,
+    )
+    .map_err(|e| format!("cannot apply {}: {}", rule, e))?;
+
+    let recorded = |pick: fn(f64, f64) -> f64| {
+        capture
+            .outputs
+            .iter()
+            .copied()
+            .reduce(pick)
+            .unwrap_or_default()
+    };
+    let config = ControllerConfig::builder()
+        .with_tune(&record)
+        .with_setpoint(args.setpoint.or(capture.setpoint).unwrap_or_default())
+        .with_output_limits(
+            args.min_output.unwrap_or_else(|| recorded(f64::min)),
+            args.max_output.unwrap_or_else(|| recorded(f64::max)),
+        )
+        .build()
+        .map_err(|e| format!("invalid config: {}", e))?;
+    Ok((record, config))
+}
+
+/// Human-readable summary of the identified model and the suggested gains
+fn report(record: &TuneRecord, config: &ControllerConfig) -> String {
+    let identification = &record.identification;
+    let model = &identification.model;
+    let mut text = String::new();
+    let _ = writeln!(text, "process model (first order plus dead time)");
+    let _ = writeln!(text, "  {:<16}{:.6}", "gain", model.gain);
+    let _ = writeln!(
+        text,
+        "  {:<16}{:.3} s",
+        "time constant", model.time_constant
+    );
+    let _ = writeln!(text, "  {:<16}{:.3} s", "dead time", model.dead_time);
+    let _ = writeln!(text, "  {:<16}{:.3}", "fit (R²)", identification.fit);
+    let _ = writeln!(text, "{} gains", record.rule);
+    let _ = writeln!(text, "  {:<16}{:.6}", "kp", config.kp());
+    let _ = writeln!(text, "  {:<16}{:.6}", "ki", config.ki());
+    let _ = writeln!(text, "  {:<16}{:.6}", "kd", config.kd());
+    text
+}
+
+/// Write `value` as JSON if `path` ends in `.json` and TOML otherwise
+fn write_file(path: &Path, value: &impl Serialize) -> Result<(), String> {
+    let contents = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+        serde_json::to_string_pretty(value).map_err(|e| e.to_string())? + "\n"
+    } else {
+        toml::to_string(value).map_err(|e| e.to_string())?
+    };
+    std::fs::write(path, contents).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Run the `tune` command
+pub fn run(args: TuneArgs) -> Result<ExitCode, String> {
+    let capture = Capture::load(&args.input, &Columns::from(&args), args.dt)?;
+    let (record, config) = tune(&capture, &args)?;
+    let identification = &record.identification;
+
+    print!("{}", report(&record, &config));
+    if identification.fit < POOR_FIT {
+        eprintln!(
+            "warning: the model explains only {:.0}% of the recording; check that the output \
+             stepped cleanly and the process was otherwise undisturbed",
+            identification.fit * 100.0
+        );
+    }
+    if args.min_output.is_none() || args.max_output.is_none() {
+        eprintln!(
+            "note: output limits not given are taken from the recording; set them with \
+             --min-output and --max-output"
+        );
+    }
+    if args.compare {
+        println!();
+        print!("{}", gains::table(&identification.model, &TuningRule::ALL));
+    }
+
+    match &args.write {
+        Some(path) => {
+            write_file(path, &config)?;
+            println!("wrote {}", path.display());
+        }
+        None => {
+            let table = toml::to_string(&config).map_err(|e| e.to_string())?;
+            println!("\n[controller]\n{}", table);
+        }
+    }
+    if let Some(path) = &args.record {
+        write_file(path, &record)?;
+        println!(
+            "wrote {} (capture fingerprint {})",
+            path.display(),
+            record.fingerprint
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: TuneArgs,
+    }
+
+    /// A step test of `y' = (2u − y) / 5` with 1 s of dead time, at 0.1 s
+    fn step_test() -> Capture {
+        let model = FopdtModel::new(2.0, 5.0, 1.0).unwrap();
+        let dt = 0.1;
+        let mut y = 0.0;
+        let mut outputs = Vec::new();
+        let mut process_values = Vec::new();
+        for k in 0..400 {
+            let u = if k < 20 { 0.0 } else { 10.0 };
+            outputs.push(u);
+            process_values.push(y);
+            let delayed = if k >= 30 { 10.0 } else { 0.0 };
+            let target = model.gain * delayed;
+            y = target + (y - target) * (-dt / model.time_constant).exp();
+        }
+        Capture {
+            outputs,
+            process_values,
+            dt,
+            setpoint: Some(15.0),
+        }
+    }
+
+    #[test]
+    fn test_tune_recovers_model_and_builds_config() {
+        let args = Cli::parse_from(["tune", "--input", "x.csv", "--max-output", "50"]).args;
+        let capture = step_test();
+        let (record, config) = tune(&capture, &args).unwrap();
+
+        let model = record.identification.model;
+        assert!((model.gain - 2.0).abs() < 0.01, "{:?}", model);
+        assert!((model.time_constant - 5.0).abs() < 0.1, "{:?}", model);
+        assert!((model.dead_time - 1.0).abs() < 0.15, "{:?}", model);
+        assert!(record.identification.fit > 0.99);
+        assert!(record.matches(&capture.outputs, &capture.process_values, capture.dt));
+
+        let gains = TuningRule::SimcPi.gains(&model).unwrap();
+        assert_eq!(config.kp(), gains.kp);
+        assert_eq!(config.ki(), gains.ki);
+        assert_eq!(config.setpoint(), 15.0);
+        assert_eq!(config.min_output(), 0.0);
+        assert_eq!(config.max_output(), 50.0);
+    }
+
+    #[test]
+    fn test_example_step_test() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/step_test.csv");
+        let args = Cli::parse_from(["tune", "-i", "x.csv"]).args;
+        let capture = Capture::load(&path, &Columns::from(&args), None).unwrap();
+        assert_eq!(capture.dt, 5.0);
+        let (record, config) = tune(&capture, &args).unwrap();
+        assert!(record.identification.fit > 0.95);
+        assert_eq!(record.identification.model.dead_time, 30.0);
+        assert_eq!((config.min_output(), config.max_output()), (0.0, 50.0));
+    }
+
+    #[test]
+    fn test_written_config_loads_back() {
+        let args = Cli::parse_from(["tune", "-i", "x.csv", "--rule", "simc-pid"]).args;
+        let (_, config) = tune(&step_test(), &args).unwrap();
+        let dir = std::env::temp_dir();
+        for name in ["pidgeon-cli-tune.toml", "pidgeon-cli-tune.json"] {
+            let path = dir.join(name);
+            write_file(&path, &config).unwrap();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let loaded: ControllerConfig = if name.ends_with(".json") {
+                serde_json::from_str(&contents).unwrap()
+            } else {
+                toml::from_str(&contents).unwrap()
+            };
+            assert_eq!(loaded.kd(), config.kd());
+            assert!(loaded.kd() > 0.0);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_written_record_loads_back() {
+        let args = Cli::parse_from(["tune", "-i", "x.csv", "--record", "tune.toml"]).args;
+        let capture = step_test();
+        let (record, config) = tune(&capture, &args).unwrap();
+        let dir = std::env::temp_dir();
+        for name in ["pidgeon-cli-record.toml", "pidgeon-cli-record.json"] {
+            let path = dir.join(name);
+            write_file(&path, &record).unwrap();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let loaded: TuneRecord = if name.ends_with(".json") {
+                serde_json::from_str(&contents).unwrap()
+            } else {
+                toml::from_str(&contents).unwrap()
+            };
+            assert_eq!(loaded.fingerprint, record.fingerprint);
+            assert!(loaded.matches(&capture.outputs, &capture.process_values, capture.dt));
+            let rebuilt = ControllerConfig::builder()
+                .with_tune(&loaded)
+                .with_output_limits(config.min_output(), config.max_output())
+                .build()
+                .unwrap();
+            assert_eq!(rebuilt.kp(), config.kp());
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rejects_flat_recording() {
+        let args = Cli::parse_from(["tune", "-i", "x.csv"]).args;
+        let mut capture = step_test();
+        capture.outputs.iter_mut().for_each(|u| *u = 5.0);
+        assert!(tune(&capture, &args).is_err());
+        assert!(Cli::try_parse_from(["tune", "-i", "x.csv", "--rule", "magic"]).is_err());
+    }
+}
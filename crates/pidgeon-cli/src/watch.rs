@@ -0,0 +1,531 @@
+use crate::publish::Target;
+use crate::subscribe::Subscriber;
+use clap::Args;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Sparkline};
+use ratatui::Frame;
+use serde::Deserialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::process::ExitCode;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// Height of the sparklines, which is also their resolution
+const LEVELS: u64 = 100;
+
+/// `pidgeon-cli watch` flags
+#[derive(Debug, Clone, Args)]
+pub struct WatchArgs {
+    /// Where to read from: iggy://[user:password@]host:port[/stream[/topic]]
+    /// or mqtt://[user:password@]host[:port][/topic/prefix]
+    #[arg(long)]
+    pub from: Target,
+    /// Only show this controller; repeat for several
+    #[arg(long = "controller-id")]
+    pub controller_ids: Vec<String>,
+    /// Samples kept per controller for the charts and window statistics
+    #[arg(long, default_value_t = 300)]
+    pub window: usize,
+    /// Milliseconds between redraws
+    #[arg(long, default_value_t = 100)]
+    pub refresh: u64,
+}
+
+/// The fields of a debug sample the viewer shows
+///
+/// Fields added after schema version 1 default to zero, as they do for
+/// Pidgeoneer.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Sample {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub controller_id: String,
+    pub setpoint: f64,
+    pub process_value: f64,
+    pub error: f64,
+    pub output: f64,
+    pub p_term: f64,
+    pub i_term: f64,
+    pub d_term: f64,
+    #[serde(default)]
+    pub kp: f64,
+    #[serde(default)]
+    pub ki: f64,
+    #[serde(default)]
+    pub kd: f64,
+    #[serde(default)]
+    pub saturated: bool,
+    #[serde(default)]
+    pub mode: String,
+}
+
+/// Statistics over the samples in a [`Trace`]'s window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowStats {
+    pub mean_abs_error: f64,
+    pub max_abs_error: f64,
+    /// Fraction of samples with the output at a limit
+    pub saturated: f64,
+    /// Samples per second, from the timestamps
+    pub rate: f64,
+}
+
+/// The recent samples from one controller
+#[derive(Debug, Clone)]
+pub struct Trace {
+    samples: VecDeque<Sample>,
+    window: usize,
+    /// Samples received since the viewer started, including those that have
+    /// left the window
+    received: u64,
+    last_arrival: Instant,
+}
+
+impl Trace {
+    fn new(window: usize) -> Self {
+        Trace {
+            samples: VecDeque::with_capacity(window),
+            window,
+            received: 0,
+            last_arrival: Instant::now(),
+        }
+    }
+
+    fn push(&mut self, sample: Sample) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.received += 1;
+        self.last_arrival = Instant::now();
+    }
+
+    /// The newest sample
+    pub fn latest(&self) -> Option<&Sample> {
+        self.samples.back()
+    }
+
+    /// One field of every sample in the window, oldest first
+    pub fn series(&self, field: impl Fn(&Sample) -> f64) -> Vec<f64> {
+        self.samples.iter().map(field).collect()
+    }
+
+    /// Statistics over the window, if it holds anything
+    pub fn stats(&self) -> Option<WindowStats> {
+        let count = self.samples.len();
+        let first = self.samples.front()?;
+        let last = self.samples.back()?;
+        let errors = self.samples.iter().map(|s| s.error.abs());
+        let span = last.timestamp.saturating_sub(first.timestamp) as f64 / 1000.0;
+        Some(WindowStats {
+            mean_abs_error: errors.clone().sum::<f64>() / count as f64,
+            max_abs_error: errors.fold(0.0, f64::max),
+            saturated: self.samples.iter().filter(|s| s.saturated).count() as f64 / count as f64,
+            rate: if span > 0.0 {
+                (count - 1) as f64 / span
+            } else {
+                0.0
+            },
+        })
+    }
+}
+
+/// Everything the viewer has received, by controller
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    traces: BTreeMap<String, Trace>,
+    /// Controllers to show; empty shows every one
+    controller_ids: Vec<String>,
+    window: usize,
+    selected: usize,
+    /// Payloads that were not debug samples
+    rejected: u64,
+    /// Last transport error, cleared by the next sample
+    status: Option<String>,
+}
+
+impl Dashboard {
+    pub fn new(controller_ids: Vec<String>, window: usize) -> Self {
+        Dashboard {
+            traces: BTreeMap::new(),
+            controller_ids,
+            window,
+            selected: 0,
+            rejected: 0,
+            status: None,
+        }
+    }
+
+    /// Add a JSON-encoded sample, skipping it if it is from a controller not
+    /// being watched
+    pub fn ingest(&mut self, payload: &[u8]) -> Result<(), String> {
+        let sample: Sample = serde_json::from_slice(payload).map_err(|e| {
+            self.rejected += 1;
+            format!("not a debug sample: {}", e)
+        })?;
+        self.status = None;
+        if !self.controller_ids.is_empty() && !self.controller_ids.contains(&sample.controller_id) {
+            return Ok(());
+        }
+        let window = self.window;
+        self.traces
+            .entry(sample.controller_id.clone())
+            .or_insert_with(|| Trace::new(window))
+            .push(sample);
+        Ok(())
+    }
+
+    /// Controllers seen, in name order
+    pub fn controllers(&self) -> impl Iterator<Item = (&str, &Trace)> {
+        self.traces.iter().map(|(id, trace)| (id.as_str(), trace))
+    }
+
+    /// The controller whose charts are shown
+    pub fn selected(&self) -> Option<(&str, &Trace)> {
+        self.controllers().nth(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.traces.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// Map `values` onto `0..=LEVELS` across their range, returning the levels
+/// and the range; a flat series sits mid-height
+pub fn scale(values: &[f64]) -> (Vec<u64>, f64, f64) {
+    let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let levels = values
+        .iter()
+        .map(|&value| {
+            if high > low {
+                ((value - low) / (high - low) * LEVELS as f64).round() as u64
+            } else {
+                LEVELS / 2
+            }
+        })
+        .collect();
+    (levels, low, high)
+}
+
+/// What the transport thread reports
+enum Feed {
+    Connected,
+    Payloads(Vec<Vec<u8>>),
+    Error(String),
+}
+
+/// Connect, then forward payloads until the viewer hangs up
+fn receive(args: WatchArgs, feed: Sender<Feed>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            let _ = feed.send(Feed::Error(format!("failed to start the runtime: {}", e)));
+            return;
+        }
+    };
+    runtime.block_on(async {
+        let mut subscriber = match Subscriber::connect(&args.from, &args.controller_ids).await {
+            Ok(subscriber) => subscriber,
+            Err(e) => {
+                let _ = feed.send(Feed::Error(e));
+                return;
+            }
+        };
+        if feed.send(Feed::Connected).is_err() {
+            return;
+        }
+        loop {
+            let message = match subscriber.next().await {
+                Ok(payloads) => Feed::Payloads(payloads),
+                Err(e) => Feed::Error(e),
+            };
+            if feed.send(message).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        0 => format!("{} ms", age.as_millis()),
+        secs @ 1..=59 => format!("{} s", secs),
+        secs => format!("{} min", secs / 60),
+    }
+}
+
+fn draw_chart(frame: &mut Frame, area: Rect, trace: &Trace, name: &str, field: fn(&Sample) -> f64) {
+    let width = area.width.saturating_sub(2) as usize;
+    let values = trace.series(field);
+    let shown = &values[values.len().saturating_sub(width)..];
+    let (levels, low, high) = scale(shown);
+    let title = format!(" {} [{:.3} .. {:.3}] ", name, low, high);
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(title))
+            .data(&levels)
+            .max(LEVELS)
+            .style(Style::default().fg(Color::Cyan)),
+        area,
+    );
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard, target: &Target) {
+    let [body, footer] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [list_area, detail] =
+        Layout::horizontal([Constraint::Length(24), Constraint::Min(0)]).areas(body);
+
+    let items: Vec<ListItem> = dashboard
+        .controllers()
+        .map(|(id, trace)| {
+            let marker = match trace.latest() {
+                Some(sample) if sample.saturated => "!",
+                _ => " ",
+            };
+            ListItem::new(format!("{} {}", marker, id))
+        })
+        .collect();
+    let mut state = ListState::default().with_selected(Some(dashboard.selected));
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(Block::bordered().title(" controllers "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+        list_area,
+        &mut state,
+    );
+
+    let status = match &dashboard.status {
+        Some(error) => error.clone(),
+        None => format!(
+            "{} | {} rejected | up/down select, q quit",
+            target, dashboard.rejected
+        ),
+    };
+    frame.render_widget(Paragraph::new(status), footer);
+
+    let Some((id, trace)) = dashboard.selected() else {
+        frame.render_widget(
+            Paragraph::new("waiting for samples...").block(Block::bordered()),
+            detail,
+        );
+        return;
+    };
+    let [summary, charts] =
+        Layout::vertical([Constraint::Length(6), Constraint::Min(0)]).areas(detail);
+    let [process, error, output] = Layout::vertical([Constraint::Ratio(1, 3); 3]).areas(charts);
+
+    let mut lines = Vec::new();
+    if let (Some(sample), Some(stats)) = (trace.latest(), trace.stats()) {
+        lines.push(Line::from(format!(
+            "setpoint {:.3}  process value {:.3}  error {:.3}  output {:.3}{}",
+            sample.setpoint,
+            sample.process_value,
+            sample.error,
+            sample.output,
+            if sample.saturated { " (saturated)" } else { "" }
+        )));
+        lines.push(Line::from(format!(
+            "P {:.3}  I {:.3}  D {:.3}  kp {}  ki {}  kd {}  {}",
+            sample.p_term,
+            sample.i_term,
+            sample.d_term,
+            sample.kp,
+            sample.ki,
+            sample.kd,
+            sample.mode
+        )));
+        lines.push(Line::from(format!(
+            "window: mean |e| {:.4}  max |e| {:.4}  saturated {:.1}%",
+            stats.mean_abs_error,
+            stats.max_abs_error,
+            stats.saturated * 100.0
+        )));
+        lines.push(Line::from(format!(
+            "{:.1} samples/s  {} received  last {} ago",
+            stats.rate,
+            trace.received,
+            format_age(trace.last_arrival.elapsed())
+        )));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(format!(" {} ", id))),
+        summary,
+    );
+    draw_chart(frame, process, trace, "process value", |s| s.process_value);
+    draw_chart(frame, error, trace, "error", |s| s.error);
+    draw_chart(frame, output, trace, "output", |s| s.output);
+}
+
+/// Redraw and handle keys until quit or the transport stops
+fn view(args: &WatchArgs, feed: &Receiver<Feed>) -> Result<(), String> {
+    let mut terminal = ratatui::init();
+    let mut dashboard = Dashboard::new(args.controller_ids.clone(), args.window);
+    let refresh = Duration::from_millis(args.refresh);
+    let result = 'view: loop {
+        loop {
+            match feed.try_recv() {
+                Ok(Feed::Payloads(payloads)) => {
+                    for payload in payloads {
+                        if let Err(e) = dashboard.ingest(&payload) {
+                            dashboard.status = Some(e);
+                        }
+                    }
+                }
+                Ok(Feed::Error(e)) => dashboard.status = Some(e),
+                Ok(Feed::Connected) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    break 'view Err("the connection closed".to_string())
+                }
+            }
+        }
+        if let Err(e) = terminal.draw(|frame| draw(frame, &dashboard, &args.from)) {
+            break Err(format!("failed to draw: {}", e));
+        }
+        match event::poll(refresh).and_then(|ready| ready.then(event::read).transpose()) {
+            Ok(Some(Event::Key(key))) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => dashboard.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => dashboard.select_previous(),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(format!("failed to read the terminal: {}", e)),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+/// Run the `watch` command
+pub fn run(args: WatchArgs) -> Result<ExitCode, String> {
+    if args.window < 2 {
+        return Err(format!(
+            "window {} must hold at least 2 samples",
+            args.window
+        ));
+    }
+    let (sender, feed) = mpsc::channel();
+    let receiver_args = args.clone();
+    std::thread::spawn(move || receive(receiver_args, sender));
+
+    // Fail before taking over the terminal if the source cannot be reached
+    match feed.recv() {
+        Ok(Feed::Connected) => {}
+        Ok(Feed::Error(e)) => return Err(e),
+        Ok(Feed::Payloads(_)) | Err(_) => return Err("the connection closed".into()),
+    }
+    view(&args, &feed)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        args: WatchArgs,
+    }
+
+    fn payload(timestamp: u64, controller_id: &str, error: f64, saturated: bool) -> Vec<u8> {
+        format!(
+            r#"{{"timestamp": {}, "controller_id": "{}", "setpoint": 1.0,
+                "process_value": {}, "error": {}, "output": 0.5, "p_term": 0.5,
+                "i_term": 0.0, "d_term": 0.0, "saturated": {}}}"#,
+            timestamp,
+            controller_id,
+            1.0 - error,
+            error,
+            saturated
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_window_and_stats() {
+        let mut dashboard = Dashboard::new(Vec::new(), 3);
+        for (i, error) in [4.0, -1.0, 2.0, -3.0].into_iter().enumerate() {
+            let saturated = i == 3;
+            dashboard
+                .ingest(&payload(1_000 + 500 * i as u64, "oven", error, saturated))
+                .unwrap();
+        }
+        let (id, trace) = dashboard.selected().unwrap();
+        assert_eq!(id, "oven");
+        assert_eq!(trace.received, 4);
+        assert_eq!(trace.series(|s| s.error), vec![-1.0, 2.0, -3.0]);
+
+        let stats = trace.stats().unwrap();
+        assert_eq!(stats.mean_abs_error, 2.0);
+        assert_eq!(stats.max_abs_error, 3.0);
+        assert!((stats.saturated - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(stats.rate, 2.0);
+    }
+
+    #[test]
+    fn test_filters_and_selects() {
+        let mut dashboard = Dashboard::new(vec!["fan".into(), "oven".into()], 10);
+        for id in ["pump", "oven", "fan"] {
+            dashboard.ingest(&payload(1_000, id, 0.0, false)).unwrap();
+        }
+        let ids: Vec<&str> = dashboard.controllers().map(|(id, _)| id).collect();
+        assert_eq!(ids, ["fan", "oven"]);
+
+        dashboard.select_next();
+        dashboard.select_next();
+        assert_eq!(dashboard.selected().unwrap().0, "oven");
+        dashboard.select_previous();
+        dashboard.select_previous();
+        assert_eq!(dashboard.selected().unwrap().0, "fan");
+
+        assert!(dashboard.ingest(b"{\"timestamp\": 1}").is_err());
+        assert!(dashboard.ingest(b"not json").is_err());
+        assert_eq!(dashboard.rejected, 2);
+    }
+
+    #[test]
+    fn test_scale() {
+        let (levels, low, high) = scale(&[2.0, 4.0, 3.0]);
+        assert_eq!(levels, vec![0, LEVELS, LEVELS / 2]);
+        assert_eq!((low, high), (2.0, 4.0));
+        assert_eq!(scale(&[7.0, 7.0]).0, vec![LEVELS / 2; 2]);
+        assert!(scale(&[]).0.is_empty());
+    }
+
+    #[test]
+    fn test_args() {
+        let args = Cli::parse_from(["watch", "--from", "mqtt://localhost"]).args;
+        assert_eq!(args.window, 300);
+        assert!(args.controller_ids.is_empty());
+        let args = Cli::parse_from([
+            "watch",
+            "--from",
+            "iggy://h:1",
+            "--controller-id",
+            "oven",
+            "--controller-id",
+            "fan",
+        ])
+        .args;
+        assert_eq!(args.controller_ids, ["oven", "fan"]);
+        assert!(Cli::try_parse_from(["watch"]).is_err());
+    }
+}
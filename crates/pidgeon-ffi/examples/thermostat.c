@@ -0,0 +1,50 @@
+/*
+ * Drives a simulated room heater with pidgeon from C.
+ *
+ *   cargo build -p pidgeon-ffi --release
+ *   cc examples/thermostat.c -Iinclude -L../../target/release -lpidgeon_ffi -o thermostat
+ *   LD_LIBRARY_PATH=../../target/release ./thermostat
+ */
+#include <stdio.h>
+
+#include "pidgeon.h"
+
+int main(void) {
+    PidgeonConfig config = pidgeon_config_default();
+    config.kp = 2.0;
+    config.ki = 0.1;
+    config.kd = 1.0;
+    config.setpoint = 21.0;
+    config.min_output = 0.0;
+    config.max_output = 100.0;
+
+    PidgeonController *controller = pidgeon_controller_new(&config);
+    if (controller == NULL) {
+        fprintf(stderr, "invalid configuration: %s\n", pidgeon_last_error());
+        return 1;
+    }
+
+    /* First-order room: heats with the output, leaks toward 15 °C outside */
+    const double dt = 1.0;
+    double temperature = 15.0;
+    for (int second = 0; second < 600; second++) {
+        double power;
+        if (pidgeon_controller_compute(controller, temperature, dt, &power) != PIDGEON_STATUS_OK) {
+            fprintf(stderr, "compute failed: %s\n", pidgeon_last_error());
+            pidgeon_controller_free(controller);
+            return 1;
+        }
+        temperature += dt * (0.01 * power - 0.02 * (temperature - 15.0));
+        if (second % 60 == 0) {
+            printf("t=%3ds  temperature=%5.2f  power=%5.1f%%\n", second, temperature, power);
+        }
+    }
+
+    PidgeonStatistics statistics;
+    pidgeon_controller_statistics(controller, &statistics);
+    printf("pidgeon %s: average error %.2f, max error %.2f\n", pidgeon_version(),
+           statistics.average_error, statistics.max_overshoot);
+
+    pidgeon_controller_free(controller);
+    return 0;
+}
@@ -0,0 +1,202 @@
+/* Pidgeon: A robust PID controller library written in Rust. MIT OR Apache-2.0. */
+
+#ifndef PIDGEON_H
+#define PIDGEON_H
+
+/* Generated by cbindgen from crates/pidgeon-ffi. Do not edit by hand. */
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/**
+ * Outcome of a fallible call.
+ */
+typedef enum PidgeonStatus {
+  /**
+   * The call succeeded.
+   */
+  PIDGEON_STATUS_OK = 0,
+  /**
+   * A required pointer argument was `NULL`.
+   */
+  PIDGEON_STATUS_NULL_POINTER = 1,
+  /**
+   * A parameter was out of range; see [`pidgeon_last_error`].
+   */
+  PIDGEON_STATUS_INVALID_PARAMETER = 2,
+  /**
+   * A thread panicked while holding the controller's lock.
+   */
+  PIDGEON_STATUS_POISONED = 3,
+} PidgeonStatus;
+
+/**
+ * Opaque handle to a controller.
+ */
+typedef struct PidgeonController PidgeonController;
+
+/**
+ * Controller parameters, mirroring pidgeon's `ControllerConfigBuilder`.
+ *
+ * Start from [`pidgeon_config_default`] so fields added later keep sensible
+ * values.
+ */
+typedef struct PidgeonConfig {
+  /**
+   * Proportional gain.
+   */
+  double kp;
+  /**
+   * Integral gain, per second.
+   */
+  double ki;
+  /**
+   * Derivative gain, in seconds.
+   */
+  double kd;
+  /**
+   * Target process value.
+   */
+  double setpoint;
+  /**
+   * Lowest output; must be finite and below `max_output`.
+   */
+  double min_output;
+  /**
+   * Highest output; must be finite and above `min_output`.
+   */
+  double max_output;
+  /**
+   * Errors smaller than this are treated as zero.
+   */
+  double deadband;
+  /**
+   * Stop integrating while the output is saturated.
+   */
+  bool anti_windup;
+} PidgeonConfig;
+
+/**
+ * Performance since the controller was created or last reset, mirroring
+ * pidgeon's `ControllerStatistics`.
+ */
+typedef struct PidgeonStatistics {
+  /**
+   * Time-weighted mean absolute error: each step counts for its `dt`.
+   */
+  double average_error;
+  /**
+   * Largest absolute error.
+   */
+  double max_overshoot;
+  /**
+   * Seconds until the error settled, or elapsed time if it has not.
+   */
+  double settling_time;
+  /**
+   * Seconds until the error first settled; `NAN` if it never has.
+   */
+  double rise_time;
+} PidgeonStatistics;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns the library version, such as `"0.1.0"`. The string is static.
+ */
+const char *pidgeon_version(void);
+
+/**
+ * Describes the last failure on the calling thread, or returns `""`.
+ *
+ * The string stays valid until the next failing call on the same thread.
+ */
+const char *pidgeon_last_error(void);
+
+/**
+ * Returns a configuration with unit proportional gain, no integral or
+ * derivative action, a zero setpoint, anti-windup on, and output limits of
+ * ±`DBL_MAX`.
+ */
+struct PidgeonConfig pidgeon_config_default(void);
+
+/**
+ * Creates a controller, or returns `NULL` if `config` is `NULL` or invalid.
+ *
+ * Release it with [`pidgeon_controller_free`].
+ *
+ * # Safety
+ *
+ * `config` must be `NULL` or point to a valid [`PidgeonConfig`].
+ */
+struct PidgeonController *pidgeon_controller_new(const struct PidgeonConfig *config);
+
+/**
+ * Destroys a controller. Does nothing if `controller` is `NULL`.
+ *
+ * # Safety
+ *
+ * `controller` must be `NULL` or a handle from [`pidgeon_controller_new`]
+ * that has not been freed, and no other thread may be using it.
+ */
+void pidgeon_controller_free(struct PidgeonController *controller);
+
+/**
+ * Replaces the controller's configuration, keeping its state and
+ * statistics. An invalid `config` leaves the old one in place.
+ *
+ * # Safety
+ *
+ * `controller` must be `NULL` or a live handle from
+ * [`pidgeon_controller_new`], and `config` must be `NULL` or point to a
+ * valid [`PidgeonConfig`].
+ */
+enum PidgeonStatus pidgeon_controller_configure(struct PidgeonController *controller,
+                                                const struct PidgeonConfig *config);
+
+/**
+ * Runs one control step and writes the clamped output to `output`.
+ *
+ * `dt` is the time since the previous step, in seconds, and must be
+ * positive. On failure `output` is left untouched.
+ *
+ * # Safety
+ *
+ * `controller` must be `NULL` or a live handle from
+ * [`pidgeon_controller_new`], and `output` must be `NULL` or point to
+ * writable memory for a `double`.
+ */
+enum PidgeonStatus pidgeon_controller_compute(struct PidgeonController *controller,
+                                              double process_value,
+                                              double dt,
+                                              double *output);
+
+/**
+ * Clears the controller's state and statistics, keeping its configuration.
+ *
+ * # Safety
+ *
+ * `controller` must be `NULL` or a live handle from
+ * [`pidgeon_controller_new`].
+ */
+enum PidgeonStatus pidgeon_controller_reset(struct PidgeonController *controller);
+
+/**
+ * Writes the controller's performance statistics to `statistics`.
+ *
+ * # Safety
+ *
+ * `controller` must be `NULL` or a live handle from
+ * [`pidgeon_controller_new`], and `statistics` must be `NULL` or point to
+ * writable memory for a [`PidgeonStatistics`].
+ */
+enum PidgeonStatus pidgeon_controller_statistics(const struct PidgeonController *controller,
+                                                 struct PidgeonStatistics *statistics);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PIDGEON_H */
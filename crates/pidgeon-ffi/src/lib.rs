@@ -0,0 +1,416 @@
+// Pidgeon: A robust PID controller library written in Rust
+// Copyright (c) 2025 Security Union LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! C ABI for the pidgeon PID controller.
+//!
+//! Builds `libpidgeon_ffi` as a shared and a static library; the matching
+//! header is `include/pidgeon.h`, generated with cbindgen. A controller is an
+//! opaque [`PidgeonController`] handle wrapping a [`ThreadSafePidController`],
+//! so one handle may be used from several threads.
+//!
+//! Every fallible function returns a [`PidgeonStatus`]. On failure,
+//! [`pidgeon_last_error`] describes what went wrong on the calling thread.
+//!
+//! ```c
+//! PidgeonConfig config = pidgeon_config_default();
+//! config.kp = 2.0;
+//! config.ki = 0.5;
+//! config.setpoint = 21.0;
+//! config.min_output = 0.0;
+//! config.max_output = 100.0;
+//!
+//! PidgeonController *controller = pidgeon_controller_new(&config);
+//! if (controller == NULL) {
+//!     fprintf(stderr, "%s\n", pidgeon_last_error());
+//!     return 1;
+//! }
+//!
+//! double output;
+//! if (pidgeon_controller_compute(controller, read_sensor(), 0.1, &output) == PIDGEON_STATUS_OK) {
+//!     set_heater(output);
+//! }
+//! pidgeon_controller_free(controller);
+//! ```
+
+use pidgeon::{AntiWindupMode, ControllerConfig, PidError, ThreadSafePidController};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+static VERSION: &CStr =
+    match CStr::from_bytes_with_nul(concat!(env!("CARGO_PKG_VERSION"), "\0").as_bytes()) {
+        Ok(version) => version,
+        Err(_) => panic!("version contains a NUL byte"),
+    };
+
+/// Outcome of a fallible call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidgeonStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was `NULL`.
+    NullPointer = 1,
+    /// A parameter was out of range; see [`pidgeon_last_error`].
+    InvalidParameter = 2,
+    /// A thread panicked while holding the controller's lock.
+    Poisoned = 3,
+}
+
+/// Controller parameters, mirroring pidgeon's `ControllerConfigBuilder`.
+///
+/// Start from [`pidgeon_config_default`] so fields added later keep sensible
+/// values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidgeonConfig {
+    /// Proportional gain.
+    pub kp: f64,
+    /// Integral gain, per second.
+    pub ki: f64,
+    /// Derivative gain, in seconds.
+    pub kd: f64,
+    /// Target process value.
+    pub setpoint: f64,
+    /// Lowest output; must be finite and below `max_output`.
+    pub min_output: f64,
+    /// Highest output; must be finite and above `min_output`.
+    pub max_output: f64,
+    /// Errors smaller than this are treated as zero.
+    pub deadband: f64,
+    /// Stop integrating while the output is saturated.
+    pub anti_windup: bool,
+}
+
+/// Performance since the controller was created or last reset, mirroring
+/// pidgeon's `ControllerStatistics`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidgeonStatistics {
+    /// Time-weighted mean absolute error: each step counts for its `dt`.
+    pub average_error: f64,
+    /// Largest absolute error.
+    pub max_overshoot: f64,
+    /// Seconds until the error settled, or elapsed time if it has not.
+    pub settling_time: f64,
+    /// Seconds until the error first settled; `NAN` if it never has.
+    pub rise_time: f64,
+}
+
+/// Opaque handle to a controller.
+pub struct PidgeonController(ThreadSafePidController);
+
+fn set_last_error(message: impl Into<Vec<u8>>) {
+    let message =
+        CString::new(message).unwrap_or_else(|_| CString::from(c"message contained a NUL byte"));
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+fn fail(error: PidError) -> PidgeonStatus {
+    set_last_error(error.to_string());
+    match error {
+        PidError::InvalidParameter(_) => PidgeonStatus::InvalidParameter,
+        PidError::MutexPoisoned => PidgeonStatus::Poisoned,
+    }
+}
+
+fn null_pointer(argument: &str) -> PidgeonStatus {
+    set_last_error(format!("{} must not be NULL", argument));
+    PidgeonStatus::NullPointer
+}
+
+fn build_config(config: &PidgeonConfig) -> Result<ControllerConfig, PidError> {
+    ControllerConfig::builder()
+        .with_kp(config.kp)
+        .with_ki(config.ki)
+        .with_kd(config.kd)
+        .with_setpoint(config.setpoint)
+        .with_output_limits(config.min_output, config.max_output)
+        .with_deadband(config.deadband)
+        .with_anti_windup_mode(if config.anti_windup {
+            AntiWindupMode::Conditional
+        } else {
+            AntiWindupMode::None
+        })
+        .build()
+}
+
+/// Returns the library version, such as `"0.1.0"`. The string is static.
+#[no_mangle]
+pub extern "C" fn pidgeon_version() -> *const c_char {
+    VERSION.as_ptr()
+}
+
+/// Describes the last failure on the calling thread, or returns `""`.
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn pidgeon_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Returns a configuration with unit proportional gain, no integral or
+/// derivative action, a zero setpoint, anti-windup on, and output limits of
+/// ±`DBL_MAX`.
+#[no_mangle]
+pub extern "C" fn pidgeon_config_default() -> PidgeonConfig {
+    PidgeonConfig {
+        kp: 1.0,
+        ki: 0.0,
+        kd: 0.0,
+        setpoint: 0.0,
+        min_output: f64::MIN,
+        max_output: f64::MAX,
+        deadband: 0.0,
+        anti_windup: true,
+    }
+}
+
+/// Creates a controller, or returns `NULL` if `config` is `NULL` or invalid.
+///
+/// Release it with [`pidgeon_controller_free`].
+///
+/// # Safety
+///
+/// `config` must be `NULL` or point to a valid [`PidgeonConfig`].
+#[no_mangle]
+pub unsafe extern "C" fn pidgeon_controller_new(
+    config: *const PidgeonConfig,
+) -> *mut PidgeonController {
+    let Some(config) = config.as_ref() else {
+        null_pointer("config");
+        return std::ptr::null_mut();
+    };
+    match build_config(config) {
+        Ok(config) => {
+            let controller = PidgeonController(ThreadSafePidController::new(config));
+            Box::into_raw(Box::new(controller))
+        }
+        Err(error) => {
+            fail(error);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Destroys a controller. Does nothing if `controller` is `NULL`.
+///
+/// # Safety
+///
+/// `controller` must be `NULL` or a handle from [`pidgeon_controller_new`]
+/// that has not been freed, and no other thread may be using it.
+#[no_mangle]
+pub unsafe extern "C" fn pidgeon_controller_free(controller: *mut PidgeonController) {
+    if !controller.is_null() {
+        drop(Box::from_raw(controller));
+    }
+}
+
+/// Replaces the controller's configuration, keeping its state and
+/// statistics. An invalid `config` leaves the old one in place.
+///
+/// # Safety
+///
+/// `controller` must be `NULL` or a live handle from
+/// [`pidgeon_controller_new`], and `config` must be `NULL` or point to a
+/// valid [`PidgeonConfig`].
+#[no_mangle]
+pub unsafe extern "C" fn pidgeon_controller_configure(
+    controller: *mut PidgeonController,
+    config: *const PidgeonConfig,
+) -> PidgeonStatus {
+    let Some(controller) = controller.as_ref() else {
+        return null_pointer("controller");
+    };
+    let Some(config) = config.as_ref() else {
+        return null_pointer("config");
+    };
+    match build_config(config).and_then(|config| controller.0.update_config(config)) {
+        Ok(()) => PidgeonStatus::Ok,
+        Err(error) => fail(error),
+    }
+}
+
+/// Runs one control step and writes the clamped output to `output`.
+///
+/// `dt` is the time since the previous step, in seconds, and must be
+/// positive. On failure `output` is left untouched.
+///
+/// # Safety
+///
+/// `controller` must be `NULL` or a live handle from
+/// [`pidgeon_controller_new`], and `output` must be `NULL` or point to
+/// writable memory for a `double`.
+#[no_mangle]
+pub unsafe extern "C" fn pidgeon_controller_compute(
+    controller: *mut PidgeonController,
+    process_value: f64,
+    dt: f64,
+    output: *mut f64,
+) -> PidgeonStatus {
+    let Some(controller) = controller.as_ref() else {
+        return null_pointer("controller");
+    };
+    if output.is_null() {
+        return null_pointer("output");
+    }
+    match controller.0.compute(process_value, dt) {
+        Ok(value) => {
+            output.write(value);
+            PidgeonStatus::Ok
+        }
+        Err(error) => fail(error),
+    }
+}
+
+/// Clears the controller's state and statistics, keeping its configuration.
+///
+/// # Safety
+///
+/// `controller` must be `NULL` or a live handle from
+/// [`pidgeon_controller_new`].
+#[no_mangle]
+pub unsafe extern "C" fn pidgeon_controller_reset(
+    controller: *mut PidgeonController,
+) -> PidgeonStatus {
+    let Some(controller) = controller.as_ref() else {
+        return null_pointer("controller");
+    };
+    match controller.0.reset() {
+        Ok(()) => PidgeonStatus::Ok,
+        Err(error) => fail(error),
+    }
+}
+
+/// Writes the controller's performance statistics to `statistics`.
+///
+/// # Safety
+///
+/// `controller` must be `NULL` or a live handle from
+/// [`pidgeon_controller_new`], and `statistics` must be `NULL` or point to
+/// writable memory for a [`PidgeonStatistics`].
+#[no_mangle]
+pub unsafe extern "C" fn pidgeon_controller_statistics(
+    controller: *const PidgeonController,
+    statistics: *mut PidgeonStatistics,
+) -> PidgeonStatus {
+    let Some(controller) = controller.as_ref() else {
+        return null_pointer("controller");
+    };
+    if statistics.is_null() {
+        return null_pointer("statistics");
+    }
+    match controller.0.get_statistics() {
+        Ok(stats) => {
+            statistics.write(PidgeonStatistics {
+                average_error: stats.average_error,
+                max_overshoot: stats.max_overshoot,
+                settling_time: stats.settling_time,
+                rise_time: stats.rise_time,
+            });
+            PidgeonStatus::Ok
+        }
+        Err(error) => fail(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(pidgeon_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_controller_lifecycle() {
+        let mut config = pidgeon_config_default();
+        config.kp = 2.0;
+        config.ki = 0.5;
+        config.setpoint = 10.0;
+        config.min_output = -100.0;
+        config.max_output = 100.0;
+
+        unsafe {
+            let controller = pidgeon_controller_new(&config);
+            assert!(!controller.is_null());
+
+            let mut output = 0.0;
+            let status = pidgeon_controller_compute(controller, 4.0, 0.1, &mut output);
+            assert_eq!(status, PidgeonStatus::Ok);
+            assert!(output > 0.0);
+
+            let mut statistics = PidgeonStatistics {
+                average_error: 0.0,
+                max_overshoot: 0.0,
+                settling_time: 0.0,
+                rise_time: 0.0,
+            };
+            let status = pidgeon_controller_statistics(controller, &mut statistics);
+            assert_eq!(status, PidgeonStatus::Ok);
+            // Time-weighted: the error integral over elapsed loop time
+            assert!((statistics.average_error - 6.0).abs() < 1e-9);
+            assert!(statistics.rise_time.is_nan());
+
+            config.kp = 0.0;
+            config.ki = 0.0;
+            let status = pidgeon_controller_configure(controller, &config);
+            assert_eq!(status, PidgeonStatus::Ok);
+            assert_eq!(pidgeon_controller_reset(controller), PidgeonStatus::Ok);
+            pidgeon_controller_compute(controller, 4.0, 0.1, &mut output);
+            assert_eq!(output, 0.0);
+
+            pidgeon_controller_free(controller);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let mut config = pidgeon_config_default();
+        config.min_output = 1.0;
+        config.max_output = -1.0;
+
+        unsafe {
+            assert!(pidgeon_controller_new(&config).is_null());
+            assert!(last_error().contains("min_output must be less than max_output"));
+
+            let controller = pidgeon_controller_new(&pidgeon_config_default());
+            let mut output = 0.0;
+            let status = pidgeon_controller_compute(controller, 1.0, 0.0, &mut output);
+            assert_eq!(status, PidgeonStatus::InvalidParameter);
+
+            // A rejected configuration keeps the old one
+            let status = pidgeon_controller_configure(controller, &config);
+            assert_eq!(status, PidgeonStatus::InvalidParameter);
+            let status = pidgeon_controller_compute(controller, 1.0, 0.1, &mut output);
+            assert_eq!(status, PidgeonStatus::Ok);
+            assert_eq!(output, -1.0);
+
+            let status = pidgeon_controller_compute(controller, 1.0, 0.1, ptr::null_mut());
+            assert_eq!(status, PidgeonStatus::NullPointer);
+            assert_eq!(last_error(), "output must not be NULL");
+            assert!(pidgeon_controller_new(ptr::null()).is_null());
+
+            pidgeon_controller_free(controller);
+            pidgeon_controller_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_version_matches_crate() {
+        let version = unsafe { CStr::from_ptr(pidgeon_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -0,0 +1,5 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Requires `protoc` on the PATH (or in $PROTOC)
+    tonic_build::compile_protos("proto/pidgeon.proto")?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// Pidgeon: A robust PID controller library written in Rust
+// Copyright (c) 2025 Security Union LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! gRPC control and monitoring service for pidgeon PID controllers.
+//!
+//! [`PidgeonService`] serves the controllers in a
+//! [`ControllerRegistry`](pidgeon::ControllerRegistry) over the
+//! `pidgeon.v1.Pidgeon` service defined in `proto/pidgeon.proto`, so SCADA
+//! systems and remote dashboards in any language can read state, stream
+//! telemetry, change gains and setpoints, and autotune from step-test data
+//! without custom glue. The control loops keep running in the application;
+//! the service only holds handles to them.
+//!
+//! ```no_run
+//! use pidgeon::{ControllerConfig, ControllerRegistry};
+//! use pidgeon_grpc::PidgeonService;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ControllerConfig::builder()
+//!     .with_kp(2.0)
+//!     .with_ki(0.1)
+//!     .with_output_limits(0.0, 100.0)
+//!     .build()?;
+//! let mut registry = ControllerRegistry::new();
+//! let boiler = registry.register_config("boiler", config);
+//!
+//! // Run the loop elsewhere with `boiler.compute(..)`
+//! # let _ = boiler;
+//! PidgeonService::from(registry)
+//!     .serve("0.0.0.0:50051".parse()?)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The service has no authentication of its own. Wrap
+//! [`PidgeonService::into_server`] with a tonic interceptor, or terminate TLS
+//! and check credentials in a proxy, before exposing it beyond a trusted
+//! network.
+
+mod service;
+
+/// Types and stubs generated from `proto/pidgeon.proto`.
+pub mod proto {
+    tonic::include_proto!("pidgeon.v1");
+}
+
+pub use service::PidgeonService;
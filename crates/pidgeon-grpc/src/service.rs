@@ -0,0 +1,319 @@
+// tonic hands `Status` back by value from every handler, large as it is
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use pidgeon::{ControllerRegistry, FopdtModel, PidError, ThreadSafePidController, TuningRule};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+
+use crate::proto::pidgeon_server::{Pidgeon, PidgeonServer};
+use crate::proto::{
+    AutotuneRequest, AutotuneResponse, ControllerState, Gains, GetStateRequest,
+    ListControllersRequest, ListControllersResponse, ProcessModel, SetGainsRequest,
+    SetSetpointRequest, Statistics, StreamTelemetryRequest, Telemetry,
+};
+
+/// Telemetry interval used when a request asks for none.
+const DEFAULT_TELEMETRY_INTERVAL: Duration = Duration::from_millis(100);
+/// Shortest telemetry interval a client may ask for.
+const MIN_TELEMETRY_INTERVAL: Duration = Duration::from_millis(10);
+/// Samples buffered per telemetry stream before a slow client holds it up.
+const TELEMETRY_BUFFER: usize = 64;
+/// Tuning rule used when an autotune request names none.
+const DEFAULT_TUNING_RULE: TuningRule = TuningRule::SimcPi;
+
+/// Serves a [`ControllerRegistry`] over the `pidgeon.v1.Pidgeon` gRPC service.
+///
+/// The registry is shared, so controllers registered or removed through
+/// [`registry`](Self::registry) after the server starts are visible to
+/// clients straight away. Cloning the service shares the same registry.
+#[derive(Clone, Default)]
+pub struct PidgeonService {
+    registry: Arc<RwLock<ControllerRegistry>>,
+}
+
+impl PidgeonService {
+    /// Creates a service over a shared registry.
+    pub fn new(registry: Arc<RwLock<ControllerRegistry>>) -> Self {
+        PidgeonService { registry }
+    }
+
+    /// The registry being served.
+    pub fn registry(&self) -> &Arc<RwLock<ControllerRegistry>> {
+        &self.registry
+    }
+
+    /// Wraps the service for use with [`tonic::transport::Server`], for
+    /// adding interceptors, TLS, or other services alongside it.
+    pub fn into_server(self) -> PidgeonServer<Self> {
+        PidgeonServer::new(self)
+    }
+
+    /// Serves on `address` until the server fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound or the server stops
+    /// with a transport error.
+    pub async fn serve(self, address: SocketAddr) -> Result<(), tonic::transport::Error> {
+        tonic::transport::Server::builder()
+            .add_service(self.into_server())
+            .serve(address)
+            .await
+    }
+
+    fn controller(&self, id: &str) -> Result<ThreadSafePidController, Status> {
+        self.registry
+            .read()
+            .map_err(|_| Status::internal("controller registry lock poisoned"))?
+            .get(id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("no controller named '{}'", id)))
+    }
+
+    /// One telemetry sample per controller in `ids`, or per registered
+    /// controller if `ids` is empty, so controllers added after a stream
+    /// starts are picked up.
+    fn sample(&self, ids: &[String], timestamp_ms: u64) -> Vec<Result<Telemetry, Status>> {
+        let controllers: Vec<Result<(String, ThreadSafePidController), Status>> = if ids.is_empty()
+        {
+            match self.registry.read() {
+                Ok(registry) => registry
+                    .iter()
+                    .map(|(id, controller)| Ok((id.to_string(), controller.clone())))
+                    .collect(),
+                Err(_) => vec![Err(Status::internal("controller registry lock poisoned"))],
+            }
+        } else {
+            ids.iter()
+                .map(|id| {
+                    self.controller(id)
+                        .map(|controller| (id.clone(), controller))
+                })
+                .collect()
+        };
+        controllers
+            .into_iter()
+            .map(|entry| -> Result<Telemetry, Status> {
+                let (id, controller) = entry?;
+                Ok(Telemetry {
+                    timestamp_ms,
+                    state: Some(controller_state(&id, &controller)?),
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<ControllerRegistry> for PidgeonService {
+    fn from(registry: ControllerRegistry) -> Self {
+        PidgeonService::new(Arc::new(RwLock::new(registry)))
+    }
+}
+
+fn status(error: PidError) -> Status {
+    match error {
+        PidError::InvalidParameter(message) => Status::invalid_argument(message),
+        PidError::MutexPoisoned => Status::internal(error.to_string()),
+    }
+}
+
+fn controller_state(
+    id: &str,
+    controller: &ThreadSafePidController,
+) -> Result<ControllerState, Status> {
+    let config = controller.get_config().map_err(status)?;
+    let state = controller.get_state().map_err(status)?;
+    let statistics = controller.get_statistics().map_err(status)?;
+    Ok(ControllerState {
+        controller_id: id.to_string(),
+        setpoint: config.setpoint(),
+        process_value: state.prev_measurement,
+        error: state.prev_error,
+        output: state.last_output,
+        gains: Some(Gains {
+            kp: config.kp(),
+            ki: config.ki(),
+            kd: config.kd(),
+        }),
+        min_output: config.min_output(),
+        max_output: config.max_output(),
+        deadband: config.deadband(),
+        statistics: Some(Statistics {
+            average_error: statistics.average_error,
+            max_overshoot: statistics.max_overshoot,
+            settling_time: statistics.settling_time,
+            rise_time: statistics.rise_time,
+        }),
+    })
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[tonic::async_trait]
+impl Pidgeon for PidgeonService {
+    async fn list_controllers(
+        &self,
+        _request: Request<ListControllersRequest>,
+    ) -> Result<Response<ListControllersResponse>, Status> {
+        let registry = self
+            .registry
+            .read()
+            .map_err(|_| Status::internal("controller registry lock poisoned"))?;
+        Ok(Response::new(ListControllersResponse {
+            controller_ids: registry.names().map(str::to_string).collect(),
+        }))
+    }
+
+    async fn get_state(
+        &self,
+        request: Request<GetStateRequest>,
+    ) -> Result<Response<ControllerState>, Status> {
+        let id = request.into_inner().controller_id;
+        let controller = self.controller(&id)?;
+        controller_state(&id, &controller).map(Response::new)
+    }
+
+    type StreamTelemetryStream = Pin<Box<dyn Stream<Item = Result<Telemetry, Status>> + Send>>;
+
+    async fn stream_telemetry(
+        &self,
+        request: Request<StreamTelemetryRequest>,
+    ) -> Result<Response<Self::StreamTelemetryStream>, Status> {
+        let request = request.into_inner();
+        let interval = match request.interval_ms {
+            0 => DEFAULT_TELEMETRY_INTERVAL,
+            ms => Duration::from_millis(u64::from(ms)),
+        };
+        if interval < MIN_TELEMETRY_INTERVAL {
+            return Err(Status::invalid_argument(format!(
+                "interval_ms must be at least {}",
+                MIN_TELEMETRY_INTERVAL.as_millis()
+            )));
+        }
+        // Check the names up front so typos fail the call instead of
+        // producing an empty stream
+        for id in &request.controller_ids {
+            self.controller(id)?;
+        }
+
+        let (sender, receiver) = mpsc::channel(TELEMETRY_BUFFER);
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                let timestamp_ms = unix_millis();
+                for sample in service.sample(&request.controller_ids, timestamp_ms) {
+                    // A failed sample, such as a removed controller, ends the stream
+                    let failed = sample.is_err();
+                    if sender.send(sample).await.is_err() || failed {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+
+    async fn set_gains(
+        &self,
+        request: Request<SetGainsRequest>,
+    ) -> Result<Response<ControllerState>, Status> {
+        let request = request.into_inner();
+        let controller = self.controller(&request.controller_id)?;
+        let gains = [request.kp, request.ki, request.kd];
+        // Reject before changing anything so a bad Kd does not leave a new Kp behind
+        if gains.iter().flatten().any(|gain| !gain.is_finite()) {
+            return Err(Status::invalid_argument("gains must be finite"));
+        }
+        if let Some(kp) = request.kp {
+            controller.set_kp(kp).map_err(status)?;
+        }
+        if let Some(ki) = request.ki {
+            controller.set_ki(ki).map_err(status)?;
+        }
+        if let Some(kd) = request.kd {
+            controller.set_kd(kd).map_err(status)?;
+        }
+        controller_state(&request.controller_id, &controller).map(Response::new)
+    }
+
+    async fn set_setpoint(
+        &self,
+        request: Request<SetSetpointRequest>,
+    ) -> Result<Response<ControllerState>, Status> {
+        let request = request.into_inner();
+        let controller = self.controller(&request.controller_id)?;
+        controller.set_setpoint(request.setpoint).map_err(status)?;
+        controller_state(&request.controller_id, &controller).map(Response::new)
+    }
+
+    async fn autotune(
+        &self,
+        request: Request<AutotuneRequest>,
+    ) -> Result<Response<AutotuneResponse>, Status> {
+        let request = request.into_inner();
+        let controller = self.controller(&request.controller_id)?;
+        let rule = match request.rule.as_str() {
+            "" => DEFAULT_TUNING_RULE,
+            name => TuningRule::from_str(name)
+                .map_err(|_| Status::invalid_argument(format!("unknown tuning rule '{}'", name)))?,
+        };
+
+        // The fit tries every dead time, which is too slow for the executor
+        let AutotuneRequest {
+            outputs,
+            process_values,
+            dt,
+            ..
+        } = request;
+        let (identification, gains) = tokio::task::spawn_blocking(move || {
+            let identification = FopdtModel::identify(&outputs, &process_values, dt)?;
+            let gains = rule.gains(&identification.model)?;
+            Ok::<_, PidError>((identification, gains))
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(status)?;
+
+        if request.apply {
+            controller.set_kp(gains.kp).map_err(status)?;
+            controller.set_ki(gains.ki).map_err(status)?;
+            controller.set_kd(gains.kd).map_err(status)?;
+        }
+
+        let FopdtModel {
+            gain,
+            time_constant,
+            dead_time,
+        } = identification.model;
+        Ok(Response::new(AutotuneResponse {
+            model: Some(ProcessModel {
+                gain,
+                time_constant,
+                dead_time,
+            }),
+            fit: identification.fit,
+            gains: Some(Gains {
+                kp: gains.kp,
+                ki: gains.ki,
+                kd: gains.kd,
+            }),
+            applied: request.apply,
+        }))
+    }
+}
@@ -0,0 +1,195 @@
+use crate::config::BridgeConfig;
+use log::*;
+use pidgeon::PidController;
+use rumqttc::{AsyncClient, Event, MqttOptions, Outgoing, Packet, QoS};
+use std::time::{Duration, Instant};
+
+/// Requests buffered between the client handle and the event loop
+const REQUEST_CAPACITY: usize = 16;
+/// Interval between keepalive pings
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+/// Wait before reconnecting after a connection error
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// How often the sensor timeout is checked
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+/// Time allowed to deliver the failsafe output when shutting down
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// One control loop between MQTT topics
+struct Bridge {
+    config: BridgeConfig,
+    client: AsyncClient,
+    controller: PidController,
+    /// When the previous process value arrived; `None` until the first one,
+    /// and again after a timeout
+    last_reading: Option<Instant>,
+}
+
+impl Bridge {
+    fn on_connect(&self) {
+        let mut topics = vec![(&self.config.input.mapping.topic, QoS::AtMostOnce)];
+        if let Some(setpoint) = &self.config.setpoint {
+            topics.push((&setpoint.topic, QoS::AtLeastOnce));
+        }
+        for (topic, qos) in topics {
+            if let Err(e) = self.client.try_subscribe(topic, qos) {
+                error!("Failed to subscribe to {}: {}", topic, e);
+            }
+        }
+    }
+
+    fn on_publish(&mut self, topic: &str, payload: &[u8]) {
+        if topic == self.config.input.mapping.topic {
+            match self.config.input.mapping.extract(payload) {
+                Ok(process_value) => self.on_reading(process_value),
+                Err(e) => warn!("Ignoring reading on {}: {}", topic, e),
+            }
+        } else if let Some(setpoint) = &self.config.setpoint {
+            if topic != setpoint.topic {
+                return;
+            }
+            match setpoint.extract(payload).and_then(|value| {
+                self.controller
+                    .set_setpoint(value)
+                    .map_err(|e| e.to_string())
+            }) {
+                Ok(()) => info!("🎯 Setpoint is now {}", self.controller.setpoint()),
+                Err(e) => warn!("Ignoring setpoint on {}: {}", topic, e),
+            }
+        }
+    }
+
+    /// Run a control step per reading, timed by when readings arrive
+    fn on_reading(&mut self, process_value: f64) {
+        let now = Instant::now();
+        let Some(previous) = self.last_reading.replace(now) else {
+            info!("📥 First reading: {}", process_value);
+            return;
+        };
+        let dt = now.duration_since(previous).as_secs_f64();
+        match self.controller.compute(process_value, dt) {
+            Ok(output) => {
+                debug!(
+                    "{} → {} (setpoint {}, dt {:.3} s)",
+                    process_value,
+                    output,
+                    self.controller.setpoint(),
+                    dt
+                );
+                self.publish(output);
+            }
+            Err(e) => warn!("Skipping control step: {}", e),
+        }
+    }
+
+    /// Fall back to the failsafe output if the sensor has gone quiet
+    fn check_timeout(&mut self) {
+        let (Some(timeout), Some(last)) = (self.config.input.timeout_secs, self.last_reading)
+        else {
+            return;
+        };
+        if last.elapsed().as_secs_f64() < timeout {
+            return;
+        }
+        warn!(
+            "⚠️ No reading on {} for {} s; resetting the controller",
+            self.config.input.mapping.topic, timeout
+        );
+        // The gap would otherwise become one huge integration step
+        self.last_reading = None;
+        self.controller.reset();
+        if let Some(failsafe) = self.config.output.failsafe {
+            self.publish(failsafe);
+        }
+    }
+
+    fn publish(&self, output: f64) {
+        let mapping = &self.config.output;
+        let payload = match mapping.render(output) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to build output payload: {}", e);
+                return;
+            }
+        };
+        let qos = match mapping.qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            _ => QoS::ExactlyOnce,
+        };
+        if let Err(e) = self
+            .client
+            .try_publish(&mapping.topic, qos, mapping.retain, payload)
+        {
+            error!("Failed to publish to {}: {}", mapping.topic, e);
+        }
+    }
+}
+
+/// Run the loop until Ctrl-C, then publish the failsafe output and disconnect
+///
+/// A control step runs for every process value that arrives, with `dt` the
+/// time since the previous one; the first reading after startup or a timeout
+/// only starts the clock. The connection is re-established after errors and
+/// subscriptions are renewed on every connect.
+pub async fn run(config: BridgeConfig) {
+    let client_id = config
+        .client_id
+        .clone()
+        .unwrap_or_else(|| format!("pidgeon-mqtt-{}", std::process::id()));
+    let mut options = MqttOptions::new(client_id, &config.broker.host, config.broker.port);
+    options.set_keep_alive(KEEP_ALIVE);
+    if let Some((user, password)) = &config.broker.credentials {
+        options.set_credentials(user, password);
+    }
+    let (client, mut event_loop) = AsyncClient::new(options, REQUEST_CAPACITY);
+
+    let mut bridge = Bridge {
+        controller: PidController::new(config.controller.clone()),
+        config,
+        client,
+        last_reading: None,
+    };
+    let mut watchdog = tokio::time::interval(WATCHDOG_INTERVAL);
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            event = event_loop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("✅ Connected to MQTT broker at {}", bridge.config.broker);
+                    bridge.on_connect();
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    bridge.on_publish(&publish.topic, &publish.payload);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("MQTT connection to {} failed: {}", bridge.config.broker, e);
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            _ = watchdog.tick() => bridge.check_timeout(),
+            _ = &mut shutdown => break,
+        }
+    }
+
+    info!("🛑 Shutting down");
+    if let Some(failsafe) = bridge.config.output.failsafe {
+        bridge.publish(failsafe);
+    }
+    if let Err(e) = bridge.client.try_disconnect() {
+        error!("Failed to disconnect: {}", e);
+        return;
+    }
+    // Drive the event loop until the queued failsafe and disconnect are sent
+    let _ = tokio::time::timeout(SHUTDOWN_GRACE, async {
+        while let Ok(event) = event_loop.poll().await {
+            if matches!(event, Event::Outgoing(Outgoing::Disconnect)) {
+                break;
+            }
+        }
+    })
+    .await;
+}
@@ -0,0 +1,41 @@
+//! `pidgeon-mqtt`: runs a complete PID loop between MQTT topics.
+//!
+//! Process values are read from a sensor topic, each one runs a control step,
+//! and the output is published to an actuator topic. Payload mapping,
+//! controller gains and the broker all come from a JSON file, so a Raspberry
+//! Pi next to a fermenter needs no custom code.
+//!
+//! ```bash
+//! pidgeon-mqtt --config fermenter.json
+//! ```
+
+mod bridge;
+mod config;
+mod payload;
+
+use clap::Parser;
+use config::{Args, BridgeConfig};
+use log::*;
+use std::process::ExitCode;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let env = env_logger::Env::default().default_filter_or("info");
+    env_logger::Builder::from_env(env).init();
+
+    let args = Args::parse();
+    let config = match BridgeConfig::load(&args.config) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    info!(
+        "🔁 {} → PID → {}",
+        config.input.mapping.topic, config.output.topic
+    );
+
+    bridge::run(config).await;
+    ExitCode::SUCCESS
+}
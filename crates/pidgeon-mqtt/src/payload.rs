@@ -0,0 +1,211 @@
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+
+/// Where a number is read from in the payloads on a topic
+///
+/// `pointer` is a JSON Pointer (RFC 6901), such as `/temperature` or
+/// `/sensors/0/value`. The default, `""`, takes the whole payload, which may
+/// then also be plain text like `21.5`. Numbers sent as JSON strings are
+/// accepted too.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InputMapping {
+    /// Topic to subscribe to; wildcards are not allowed
+    pub topic: String,
+    /// Location of the number in the payload
+    #[serde(default)]
+    pub pointer: String,
+}
+
+impl InputMapping {
+    /// Read the number this mapping points at out of `payload`
+    pub fn extract(&self, payload: &[u8]) -> Result<f64, String> {
+        let value = match serde_json::from_slice::<Value>(payload) {
+            Ok(value) => value,
+            Err(_) if self.pointer.is_empty() => {
+                return parse_text(payload).ok_or_else(|| "payload is not a number".to_string())
+            }
+            Err(e) => return Err(format!("payload is not JSON: {}", e)),
+        };
+        let found = value
+            .pointer(&self.pointer)
+            .ok_or_else(|| format!("payload has nothing at '{}'", self.pointer))?;
+        let number = match found {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => text.trim().parse().ok(),
+            _ => None,
+        };
+        number
+            .filter(|n: &f64| n.is_finite())
+            .ok_or_else(|| format!("'{}' is not a number: {}", self.pointer, found))
+    }
+}
+
+/// How the controller output is written into the payloads on a topic
+///
+/// The output is placed at `pointer` inside a copy of `template`, creating
+/// objects along the way. With the default pointer, `""`, the payload is the
+/// bare number.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutputMapping {
+    /// Topic to publish to
+    pub topic: String,
+    /// Location of the output in the payload
+    #[serde(default)]
+    pub pointer: String,
+    /// Fixed payload content around the output
+    #[serde(default)]
+    pub template: Value,
+    /// Round the output to this many decimal places
+    #[serde(default)]
+    pub decimals: Option<u32>,
+    /// MQTT QoS level, 0 to 2
+    #[serde(default)]
+    pub qos: u8,
+    /// Publish with the retain flag, so late subscribers get the last output
+    #[serde(default)]
+    pub retain: bool,
+    /// Output published when the sensor goes quiet or the bridge stops
+    #[serde(default)]
+    pub failsafe: Option<f64>,
+}
+
+impl OutputMapping {
+    /// Build the payload carrying `output`
+    pub fn render(&self, output: f64) -> Result<Vec<u8>, String> {
+        let output = match self.decimals {
+            Some(decimals) => {
+                let scale = 10f64.powi(decimals as i32);
+                (output * scale).round() / scale
+            }
+            None => output,
+        };
+        // Whole numbers are written without a fraction, which integer-only
+        // actuators expect
+        let number = if output.fract() == 0.0 && output.abs() < i64::MAX as f64 {
+            Number::from(output as i64)
+        } else {
+            Number::from_f64(output).ok_or_else(|| format!("output {} is not finite", output))?
+        };
+
+        let mut payload = self.template.clone();
+        insert(&mut payload, &self.pointer, Value::Number(number))?;
+        serde_json::to_vec(&payload).map_err(|e| e.to_string())
+    }
+}
+
+/// Parse a plain-text number, such as `21.5\n`
+fn parse_text(payload: &[u8]) -> Option<f64> {
+    std::str::from_utf8(payload)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite())
+}
+
+/// Set the value at `pointer` in `target`, creating objects for missing or
+/// null parents
+fn insert(target: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    if pointer.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let Some(path) = pointer.strip_prefix('/') else {
+        return Err(format!("pointer '{}' must start with '/'", pointer));
+    };
+
+    let mut current = target;
+    for token in path.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        if current.is_null() {
+            *current = Value::Object(Map::new());
+        }
+        current = match current {
+            Value::Object(map) => map.entry(token).or_insert(Value::Null),
+            Value::Array(items) => {
+                let index: usize = token
+                    .parse()
+                    .map_err(|_| format!("'{}' in '{}' is not an array index", token, pointer))?;
+                items
+                    .get_mut(index)
+                    .ok_or_else(|| format!("index {} in '{}' is out of range", index, pointer))?
+            }
+            _ => {
+                return Err(format!(
+                    "cannot place the output at '{}': the template has a value in the way",
+                    pointer
+                ))
+            }
+        };
+    }
+    *current = value;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(pointer: &str) -> InputMapping {
+        InputMapping {
+            topic: "sensor".to_string(),
+            pointer: pointer.to_string(),
+        }
+    }
+
+    fn output(pointer: &str, template: Value) -> OutputMapping {
+        OutputMapping {
+            topic: "actuator".to_string(),
+            pointer: pointer.to_string(),
+            template,
+            decimals: None,
+            qos: 0,
+            retain: false,
+            failsafe: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_follows_pointer() {
+        let payload = br#"{"temperature": 19.25, "sensors": [{"value": "3.5"}]}"#;
+        assert_eq!(input("/temperature").extract(payload), Ok(19.25));
+        assert_eq!(input("/sensors/0/value").extract(payload), Ok(3.5));
+        assert!(input("/humidity").extract(payload).is_err());
+        assert!(input("/sensors").extract(payload).is_err());
+    }
+
+    #[test]
+    fn test_extract_accepts_bare_numbers() {
+        assert_eq!(input("").extract(b"21.5"), Ok(21.5));
+        assert_eq!(input("").extract(b" 21.5\n"), Ok(21.5));
+        assert_eq!(input("").extract(b"\"-4\""), Ok(-4.0));
+        assert!(input("").extract(b"on").is_err());
+        assert!(input("/temperature").extract(b"21.5").is_err());
+    }
+
+    #[test]
+    fn test_render_places_output_in_template() {
+        let mapping = output("/heater/power", json!({"device": "fermenter"}));
+        let payload: Value = serde_json::from_slice(&mapping.render(42.5).unwrap()).unwrap();
+        assert_eq!(
+            payload,
+            json!({"device": "fermenter", "heater": {"power": 42.5}})
+        );
+
+        assert_eq!(output("", Value::Null).render(7.0).unwrap(), b"7");
+        assert!(output("/device/power", json!({"device": "x"}))
+            .render(1.0)
+            .is_err());
+        assert!(output("power", Value::Null).render(1.0).is_err());
+    }
+
+    #[test]
+    fn test_render_rounds_to_decimals() {
+        let mut mapping = output("/power", Value::Null);
+        mapping.decimals = Some(0);
+        assert_eq!(mapping.render(41.6).unwrap(), br#"{"power":42}"#);
+        mapping.decimals = Some(1);
+        assert_eq!(mapping.render(41.64).unwrap(), br#"{"power":41.6}"#);
+    }
+}
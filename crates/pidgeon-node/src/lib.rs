@@ -0,0 +1,215 @@
+// Pidgeon: A robust PID controller library written in Rust
+// Copyright (c) 2025 Security Union LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Node.js native bindings for the pidgeon PID controller.
+//!
+//! Built with napi-rs into a `.node` addon, so Node-RED nodes, Homebridge
+//! plugins, and other Node-based automation call the controller in-process
+//! instead of through an HTTP shim.
+//!
+//! ```js
+//! const { PidController } = require("pidgeon-node");
+//!
+//! const controller = new PidController({
+//!   kp: 2.0,
+//!   ki: 0.1,
+//!   setpoint: 21.0,
+//!   minOutput: 0.0,
+//!   maxOutput: 100.0,
+//! });
+//! const power = controller.compute(readTemperature(), 1.0);
+//! ```
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use pidgeon::{AntiWindupMode, ControllerConfig, PidError};
+
+/// Controller parameters. Fields left out keep their defaults when creating
+/// a controller, and their current values when reconfiguring one.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct PidConfig {
+    /// Proportional gain; defaults to 1.
+    pub kp: Option<f64>,
+    /// Integral gain, per second; defaults to 0.
+    pub ki: Option<f64>,
+    /// Derivative gain, in seconds; defaults to 0.
+    pub kd: Option<f64>,
+    /// Target process value; defaults to 0.
+    pub setpoint: Option<f64>,
+    /// Lowest output. Required when creating a controller.
+    pub min_output: Option<f64>,
+    /// Highest output. Required when creating a controller.
+    pub max_output: Option<f64>,
+    /// Errors smaller than this are treated as zero; defaults to 0.
+    pub deadband: Option<f64>,
+    /// Stop integrating while the output is saturated; defaults to `true`.
+    pub anti_windup: Option<bool>,
+}
+
+impl PidConfig {
+    /// Fills the fields missing from `self` with those of `fallback`.
+    fn or(self, fallback: PidConfig) -> PidConfig {
+        PidConfig {
+            kp: self.kp.or(fallback.kp),
+            ki: self.ki.or(fallback.ki),
+            kd: self.kd.or(fallback.kd),
+            setpoint: self.setpoint.or(fallback.setpoint),
+            min_output: self.min_output.or(fallback.min_output),
+            max_output: self.max_output.or(fallback.max_output),
+            deadband: self.deadband.or(fallback.deadband),
+            anti_windup: self.anti_windup.or(fallback.anti_windup),
+        }
+    }
+
+    /// Validates the parameters through [`ControllerConfig::builder`].
+    fn build(&self) -> Result<ControllerConfig> {
+        let (Some(min_output), Some(max_output)) = (self.min_output, self.max_output) else {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "minOutput and maxOutput are required",
+            ));
+        };
+        let mut builder = ControllerConfig::builder()
+            .with_output_limits(min_output, max_output)
+            .with_anti_windup(self.anti_windup.unwrap_or(true));
+        if let Some(kp) = self.kp {
+            builder = builder.with_kp(kp);
+        }
+        if let Some(ki) = self.ki {
+            builder = builder.with_ki(ki);
+        }
+        if let Some(kd) = self.kd {
+            builder = builder.with_kd(kd);
+        }
+        if let Some(setpoint) = self.setpoint {
+            builder = builder.with_setpoint(setpoint);
+        }
+        if let Some(deadband) = self.deadband {
+            builder = builder.with_deadband(deadband);
+        }
+        builder.build().map_err(invalid)
+    }
+}
+
+/// Performance since the controller was created or last reset.
+#[napi(object)]
+#[derive(Debug, Clone, Copy)]
+pub struct PidStatistics {
+    /// Time-weighted mean absolute error: each step counts for its `dt`.
+    pub average_error: f64,
+    /// Largest absolute error.
+    pub max_overshoot: f64,
+    /// Seconds until the error settled, or elapsed time if it has not.
+    pub settling_time: f64,
+    /// Seconds until the error first settled; `NaN` if it never has.
+    pub rise_time: f64,
+}
+
+/// A PID controller running pidgeon's algorithm.
+#[napi]
+pub struct PidController {
+    inner: pidgeon::PidController,
+}
+
+#[napi]
+impl PidController {
+    /// Creates a controller.
+    ///
+    /// Throws if `config` is invalid, such as missing or non-finite output
+    /// limits, or `minOutput >= maxOutput`.
+    #[napi(constructor)]
+    pub fn new(config: PidConfig) -> Result<Self> {
+        Ok(PidController {
+            inner: pidgeon::PidController::new(config.build()?),
+        })
+    }
+
+    /// Runs one control step and returns the clamped output.
+    ///
+    /// `dt` is the time since the previous step, in seconds. Throws if it is
+    /// not positive or `processValue` is not finite.
+    #[napi]
+    pub fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64> {
+        self.inner.compute(process_value, dt).map_err(invalid)
+    }
+
+    /// Updates the fields present in `config`, keeping the controller's
+    /// state so a running loop is retuned without a bump. Throws, leaving
+    /// the configuration unchanged, if the result would be invalid.
+    #[napi]
+    pub fn configure(&mut self, config: PidConfig) -> Result<()> {
+        let updated = config.or(self.config()).build()?;
+        self.inner.set_kp(updated.kp()).map_err(invalid)?;
+        self.inner.set_ki(updated.ki()).map_err(invalid)?;
+        self.inner.set_kd(updated.kd()).map_err(invalid)?;
+        self.inner
+            .set_setpoint(updated.setpoint())
+            .map_err(invalid)?;
+        self.inner
+            .set_output_limits(updated.min_output(), updated.max_output());
+        self.inner
+            .set_deadband(updated.deadband())
+            .map_err(invalid)?;
+        self.inner
+            .set_anti_windup(updated.anti_windup_mode() != AntiWindupMode::None);
+        Ok(())
+    }
+
+    /// The controller's current parameters, with every field present.
+    #[napi(getter)]
+    pub fn config(&self) -> PidConfig {
+        let config = self.inner.config();
+        PidConfig {
+            kp: Some(config.kp()),
+            ki: Some(config.ki()),
+            kd: Some(config.kd()),
+            setpoint: Some(config.setpoint()),
+            min_output: Some(config.min_output()),
+            max_output: Some(config.max_output()),
+            deadband: Some(config.deadband()),
+            anti_windup: Some(config.anti_windup_mode() != AntiWindupMode::None),
+        }
+    }
+
+    /// Target process value.
+    #[napi(getter)]
+    pub fn setpoint(&self) -> f64 {
+        self.inner.setpoint()
+    }
+
+    /// Moves the target. Throws if `setpoint` is not finite.
+    #[napi(setter, js_name = "setpoint")]
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<()> {
+        self.inner.set_setpoint(setpoint).map_err(invalid)
+    }
+
+    /// Clears the controller's state and statistics.
+    #[napi]
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// Performance since the controller was created or last reset.
+    #[napi]
+    pub fn statistics(&self) -> PidStatistics {
+        let stats = self.inner.get_statistics();
+        PidStatistics {
+            average_error: stats.average_error,
+            max_overshoot: stats.max_overshoot,
+            settling_time: stats.settling_time,
+            rise_time: stats.rise_time,
+        }
+    }
+}
+
+/// Surfaces a [`PidError`] as a JavaScript `Error`.
+fn invalid(e: PidError) -> Error {
+    Error::new(Status::InvalidArg, e.to_string())
+}
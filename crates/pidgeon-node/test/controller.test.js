@@ -0,0 +1,48 @@
+const test = require("node:test");
+const assert = require("node:assert");
+const { PidController } = require("..");
+
+test("drives a first-order process to the setpoint", () => {
+  const controller = new PidController({
+    kp: 0.6,
+    ki: 0.05,
+    setpoint: 30,
+    minOutput: 0,
+    maxOutput: 100,
+  });
+  let value = 20;
+  for (let i = 0; i < 5000; i++) {
+    const output = controller.compute(value, 0.1);
+    value += ((20 + 0.5 * output - value) * 0.1) / 10;
+  }
+  assert.ok(Math.abs(value - 30) < 0.01, `ended at ${value}`);
+  assert.ok(controller.statistics().averageError > 0);
+});
+
+test("configure changes only the given fields and keeps the integral", () => {
+  const controller = new PidController({ kp: 0, ki: 1, setpoint: 1, minOutput: -10, maxOutput: 10 });
+  for (let i = 0; i < 10; i++) {
+    controller.compute(0, 0.1);
+  }
+  const before = controller.compute(0, 0.1);
+
+  controller.configure({ kp: 0.5 });
+  assert.strictEqual(controller.config.kp, 0.5);
+  assert.strictEqual(controller.config.maxOutput, 10);
+  assert.ok(Math.abs(controller.compute(1, 0.1) - before) < 1e-9);
+
+  controller.setpoint = 2;
+  assert.strictEqual(controller.setpoint, 2);
+  controller.reset();
+  assert.strictEqual(controller.compute(2, 0.1), 0);
+});
+
+test("rejects invalid parameters", () => {
+  assert.throws(() => new PidController({ kp: 1 }), /minOutput and maxOutput are required/);
+  assert.throws(() => new PidController({ minOutput: 1, maxOutput: 0 }), /min_output must be less than max_output/);
+
+  const controller = new PidController({ minOutput: -1, maxOutput: 1 });
+  assert.throws(() => controller.configure({ minOutput: 5 }));
+  assert.strictEqual(controller.config.minOutput, -1);
+  assert.throws(() => controller.compute(0, 0));
+});
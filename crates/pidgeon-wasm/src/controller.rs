@@ -0,0 +1,192 @@
+use pidgeon::{AntiWindupMode, ControllerConfig};
+use wasm_bindgen::prelude::*;
+
+/// Controller parameters. Exposed to JavaScript with camelCase properties.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidConfig {
+    /// Proportional gain.
+    pub kp: f64,
+    /// Integral gain, per second.
+    pub ki: f64,
+    /// Derivative gain, in seconds.
+    pub kd: f64,
+    /// Target process value.
+    pub setpoint: f64,
+    /// Lowest output; must be finite and below `maxOutput`.
+    #[wasm_bindgen(js_name = minOutput)]
+    pub min_output: f64,
+    /// Highest output; must be finite and above `minOutput`.
+    #[wasm_bindgen(js_name = maxOutput)]
+    pub max_output: f64,
+    /// Errors smaller than this are treated as zero.
+    pub deadband: f64,
+    /// Stop integrating while the output is saturated.
+    #[wasm_bindgen(js_name = antiWindup)]
+    pub anti_windup: bool,
+}
+
+impl Default for PidConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl PidConfig {
+    /// Unit proportional gain, no integral or derivative action, a zero
+    /// setpoint, anti-windup on, and outputs limited to ±100.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        PidConfig {
+            kp: 1.0,
+            ki: 0.0,
+            kd: 0.0,
+            setpoint: 0.0,
+            min_output: -100.0,
+            max_output: 100.0,
+            deadband: 0.0,
+            anti_windup: true,
+        }
+    }
+}
+
+impl TryFrom<&PidConfig> for ControllerConfig {
+    type Error = pidgeon::PidError;
+
+    fn try_from(config: &PidConfig) -> Result<Self, Self::Error> {
+        ControllerConfig::builder()
+            .with_kp(config.kp)
+            .with_ki(config.ki)
+            .with_kd(config.kd)
+            .with_setpoint(config.setpoint)
+            .with_output_limits(config.min_output, config.max_output)
+            .with_deadband(config.deadband)
+            .with_anti_windup_mode(if config.anti_windup {
+                AntiWindupMode::Conditional
+            } else {
+                AntiWindupMode::None
+            })
+            .build()
+    }
+}
+
+/// Performance since the controller was created or last reset.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidStatistics {
+    /// Time-weighted mean absolute error: each step counts for its `dt`.
+    #[wasm_bindgen(js_name = averageError)]
+    pub average_error: f64,
+    /// Largest absolute error.
+    #[wasm_bindgen(js_name = maxOvershoot)]
+    pub max_overshoot: f64,
+    /// Seconds until the error settled, or elapsed time if it has not.
+    #[wasm_bindgen(js_name = settlingTime)]
+    pub settling_time: f64,
+    /// Seconds until the error first settled; `NaN` if it never has.
+    #[wasm_bindgen(js_name = riseTime)]
+    pub rise_time: f64,
+}
+
+/// A PID controller running pidgeon's algorithm.
+#[wasm_bindgen]
+pub struct PidController {
+    inner: pidgeon::PidController,
+}
+
+#[wasm_bindgen]
+impl PidController {
+    /// Creates a controller.
+    ///
+    /// Throws if `config` is invalid, such as non-finite gains or
+    /// `minOutput >= maxOutput`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &PidConfig) -> Result<PidController, JsError> {
+        Ok(PidController {
+            inner: pidgeon::PidController::new(config.try_into()?),
+        })
+    }
+
+    /// Runs one control step and returns the clamped output.
+    ///
+    /// `dt` is the time since the previous step, in seconds. Throws if it is
+    /// not positive or `processValue` is not finite.
+    pub fn compute(
+        &mut self,
+        #[wasm_bindgen(js_name = processValue)] process_value: f64,
+        dt: f64,
+    ) -> Result<f64, JsError> {
+        Ok(self.inner.compute(process_value, dt)?)
+    }
+
+    /// Replaces the configuration, keeping the controller's state so a
+    /// running loop is retuned without a bump. Throws, leaving the old
+    /// configuration in place, if `config` is invalid.
+    pub fn configure(&mut self, config: &PidConfig) -> Result<(), JsError> {
+        ControllerConfig::try_from(config)?;
+        self.inner.set_kp(config.kp)?;
+        self.inner.set_ki(config.ki)?;
+        self.inner.set_kd(config.kd)?;
+        self.inner.set_setpoint(config.setpoint)?;
+        self.inner
+            .set_output_limits(config.min_output, config.max_output);
+        self.inner.set_deadband(config.deadband)?;
+        self.inner.set_anti_windup(config.anti_windup);
+        Ok(())
+    }
+
+    /// Clears the controller's state and statistics.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// Target process value.
+    #[wasm_bindgen(getter)]
+    pub fn setpoint(&self) -> f64 {
+        self.inner.setpoint()
+    }
+
+    /// Moves the target. Throws if `setpoint` is not finite.
+    #[wasm_bindgen(setter)]
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), JsError> {
+        Ok(self.inner.set_setpoint(setpoint)?)
+    }
+
+    /// Performance since the controller was created or last reset.
+    pub fn statistics(&self) -> PidStatistics {
+        let stats = self.inner.get_statistics();
+        PidStatistics {
+            average_error: stats.average_error,
+            max_overshoot: stats.max_overshoot,
+            settling_time: stats.settling_time,
+            rise_time: stats.rise_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configure_keeps_integral() {
+        let mut config = PidConfig::new();
+        config.kp = 0.0;
+        config.ki = 1.0;
+        config.setpoint = 1.0;
+        let mut controller = PidController::new(&config).unwrap();
+        for _ in 0..10 {
+            controller.compute(0.0, 0.1).unwrap();
+        }
+        let before = controller.compute(0.0, 0.1).unwrap();
+
+        config.kp = 0.5;
+        controller.configure(&config).unwrap();
+        let after = controller.compute(1.0, 0.1).unwrap();
+        assert!((after - before).abs() < 1e-9, "{} != {}", after, before);
+
+        controller.reset();
+        assert_eq!(controller.compute(1.0, 0.1).unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,42 @@
+// Pidgeon: A robust PID controller library written in Rust
+// Copyright (c) 2025 Security Union LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! WebAssembly bindings for the pidgeon PID controller.
+//!
+//! Built with `wasm-pack` into an npm package, so browser UIs and JavaScript
+//! dashboards run the same controller as the firmware instead of a
+//! re-implementation. Alongside [`PidController`] come simple simulated
+//! processes, [`FirstOrderPlant`] and [`SecondOrderPlant`], to close the loop
+//! in demos.
+//!
+//! ```js
+//! import init, { PidConfig, PidController, FirstOrderPlant } from "pidgeon-wasm";
+//!
+//! await init();
+//! const config = new PidConfig();
+//! config.kp = 2.0;
+//! config.ki = 0.1;
+//! config.setpoint = 21.0;
+//! config.minOutput = 0.0;
+//! config.maxOutput = 100.0;
+//!
+//! const controller = new PidController(config);
+//! const room = new FirstOrderPlant(0.5, 50.0, 2.0, 15.0);
+//! let temperature = room.value;
+//! for (let i = 0; i < 600; i++) {
+//!     const power = controller.compute(temperature, 1.0);
+//!     temperature = room.step(power, 1.0);
+//! }
+//! ```
+
+mod controller;
+mod plants;
+
+pub use controller::{PidConfig, PidController, PidStatistics};
+pub use plants::{FirstOrderPlant, SecondOrderPlant};
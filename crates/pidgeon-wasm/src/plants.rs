@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// A first-order process with dead time, such as a heated room or a tank.
+///
+/// Settles at `ambient + gain × input` with time constant `timeConstant`,
+/// and reacts to an input only `deadTime` seconds after it is applied.
+#[wasm_bindgen]
+pub struct FirstOrderPlant {
+    gain: f64,
+    time_constant: f64,
+    dead_time: f64,
+    ambient: f64,
+    value: f64,
+    elapsed: f64,
+    /// Inputs not yet felt, with the time they were applied
+    pending: VecDeque<(f64, f64)>,
+    applied: f64,
+}
+
+#[wasm_bindgen]
+impl FirstOrderPlant {
+    /// Creates a plant resting at `ambient`.
+    ///
+    /// Throws unless `gain` and `ambient` are finite, `timeConstant` is
+    /// positive, and `deadTime` is non-negative.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        gain: f64,
+        #[wasm_bindgen(js_name = timeConstant)] time_constant: f64,
+        #[wasm_bindgen(js_name = deadTime)] dead_time: f64,
+        ambient: f64,
+    ) -> Result<FirstOrderPlant, JsError> {
+        if !gain.is_finite() || !ambient.is_finite() {
+            return Err(JsError::new("gain and ambient must be finite numbers"));
+        }
+        if !time_constant.is_finite() || time_constant <= 0.0 {
+            return Err(JsError::new("timeConstant must be a positive number"));
+        }
+        if !dead_time.is_finite() || dead_time < 0.0 {
+            return Err(JsError::new("deadTime must be a non-negative number"));
+        }
+        Ok(FirstOrderPlant {
+            gain,
+            time_constant,
+            dead_time,
+            ambient,
+            value: ambient,
+            elapsed: 0.0,
+            pending: VecDeque::new(),
+            applied: 0.0,
+        })
+    }
+
+    /// Applies `input` for `dt` seconds and returns the new process value.
+    pub fn step(&mut self, input: f64, dt: f64) -> f64 {
+        if dt <= 0.0 || !input.is_finite() {
+            return self.value;
+        }
+        self.pending.push_back((self.elapsed, input));
+        while let Some(&(applied_at, delayed)) = self.pending.front() {
+            // Allow for rounding in the accumulated time
+            if applied_at > self.elapsed - self.dead_time + dt * 1e-6 {
+                break;
+            }
+            self.applied = delayed;
+            self.pending.pop_front();
+        }
+        self.elapsed += dt;
+
+        // Exact for an input held over the step
+        let target = self.ambient + self.gain * self.applied;
+        let decay = (-dt / self.time_constant).exp();
+        self.value = target + (self.value - target) * decay;
+        self.value
+    }
+
+    /// Current process value.
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Returns to rest at `ambient`, forgetting pending inputs.
+    pub fn reset(&mut self) {
+        self.value = self.ambient;
+        self.elapsed = 0.0;
+        self.pending.clear();
+        self.applied = 0.0;
+    }
+}
+
+/// A mass on a spring and damper driven by a force, such as a motor
+/// positioning a load.
+///
+/// Obeys `mass × acceleration = force − damping × velocity − stiffness ×
+/// position`, integrated with semi-implicit Euler steps.
+#[wasm_bindgen]
+pub struct SecondOrderPlant {
+    mass: f64,
+    damping: f64,
+    stiffness: f64,
+    position: f64,
+    velocity: f64,
+}
+
+#[wasm_bindgen]
+impl SecondOrderPlant {
+    /// Creates a plant at rest at position 0.
+    ///
+    /// Throws unless `mass` is positive and `damping` and `stiffness` are
+    /// non-negative.
+    #[wasm_bindgen(constructor)]
+    pub fn new(mass: f64, damping: f64, stiffness: f64) -> Result<SecondOrderPlant, JsError> {
+        if !mass.is_finite() || mass <= 0.0 {
+            return Err(JsError::new("mass must be a positive number"));
+        }
+        if !damping.is_finite() || damping < 0.0 || !stiffness.is_finite() || stiffness < 0.0 {
+            return Err(JsError::new(
+                "damping and stiffness must be non-negative numbers",
+            ));
+        }
+        Ok(SecondOrderPlant {
+            mass,
+            damping,
+            stiffness,
+            position: 0.0,
+            velocity: 0.0,
+        })
+    }
+
+    /// Applies `force` for `dt` seconds and returns the new position.
+    pub fn step(&mut self, force: f64, dt: f64) -> f64 {
+        if dt <= 0.0 || !force.is_finite() {
+            return self.position;
+        }
+        let acceleration =
+            (force - self.damping * self.velocity - self.stiffness * self.position) / self.mass;
+        self.velocity += acceleration * dt;
+        self.position += self.velocity * dt;
+        self.position
+    }
+
+    /// Current position.
+    #[wasm_bindgen(getter)]
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// Current velocity.
+    #[wasm_bindgen(getter)]
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Returns to rest at position 0.
+    pub fn reset(&mut self) {
+        self.position = 0.0;
+        self.velocity = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_order_plant_delays_and_settles() {
+        let mut plant = FirstOrderPlant::new(2.0, 5.0, 1.0, 20.0).unwrap();
+        // Nothing happens during the dead time
+        for _ in 0..10 {
+            assert_eq!(plant.step(10.0, 0.1), 20.0);
+        }
+        // One time constant after the dead time: 63% of the way there
+        for _ in 0..50 {
+            plant.step(10.0, 0.1);
+        }
+        assert!((plant.value() - (20.0 + 20.0 * (1.0 - (-1.0f64).exp()))).abs() < 1e-9);
+        for _ in 0..1000 {
+            plant.step(10.0, 0.1);
+        }
+        assert!((plant.value() - 40.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_second_order_plant_comes_to_rest_at_force_over_stiffness() {
+        let mut plant = SecondOrderPlant::new(1.0, 2.0, 4.0).unwrap();
+        for _ in 0..10_000 {
+            plant.step(8.0, 0.01);
+        }
+        assert!((plant.position() - 2.0).abs() < 1e-6);
+        assert!(plant.velocity().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_controller_drives_plant_to_setpoint() {
+        let mut config = crate::PidConfig::new();
+        config.kp = 0.6;
+        config.ki = 0.05;
+        config.setpoint = 30.0;
+        config.min_output = 0.0;
+        config.max_output = 100.0;
+        let mut controller = crate::PidController::new(&config).unwrap();
+        let mut plant = FirstOrderPlant::new(0.5, 10.0, 1.0, 20.0).unwrap();
+        let mut value = plant.value();
+        for _ in 0..5000 {
+            let output = controller.compute(value, 0.1).unwrap();
+            value = plant.step(output, 0.1);
+        }
+        assert!((value - 30.0).abs() < 0.01, "ended at {}", value);
+    }
+}
@@ -1,5 +1,8 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use pidgeon::{ControllerConfig, PidController, ThreadSafePidController};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pidgeon::{
+    pid_compute, AntiWindupMode, ControllerBank, ControllerConfig, DerivativeMode, PidController,
+    PidState, ThreadSafePidController,
+};
 use std::sync::Arc;
 use std::thread;
 
@@ -75,5 +78,80 @@ fn benchmark_pid_controller(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, benchmark_pid_controller);
+/// Loop `i` of a bank; options vary so no lane takes a uniform path
+fn bank_config(i: usize) -> ControllerConfig {
+    ControllerConfig::builder()
+        .with_kp(1.0 + (i % 7) as f64 * 0.1)
+        .with_ki(0.1)
+        .with_kd(0.05)
+        .with_setpoint((i % 10) as f64)
+        .with_output_limits(-100.0, 100.0)
+        .with_anti_windup_mode(match i % 3 {
+            0 => AntiWindupMode::None,
+            1 => AntiWindupMode::Conditional,
+            _ => AntiWindupMode::BackCalculation { tracking_time: 0.5 },
+        })
+        .with_derivative_mode(if i.is_multiple_of(2) {
+            DerivativeMode::OnMeasurement
+        } else {
+            DerivativeMode::OnError
+        })
+        .build()
+        .unwrap()
+}
+
+fn benchmark_compute(c: &mut Criterion) {
+    let config = bank_config(0);
+    let mut state = PidState::new(&config);
+
+    // One step of the pure function, the floor for everything else
+    c.bench_function("pure_pid_compute", |b| {
+        b.iter(|| {
+            let (output, next) =
+                pid_compute(black_box(&config), &state, black_box(4.0), black_box(0.01)).unwrap();
+            state = next;
+            black_box(output)
+        })
+    });
+
+    // Every loop stepped once, banked against one pid_compute call per loop
+    let mut group = c.benchmark_group("bank");
+    for loops in [16, 256, 1024] {
+        let configs: Vec<ControllerConfig> = (0..loops).map(bank_config).collect();
+        let process_values: Vec<f64> = (0..loops).map(|i| (i % 13) as f64 * 0.5).collect();
+        let mut outputs = vec![0.0; loops];
+        group.throughput(Throughput::Elements(loops as u64));
+
+        let mut states: Vec<PidState> = configs.iter().map(PidState::new).collect();
+        group.bench_with_input(BenchmarkId::new("scalar", loops), &loops, |b, _| {
+            b.iter(|| {
+                for ((config, state), (pv, out)) in configs
+                    .iter()
+                    .zip(states.iter_mut())
+                    .zip(process_values.iter().zip(outputs.iter_mut()))
+                {
+                    let (output, next) = pid_compute(config, state, black_box(*pv), 0.01).unwrap();
+                    *state = next;
+                    *out = output;
+                }
+                black_box(&outputs);
+            })
+        });
+
+        let mut bank = ControllerBank::new();
+        for config in &configs {
+            bank.push(config.clone());
+        }
+        group.bench_with_input(BenchmarkId::new("banked", loops), &loops, |b, _| {
+            b.iter(|| {
+                bank.compute(black_box(&process_values), 0.01, &mut outputs)
+                    .unwrap();
+                black_box(&outputs);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_pid_controller, benchmark_compute);
 criterion_main!(benches);
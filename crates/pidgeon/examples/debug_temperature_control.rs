@@ -40,6 +40,7 @@ fn main() {
             topic_name: "controller_data".to_string(),
             controller_id: "temperature_controller".to_string(),
             sample_rate_hz: Some(10.0), // 10Hz sample rate
+            ..Default::default()
         };
 
         // Create controller with debugging
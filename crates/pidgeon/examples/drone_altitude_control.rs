@@ -3,7 +3,9 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use pidgeon::{ControllerConfig, ThreadSafePidController};
+use pidgeon::{
+    ControllerConfig, DroneAltitudePlant, GustGenerator, SimRng, ThreadSafePidController,
+};
 use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -25,6 +27,8 @@ const SETPOINT_STEP: f64 = 2.0;
 const MAX_SETPOINT: f64 = 25.0;
 const MIN_SETPOINT: f64 = 0.0;
 const WIND_GUST_STRENGTH: f64 = 4.0;
+const GUST_MEAN_INTERVAL: f64 = 12.0;
+const DEFAULT_SEED: u64 = 1;
 const MAX_HISTORY_SECONDS: f64 = 60.0;
 const MAX_HISTORY_POINTS: usize = (MAX_HISTORY_SECONDS * CONTROL_RATE_HZ) as usize;
 
@@ -71,7 +75,56 @@ const MISSIONS: &[Mission] = &[
     },
 ];
 
+/// Everything random about a flight, drawn from one seed so a run with the
+/// same seed and the same key presses flies the same way
+struct Scenario {
+    seed: u64,
+    rng: SimRng,
+    gusts: GustGenerator,
+    turbulence: bool,
+}
+
+impl Scenario {
+    fn new(seed: u64) -> Self {
+        Scenario {
+            seed,
+            rng: SimRng::new(seed),
+            gusts: GustGenerator::new(GUST_MEAN_INTERVAL, WIND_GUST_STRENGTH)
+                .expect("Invalid gust parameters"),
+            turbulence: true,
+        }
+    }
+
+    /// Random gust arriving during this step, while turbulence is on
+    fn gust(&mut self, dt: f64) -> Option<f64> {
+        if !self.turbulence {
+            return None;
+        }
+        self.gusts.step(&mut self.rng, dt)
+    }
+}
+
+/// Seed from `--seed <n>`, or the default
+fn parse_seed() -> Result<u64, String> {
+    let mut args = std::env::args().skip(1);
+    let mut seed = DEFAULT_SEED;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a value")?;
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed '{value}'"))?;
+            }
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    Ok(seed)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut scenario = Scenario::new(parse_seed()?);
+
     // Set up terminal
     enable_raw_mode()?;
     io::stdout().execute(EnterAlternateScreen)?;
@@ -115,16 +168,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let controller = ThreadSafePidController::new(config);
 
     // Drone physics
-    let gravity = 9.81;
-    let drone_mass = 1.2;
-    let max_thrust = 30.0;
-    let drag_coefficient = 0.3;
-    let motor_response_delay = 0.1;
+    let mut drone = DroneAltitudePlant::new(0.0);
 
     // State
-    let mut altitude = 0.0;
-    let mut velocity: f64 = 0.0;
-    let mut commanded_thrust = 0.0;
     let mut setpoint = INITIAL_SETPOINT;
     let mut pending_gust: Option<f64> = None;
     let mut auto_missions = true;
@@ -147,7 +193,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut p_term_data: Vec<(f64, f64)> = Vec::new();
     let mut i_term_data: Vec<(f64, f64)> = Vec::new();
     let mut d_term_data: Vec<(f64, f64)> = Vec::new();
-    let mut event_log: Vec<(f64, String)> = Vec::new();
+    let mut event_log: Vec<(f64, String)> = vec![(0.0, format!("Seed {}", scenario.seed))];
 
     let mut current_event = "Engines starting...".to_string();
     let mut time_step: usize = 0;
@@ -205,6 +251,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             current_event = msg.clone();
                             event_log.push((time_step as f64 * DT, msg));
                         }
+                        KeyCode::Char('g') => {
+                            scenario.turbulence = !scenario.turbulence;
+                            let msg = if scenario.turbulence {
+                                format!("WIND: turbulence on (seed {})", scenario.seed)
+                            } else {
+                                "WIND: turbulence off".to_string()
+                            };
+                            current_event = msg.clone();
+                            event_log.push((time_step as f64 * DT, msg));
+                        }
                         // PID gain controls
                         KeyCode::Char('1') => {
                             kp = (kp + GAIN_STEP).min(50.0);
@@ -289,12 +345,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        // Random turbulence
+        if let Some(gust) = scenario.gust(DT) {
+            pending_gust = Some(pending_gust.unwrap_or(0.0) + gust);
+            let msg = format!("WIND GUST (random, {gust:+.1} m/s)");
+            current_event = msg.clone();
+            event_log.push((time, msg));
+        }
+
         // Apply pending wind gust
         if let Some(gust) = pending_gust.take() {
-            velocity += gust;
+            drone.push(gust);
         }
 
         // PID compute
+        let altitude = drone.altitude();
         let control_signal = controller.compute(altitude, DT).expect("Failed to compute");
 
         // Compute PID terms locally (mirrors the controller's internal math)
@@ -314,22 +379,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
         prev_measurement = altitude;
 
-        // Motor response delay
-        commanded_thrust += (control_signal - commanded_thrust) * DT / motor_response_delay;
-
-        // Convert to Newtons
-        let thrust = commanded_thrust * max_thrust / 100.0;
-
         // Physics
-        let weight = drone_mass * gravity;
-        let drag = drag_coefficient * velocity.abs() * velocity;
-        let accel = (thrust - weight - drag) / drone_mass;
-        velocity += accel * DT;
-        altitude += velocity * DT;
-        if altitude < 0.0 {
-            altitude = 0.0;
-            velocity = 0.0;
-        }
+        let altitude = drone.step(control_signal, DT);
+        let velocity = drone.velocity();
+        let commanded_thrust = drone.thrust();
 
         let error = (setpoint - altitude).abs();
 
@@ -437,7 +490,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("================================");
     println!("PID gains:       Kp={kp:.1}  Ki={ki:.1}  Kd={kd:.1}");
     println!("Final setpoint:  {:.1} m", setpoint);
-    println!("Final altitude:  {:.2} m", altitude);
+    println!("Final altitude:  {:.2} m", drone.altitude());
     println!("Average error:   {:.2} m", stats.average_error);
     println!("Max overshoot:   {:.2} m", stats.max_overshoot);
     println!("Rise time:       {:.1} s", stats.rise_time);
@@ -506,6 +559,8 @@ fn draw_intro(f: &mut Frame) {
             Span::raw(" target  "),
             Span::styled("W/S", Style::default().fg(Color::Red)),
             Span::raw(" wind  "),
+            Span::styled("G", Style::default().fg(Color::Red)),
+            Span::raw(" turbulence  "),
             Span::styled("M", Style::default().fg(Color::Green)),
             Span::raw(" auto"),
         ]),
@@ -692,6 +747,11 @@ fn draw_ui(
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         ),
         Span::raw("Wind  "),
+        Span::styled(
+            " G ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("Gusts  "),
         Span::styled(
             " 1/2 ",
             Style::default()
@@ -0,0 +1,538 @@
+//! Interactive thermostat: tune a room heater's PID loop while it runs.
+//!
+//! Pick a gain with Tab, slide it with Left/Right, and throw disturbances at
+//! the room to see how the loop copes. The room is a [`ThermalRoomPlant`],
+//! so the air answers in minutes and the walls in hours; `[` and `]` run the
+//! simulation from real time up to 60x. The strip chart shows the last hour
+//! of room, wall, setpoint and outdoor temperature, and the heater output.
+//!
+//! ```text
+//! cargo run --example thermostat
+//! ```
+
+use crossterm::{
+    event::{self, Event, KeyCode},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use pidgeon::{ControllerConfig, PidController, ThermalRoomPlant};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, LineGauge, Paragraph},
+    Frame, Terminal,
+};
+use std::{
+    collections::VecDeque,
+    io,
+    time::{Duration, Instant},
+};
+
+// Simulation constants
+const DT: f64 = 1.0; // simulated seconds per control step
+const FRAME_TIME: Duration = Duration::from_millis(100);
+const SPEEDS: [f64; 6] = [1.0, 2.0, 5.0, 10.0, 30.0, 60.0];
+const INITIAL_SPEED: usize = 4;
+const HISTORY_SECONDS: f64 = 3600.0;
+const SAMPLE_STEPS: u64 = 5; // control steps per chart point
+const HISTORY_POINTS: usize = (HISTORY_SECONDS / DT) as usize / SAMPLE_STEPS as usize;
+
+// Room
+const OUTDOOR_TEMPERATURE: f64 = 5.0;
+const INITIAL_TEMPERATURE: f64 = 16.0;
+const HEAT_WAVE_RISE: f64 = 20.0;
+
+// Setpoint
+const INITIAL_SETPOINT: f64 = 21.0;
+const SETPOINT_STEP: f64 = 0.5;
+const MIN_SETPOINT: f64 = 10.0;
+const MAX_SETPOINT: f64 = 30.0;
+
+/// A gain the user can slide
+struct Slider {
+    name: &'static str,
+    value: f64,
+    step: f64,
+    max: f64,
+    color: Color,
+}
+
+const INITIAL_GAINS: [f64; 3] = [20.0, 0.02, 0.0];
+
+/// Everything the UI shows and the keys change
+struct Demo {
+    controller: PidController,
+    sliders: [Slider; 3],
+    selected: usize,
+    room: ThermalRoomPlant,
+    speed: usize,
+    /// Simulated seconds owed to the wall clock, run off a step at a time
+    backlog: f64,
+    steps: u64,
+    time: f64,
+    output: f64,
+    temperature_data: Vec<(f64, f64)>,
+    wall_data: Vec<(f64, f64)>,
+    setpoint_data: Vec<(f64, f64)>,
+    outdoor_data: Vec<(f64, f64)>,
+    output_data: Vec<(f64, f64)>,
+    event_log: VecDeque<(f64, String)>,
+}
+
+impl Demo {
+    fn new() -> Self {
+        let [kp, ki, kd] = INITIAL_GAINS;
+        let config = ControllerConfig::builder()
+            .with_kp(kp)
+            .with_ki(ki)
+            .with_kd(kd)
+            .with_output_limits(0.0, 100.0)
+            .with_setpoint(INITIAL_SETPOINT)
+            .with_anti_windup(true)
+            .build()
+            .expect("Invalid PID config");
+
+        let mut demo = Self {
+            controller: PidController::new(config),
+            sliders: [
+                Slider {
+                    name: "Kp",
+                    value: kp,
+                    step: 1.0,
+                    max: 100.0,
+                    color: Color::LightRed,
+                },
+                Slider {
+                    name: "Ki",
+                    value: ki,
+                    step: 0.005,
+                    max: 0.2,
+                    color: Color::LightGreen,
+                },
+                Slider {
+                    name: "Kd",
+                    value: kd,
+                    step: 100.0,
+                    max: 5000.0,
+                    color: Color::LightBlue,
+                },
+            ],
+            selected: 0,
+            room: ThermalRoomPlant::new(INITIAL_TEMPERATURE, OUTDOOR_TEMPERATURE),
+            speed: INITIAL_SPEED,
+            backlog: 0.0,
+            steps: 0,
+            time: 0.0,
+            output: 0.0,
+            temperature_data: Vec::with_capacity(HISTORY_POINTS + 1),
+            wall_data: Vec::with_capacity(HISTORY_POINTS + 1),
+            setpoint_data: Vec::with_capacity(HISTORY_POINTS + 1),
+            outdoor_data: Vec::with_capacity(HISTORY_POINTS + 1),
+            output_data: Vec::with_capacity(HISTORY_POINTS + 1),
+            event_log: VecDeque::new(),
+        };
+        demo.log(format!("Heating up from {INITIAL_TEMPERATURE:.1}°C"));
+        demo
+    }
+
+    fn heat_wave(&self) -> bool {
+        self.room.outdoor_temperature() > OUTDOOR_TEMPERATURE
+    }
+
+    fn log(&mut self, message: impl Into<String>) {
+        self.event_log.push_back((self.time, message.into()));
+        if self.event_log.len() > 3 {
+            self.event_log.pop_front();
+        }
+    }
+
+    /// Run as many control periods as `elapsed` wall-clock time covers at
+    /// the current speed
+    fn advance(&mut self, elapsed: Duration) {
+        self.backlog += elapsed.as_secs_f64() * SPEEDS[self.speed];
+        while self.backlog >= DT {
+            self.step();
+            self.backlog -= DT;
+        }
+    }
+
+    /// Advance the room and the controller by one control period
+    fn step(&mut self) {
+        self.output = self
+            .controller
+            .compute(self.room.temperature(), DT)
+            .expect("Failed to compute");
+        self.room.step(self.output, DT);
+        self.time += DT;
+        self.steps += 1;
+        if !self.steps.is_multiple_of(SAMPLE_STEPS) {
+            return;
+        }
+
+        let setpoint = self.controller.setpoint();
+        for (data, value) in [
+            (&mut self.temperature_data, self.room.temperature()),
+            (&mut self.wall_data, self.room.wall_temperature()),
+            (&mut self.setpoint_data, setpoint),
+            (&mut self.outdoor_data, self.room.outdoor_temperature()),
+            (&mut self.output_data, self.output),
+        ] {
+            data.push((self.time, value));
+            if data.len() > HISTORY_POINTS {
+                data.remove(0);
+            }
+        }
+    }
+
+    /// Move the selected slider by `steps` and hand the new gain to the controller
+    fn nudge_gain(&mut self, steps: f64) {
+        let slider = &mut self.sliders[self.selected];
+        slider.value = (slider.value + steps * slider.step).clamp(0.0, slider.max);
+        // Snap to the step grid so repeated nudges don't accumulate rounding
+        slider.value = (slider.value / slider.step).round() * slider.step;
+        let value = slider.value;
+        let result = match self.selected {
+            0 => self.controller.set_kp(value),
+            1 => self.controller.set_ki(value),
+            _ => self.controller.set_kd(value),
+        };
+        result.expect("Gain out of range");
+        let name = self.sliders[self.selected].name;
+        self.log(format!("{name} set to {value:.3}"));
+    }
+
+    fn reset_gains(&mut self) {
+        for (slider, gain) in self.sliders.iter_mut().zip(INITIAL_GAINS) {
+            slider.value = gain;
+        }
+        let [kp, ki, kd] = INITIAL_GAINS;
+        self.controller.set_kp(kp).expect("Invalid Kp");
+        self.controller.set_ki(ki).expect("Invalid Ki");
+        self.controller.set_kd(kd).expect("Invalid Kd");
+        self.log("Gains reset");
+    }
+
+    fn nudge_setpoint(&mut self, delta: f64) {
+        let setpoint = (self.controller.setpoint() + delta).clamp(MIN_SETPOINT, MAX_SETPOINT);
+        self.controller
+            .set_setpoint(setpoint)
+            .expect("Invalid setpoint");
+        self.log(format!("Setpoint {setpoint:.1}°C"));
+    }
+
+    fn toggle_window(&mut self) {
+        let open = !self.room.window_open();
+        self.room.set_window_open(open);
+        self.log(if open {
+            "WINDOW OPEN: heat pours out"
+        } else {
+            "Window shut"
+        });
+    }
+
+    fn toggle_heat_wave(&mut self) {
+        if self.heat_wave() {
+            self.room.set_outdoor_temperature(OUTDOOR_TEMPERATURE);
+            self.log("Heat wave over");
+        } else {
+            let outdoor = OUTDOOR_TEMPERATURE + HEAT_WAVE_RISE;
+            self.room.set_outdoor_temperature(outdoor);
+            self.log(format!("HEAT WAVE: outdoors at {outdoor:.0}°C"));
+        }
+    }
+
+    fn change_speed(&mut self, faster: bool) {
+        self.speed = if faster {
+            (self.speed + 1).min(SPEEDS.len() - 1)
+        } else {
+            self.speed.saturating_sub(1)
+        };
+        self.log(format!("Speed {}x", SPEEDS[self.speed]));
+    }
+
+    /// Returns false when the user asked to quit
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab | KeyCode::Down => self.selected = (self.selected + 1) % 3,
+            KeyCode::BackTab | KeyCode::Up => self.selected = (self.selected + 2) % 3,
+            KeyCode::Right => self.nudge_gain(1.0),
+            KeyCode::Left => self.nudge_gain(-1.0),
+            KeyCode::Char('r') => self.reset_gains(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.nudge_setpoint(SETPOINT_STEP),
+            KeyCode::Char('-') => self.nudge_setpoint(-SETPOINT_STEP),
+            KeyCode::Char('w') => self.toggle_window(),
+            KeyCode::Char('h') => self.toggle_heat_wave(),
+            KeyCode::Char(']') => self.change_speed(true),
+            KeyCode::Char('[') => self.change_speed(false),
+            _ => {}
+        }
+        true
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Set up terminal
+    enable_raw_mode()?;
+    io::stdout().execute(EnterAlternateScreen)?;
+    let backend = ratatui::backend::CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut demo = Demo::new();
+    let mut running = true;
+    let mut last_frame = Instant::now();
+    while running {
+        let frame_start = Instant::now();
+        demo.advance(frame_start - last_frame);
+        last_frame = frame_start;
+        terminal.draw(|f| draw_ui(f, &demo))?;
+
+        // Handle every key that arrives before the next frame is due
+        while let Some(remaining) = FRAME_TIME.checked_sub(frame_start.elapsed()) {
+            if !event::poll(remaining)? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                if !demo.handle_key(key.code) {
+                    running = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Restore terminal
+    disable_raw_mode()?;
+    io::stdout().execute(LeaveAlternateScreen)?;
+
+    let stats = demo.controller.get_statistics();
+    let [kp, ki, kd] = demo.sliders.map(|slider| slider.value);
+
+    println!("\nThermostat — Results");
+    println!("====================");
+    println!("PID gains:        Kp={kp:.3}  Ki={ki:.3}  Kd={kd:.3}");
+    println!("Simulated time:   {:.0} min", demo.time / 60.0);
+    println!("Final setpoint:   {:.1} °C", demo.controller.setpoint());
+    println!("Final temperature:{:>6.2} °C", demo.room.temperature());
+    println!("Average error:    {:.2} °C", stats.average_error);
+    println!("Max overshoot:    {:.2} °C", stats.max_overshoot);
+    // Percent-seconds of duty to kWh at the heater's rated power
+    let energy = stats.output_total / 100.0 * ThermalRoomPlant::DEFAULT_HEATER_POWER / 3.6e6;
+    println!("Heater energy:    {energy:.2} kWh");
+
+    Ok(())
+}
+
+fn draw_ui(f: &mut Frame, demo: &Demo) {
+    let layout = Layout::vertical([
+        Constraint::Length(3), // status
+        Constraint::Length(5), // gain sliders
+        Constraint::Length(3), // disturbance buttons
+        Constraint::Min(8),    // temperature chart
+        Constraint::Length(8), // output chart
+        Constraint::Length(5), // event log and keys
+    ])
+    .split(f.area());
+
+    draw_status(f, demo, layout[0]);
+    draw_sliders(f, demo, layout[1]);
+    draw_buttons(f, demo, layout[2]);
+
+    let time_max = demo.time.max(HISTORY_SECONDS);
+    let time_min = time_max - HISTORY_SECONDS;
+    let setpoint = demo.controller.setpoint();
+    let temperature = demo.room.temperature();
+    let outdoor = demo.room.outdoor_temperature();
+    let low = (OUTDOOR_TEMPERATURE - 1.0).min(temperature - 1.0);
+    let high = (setpoint + 4.0).max(temperature + 1.0).max(outdoor + 1.0);
+    draw_chart(
+        f,
+        layout[3],
+        " Temperature (°C) ",
+        vec![
+            dataset("room", &demo.temperature_data, Color::Red),
+            dataset("walls", &demo.wall_data, Color::Magenta),
+            dataset("setpoint", &demo.setpoint_data, Color::Yellow),
+            dataset("outdoor", &demo.outdoor_data, Color::Blue),
+        ],
+        [time_min, time_max],
+        [low.floor(), high.ceil()],
+    );
+    draw_chart(
+        f,
+        layout[4],
+        " Heater output (%) ",
+        vec![dataset("output", &demo.output_data, Color::Green)],
+        [time_min, time_max],
+        [0.0, 100.0],
+    );
+
+    draw_log(f, demo, layout[5]);
+}
+
+fn draw_status(f: &mut Frame, demo: &Demo, area: Rect) {
+    let setpoint = demo.controller.setpoint();
+    let temperature = demo.room.temperature();
+    let error = (setpoint - temperature).abs();
+    let temperature_color = if error < 0.3 {
+        Color::Green
+    } else if error < 1.5 {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+
+    let line = Line::from(vec![
+        Span::raw(format!(" t={:.1} min  Room=", demo.time / 60.0)),
+        Span::styled(
+            format!("{temperature:.2}°C"),
+            Style::default()
+                .fg(temperature_color)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(
+            "  Walls={:.1}°C  Setpoint={setpoint:.1}°C  Outdoor={:.0}°C  Heater={:.0}%  Speed={}x",
+            demo.room.wall_temperature(),
+            demo.room.outdoor_temperature(),
+            demo.output,
+            SPEEDS[demo.speed],
+        )),
+    ]);
+
+    let status = Paragraph::new(line).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Thermostat ")
+            .title_alignment(Alignment::Center),
+    );
+    f.render_widget(status, area);
+}
+
+fn draw_sliders(f: &mut Frame, demo: &Demo, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Gains  (Tab select, Left/Right adjust, R reset) ");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::vertical([Constraint::Length(1); 3]).split(inner);
+    for (index, (slider, row)) in demo.sliders.iter().zip(rows.iter()).enumerate() {
+        let selected = index == demo.selected;
+        let marker = if selected { "▶" } else { " " };
+        let label_style = if selected {
+            Style::default()
+                .fg(slider.color)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        let gauge = LineGauge::default()
+            .label(Span::styled(
+                format!("{marker} {} {:>8.3} ", slider.name, slider.value),
+                label_style,
+            ))
+            .ratio((slider.value / slider.max).clamp(0.0, 1.0))
+            .filled_style(Style::default().fg(slider.color))
+            .unfilled_style(Style::default().fg(Color::DarkGray));
+        f.render_widget(gauge, *row);
+    }
+}
+
+fn draw_buttons(f: &mut Frame, demo: &Demo, area: Rect) {
+    let button = |key: &str, label: &str, active: bool, color: Color| {
+        let style = if active {
+            Style::default().fg(Color::Black).bg(color)
+        } else {
+            Style::default().fg(color)
+        };
+        Span::styled(format!(" [{key}] {label} "), style)
+    };
+
+    let line = Line::from(vec![
+        Span::raw(" "),
+        button(
+            "W",
+            "Open window",
+            demo.room.window_open(),
+            Color::LightBlue,
+        ),
+        Span::raw("  "),
+        button("H", "Heat wave", demo.heat_wave(), Color::LightRed),
+        Span::raw("  "),
+        button("+/-", "Setpoint", false, Color::Yellow),
+        Span::raw("  "),
+        button("[/]", "Speed", false, Color::Cyan),
+        Span::raw("  "),
+        button("Q", "Quit", false, Color::DarkGray),
+    ]);
+
+    let buttons = Paragraph::new(line).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Disturbances "),
+    );
+    f.render_widget(buttons, area);
+}
+
+fn dataset<'a>(name: &'a str, data: &'a [(f64, f64)], color: Color) -> Dataset<'a> {
+    Dataset::default()
+        .name(name)
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(color))
+        .data(data)
+}
+
+fn draw_chart(
+    f: &mut Frame,
+    area: Rect,
+    title: &str,
+    datasets: Vec<Dataset>,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+) {
+    let minutes = |seconds: f64| format!("{:.0}m", seconds / 60.0);
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds(x_bounds)
+                .labels(vec![
+                    Span::raw(minutes(x_bounds[0])),
+                    Span::raw(minutes((x_bounds[0] + x_bounds[1]) / 2.0)),
+                    Span::raw(minutes(x_bounds[1])),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds(y_bounds)
+                .labels(vec![
+                    Span::raw(format!("{:.0}", y_bounds[0])),
+                    Span::raw(format!("{:.0}", (y_bounds[0] + y_bounds[1]) / 2.0)),
+                    Span::raw(format!("{:.0}", y_bounds[1])),
+                ]),
+        );
+    f.render_widget(chart, area);
+}
+
+fn draw_log(f: &mut Frame, demo: &Demo, area: Rect) {
+    let lines: Vec<Line> = demo
+        .event_log
+        .iter()
+        .map(|(time, message)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:>5.1} min  ", time / 60.0),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(message.as_str()),
+            ])
+        })
+        .collect();
+    let log = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Events "));
+    f.render_widget(log, area);
+}
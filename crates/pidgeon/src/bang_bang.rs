@@ -0,0 +1,205 @@
+use crate::error::PidError;
+use crate::traits::Controller;
+
+/// On/off controller with a hysteresis band, for actuators that only
+/// switch: a heater relay, a compressor, a solenoid valve.
+///
+/// Direct acting (the default) it switches on once the process value falls
+/// to `setpoint - hysteresis / 2` and off once it rises to
+/// `setpoint + hysteresis / 2`, as for heating; reverse acting it does the
+/// opposite, as for cooling. Inside the band it keeps its last state. A
+/// minimum switch time stops it cycling a compressor faster than it can
+/// take.
+///
+/// ```
+/// use pidgeon::{BangBangController, Controller};
+///
+/// let mut thermostat = BangBangController::new(21.0, 1.0, 0.0, 100.0).unwrap();
+/// assert_eq!(thermostat.compute(20.0, 1.0).unwrap(), 100.0);
+/// assert_eq!(thermostat.compute(21.3, 1.0).unwrap(), 100.0);
+/// assert_eq!(thermostat.compute(21.5, 1.0).unwrap(), 0.0);
+/// assert_eq!(thermostat.compute(20.8, 1.0).unwrap(), 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BangBangController {
+    setpoint: f64,
+    hysteresis: f64,
+    off_output: f64,
+    on_output: f64,
+    reverse: bool,
+    min_switch_time: f64,
+    /// `None` before the first sample
+    on: Option<bool>,
+    since_switch: f64,
+}
+
+impl BangBangController {
+    /// Creates a direct-acting controller around `setpoint` with a band
+    /// `hysteresis` wide, outputting `off_output` or `on_output`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if any argument is
+    /// non-finite, `hysteresis` is negative, or the two outputs are equal.
+    pub fn new(
+        setpoint: f64,
+        hysteresis: f64,
+        off_output: f64,
+        on_output: f64,
+    ) -> Result<Self, PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        if !hysteresis.is_finite() || hysteresis < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "hysteresis must be a finite non-negative number",
+            ));
+        }
+        if !off_output.is_finite() || !on_output.is_finite() || off_output == on_output {
+            return Err(PidError::InvalidParameter(
+                "on and off outputs must be distinct finite numbers",
+            ));
+        }
+        Ok(Self {
+            setpoint,
+            hysteresis,
+            off_output,
+            on_output,
+            reverse: false,
+            min_switch_time: 0.0,
+            on: None,
+            since_switch: 0.0,
+        })
+    }
+
+    /// Switches on above the band and off below it instead, as for cooling.
+    pub fn with_reverse_action(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Holds each state for at least `seconds` before switching again.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `seconds` is non-finite or
+    /// negative.
+    pub fn with_min_switch_time(mut self, seconds: f64) -> Result<Self, PidError> {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "min_switch_time must be a finite non-negative number",
+            ));
+        }
+        self.min_switch_time = seconds;
+        Ok(self)
+    }
+
+    /// Takes a process value `dt` seconds after the previous one and
+    /// returns the on or off output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` is
+    /// non-finite or `dt` is non-finite / non-positive.
+    pub fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+        // Positive when the process is short of the setpoint in the
+        // direction the actuator pushes it
+        let demand = if self.reverse {
+            process_value - self.setpoint
+        } else {
+            self.setpoint - process_value
+        };
+        let half_band = self.hysteresis / 2.0;
+        let on = match self.on {
+            None => demand > 0.0,
+            Some(on) if self.since_switch + dt < self.min_switch_time => on,
+            Some(true) => demand > -half_band,
+            Some(false) => demand >= half_band,
+        };
+        if self.on == Some(on) {
+            self.since_switch += dt;
+        } else {
+            self.since_switch = 0.0;
+        }
+        self.on = Some(on);
+        Ok(self.output())
+    }
+
+    /// Output for the current state; the off output before the first
+    /// sample.
+    pub fn output(&self) -> f64 {
+        if self.on == Some(true) {
+            self.on_output
+        } else {
+            self.off_output
+        }
+    }
+
+    /// Whether the actuator is on.
+    pub fn is_on(&self) -> bool {
+        self.on == Some(true)
+    }
+
+    /// Returns the current setpoint.
+    pub fn setpoint(&self) -> f64 {
+        self.setpoint
+    }
+
+    /// Updates the setpoint at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        self.setpoint = setpoint;
+        Ok(())
+    }
+
+    /// Width of the hysteresis band.
+    pub fn hysteresis(&self) -> f64 {
+        self.hysteresis
+    }
+
+    /// Forgets the state; the next sample decides it afresh.
+    pub fn reset(&mut self) {
+        self.on = None;
+        self.since_switch = 0.0;
+    }
+}
+
+impl Controller for BangBangController {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        BangBangController::compute(self, process_value, dt)
+    }
+
+    fn setpoint(&self) -> f64 {
+        BangBangController::setpoint(self)
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        BangBangController::set_setpoint(self, setpoint)
+    }
+
+    fn reset(&mut self) {
+        BangBangController::reset(self)
+    }
+}
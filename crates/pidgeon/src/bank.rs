@@ -0,0 +1,466 @@
+use crate::config::ControllerConfig;
+use crate::enums::{AntiWindupMode, DerivativeMode, ProportionalShape};
+use crate::error::PidError;
+use crate::lanes::{Lanes, LANES};
+use crate::state::PidState;
+
+/// Configuration and state of [`LANES`] controllers, one field per array so
+/// each can be loaded as [`Lanes`]. Flags are `1.0` or `0.0`.
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    kp: [f64; LANES],
+    ki: [f64; LANES],
+    kd: [f64; LANES],
+    /// Gains for a negative previous output; the same as `kp`/`ki`/`kd` on
+    /// lanes without separate ones
+    negative_kp: [f64; LANES],
+    negative_ki: [f64; LANES],
+    negative_kd: [f64; LANES],
+    asymmetric: [f64; LANES],
+    min_output: [f64; LANES],
+    max_output: [f64; LANES],
+    setpoint: [f64; LANES],
+    has_setpoint_filter: [f64; LANES],
+    /// `1.0` on lanes without a setpoint filter, so the division stays finite
+    setpoint_time_constant: [f64; LANES],
+    deadband: [f64; LANES],
+    error_squared: [f64; LANES],
+    gap: [f64; LANES],
+    /// `1.0` on lanes without error-squared control, so the division stays finite
+    full_gain_error: [f64; LANES],
+    gap_half_width: [f64; LANES],
+    gap_ratio: [f64; LANES],
+    filter_coeff: [f64; LANES],
+    limits_derivative: [f64; LANES],
+    /// `-inf`/`inf` on lanes without derivative limits
+    derivative_min: [f64; LANES],
+    derivative_max: [f64; LANES],
+    on_error: [f64; LANES],
+    conditional: [f64; LANES],
+    back_calculation: [f64; LANES],
+    /// `1.0` on lanes without back-calculation, so the division stays finite
+    tracking_time: [f64; LANES],
+    integral: [f64; LANES],
+    prev_error: [f64; LANES],
+    prev_measurement: [f64; LANES],
+    prev_filtered: [f64; LANES],
+    last_output: [f64; LANES],
+    filtered_setpoint: [f64; LANES],
+    first_run: [f64; LANES],
+}
+
+/// Lanes past the last controller run this harmless loop
+const PADDING: Chunk = Chunk {
+    kp: [0.0; LANES],
+    ki: [0.0; LANES],
+    kd: [0.0; LANES],
+    negative_kp: [0.0; LANES],
+    negative_ki: [0.0; LANES],
+    negative_kd: [0.0; LANES],
+    asymmetric: [0.0; LANES],
+    min_output: [-1.0; LANES],
+    max_output: [1.0; LANES],
+    setpoint: [0.0; LANES],
+    has_setpoint_filter: [0.0; LANES],
+    setpoint_time_constant: [1.0; LANES],
+    deadband: [0.0; LANES],
+    error_squared: [0.0; LANES],
+    gap: [0.0; LANES],
+    full_gain_error: [1.0; LANES],
+    gap_half_width: [1.0; LANES],
+    gap_ratio: [1.0; LANES],
+    filter_coeff: [1.0; LANES],
+    limits_derivative: [0.0; LANES],
+    derivative_min: [f64::NEG_INFINITY; LANES],
+    derivative_max: [f64::INFINITY; LANES],
+    on_error: [0.0; LANES],
+    conditional: [0.0; LANES],
+    back_calculation: [0.0; LANES],
+    tracking_time: [1.0; LANES],
+    integral: [0.0; LANES],
+    prev_error: [0.0; LANES],
+    prev_measurement: [0.0; LANES],
+    prev_filtered: [0.0; LANES],
+    last_output: [0.0; LANES],
+    filtered_setpoint: [0.0; LANES],
+    first_run: [1.0; LANES],
+};
+
+fn flag(set: bool) -> f64 {
+    if set {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Chunk {
+    fn set_config(&mut self, lane: usize, config: &ControllerConfig) {
+        let (kp, ki, kd) = config.gains_for(false);
+        self.kp[lane] = kp;
+        self.ki[lane] = ki;
+        self.kd[lane] = kd;
+        let (negative_kp, negative_ki, negative_kd) = config.gains_for(true);
+        self.negative_kp[lane] = negative_kp;
+        self.negative_ki[lane] = negative_ki;
+        self.negative_kd[lane] = negative_kd;
+        self.asymmetric[lane] = flag(config.negative_gains.is_some());
+        self.min_output[lane] = config.min_output;
+        self.max_output[lane] = config.max_output;
+        self.setpoint[lane] = config.setpoint;
+        self.has_setpoint_filter[lane] = flag(config.setpoint_filter.is_some());
+        self.setpoint_time_constant[lane] = config.setpoint_filter.unwrap_or(1.0);
+        self.deadband[lane] = config.deadband;
+        let (error_squared, full_gain_error) = match config.proportional_shape {
+            ProportionalShape::ErrorSquared { full_gain_error } => (true, full_gain_error),
+            _ => (false, 1.0),
+        };
+        let (gap, gap_half_width, gap_ratio) = match config.proportional_shape {
+            ProportionalShape::Gap {
+                half_width,
+                gain_ratio,
+            } => (true, half_width, gain_ratio),
+            _ => (false, 1.0, 1.0),
+        };
+        self.error_squared[lane] = flag(error_squared);
+        self.gap[lane] = flag(gap);
+        self.full_gain_error[lane] = full_gain_error;
+        self.gap_half_width[lane] = gap_half_width;
+        self.gap_ratio[lane] = gap_ratio;
+        self.filter_coeff[lane] = config.derivative_filter_coeff;
+        let (derivative_min, derivative_max) = config
+            .derivative_limits
+            .unwrap_or((f64::NEG_INFINITY, f64::INFINITY));
+        self.limits_derivative[lane] = flag(config.derivative_limits.is_some());
+        self.derivative_min[lane] = derivative_min;
+        self.derivative_max[lane] = derivative_max;
+        self.on_error[lane] = flag(config.derivative_mode == DerivativeMode::OnError);
+        let (conditional, back_calculation, tracking_time) = match config.anti_windup_mode {
+            AntiWindupMode::None => (false, false, 1.0),
+            AntiWindupMode::Conditional => (true, false, 1.0),
+            AntiWindupMode::BackCalculation { tracking_time } => (false, true, tracking_time),
+        };
+        self.conditional[lane] = flag(conditional);
+        self.back_calculation[lane] = flag(back_calculation);
+        self.tracking_time[lane] = tracking_time;
+    }
+
+    fn set_state(&mut self, lane: usize, state: &PidState) {
+        self.integral[lane] = state.integral_contribution;
+        self.prev_error[lane] = state.prev_error;
+        self.prev_measurement[lane] = state.prev_measurement;
+        self.prev_filtered[lane] = state.prev_filtered_derivative;
+        self.last_output[lane] = state.last_output;
+        self.filtered_setpoint[lane] = state.filtered_setpoint;
+        self.first_run[lane] = flag(state.first_run);
+    }
+
+    fn state(&self, lane: usize) -> PidState {
+        PidState {
+            integral_contribution: self.integral[lane],
+            prev_error: self.prev_error[lane],
+            prev_measurement: self.prev_measurement[lane],
+            prev_filtered_derivative: self.prev_filtered[lane],
+            last_output: self.last_output[lane],
+            filtered_setpoint: self.filtered_setpoint[lane],
+            first_run: self.first_run[lane] != 0.0,
+        }
+    }
+
+    /// [`pid_compute`](crate::pid_compute) on every lane, operation for
+    /// operation, with branches turned into selects so results match it bit
+    /// for bit.
+    fn compute(&mut self, process_value: Lanes, dt: Lanes) -> Lanes {
+        let zero = Lanes::splat(0.0);
+        let one = Lanes::splat(1.0);
+        let load = Lanes::from;
+
+        let first_run = load(self.first_run).gt(zero);
+        let on_error = load(self.on_error).gt(zero);
+        let conditional = load(self.conditional).gt(zero);
+        let back_calculation = load(self.back_calculation).gt(zero);
+        let asymmetric = load(self.asymmetric).gt(zero);
+        let negative = load(self.last_output).lt(zero);
+        let (kp, ki, kd) = (load(self.kp), load(self.ki), load(self.kd));
+        let (negative_kp, negative_kd) = (load(self.negative_kp), load(self.negative_kd));
+        let active_kp = negative.select(negative_kp, kp);
+        let active_kd = negative.select(negative_kd, kd);
+
+        let setpoint = load(self.setpoint);
+        let start = first_run.select(process_value, load(self.filtered_setpoint));
+        let lagged = start + dt / (load(self.setpoint_time_constant) + dt) * (setpoint - start);
+        let has_setpoint_filter = load(self.has_setpoint_filter).gt(zero);
+        let target = has_setpoint_filter.select(lagged, setpoint);
+        let filtered_setpoint = has_setpoint_filter.select(lagged, load(self.filtered_setpoint));
+        let error = target - process_value;
+        let deadband = load(self.deadband);
+        let sign = error.lt(zero).select(-one, one);
+        let working_error = error
+            .abs()
+            .le(deadband)
+            .select(zero, error - deadband * sign);
+
+        let ki = negative.select(load(self.negative_ki), ki);
+        let squared = working_error * working_error.abs() / load(self.full_gain_error);
+        let half_width = load(self.gap_half_width);
+        let gap_ratio = load(self.gap_ratio);
+        let error_sign = working_error.lt(zero).select(-one, one);
+        let gapped = working_error.abs().le(half_width).select(
+            gap_ratio * working_error,
+            working_error - (one - gap_ratio) * half_width * error_sign,
+        );
+        let proportional_error = load(self.error_squared).gt(zero).select(
+            squared,
+            load(self.gap).gt(zero).select(gapped, working_error),
+        );
+        let p_term = active_kp * proportional_error;
+        let integral_step = ki * working_error * dt;
+        let integral = load(self.integral) + integral_step;
+
+        let raw_derivative = on_error.select(
+            (working_error - load(self.prev_error)) / dt,
+            -(process_value - load(self.prev_measurement)) / dt,
+        );
+        let limited = load(self.limits_derivative).gt(zero);
+        let derivative_min = load(self.derivative_min);
+        let derivative_max = load(self.derivative_max);
+        let limit = |d_term: Lanes| {
+            let d_term = d_term.lt(derivative_min).select(derivative_min, d_term);
+            d_term.gt(derivative_max).select(derivative_max, d_term)
+        };
+        let raw_derivative = (limited & active_kd.abs().gt(zero)).select(
+            limit(active_kd * raw_derivative) / active_kd,
+            raw_derivative,
+        );
+        let n = load(self.filter_coeff);
+        let alpha = n * dt / (one + n * dt);
+        let prev_filtered = load(self.prev_filtered);
+        let filtered = first_run.select(
+            zero,
+            prev_filtered + alpha * (raw_derivative - prev_filtered),
+        );
+        let d_term = first_run.select(zero, limit(active_kd * filtered));
+
+        let unclamped = p_term + integral + d_term;
+        let min_output = load(self.min_output);
+        let max_output = load(self.max_output);
+        let output = unclamped.lt(min_output).select(min_output, unclamped);
+        let output = output.gt(max_output).select(max_output, output);
+
+        let saturated = (output - unclamped).abs().gt(Lanes::splat(f64::EPSILON));
+        let integral = (saturated & conditional).select(integral - integral_step, integral);
+        let integral = (saturated & back_calculation).select(
+            integral + (output - unclamped) * dt / load(self.tracking_time),
+            integral,
+        );
+
+        let switched = asymmetric & (output.lt(zero) ^ negative);
+        let next_kp = negative.select(kp, negative_kp);
+        let next_kd = negative.select(kd, negative_kd);
+        let integral = switched.select(
+            integral
+                + ((active_kp - next_kp) * proportional_error
+                    + limited.select(
+                        limit(active_kd * filtered) - limit(next_kd * filtered),
+                        (active_kd - next_kd) * filtered,
+                    )),
+            integral,
+        );
+
+        self.integral = integral.to_array();
+        self.prev_error = working_error.to_array();
+        self.prev_measurement = process_value.to_array();
+        self.prev_filtered = filtered.to_array();
+        self.last_output = output.to_array();
+        self.filtered_setpoint = filtered_setpoint.to_array();
+        self.first_run = [0.0; LANES];
+        output
+    }
+}
+
+/// Many independent controllers stepped together, four at a time in SIMD
+/// lanes.
+///
+/// A bank suits plants with hundreds of similar loops sampled on one clock,
+/// such as every zone of a furnace or every cell of a battery pack. Each
+/// controller keeps its own [`ControllerConfig`] and [`PidState`], and
+/// [`compute`](Self::compute) produces exactly the outputs and states that
+/// [`pid_compute`](crate::pid_compute) would, bit for bit, in about half the
+/// time of calling it once per loop. Enable the `simd` feature to use explicit SIMD
+/// instructions rather than relying on auto-vectorization.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerBank, ControllerConfig};
+///
+/// let zone = |setpoint| {
+///     ControllerConfig::builder()
+///         .with_kp(2.0)
+///         .with_ki(0.1)
+///         .with_setpoint(setpoint)
+///         .with_output_limits(0.0, 100.0)
+///         .build()
+///         .unwrap()
+/// };
+///
+/// let mut bank = ControllerBank::new();
+/// for setpoint in [180.0, 200.0, 220.0] {
+///     bank.push(zone(setpoint));
+/// }
+///
+/// let temperatures = [175.0, 201.0, 150.0];
+/// let mut heater_duty = [0.0; 3];
+/// bank.compute(&temperatures, 0.1, &mut heater_duty).unwrap();
+///
+/// assert!(heater_duty[0] > 0.0);
+/// assert_eq!(heater_duty[1], 0.0);
+/// assert_eq!(heater_duty[2], 100.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ControllerBank {
+    configs: Vec<ControllerConfig>,
+    chunks: Vec<Chunk>,
+}
+
+impl ControllerBank {
+    /// Creates an empty bank.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a controller with fresh [`PidState::new`] state and returns its
+    /// index.
+    pub fn push(&mut self, config: ControllerConfig) -> usize {
+        let index = self.configs.len();
+        if index.is_multiple_of(LANES) {
+            self.chunks.push(PADDING);
+        }
+        let chunk = &mut self.chunks[index / LANES];
+        chunk.set_config(index % LANES, &config);
+        chunk.set_state(index % LANES, &PidState::new(&config));
+        self.configs.push(config);
+        index
+    }
+
+    /// Number of controllers in the bank.
+    pub fn len(&self) -> usize {
+        self.configs.len()
+    }
+
+    /// Returns `true` if the bank has no controllers.
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+
+    /// Configuration of the controller at `index`.
+    pub fn config(&self, index: usize) -> Option<&ControllerConfig> {
+        self.configs.get(index)
+    }
+
+    /// Current state of the controller at `index`.
+    pub fn state(&self, index: usize) -> Option<PidState> {
+        (index < self.len()).then(|| self.chunks[index / LANES].state(index % LANES))
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), PidError> {
+        if index < self.len() {
+            Ok(())
+        } else {
+            Err(PidError::InvalidParameter("no controller at that index"))
+        }
+    }
+
+    /// Replaces the configuration of the controller at `index`, keeping its
+    /// state.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if there is no controller at
+    /// `index`.
+    pub fn set_config(&mut self, index: usize, config: ControllerConfig) -> Result<(), PidError> {
+        self.check_index(index)?;
+        self.chunks[index / LANES].set_config(index % LANES, &config);
+        self.configs[index] = config;
+        Ok(())
+    }
+
+    /// Moves the setpoint of the controller at `index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if there is no controller at
+    /// `index` or `setpoint` is not finite. A setpoint outside the
+    /// controller's [`setpoint_limits`](ControllerConfig::setpoint_limits)
+    /// is clamped or rejected like [`PidController::set_setpoint`](crate::PidController::set_setpoint)
+    /// does, without the event.
+    pub fn set_setpoint(&mut self, index: usize, setpoint: f64) -> Result<(), PidError> {
+        self.check_index(index)?;
+        let setpoint = self.configs[index].limit_setpoint(setpoint)?;
+        self.chunks[index / LANES].setpoint[index % LANES] = setpoint;
+        self.configs[index].setpoint = setpoint;
+        Ok(())
+    }
+
+    /// Restores the controller at `index` to [`PidState::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if there is no controller at
+    /// `index`.
+    pub fn reset(&mut self, index: usize) -> Result<(), PidError> {
+        self.check_index(index)?;
+        let state = PidState::new(&self.configs[index]);
+        self.chunks[index / LANES].set_state(index % LANES, &state);
+        Ok(())
+    }
+
+    /// Restores every controller to [`PidState::new`].
+    pub fn reset_all(&mut self) {
+        for (index, config) in self.configs.iter().enumerate() {
+            self.chunks[index / LANES].set_state(index % LANES, &PidState::new(config));
+        }
+    }
+
+    /// Steps every controller once, reading `process_values[i]` for the
+    /// controller at index `i` and writing its output to `outputs[i]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if either slice's length differs
+    /// from [`len`](Self::len), `dt` is non-finite or non-positive, or any
+    /// process value is non-finite. No controller is stepped in that case.
+    pub fn compute(
+        &mut self,
+        process_values: &[f64],
+        dt: f64,
+        outputs: &mut [f64],
+    ) -> Result<(), PidError> {
+        if process_values.len() != self.len() || outputs.len() != self.len() {
+            return Err(PidError::InvalidParameter(
+                "process_values and outputs need one entry per controller",
+            ));
+        }
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_values.iter().all(|value| value.is_finite()) {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+
+        let dt = Lanes::splat(dt);
+        let pvs = process_values.chunks(LANES);
+        let outs = outputs.chunks_mut(LANES);
+        for ((chunk, pv), out) in self.chunks.iter_mut().zip(pvs).zip(outs) {
+            let mut lanes = [0.0; LANES];
+            lanes[..pv.len()].copy_from_slice(pv);
+            let output = chunk.compute(Lanes::from(lanes), dt).to_array();
+            out.copy_from_slice(&output[..out.len()]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+use heapless::Deque;
+
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::response::{bin_of, Bin, SetpointResponse};
+
+/// Seconds spent in each of `BINS` equal-width bins over `[low, high)`,
+/// in a fixed-size array.
+///
+/// The `no_std` counterpart of `Histogram`, which it converts into with
+/// `std`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BoundedHistogram<const BINS: usize> {
+    /// Lower edge of the first bin.
+    pub low: f64,
+    /// Upper edge of the last bin.
+    pub high: f64,
+    /// Seconds spent in each bin, lowest first.
+    pub bins: [f64; BINS],
+    /// Seconds spent below `low`.
+    pub below: f64,
+    /// Seconds spent at or above `high`.
+    pub above: f64,
+}
+
+impl<const BINS: usize> BoundedHistogram<BINS> {
+    const fn new(low: f64, high: f64) -> Self {
+        BoundedHistogram {
+            low,
+            high,
+            bins: [0.0; BINS],
+            below: 0.0,
+            above: 0.0,
+        }
+    }
+
+    /// Width of each bin.
+    pub fn bin_width(&self) -> f64 {
+        (self.high - self.low) / BINS as f64
+    }
+
+    /// Seconds counted in every bin, including those outside the range.
+    pub fn total(&self) -> f64 {
+        self.below + self.above + self.bins.iter().sum::<f64>()
+    }
+
+    fn record(&mut self, value: f64, dt: f64) {
+        match bin_of(self.low, self.high, BINS, value) {
+            Bin::Below => self.below += dt,
+            Bin::At(bin) => self.bins[bin] += dt,
+            Bin::Above => self.above += dt,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const BINS: usize> From<&BoundedHistogram<BINS>> for crate::Histogram {
+    fn from(histogram: &BoundedHistogram<BINS>) -> Self {
+        crate::Histogram {
+            low: histogram.low,
+            high: histogram.high,
+            bins: histogram.bins.to_vec(),
+            below: histogram.below,
+            above: histogram.above,
+        }
+    }
+}
+
+/// Controller statistics in fixed-capacity storage, for targets without an
+/// allocator.
+///
+/// Keeps what `ControllerStatistics` keeps -- error indices, rise and
+/// settling times, the last `RESPONSES` [`SetpointResponse`]s, and
+/// time-weighted histograms of the error and output with `BINS` bins each
+/// -- in a size fixed at compile time, so it can sit in a `static` next to
+/// the controller. Feed it after each [`pid_compute`](crate::pid_compute)
+/// with the same inputs; like `PidController` it runs on the `dt` values it
+/// is given, not a clock.
+///
+/// The error histogram spans five settled thresholds either side of zero,
+/// so each of the default ten bins is one threshold wide; the output
+/// histogram spans the output limits.
+///
+/// ```
+/// use pidgeon::{pid_compute, BoundedStatistics, ControllerConfig, PidState};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(0.5)
+///     .with_setpoint(10.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut state = PidState::new(&config);
+/// let mut stats: BoundedStatistics = BoundedStatistics::new();
+///
+/// let mut level = 0.0;
+/// for _ in 0..200 {
+///     let (output, next) = pid_compute(&config, &state, level, 0.1).unwrap();
+///     state = next;
+///     stats.update(&config, level, output, 0.1);
+///     level += output * 0.1;
+/// }
+///
+/// assert!(stats.settling_time().is_some());
+/// assert_eq!(stats.responses().count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoundedStatistics<const RESPONSES: usize = 4, const BINS: usize = 10> {
+    settled_threshold: f64,
+    elapsed: f64,
+    error_integral: f64,
+    squared_error_integral: f64,
+    time_weighted_error_integral: f64,
+    max_error: f64,
+    rise_time: Option<f64>,
+    settle_time: Option<f64>,
+    output_total: f64,
+    responses: Deque<SetpointResponse, RESPONSES>,
+    /// Sign of the current response's step, or 0 with nowhere to go
+    direction: f64,
+    error_histogram: BoundedHistogram<BINS>,
+    output_histogram: Option<BoundedHistogram<BINS>>,
+}
+
+impl<const RESPONSES: usize, const BINS: usize> BoundedStatistics<RESPONSES, BINS> {
+    /// Settled threshold unless one is given: `0.05`, as for
+    /// `PidController`.
+    pub const DEFAULT_SETTLED_THRESHOLD: f64 = 0.05;
+
+    /// Creates empty statistics with the default settled threshold.
+    pub const fn new() -> Self {
+        let span = 5.0 * Self::DEFAULT_SETTLED_THRESHOLD;
+        BoundedStatistics {
+            settled_threshold: Self::DEFAULT_SETTLED_THRESHOLD,
+            elapsed: 0.0,
+            error_integral: 0.0,
+            squared_error_integral: 0.0,
+            time_weighted_error_integral: 0.0,
+            max_error: 0.0,
+            rise_time: None,
+            settle_time: None,
+            output_total: 0.0,
+            responses: Deque::new(),
+            direction: 0.0,
+            error_histogram: BoundedHistogram::new(-span, span),
+            output_histogram: None,
+        }
+    }
+
+    /// Sets the error within which the process counts as at the setpoint,
+    /// for rise and settling times and the error histogram's range, and
+    /// starts afresh.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `threshold` is not finite
+    /// and positive.
+    pub fn with_settled_threshold(mut self, threshold: f64) -> Result<Self, PidError> {
+        if !threshold.is_finite() || threshold <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "settled threshold must be a finite positive number",
+            ));
+        }
+        self.settled_threshold = threshold;
+        self.reset();
+        Ok(self)
+    }
+
+    /// Accounts for a step that ended `dt` seconds after the previous one,
+    /// with `process_value` measured against `config`'s setpoint and
+    /// `output` applied. Skip it for a step `pid_compute` rejected.
+    pub fn update(&mut self, config: &ControllerConfig, process_value: f64, output: f64, dt: f64) {
+        let setpoint = config.setpoint;
+        let error = setpoint - process_value;
+        self.elapsed += dt;
+        self.error_integral += error.abs() * dt;
+        self.squared_error_integral += error * error * dt;
+        self.time_weighted_error_integral += self.elapsed * error.abs() * dt;
+        self.max_error = self.max_error.max(error.abs());
+        self.output_total += output.abs() * dt;
+
+        if error.abs() <= self.settled_threshold {
+            self.rise_time.get_or_insert(self.elapsed);
+            self.settle_time.get_or_insert(self.elapsed);
+        } else {
+            self.settle_time = None;
+        }
+
+        let current = self.responses.back().map(|response| response.setpoint);
+        if current != Some(setpoint) {
+            let (response, direction) =
+                SetpointResponse::open(self.elapsed - dt, current, setpoint, process_value);
+            if self.responses.is_full() {
+                self.responses.pop_front();
+            }
+            // Room was just made, unless the capacity is zero
+            let _ = self.responses.push_back(response);
+            self.direction = direction;
+        }
+        if let Some(response) = self.responses.back_mut() {
+            response.update(process_value, self.direction, self.settled_threshold, dt);
+        }
+
+        self.error_histogram.record(error, dt);
+        let (low, high) = (config.min_output, config.max_output);
+        let histogram = match &mut self.output_histogram {
+            Some(histogram) if histogram.low == low && histogram.high == high => histogram,
+            stale => stale.insert(BoundedHistogram::new(low, high)),
+        };
+        histogram.record(output, dt);
+    }
+
+    /// Clears everything but the settled threshold.
+    pub fn reset(&mut self) {
+        let threshold = self.settled_threshold;
+        *self = Self::new();
+        let span = 5.0 * threshold;
+        self.settled_threshold = threshold;
+        self.error_histogram = BoundedHistogram::new(-span, span);
+    }
+
+    /// Loop time in seconds since the last reset.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Time-weighted mean absolute error, or `0.0` before the first step.
+    pub fn average_error(&self) -> f64 {
+        if self.elapsed > 0.0 {
+            self.error_integral / self.elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Largest absolute error.
+    pub fn max_error(&self) -> f64 {
+        self.max_error
+    }
+
+    /// Until the error first entered the settled threshold, if it has.
+    pub fn rise_time(&self) -> Option<f64> {
+        self.rise_time
+    }
+
+    /// Until the error entered the settled threshold for good, if it is
+    /// there now.
+    pub fn settling_time(&self) -> Option<f64> {
+        self.settle_time
+    }
+
+    /// Integral of the absolute error (IAE).
+    pub fn iae(&self) -> f64 {
+        self.error_integral
+    }
+
+    /// Integral of the squared error (ISE).
+    pub fn ise(&self) -> f64 {
+        self.squared_error_integral
+    }
+
+    /// Integral of the absolute error weighted by loop time (ITAE).
+    pub fn itae(&self) -> f64 {
+        self.time_weighted_error_integral
+    }
+
+    /// Time-integral of the output's magnitude, in output units × seconds.
+    pub fn output_total(&self) -> f64 {
+        self.output_total
+    }
+
+    /// The last `RESPONSES` setpoint responses, oldest first.
+    pub fn responses(&self) -> impl Iterator<Item = &SetpointResponse> {
+        self.responses.iter()
+    }
+
+    /// Time spent at each error.
+    pub fn error_histogram(&self) -> &BoundedHistogram<BINS> {
+        &self.error_histogram
+    }
+
+    /// Time spent at each output, once a step has been recorded.
+    pub fn output_histogram(&self) -> Option<&BoundedHistogram<BINS>> {
+        self.output_histogram.as_ref()
+    }
+}
+
+impl<const RESPONSES: usize, const BINS: usize> Default for BoundedStatistics<RESPONSES, BINS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The same figures as `PidController` statistics, so
+/// [`to_report`](crate::ControllerStatistics::to_report) works on them.
+#[cfg(feature = "std")]
+impl<const RESPONSES: usize, const BINS: usize> From<&BoundedStatistics<RESPONSES, BINS>>
+    for crate::ControllerStatistics
+{
+    fn from(stats: &BoundedStatistics<RESPONSES, BINS>) -> Self {
+        crate::ControllerStatistics {
+            average_error: stats.average_error(),
+            max_overshoot: stats.max_error,
+            settling_time: stats.settle_time.unwrap_or(stats.elapsed),
+            rise_time: stats.rise_time.unwrap_or(f64::NAN),
+            output_total: stats.output_total,
+            output_total_rollovers: 0,
+            elapsed: stats.elapsed,
+            settled: stats.settle_time.is_some(),
+            iae: stats.error_integral,
+            ise: stats.squared_error_integral,
+            itae: stats.time_weighted_error_integral,
+            responses: stats.responses.iter().cloned().collect(),
+            error_histogram: (&stats.error_histogram).into(),
+            output_histogram: stats.output_histogram.as_ref().map(Into::into),
+        }
+    }
+}
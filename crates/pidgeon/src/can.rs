@@ -0,0 +1,322 @@
+use crate::config::ControllerConfig;
+use crate::enums::Gain;
+use crate::error::PidError;
+use crate::state::PidState;
+
+/// Number of consecutive CAN IDs a [`CanCodec`] uses, starting at its base ID.
+pub const CAN_ID_SPAN: u32 = 5;
+
+/// Largest 11-bit (standard) CAN identifier.
+const MAX_STANDARD_ID: u32 = 0x7FF;
+/// Largest 29-bit (extended) CAN identifier.
+const MAX_EXTENDED_ID: u32 = 0x1FFF_FFFF;
+
+/// A classic CAN data frame of up to 8 bytes.
+///
+/// Deliberately minimal, so it converts to and from any driver's frame type
+/// (SocketCAN, `embedded-can`, a microcontroller's CAN peripheral) with a
+/// few field copies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CanFrame {
+    /// Identifier, 11 bits for standard frames and 29 bits for extended ones.
+    pub id: u32,
+    /// `true` for a 29-bit extended identifier.
+    pub extended: bool,
+    /// Number of valid bytes in `data`, 0 to 8.
+    pub len: u8,
+    /// Payload; bytes past `len` are zero.
+    pub data: [u8; 8],
+}
+
+impl CanFrame {
+    /// The valid bytes of the payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.data[..usize::from(self.len.min(8))]
+    }
+}
+
+/// Controller telemetry and commands carried in single CAN frames.
+///
+/// Values are `f32`, little-endian, as is usual on CANopen-style buses. Each
+/// message has its own identifier, offset from the [`CanCodec`]'s base ID:
+///
+/// | Offset | Message | Payload |
+/// |--------|---------|---------|
+/// | 0 | [`Status`](Self::Status) | process value, output (8 bytes) |
+/// | 1 | [`Target`](Self::Target) | setpoint, error (8 bytes) |
+/// | 2 | [`SetSetpoint`](Self::SetSetpoint) | setpoint (4 bytes) |
+/// | 3 | [`SetGain`](Self::SetGain) | gain (`0` = Kp, `1` = Ki, `2` = Kd), value (5 bytes) |
+/// | 4 | [`Reset`](Self::Reset) | none |
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CanMessage {
+    /// Telemetry: the measured process value and the clamped output.
+    Status {
+        /// Measured process value.
+        process_value: f32,
+        /// Clamped controller output.
+        output: f32,
+    },
+    /// Telemetry: the setpoint and the error after deadband.
+    Target {
+        /// Setpoint in effect.
+        setpoint: f32,
+        /// Error after deadband.
+        error: f32,
+    },
+    /// Command: move the setpoint.
+    SetSetpoint {
+        /// New setpoint.
+        setpoint: f32,
+    },
+    /// Command: change one gain.
+    SetGain {
+        /// Which gain to change.
+        gain: Gain,
+        /// New value.
+        value: f32,
+    },
+    /// Command: clear the controller's state.
+    Reset,
+}
+
+impl CanMessage {
+    /// `true` for messages sent to a controller rather than by it.
+    pub fn is_command(&self) -> bool {
+        matches!(
+            self,
+            CanMessage::SetSetpoint { .. } | CanMessage::SetGain { .. } | CanMessage::Reset
+        )
+    }
+
+    fn offset(&self) -> u32 {
+        match self {
+            CanMessage::Status { .. } => 0,
+            CanMessage::Target { .. } => 1,
+            CanMessage::SetSetpoint { .. } => 2,
+            CanMessage::SetGain { .. } => 3,
+            CanMessage::Reset => 4,
+        }
+    }
+}
+
+/// Encodes and decodes one controller's [`CanMessage`]s.
+///
+/// A codec owns [`CAN_ID_SPAN`] consecutive identifiers starting at its base
+/// ID, so controllers sharing a bus need base IDs at least that far apart.
+/// Lower identifiers win arbitration, so pick a base below less urgent
+/// traffic.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{CanCodec, CanMessage, ControllerConfig, PidState, pid_compute};
+///
+/// let codec = CanCodec::standard(0x180).unwrap();
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+///
+/// let (_, state) = pid_compute(&config, &PidState::default(), 45.0, 0.01).unwrap();
+/// let [status, target] = codec.telemetry(&config, &state, 45.0);
+/// assert_eq!(status.id, 0x180);
+/// assert_eq!(
+///     codec.decode(&target).unwrap(),
+///     Some(CanMessage::Target { setpoint: 50.0, error: 5.0 })
+/// );
+///
+/// // Commands arrive the same way
+/// let command = codec.encode(&CanMessage::SetSetpoint { setpoint: 60.0 });
+/// assert_eq!(command.id, 0x182);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CanCodec {
+    base_id: u32,
+    extended: bool,
+}
+
+impl CanCodec {
+    /// Uses 11-bit identifiers from `base_id` to `base_id + 4`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the identifiers would exceed `0x7FF`.
+    pub fn standard(base_id: u16) -> Result<Self, PidError> {
+        Self::new(u32::from(base_id), false, MAX_STANDARD_ID)
+    }
+
+    /// Uses 29-bit identifiers from `base_id` to `base_id + 4`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the identifiers would exceed
+    /// `0x1FFF_FFFF`.
+    pub fn extended(base_id: u32) -> Result<Self, PidError> {
+        Self::new(base_id, true, MAX_EXTENDED_ID)
+    }
+
+    fn new(base_id: u32, extended: bool, max_id: u32) -> Result<Self, PidError> {
+        if base_id > max_id - (CAN_ID_SPAN - 1) {
+            return Err(PidError::InvalidParameter(
+                "CAN base ID leaves no room for the message IDs",
+            ));
+        }
+        Ok(CanCodec { base_id, extended })
+    }
+
+    /// First identifier used by this codec.
+    pub fn base_id(&self) -> u32 {
+        self.base_id
+    }
+
+    /// `true` if the codec uses 29-bit identifiers.
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+
+    /// Identifiers of the command messages, for receive filters.
+    pub fn command_ids(&self) -> [u32; 3] {
+        [self.base_id + 2, self.base_id + 3, self.base_id + 4]
+    }
+
+    /// Packs `message` into a frame.
+    pub fn encode(&self, message: &CanMessage) -> CanFrame {
+        let mut data = [0u8; 8];
+        let len = match *message {
+            CanMessage::Status {
+                process_value: first,
+                output: second,
+            }
+            | CanMessage::Target {
+                setpoint: first,
+                error: second,
+            } => {
+                data[..4].copy_from_slice(&first.to_le_bytes());
+                data[4..].copy_from_slice(&second.to_le_bytes());
+                8
+            }
+            CanMessage::SetSetpoint { setpoint } => {
+                data[..4].copy_from_slice(&setpoint.to_le_bytes());
+                4
+            }
+            CanMessage::SetGain { gain, value } => {
+                data[0] = match gain {
+                    Gain::Kp => 0,
+                    Gain::Ki => 1,
+                    Gain::Kd => 2,
+                };
+                data[1..5].copy_from_slice(&value.to_le_bytes());
+                5
+            }
+            CanMessage::Reset => 0,
+        };
+        CanFrame {
+            id: self.base_id + message.offset(),
+            extended: self.extended,
+            len,
+            data,
+        }
+    }
+
+    /// Unpacks a frame addressed to this codec.
+    ///
+    /// Returns `Ok(None)` for frames with another identifier, so every frame
+    /// on the bus can be passed in.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a frame with one of this
+    /// codec's identifiers has the wrong length, an unknown gain, or a
+    /// non-finite value.
+    pub fn decode(&self, frame: &CanFrame) -> Result<Option<CanMessage>, PidError> {
+        if frame.extended != self.extended
+            || frame.id < self.base_id
+            || frame.id - self.base_id >= CAN_ID_SPAN
+        {
+            return Ok(None);
+        }
+        let payload = frame.payload();
+        let expected_len = match frame.id - self.base_id {
+            0 | 1 => 8,
+            2 => 4,
+            3 => 5,
+            _ => 0,
+        };
+        if payload.len() != expected_len {
+            return Err(PidError::InvalidParameter(
+                "CAN frame has the wrong length for its message",
+            ));
+        }
+        let value = |at: usize| {
+            let value = f32::from_le_bytes([
+                payload[at],
+                payload[at + 1],
+                payload[at + 2],
+                payload[at + 3],
+            ]);
+            if value.is_finite() {
+                Ok(value)
+            } else {
+                Err(PidError::InvalidParameter(
+                    "CAN frame carries a non-finite value",
+                ))
+            }
+        };
+
+        let message = match frame.id - self.base_id {
+            0 => CanMessage::Status {
+                process_value: value(0)?,
+                output: value(4)?,
+            },
+            1 => CanMessage::Target {
+                setpoint: value(0)?,
+                error: value(4)?,
+            },
+            2 => CanMessage::SetSetpoint {
+                setpoint: value(0)?,
+            },
+            3 => CanMessage::SetGain {
+                gain: match payload[0] {
+                    0 => Gain::Kp,
+                    1 => Gain::Ki,
+                    2 => Gain::Kd,
+                    _ => {
+                        return Err(PidError::InvalidParameter(
+                            "CAN frame names an unknown gain",
+                        ))
+                    }
+                },
+                value: value(1)?,
+            },
+            _ => CanMessage::Reset,
+        };
+        Ok(Some(message))
+    }
+
+    /// Builds the [`Status`](CanMessage::Status) and
+    /// [`Target`](CanMessage::Target) frames for one cycle, from the
+    /// configuration and the state returned by
+    /// [`pid_compute`](crate::pid_compute).
+    pub fn telemetry(
+        &self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+    ) -> [CanFrame; 2] {
+        [
+            self.encode(&CanMessage::Status {
+                process_value: process_value as f32,
+                output: state.last_output as f32,
+            }),
+            self.encode(&CanMessage::Target {
+                setpoint: config.setpoint as f32,
+                error: state.prev_error as f32,
+            }),
+        ]
+    }
+}
@@ -1,5 +1,5 @@
 use crate::config::ControllerConfig;
-use crate::enums::{AntiWindupMode, DerivativeMode};
+use crate::enums::{AntiWindupMode, DerivativeMode, ProportionalShape};
 use crate::error::PidError;
 use crate::state::PidState;
 
@@ -12,17 +12,32 @@ use crate::state::PidState;
 ///
 /// # Algorithm
 ///
+/// 0. **Setpoint filter**: with [`setpoint_filter`](ControllerConfig::setpoint_filter)
+///    `T`, the error is taken against `filtered += dt / (T + dt) * (setpoint - filtered)`,
+///    which starts from the process value on the first run.
 /// 1. **Deadband**: errors within `+/- deadband` are zeroed for P and I terms
 ///    (D still sees the raw signal).
-/// 2. **P term**: `Kp * working_error`
+/// 2. **P term**: `Kp * working_error`, or `Kp` times the error reshaped per
+///    [`ProportionalShape`].
 /// 3. **I term**: accumulated as `integral += Ki * working_error * dt` (Ki baked in).
 /// 4. **D term** (skipped on first run):
-///    - Raw derivative: `-d(measurement)/dt` or `d(error)/dt` per [`DerivativeMode`].
+///    - Raw derivative: `-d(measurement)/dt` or `d(error)/dt` per [`DerivativeMode`],
+///      or `-rate` from [`pid_compute_with_rate`].
 ///    - IIR low-pass filter: `alpha = N*dt / (1 + N*dt)`, then
 ///      `filtered = prev + alpha * (raw - prev)`.
 ///    - Final: `Kd * filtered`.
-/// 5. **Clamp**: output = `clamp(P + I + D, min_output, max_output)`.
+///    - With [`derivative_limits`](ControllerConfig::derivative_limits), both
+///      `Kd * raw` (before filtering) and the final term are clamped to them.
+/// 5. **Clamp**: output = `clamp(P + I + D, min_output, max_output)`, with the
+///    term from [`pid_compute_with_feedforward`] added to the sum before clamping.
 /// 6. **Anti-windup**: if clamped, adjust the integral per [`AntiWindupMode`].
+/// 7. **Gain switch**: with [`negative_gains`](ControllerConfig::negative_gains),
+///    steps 2–4 use the set for the sign of the previous output. If this output
+///    lands on the other side, the integral is shifted by the difference in
+///    `P + D` between the two sets, so the next step continues without a bump.
+///
+/// [`pid_track`] replaces steps 5–7: the output is the tracked one, clamped,
+/// and the integral becomes whatever makes `P + I + D` equal to it.
 ///
 /// On the **first run** (`state.first_run == true`), the derivative term is zero
 /// and the output is `P + I` only.
@@ -63,6 +78,180 @@ pub fn pid_compute(
     state: &PidState,
     process_value: f64,
     dt: f64,
+) -> Result<(f64, PidState), PidError> {
+    compute_step(config, state, process_value, StepInputs::default(), dt)
+}
+
+/// [`pid_compute`] with the process value's rate of change supplied rather
+/// than differenced from the previous sample.
+///
+/// Pass a rate from a tachometer, a gyro, or a
+/// [`LuenbergerObserver`](crate::LuenbergerObserver) when differencing a
+/// noisy or coarsely quantized measurement makes the D term useless. The
+/// raw derivative becomes `-rate` in either [`DerivativeMode`], so setpoint
+/// steps never kick, and it still goes through the derivative filter; raise
+/// [`derivative_filter_coeff`](ControllerConfig::derivative_filter_coeff)
+/// if the rate is already clean. The first run skips D as usual.
+///
+/// # Errors
+///
+/// Returns [`PidError::InvalidParameter`] if `dt` is non-finite or
+/// non-positive, or if `process_value` or `rate` is non-finite.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidState, pid_compute_with_rate};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_kd(0.5)
+///     .with_output_limits(-10.0, 10.0)
+///     .build()
+///     .unwrap();
+///
+/// let (_, state) = pid_compute_with_rate(&config, &PidState::default(), 0.0, 0.0, 0.01).unwrap();
+/// // Rising at 2 units/s: D pushes back even though the position has not moved
+/// let (output, _) = pid_compute_with_rate(&config, &state, 0.0, 2.0, 0.01).unwrap();
+/// assert!(output < 0.0);
+/// ```
+pub fn pid_compute_with_rate(
+    config: &ControllerConfig,
+    state: &PidState,
+    process_value: f64,
+    rate: f64,
+    dt: f64,
+) -> Result<(f64, PidState), PidError> {
+    let inputs = StepInputs {
+        rate: Some(rate),
+        ..StepInputs::default()
+    };
+    compute_step(config, state, process_value, inputs, dt)
+}
+
+/// [`pid_compute`] with a feedforward term, in output units, added to
+/// `P + I + D` before the output is clamped.
+///
+/// The term is usually a measured disturbance passed through a
+/// [`Feedforward`](crate::Feedforward) compensator, so the output moves as
+/// soon as the disturbance does instead of waiting for the error it would
+/// cause. Because it is added before clamping, anti-windup sees the real
+/// output and the integral stops winding once feedforward alone saturates
+/// it. Pass the same term every step; the integral takes up whatever part
+/// of the load it gets wrong.
+///
+/// # Errors
+///
+/// Returns [`PidError::InvalidParameter`] if `dt` is non-finite or
+/// non-positive, or if `process_value` or `feedforward` is non-finite.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidState, pid_compute, pid_compute_with_feedforward};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_setpoint(20.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+///
+/// let state = PidState::default();
+/// let (feedback, _) = pid_compute(&config, &state, 19.0, 1.0).unwrap();
+/// let (output, _) = pid_compute_with_feedforward(&config, &state, 19.0, 30.0, 1.0).unwrap();
+/// assert_eq!(output, feedback + 30.0);
+/// ```
+pub fn pid_compute_with_feedforward(
+    config: &ControllerConfig,
+    state: &PidState,
+    process_value: f64,
+    feedforward: f64,
+    dt: f64,
+) -> Result<(f64, PidState), PidError> {
+    let inputs = StepInputs {
+        feedforward: Some(feedforward),
+        ..StepInputs::default()
+    };
+    compute_step(config, state, process_value, inputs, dt)
+}
+
+/// One step in tracking mode: the output follows `tracked_output`, clamped
+/// to the limits, and the integral is back-calculated so that `P + I + D`
+/// equals it.
+///
+/// Call it in place of [`pid_compute`] while something else drives the
+/// actuator -- a manual station, a selector that picked another
+/// controller, a cascade master on hold. P and D keep seeing the process
+/// as usual, so when the loop goes back to [`pid_compute`] the first
+/// output continues from the tracked one, plus whatever the error and
+/// derivative do in that one step, instead of jumping to wherever a stale
+/// integral would put it. With
+/// [`negative_gains`](ControllerConfig::negative_gains) the set for the
+/// tracked output's sign is used.
+///
+/// # Errors
+///
+/// Returns [`PidError::InvalidParameter`] if `dt` is non-finite or
+/// non-positive, or if `process_value` or `tracked_output` is non-finite.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidState, pid_compute, pid_track};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_ki(0.5)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+///
+/// // An operator holds the valve at 35% while the process sits at 48
+/// let mut state = PidState::default();
+/// for _ in 0..10 {
+///     let (output, next) = pid_track(&config, &state, 48.0, 35.0, 1.0).unwrap();
+///     assert_eq!(output, 35.0);
+///     state = next;
+/// }
+///
+/// // Back in automatic, the output picks up from 35 rather than jumping
+/// let (output, _) = pid_compute(&config, &state, 48.0, 1.0).unwrap();
+/// assert_eq!(output, 35.0 + 0.5 * 2.0);
+/// ```
+pub fn pid_track(
+    config: &ControllerConfig,
+    state: &PidState,
+    process_value: f64,
+    tracked_output: f64,
+    dt: f64,
+) -> Result<(f64, PidState), PidError> {
+    let inputs = StepInputs {
+        tracked_output: Some(tracked_output),
+        ..StepInputs::default()
+    };
+    compute_step(config, state, process_value, inputs, dt)
+}
+
+/// Optional inputs to one step, beyond the process value.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct StepInputs {
+    /// Replaces the differenced derivative.
+    pub(crate) rate: Option<f64>,
+    /// Added to `P + I + D` before clamping.
+    pub(crate) feedforward: Option<f64>,
+    /// Output to follow instead of the computed one.
+    pub(crate) tracked_output: Option<f64>,
+}
+
+/// One step of any entry point.
+pub(crate) fn compute_step(
+    config: &ControllerConfig,
+    state: &PidState,
+    process_value: f64,
+    inputs: StepInputs,
+    dt: f64,
 ) -> Result<(f64, PidState), PidError> {
     if !dt.is_finite() || dt <= 0.0 {
         return Err(PidError::InvalidParameter(
@@ -74,8 +263,40 @@ pub fn pid_compute(
             "process_value must be a finite number",
         ));
     }
+    if inputs.rate.is_some_and(|rate| !rate.is_finite()) {
+        return Err(PidError::InvalidParameter("rate must be a finite number"));
+    }
+    if inputs
+        .feedforward
+        .is_some_and(|feedforward| !feedforward.is_finite())
+    {
+        return Err(PidError::InvalidParameter(
+            "feedforward must be a finite number",
+        ));
+    }
+    if inputs
+        .tracked_output
+        .is_some_and(|tracked| !tracked.is_finite())
+    {
+        return Err(PidError::InvalidParameter(
+            "tracked_output must be a finite number",
+        ));
+    }
 
-    let error = config.setpoint - process_value;
+    // First-order lag on the setpoint, if configured
+    let (target, filtered_setpoint) = match config.setpoint_filter {
+        Some(time_constant) => {
+            let start = if state.first_run {
+                process_value
+            } else {
+                state.filtered_setpoint
+            };
+            let filtered = start + dt / (time_constant + dt) * (config.setpoint - start);
+            (filtered, filtered)
+        }
+        None => (config.setpoint, state.filtered_setpoint),
+    };
+    let error = target - process_value;
 
     // Apply deadband to get working_error (for P and I only, NOT D)
     let working_error = if error.abs() <= config.deadband {
@@ -84,82 +305,104 @@ pub fn pid_compute(
         error - config.deadband * error.signum()
     };
 
-    let n = config.derivative_filter_coeff;
-
-    if state.first_run {
-        // P term
-        let p_term = config.kp * working_error;
-
-        // I term: initial accumulation
-        let mut integral_contribution =
-            state.integral_contribution + config.ki * working_error * dt;
-
-        // D = 0 on first run (no previous measurement)
-        let d_term = 0.0;
-
-        let unclamped = p_term + integral_contribution + d_term;
-        let output = unclamped.clamp(config.min_output, config.max_output);
-
-        // Anti-windup correction on first run if saturated
-        if (output - unclamped).abs() > f64::EPSILON {
-            match config.anti_windup_mode {
-                AntiWindupMode::None => {}
-                AntiWindupMode::Conditional => {
-                    integral_contribution -= config.ki * working_error * dt;
-                }
-                AntiWindupMode::BackCalculation { tracking_time } => {
-                    integral_contribution += (output - unclamped) * dt / tracking_time;
-                }
+    // Nonlinear proportional response, if configured
+    let proportional_error = match config.proportional_shape {
+        ProportionalShape::Linear => working_error,
+        ProportionalShape::ErrorSquared { full_gain_error } => {
+            working_error * working_error.abs() / full_gain_error
+        }
+        ProportionalShape::Gap {
+            half_width,
+            gain_ratio,
+        } => {
+            if working_error.abs() <= half_width {
+                gain_ratio * working_error
+            } else {
+                working_error - (1.0 - gain_ratio) * half_width * working_error.signum()
             }
         }
+    };
 
-        let new_state = PidState {
-            integral_contribution,
-            prev_error: working_error,
-            prev_measurement: process_value,
-            prev_filtered_derivative: 0.0,
-            last_output: output,
-            first_run: false,
-        };
+    let n = config.derivative_filter_coeff;
 
-        return Ok((output, new_state));
-    }
+    // Gain set for the side of zero the output is on; a followed output
+    // picks the set the next computed step will use
+    let tracked_output = inputs
+        .tracked_output
+        .map(|tracked| tracked.clamp(config.min_output, config.max_output));
+    let negative = tracked_output.unwrap_or(state.last_output) < 0.0;
+    let (kp, ki, kd) = config.gains_for(negative);
 
     // P term
-    let p_term = config.kp * working_error;
+    let p_term = kp * proportional_error;
 
     // I term: accumulate
-    let mut integral_contribution = state.integral_contribution + config.ki * working_error * dt;
+    let mut integral_contribution = state.integral_contribution + ki * working_error * dt;
 
-    // D term: compute raw derivative INPUT (without Kd)
-    let raw_derivative = match config.derivative_mode {
-        DerivativeMode::OnMeasurement => -(process_value - state.prev_measurement) / dt,
-        DerivativeMode::OnError => (working_error - state.prev_error) / dt,
-    };
-
-    // Apply IIR low-pass filter to raw derivative
-    let alpha = n * dt / (1.0 + n * dt);
-    let filtered =
-        state.prev_filtered_derivative + alpha * (raw_derivative - state.prev_filtered_derivative);
+    let (filtered, d_term) = if state.first_run {
+        // D = 0 on first run (no previous measurement)
+        (0.0, 0.0)
+    } else {
+        // D term: compute raw derivative INPUT (without Kd)
+        let raw_derivative = match (inputs.rate, config.derivative_mode) {
+            (Some(rate), _) => -rate,
+            (None, DerivativeMode::OnMeasurement) => -(process_value - state.prev_measurement) / dt,
+            (None, DerivativeMode::OnError) => (working_error - state.prev_error) / dt,
+        };
+        // Clamp the sample itself too, so an outlier cannot load the filter
+        let raw_derivative = if config.derivative_limits.is_some() && kd != 0.0 {
+            config.limit_derivative(kd * raw_derivative) / kd
+        } else {
+            raw_derivative
+        };
 
-    // Multiply by Kd at output time
-    let d_term = config.kd * filtered;
+        // Apply IIR low-pass filter to raw derivative
+        let alpha = n * dt / (1.0 + n * dt);
+        let filtered = state.prev_filtered_derivative
+            + alpha * (raw_derivative - state.prev_filtered_derivative);
 
-    let unclamped = p_term + integral_contribution + d_term;
-    let output = unclamped.clamp(config.min_output, config.max_output);
+        // Multiply by Kd at output time
+        (filtered, config.limit_derivative(kd * filtered))
+    };
 
-    // Anti-windup on integral_contribution
-    if (output - unclamped).abs() > f64::EPSILON {
-        match config.anti_windup_mode {
-            AntiWindupMode::None => {}
-            AntiWindupMode::Conditional => {
-                integral_contribution -= config.ki * working_error * dt;
+    let output = match tracked_output {
+        // Follow the tracked output, back-calculating the integral so the
+        // next computed step starts from it
+        Some(output) => {
+            integral_contribution = output - p_term - d_term - inputs.feedforward.unwrap_or(0.0);
+            output
+        }
+        None => {
+            let mut unclamped = p_term + integral_contribution + d_term;
+            if let Some(feedforward) = inputs.feedforward {
+                unclamped += feedforward;
             }
-            AntiWindupMode::BackCalculation { tracking_time } => {
-                integral_contribution += (output - unclamped) * dt / tracking_time;
+            let output = unclamped.clamp(config.min_output, config.max_output);
+
+            // Anti-windup on integral_contribution
+            if (output - unclamped).abs() > f64::EPSILON {
+                match config.anti_windup_mode {
+                    AntiWindupMode::None => {}
+                    AntiWindupMode::Conditional => {
+                        integral_contribution -= ki * working_error * dt;
+                    }
+                    AntiWindupMode::BackCalculation { tracking_time } => {
+                        integral_contribution += (output - unclamped) * dt / tracking_time;
+                    }
+                }
             }
+
+            rebase_integral(
+                config,
+                negative,
+                output,
+                proportional_error,
+                filtered,
+                &mut integral_contribution,
+            );
+            output
         }
-    }
+    };
 
     let new_state = PidState {
         integral_contribution,
@@ -167,8 +410,33 @@ pub fn pid_compute(
         prev_measurement: process_value,
         prev_filtered_derivative: filtered,
         last_output: output,
+        filtered_setpoint,
         first_run: false,
     };
 
     Ok((output, new_state))
 }
+
+/// Bumpless transfer between gain sets: when `output` has crossed to the other
+/// side of zero, shifts the integral so the other set's `P + I + D` equals this
+/// set's for the same error and derivative.
+fn rebase_integral(
+    config: &ControllerConfig,
+    negative: bool,
+    output: f64,
+    proportional_error: f64,
+    filtered: f64,
+    integral_contribution: &mut f64,
+) {
+    if config.negative_gains.is_none() || (output < 0.0) == negative {
+        return;
+    }
+    let (kp, _, kd) = config.gains_for(negative);
+    let (next_kp, _, next_kd) = config.gains_for(!negative);
+    let d_shift = if config.derivative_limits.is_some() {
+        config.limit_derivative(kd * filtered) - config.limit_derivative(next_kd * filtered)
+    } else {
+        (kd - next_kd) * filtered
+    };
+    *integral_contribution += (kp - next_kp) * proportional_error + d_shift;
+}
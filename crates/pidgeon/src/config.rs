@@ -1,5 +1,6 @@
-use crate::enums::{AntiWindupMode, DerivativeMode};
+use crate::enums::{AntiWindupMode, DerivativeMode, Gain, ProportionalShape, SetpointLimitAction};
 use crate::error::PidError;
+use crate::scaling::Scaling;
 
 /// Builder for [`ControllerConfig`]. Collects PID parameters without validation
 /// until [`build()`](ControllerConfigBuilder::build) is called.
@@ -14,13 +15,26 @@ use crate::error::PidError;
 /// | `kp`                     | `1.0`                                |
 /// | `ki`                     | `0.0`                                |
 /// | `kd`                     | `0.0`                                |
+/// | `kp_limits`              | none (any finite `kp`)               |
+/// | `ki_limits`              | none (any finite `ki`)               |
+/// | `kd_limits`              | none (any finite `kd`)               |
 /// | `min_output`             | `-f64::INFINITY`                     |
 /// | `max_output`             | `f64::INFINITY`                      |
 /// | `anti_windup_mode`       | [`AntiWindupMode::Conditional`]      |
 /// | `setpoint`               | `0.0`                                |
+/// | `setpoint_limits`        | none (any finite setpoint)           |
+/// | `setpoint_limit_action`  | [`SetpointLimitAction::Clamp`]       |
 /// | `deadband`               | `0.0`                                |
 /// | `derivative_mode`        | [`DerivativeMode::OnMeasurement`]    |
 /// | `derivative_filter_coeff`| `10.0`                               |
+/// | `derivative_limits`      | none (the D term is unbounded)       |
+/// | `initial_output`         | none (the integral starts at `0.0`)  |
+/// | `negative_gains`         | none (one gain set for both signs)   |
+/// | `setpoint_filter`        | none (steps reach the error at once) |
+/// | `proportional_shape`     | [`ProportionalShape::Linear`]        |
+/// | `process_scaling`        | none (gains in engineering units)    |
+/// | `output_scaling`         | none (gains in engineering units)    |
+/// | `statistics`             | `true`                               |
 ///
 /// # Examples
 ///
@@ -39,17 +53,32 @@ use crate::error::PidError;
 /// assert_eq!(config.kp(), 2.0);
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ControllerConfigBuilder {
     kp: f64,
     ki: f64,
     kd: f64,
+    kp_limits: Option<(f64, f64)>,
+    ki_limits: Option<(f64, f64)>,
+    kd_limits: Option<(f64, f64)>,
     min_output: f64,
     max_output: f64,
     anti_windup_mode: AntiWindupMode,
     setpoint: f64,
+    setpoint_limits: Option<(f64, f64)>,
+    setpoint_limit_action: SetpointLimitAction,
     deadband: f64,
     derivative_mode: DerivativeMode,
     derivative_filter_coeff: f64,
+    derivative_limits: Option<(f64, f64)>,
+    initial_output: Option<f64>,
+    negative_gains: Option<PidGains>,
+    setpoint_filter: Option<f64>,
+    proportional_shape: ProportionalShape,
+    process_scaling: Option<Scaling>,
+    output_scaling: Option<Scaling>,
+    statistics: bool,
 }
 
 impl Default for ControllerConfigBuilder {
@@ -58,13 +87,26 @@ impl Default for ControllerConfigBuilder {
             kp: 1.0,
             ki: 0.0,
             kd: 0.0,
+            kp_limits: None,
+            ki_limits: None,
+            kd_limits: None,
             min_output: -f64::INFINITY,
             max_output: f64::INFINITY,
             anti_windup_mode: AntiWindupMode::Conditional,
             setpoint: 0.0,
+            setpoint_limits: None,
+            setpoint_limit_action: SetpointLimitAction::Clamp,
             deadband: 0.0,
             derivative_mode: DerivativeMode::OnMeasurement,
             derivative_filter_coeff: 10.0,
+            derivative_limits: None,
+            initial_output: None,
+            negative_gains: None,
+            setpoint_filter: None,
+            proportional_shape: ProportionalShape::Linear,
+            process_scaling: None,
+            output_scaling: None,
+            statistics: true,
         }
     }
 }
@@ -96,6 +138,24 @@ impl ControllerConfigBuilder {
         self
     }
 
+    /// Range `min..=max` the gain must stay within, here and in every later
+    /// runtime change: [`set_kp`](crate::PidController::set_kp) and its
+    /// siblings, serial, CAN, and remote tuning commands. A change outside
+    /// it is refused with an error and logged as
+    /// [`GainRejected`](crate::EventKind::GainRejected) instead of reaching
+    /// the loop, which guards against a mistyped value on a dashboard.
+    /// Either bound may be infinite. Applies to both the main gain set and
+    /// the [`negative_gains`](Self::with_negative_gains). Default: none.
+    pub fn with_gain_limits(mut self, gain: Gain, min: f64, max: f64) -> Self {
+        let limits = Some((min, max));
+        match gain {
+            Gain::Kp => self.kp_limits = limits,
+            Gain::Ki => self.ki_limits = limits,
+            Gain::Kd => self.kd_limits = limits,
+        }
+        self
+    }
+
     /// Clamps the controller output to `[min, max]`. Both must be finite and `min < max`.
     /// Default: no limits (`-inf`, `+inf`), but note that [`build()`](Self::build) requires
     /// finite values.
@@ -129,6 +189,27 @@ impl ControllerConfigBuilder {
         self
     }
 
+    /// Safe envelope for the setpoint. Once set, the configured setpoint
+    /// must lie inside it, and every later change, whether from
+    /// [`set_setpoint`](crate::PidController::set_setpoint), a schedule, a
+    /// group broadcast, or a remote, CAN, or serial command, is clamped to it
+    /// or rejected per the [`SetpointLimitAction`]. A
+    /// [`PidController`](crate::PidController) logs each out-of-range request
+    /// in its event log. Both limits must be finite with `min < max`.
+    /// Default: none.
+    pub fn with_setpoint_limits(mut self, min: f64, max: f64) -> Self {
+        self.setpoint_limits = Some((min, max));
+        self
+    }
+
+    /// What happens to a setpoint outside the
+    /// [`setpoint limits`](Self::with_setpoint_limits).
+    /// Default: [`SetpointLimitAction::Clamp`].
+    pub fn with_setpoint_limit_action(mut self, action: SetpointLimitAction) -> Self {
+        self.setpoint_limit_action = action;
+        self
+    }
+
     /// Error values within `+/- deadband` are treated as zero for the proportional
     /// and integral terms (the derivative term still sees the full signal).
     /// The value is forced non-negative via `abs()`. Default: `0.0` (no deadband).
@@ -153,17 +234,147 @@ impl ControllerConfigBuilder {
         self
     }
 
+    /// Bounds the derivative term's contribution to the output to
+    /// `min..=max`, separately in each direction. A single bad sample (a
+    /// glitching encoder, an ADC spike) otherwise differentiates into a
+    /// full-scale output swing. The bound also applies to the raw derivative
+    /// before it reaches the filter, so one outlier cannot load the filter
+    /// with a kick that takes several steps to drain.
+    ///
+    /// `min` must be zero or negative and `max` zero or positive, not both
+    /// zero; either may be infinite to bound one direction only.
+    /// Default: none.
+    pub fn with_derivative_limits(mut self, min: f64, max: f64) -> Self {
+        self.derivative_limits = Some((min, max));
+        self
+    }
+
+    /// Output the controller starts from: the integral is preloaded with this
+    /// value, so a loop with a known steady-state output (hover thrust, a
+    /// heater's holding duty) produces it from the first step instead of
+    /// sagging while the integral winds up from zero. Applies on creation and
+    /// after every reset. Must be finite and within the output limits.
+    /// Default: none.
+    pub fn with_initial_output(mut self, output: f64) -> Self {
+        self.initial_output = Some(output);
+        self
+    }
+
+    /// Separate gains for while the output is negative: cooling rather than
+    /// heating, braking rather than accelerating. Many plants are far faster
+    /// in one direction, and one gain set tuned for both is too slow on one
+    /// side or oscillates on the other. `kp`, `ki`, and `kd` stay the gains
+    /// for a zero or positive output, and the output limits already bound
+    /// each side separately (`min_output` for negative, `max_output` for
+    /// positive).
+    ///
+    /// The controller picks a set by the sign of its previous output. When
+    /// the output crosses zero, the integral is rebased so the new set
+    /// produces the same output the old one did, so the switch is bumpless,
+    /// and anti-windup always applies to the set that produced the step.
+    /// Default: none.
+    pub fn with_negative_gains(mut self, gains: PidGains) -> Self {
+        self.negative_gains = Some(gains);
+        self
+    }
+
+    /// Sets `kp`, `ki`, and `kd` to the gains a [`TuneRecord`](crate::TuneRecord)
+    /// holds, so a controller can be rebuilt from a saved tune. Limits,
+    /// setpoint, and the rest still come from the builder.
+    #[cfg(feature = "std")]
+    pub fn with_tune(self, record: &crate::TuneRecord) -> Self {
+        self.with_kp(record.gains.kp)
+            .with_ki(record.gains.ki)
+            .with_kd(record.gains.kd)
+    }
+
+    /// Passes the setpoint through a first-order low-pass filter with this
+    /// time constant, in seconds, before the error is computed. A setpoint
+    /// step then reaches the loop as an exponential approach, which softens
+    /// the proportional kick and the overshoot it causes without changing
+    /// how the loop rejects disturbances. It is a simpler alternative to
+    /// setpoint weighting; a time constant near the closed loop's own
+    /// response time is a good start.
+    ///
+    /// The filter starts from the process value on the first step (and after
+    /// every reset), so start-up is softened too. Must be finite and positive.
+    /// Default: none.
+    pub fn with_setpoint_filter(mut self, time_constant: f64) -> Self {
+        self.setpoint_filter = Some(time_constant);
+        self
+    }
+
+    /// Makes the proportional term nonlinear in the error: error-squared, or
+    /// a reduced gain inside a gap around the setpoint. See
+    /// [`ProportionalShape`]. Default: [`ProportionalShape::Linear`].
+    pub fn with_proportional_shape(mut self, shape: ProportionalShape) -> Self {
+        self.proportional_shape = shape;
+        self
+    }
+
+    /// Engineering range of the process value. Once set, the gains are read
+    /// as acting on the error in percent of this range rather than in
+    /// engineering units, so a `kp` of `2.0` means 2 % of output per 1 % of
+    /// span, whatever the span is. The setpoint, deadband, and everything
+    /// else measured against the process value stay in engineering units.
+    ///
+    /// Its raw range is what [`PidController::compute_raw`](crate::PidController::compute_raw)
+    /// reads counts through. Default: none.
+    pub fn with_process_scaling(mut self, scaling: Scaling) -> Self {
+        self.process_scaling = Some(scaling);
+        self
+    }
+
+    /// Engineering range of the output, the other half of `%/%` gains: the
+    /// controller's output stays in engineering units, but the gains are
+    /// read as producing percent of this range. If the output limits are
+    /// left unset they default to the range.
+    ///
+    /// Its raw range is what [`PidController::compute_raw`](crate::PidController::compute_raw)
+    /// writes counts through. Default: none.
+    pub fn with_output_scaling(mut self, scaling: Scaling) -> Self {
+        self.output_scaling = Some(scaling);
+        self
+    }
+
+    /// Whether controllers running this config keep statistics. With
+    /// `false`, [`PidController`](crate::PidController) and
+    /// `CriticalSectionPidController` skip every bit of statistics
+    /// bookkeeping in `compute`, for loops where each cycle counts; their
+    /// statistics then stay at zero and the `Controller` trait reports
+    /// none. Default: `true`.
+    pub fn with_statistics(mut self, enabled: bool) -> Self {
+        self.statistics = enabled;
+        self
+    }
+
     /// Validates all parameters and produces an immutable [`ControllerConfig`].
     ///
     /// # Errors
     ///
     /// Returns [`PidError::InvalidParameter`] if:
-    /// - Any gain (`kp`, `ki`, `kd`) is non-finite.
+    /// - Any gain (`kp`, `ki`, `kd`, or a negative-side gain) is non-finite.
     /// - `setpoint` or `deadband` is non-finite (or deadband is negative).
+    /// - Setpoint limits are non-finite, `min >= max`, or exclude `setpoint`.
     /// - Output limits are non-finite or `min >= max`.
     /// - `derivative_filter_coeff` is non-finite or non-positive.
+    /// - A derivative limit is NaN or on the wrong side of zero, or both are zero.
     /// - [`AntiWindupMode::BackCalculation`] has a non-finite or non-positive `tracking_time`.
-    pub fn build(self) -> Result<ControllerConfig, PidError> {
+    /// - `initial_output` is non-finite or outside the output limits.
+    /// - `setpoint_filter` is non-finite or non-positive.
+    /// - A [`ProportionalShape`] width is non-finite or non-positive, or its
+    ///   `gain_ratio` is outside `0.0..=1.0`.
+    /// - A [`Scaling`] range is non-finite or empty.
+    pub fn build(mut self) -> Result<ControllerConfig, PidError> {
+        for scaling in [self.process_scaling, self.output_scaling].iter().flatten() {
+            scaling.check()?;
+        }
+        if let Some(scaling) = self.output_scaling {
+            if self.min_output == -f64::INFINITY && self.max_output == f64::INFINITY {
+                self.min_output = scaling.low();
+                self.max_output = scaling.high();
+            }
+        }
         if !self.kp.is_finite() {
             return Err(PidError::InvalidParameter("kp must be a finite number"));
         }
@@ -173,11 +384,59 @@ impl ControllerConfigBuilder {
         if !self.kd.is_finite() {
             return Err(PidError::InvalidParameter("kd must be a finite number"));
         }
+        for (gain, limits) in [
+            (self.kp, self.kp_limits),
+            (self.ki, self.ki_limits),
+            (self.kd, self.kd_limits),
+        ] {
+            if let Some((min, max)) = limits {
+                if min.is_nan() || max.is_nan() || min > max {
+                    return Err(PidError::InvalidParameter(
+                        "gain limits must be numbers with min <= max",
+                    ));
+                }
+                if !(min..=max).contains(&gain) {
+                    return Err(PidError::InvalidParameter(
+                        "gains must lie within their gain limits",
+                    ));
+                }
+            }
+        }
+        if let Some(gains) = self.negative_gains {
+            if !gains.kp.is_finite() || !gains.ki.is_finite() || !gains.kd.is_finite() {
+                return Err(PidError::InvalidParameter(
+                    "negative-side gains must be finite numbers",
+                ));
+            }
+            for (gain, limits) in [
+                (gains.kp, self.kp_limits),
+                (gains.ki, self.ki_limits),
+                (gains.kd, self.kd_limits),
+            ] {
+                if limits.is_some_and(|(min, max)| !(min..=max).contains(&gain)) {
+                    return Err(PidError::InvalidParameter(
+                        "negative-side gains must lie within their gain limits",
+                    ));
+                }
+            }
+        }
         if !self.setpoint.is_finite() {
             return Err(PidError::InvalidParameter(
                 "setpoint must be a finite number",
             ));
         }
+        if let Some((min, max)) = self.setpoint_limits {
+            if !min.is_finite() || !max.is_finite() || min >= max {
+                return Err(PidError::InvalidParameter(
+                    "setpoint limits must be finite with min < max",
+                ));
+            }
+            if !(min..=max).contains(&self.setpoint) {
+                return Err(PidError::InvalidParameter(
+                    "setpoint must lie within the setpoint limits",
+                ));
+            }
+        }
         if !self.deadband.is_finite() || self.deadband < 0.0 {
             return Err(PidError::InvalidParameter(
                 "deadband must be a finite non-negative number",
@@ -198,6 +457,13 @@ impl ControllerConfigBuilder {
                 "derivative_filter_coeff must be a finite positive number",
             ));
         }
+        if let Some((min, max)) = self.derivative_limits {
+            if min.is_nan() || max.is_nan() || min > 0.0 || max < 0.0 || min == max {
+                return Err(PidError::InvalidParameter(
+                    "derivative limits must satisfy min <= 0 <= max with min < max",
+                ));
+            }
+        }
         if let AntiWindupMode::BackCalculation { tracking_time } = self.anti_windup_mode {
             if !tracking_time.is_finite() || tracking_time <= 0.0 {
                 return Err(PidError::InvalidParameter(
@@ -206,17 +472,74 @@ impl ControllerConfigBuilder {
             }
         }
 
+        if let Some(output) = self.initial_output {
+            if !output.is_finite() || output < self.min_output || output > self.max_output {
+                return Err(PidError::InvalidParameter(
+                    "initial_output must be a finite number within the output limits",
+                ));
+            }
+        }
+
+        if let Some(time_constant) = self.setpoint_filter {
+            if !time_constant.is_finite() || time_constant <= 0.0 {
+                return Err(PidError::InvalidParameter(
+                    "setpoint_filter must be a finite positive time constant",
+                ));
+            }
+        }
+
+        match self.proportional_shape {
+            ProportionalShape::Linear => {}
+            ProportionalShape::ErrorSquared { full_gain_error } => {
+                if !full_gain_error.is_finite() || full_gain_error <= 0.0 {
+                    return Err(PidError::InvalidParameter(
+                        "error-squared full_gain_error must be a finite positive number",
+                    ));
+                }
+            }
+            ProportionalShape::Gap {
+                half_width,
+                gain_ratio,
+            } => {
+                if !half_width.is_finite() || half_width <= 0.0 {
+                    return Err(PidError::InvalidParameter(
+                        "gap half_width must be a finite positive number",
+                    ));
+                }
+                if !(0.0..=1.0).contains(&gain_ratio) {
+                    return Err(PidError::InvalidParameter(
+                        "gap gain_ratio must be between 0 and 1",
+                    ));
+                }
+            }
+        }
+
         Ok(ControllerConfig {
             kp: self.kp,
             ki: self.ki,
             kd: self.kd,
+            kp_limits: self.kp_limits,
+            ki_limits: self.ki_limits,
+            kd_limits: self.kd_limits,
             min_output: self.min_output,
             max_output: self.max_output,
             anti_windup_mode: self.anti_windup_mode,
             setpoint: self.setpoint,
+            setpoint_limits: self.setpoint_limits,
+            setpoint_limit_action: self.setpoint_limit_action,
             deadband: self.deadband,
             derivative_mode: self.derivative_mode,
             derivative_filter_coeff: self.derivative_filter_coeff,
+            derivative_limits: self.derivative_limits,
+            initial_output: self.initial_output,
+            negative_gains: self.negative_gains,
+            setpoint_filter: self.setpoint_filter,
+            proportional_shape: self.proportional_shape,
+            process_scaling: self.process_scaling,
+            output_scaling: self.output_scaling,
+            statistics: self.statistics,
+            gain_scale: self.process_scaling.map_or(1.0, |s| 100.0 / s.span())
+                * self.output_scaling.map_or(1.0, |s| s.span() / 100.0),
         })
     }
 }
@@ -226,18 +549,78 @@ impl ControllerConfigBuilder {
 /// Obtain via [`ControllerConfig::builder()`] followed by
 /// [`ControllerConfigBuilder::build()`]. All fields are guaranteed valid at
 /// construction time; accessor methods provide read-only access.
-#[derive(Debug, Clone)]
+///
+/// With the `serde` feature, deserialization goes through
+/// [`ControllerConfigBuilder::build()`], so a deserialized config is validated
+/// exactly like one built in code.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ControllerConfigBuilder"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ControllerConfig {
     pub(crate) kp: f64,
     pub(crate) ki: f64,
     pub(crate) kd: f64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) kp_limits: Option<(f64, f64)>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) ki_limits: Option<(f64, f64)>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) kd_limits: Option<(f64, f64)>,
     pub(crate) min_output: f64,
     pub(crate) max_output: f64,
     pub(crate) anti_windup_mode: AntiWindupMode,
     pub(crate) setpoint: f64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) setpoint_limits: Option<(f64, f64)>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_clamp"))]
+    pub(crate) setpoint_limit_action: SetpointLimitAction,
     pub(crate) deadband: f64,
     pub(crate) derivative_mode: DerivativeMode,
     pub(crate) derivative_filter_coeff: f64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) derivative_limits: Option<(f64, f64)>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) initial_output: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) negative_gains: Option<PidGains>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) setpoint_filter: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_linear"))]
+    pub(crate) proportional_shape: ProportionalShape,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) process_scaling: Option<Scaling>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) output_scaling: Option<Scaling>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_true"))]
+    pub(crate) statistics: bool,
+    /// Converts the configured `%/%` gains to engineering units; `1.0`
+    /// without scaling. Derived from the scalings, so never serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) gain_scale: f64,
+}
+
+#[cfg(feature = "serde")]
+fn is_linear(shape: &ProportionalShape) -> bool {
+    *shape == ProportionalShape::Linear
+}
+
+#[cfg(feature = "serde")]
+fn is_clamp(action: &SetpointLimitAction) -> bool {
+    *action == SetpointLimitAction::Clamp
+}
+
+#[cfg(feature = "serde")]
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+impl TryFrom<ControllerConfigBuilder> for ControllerConfig {
+    type Error = PidError;
+
+    fn try_from(builder: ControllerConfigBuilder) -> Result<Self, Self::Error> {
+        builder.build()
+    }
 }
 
 impl ControllerConfig {
@@ -246,6 +629,37 @@ impl ControllerConfig {
         ControllerConfigBuilder::new()
     }
 
+    /// A builder holding this configuration, for changing some fields and
+    /// validating the result like a fresh build
+    #[cfg(feature = "std")]
+    pub(crate) fn to_builder(&self) -> ControllerConfigBuilder {
+        ControllerConfigBuilder {
+            kp: self.kp,
+            ki: self.ki,
+            kd: self.kd,
+            kp_limits: self.kp_limits,
+            ki_limits: self.ki_limits,
+            kd_limits: self.kd_limits,
+            min_output: self.min_output,
+            max_output: self.max_output,
+            anti_windup_mode: self.anti_windup_mode,
+            setpoint: self.setpoint,
+            setpoint_limits: self.setpoint_limits,
+            setpoint_limit_action: self.setpoint_limit_action,
+            deadband: self.deadband,
+            derivative_mode: self.derivative_mode,
+            derivative_filter_coeff: self.derivative_filter_coeff,
+            derivative_limits: self.derivative_limits,
+            initial_output: self.initial_output,
+            negative_gains: self.negative_gains,
+            setpoint_filter: self.setpoint_filter,
+            proportional_shape: self.proportional_shape,
+            process_scaling: self.process_scaling,
+            output_scaling: self.output_scaling,
+            statistics: self.statistics,
+        }
+    }
+
     /// Proportional gain.
     pub fn kp(&self) -> f64 {
         self.kp
@@ -258,6 +672,14 @@ impl ControllerConfig {
     pub fn kd(&self) -> f64 {
         self.kd
     }
+    /// Range `(min, max)` runtime changes to `gain` must stay within, if any.
+    pub fn gain_limits(&self, gain: Gain) -> Option<(f64, f64)> {
+        match gain {
+            Gain::Kp => self.kp_limits,
+            Gain::Ki => self.ki_limits,
+            Gain::Kd => self.kd_limits,
+        }
+    }
     /// Lower output clamp.
     pub fn min_output(&self) -> f64 {
         self.min_output
@@ -274,6 +696,14 @@ impl ControllerConfig {
     pub fn setpoint(&self) -> f64 {
         self.setpoint
     }
+    /// Safe envelope `(min, max)` for the setpoint, if any.
+    pub fn setpoint_limits(&self) -> Option<(f64, f64)> {
+        self.setpoint_limits
+    }
+    /// What happens to a setpoint outside the setpoint limits.
+    pub fn setpoint_limit_action(&self) -> SetpointLimitAction {
+        self.setpoint_limit_action
+    }
     /// Deadband half-width. Errors within this magnitude are zeroed for P and I.
     pub fn deadband(&self) -> f64 {
         self.deadband
@@ -286,4 +716,147 @@ impl ControllerConfig {
     pub fn derivative_filter_coeff(&self) -> f64 {
         self.derivative_filter_coeff
     }
+    /// Bounds `(min, max)` on the derivative term's contribution, if any.
+    pub fn derivative_limits(&self) -> Option<(f64, f64)> {
+        self.derivative_limits
+    }
+    /// Output the integral is preloaded with on creation and reset, if any.
+    pub fn initial_output(&self) -> Option<f64> {
+        self.initial_output
+    }
+    /// Gains used while the output is negative, if they differ from the
+    /// positive side.
+    pub fn negative_gains(&self) -> Option<PidGains> {
+        self.negative_gains
+    }
+
+    /// Time constant of the setpoint filter in seconds, if any.
+    pub fn setpoint_filter(&self) -> Option<f64> {
+        self.setpoint_filter
+    }
+
+    /// Shape of the proportional response to the error.
+    pub fn proportional_shape(&self) -> ProportionalShape {
+        self.proportional_shape
+    }
+
+    /// Engineering range of the process value, if the gains are in `%/%`.
+    pub fn process_scaling(&self) -> Option<Scaling> {
+        self.process_scaling
+    }
+
+    /// Engineering range of the output, if the gains are in `%/%`.
+    pub fn output_scaling(&self) -> Option<Scaling> {
+        self.output_scaling
+    }
+
+    /// Whether controllers keep statistics for this config.
+    pub fn statistics(&self) -> bool {
+        self.statistics
+    }
+
+    /// `(kp, ki, kd)` in engineering units for an output on the negative
+    /// side or not.
+    pub(crate) fn gains_for(&self, negative: bool) -> (f64, f64, f64) {
+        let (kp, ki, kd) = match self.negative_gains {
+            Some(gains) if negative => (gains.kp, gains.ki, gains.kd),
+            _ => (self.kp, self.ki, self.kd),
+        };
+        (
+            kp * self.gain_scale,
+            ki * self.gain_scale,
+            kd * self.gain_scale,
+        )
+    }
+
+    /// The setpoint to apply for a requested `setpoint`: itself inside the
+    /// [`setpoint_limits`](Self::setpoint_limits), otherwise the nearest
+    /// limit. Controllers call it on every change; code driving
+    /// [`pid_compute`](crate::pid_compute) with its own config can too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite,
+    /// or outside the limits with [`SetpointLimitAction::Reject`].
+    pub fn limit_setpoint(&self, setpoint: f64) -> Result<f64, PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        match self.setpoint_limits {
+            Some((min, max)) if !(min..=max).contains(&setpoint) => {
+                match self.setpoint_limit_action {
+                    SetpointLimitAction::Clamp => Ok(setpoint.clamp(min, max)),
+                    SetpointLimitAction::Reject => Err(PidError::InvalidParameter(
+                        "setpoint is outside the setpoint limits",
+                    )),
+                }
+            }
+            _ => Ok(setpoint),
+        }
+    }
+
+    /// Checks a requested runtime value for `gain`, on either side, against
+    /// the [`gain_limits`](Self::gain_limits) and returns it if it may be
+    /// applied. Controllers call it on every change; code driving
+    /// [`pid_compute`](crate::pid_compute) with its own config can too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `value` is non-finite or
+    /// outside the gain's limits.
+    pub fn check_gain(&self, gain: Gain, value: f64) -> Result<f64, PidError> {
+        if !value.is_finite() {
+            return Err(PidError::InvalidParameter(match gain {
+                Gain::Kp => "kp must be a finite number",
+                Gain::Ki => "ki must be a finite number",
+                Gain::Kd => "kd must be a finite number",
+            }));
+        }
+        match self.gain_limits(gain) {
+            Some((min, max)) if !(min..=max).contains(&value) => {
+                Err(PidError::InvalidParameter(match gain {
+                    Gain::Kp => "kp is outside its gain limits",
+                    Gain::Ki => "ki is outside its gain limits",
+                    Gain::Kd => "kd is outside its gain limits",
+                }))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// `d_term` held within the derivative limits, if any.
+    pub(crate) fn limit_derivative(&self, d_term: f64) -> f64 {
+        match self.derivative_limits {
+            Some((min, max)) => d_term.clamp(min, max),
+            None => d_term,
+        }
+    }
+}
+
+/// Parallel-form gains, as taken by [`ControllerConfigBuilder::with_negative_gains`]
+/// and produced by `TuningRule::gains`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PidGains {
+    /// Proportional gain.
+    pub kp: f64,
+    /// Integral gain (`Kp / Ti`).
+    pub ki: f64,
+    /// Derivative gain (`Kp · Td`).
+    pub kd: f64,
+}
+
+impl PidGains {
+    /// Converts standard-form gain `kc`, integral time `ti`, and derivative
+    /// time `td` (seconds) to parallel form.
+    pub fn from_standard(kc: f64, ti: f64, td: f64) -> Self {
+        Self {
+            kp: kc,
+            ki: kc / ti,
+            kd: kc * td,
+        }
+    }
 }
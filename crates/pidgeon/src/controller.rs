@@ -1,74 +1,195 @@
-use crate::compute::pid_compute;
-use crate::config::ControllerConfig;
-use crate::enums::AntiWindupMode;
+use crate::compute::{compute_step, StepInputs};
+use crate::config::{ControllerConfig, PidGains};
+use crate::enums::{AntiWindupMode, Gain};
 use crate::error::PidError;
+use crate::events::{EventKind, EventLog, OperatingMode};
+use crate::feedforward::Feedforward;
+use crate::record::Recording;
+use crate::report::Histogram;
+use crate::response::SetpointResponse;
 use crate::state::PidState;
+use crate::traits::Controller;
 
-#[cfg(all(feature = "std", feature = "wasm"))]
-use web_time::Instant;
+use std::collections::VecDeque;
 
-#[cfg(all(feature = "std", not(feature = "wasm")))]
-use std::time::Instant;
-
-#[cfg(all(feature = "std", feature = "wasm"))]
-use web_time::Duration;
-
-#[cfg(all(feature = "std", not(feature = "wasm")))]
-use std::time::Duration;
+#[cfg(feature = "debugging")]
+use crate::time::Duration;
 
 #[cfg(feature = "debugging")]
 use crate::debug::ControllerDebugger;
 
 #[cfg(feature = "debugging")]
-use crate::debug::DebugConfig;
+use crate::debug::{ControllerSnapshot, DebugConfig, DebuggerDiagnostics};
+
+#[cfg(feature = "remote-control")]
+use crate::remote::{CommandOutcome, RemoteAction};
+
+#[cfg(feature = "can")]
+use crate::can::CanMessage;
+
+#[cfg(feature = "serial")]
+use crate::serial::SerialMessage;
 
 /// Runtime performance metrics for a [`PidController`].
 ///
 /// Tracks how well the controller is performing relative to the setpoint.
-/// Obtain via [`PidController::get_statistics`].
+/// Obtain via [`PidController::get_statistics`], and summarize with
+/// [`to_report`](Self::to_report).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ControllerStatistics {
-    /// Mean absolute error across all time steps since the last reset.
+    /// Time-weighted mean absolute error since the last reset: each step's
+    /// error counts for its `dt`.
     pub average_error: f64,
     /// Largest absolute error observed since the last reset.
     pub max_overshoot: f64,
     /// Seconds from start (or last reset) until the error settled within the
     /// settled threshold and remained there. If not yet settled, reports the
     /// elapsed time so far.
+    ///
+    /// Like [`rise_time`](Self::rise_time), this is loop time: the sum of the
+    /// `dt` values passed to `compute`, not the wall clock, so simulations
+    /// run faster than real time and replayed logs report the process's own
+    /// timing.
     pub settling_time: f64,
     /// Seconds from start (or last reset) until the error first entered the
     /// settled threshold. [`f64::NAN`] if the setpoint has never been reached.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "nan_from_null"))]
     pub rise_time: f64,
+    /// Time-integral of the output's magnitude, in output units × seconds:
+    /// with a 0–100 % heater duty, `output_total / 360_000` is hours at
+    /// full power, and times the rated power that is energy. Counts up from
+    /// zero to the rollover limit, if one is set, then wraps. Kept across
+    /// [`PidController::reset`]; cleared by
+    /// [`PidController::reset_totalizer`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub output_total: f64,
+    /// Times [`output_total`](Self::output_total) has wrapped past the
+    /// rollover limit, so the full total is
+    /// `output_total_rollovers × limit + output_total`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub output_total_rollovers: u64,
+    /// Loop time in seconds since the last reset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub elapsed: f64,
+    /// Whether the error is inside the settled threshold and has stayed
+    /// there since [`settling_time`](Self::settling_time).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub settled: bool,
+    /// Integral of the absolute error (IAE) since the last reset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub iae: f64,
+    /// Integral of the squared error (ISE) since the last reset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ise: f64,
+    /// Integral of the absolute error weighted by loop time since the last
+    /// reset (ITAE), which punishes error that lingers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub itae: f64,
+    /// How the process responded to each setpoint since the last reset,
+    /// oldest first, keeping the most recent
+    /// [`MAX_RESPONSES`](Self::MAX_RESPONSES).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub responses: Vec<SetpointResponse>,
+    /// Time spent at each error, over five settled thresholds either side
+    /// of zero, so each bin is one threshold wide. Changing the threshold
+    /// starts it afresh.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub error_histogram: Histogram,
+    /// Time spent at each output across the output limits, if both are
+    /// finite. Changing the limits starts it afresh.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub output_histogram: Option<Histogram>,
 }
 
+impl ControllerStatistics {
+    /// Setpoint responses kept in [`responses`](Self::responses).
+    pub const MAX_RESPONSES: usize = 16;
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ControllerStatistics {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "stats avg={=f64} max={=f64} rise={=f64} settle={=f64} iae={=f64} total={=f64} responses={=usize}",
+            self.average_error,
+            self.max_overshoot,
+            self.rise_time,
+            self.settling_time,
+            self.iae,
+            self.output_total,
+            self.responses.len(),
+        )
+    }
+}
+
+/// JSON has no NaN, so serde_json writes it as `null`; read that back as NaN
+#[cfg(feature = "serde")]
+fn nan_from_null<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    use serde::Deserialize;
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+#[derive(Clone)]
 pub(crate) struct StatisticsTracker {
-    pub(crate) start_time: Instant,
-    pub(crate) error_sum: f64,
-    pub(crate) error_count: u64,
+    /// Loop time since the last reset: the sum of every step's `dt`
+    pub(crate) elapsed: f64,
+    /// Integral of the absolute error over `elapsed`
+    pub(crate) error_integral: f64,
+    pub(crate) squared_error_integral: f64,
+    pub(crate) time_weighted_error_integral: f64,
     pub(crate) max_error: f64,
     pub(crate) reached_setpoint: bool,
-    pub(crate) rise_time: Option<Duration>,
-    pub(crate) settle_time: Option<Duration>,
+    pub(crate) rise_time: Option<f64>,
+    pub(crate) settle_time: Option<f64>,
     pub(crate) settled_threshold: f64,
+    pub(crate) output_total: f64,
+    pub(crate) output_total_rollovers: u64,
+    pub(crate) totalizer_rollover: Option<f64>,
+    pub(crate) responses: VecDeque<SetpointResponse>,
+    /// Sign of the current response's step, or 0 with nowhere to go
+    pub(crate) direction: f64,
+    pub(crate) error_histogram: Histogram,
+    pub(crate) output_histogram: Option<Histogram>,
 }
 
 impl StatisticsTracker {
     pub(crate) fn new() -> Self {
         StatisticsTracker {
-            start_time: Instant::now(),
-            error_sum: 0.0,
-            error_count: 0,
+            elapsed: 0.0,
+            error_integral: 0.0,
+            squared_error_integral: 0.0,
+            time_weighted_error_integral: 0.0,
             max_error: 0.0,
             reached_setpoint: false,
             rise_time: None,
             settle_time: None,
             settled_threshold: 0.05,
+            output_total: 0.0,
+            output_total_rollovers: 0,
+            totalizer_rollover: None,
+            responses: VecDeque::new(),
+            direction: 0.0,
+            error_histogram: Histogram::new(-0.25, 0.25, Histogram::BINS),
+            output_histogram: None,
         }
     }
 
-    pub(crate) fn update(&mut self, error: f64) {
-        self.error_sum += error.abs();
-        self.error_count += 1;
+    /// Accounts for a step that ended `dt` seconds after the previous one
+    /// with `process_value` measured and `output` applied
+    pub(crate) fn update(
+        &mut self,
+        config: &ControllerConfig,
+        process_value: f64,
+        output: f64,
+        dt: f64,
+    ) {
+        let error = config.setpoint - process_value;
+        self.elapsed += dt;
+        self.error_integral += error.abs() * dt;
+        self.squared_error_integral += error * error * dt;
+        self.time_weighted_error_integral += self.elapsed * error.abs() * dt;
 
         if error.abs() > self.max_error {
             self.max_error = error.abs();
@@ -76,48 +197,113 @@ impl StatisticsTracker {
 
         if !self.reached_setpoint && error.abs() <= self.settled_threshold {
             self.reached_setpoint = true;
-            self.rise_time = Some(Instant::now() - self.start_time);
+            self.rise_time = Some(self.elapsed);
         }
 
         if self.reached_setpoint
             && error.abs() <= self.settled_threshold
             && self.settle_time.is_none()
         {
-            self.settle_time = Some(Instant::now() - self.start_time);
+            self.settle_time = Some(self.elapsed);
         } else if error.abs() > self.settled_threshold {
             self.settle_time = None;
         }
+
+        self.update_response(config.setpoint, process_value, dt);
+        self.update_histograms(config, error, output, dt);
+    }
+
+    fn update_response(&mut self, setpoint: f64, process_value: f64, dt: f64) {
+        let current = self.responses.back().map(|response| response.setpoint);
+        if current != Some(setpoint) {
+            let (response, direction) =
+                SetpointResponse::open(self.elapsed - dt, current, setpoint, process_value);
+            if self.responses.len() == ControllerStatistics::MAX_RESPONSES {
+                self.responses.pop_front();
+            }
+            self.responses.push_back(response);
+            self.direction = direction;
+        }
+        if let Some(response) = self.responses.back_mut() {
+            response.update(process_value, self.direction, self.settled_threshold, dt);
+        }
     }
 
-    pub(crate) fn get_statistics(&self) -> ControllerStatistics {
-        let avg_error = if self.error_count > 0 {
-            self.error_sum / self.error_count as f64
-        } else {
-            0.0
-        };
+    fn update_histograms(&mut self, config: &ControllerConfig, error: f64, output: f64, dt: f64) {
+        let span = 5.0 * self.settled_threshold;
+        if !self.error_histogram.covers(-span, span) {
+            self.error_histogram = Histogram::new(-span, span, Histogram::BINS);
+        }
+        self.error_histogram.record(error, dt);
 
-        let settling_time = match self.settle_time {
-            Some(time) => time.as_secs_f64(),
-            None => (Instant::now() - self.start_time).as_secs_f64(),
+        let (low, high) = (config.min_output, config.max_output);
+        if !(low.is_finite() && high.is_finite()) {
+            self.output_histogram = None;
+            return;
+        }
+        let histogram = match &mut self.output_histogram {
+            Some(histogram) if histogram.covers(low, high) => histogram,
+            stale => stale.insert(Histogram::new(low, high, Histogram::BINS)),
         };
+        histogram.record(output, dt);
+    }
 
-        let rise_time = match self.rise_time {
-            Some(time) => time.as_secs_f64(),
-            None => f64::NAN,
+    /// Adds `output` held for `dt` seconds to the totalizer, wrapping at
+    /// the rollover limit.
+    pub(crate) fn accumulate(&mut self, output: f64, dt: f64) {
+        self.output_total += output.abs() * dt;
+        if let Some(limit) = self.totalizer_rollover {
+            if self.output_total >= limit {
+                let wraps = (self.output_total / limit).floor();
+                self.output_total -= wraps * limit;
+                self.output_total_rollovers += wraps as u64;
+            }
+        }
+    }
+
+    pub(crate) fn reset_totalizer(&mut self) {
+        self.output_total = 0.0;
+        self.output_total_rollovers = 0;
+    }
+
+    pub(crate) fn get_statistics(&self) -> ControllerStatistics {
+        let avg_error = if self.elapsed > 0.0 {
+            self.error_integral / self.elapsed
+        } else {
+            0.0
         };
 
         ControllerStatistics {
             average_error: avg_error,
             max_overshoot: self.max_error,
-            settling_time,
-            rise_time,
+            settling_time: self.settle_time.unwrap_or(self.elapsed),
+            rise_time: self.rise_time.unwrap_or(f64::NAN),
+            output_total: self.output_total,
+            output_total_rollovers: self.output_total_rollovers,
+            elapsed: self.elapsed,
+            settled: self.settle_time.is_some(),
+            iae: self.error_integral,
+            ise: self.squared_error_integral,
+            itae: self.time_weighted_error_integral,
+            responses: self.responses.iter().cloned().collect(),
+            error_histogram: self.error_histogram.clone(),
+            output_histogram: self.output_histogram.clone(),
         }
     }
 
     pub(crate) fn reset(&mut self) {
-        self.start_time = Instant::now();
-        self.error_sum = 0.0;
-        self.error_count = 0;
+        self.elapsed = 0.0;
+        self.error_integral = 0.0;
+        self.squared_error_integral = 0.0;
+        self.time_weighted_error_integral = 0.0;
+        self.responses.clear();
+        self.direction = 0.0;
+        self.error_histogram = Histogram::new(
+            self.error_histogram.low,
+            self.error_histogram.high,
+            Histogram::BINS,
+        );
+        self.output_histogram = None;
         self.max_error = 0.0;
         self.reached_setpoint = false;
         self.rise_time = None;
@@ -127,8 +313,8 @@ impl StatisticsTracker {
 
 /// Stateful PID controller with built-in performance statistics.
 ///
-/// Wraps [`pid_compute`] with automatic time tracking and statistics. For
-/// multi-threaded use, see [`ThreadSafePidController`](crate::ThreadSafePidController).
+/// Wraps [`pid_compute`](crate::pid_compute) with automatic time tracking
+/// and statistics. For multi-threaded use, see [`ThreadSafePidController`](crate::ThreadSafePidController).
 ///
 /// # Examples
 ///
@@ -153,6 +339,20 @@ pub struct PidController {
     pub(crate) config: ControllerConfig,
     pub(crate) state: PidState,
     pub(crate) stats: StatisticsTracker,
+    pub(crate) recording: Option<Recording>,
+    pub(crate) feedforward: Option<Feedforward>,
+    pub(crate) disturbance: Option<f64>,
+    pub(crate) events: EventLog,
+    pub(crate) mode: OperatingMode,
+    /// Output forced by an engaged interlock
+    pub(crate) interlock: Option<f64>,
+    /// Integral that continues from the forced output once the interlock
+    /// is released; back-calculated on every interlocked step
+    pub(crate) resume_integral: Option<f64>,
+    /// Sum of every successful step's `dt`; never reset
+    pub(crate) run_time: f64,
+    /// Run time at which the output reached a limit, while it stays there
+    pub(crate) saturated_since: Option<f64>,
     #[cfg(feature = "debugging")]
     pub(crate) debugger: Option<ControllerDebugger>,
 }
@@ -161,9 +361,18 @@ impl PidController {
     /// Creates a controller from a validated [`ControllerConfig`].
     pub fn new(config: ControllerConfig) -> Self {
         PidController {
+            state: PidState::new(&config),
             config,
-            state: PidState::default(),
             stats: StatisticsTracker::new(),
+            recording: None,
+            feedforward: None,
+            disturbance: None,
+            events: EventLog::default(),
+            mode: OperatingMode::Automatic,
+            interlock: None,
+            resume_integral: None,
+            run_time: 0.0,
+            saturated_since: None,
             #[cfg(feature = "debugging")]
             debugger: None,
         }
@@ -171,49 +380,400 @@ impl PidController {
 
     /// Runs one PID iteration and returns the clamped control output.
     ///
-    /// Also updates internal statistics (average error, overshoot, etc.).
+    /// Also updates internal statistics (average error, overshoot, etc.),
+    /// timed by `dt` rather than the wall clock. A rejected step counts for
+    /// nothing.
     ///
     /// # Errors
     ///
     /// Returns [`PidError::InvalidParameter`] if `process_value` is non-finite
     /// or `dt` is non-finite / non-positive.
     pub fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
-        let error = self.config.setpoint - process_value;
-        self.stats.update(error);
+        self.step(process_value, StepInputs::default(), dt)
+    }
 
-        let (output, new_state) = pid_compute(&self.config, &self.state, process_value, dt)?;
+    /// Runs one PID iteration on a raw reading, such as ADC counts, and
+    /// returns the output as a raw value, such as DAC counts.
+    ///
+    /// The reading goes through the configured
+    /// [`process scaling`](crate::ControllerConfigBuilder::with_process_scaling)
+    /// to engineering units and the output back out through the
+    /// [`output scaling`](crate::ControllerConfigBuilder::with_output_scaling),
+    /// so the counts never reach the setpoint, limits, or gains. A side
+    /// without scaling passes through unchanged. The raw output is not
+    /// rounded; round or truncate it as the converter expects.
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, PidController, Scaling};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(2.0)
+    ///     .with_setpoint(75.0)
+    ///     .with_process_scaling(Scaling::from_raw(0.0, 4095.0, 0.0, 150.0).unwrap())
+    ///     .with_output_scaling(Scaling::from_raw(0.0, 1023.0, 0.0, 100.0).unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let mut controller = PidController::new(config);
+    ///
+    /// // 15 °C low is 10 % of span, so the output is 20 % of its range
+    /// let counts = controller.compute_raw(4095.0 * 60.0 / 150.0, 0.1).unwrap();
+    /// assert!((counts - 204.6).abs() < 1e-9);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `raw_process_value` is
+    /// non-finite or `dt` is non-finite / non-positive.
+    pub fn compute_raw(&mut self, raw_process_value: f64, dt: f64) -> Result<f64, PidError> {
+        let process_value = match self.config.process_scaling {
+            Some(scaling) => scaling.to_engineering(raw_process_value),
+            None => raw_process_value,
+        };
+        let output = self.compute(process_value, dt)?;
+        Ok(match self.config.output_scaling {
+            Some(scaling) => scaling.to_raw(output),
+            None => output,
+        })
+    }
+
+    /// Runs one PID iteration with the process value's rate of change
+    /// supplied, as [`pid_compute_with_rate`](crate::pid_compute_with_rate)
+    /// does, and returns the clamped control output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` or `rate`
+    /// is non-finite, or `dt` is non-finite / non-positive.
+    pub fn compute_with_rate(
+        &mut self,
+        process_value: f64,
+        rate: f64,
+        dt: f64,
+    ) -> Result<f64, PidError> {
+        let inputs = StepInputs {
+            rate: Some(rate),
+            ..StepInputs::default()
+        };
+        self.step(process_value, inputs, dt)
+    }
+
+    /// Follows `tracked_output` for one iteration, as
+    /// [`pid_track`](crate::pid_track) does, and returns it clamped to the
+    /// output limits.
+    ///
+    /// Call it every cycle while another controller or a manual station
+    /// drives the actuator, with the output actually applied. The integral
+    /// is back-calculated each time, so the controller stays warm and the
+    /// first [`compute`](Self::compute) afterwards takes over without a
+    /// bump. An attached [`Feedforward`] keeps running and is accounted for.
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, PidController};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(1.0)
+    ///     .with_ki(0.2)
+    ///     .with_setpoint(60.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut standby = PidController::new(config);
+    ///
+    /// // The primary controller holds 42% while the process is at setpoint
+    /// standby.track(60.0, 42.0, 0.5).unwrap();
+    /// assert_eq!(standby.compute(60.0, 0.5).unwrap(), 42.0);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` or
+    /// `tracked_output` is non-finite, or `dt` is non-finite / non-positive.
+    pub fn track(
+        &mut self,
+        process_value: f64,
+        tracked_output: f64,
+        dt: f64,
+    ) -> Result<f64, PidError> {
+        let inputs = StepInputs {
+            tracked_output: Some(tracked_output),
+            ..StepInputs::default()
+        };
+        self.step(process_value, inputs, dt)
+    }
+
+    fn step(
+        &mut self,
+        process_value: f64,
+        mut inputs: StepInputs,
+        dt: f64,
+    ) -> Result<f64, PidError> {
+        #[cfg(feature = "remote-control")]
+        self.apply_remote_commands();
+
+        // Compensate on a copy so a rejected step leaves the lead/lag alone
+        let mut compensator = self.feedforward.clone();
+        if let (Some(compensator), Some(disturbance)) = (&mut compensator, self.disturbance) {
+            inputs.feedforward = Some(compensator.update(disturbance, dt)?);
+        }
+
+        let mode = if self.interlock.is_some() {
+            OperatingMode::Interlocked
+        } else if inputs.tracked_output.is_some() {
+            OperatingMode::Tracking
+        } else {
+            OperatingMode::Automatic
+        };
+        // An interlock is followed like a tracked output, so the release
+        // is bumpless, but the integral itself stays frozen until then.
+        // The loop can only resume from inside the limits, so the integral
+        // is back-calculated from the forced output clamped into them
+        if let Some(forced) = self.interlock {
+            inputs.tracked_output =
+                Some(forced.clamp(self.config.min_output, self.config.max_output));
+        }
+        let (config, state) = (&self.config, &self.state);
+        let (mut output, mut new_state) = match &mut self.recording {
+            Some(recording) => recording.record(config, state, process_value, inputs, dt)?,
+            None => compute_step(config, state, process_value, inputs, dt)?,
+        };
+        if let Some(forced) = self.interlock {
+            self.resume_integral = Some(new_state.integral_contribution);
+            new_state.integral_contribution = self.state.integral_contribution;
+            new_state.last_output = forced;
+            output = forced;
+        }
+        self.feedforward = compensator;
+        if self.config.statistics {
+            self.stats.update(&self.config, process_value, output, dt);
+            self.stats.accumulate(output, dt);
+        }
+        self.run_time += dt;
+        self.log_step(mode, output);
 
         // Debugging
         #[cfg(feature = "debugging")]
         if let Some(ref mut debugger) = self.debugger {
-            let working_error = if error.abs() <= self.config.deadband {
-                0.0
-            } else {
-                error - self.config.deadband * error.signum()
-            };
-            let p_term = self.config.kp * working_error;
-            let d_term = self.config.kd * new_state.prev_filtered_derivative;
-            debugger.log_pid_state(
-                self.config.setpoint,
-                process_value,
-                working_error,
-                p_term,
-                new_state.integral_contribution,
-                d_term,
-                output,
-                dt,
-            );
+            debugger.log_cycle(&self.config, &new_state, process_value, dt);
         }
 
         self.state = new_state;
         Ok(output)
     }
 
+    /// Logs a mode switch and the start or end of a saturation episode
+    fn log_step(&mut self, mode: OperatingMode, output: f64) {
+        self.set_mode(mode);
+        // Following an external output that sits on a limit is not windup
+        let saturated = mode == OperatingMode::Automatic
+            && (output >= self.config.max_output || output <= self.config.min_output);
+        match (saturated, self.saturated_since) {
+            (true, None) => {
+                self.saturated_since = Some(self.run_time);
+                self.log(EventKind::SaturationStarted { output });
+            }
+            (false, Some(since)) => {
+                self.saturated_since = None;
+                self.log(EventKind::SaturationEnded {
+                    duration: self.run_time - since,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn log(&mut self, kind: EventKind) {
+        self.events.record(self.run_time, kind);
+    }
+
+    /// Swaps in a whole new config, logging the setpoint and gain changes
+    pub(crate) fn replace_config(&mut self, config: ControllerConfig) {
+        let old = core::mem::replace(&mut self.config, config);
+        if old.setpoint != self.config.setpoint {
+            self.log(EventKind::SetpointChanged {
+                from: old.setpoint,
+                to: self.config.setpoint,
+            });
+        }
+        if (old.kp, old.ki, old.kd) != (self.config.kp, self.config.ki, self.config.kd) {
+            self.log_gains();
+        }
+    }
+
+    /// `setpoint` bounded by the setpoint limits, logging it if it was
+    /// outside them
+    pub(crate) fn limit_setpoint(&mut self, setpoint: f64) -> Result<f64, PidError> {
+        let limited = self.config.limit_setpoint(setpoint);
+        match limited {
+            Ok(to) if to != setpoint => self.log(EventKind::SetpointClamped {
+                requested: setpoint,
+                to,
+            }),
+            Err(_) if setpoint.is_finite() => self.log(EventKind::SetpointRejected {
+                requested: setpoint,
+            }),
+            _ => {}
+        }
+        limited
+    }
+
+    /// [`ControllerConfig::check_gain`], logging a refused finite value.
+    fn check_gain(&mut self, gain: Gain, requested: f64) -> Result<f64, PidError> {
+        let checked = self.config.check_gain(gain, requested);
+        if checked.is_err() && requested.is_finite() {
+            self.log(EventKind::GainRejected { gain, requested });
+        }
+        checked
+    }
+
+    /// Logs the start of a setpoint ramp toward `target` as one change;
+    /// the ramp's steps then go through [`ramp_setpoint`](Self::ramp_setpoint)
+    pub(crate) fn start_setpoint_ramp(&mut self, target: f64) {
+        if target != self.config.setpoint {
+            self.log(EventKind::SetpointChanged {
+                from: self.config.setpoint,
+                to: target,
+            });
+        }
+    }
+
+    /// Moves the setpoint along a ramp without logging each step
+    pub(crate) fn ramp_setpoint(&mut self, setpoint: f64) {
+        self.config.setpoint = setpoint;
+    }
+
+    fn log_gains(&mut self) {
+        self.log(EventKind::GainsChanged {
+            kp: self.config.kp,
+            ki: self.config.ki,
+            kd: self.config.kd,
+        });
+    }
+
     /// Resets controller state and statistics to initial values. The
-    /// configuration is preserved.
+    /// configuration and the [`events`](Self::events) are preserved; the
+    /// reset itself is logged.
     pub fn reset(&mut self) {
-        self.state = PidState::default();
+        self.state = PidState::new(&self.config);
         self.stats.reset();
+        if let Some(feedforward) = &mut self.feedforward {
+            feedforward.reset();
+        }
+        self.saturated_since = None;
+        self.resume_integral = None;
+        self.log(EventKind::Reset);
+    }
+
+    /// Forces the output to `safe_output` until
+    /// [`release_interlock`](Self::release_interlock), for as long as an
+    /// external condition such as an open door or an over-temperature trip
+    /// holds.
+    ///
+    /// While engaged, every step returns `safe_output`, even outside the
+    /// output limits, and the integral is frozen so nothing winds up while
+    /// the loop is open. Releasing resumes from `safe_output` clamped into
+    /// the limits, so from outside them the output steps to the nearest
+    /// limit and continues from there. The derivative and setpoint filter keep following
+    /// the process, so releasing does not kick. Engaging again with a new
+    /// value replaces the forced output. The switch is logged as a
+    /// [`ModeChanged`](EventKind::ModeChanged) to
+    /// [`OperatingMode::Interlocked`].
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, PidController};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(4.0)
+    ///     .with_ki(0.5)
+    ///     .with_setpoint(180.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut oven = PidController::new(config);
+    /// oven.compute(175.0, 1.0).unwrap();
+    ///
+    /// // Door opened: heater off, whatever the error says
+    /// oven.engage_interlock(0.0).unwrap();
+    /// assert_eq!(oven.compute(160.0, 1.0).unwrap(), 0.0);
+    ///
+    /// // Door shut: control picks up from 0%, moved only by one second of
+    /// // integral action (0.5 × 20 °C × 1 s), rather than jumping to 90%
+    /// oven.release_interlock();
+    /// assert_eq!(oven.compute(160.0, 1.0).unwrap(), 10.0);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `safe_output` is non-finite.
+    pub fn engage_interlock(&mut self, safe_output: f64) -> Result<(), PidError> {
+        if !safe_output.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "interlock output must be a finite number",
+            ));
+        }
+        self.interlock = Some(safe_output);
+        self.set_mode(OperatingMode::Interlocked);
+        Ok(())
+    }
+
+    /// Releases an engaged interlock. The next step continues from the
+    /// forced output clamped into the output limits: the integral is
+    /// rebased to what the last interlocked step back-calculated from that
+    /// value, so the output moves off it by the normal action of the loop
+    /// rather than jumping back to where it was before. Does nothing if no
+    /// interlock is engaged.
+    pub fn release_interlock(&mut self) {
+        if self.interlock.take().is_none() {
+            return;
+        }
+        if let Some(integral) = self.resume_integral.take() {
+            self.state.integral_contribution = integral;
+        }
+        self.set_mode(OperatingMode::Automatic);
+    }
+
+    /// The output forced by an engaged interlock, if any.
+    pub fn interlock(&self) -> Option<f64> {
+        self.interlock
+    }
+
+    fn set_mode(&mut self, mode: OperatingMode) {
+        if mode != self.mode {
+            let from = core::mem::replace(&mut self.mode, mode);
+            self.log(EventKind::ModeChanged { from, to: mode });
+        }
+    }
+
+    /// Overwrites the integral term, in output units (`Ki` is already applied).
+    ///
+    /// Use it to warm-start a loop whose steady-state output is known, or
+    /// for bumpless transfer from manual control: setting it to the manual
+    /// output minus `Kp · error` makes the next output pick up where the
+    /// operator left off. See also
+    /// [`with_initial_output`](crate::ControllerConfigBuilder::with_initial_output).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `value` is non-finite.
+    pub fn set_integral(&mut self, value: f64) -> Result<(), PidError> {
+        if !value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "integral must be a finite number",
+            ));
+        }
+        self.state.integral_contribution = value;
+        Ok(())
+    }
+
+    /// Starts capturing every [`compute`](Self::compute) into a
+    /// [`Recording`], discarding any recording in progress.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::new());
+    }
+
+    /// Stops recording and returns what was captured, or `None` if
+    /// [`start_recording`](Self::start_recording) was not called.
+    pub fn take_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
     }
 
     /// Returns a reference to the current [`PidState`].
@@ -226,16 +786,67 @@ impl PidController {
         &self.config
     }
 
+    /// Returns an independent copy of the controller as it stands: config,
+    /// state, statistics, feedforward, mode, interlock, event log, and any
+    /// recording in progress. Fed the same measurements, the fork computes
+    /// exactly what this controller would until either is changed.
+    ///
+    /// Change the fork's gains and run it in a shadow loop, discarding its
+    /// outputs, to see how a candidate tune would behave on the live process
+    /// before promoting it. The debugger is not copied, so a fork never
+    /// publishes telemetry under this controller's id.
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, PidController};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(1.0)
+    ///     .with_ki(0.5)
+    ///     .with_setpoint(50.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut live = PidController::new(config);
+    /// live.compute(40.0, 0.1).unwrap();
+    ///
+    /// let mut candidate = live.fork();
+    /// assert_eq!(candidate.compute(42.0, 0.1), live.compute(42.0, 0.1));
+    ///
+    /// candidate.set_ki(0.8).unwrap();
+    /// let _shadow = candidate.compute(43.0, 0.1).unwrap(); // never actuated
+    /// let output = live.compute(43.0, 0.1).unwrap();
+    /// assert_eq!(live.config().ki(), 0.5);
+    /// # let _ = output;
+    /// ```
+    pub fn fork(&self) -> PidController {
+        PidController {
+            config: self.config.clone(),
+            state: self.state.clone(),
+            stats: self.stats.clone(),
+            recording: self.recording.clone(),
+            feedforward: self.feedforward.clone(),
+            disturbance: self.disturbance,
+            events: self.events.clone(),
+            mode: self.mode,
+            interlock: self.interlock,
+            resume_integral: self.resume_integral,
+            run_time: self.run_time,
+            saturated_since: self.saturated_since,
+            #[cfg(feature = "debugging")]
+            debugger: None,
+        }
+    }
+
     /// Updates the proportional gain at runtime.
     ///
     /// # Errors
     ///
-    /// Returns [`PidError::InvalidParameter`] if `kp` is non-finite.
+    /// Returns [`PidError::InvalidParameter`] if `kp` is non-finite or
+    /// outside its [`gain_limits`](ControllerConfig::gain_limits); the
+    /// latter is logged as [`GainRejected`](EventKind::GainRejected).
     pub fn set_kp(&mut self, kp: f64) -> Result<(), PidError> {
-        if !kp.is_finite() {
-            return Err(PidError::InvalidParameter("kp must be a finite number"));
-        }
-        self.config.kp = kp;
+        self.config.kp = self.check_gain(Gain::Kp, kp)?;
+        self.log_gains();
         Ok(())
     }
 
@@ -243,12 +854,12 @@ impl PidController {
     ///
     /// # Errors
     ///
-    /// Returns [`PidError::InvalidParameter`] if `ki` is non-finite.
+    /// Returns [`PidError::InvalidParameter`] if `ki` is non-finite or
+    /// outside its [`gain_limits`](ControllerConfig::gain_limits); the
+    /// latter is logged as [`GainRejected`](EventKind::GainRejected).
     pub fn set_ki(&mut self, ki: f64) -> Result<(), PidError> {
-        if !ki.is_finite() {
-            return Err(PidError::InvalidParameter("ki must be a finite number"));
-        }
-        self.config.ki = ki;
+        self.config.ki = self.check_gain(Gain::Ki, ki)?;
+        self.log_gains();
         Ok(())
     }
 
@@ -256,12 +867,32 @@ impl PidController {
     ///
     /// # Errors
     ///
-    /// Returns [`PidError::InvalidParameter`] if `kd` is non-finite.
+    /// Returns [`PidError::InvalidParameter`] if `kd` is non-finite or
+    /// outside its [`gain_limits`](ControllerConfig::gain_limits); the
+    /// latter is logged as [`GainRejected`](EventKind::GainRejected).
     pub fn set_kd(&mut self, kd: f64) -> Result<(), PidError> {
-        if !kd.is_finite() {
-            return Err(PidError::InvalidParameter("kd must be a finite number"));
+        self.config.kd = self.check_gain(Gain::Kd, kd)?;
+        self.log_gains();
+        Ok(())
+    }
+
+    /// Replaces the [`negative_gains`](ControllerConfig::negative_gains) at
+    /// runtime, or with `None` goes back to one gain set for both signs.
+    /// The new set takes effect from the next step on the negative side.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a gain is non-finite or
+    /// outside its [`gain_limits`](ControllerConfig::gain_limits), the
+    /// latter logged as [`GainRejected`](EventKind::GainRejected). No gain
+    /// changes then.
+    pub fn set_negative_gains(&mut self, gains: Option<PidGains>) -> Result<(), PidError> {
+        if let Some(gains) = gains {
+            self.check_gain(Gain::Kp, gains.kp)?;
+            self.check_gain(Gain::Ki, gains.ki)?;
+            self.check_gain(Gain::Kd, gains.kd)?;
         }
-        self.config.kd = kd;
+        self.config.negative_gains = gains;
         Ok(())
     }
 
@@ -283,16 +914,22 @@ impl PidController {
 
     /// Updates the setpoint at runtime.
     ///
+    /// With [`setpoint_limits`](ControllerConfig::setpoint_limits), a
+    /// setpoint outside them is clamped or rejected per the
+    /// [`SetpointLimitAction`](crate::SetpointLimitAction), and logged as
+    /// [`EventKind::SetpointClamped`] or [`EventKind::SetpointRejected`].
+    ///
     /// # Errors
     ///
-    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite or
+    /// rejected by the setpoint limits.
     pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
-        if !setpoint.is_finite() {
-            return Err(PidError::InvalidParameter(
-                "setpoint must be a finite number",
-            ));
+        let setpoint = self.limit_setpoint(setpoint)?;
+        if setpoint != self.config.setpoint {
+            let from = self.config.setpoint;
+            self.config.setpoint = setpoint;
+            self.log(EventKind::SetpointChanged { from, to: setpoint });
         }
-        self.config.setpoint = setpoint;
         Ok(())
     }
 
@@ -312,6 +949,32 @@ impl PidController {
         self.stats.settled_threshold = threshold;
     }
 
+    /// Sets the value at which
+    /// [`output_total`](ControllerStatistics::output_total) wraps back to
+    /// zero, counting a rollover, like a meter's register. `None`, the
+    /// default, never wraps.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `limit` is not finite and
+    /// positive.
+    pub fn set_totalizer_rollover(&mut self, limit: Option<f64>) -> Result<(), PidError> {
+        if limit.is_some_and(|limit| !limit.is_finite() || limit <= 0.0) {
+            return Err(PidError::InvalidParameter(
+                "totalizer rollover must be a finite positive number",
+            ));
+        }
+        self.stats.totalizer_rollover = limit;
+        Ok(())
+    }
+
+    /// Clears [`output_total`](ControllerStatistics::output_total) and its
+    /// rollover count, as when a meter is read and zeroed.
+    /// [`reset`](Self::reset) leaves them alone.
+    pub fn reset_totalizer(&mut self) {
+        self.stats.reset_totalizer();
+    }
+
     /// Updates the deadband half-width at runtime. The value is forced
     /// non-negative via `abs()`.
     ///
@@ -328,6 +991,74 @@ impl PidController {
         Ok(())
     }
 
+    /// Attaches a [`Feedforward`] compensator for a measured disturbance.
+    /// Once a measurement arrives through
+    /// [`set_disturbance`](Self::set_disturbance), every compute adds its
+    /// term to `P + I + D` before clamping, as
+    /// [`pid_compute_with_feedforward`](crate::pid_compute_with_feedforward)
+    /// does.
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, Feedforward, PidController};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(4.0)
+    ///     .with_ki(0.02)
+    ///     .with_setpoint(21.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut controller =
+    ///     PidController::new(config).with_feedforward(Feedforward::new(-2.5, 0.0, 600.0).unwrap());
+    ///
+    /// // Outdoor air 6 °C below the design point: heat before the room cools
+    /// controller.set_disturbance(-6.0).unwrap();
+    /// assert_eq!(controller.compute(21.0, 1.0).unwrap(), 15.0);
+    /// ```
+    pub fn with_feedforward(mut self, feedforward: Feedforward) -> Self {
+        self.feedforward = Some(feedforward);
+        self
+    }
+
+    /// Records the latest measurement of the disturbance the attached
+    /// [`Feedforward`] compensates. It stays in use until the next one, so
+    /// a disturbance sampled slower than the loop needs no resampling.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `disturbance` is non-finite.
+    pub fn set_disturbance(&mut self, disturbance: f64) -> Result<(), PidError> {
+        if !disturbance.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "disturbance must be a finite number",
+            ));
+        }
+        self.disturbance = Some(disturbance);
+        Ok(())
+    }
+
+    /// The attached feedforward compensator, if any.
+    pub fn feedforward(&self) -> Option<&Feedforward> {
+        self.feedforward.as_ref()
+    }
+
+    /// The log of recent setpoint and gain changes, mode switches,
+    /// saturation episodes, and resets.
+    pub fn events(&self) -> &EventLog {
+        &self.events
+    }
+
+    /// Sets how many events the [`events`](Self::events) log keeps, dropping
+    /// the oldest if it shrinks. Default: [`EventLog::DEFAULT_CAPACITY`].
+    pub fn set_event_capacity(&mut self, capacity: usize) {
+        self.events.set_capacity(capacity);
+    }
+
+    /// Empties the [`events`](Self::events) log, as after it has been saved.
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
     /// Attaches a debugger that streams PID telemetry via Iggy.rs.
     /// Only available with the `debugging` feature.
     #[cfg(feature = "debugging")]
@@ -335,4 +1066,179 @@ impl PidController {
         self.debugger = Some(ControllerDebugger::new(debug_config));
         self
     }
+
+    /// Applies an operator command to the controller. Gain changes are
+    /// validated as a whole, so either all requested gains change or none do.
+    /// Only available with the `remote-control` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a requested value is non-finite.
+    #[cfg(feature = "remote-control")]
+    pub fn apply_command(&mut self, action: &RemoteAction) -> Result<(), PidError> {
+        match *action {
+            RemoteAction::SetSetpoint { value } => self.set_setpoint(value),
+            RemoteAction::SetGains { kp, ki, kd } => {
+                if [kp, ki, kd].iter().flatten().any(|gain| !gain.is_finite()) {
+                    return Err(PidError::InvalidParameter("gains must be finite numbers"));
+                }
+                // All or nothing: check every gain before applying any
+                let kp = kp.map(|kp| self.check_gain(Gain::Kp, kp)).transpose()?;
+                let ki = ki.map(|ki| self.check_gain(Gain::Ki, ki)).transpose()?;
+                let kd = kd.map(|kd| self.check_gain(Gain::Kd, kd)).transpose()?;
+                if let Some(kp) = kp {
+                    self.config.kp = kp;
+                }
+                if let Some(ki) = ki {
+                    self.config.ki = ki;
+                }
+                if let Some(kd) = kd {
+                    self.config.kd = kd;
+                }
+                self.log_gains();
+                Ok(())
+            }
+            RemoteAction::SetMode { mode } => {
+                if let Some(debugger) = &mut self.debugger {
+                    debugger.set_mode(mode);
+                }
+                Ok(())
+            }
+            RemoteAction::Snapshot => {
+                let snapshot = self.snapshot().ok_or(PidError::InvalidParameter(
+                    "snapshots require an attached debugger",
+                ))?;
+                if let Some(debugger) = &self.debugger {
+                    debugger.publish_snapshot(snapshot);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies a command received over CAN. Only available with the `can`
+    /// feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a value is rejected or
+    /// `message` is telemetry rather than a command.
+    #[cfg(feature = "can")]
+    pub fn apply_can_message(&mut self, message: &CanMessage) -> Result<(), PidError> {
+        match *message {
+            CanMessage::SetSetpoint { setpoint } => self.set_setpoint(f64::from(setpoint)),
+            CanMessage::SetGain { gain, value } => match gain {
+                Gain::Kp => self.set_kp(f64::from(value)),
+                Gain::Ki => self.set_ki(f64::from(value)),
+                Gain::Kd => self.set_kd(f64::from(value)),
+            },
+            CanMessage::Reset => {
+                self.reset();
+                Ok(())
+            }
+            CanMessage::Status { .. } | CanMessage::Target { .. } => Err(
+                PidError::InvalidParameter("CAN telemetry cannot be applied as a command"),
+            ),
+        }
+    }
+
+    /// Applies a command received over a serial line. Only available with
+    /// the `serial` feature.
+    ///
+    /// [`GetGains`](SerialMessage::GetGains) changes nothing; answer it, like
+    /// every other command, with [`SerialMessage::gains`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a value is rejected or
+    /// `message` is not a command.
+    #[cfg(feature = "serial")]
+    pub fn apply_serial_message(&mut self, message: &SerialMessage) -> Result<(), PidError> {
+        match *message {
+            SerialMessage::SetSetpoint { setpoint } => self.set_setpoint(setpoint),
+            SerialMessage::SetGain { gain, value } => match gain {
+                Gain::Kp => self.set_kp(value),
+                Gain::Ki => self.set_ki(value),
+                Gain::Kd => self.set_kd(value),
+            },
+            SerialMessage::Reset => {
+                self.reset();
+                Ok(())
+            }
+            SerialMessage::GetGains => Ok(()),
+            SerialMessage::Telemetry { .. }
+            | SerialMessage::Gains { .. }
+            | SerialMessage::Rejected => Err(PidError::InvalidParameter(
+                "serial replies cannot be applied as a command",
+            )),
+        }
+    }
+
+    /// Applies commands received by the debugger and audits each one
+    #[cfg(feature = "remote-control")]
+    fn apply_remote_commands(&mut self) {
+        let Some(debugger) = &self.debugger else {
+            return;
+        };
+        for command in debugger.take_commands() {
+            let outcome = match self.apply_command(&command.action) {
+                Ok(()) => CommandOutcome::Applied,
+                Err(e) => CommandOutcome::Rejected {
+                    reason: e.to_string(),
+                },
+            };
+            if let Some(debugger) = &self.debugger {
+                debugger.audit(&command, outcome);
+            }
+        }
+    }
+
+    /// Flushes queued debug samples and stops the debugger, waiting at most
+    /// `timeout`. Returns `true` if every sample was delivered, or if no
+    /// debugger is attached. Only available with the `debugging` feature.
+    #[cfg(feature = "debugging")]
+    pub fn shutdown_debugger(&mut self, timeout: Duration) -> bool {
+        self.debugger
+            .as_mut()
+            .is_none_or(|debugger| debugger.shutdown(timeout))
+    }
+
+    /// Captures config, state, statistics, and the debugger's recent samples
+    /// in one [`ControllerSnapshot`], or `None` if no debugger is attached.
+    /// Only available with the `debugging` feature.
+    #[cfg(feature = "debugging")]
+    pub fn snapshot(&self) -> Option<ControllerSnapshot> {
+        self.debugger
+            .as_ref()
+            .map(|debugger| debugger.snapshot(&self.config, &self.state, self.get_statistics()))
+    }
+
+    /// Returns the debugger's queue counters, or `None` if no debugger is
+    /// attached. Only available with the `debugging` feature.
+    #[cfg(feature = "debugging")]
+    pub fn debug_diagnostics(&self) -> Option<DebuggerDiagnostics> {
+        self.debugger.as_ref().map(ControllerDebugger::diagnostics)
+    }
+}
+
+impl Controller for PidController {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        PidController::compute(self, process_value, dt)
+    }
+
+    fn setpoint(&self) -> f64 {
+        PidController::setpoint(self)
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        PidController::set_setpoint(self, setpoint)
+    }
+
+    fn reset(&mut self) {
+        PidController::reset(self)
+    }
+
+    fn statistics(&self) -> Option<ControllerStatistics> {
+        self.config.statistics.then(|| self.get_statistics())
+    }
 }
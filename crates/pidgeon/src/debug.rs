@@ -1,19 +1,37 @@
 use iggy::client::{Client, UserClient};
 use iggy::messages::send_messages::{Message, Partitioning};
+use iggy::models::header::{HeaderKey, HeaderValue};
 use iggy::utils::duration::IggyDuration;
 #[cfg(feature = "debugging")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "debugging")]
+use std::collections::HashMap;
+#[cfg(feature = "debugging")]
 use std::fs::OpenOptions;
 #[cfg(feature = "debugging")]
-use std::io::Write;
+use std::io::{self, Write};
 use std::str::FromStr;
+
 #[cfg(feature = "debugging")]
-use std::sync::mpsc::{channel, Sender};
+use crate::config::ControllerConfig;
 #[cfg(feature = "debugging")]
-use std::thread;
+use crate::controller::ControllerStatistics;
+#[cfg(feature = "remote-control")]
+use crate::remote::{CommandOutcome, RemoteCommand, RemoteControlConfig, RemoteLink};
+#[cfg(feature = "debugging")]
+use crate::sinks::{DebugSink, SharedDebugSink};
+#[cfg(feature = "debugging")]
+use crate::state::PidState;
+#[cfg(feature = "debugging")]
+use crate::time::{self, Duration, Instant};
+#[cfg(feature = "debugging")]
+use std::collections::VecDeque;
 #[cfg(feature = "debugging")]
-use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "debugging")]
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard};
+#[cfg(feature = "debugging")]
+use std::thread;
 
 /// Configuration for PID controller debugging
 #[cfg(feature = "debugging")]
@@ -29,6 +47,80 @@ pub struct DebugConfig {
     pub controller_id: String,
     /// Optional sampling rate (in Hz) for debug data
     pub sample_rate_hz: Option<f64>,
+    /// How samples are reduced to `sample_rate_hz`
+    pub decimation: Decimation,
+    /// Number of recently sent samples kept for [`ControllerSnapshot`]s
+    pub snapshot_history: usize,
+    /// Switch to full-rate capture around interesting events
+    pub capture: Option<CaptureConfig>,
+    /// Additional destinations for debug samples, written alongside Iggy
+    pub sinks: Vec<SharedDebugSink>,
+    /// Wire format for samples sent to Iggy. The local log file is always JSON.
+    pub encoding: DebugEncoding,
+    /// Maximum number of samples waiting for the background thread
+    pub queue_capacity: usize,
+    /// What to do with a new sample when the queue is full
+    pub backpressure: BackpressurePolicy,
+    /// Reconnection backoff and outage buffering for the Iggy connection
+    pub reconnect: ReconnectPolicy,
+    /// How long dropping the debugger waits for queued samples to be delivered
+    pub shutdown_timeout: Duration,
+    /// Accept operator commands over the debug transport. Off unless set.
+    #[cfg(feature = "remote-control")]
+    pub remote_control: Option<RemoteControlConfig>,
+}
+
+#[cfg(feature = "debugging")]
+impl DebugConfig {
+    /// Sets the wire format for samples sent to Iggy
+    pub fn with_encoding(mut self, encoding: DebugEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets how samples are reduced to `sample_rate_hz`
+    pub fn with_decimation(mut self, decimation: Decimation) -> Self {
+        self.decimation = decimation;
+        self
+    }
+
+    /// Captures every cycle around trigger events, overriding decimation
+    pub fn with_capture(mut self, capture: CaptureConfig) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Sets the queue capacity and the policy applied when it is full
+    pub fn with_backpressure(mut self, queue_capacity: usize, policy: BackpressurePolicy) -> Self {
+        self.queue_capacity = queue_capacity;
+        self.backpressure = policy;
+        self
+    }
+
+    /// Sets how the debugger reconnects to Iggy after a failure
+    pub fn with_reconnect(mut self, reconnect: ReconnectPolicy) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Accepts signed operator commands on the debug stream
+    #[cfg(feature = "remote-control")]
+    pub fn with_remote_control(mut self, remote_control: RemoteControlConfig) -> Self {
+        self.remote_control = Some(remote_control);
+        self
+    }
+
+    /// Sets how long dropping the debugger waits for queued samples
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Adds a sink that receives every sample sent by the debugger
+    pub fn with_sink(mut self, sink: impl DebugSink + 'static) -> Self {
+        self.sinks.push(Arc::new(Mutex::new(sink)));
+        self
+    }
 }
 
 #[cfg(feature = "debugging")]
@@ -40,14 +132,438 @@ impl Default for DebugConfig {
             topic_name: "controller_data".to_string(),
             controller_id: "pid_controller".to_string(),
             sample_rate_hz: None,
+            decimation: Decimation::default(),
+            snapshot_history: 256,
+            capture: None,
+            sinks: Vec::new(),
+            encoding: DebugEncoding::default(),
+            queue_capacity: 1024,
+            backpressure: BackpressurePolicy::default(),
+            reconnect: ReconnectPolicy::default(),
+            shutdown_timeout: Duration::from_secs(2),
+            #[cfg(feature = "remote-control")]
+            remote_control: None,
+        }
+    }
+}
+
+/// Reconnection behaviour of the debugger's Iggy connection
+///
+/// After a failed connect or send, the worker waits
+/// `initial_backoff * multiplier^(failures - 1)` (capped at `max_backoff`)
+/// before the next attempt. Up to `outage_buffer` encoded samples are kept
+/// meanwhile and delivered in order once the connection is back; older ones
+/// are discarded. The local log file and extra sinks are unaffected.
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay after the first failure
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between attempts
+    pub max_backoff: Duration,
+    /// Growth factor applied per consecutive failure
+    pub multiplier: f64,
+    /// Samples kept for Iggy while disconnected
+    pub outage_buffer: usize,
+}
+
+#[cfg(feature = "debugging")]
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            outage_buffer: 1000,
+        }
+    }
+}
+
+#[cfg(feature = "debugging")]
+impl ReconnectPolicy {
+    /// Delay before the next attempt after `failures` consecutive failures
+    pub fn backoff_after(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        if secs.is_finite() && secs < self.max_backoff.as_secs_f64() {
+            Duration::from_secs_f64(secs)
+        } else {
+            self.max_backoff
+        }
+    }
+}
+
+/// State of the debugger's connection to Iggy
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// A connection attempt is in progress
+    Connecting,
+    /// The producer is ready and samples are being delivered
+    Connected,
+    /// The last attempt failed; samples are buffered until the next retry
+    Disconnected {
+        /// Consecutive failed attempts
+        failures: u32,
+    },
+    /// The debugger was dropped and its worker has exited
+    Closed,
+}
+
+/// How [`ControllerDebugger`] reduces fast loops to `sample_rate_hz`
+///
+/// Forwarding one raw sample per interval is cheap but aliases: a 1 kHz loop
+/// oscillating at 50 Hz looks flat or slow when picked at 10 Hz. The other
+/// modes look at every cycle and summarise the interval instead. Without a
+/// `sample_rate_hz`, [`Latest`](Self::Latest), [`Average`](Self::Average), and
+/// [`MinMaxEnvelope`](Self::MinMaxEnvelope) forward every sample.
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Decimation {
+    /// Forward the first sample of each interval and discard the rest
+    #[default]
+    Latest,
+    /// Forward the mean of every sample in the interval. `saturated` is set
+    /// if any cycle saturated; the timestamp and mode are the last cycle's.
+    Average,
+    /// Forward the samples with the lowest and highest process value in the
+    /// interval, in the order they occurred
+    MinMaxEnvelope,
+    /// Forward a sample whenever the setpoint, process value, or output has
+    /// moved more than `threshold` since the last one forwarded. With a
+    /// `sample_rate_hz`, an unchanged loop still reports once per interval.
+    OnChange {
+        /// Minimum absolute change that triggers a sample
+        threshold: f64,
+    },
+}
+
+/// Event that starts a full-rate capture
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureTrigger {
+    /// The absolute control error exceeds the threshold
+    ErrorAbove(f64),
+    /// The unclamped output falls outside the output limits
+    Saturation,
+    /// The operating mode changes
+    ModeChange,
+}
+
+/// Oscilloscope-style capture around rare events
+///
+/// Between events the debugger streams at `sample_rate_hz` using its
+/// [`Decimation`] mode. When any trigger fires, it sends every raw cycle from
+/// the last `pre_trigger` (up to the queue capacity) followed by every cycle
+/// until `post_trigger` has passed without another trigger, then returns to
+/// summary sampling.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{CaptureConfig, CaptureTrigger, DebugConfig};
+/// use std::time::Duration;
+///
+/// let debug_config = DebugConfig {
+///     sample_rate_hz: Some(10.0),
+///     ..Default::default()
+/// }
+/// .with_capture(
+///     CaptureConfig::new(Duration::from_millis(200), Duration::from_secs(1))
+///         .with_trigger(CaptureTrigger::ErrorAbove(5.0))
+///         .with_trigger(CaptureTrigger::Saturation),
+/// );
+/// ```
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureConfig {
+    /// Conditions that start a capture; any one is enough
+    pub triggers: Vec<CaptureTrigger>,
+    /// How much history before the trigger to include
+    pub pre_trigger: Duration,
+    /// How long to keep capturing after the last trigger
+    pub post_trigger: Duration,
+}
+
+#[cfg(feature = "debugging")]
+impl CaptureConfig {
+    /// A capture window with no triggers yet
+    pub fn new(pre_trigger: Duration, post_trigger: Duration) -> Self {
+        CaptureConfig {
+            triggers: Vec::new(),
+            pre_trigger,
+            post_trigger,
         }
     }
+
+    /// Adds a trigger condition
+    pub fn with_trigger(mut self, trigger: CaptureTrigger) -> Self {
+        self.triggers.push(trigger);
+        self
+    }
+}
+
+/// How [`ControllerDebugger`] handles a new sample when its queue is full
+///
+/// The queue sits between the control loop and the background thread that
+/// talks to Iggy and the sinks. Every policy except
+/// [`BlockWithTimeout`](Self::BlockWithTimeout) keeps the control loop
+/// non-blocking; discarded samples are counted in
+/// [`DebuggerDiagnostics::dropped`].
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// Discard the oldest queued sample to make room (the default). Telemetry
+    /// stays current at the cost of gaps further back.
+    #[default]
+    DropOldest,
+    /// Discard the new sample. Preserves a contiguous history up to the stall.
+    DropNewest,
+    /// Overwrite the most recently queued sample with the new one, so the
+    /// latest value always reaches the consumer while older queued samples
+    /// are kept.
+    CoalesceLatest,
+    /// Block the control loop for up to the given duration waiting for room,
+    /// then discard the new sample. Only for loops that can tolerate jitter.
+    BlockWithTimeout(Duration),
+}
+
+/// Counters describing a [`ControllerDebugger`]'s queue
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DebuggerDiagnostics {
+    /// Samples accepted into the queue
+    pub enqueued: u64,
+    /// Samples discarded or overwritten because the queue was full
+    pub dropped: u64,
+    /// Samples currently waiting for the background thread
+    pub queued: usize,
+    /// Queue capacity
+    pub capacity: usize,
+}
+
+/// Bounded sample queue shared between the control loop and the worker thread
+#[cfg(feature = "debugging")]
+pub(crate) struct SampleQueue {
+    capacity: usize,
+    policy: BackpressurePolicy,
+    state: Mutex<QueueState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    enqueued: AtomicU64,
+    dropped: AtomicU64,
+}
+
+#[cfg(feature = "debugging")]
+struct QueueState {
+    samples: VecDeque<ControllerDebugData>,
+    closed: bool,
+}
+
+#[cfg(feature = "debugging")]
+impl SampleQueue {
+    pub(crate) fn new(capacity: usize, policy: BackpressurePolicy) -> Self {
+        let capacity = capacity.max(1);
+        SampleQueue {
+            capacity,
+            policy,
+            state: Mutex::new(QueueState {
+                samples: VecDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            enqueued: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn push(&self, sample: ControllerDebugData) {
+        let mut state = self.lock();
+        if state.closed {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if state.samples.len() >= self.capacity {
+            match self.policy {
+                BackpressurePolicy::DropOldest => {
+                    state.samples.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                BackpressurePolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                BackpressurePolicy::CoalesceLatest => {
+                    if let Some(last) = state.samples.back_mut() {
+                        *last = sample;
+                    }
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                BackpressurePolicy::BlockWithTimeout(timeout) => {
+                    let capacity = self.capacity;
+                    state = self
+                        .not_full
+                        .wait_timeout_while(state, timeout, |s| {
+                            s.samples.len() >= capacity && !s.closed
+                        })
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0;
+                    if state.samples.len() >= self.capacity {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                }
+            }
+        }
+        state.samples.push_back(sample);
+        self.enqueued.fetch_add(1, Ordering::Relaxed);
+        drop(state);
+        self.not_empty.notify_one();
+    }
+
+    /// Waits up to `timeout` for the next sample; `None` on timeout or once
+    /// closed and drained
+    pub(crate) fn pop_timeout(&self, timeout: Duration) -> Option<ControllerDebugData> {
+        let (mut state, _) = self
+            .not_empty
+            .wait_timeout_while(self.lock(), timeout, |s| s.samples.is_empty() && !s.closed)
+            .unwrap_or_else(|e| e.into_inner());
+        let sample = state.samples.pop_front();
+        drop(state);
+        if sample.is_some() {
+            self.not_full.notify_one();
+        }
+        sample
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Blocks for the next sample; `None` once closed and drained
+    pub(crate) fn pop(&self) -> Option<ControllerDebugData> {
+        let mut state = self
+            .not_empty
+            .wait_while(self.lock(), |s| s.samples.is_empty() && !s.closed)
+            .unwrap_or_else(|e| e.into_inner());
+        let sample = state.samples.pop_front();
+        drop(state);
+        self.not_full.notify_one();
+        sample
+    }
+
+    pub(crate) fn try_pop(&self) -> Option<ControllerDebugData> {
+        let sample = self.lock().samples.pop_front();
+        if sample.is_some() {
+            self.not_full.notify_one();
+        }
+        sample
+    }
+
+    pub(crate) fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    pub(crate) fn diagnostics(&self) -> DebuggerDiagnostics {
+        DebuggerDiagnostics {
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            queued: self.lock().samples.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// Wire format for [`ControllerDebugData`] payloads
+///
+/// Every Iggy message carries a `content-type` header naming its encoding, so
+/// consumers such as Pidgeoneer can decode a mix of producers. The binary
+/// formats keep field names, which lets consumers ignore fields they don't know.
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugEncoding {
+    /// UTF-8 JSON (the default, human readable)
+    #[default]
+    Json,
+    /// MessagePack with named fields
+    MessagePack,
+    /// CBOR (RFC 8949)
+    Cbor,
+}
+
+#[cfg(feature = "debugging")]
+impl DebugEncoding {
+    /// Name of the message header carrying the content type
+    pub const HEADER: &'static str = "content-type";
+
+    /// MIME type sent in the [`HEADER`](Self::HEADER) message header
+    pub fn content_type(self) -> &'static str {
+        match self {
+            DebugEncoding::Json => "application/json",
+            DebugEncoding::MessagePack => "application/msgpack",
+            DebugEncoding::Cbor => "application/cbor",
+        }
+    }
+
+    /// Parses a MIME type produced by [`content_type`](Self::content_type)
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type {
+            "application/json" => Some(DebugEncoding::Json),
+            "application/msgpack" | "application/x-msgpack" => Some(DebugEncoding::MessagePack),
+            "application/cbor" => Some(DebugEncoding::Cbor),
+            _ => None,
+        }
+    }
+}
+
+/// Current version of the [`ControllerDebugData`] schema
+///
+/// - Version 1: timestamp, controller ID, setpoint, process value, error,
+///   output, and the three PID terms. Payloads without a `schema_version`
+///   field are version 1.
+/// - Version 2: adds `dt`, the gains in effect, the saturation flag, and the
+///   operating mode.
+#[cfg(feature = "debugging")]
+pub const DEBUG_SCHEMA_VERSION: u32 = 2;
+
+/// Operating mode of the loop when a sample was taken
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerMode {
+    /// The PID output drives the actuator
+    #[default]
+    Automatic,
+    /// The actuator is driven by an operator or fallback value; the PID
+    /// output is computed but not applied
+    Manual,
+}
+
+#[cfg(feature = "debugging")]
+fn schema_version_v1() -> u32 {
+    1
 }
 
 /// Debug data for a PID controller
+///
+/// Fields introduced after version 1 default to zero/`false`/automatic when
+/// decoding older payloads; check [`schema_version`](Self::schema_version)
+/// before relying on them.
 #[cfg(feature = "debugging")]
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ControllerDebugData {
+    /// Schema version, see [`DEBUG_SCHEMA_VERSION`]
+    #[serde(default = "schema_version_v1")]
+    pub schema_version: u32,
     /// Timestamp in milliseconds since UNIX epoch
     pub timestamp: u64,
     /// Controller ID
@@ -66,22 +582,362 @@ pub struct ControllerDebugData {
     pub i_term: f64,
     /// Derivative term
     pub d_term: f64,
+    /// Time step in seconds (since version 2)
+    #[serde(default)]
+    pub dt: f64,
+    /// Proportional gain in effect (since version 2)
+    #[serde(default)]
+    pub kp: f64,
+    /// Integral gain in effect (since version 2)
+    #[serde(default)]
+    pub ki: f64,
+    /// Derivative gain in effect (since version 2)
+    #[serde(default)]
+    pub kd: f64,
+    /// Whether the unclamped output exceeded the output limits (since version 2)
+    #[serde(default)]
+    pub saturated: bool,
+    /// Operating mode of the loop (since version 2)
+    #[serde(default)]
+    pub mode: ControllerMode,
+}
+
+#[cfg(feature = "debugging")]
+impl ControllerDebugData {
+    /// Serializes the sample in the given encoding
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn encode(&self, encoding: DebugEncoding) -> io::Result<Vec<u8>> {
+        match encoding {
+            DebugEncoding::Json => serde_json::to_vec(self).map_err(io::Error::other),
+            DebugEncoding::MessagePack => rmp_serde::to_vec_named(self).map_err(io::Error::other),
+            DebugEncoding::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(self, &mut buf).map_err(io::Error::other)?;
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Deserializes a sample produced by [`encode`](Self::encode)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` is not a valid sample in `encoding`.
+    pub fn decode(bytes: &[u8], encoding: DebugEncoding) -> io::Result<Self> {
+        match encoding {
+            DebugEncoding::Json => serde_json::from_slice(bytes).map_err(io::Error::other),
+            DebugEncoding::MessagePack => rmp_serde::from_slice(bytes).map_err(io::Error::other),
+            DebugEncoding::Cbor => ciborium::from_reader(bytes).map_err(io::Error::other),
+        }
+    }
+}
+
+/// Applies a [`Decimation`] mode to the samples produced by the control loop
+#[cfg(feature = "debugging")]
+pub(crate) struct Decimator {
+    decimation: Decimation,
+    interval: Option<Duration>,
+    window_start: Instant,
+    count: u32,
+    sum: Option<ControllerDebugData>,
+    min: Option<(u32, ControllerDebugData)>,
+    max: Option<(u32, ControllerDebugData)>,
+    last_sent: Option<ControllerDebugData>,
+}
+
+#[cfg(feature = "debugging")]
+impl Decimator {
+    pub(crate) fn new(decimation: Decimation, interval: Option<Duration>, now: Instant) -> Self {
+        Decimator {
+            decimation,
+            interval,
+            window_start: now,
+            count: 0,
+            sum: None,
+            min: None,
+            max: None,
+            last_sent: None,
+        }
+    }
+
+    fn interval_elapsed(&self, now: Instant) -> bool {
+        self.interval
+            .is_none_or(|interval| now.duration_since(self.window_start) >= interval)
+    }
+
+    /// Whether the next cycle needs a sample at all. Only
+    /// [`Decimation::Latest`] can skip building one.
+    pub(crate) fn wants_sample(&self, now: Instant) -> bool {
+        self.decimation != Decimation::Latest || self.interval_elapsed(now)
+    }
+
+    /// Discards the current interval and starts a new one at `now`
+    pub(crate) fn reset(&mut self, now: Instant) {
+        self.window_start = now;
+        self.count = 0;
+        self.sum = None;
+        self.min = None;
+        self.max = None;
+    }
+
+    /// Feeds one cycle's sample, passing any samples to forward to `emit`
+    pub(crate) fn offer(
+        &mut self,
+        data: ControllerDebugData,
+        now: Instant,
+        mut emit: impl FnMut(ControllerDebugData),
+    ) {
+        match self.decimation {
+            Decimation::Latest => {
+                if self.interval_elapsed(now) {
+                    self.window_start = now;
+                    emit(data);
+                }
+            }
+            Decimation::Average => {
+                self.count += 1;
+                match &mut self.sum {
+                    Some(sum) => accumulate(sum, &data),
+                    None => self.sum = Some(data),
+                }
+                if self.interval_elapsed(now) {
+                    self.window_start = now;
+                    self.finish(emit);
+                }
+            }
+            Decimation::MinMaxEnvelope => {
+                let index = self.count;
+                self.count += 1;
+                if self
+                    .min
+                    .as_ref()
+                    .is_none_or(|(_, min)| data.process_value < min.process_value)
+                {
+                    self.min = Some((index, data.clone()));
+                }
+                if self
+                    .max
+                    .as_ref()
+                    .is_none_or(|(_, max)| data.process_value > max.process_value)
+                {
+                    self.max = Some((index, data));
+                }
+                if self.interval_elapsed(now) {
+                    self.window_start = now;
+                    self.finish(emit);
+                }
+            }
+            Decimation::OnChange { threshold } => {
+                let changed = self.last_sent.as_ref().is_none_or(|last| {
+                    (data.setpoint - last.setpoint).abs() > threshold
+                        || (data.process_value - last.process_value).abs() > threshold
+                        || (data.output - last.output).abs() > threshold
+                });
+                let heartbeat = self.interval.is_some() && self.interval_elapsed(now);
+                if changed || heartbeat {
+                    self.window_start = now;
+                    self.last_sent = Some(data.clone());
+                    emit(data);
+                }
+            }
+        }
+    }
+
+    /// Forwards whatever the current interval has accumulated
+    pub(crate) fn finish(&mut self, mut emit: impl FnMut(ControllerDebugData)) {
+        let count = std::mem::take(&mut self.count);
+        if let Some(mut sum) = self.sum.take() {
+            let n = f64::from(count.max(1));
+            for field in numeric_fields(&mut sum) {
+                *field /= n;
+            }
+            emit(sum);
+        }
+        match (self.min.take(), self.max.take()) {
+            (Some((min_index, min)), Some((max_index, max))) => {
+                if min_index == max_index {
+                    emit(min);
+                } else if min_index < max_index {
+                    emit(min);
+                    emit(max);
+                } else {
+                    emit(max);
+                    emit(min);
+                }
+            }
+            (Some((_, only)), None) | (None, Some((_, only))) => emit(only),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Trigger state machine for [`CaptureConfig`]
+#[cfg(feature = "debugging")]
+pub(crate) struct Capture {
+    config: CaptureConfig,
+    history: VecDeque<(Instant, ControllerDebugData)>,
+    max_history: usize,
+    last_mode: Option<ControllerMode>,
+    capture_until: Option<Instant>,
+}
+
+#[cfg(feature = "debugging")]
+impl Capture {
+    pub(crate) fn new(config: CaptureConfig, max_history: usize) -> Self {
+        Capture {
+            config,
+            history: VecDeque::new(),
+            max_history: max_history.max(1),
+            last_mode: None,
+            capture_until: None,
+        }
+    }
+
+    pub(crate) fn is_capturing(&self) -> bool {
+        self.capture_until.is_some()
+    }
+
+    fn triggered(&self, data: &ControllerDebugData) -> bool {
+        self.config.triggers.iter().any(|trigger| match *trigger {
+            CaptureTrigger::ErrorAbove(threshold) => data.error.abs() > threshold,
+            CaptureTrigger::Saturation => data.saturated,
+            CaptureTrigger::ModeChange => self.last_mode.is_some_and(|mode| mode != data.mode),
+        })
+    }
+
+    /// Feeds one cycle's sample. Samples that belong to a capture are passed
+    /// to `emit`, along with the pre-trigger history when a capture starts;
+    /// otherwise the sample is handed back for regular decimation.
+    pub(crate) fn observe(
+        &mut self,
+        data: ControllerDebugData,
+        now: Instant,
+        mut emit: impl FnMut(ControllerDebugData),
+    ) -> Option<ControllerDebugData> {
+        let triggered = self.triggered(&data);
+        self.last_mode = Some(data.mode);
+
+        if triggered {
+            self.capture_until = Some(now + self.config.post_trigger);
+        }
+        match self.capture_until {
+            Some(until) if now <= until => {
+                let pre_trigger = self.config.pre_trigger;
+                for (at, sample) in self.history.drain(..) {
+                    if now.duration_since(at) <= pre_trigger {
+                        emit(sample);
+                    }
+                }
+                emit(data);
+                None
+            }
+            _ => {
+                self.capture_until = None;
+                let pre_trigger = self.config.pre_trigger;
+                while self.history.len() >= self.max_history
+                    || self
+                        .history
+                        .front()
+                        .is_some_and(|(at, _)| now.duration_since(*at) > pre_trigger)
+                {
+                    self.history.pop_front();
+                }
+                if !pre_trigger.is_zero() {
+                    self.history.push_back((now, data.clone()));
+                }
+                Some(data)
+            }
+        }
+    }
+}
+
+/// The fields [`Decimation::Average`] averages
+#[cfg(feature = "debugging")]
+fn numeric_fields(data: &mut ControllerDebugData) -> [&mut f64; 11] {
+    [
+        &mut data.setpoint,
+        &mut data.process_value,
+        &mut data.error,
+        &mut data.output,
+        &mut data.p_term,
+        &mut data.i_term,
+        &mut data.d_term,
+        &mut data.dt,
+        &mut data.kp,
+        &mut data.ki,
+        &mut data.kd,
+    ]
+}
+
+/// Adds `data` into the running sum, keeping its timestamp and mode
+#[cfg(feature = "debugging")]
+fn accumulate(sum: &mut ControllerDebugData, data: &ControllerDebugData) {
+    let mut data = data.clone();
+    for (total, value) in numeric_fields(sum)
+        .into_iter()
+        .zip(numeric_fields(&mut data))
+    {
+        *total += *value;
+    }
+    sum.timestamp = data.timestamp;
+    sum.mode = data.mode;
+    sum.schema_version = data.schema_version;
+    sum.saturated |= data.saturated;
+}
+
+/// Complete state of one controller, for bug reports and post-mortems
+///
+/// Obtained locally from [`PidController::snapshot`](crate::PidController::snapshot)
+/// or, with the `remote-control` feature, published on request to the
+/// snapshot topic. Serializes to a single self-describing JSON document.
+#[cfg(feature = "debugging")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControllerSnapshot {
+    /// Debug schema version of the embedded samples
+    pub schema_version: u32,
+    /// Milliseconds since the Unix epoch when the snapshot was taken
+    pub timestamp: u64,
+    /// Controller the snapshot describes
+    pub controller_id: String,
+    /// Operating mode at the time of the snapshot
+    pub mode: ControllerMode,
+    /// Gains, limits, and filter settings
+    pub config: ControllerConfig,
+    /// Integral, previous error/measurement, and derivative filter state
+    pub state: PidState,
+    /// Performance statistics since the last reset
+    pub statistics: ControllerStatistics,
+    /// Debugger queue counters
+    pub diagnostics: DebuggerDiagnostics,
+    /// Debugger's Iggy connection state
+    pub connection: ConnectionState,
+    /// Most recently sent samples, oldest first
+    pub recent_samples: Vec<ControllerDebugData>,
 }
 
 /// Component for debugging PID controllers
 #[cfg(feature = "debugging")]
 pub struct ControllerDebugger {
     config: DebugConfig,
-    tx: Sender<ControllerDebugData>,
-    last_sample: Instant,
-    sample_interval: Option<Duration>,
+    queue: Arc<SampleQueue>,
+    connection: Arc<Mutex<ConnectionState>>,
+    decimator: Decimator,
+    capture: Option<Capture>,
+    recent: VecDeque<ControllerDebugData>,
+    mode: ControllerMode,
+    worker: Option<mpsc::Receiver<bool>>,
+    #[cfg(feature = "remote-control")]
+    remote: Option<RemoteLink>,
 }
 
 #[cfg(feature = "debugging")]
 impl ControllerDebugger {
     /// Create a new controller debugger with the given configuration
     pub fn new(config: DebugConfig) -> Self {
-        let (tx, rx) = channel();
+        let queue = Arc::new(SampleQueue::new(config.queue_capacity, config.backpressure));
 
         // Set up sampling interval if specified
         let sample_interval = config
@@ -92,137 +948,170 @@ impl ControllerDebugger {
         let thread_config = config.clone();
 
         // Spawn a separate thread to handle debugging data
+        let connection = Arc::new(Mutex::new(ConnectionState::Connecting));
+        let worker_queue = Arc::clone(&queue);
+        let worker_connection = Arc::clone(&connection);
+        let (done, worker) = mpsc::channel();
         thread::spawn(move || {
-            println!(
-                "🔍 PID controller debugging started for '{}'",
-                thread_config.controller_id
-            );
+            let delivered = run_worker(thread_config, worker_queue, worker_connection);
+            let _ = done.send(delivered);
+        });
 
-            // Create and open a log file for debug data
-            let log_filename = format!("{}_debug.log", thread_config.controller_id);
+        let decimator = Decimator::new(config.decimation, sample_interval, Instant::now());
+        let capture = config
+            .capture
+            .clone()
+            .map(|capture| Capture::new(capture, config.queue_capacity));
+        #[cfg(feature = "remote-control")]
+        let remote = config
+            .remote_control
+            .clone()
+            .map(|remote| RemoteLink::spawn(&config, remote));
+        Self {
+            config,
+            queue,
+            connection,
+            decimator,
+            capture,
+            recent: VecDeque::new(),
+            mode: ControllerMode::Automatic,
+            worker: Some(worker),
+            #[cfg(feature = "remote-control")]
+            remote,
+        }
+    }
 
-            println!("📊 Debug data will be logged to {}", log_filename);
-            println!(
-                "⚠️  Attempting to connect to Iggy server at {}",
-                thread_config.iggy_url
-            );
-            println!(
-                "   Stream: {}, Topic: {}",
-                thread_config.stream_name, thread_config.topic_name
-            );
+    /// Flushes queued samples and stops the background thread
+    ///
+    /// Forwards any partially decimated interval, lets the worker drain the
+    /// queue into Iggy and the sinks, and disconnects from Iggy. Waits at most
+    /// `timeout` for that to finish. Returns `true` if every sample was
+    /// delivered in time; `false` if the worker is still busy or had to
+    /// discard samples because Iggy was unreachable. Samples logged after
+    /// shutdown are dropped. Calling it again returns `true` immediately.
+    pub fn shutdown(&mut self, timeout: Duration) -> bool {
+        let Some(worker) = self.worker.take() else {
+            return true;
+        };
+        let queue = &self.queue;
+        self.decimator.finish(|data| queue.push(data));
+        self.queue.close();
+        #[cfg(feature = "remote-control")]
+        {
+            self.remote = None;
+        }
+        worker.recv_timeout(timeout).unwrap_or(false)
+    }
 
-            // Create a runtime for async operations
-            let runtime = match tokio::runtime::Runtime::new() {
-                Ok(rt) => rt,
-                Err(e) => {
-                    eprintln!("Failed to create tokio runtime: {}", e);
-                    return;
-                }
-            };
+    /// Validated remote commands waiting to be applied, oldest first
+    #[cfg(feature = "remote-control")]
+    pub(crate) fn take_commands(&self) -> Vec<RemoteCommand> {
+        self.remote
+            .as_ref()
+            .map(RemoteLink::take_commands)
+            .unwrap_or_default()
+    }
 
-            // Try to connect to Iggy client
-            let connection_string = format!("iggy://iggy:iggy@{}", thread_config.iggy_url);
-            let iggy_result = runtime.block_on(async {
-                // Try to connect and create producer
-                match iggy::clients::client::IggyClient::from_connection_string(&connection_string)
-                {
-                    Ok(client) => {
-                        client.connect().await.unwrap();
-                        println!("✅ Connected to Iggy server");
-                        client.login_user("iggy", "iggy").await.unwrap();
-
-                        let mut producer = client
-                            .producer(&thread_config.stream_name, &thread_config.topic_name)
-                            .unwrap()
-                            .batch_size(1000)
-                            .send_interval(IggyDuration::from_str("1ms").unwrap())
-                            .partitioning(Partitioning::balanced())
-                            .build();
-
-                        producer.init().await.unwrap();
-
-                        println!(
-                            "✅ Producer initialized for stream '{}', topic '{}'",
-                            thread_config.stream_name, thread_config.topic_name
-                        );
-                        // Create a producer
-                        Some(producer)
-                    }
-                    Err(e) => {
-                        eprintln!("❌ Failed to connect to Iggy server: {}", e);
-                        None
-                    }
-                }
-            });
-
-            // Process messages from the channel
-            match iggy_result {
-                Some(producer) => {
-                    println!("✅ Ready to send messages to Iggy");
-
-                    // Process debug data and send to Iggy
-                    while let Ok(debug_data) = rx.recv() {
-                        // Convert to JSON for display
-                        if let Ok(json) = serde_json::to_string(&debug_data) {
-                            // Write to log file as backup
-                            if let Ok(mut file) = OpenOptions::new()
-                                .create(true)
-                                .append(true)
-                                .open(&log_filename)
-                            {
-                                if let Err(e) = writeln!(file, "{}", json) {
-                                    eprintln!("Error writing to log file: {}", e);
-                                }
-                            }
-
-                            let result = runtime.block_on(async {
-                                let payload = serde_json::to_vec(&debug_data).unwrap();
-                                let message = Message::new(None, payload.into(), None);
-                                producer.send(vec![message]).await
-                            });
-
-                            if let Err(e) = result {
-                                eprintln!("❌ Failed to send message to Iggy: {}", e);
-                            }
-                        }
-                    }
-                }
-                None => {
-                    println!("⚠️ Falling back to file logging only");
-
-                    // If Iggy is not available, just log to file
-                    while let Ok(debug_data) = rx.recv() {
-                        // Convert to JSON
-                        if let Ok(json) = serde_json::to_string(&debug_data) {
-                            println!("📥 Logging: {}", json);
-
-                            // Write to log file
-                            if let Ok(mut file) = OpenOptions::new()
-                                .create(true)
-                                .append(true)
-                                .open(&log_filename)
-                            {
-                                if let Err(e) = writeln!(file, "{}", json) {
-                                    eprintln!("Error writing to log file: {}", e);
-                                }
-                            } else {
-                                eprintln!("Error opening log file");
-                            }
-                        }
-                    }
-                }
-            }
-        });
+    /// Publishes the audit event for a handled remote command
+    #[cfg(feature = "remote-control")]
+    pub(crate) fn audit(&self, command: &RemoteCommand, outcome: CommandOutcome) {
+        if let Some(remote) = &self.remote {
+            remote.audit(command, outcome);
+        }
+    }
 
-        Self {
-            config,
-            tx,
-            last_sample: Instant::now(),
-            sample_interval,
+    /// Queue counters, including samples dropped under backpressure
+    pub fn diagnostics(&self) -> DebuggerDiagnostics {
+        self.queue.diagnostics()
+    }
+
+    /// Bundles the caller's controller state with the debugger's mode,
+    /// counters, and recently sent samples
+    pub fn snapshot(
+        &self,
+        config: &ControllerConfig,
+        state: &PidState,
+        statistics: ControllerStatistics,
+    ) -> ControllerSnapshot {
+        ControllerSnapshot {
+            schema_version: DEBUG_SCHEMA_VERSION,
+            timestamp: time::unix_millis(),
+            controller_id: self.config.controller_id.clone(),
+            mode: self.mode,
+            config: config.clone(),
+            state: state.clone(),
+            statistics,
+            diagnostics: self.diagnostics(),
+            connection: self.connection_state(),
+            recent_samples: self.recent.iter().cloned().collect(),
         }
     }
 
+    /// Publishes a snapshot on the remote-control snapshot topic
+    #[cfg(feature = "remote-control")]
+    pub(crate) fn publish_snapshot(&self, snapshot: ControllerSnapshot) {
+        if let Some(remote) = &self.remote {
+            remote.publish_snapshot(snapshot);
+        }
+    }
+
+    /// Whether a triggered full-rate capture is in progress
+    pub fn is_capturing(&self) -> bool {
+        self.capture.as_ref().is_some_and(Capture::is_capturing)
+    }
+
+    /// Current state of the Iggy connection
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.connection.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Operating mode recorded in subsequent samples
+    pub fn mode(&self) -> ControllerMode {
+        self.mode
+    }
+
+    /// Sets the operating mode recorded in subsequent samples
+    pub fn set_mode(&mut self, mode: ControllerMode) {
+        self.mode = mode;
+    }
+
+    /// Log one control cycle with full loop context
+    ///
+    /// `state` is the state returned by [`pid_compute`](crate::pid_compute)
+    /// for this cycle and `process_value` the measurement it was given.
+    pub fn log_cycle(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        dt: f64,
+    ) {
+        if !self.should_sample() {
+            return;
+        }
+
+        let error = state.prev_error;
+        let (kp, _, kd) = config.gains_for(false);
+        let p_term = kp * error;
+        let i_term = state.integral_contribution;
+        let d_term = kd * state.prev_filtered_derivative;
+        let unclamped = p_term + i_term + d_term;
+        let mut data = self.sample(config.setpoint, process_value, error, state.last_output);
+        data.p_term = p_term;
+        data.i_term = i_term;
+        data.d_term = d_term;
+        data.dt = dt;
+        data.kp = config.kp;
+        data.ki = config.ki;
+        data.kd = config.kd;
+        data.saturated = unclamped > config.max_output || unclamped < config.min_output;
+        self.send(data);
+    }
+
     /// Log the current state of the PID controller
+    ///
+    /// Produces a version 1 sample without gains or saturation; prefer
+    /// [`log_cycle`](Self::log_cycle).
     pub fn log_pid_state(
         &mut self,
         setpoint: f64,
@@ -232,20 +1121,24 @@ impl ControllerDebugger {
         i_term: f64,
         d_term: f64,
         output: f64,
-        _dt: f64,
+        dt: f64,
     ) {
-        self.send_debug_data(
-            setpoint,
-            process_value,
-            error,
-            output,
-            p_term,
-            i_term,
-            d_term,
-        );
+        if !self.should_sample() {
+            return;
+        }
+        let mut data = self.sample(setpoint, process_value, error, output);
+        data.schema_version = 1;
+        data.p_term = p_term;
+        data.i_term = i_term;
+        data.d_term = d_term;
+        data.dt = dt;
+        self.send(data);
     }
 
     /// Send debug data
+    ///
+    /// Produces a version 1 sample without gains or saturation; prefer
+    /// [`log_cycle`](Self::log_cycle).
     pub fn send_debug_data(
         &mut self,
         setpoint: f64,
@@ -256,34 +1149,437 @@ impl ControllerDebugger {
         i_term: f64,
         d_term: f64,
     ) {
-        // Check if we should send debug data (based on sampling rate)
-        if let Some(interval) = self.sample_interval {
-            let now = Instant::now();
-            if now.duration_since(self.last_sample) < interval {
-                return;
-            }
-            self.last_sample = now;
+        if !self.should_sample() {
+            return;
         }
+        let mut data = self.sample(setpoint, process_value, error, output);
+        data.schema_version = 1;
+        data.p_term = p_term;
+        data.i_term = i_term;
+        data.d_term = d_term;
+        self.send(data);
+    }
 
-        // Create debug data
-        let debug_data = ControllerDebugData {
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
+    /// Check if we should send debug data (based on sampling rate)
+    fn should_sample(&mut self) -> bool {
+        self.capture.is_some() || self.decimator.wants_sample(Instant::now())
+    }
+
+    /// Build a sample stamped with the current time, controller ID, and mode
+    fn sample(
+        &self,
+        setpoint: f64,
+        process_value: f64,
+        error: f64,
+        output: f64,
+    ) -> ControllerDebugData {
+        ControllerDebugData {
+            schema_version: DEBUG_SCHEMA_VERSION,
+            timestamp: time::unix_millis(),
             controller_id: self.config.controller_id.clone(),
             setpoint,
             process_value,
             error,
             output,
-            p_term,
-            i_term,
-            d_term,
+            p_term: 0.0,
+            i_term: 0.0,
+            d_term: 0.0,
+            dt: 0.0,
+            kp: 0.0,
+            ki: 0.0,
+            kd: 0.0,
+            saturated: false,
+            mode: self.mode,
+        }
+    }
+
+    fn send(&mut self, debug_data: ControllerDebugData) {
+        let now = Instant::now();
+        let queue = &self.queue;
+        let recent = &mut self.recent;
+        let history = self.config.snapshot_history;
+        let mut emit = |data: ControllerDebugData| {
+            if history > 0 {
+                if recent.len() >= history {
+                    recent.pop_front();
+                }
+                recent.push_back(data.clone());
+            }
+            queue.push(data);
+        };
+        let debug_data = match &mut self.capture {
+            Some(capture) => match capture.observe(debug_data, now, &mut emit) {
+                Some(data) => data,
+                None => {
+                    // Captured at full rate; summarise afresh once it ends
+                    self.decimator.reset(now);
+                    return;
+                }
+            },
+            None => debug_data,
         };
+        self.decimator.offer(debug_data, now, emit);
+    }
+}
+
+#[cfg(feature = "debugging")]
+impl Drop for ControllerDebugger {
+    fn drop(&mut self) {
+        self.shutdown(self.config.shutdown_timeout);
+    }
+}
 
-        // Send debug data to channel
-        if let Err(e) = self.tx.send(debug_data) {
-            eprintln!("Failed to send debug data to channel: {}", e);
+/// Connect to Iggy and build a producer, or return `None` if the server is unreachable
+///
+/// The client's own retry loop is disabled so a dead server fails fast; the
+/// worker schedules retries according to [`ReconnectPolicy`].
+#[cfg(feature = "debugging")]
+fn connect_iggy(
+    runtime: &tokio::runtime::Runtime,
+    config: &DebugConfig,
+) -> Option<(
+    iggy::clients::client::IggyClient,
+    iggy::clients::producer::IggyProducer,
+)> {
+    runtime.block_on(async {
+        let client = iggy_client(config).await?;
+        let producer = iggy_producer(&client, &config.stream_name, &config.topic_name).await?;
+        Some((client, producer))
+    })
+}
+
+/// Connect and log in to the Iggy server at `config.iggy_url`, failing fast
+#[cfg(feature = "debugging")]
+pub(crate) async fn iggy_client(config: &DebugConfig) -> Option<iggy::clients::client::IggyClient> {
+    let connection_string = format!(
+        "iggy://iggy:iggy@{}?reconnection_retries=0",
+        config.iggy_url
+    );
+    let client = match iggy::clients::client::IggyClient::from_connection_string(&connection_string)
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("❌ Failed to create Iggy client: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = client.connect().await {
+        eprintln!("❌ Failed to connect to Iggy server: {}", e);
+        return None;
+    }
+    println!("✅ Connected to Iggy server");
+    if let Err(e) = client.login_user("iggy", "iggy").await {
+        eprintln!("❌ Failed to login to Iggy: {}", e);
+        return None;
+    }
+    Some(client)
+}
+
+/// Build a producer for `stream`/`topic`, creating them if needed
+#[cfg(feature = "debugging")]
+pub(crate) async fn iggy_producer(
+    client: &iggy::clients::client::IggyClient,
+    stream: &str,
+    topic: &str,
+) -> Option<iggy::clients::producer::IggyProducer> {
+    let mut producer = match client.producer(stream, topic) {
+        Ok(builder) => builder
+            .batch_size(1000)
+            .send_interval(IggyDuration::from_str("1ms").unwrap())
+            .partitioning(Partitioning::balanced())
+            .build(),
+        Err(e) => {
+            eprintln!("❌ Failed to create Iggy producer: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = producer.init().await {
+        eprintln!("❌ Failed to initialize Iggy producer: {}", e);
+        return None;
+    }
+
+    println!(
+        "✅ Producer initialized for stream '{}', topic '{}'",
+        stream, topic
+    );
+    Some(producer)
+}
+
+/// Message headers announcing the payload encoding
+#[cfg(feature = "debugging")]
+fn content_type_header(encoding: DebugEncoding) -> HashMap<HeaderKey, HeaderValue> {
+    let mut headers = HashMap::new();
+    if let (Ok(key), Ok(value)) = (
+        HeaderKey::new(DebugEncoding::HEADER),
+        HeaderValue::from_str(encoding.content_type()),
+    ) {
+        headers.insert(key, value);
+    }
+    headers
+}
+
+/// The worker's Iggy connection: reconnects with backoff and buffers
+/// encoded payloads while the server is unreachable
+#[cfg(feature = "debugging")]
+struct IggyLink<'a> {
+    config: &'a DebugConfig,
+    runtime: tokio::runtime::Runtime,
+    client: Option<iggy::clients::client::IggyClient>,
+    producer: Option<iggy::clients::producer::IggyProducer>,
+    state: Arc<Mutex<ConnectionState>>,
+    failures: u32,
+    next_attempt: Instant,
+    backlog: VecDeque<Vec<u8>>,
+}
+
+#[cfg(feature = "debugging")]
+impl<'a> IggyLink<'a> {
+    fn new(
+        config: &'a DebugConfig,
+        runtime: tokio::runtime::Runtime,
+        state: Arc<Mutex<ConnectionState>>,
+    ) -> Self {
+        IggyLink {
+            config,
+            runtime,
+            client: None,
+            producer: None,
+            state,
+            failures: 0,
+            next_attempt: Instant::now(),
+            backlog: VecDeque::new(),
         }
     }
+
+    fn set_state(&self, state: ConnectionState) {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) = state;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.producer.is_some()
+    }
+
+    /// How long the worker may sleep before a retry is due, if one is pending
+    fn retry_wait(&self) -> Option<Duration> {
+        if self.producer.is_some() || self.backlog.is_empty() {
+            return None;
+        }
+        Some(self.next_attempt.saturating_duration_since(Instant::now()))
+    }
+
+    fn fail(&mut self) {
+        self.producer = None;
+        self.client = None;
+        self.failures = self.failures.saturating_add(1);
+        let delay = self.config.reconnect.backoff_after(self.failures);
+        self.next_attempt = Instant::now() + delay;
+        self.set_state(ConnectionState::Disconnected {
+            failures: self.failures,
+        });
+        println!(
+            "⚠️ Iggy unavailable, retrying in {:?} ({} samples buffered)",
+            delay,
+            self.backlog.len()
+        );
+    }
+
+    fn ensure_connected(&mut self) -> bool {
+        if self.producer.is_some() {
+            return true;
+        }
+        if Instant::now() < self.next_attempt {
+            return false;
+        }
+        self.set_state(ConnectionState::Connecting);
+        match connect_iggy(&self.runtime, self.config) {
+            Some((client, producer)) => {
+                println!("✅ Ready to send messages to Iggy");
+                self.client = Some(client);
+                self.producer = Some(producer);
+                self.failures = 0;
+                self.set_state(ConnectionState::Connected);
+                true
+            }
+            None => {
+                self.fail();
+                false
+            }
+        }
+    }
+
+    /// Queue a payload and try to deliver everything buffered
+    fn send(&mut self, payload: Vec<u8>) {
+        self.backlog.push_back(payload);
+        while self.backlog.len() > self.config.reconnect.outage_buffer.max(1) {
+            self.backlog.pop_front();
+        }
+        self.flush();
+    }
+
+    fn flush(&mut self) {
+        if self.backlog.is_empty() || !self.ensure_connected() {
+            return;
+        }
+        let Some(producer) = &self.producer else {
+            return;
+        };
+        let headers = content_type_header(self.config.encoding);
+        let messages: Vec<Message> = self
+            .backlog
+            .iter()
+            .map(|payload| Message::new(None, payload.clone().into(), Some(headers.clone())))
+            .collect();
+
+        match self.runtime.block_on(producer.send(messages)) {
+            Ok(()) => self.backlog.clear(),
+            Err(e) => {
+                eprintln!("❌ Failed to send message to Iggy: {}", e);
+                self.fail();
+            }
+        }
+    }
+
+    /// Delivers what is buffered if still connected, then disconnects.
+    /// Returns `false` if buffered samples had to be abandoned.
+    fn close(&mut self) -> bool {
+        if self.producer.is_some() {
+            self.flush();
+        }
+        if let Some(client) = self.client.take() {
+            self.producer = None;
+            if let Err(e) = self.runtime.block_on(client.disconnect()) {
+                eprintln!("❌ Failed to disconnect from Iggy: {}", e);
+            }
+        }
+        self.set_state(ConnectionState::Closed);
+        if !self.backlog.is_empty() {
+            eprintln!(
+                "⚠️ Discarding {} samples that never reached Iggy",
+                self.backlog.len()
+            );
+        }
+        self.backlog.is_empty()
+    }
+}
+
+/// Write one sample to the log file, Iggy, and any extra sinks
+#[cfg(feature = "debugging")]
+fn process_sample(
+    config: &DebugConfig,
+    log_filename: &str,
+    link: &mut IggyLink<'_>,
+    debug_data: &ControllerDebugData,
+) {
+    // Convert to JSON for the log file and Iggy
+    if let Ok(json) = serde_json::to_string(debug_data) {
+        if !link.is_connected() {
+            println!("📥 Logging: {}", json);
+        }
+
+        // Write to log file as backup
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_filename)
+        {
+            Ok(mut file) => {
+                if let Err(e) = writeln!(file, "{}", json) {
+                    eprintln!("Error writing to log file: {}", e);
+                }
+            }
+            Err(_) => eprintln!("Error opening log file"),
+        }
+
+        let payload = match config.encoding {
+            DebugEncoding::Json => Ok(json.into_bytes()),
+            encoding => debug_data.encode(encoding),
+        };
+        match payload {
+            Ok(payload) => link.send(payload),
+            Err(e) => eprintln!("❌ Failed to encode debug data: {}", e),
+        }
+    }
+
+    for sink in &config.sinks {
+        if let Ok(mut sink) = sink.lock() {
+            if let Err(e) = sink.write(debug_data) {
+                eprintln!("Error writing to debug sink: {}", e);
+            }
+        }
+    }
+}
+
+/// Background thread body: forwards samples to the log file, Iggy, and any extra sinks
+///
+/// Returns `true` once every queued sample has been delivered, `false` if
+/// samples were abandoned on the way out.
+#[cfg(feature = "debugging")]
+fn run_worker(
+    config: DebugConfig,
+    queue: Arc<SampleQueue>,
+    state: Arc<Mutex<ConnectionState>>,
+) -> bool {
+    println!(
+        "🔍 PID controller debugging started for '{}'",
+        config.controller_id
+    );
+
+    // Create and open a log file for debug data
+    let log_filename = format!("{}_debug.log", config.controller_id);
+
+    println!("📊 Debug data will be logged to {}", log_filename);
+    println!(
+        "⚠️  Attempting to connect to Iggy server at {}",
+        config.iggy_url
+    );
+    println!(
+        "   Stream: {}, Topic: {}",
+        config.stream_name, config.topic_name
+    );
+
+    // Create a runtime for async operations
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("Failed to create tokio runtime: {}", e);
+            *state.lock().unwrap_or_else(|e| e.into_inner()) = ConnectionState::Closed;
+            return false;
+        }
+    };
+
+    let mut link = IggyLink::new(&config, runtime, Arc::clone(&state));
+    if !link.ensure_connected() {
+        println!("⚠️ Falling back to file logging until Iggy is reachable");
+    }
+
+    // Process debug data until the debugger is dropped, flushing sinks
+    // whenever the queue runs dry. While disconnected with buffered samples,
+    // wake up when the next reconnect attempt is due.
+    loop {
+        let next = match link.retry_wait() {
+            Some(wait) => queue.pop_timeout(wait),
+            None => queue.pop(),
+        };
+        let Some(debug_data) = next else {
+            if queue.is_closed() {
+                break;
+            }
+            link.flush();
+            continue;
+        };
+
+        process_sample(&config, &log_filename, &mut link, &debug_data);
+        while let Some(debug_data) = queue.try_pop() {
+            process_sample(&config, &log_filename, &mut link, &debug_data);
+        }
+        for sink in &config.sinks {
+            if let Ok(mut sink) = sink.lock() {
+                if let Err(e) = sink.flush() {
+                    eprintln!("Error flushing debug sink: {}", e);
+                }
+            }
+        }
+    }
+
+    link.close()
 }
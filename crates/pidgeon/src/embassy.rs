@@ -0,0 +1,104 @@
+use embassy_time::{Duration, Ticker};
+
+use crate::hal::{Actuator, HalLoop, HalLoopError, Sensor};
+
+/// Drives a [`HalLoop`] from an `embassy-time` [`Ticker`].
+///
+/// Each [`tick`](Self::tick) waits for the next ticker deadline and then runs
+/// one sensor-compute-actuate step with `dt` fixed to the ticker period. The
+/// ticker catches up on missed deadlines instead of drifting, so a loop that
+/// occasionally overruns keeps its average rate.
+///
+/// If the loop was built [`with_oversampling`](HalLoop::with_oversampling),
+/// the ticker runs that many times faster, and every tick but the last in
+/// each period only takes a reading, so the readings averaged into each step
+/// are spread evenly across its period.
+///
+/// Spawn [`run`](Self::run) as an embassy task for a fire-and-forget loop, or
+/// call [`tick`](Self::tick) yourself to interleave other work such as
+/// setpoint updates from a channel.
+///
+/// # Examples
+///
+/// ```ignore
+/// use embassy_time::Duration;
+/// use pidgeon::{ControllerConfig, HalLoop, TickerLoop};
+///
+/// #[embassy_executor::task]
+/// async fn control_task(mut pid: TickerLoop<MySensor, MyActuator>) {
+///     let err = pid.run().await;
+///     defmt::error!("control loop stopped: {}", defmt::Debug2Format(&err));
+/// }
+///
+/// let hal_loop = HalLoop::new(config, sensor, actuator);
+/// spawner.spawn(control_task(TickerLoop::new(hal_loop, Duration::from_millis(10)))).unwrap();
+/// ```
+pub struct TickerLoop<S, A> {
+    hal_loop: HalLoop<S, A>,
+    ticker: Ticker,
+    dt: f64,
+    samples: u32,
+}
+
+impl<S: Sensor, A: Actuator> TickerLoop<S, A> {
+    /// Wraps `hal_loop`, stepping it every `period`.
+    pub fn new(hal_loop: HalLoop<S, A>, period: Duration) -> Self {
+        let samples = hal_loop
+            .oversampler()
+            .map_or(1, |oversampler| oversampler.samples() as u32)
+            .max(1);
+        TickerLoop {
+            hal_loop,
+            ticker: Ticker::every(period / samples),
+            dt: period.as_micros() as f64 / 1_000_000.0,
+            samples,
+        }
+    }
+
+    /// The fixed `dt` in seconds passed to every step.
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// Waits for the next deadline, then runs one step and returns the
+    /// applied output. With oversampling, first takes a reading at each
+    /// intermediate deadline of the period.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`HalLoopError`] from [`HalLoop::step`], or
+    /// [`HalLoopError::Sensor`] if an intermediate reading fails.
+    pub async fn tick(&mut self) -> Result<f64, HalLoopError<S::Error, A::Error>> {
+        for _ in 1..self.samples {
+            self.ticker.next().await;
+            self.hal_loop.sample().map_err(HalLoopError::Sensor)?;
+        }
+        self.ticker.next().await;
+        self.hal_loop.step(self.dt)
+    }
+
+    /// Steps the loop forever, returning only when a step fails.
+    pub async fn run(&mut self) -> HalLoopError<S::Error, A::Error> {
+        loop {
+            if let Err(e) = self.tick().await {
+                return e;
+            }
+        }
+    }
+
+    /// Restarts the ticker so the next deadline is one period from now.
+    /// Call after a long pause to avoid a burst of catch-up steps.
+    pub fn reset_ticker(&mut self) {
+        self.ticker.reset();
+    }
+
+    /// The wrapped loop, e.g. to change its configuration between ticks.
+    pub fn hal_loop(&mut self) -> &mut HalLoop<S, A> {
+        &mut self.hal_loop
+    }
+
+    /// Releases the wrapped loop.
+    pub fn into_inner(self) -> HalLoop<S, A> {
+        self.hal_loop
+    }
+}
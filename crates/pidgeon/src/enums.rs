@@ -19,6 +19,8 @@
 ///     .unwrap();
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DerivativeMode {
     /// Derivative of the error signal: `d(error)/dt`.
     ///
@@ -54,6 +56,8 @@ pub enum DerivativeMode {
 ///     .unwrap();
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AntiWindupMode {
     /// No anti-windup protection. The integral term accumulates without bound
     /// when the output saturates.
@@ -72,3 +76,103 @@ pub enum AntiWindupMode {
         tracking_time: f64,
     },
 }
+
+/// Shape of the proportional response to the (post-deadband) error.
+///
+/// Both nonlinear shapes keep the output continuous in the error and only
+/// change the P term; I and D see the error unchanged. They suit averaging
+/// level control, such as a surge tank, where small level changes should be
+/// absorbed by the tank instead of passed on to the outflow, while large ones
+/// still get a firm response.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, ProportionalShape};
+///
+/// // A quarter of Kp within 5 cm of the setpoint, full Kp beyond it
+/// let config = ControllerConfig::builder()
+///     .with_kp(4.0)
+///     .with_ki(0.01)
+///     .with_setpoint(1.5)
+///     .with_output_limits(0.0, 100.0)
+///     .with_proportional_shape(ProportionalShape::Gap {
+///         half_width: 0.05,
+///         gain_ratio: 0.25,
+///     })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProportionalShape {
+    /// `P = Kp * error` (default).
+    Linear,
+    /// Error-squared control: `P = Kp * error * |error| / full_gain_error`,
+    /// so the effective gain grows with the error and equals `Kp` at
+    /// `|error| == full_gain_error`.
+    ErrorSquared {
+        /// Error magnitude at which the effective gain reaches `Kp`. Must be
+        /// finite and positive.
+        full_gain_error: f64,
+    },
+    /// Gap gain: the effective gain is `Kp * gain_ratio` within
+    /// `±half_width` of the setpoint, and `Kp` for the error beyond it, so
+    /// `P = Kp * (error - (1 - gain_ratio) * half_width * sign(error))`
+    /// outside the gap.
+    Gap {
+        /// Half-width of the reduced-gain band around the setpoint. Must be
+        /// finite and positive.
+        half_width: f64,
+        /// Fraction of `Kp` applied inside the gap, from `0.0` to `1.0`.
+        gain_ratio: f64,
+    },
+}
+
+/// What a controller does with a setpoint outside its
+/// [`setpoint_limits`](crate::ControllerConfig::setpoint_limits).
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidController, SetpointLimitAction};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_output_limits(0.0, 100.0)
+///     .with_setpoint(180.0)
+///     .with_setpoint_limits(20.0, 250.0)
+///     .with_setpoint_limit_action(SetpointLimitAction::Reject)
+///     .build()
+///     .unwrap();
+/// let mut oven = PidController::new(config);
+///
+/// assert!(oven.set_setpoint(400.0).is_err());
+/// assert_eq!(oven.setpoint(), 180.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SetpointLimitAction {
+    /// Move the setpoint to the nearest limit instead (default).
+    #[default]
+    Clamp,
+    /// Refuse the change and keep the current setpoint.
+    Reject,
+}
+
+/// A gain addressed by a runtime tuning command, such as a CAN or serial
+/// `SetGain` message, or by
+/// [`with_gain_limits`](crate::ControllerConfigBuilder::with_gain_limits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Gain {
+    /// Proportional gain.
+    Kp,
+    /// Integral gain.
+    Ki,
+    /// Derivative gain.
+    Kd,
+}
@@ -3,6 +3,7 @@
 /// Returned by [`pid_compute`](crate::pid_compute), builder validation, and
 /// runtime parameter updates when inputs are invalid or a mutex is poisoned.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PidError {
     /// A parameter failed validation (non-finite, out of range, or violating constraints).
     ///
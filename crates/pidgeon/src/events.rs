@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+
+use crate::enums::Gain;
+use crate::time::{SystemTime, UNIX_EPOCH};
+
+/// Where a [`PidController`](crate::PidController)'s output comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OperatingMode {
+    /// The controller computes the output from the error.
+    Automatic,
+    /// The controller follows an externally applied output through
+    /// [`track`](crate::PidController::track).
+    Tracking,
+    /// An [interlock](crate::PidController::engage_interlock) forces the
+    /// output to a safe value.
+    Interlocked,
+}
+
+/// What happened in a [`ControllerEvent`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventKind {
+    /// The setpoint was moved.
+    SetpointChanged {
+        /// Setpoint before the change.
+        from: f64,
+        /// Setpoint after the change.
+        to: f64,
+    },
+    /// A setpoint outside the
+    /// [`setpoint_limits`](crate::ControllerConfig::setpoint_limits) was
+    /// requested and clamped; a
+    /// [`SetpointChanged`](EventKind::SetpointChanged) follows if the
+    /// clamped value differs from the old setpoint.
+    SetpointClamped {
+        /// Setpoint that was asked for.
+        requested: f64,
+        /// Limit it was clamped to.
+        to: f64,
+    },
+    /// A setpoint outside the
+    /// [`setpoint_limits`](crate::ControllerConfig::setpoint_limits) was
+    /// requested and refused, leaving the setpoint unchanged.
+    SetpointRejected {
+        /// Setpoint that was asked for.
+        requested: f64,
+    },
+    /// One or more gains were changed; carries the full set now in force.
+    GainsChanged {
+        /// Proportional gain.
+        kp: f64,
+        /// Integral gain.
+        ki: f64,
+        /// Derivative gain.
+        kd: f64,
+    },
+    /// A runtime gain change outside the
+    /// [`gain_limits`](crate::ControllerConfig::gain_limits) was refused,
+    /// leaving the gains unchanged.
+    GainRejected {
+        /// Gain the change was for.
+        gain: Gain,
+        /// Value that was asked for.
+        requested: f64,
+    },
+    /// The controller switched between computing and tracking.
+    ModeChanged {
+        /// Mode before the switch.
+        from: OperatingMode,
+        /// Mode after the switch.
+        to: OperatingMode,
+    },
+    /// The output reached one of its limits.
+    SaturationStarted {
+        /// The limit it is pinned at.
+        output: f64,
+    },
+    /// The output came off its limit.
+    SaturationEnded {
+        /// Seconds of loop time it spent there.
+        duration: f64,
+    },
+    /// The controller was reset.
+    Reset,
+}
+
+/// One entry in an [`EventLog`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControllerEvent {
+    /// Loop time in seconds: the sum of every `dt` the controller has run
+    /// with since it was created. Not cleared by a reset.
+    pub elapsed: f64,
+    /// Wall-clock time, in seconds since the Unix epoch, for lining events
+    /// up with other logs.
+    pub unix_time: f64,
+    /// What happened.
+    pub kind: EventKind,
+}
+
+/// A bounded log of a controller's significant events, oldest first.
+///
+/// Once full, each new event drops the oldest one and counts it in
+/// [`dropped`](Self::dropped), so the log always holds the most recent
+/// history however long the loop has run. It is kept whether or not any
+/// debugging or logging is set up, and with the `serde` feature it
+/// serializes as is, so it can be written out after an incident.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, EventKind, PidController};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(10.0)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = PidController::new(config);
+///
+/// controller.set_setpoint(80.0).unwrap();
+/// controller.compute(20.0, 1.0).unwrap();
+///
+/// let kinds: Vec<_> = controller.events().iter().map(|event| event.kind).collect();
+/// assert_eq!(
+///     kinds,
+///     [
+///         EventKind::SetpointChanged { from: 50.0, to: 80.0 },
+///         EventKind::SaturationStarted { output: 100.0 },
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventLog {
+    events: VecDeque<ControllerEvent>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl EventLog {
+    /// Number of events a controller keeps unless told otherwise.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    /// Creates an empty log holding at most `capacity` events. A capacity
+    /// of zero keeps nothing.
+    pub fn new(capacity: usize) -> Self {
+        EventLog {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Events held, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &ControllerEvent> {
+        self.events.iter()
+    }
+
+    /// The most recent event, if any.
+    pub fn latest(&self) -> Option<&ControllerEvent> {
+        self.events.back()
+    }
+
+    /// Number of events held.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether the log holds no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Maximum number of events held.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Events pushed out by newer ones since the log was created or last
+    /// cleared.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Discards every event and the dropped count.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.dropped = 0;
+    }
+
+    /// Changes the capacity, dropping the oldest events if it shrinks below
+    /// the number held.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    /// Appends an event that happened at loop time `elapsed`, stamping it
+    /// with the wall clock.
+    pub(crate) fn record(&mut self, elapsed: f64, kind: EventKind) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        self.events.push_back(ControllerEvent {
+            elapsed,
+            unix_time,
+            kind,
+        });
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.events.len() > self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        EventLog::new(Self::DEFAULT_CAPACITY)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::PidError;
+
+/// Lead/lag compensator turning a measured disturbance into a feedforward
+/// term for [`pid_compute_with_feedforward`](crate::pid_compute_with_feedforward).
+///
+/// The term is `gain · (lead·s + 1) / (lag·s + 1)` applied to the
+/// disturbance: the static `gain` is the output change that cancels a unit
+/// change in the disturbance at steady state, and the lead and lag times
+/// shape how fast it arrives. Set `lead` to the time constant of the path
+/// from the controller output to the process value, and `lag` to that of
+/// the path from the disturbance, so the correction reaches the process as
+/// fast as the disturbance does rather than only once both have settled.
+/// With both at zero the compensation is static.
+///
+/// Feed it the disturbance as a deviation from the value the loop was
+/// tuned at; the integral takes up the remaining offset either way, but a
+/// large absolute term would bump the output when feedforward is switched
+/// on. On the first update the compensator starts settled at that value.
+///
+/// ```
+/// use pidgeon::Feedforward;
+///
+/// // Heating output per degree the outdoor air falls below 10 °C, arriving
+/// // with the building's 600 s response
+/// let mut feedforward = Feedforward::new(-2.5, 0.0, 600.0).unwrap();
+/// let outdoor = 4.0;
+///
+/// let first = feedforward.update(outdoor - 10.0, 1.0).unwrap();
+/// assert_eq!(first, 15.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Feedforward {
+    gain: f64,
+    lead: f64,
+    lag: f64,
+    /// Disturbance at the previous update, `None` before the first
+    prev_disturbance: Option<f64>,
+    output: f64,
+}
+
+impl Feedforward {
+    /// Creates a compensator with static `gain` and `lead` / `lag` time
+    /// constants in seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `gain` is non-finite, or
+    /// `lead` or `lag` is negative or non-finite.
+    pub fn new(gain: f64, lead: f64, lag: f64) -> Result<Self, PidError> {
+        if !gain.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "feedforward gain must be a finite number",
+            ));
+        }
+        if !lead.is_finite() || lead < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "lead must be a finite non-negative number",
+            ));
+        }
+        if !lag.is_finite() || lag < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "lag must be a finite non-negative number",
+            ));
+        }
+        Ok(Self {
+            gain,
+            lead,
+            lag,
+            prev_disturbance: None,
+            output: 0.0,
+        })
+    }
+
+    /// Takes a disturbance measurement `dt` seconds after the previous one
+    /// and returns the feedforward term, in output units.
+    ///
+    /// Discretized with the backward difference, so it stays stable for
+    /// any `dt` and settles at exactly `gain · disturbance`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `disturbance` is non-finite
+    /// or `dt` is non-finite / non-positive.
+    pub fn update(&mut self, disturbance: f64, dt: f64) -> Result<f64, PidError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !disturbance.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "disturbance must be a finite number",
+            ));
+        }
+        self.output = match self.prev_disturbance {
+            None => self.gain * disturbance,
+            Some(prev) => {
+                let input = (self.lead + dt) * disturbance - self.lead * prev;
+                (self.lag * self.output + self.gain * input) / (self.lag + dt)
+            }
+        };
+        self.prev_disturbance = Some(disturbance);
+        Ok(self.output)
+    }
+
+    /// Last feedforward term returned by [`update`](Self::update), zero
+    /// before the first.
+    pub fn output(&self) -> f64 {
+        self.output
+    }
+
+    /// Static gain.
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    /// Lead time constant in seconds.
+    pub fn lead(&self) -> f64 {
+        self.lead
+    }
+
+    /// Lag time constant in seconds.
+    pub fn lag(&self) -> f64 {
+        self.lag
+    }
+
+    /// Forgets the history; the next update starts settled again.
+    pub fn reset(&mut self) {
+        self.prev_disturbance = None;
+        self.output = 0.0;
+    }
+}
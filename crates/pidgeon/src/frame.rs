@@ -0,0 +1,79 @@
+use crate::config::ControllerConfig;
+use crate::state::PidState;
+
+/// Compact per-cycle telemetry record for embedded targets.
+///
+/// The `defmt` counterpart of the Iggy debugger's JSON payload: one frame per
+/// control cycle carrying the inputs, the three PID terms, and the output.
+/// Values are narrowed to `f32` when formatted, keeping each frame small on
+/// the RTT link. [`HalLoop`](crate::HalLoop) emits one frame at `trace`
+/// level per step; other loops can call [`emit`](Self::emit) themselves.
+///
+/// # Examples
+///
+/// ```ignore
+/// use pidgeon::{pid_compute, DebugFrame};
+///
+/// let (output, next) = pid_compute(&config, &state, pv, dt).unwrap();
+/// DebugFrame::new(&config, &next, pv, dt).emit();
+/// state = next;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugFrame {
+    /// Setpoint in effect for this cycle.
+    pub setpoint: f64,
+    /// Measured process value.
+    pub process_value: f64,
+    /// Error after deadband, as used by the P and I terms.
+    pub error: f64,
+    /// Proportional contribution.
+    pub p_term: f64,
+    /// Integral contribution (Ki already applied).
+    pub i_term: f64,
+    /// Derivative contribution (Kd applied to the filtered derivative).
+    pub d_term: f64,
+    /// Clamped output.
+    pub output: f64,
+    /// Time step in seconds.
+    pub dt: f64,
+}
+
+impl DebugFrame {
+    /// Builds a frame from the configuration and the state returned by
+    /// [`pid_compute`](crate::pid_compute) for this cycle.
+    pub fn new(config: &ControllerConfig, state: &PidState, process_value: f64, dt: f64) -> Self {
+        let (kp, _, kd) = config.gains_for(false);
+        DebugFrame {
+            setpoint: config.setpoint,
+            process_value,
+            error: state.prev_error,
+            p_term: kp * state.prev_error,
+            i_term: state.integral_contribution,
+            d_term: kd * state.prev_filtered_derivative,
+            output: state.last_output,
+            dt,
+        }
+    }
+
+    /// Logs the frame at `debug` level.
+    pub fn emit(&self) {
+        defmt::debug!("{}", self);
+    }
+}
+
+impl defmt::Format for DebugFrame {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "pid sp={=f32} pv={=f32} e={=f32} p={=f32} i={=f32} d={=f32} u={=f32} dt={=f32}",
+            self.setpoint as f32,
+            self.process_value as f32,
+            self.error as f32,
+            self.p_term as f32,
+            self.i_term as f32,
+            self.d_term as f32,
+            self.output as f32,
+            self.dt as f32,
+        );
+    }
+}
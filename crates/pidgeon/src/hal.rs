@@ -0,0 +1,361 @@
+use embedded_hal::pwm::SetDutyCycle;
+
+use crate::compute::pid_compute;
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::oversample::Oversampler;
+use crate::state::PidState;
+
+/// A source of process-value readings in engineering units.
+///
+/// Implemented for any `FnMut() -> Result<f64, E>`, so a closure wrapping a
+/// driver call is enough. Use [`AdcSensor`] to scale raw converter counts.
+pub trait Sensor {
+    /// Error returned by the underlying peripheral.
+    type Error;
+
+    /// Takes one reading.
+    fn read(&mut self) -> Result<f64, Self::Error>;
+}
+
+impl<F, E> Sensor for F
+where
+    F: FnMut() -> Result<f64, E>,
+{
+    type Error = E;
+
+    fn read(&mut self) -> Result<f64, E> {
+        self()
+    }
+}
+
+/// A sink for controller outputs.
+///
+/// Implemented for any `FnMut(f64) -> Result<(), E>`, and by [`PwmActuator`]
+/// for `embedded-hal` PWM channels.
+pub trait Actuator {
+    /// Error returned by the underlying peripheral.
+    type Error;
+
+    /// Applies one controller output.
+    fn write(&mut self, output: f64) -> Result<(), Self::Error>;
+}
+
+impl<F, E> Actuator for F
+where
+    F: FnMut(f64) -> Result<(), E>,
+{
+    type Error = E;
+
+    fn write(&mut self, output: f64) -> Result<(), E> {
+        self(output)
+    }
+}
+
+/// Scales raw ADC counts linearly into engineering units.
+///
+/// `embedded-hal` 1.0 has no ADC trait, so the raw read is any
+/// `FnMut() -> Result<u16, E>` (typically a closure around a HAL's one-shot
+/// conversion). A count of `0` maps to `min` and `full_scale` maps to `max`.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{AdcSensor, Sensor};
+///
+/// // 12-bit ADC reading a 0-100 degC probe
+/// let mut sensor = AdcSensor::new(|| Ok::<u16, ()>(2048), 4095, 0.0, 100.0).unwrap();
+/// let celsius = sensor.read().unwrap();
+/// assert!((celsius - 50.0).abs() < 0.1);
+/// ```
+pub struct AdcSensor<R> {
+    read_raw: R,
+    full_scale: u16,
+    min: f64,
+    max: f64,
+}
+
+impl<R, E> AdcSensor<R>
+where
+    R: FnMut() -> Result<u16, E>,
+{
+    /// Creates a scaled sensor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `full_scale` is zero or the
+    /// range bounds are non-finite or equal.
+    pub fn new(read_raw: R, full_scale: u16, min: f64, max: f64) -> Result<Self, PidError> {
+        if full_scale == 0 {
+            return Err(PidError::InvalidParameter(
+                "ADC full scale must be greater than zero",
+            ));
+        }
+        if !min.is_finite() || !max.is_finite() || min == max {
+            return Err(PidError::InvalidParameter(
+                "sensor range must be finite and non-empty",
+            ));
+        }
+        Ok(AdcSensor {
+            read_raw,
+            full_scale,
+            min,
+            max,
+        })
+    }
+}
+
+impl<R, E> Sensor for AdcSensor<R>
+where
+    R: FnMut() -> Result<u16, E>,
+{
+    type Error = E;
+
+    fn read(&mut self) -> Result<f64, E> {
+        let raw = (self.read_raw)()?;
+        let fraction = f64::from(raw) / f64::from(self.full_scale);
+        Ok(self.min + fraction * (self.max - self.min))
+    }
+}
+
+/// Drives an `embedded-hal` PWM channel from controller output.
+///
+/// Outputs in `[min_output, max_output]` map linearly onto duty cycles in
+/// `[0, max_duty_cycle]`. Values outside the range are clamped.
+pub struct PwmActuator<P> {
+    pin: P,
+    min_output: f64,
+    max_output: f64,
+}
+
+impl<P: SetDutyCycle> PwmActuator<P> {
+    /// Wraps `pin`, mapping `min_output` to 0% and `max_output` to 100% duty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the bounds are non-finite or
+    /// `min_output >= max_output`.
+    pub fn new(pin: P, min_output: f64, max_output: f64) -> Result<Self, PidError> {
+        if !min_output.is_finite() || !max_output.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "output limits must be finite numbers",
+            ));
+        }
+        if min_output >= max_output {
+            return Err(PidError::InvalidParameter(
+                "min_output must be less than max_output",
+            ));
+        }
+        Ok(PwmActuator {
+            pin,
+            min_output,
+            max_output,
+        })
+    }
+
+    /// Wraps `pin` using the output limits of `config`.
+    pub fn for_config(pin: P, config: &ControllerConfig) -> Self {
+        PwmActuator {
+            pin,
+            min_output: config.min_output,
+            max_output: config.max_output,
+        }
+    }
+
+    /// Releases the wrapped PWM channel.
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+impl<P: SetDutyCycle> Actuator for PwmActuator<P> {
+    type Error = P::Error;
+
+    fn write(&mut self, output: f64) -> Result<(), P::Error> {
+        let fraction =
+            ((output - self.min_output) / (self.max_output - self.min_output)).clamp(0.0, 1.0);
+        let max_duty = self.pin.max_duty_cycle();
+        // Round to nearest without relying on `f64::round`, which needs `std`.
+        let duty = (fraction * f64::from(max_duty) + 0.5) as u16;
+        self.pin.set_duty_cycle(duty.min(max_duty))
+    }
+}
+
+/// Error from a [`HalLoop`] step.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HalLoopError<SE, AE> {
+    /// Reading the sensor failed. The controller state was not advanced.
+    Sensor(SE),
+    /// The PID computation rejected its inputs.
+    Pid(PidError),
+    /// Writing the actuator failed. The controller state *was* advanced.
+    Actuator(AE),
+}
+
+/// Closed loop tying a [`Sensor`] and an [`Actuator`] to [`pid_compute`].
+///
+/// Owns its [`PidState`] and works without `std` or an allocator, so it can
+/// live in a `static` or a task on a microcontroller. Call
+/// [`step`](Self::step) from a timer interrupt or periodic task. The loop
+/// is PID-only; other [`Controller`](crate::Controller)s need a loop of
+/// their own around the same [`Sensor`] and [`Actuator`].
+///
+/// With [`with_oversampling`](Self::with_oversampling), call
+/// [`sample`](Self::sample) from a faster timer as well; each step then
+/// computes with the mean of the readings taken since the last one.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, HalLoop};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_setpoint(25.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+///
+/// // Closures stand in for peripheral drivers
+/// let sensor = || Ok::<f64, ()>(20.0);
+/// let actuator = |_output: f64| Ok::<(), ()>(());
+/// let mut hal_loop = HalLoop::new(config, sensor, actuator);
+///
+/// let output = hal_loop.step(0.01).unwrap();
+/// assert_eq!(output, 10.0);
+/// ```
+pub struct HalLoop<S, A> {
+    config: ControllerConfig,
+    state: PidState,
+    sensor: S,
+    actuator: A,
+    oversampler: Option<Oversampler>,
+}
+
+impl<S: Sensor, A: Actuator> HalLoop<S, A> {
+    /// Creates a loop with fresh [`PidState`].
+    pub fn new(config: ControllerConfig, sensor: S, actuator: A) -> Self {
+        HalLoop {
+            state: PidState::new(&config),
+            config,
+            sensor,
+            actuator,
+            oversampler: None,
+        }
+    }
+
+    /// Averages the readings from [`sample`](Self::sample) and the one each
+    /// [`step`](Self::step) takes itself, through `oversampler`, instead of
+    /// using a single reading per step. Its [`samples`](Oversampler::samples)
+    /// count sets how often a `TickerLoop` samples; a step driven by hand
+    /// averages however many readings it finds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, HalLoop, Oversampler};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(2.0)
+    ///     .with_setpoint(25.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // A noisy sensor alternating around 20.0
+    /// let mut readings = [19.0, 21.0].into_iter().cycle();
+    /// let sensor = move || Ok::<f64, ()>(readings.next().unwrap());
+    /// let actuator = |_output: f64| Ok::<(), ()>(());
+    /// let mut hal_loop = HalLoop::new(config, sensor, actuator)
+    ///     .with_oversampling(Oversampler::new(4).unwrap());
+    ///
+    /// // Three readings from a fast timer, the fourth taken by the step
+    /// for _ in 0..3 {
+    ///     hal_loop.sample().unwrap();
+    /// }
+    /// assert_eq!(hal_loop.step(0.01).unwrap(), 10.0);
+    /// ```
+    pub fn with_oversampling(mut self, oversampler: Oversampler) -> Self {
+        self.oversampler = Some(oversampler);
+        self
+    }
+
+    /// Takes one reading for the next step's average. Without oversampling
+    /// the reading is discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns the sensor's error. Readings already taken are kept.
+    pub fn sample(&mut self) -> Result<(), S::Error> {
+        let raw = self.sensor.read()?;
+        if let Some(oversampler) = &mut self.oversampler {
+            oversampler.push(raw);
+        }
+        Ok(())
+    }
+
+    /// Reads the sensor, computes one PID step, writes the actuator, and
+    /// returns the applied output. With oversampling, the process value is
+    /// the mean of this reading and those taken by [`sample`](Self::sample)
+    /// since the last step.
+    ///
+    /// # Errors
+    ///
+    /// See [`HalLoopError`] for which failures leave the state untouched.
+    pub fn step(&mut self, dt: f64) -> Result<f64, HalLoopError<S::Error, A::Error>> {
+        let raw = self.sensor.read().map_err(HalLoopError::Sensor)?;
+        let pv = match &mut self.oversampler {
+            Some(oversampler) => {
+                oversampler.push(raw);
+                oversampler.take().unwrap_or(raw)
+            }
+            None => raw,
+        };
+        let (output, next) =
+            pid_compute(&self.config, &self.state, pv, dt).map_err(HalLoopError::Pid)?;
+        self.state = next;
+        #[cfg(feature = "defmt")]
+        defmt::trace!(
+            "{}",
+            crate::frame::DebugFrame::new(&self.config, &self.state, pv, dt)
+        );
+        self.actuator
+            .write(output)
+            .map_err(HalLoopError::Actuator)?;
+        Ok(output)
+    }
+
+    /// Current controller configuration.
+    pub fn config(&self) -> &ControllerConfig {
+        &self.config
+    }
+
+    /// Replaces the configuration, keeping state.
+    pub fn set_config(&mut self, config: ControllerConfig) {
+        self.config = config;
+    }
+
+    /// Current controller state.
+    pub fn state(&self) -> &PidState {
+        &self.state
+    }
+
+    /// Resets the controller state and discards readings taken for the
+    /// next step.
+    pub fn reset(&mut self) {
+        self.state = PidState::new(&self.config);
+        if let Some(oversampler) = &mut self.oversampler {
+            oversampler.reset();
+        }
+    }
+
+    /// The oversampler set with [`with_oversampling`](Self::with_oversampling).
+    pub fn oversampler(&self) -> Option<&Oversampler> {
+        self.oversampler.as_ref()
+    }
+
+    /// Releases the sensor and actuator.
+    pub fn release(self) -> (S, A) {
+        (self.sensor, self.actuator)
+    }
+}
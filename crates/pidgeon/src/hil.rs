@@ -0,0 +1,704 @@
+use crate::compute::pid_compute;
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::state::PidState;
+
+/// Longest encoded frame, delimiter included, that [`HilFrame::encode`]
+/// writes and [`HilDecoder`] accepts.
+pub const HIL_FRAME_MAX: usize = 22;
+
+const KIND_PROCESS_VALUE: u8 = 1;
+const KIND_COMMAND: u8 = 2;
+const KIND_TELEMETRY: u8 = 3;
+const KIND_RESET: u8 = 4;
+
+/// Kind byte, sequence number, the largest payload, and the CRC.
+const RAW_MAX: usize = 2 + 16 + 2;
+
+/// One message of the hardware-in-the-loop protocol.
+///
+/// Values travel as little-endian `f32`, which keeps a full exchange under
+/// 40 bytes: a rig at 115200 baud can close the loop at well over 1 kHz.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HilMessage {
+    /// Host to device: the simulated plant's process value for one cycle.
+    ProcessValue {
+        /// Measured process value.
+        process_value: f32,
+        /// Time step in seconds.
+        dt: f32,
+    },
+    /// Device to host: the controller output for the cycle with the same
+    /// sequence number.
+    Command {
+        /// Clamped controller output.
+        output: f32,
+    },
+    /// Device to host: the controller's view of the cycle with the same
+    /// sequence number, sent before its [`Command`](Self::Command).
+    Telemetry {
+        /// Setpoint in effect.
+        setpoint: f32,
+        /// Process value the controller computed with.
+        process_value: f32,
+        /// Clamped controller output.
+        output: f32,
+        /// Error after deadband.
+        error: f32,
+    },
+    /// Host to device: clear the controller's state before a run.
+    Reset,
+}
+
+/// A [`HilMessage`] with the sequence number that pairs commands with the
+/// process values they answer.
+///
+/// On the wire a frame is its kind byte, the sequence number, the payload,
+/// and a CRC-16/CCITT-FALSE of all three, COBS-encoded and terminated by a
+/// zero byte. A receiver that joins mid-stream or loses bytes resynchronizes
+/// at the next zero, and the CRC catches what line noise corrupts.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{HilDecoder, HilFrame, HilMessage, HIL_FRAME_MAX};
+///
+/// let frame = HilFrame {
+///     seq: 7,
+///     message: HilMessage::Command { output: 12.5 },
+/// };
+/// let mut buf = [0u8; HIL_FRAME_MAX];
+/// let len = frame.encode(&mut buf).unwrap();
+///
+/// let mut decoder = HilDecoder::new();
+/// let decoded = buf[..len].iter().find_map(|&byte| decoder.push(byte));
+/// assert_eq!(decoded, Some(Ok(frame)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HilFrame {
+    /// Sequence number, chosen by the host and echoed by the device.
+    pub seq: u8,
+    /// The message.
+    pub message: HilMessage,
+}
+
+impl HilFrame {
+    /// Writes the encoded frame and its delimiter into `buf`, returning the
+    /// number of bytes written. A buffer of [`HIL_FRAME_MAX`] bytes always
+    /// fits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `buf` is too small.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, PidError> {
+        let mut raw = [0u8; RAW_MAX];
+        let (kind, values, count) = match self.message {
+            HilMessage::ProcessValue { process_value, dt } => {
+                (KIND_PROCESS_VALUE, [process_value, dt, 0.0, 0.0], 2)
+            }
+            HilMessage::Command { output } => (KIND_COMMAND, [output, 0.0, 0.0, 0.0], 1),
+            HilMessage::Telemetry {
+                setpoint,
+                process_value,
+                output,
+                error,
+            } => (KIND_TELEMETRY, [setpoint, process_value, output, error], 4),
+            HilMessage::Reset => (KIND_RESET, [0.0; 4], 0),
+        };
+        raw[0] = kind;
+        raw[1] = self.seq;
+        for (i, value) in values[..count].iter().enumerate() {
+            raw[2 + 4 * i..6 + 4 * i].copy_from_slice(&value.to_le_bytes());
+        }
+        let body = 2 + 4 * count;
+        let crc = crc16(&raw[..body]);
+        raw[body..body + 2].copy_from_slice(&crc.to_le_bytes());
+        cobs_encode(&raw[..body + 2], buf)
+    }
+
+    fn decode(raw: &[u8]) -> Result<Self, PidError> {
+        if raw.len() < 4 {
+            return Err(PidError::InvalidParameter("HIL frame is too short"));
+        }
+        let (body, crc) = raw.split_at(raw.len() - 2);
+        if crc16(body).to_le_bytes() != crc {
+            return Err(PidError::InvalidParameter("HIL frame failed its CRC"));
+        }
+        let payload = &body[2..];
+        let value = |i: usize| {
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(&payload[4 * i..4 * i + 4]);
+            f32::from_le_bytes(bytes)
+        };
+        let (message, count) = match body[0] {
+            KIND_PROCESS_VALUE if payload.len() == 8 => (
+                HilMessage::ProcessValue {
+                    process_value: value(0),
+                    dt: value(1),
+                },
+                2,
+            ),
+            KIND_COMMAND if payload.len() == 4 => (HilMessage::Command { output: value(0) }, 1),
+            KIND_TELEMETRY if payload.len() == 16 => (
+                HilMessage::Telemetry {
+                    setpoint: value(0),
+                    process_value: value(1),
+                    output: value(2),
+                    error: value(3),
+                },
+                4,
+            ),
+            KIND_RESET if payload.is_empty() => (HilMessage::Reset, 0),
+            KIND_PROCESS_VALUE | KIND_COMMAND | KIND_TELEMETRY | KIND_RESET => {
+                return Err(PidError::InvalidParameter(
+                    "HIL frame has the wrong length for its kind",
+                ))
+            }
+            _ => return Err(PidError::InvalidParameter("HIL frame has an unknown kind")),
+        };
+        if (0..count).any(|i| !value(i).is_finite()) {
+            return Err(PidError::InvalidParameter(
+                "HIL frame carries a non-finite value",
+            ));
+        }
+        Ok(HilFrame {
+            seq: body[1],
+            message,
+        })
+    }
+}
+
+/// Assembles [`HilFrame`]s from a byte stream without allocating.
+///
+/// Frames longer than [`HIL_FRAME_MAX`] are dropped and reported as errors
+/// once their delimiter arrives, after which decoding carries on normally.
+#[derive(Debug, Clone)]
+pub struct HilDecoder {
+    buf: [u8; HIL_FRAME_MAX],
+    len: usize,
+    overflowed: bool,
+}
+
+impl HilDecoder {
+    /// Creates an empty decoder.
+    pub const fn new() -> Self {
+        HilDecoder {
+            buf: [0; HIL_FRAME_MAX],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Adds one byte. Returns the decoded frame, or why it could not be
+    /// decoded, when `byte` ends a frame that is not empty.
+    pub fn push(&mut self, byte: u8) -> Option<Result<HilFrame, PidError>> {
+        if byte != 0 {
+            if self.len < self.buf.len() {
+                self.buf[self.len] = byte;
+                self.len += 1;
+            } else {
+                self.overflowed = true;
+            }
+            return None;
+        }
+
+        let len = core::mem::take(&mut self.len);
+        if core::mem::take(&mut self.overflowed) {
+            return Some(Err(PidError::InvalidParameter("HIL frame is too long")));
+        }
+        if len == 0 {
+            return None;
+        }
+        let mut raw = [0u8; HIL_FRAME_MAX];
+        Some(cobs_decode(&self.buf[..len], &mut raw).and_then(|n| HilFrame::decode(&raw[..n])))
+    }
+}
+
+impl Default for HilDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Device side of the hardware-in-the-loop protocol: runs
+/// [`pid_compute`] on every process value the host sends and answers with
+/// telemetry and the command.
+///
+/// Firmware keeps its own configuration and [`PidState`], exactly as in
+/// production, and only swaps its sensor and actuator for the serial port.
+/// Controllers other than [`pid_compute`] can speak the protocol through
+/// [`HilDecoder`] and [`HilFrame::encode`] directly.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, HilDevice, HilFrame, HilMessage, PidState, HIL_FRAME_MAX};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut state = PidState::default();
+/// let mut device = HilDevice::new();
+///
+/// // Bytes from the UART
+/// let mut rx = [0u8; HIL_FRAME_MAX];
+/// let frame = HilFrame {
+///     seq: 1,
+///     message: HilMessage::ProcessValue { process_value: 45.0, dt: 0.01 },
+/// };
+/// let len = frame.encode(&mut rx).unwrap();
+///
+/// let mut tx = [0u8; 2 * HIL_FRAME_MAX];
+/// for &byte in &rx[..len] {
+///     if let Some(reply) = device.push(byte, &config, &mut state, &mut tx) {
+///         let reply_len = reply.unwrap();
+///         // Write `tx[..reply_len]` to the UART
+///         assert!(reply_len > 0);
+///     }
+/// }
+/// assert_eq!(state.last_output, 10.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HilDevice {
+    decoder: HilDecoder,
+    telemetry: bool,
+}
+
+impl HilDevice {
+    /// Creates a device that sends telemetry with every command.
+    pub const fn new() -> Self {
+        HilDevice {
+            decoder: HilDecoder::new(),
+            telemetry: true,
+        }
+    }
+
+    /// Whether to send a [`Telemetry`](HilMessage::Telemetry) frame before
+    /// each command. Turning it off halves the traffic per cycle.
+    pub const fn with_telemetry(mut self, telemetry: bool) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Adds one received byte. When it completes a
+    /// [`ProcessValue`](HilMessage::ProcessValue), runs one [`pid_compute`]
+    /// step, writes the reply frames into `reply`, and returns their length.
+    /// A [`Reset`](HilMessage::Reset) clears `state` and is answered with
+    /// nothing. A buffer of twice [`HIL_FRAME_MAX`] bytes always fits.
+    ///
+    /// # Errors
+    ///
+    /// Returns the decoding error for a corrupt frame, or whatever
+    /// [`pid_compute`] returns. Nothing is sent and `state` is unchanged in
+    /// either case, so the host sees the cycle time out.
+    pub fn push(
+        &mut self,
+        byte: u8,
+        config: &ControllerConfig,
+        state: &mut PidState,
+        reply: &mut [u8],
+    ) -> Option<Result<usize, PidError>> {
+        let frame = match self.decoder.push(byte)? {
+            Ok(frame) => frame,
+            Err(e) => return Some(Err(e)),
+        };
+        match frame.message {
+            HilMessage::ProcessValue { process_value, dt } => {
+                Some(self.step(frame.seq, process_value, dt, config, state, reply))
+            }
+            HilMessage::Reset => {
+                *state = PidState::new(config);
+                Some(Ok(0))
+            }
+            HilMessage::Command { .. } | HilMessage::Telemetry { .. } => Some(Err(
+                PidError::InvalidParameter("HIL device received a device-to-host frame"),
+            )),
+        }
+    }
+
+    fn step(
+        &self,
+        seq: u8,
+        process_value: f32,
+        dt: f32,
+        config: &ControllerConfig,
+        state: &mut PidState,
+        reply: &mut [u8],
+    ) -> Result<usize, PidError> {
+        let (output, next) = pid_compute(config, state, f64::from(process_value), f64::from(dt))?;
+        let mut len = 0;
+        if self.telemetry {
+            let telemetry = HilFrame {
+                seq,
+                message: HilMessage::Telemetry {
+                    setpoint: config.setpoint as f32,
+                    process_value,
+                    output: output as f32,
+                    error: next.prev_error as f32,
+                },
+            };
+            len += telemetry.encode(reply)?;
+        }
+        let command = HilFrame {
+            seq,
+            message: HilMessage::Command {
+                output: output as f32,
+            },
+        };
+        len += command.encode(&mut reply[len..])?;
+        *state = next;
+        Ok(len)
+    }
+}
+
+impl Default for HilDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// COBS-encodes `data` into `out` and appends the zero delimiter. `data` is
+/// always shorter than 254 bytes here, so one code byte per zero suffices.
+fn cobs_encode(data: &[u8], out: &mut [u8]) -> Result<usize, PidError> {
+    if out.len() < data.len() + 2 {
+        return Err(PidError::InvalidParameter(
+            "buffer too small for the HIL frame",
+        ));
+    }
+    let mut code_at = 0;
+    let mut len = 1;
+    for &byte in data {
+        if byte == 0 {
+            out[code_at] = (len - code_at) as u8;
+            code_at = len;
+        } else {
+            out[len] = byte;
+        }
+        len += 1;
+    }
+    out[code_at] = (len - code_at) as u8;
+    out[len] = 0;
+    Ok(len + 1)
+}
+
+/// Reverses [`cobs_encode`] for one frame without its delimiter.
+fn cobs_decode(data: &[u8], out: &mut [u8]) -> Result<usize, PidError> {
+    let mut len = 0;
+    let mut i = 0;
+    while i < data.len() {
+        let code = usize::from(data[i]);
+        let end = i + code;
+        if code == 0 || end > data.len() {
+            return Err(PidError::InvalidParameter("HIL frame is not valid COBS"));
+        }
+        let run = &data[i + 1..end];
+        out[len..len + run.len()].copy_from_slice(run);
+        len += run.len();
+        i = end;
+        if i < data.len() {
+            out[len] = 0;
+            len += 1;
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(feature = "std")]
+pub use host::{HilComparison, HilHost, HilRun, HilStep};
+
+#[cfg(feature = "std")]
+mod host {
+    use std::io::{self, Read, Write};
+
+    use super::{HilDecoder, HilFrame, HilMessage, HIL_FRAME_MAX};
+    use crate::compute::pid_compute;
+    use crate::config::ControllerConfig;
+    use crate::error::PidError;
+    use crate::state::PidState;
+
+    /// One cycle of a hardware-in-the-loop run.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HilStep {
+        /// Process value as the device received it, rounded to `f32`.
+        pub process_value: f64,
+        /// Time step as the device received it, rounded to `f32`.
+        pub dt: f64,
+        /// Output the device commanded.
+        pub output: f64,
+        /// Error the device reported, if it sends telemetry.
+        pub error: Option<f64>,
+        /// Setpoint the device reported, if it sends telemetry.
+        pub setpoint: Option<f64>,
+    }
+
+    /// How far a device's outputs are from [`pid_compute`] fed the same
+    /// process values, as returned by [`HilRun::compare`].
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HilComparison {
+        /// Largest absolute difference between device and simulated output.
+        pub max_output_error: f64,
+        /// Root-mean-square difference over the run.
+        pub rms_output_error: f64,
+        /// Index of the step with the largest difference, if any steps ran.
+        pub worst_step: Option<usize>,
+    }
+
+    impl HilComparison {
+        /// `true` if no output differs from the simulation by more than
+        /// `tolerance`.
+        pub fn within(&self, tolerance: f64) -> bool {
+            self.max_output_error <= tolerance
+        }
+    }
+
+    /// The cycles of a hardware-in-the-loop run, in order.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct HilRun {
+        /// One entry per exchanged cycle.
+        pub steps: Vec<HilStep>,
+    }
+
+    impl HilRun {
+        /// Replays the process values the device received through
+        /// [`pid_compute`] with `config`, from a fresh [`PidState`], and
+        /// measures how far the device's outputs are from the simulation's.
+        ///
+        /// Outputs cross the wire as `f32`, so even identical firmware
+        /// differs by about 1e-7 relative; a tolerance of 1e-4 of the output
+        /// range separates that from a real mismatch.
+        ///
+        /// # Errors
+        ///
+        /// Returns whatever [`pid_compute`] returns for a recorded step.
+        pub fn compare(&self, config: &ControllerConfig) -> Result<HilComparison, PidError> {
+            let mut state = PidState::new(config);
+            let mut comparison = HilComparison {
+                max_output_error: 0.0,
+                rms_output_error: 0.0,
+                worst_step: None,
+            };
+            let mut sum_squares = 0.0;
+            for (i, step) in self.steps.iter().enumerate() {
+                let (output, next) = pid_compute(config, &state, step.process_value, step.dt)?;
+                state = next;
+                let difference = (step.output - output).abs();
+                sum_squares += difference * difference;
+                if comparison.worst_step.is_none() || difference > comparison.max_output_error {
+                    comparison.max_output_error = difference;
+                    comparison.worst_step = Some(i);
+                }
+            }
+            if !self.steps.is_empty() {
+                comparison.rms_output_error = (sum_squares / self.steps.len() as f64).sqrt();
+            }
+            Ok(comparison)
+        }
+    }
+
+    /// Host side of the hardware-in-the-loop protocol: stands in for the
+    /// plant, sending process values to firmware running [`HilDevice`] and
+    /// reading back what it commands.
+    ///
+    /// `port` is anything readable and writable, usually a serial port
+    /// opened with a read timeout so a silent device surfaces as
+    /// [`io::ErrorKind::TimedOut`] rather than a hang. Corrupt frames and
+    /// replies to earlier cycles are skipped and counted.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pidgeon::{ControllerConfig, HilHost, ThermalRoomPlant};
+    ///
+    /// let port = std::fs::OpenOptions::new()
+    ///     .read(true)
+    ///     .write(true)
+    ///     .open("/dev/ttyACM0")
+    ///     .unwrap();
+    /// let mut host = HilHost::new(port);
+    /// let mut room = ThermalRoomPlant::new(18.0, 5.0);
+    ///
+    /// host.reset().unwrap();
+    /// let run = host
+    ///     .run(room.temperature(), 1.0, 600, |output, dt| room.step(output, dt))
+    ///     .unwrap();
+    ///
+    /// // The same gains the firmware was built with
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(8.0)
+    ///     .with_ki(0.05)
+    ///     .with_setpoint(21.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(run.compare(&config).unwrap().within(0.01));
+    /// ```
+    ///
+    /// [`HilDevice`]: super::HilDevice
+    #[derive(Debug)]
+    pub struct HilHost<T> {
+        port: T,
+        decoder: HilDecoder,
+        seq: u8,
+        skipped: u64,
+    }
+
+    impl<T: Read + Write> HilHost<T> {
+        /// Wraps an open port.
+        pub fn new(port: T) -> Self {
+            HilHost {
+                port,
+                decoder: HilDecoder::new(),
+                seq: 0,
+                skipped: 0,
+            }
+        }
+
+        /// Tells the device to clear its controller state, so a run starts
+        /// from the same point as [`HilRun::compare`].
+        ///
+        /// # Errors
+        ///
+        /// Returns any error from writing the port.
+        pub fn reset(&mut self) -> io::Result<()> {
+            self.send(HilMessage::Reset)
+        }
+
+        /// Sends one process value and waits for the device's command.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`io::ErrorKind::InvalidInput`] for a non-finite value,
+        /// [`io::ErrorKind::UnexpectedEof`] if the port closes, and any error
+        /// from reading or writing it, including its read timeout.
+        pub fn exchange(&mut self, process_value: f64, dt: f64) -> io::Result<HilStep> {
+            let process_value = process_value as f32;
+            let dt = dt as f32;
+            if !process_value.is_finite() || !dt.is_finite() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "HIL process value and dt must be finite",
+                ));
+            }
+            self.seq = self.seq.wrapping_add(1);
+            self.send(HilMessage::ProcessValue { process_value, dt })?;
+
+            let mut step = HilStep {
+                process_value: f64::from(process_value),
+                dt: f64::from(dt),
+                output: 0.0,
+                error: None,
+                setpoint: None,
+            };
+            loop {
+                let frame = self.receive()?;
+                if frame.seq != self.seq {
+                    self.skipped += 1;
+                    continue;
+                }
+                match frame.message {
+                    HilMessage::Command { output } => {
+                        step.output = f64::from(output);
+                        return Ok(step);
+                    }
+                    HilMessage::Telemetry {
+                        setpoint, error, ..
+                    } => {
+                        step.setpoint = Some(f64::from(setpoint));
+                        step.error = Some(f64::from(error));
+                    }
+                    HilMessage::ProcessValue { .. } | HilMessage::Reset => self.skipped += 1,
+                }
+            }
+        }
+
+        /// Closes the loop through the device for `steps` cycles of `dt`
+        /// seconds, starting the plant at `process_value`. `plant` takes each
+        /// commanded output and `dt` and returns the next process value, as
+        /// [`ThermalRoomPlant::step`](crate::ThermalRoomPlant::step) does.
+        ///
+        /// # Errors
+        ///
+        /// Returns the first error from [`exchange`](Self::exchange).
+        pub fn run(
+            &mut self,
+            mut process_value: f64,
+            dt: f64,
+            steps: usize,
+            mut plant: impl FnMut(f64, f64) -> f64,
+        ) -> io::Result<HilRun> {
+            let mut run = HilRun {
+                steps: Vec::with_capacity(steps),
+            };
+            for _ in 0..steps {
+                let step = self.exchange(process_value, dt)?;
+                process_value = plant(step.output, dt);
+                run.steps.push(step);
+            }
+            Ok(run)
+        }
+
+        /// Frames skipped so far: corrupt, out of sequence, or unexpected.
+        pub fn skipped_frames(&self) -> u64 {
+            self.skipped
+        }
+
+        /// Releases the port.
+        pub fn into_inner(self) -> T {
+            self.port
+        }
+
+        fn send(&mut self, message: HilMessage) -> io::Result<()> {
+            let mut buf = [0u8; HIL_FRAME_MAX];
+            let frame = HilFrame {
+                seq: self.seq,
+                message,
+            };
+            let len = frame
+                .encode(&mut buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            self.port.write_all(&buf[..len])?;
+            self.port.flush()
+        }
+
+        fn receive(&mut self) -> io::Result<HilFrame> {
+            let mut byte = [0u8; 1];
+            loop {
+                if self.port.read(&mut byte)? == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "HIL device closed the port",
+                    ));
+                }
+                match self.decoder.push(byte[0]) {
+                    Some(Ok(frame)) => return Ok(frame),
+                    Some(Err(_)) => self.skipped += 1,
+                    None => {}
+                }
+            }
+        }
+    }
+}
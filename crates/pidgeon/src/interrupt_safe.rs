@@ -0,0 +1,274 @@
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::compute::pid_compute;
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::state::PidState;
+
+#[cfg(feature = "serial")]
+use crate::serial::SerialMessage;
+
+/// Lightweight running statistics kept by [`CriticalSectionPidController`].
+///
+/// Unlike [`ControllerStatistics`](crate::ControllerStatistics) this needs no
+/// clock or allocator, so it is available on bare-metal targets.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CycleStatistics {
+    /// Number of successful compute cycles since the last reset.
+    pub cycles: u64,
+    /// Mean absolute error across all cycles since the last reset.
+    pub average_error: f64,
+    /// Largest absolute error observed since the last reset.
+    pub max_error: f64,
+    /// Clamped output from the most recent cycle.
+    pub last_output: f64,
+}
+
+struct Inner {
+    config: ControllerConfig,
+    state: PidState,
+    error_sum: f64,
+    stats: CycleStatistics,
+}
+
+impl Inner {
+    fn new(config: ControllerConfig) -> Self {
+        Inner {
+            state: PidState::new(&config),
+            config,
+            error_sum: 0.0,
+            stats: CycleStatistics::default(),
+        }
+    }
+}
+
+/// PID controller guarded by a [`critical_section::Mutex`] instead of
+/// `std::sync::Mutex`.
+///
+/// Every method runs inside a critical section, so [`compute`](Self::compute)
+/// can be called from an interrupt handler while a background task (or an
+/// RTIC task at a different priority) reads [`statistics`](Self::statistics)
+/// or changes the setpoint. Critical sections are held only for the duration
+/// of a single [`pid_compute`] call or field copy.
+///
+/// The controller can be placed in a `static` with
+/// [`new_uninit`](Self::new_uninit) and initialized at startup with
+/// [`init`](Self::init). Methods called before initialization return
+/// [`PidError::InvalidParameter`].
+///
+/// The final binary must provide a `critical-section` implementation (usually
+/// via the HAL or the `cortex-m` crate's `critical-section-single-core` feature).
+///
+/// # Examples
+///
+/// ```ignore
+/// use pidgeon::{ControllerConfig, CriticalSectionPidController};
+///
+/// static PID: CriticalSectionPidController = CriticalSectionPidController::new_uninit();
+///
+/// fn main() {
+///     let config = ControllerConfig::builder()
+///         .with_kp(1.5)
+///         .with_ki(0.2)
+///         .with_setpoint(3.3)
+///         .with_output_limits(0.0, 1.0)
+///         .build()
+///         .unwrap();
+///     PID.init(config);
+/// }
+///
+/// #[interrupt]
+/// fn TIM2() {
+///     let voltage = read_adc();
+///     let duty = PID.compute(voltage, 0.001).unwrap_or(0.0);
+///     set_pwm(duty);
+/// }
+/// ```
+pub struct CriticalSectionPidController {
+    inner: Mutex<RefCell<Option<Inner>>>,
+}
+
+impl CriticalSectionPidController {
+    /// Creates an initialized controller.
+    pub fn new(config: ControllerConfig) -> Self {
+        CriticalSectionPidController {
+            inner: Mutex::new(RefCell::new(Some(Inner::new(config)))),
+        }
+    }
+
+    /// Creates an uninitialized controller suitable for a `static`. Call
+    /// [`init`](Self::init) before use.
+    pub const fn new_uninit() -> Self {
+        CriticalSectionPidController {
+            inner: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Installs `config` and resets state and statistics.
+    pub fn init(&self, config: ControllerConfig) {
+        critical_section::with(|cs| {
+            self.inner.borrow(cs).replace(Some(Inner::new(config)));
+        });
+    }
+
+    /// Returns `true` once [`init`](Self::init) has been called (or the
+    /// controller was created with [`new`](Self::new)).
+    pub fn is_initialized(&self) -> bool {
+        critical_section::with(|cs| self.inner.borrow(cs).borrow().is_some())
+    }
+
+    fn with_inner<R>(
+        &self,
+        f: impl FnOnce(&mut Inner) -> Result<R, PidError>,
+    ) -> Result<R, PidError> {
+        critical_section::with(|cs| {
+            let mut guard = self.inner.borrow(cs).borrow_mut();
+            match guard.as_mut() {
+                Some(inner) => f(inner),
+                None => Err(PidError::InvalidParameter("controller is not initialized")),
+            }
+        })
+    }
+
+    /// Runs one PID iteration and returns the clamped control output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not
+    /// initialized or the inputs are invalid (see [`pid_compute`]).
+    pub fn compute(&self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        self.with_inner(|inner| {
+            let (output, next) = pid_compute(&inner.config, &inner.state, process_value, dt)?;
+            inner.state = next;
+            if !inner.config.statistics {
+                return Ok(output);
+            }
+            let error = (inner.config.setpoint - process_value).abs();
+            inner.error_sum += error;
+            inner.stats.cycles += 1;
+            inner.stats.average_error = inner.error_sum / inner.stats.cycles as f64;
+            if error > inner.stats.max_error {
+                inner.stats.max_error = error;
+            }
+            inner.stats.last_output = output;
+            Ok(output)
+        })
+    }
+
+    /// Returns the most recent clamped control output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not initialized.
+    pub fn get_control_signal(&self) -> Result<f64, PidError> {
+        self.with_inner(|inner| Ok(inner.state.last_output))
+    }
+
+    /// Returns a snapshot of the running statistics, all zero if the config
+    /// turned them off with
+    /// [`with_statistics(false)`](crate::ControllerConfigBuilder::with_statistics).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not initialized.
+    pub fn statistics(&self) -> Result<CycleStatistics, PidError> {
+        self.with_inner(|inner| Ok(inner.stats))
+    }
+
+    /// Returns a copy of the current [`PidState`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not initialized.
+    pub fn state(&self) -> Result<PidState, PidError> {
+        self.with_inner(|inner| Ok(inner.state.clone()))
+    }
+
+    /// Returns a copy of the current [`ControllerConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not initialized.
+    pub fn config(&self) -> Result<ControllerConfig, PidError> {
+        self.with_inner(|inner| Ok(inner.config.clone()))
+    }
+
+    /// Updates the setpoint, clamped or rejected by the config's
+    /// [`setpoint_limits`](ControllerConfig::setpoint_limits).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite or
+    /// rejected by the setpoint limits, or the controller is not initialized.
+    pub fn set_setpoint(&self, setpoint: f64) -> Result<(), PidError> {
+        self.with_inner(|inner| {
+            inner.config.setpoint = inner.config.limit_setpoint(setpoint)?;
+            Ok(())
+        })
+    }
+
+    /// Overwrites the integral term, in output units, to warm-start the loop
+    /// or take over smoothly from manual control.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `value` is non-finite or the
+    /// controller is not initialized.
+    pub fn set_integral(&self, value: f64) -> Result<(), PidError> {
+        if !value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "integral must be a finite number",
+            ));
+        }
+        self.with_inner(|inner| {
+            inner.state.integral_contribution = value;
+            Ok(())
+        })
+    }
+
+    /// Replaces the entire configuration. State and statistics are preserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not initialized.
+    pub fn update_config(&self, config: ControllerConfig) -> Result<(), PidError> {
+        self.with_inner(|inner| {
+            inner.config = config;
+            Ok(())
+        })
+    }
+
+    /// Applies a command received over a serial line; see
+    /// [`SerialMessage::apply`]. [`Reset`](SerialMessage::Reset) also clears
+    /// the statistics, like [`reset`](Self::reset). Only available with the
+    /// `serial` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a value is rejected,
+    /// `message` is not a command, or the controller is not initialized.
+    #[cfg(feature = "serial")]
+    pub fn apply_serial_message(&self, message: &SerialMessage) -> Result<(), PidError> {
+        if let SerialMessage::Reset = message {
+            return self.reset();
+        }
+        self.with_inner(|inner| message.apply(&mut inner.config, &mut inner.state))
+    }
+
+    /// Resets controller state and statistics. The configuration is preserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the controller is not initialized.
+    pub fn reset(&self) -> Result<(), PidError> {
+        self.with_inner(|inner| {
+            inner.state = PidState::new(&inner.config);
+            inner.error_sum = 0.0;
+            inner.stats = CycleStatistics::default();
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,206 @@
+//! Four `f64`s operated on together, for [`ControllerBank`](crate::ControllerBank).
+//!
+//! With the `simd` feature the lanes are a `wide::f64x4`, which maps to
+//! SSE2/AVX or NEON registers. Without it they are a plain array that LLVM
+//! usually vectorizes on its own. Every operation is IEEE-exact per lane in
+//! both, so the two agree bit for bit with each other and with
+//! [`pid_compute`](crate::pid_compute).
+
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Sub};
+
+/// Lanes processed together.
+pub(crate) const LANES: usize = 4;
+
+#[cfg(feature = "simd")]
+type Inner = wide::f64x4;
+
+#[cfg(not(feature = "simd"))]
+type Inner = [f64; LANES];
+
+/// Four `f64` lanes.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Lanes(Inner);
+
+/// Per-lane result of a comparison.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Mask(MaskInner);
+
+#[cfg(feature = "simd")]
+type MaskInner = wide::f64x4;
+
+/// All ones or all zeros per lane, so selects are bitwise and branch-free
+#[cfg(not(feature = "simd"))]
+type MaskInner = [u64; LANES];
+
+#[cfg(feature = "simd")]
+impl Lanes {
+    #[inline]
+    pub(crate) fn splat(value: f64) -> Self {
+        Lanes(wide::f64x4::splat(value))
+    }
+
+    #[inline]
+    pub(crate) fn to_array(self) -> [f64; LANES] {
+        self.0.to_array()
+    }
+
+    #[inline]
+    pub(crate) fn abs(self) -> Self {
+        Lanes(self.0.abs())
+    }
+
+    #[inline]
+    pub(crate) fn lt(self, other: Self) -> Mask {
+        Mask(wide::CmpLt::cmp_lt(self.0, other.0))
+    }
+
+    #[inline]
+    pub(crate) fn gt(self, other: Self) -> Mask {
+        Mask(wide::CmpGt::cmp_gt(self.0, other.0))
+    }
+
+    #[inline]
+    pub(crate) fn le(self, other: Self) -> Mask {
+        Mask(wide::CmpLe::cmp_le(self.0, other.0))
+    }
+}
+
+#[cfg(feature = "simd")]
+impl Mask {
+    /// `if_true` where the mask is set, `if_false` elsewhere
+    #[inline]
+    pub(crate) fn select(self, if_true: Lanes, if_false: Lanes) -> Lanes {
+        Lanes(self.0.blend(if_true.0, if_false.0))
+    }
+}
+
+#[cfg(feature = "simd")]
+impl From<[f64; LANES]> for Lanes {
+    fn from(values: [f64; LANES]) -> Self {
+        Lanes(wide::f64x4::from(values))
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl Lanes {
+    #[inline]
+    pub(crate) fn splat(value: f64) -> Self {
+        Lanes([value; LANES])
+    }
+
+    #[inline]
+    pub(crate) fn to_array(self) -> [f64; LANES] {
+        self.0
+    }
+
+    #[inline]
+    pub(crate) fn abs(self) -> Self {
+        Lanes(self.0.map(f64::abs))
+    }
+
+    #[inline(always)]
+    fn compare(self, other: Self, op: impl Fn(f64, f64) -> bool) -> Mask {
+        Mask(core::array::from_fn(|i| {
+            u64::from(op(self.0[i], other.0[i])).wrapping_neg()
+        }))
+    }
+
+    #[inline]
+    pub(crate) fn lt(self, other: Self) -> Mask {
+        self.compare(other, |a, b| a < b)
+    }
+
+    #[inline]
+    pub(crate) fn gt(self, other: Self) -> Mask {
+        self.compare(other, |a, b| a > b)
+    }
+
+    #[inline]
+    pub(crate) fn le(self, other: Self) -> Mask {
+        self.compare(other, |a, b| a <= b)
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl Mask {
+    /// `if_true` where the mask is set, `if_false` elsewhere
+    #[inline]
+    pub(crate) fn select(self, if_true: Lanes, if_false: Lanes) -> Lanes {
+        Lanes(core::array::from_fn(|i| {
+            let mask = self.0[i];
+            f64::from_bits((if_true.0[i].to_bits() & mask) | (if_false.0[i].to_bits() & !mask))
+        }))
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+impl From<[f64; LANES]> for Lanes {
+    fn from(values: [f64; LANES]) -> Self {
+        Lanes(values)
+    }
+}
+
+macro_rules! lane_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl $trait for Lanes {
+            type Output = Lanes;
+
+            #[cfg(feature = "simd")]
+            #[inline]
+            fn $method(self, rhs: Lanes) -> Lanes {
+                Lanes(self.0 $op rhs.0)
+            }
+
+            #[cfg(not(feature = "simd"))]
+            #[inline]
+            fn $method(self, rhs: Lanes) -> Lanes {
+                Lanes(core::array::from_fn(|i| self.0[i] $op rhs.0[i]))
+            }
+        }
+    };
+}
+
+lane_op!(Add, add, +);
+lane_op!(Sub, sub, -);
+lane_op!(Mul, mul, *);
+lane_op!(Div, div, /);
+
+impl Neg for Lanes {
+    type Output = Lanes;
+
+    #[cfg(feature = "simd")]
+    #[inline]
+    fn neg(self) -> Lanes {
+        Lanes(-self.0)
+    }
+
+    #[cfg(not(feature = "simd"))]
+    #[inline]
+    fn neg(self) -> Lanes {
+        Lanes(self.0.map(|value| -value))
+    }
+}
+
+macro_rules! mask_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl $trait for Mask {
+            type Output = Mask;
+
+            #[cfg(feature = "simd")]
+            #[inline]
+            fn $method(self, rhs: Mask) -> Mask {
+                Mask(self.0 $op rhs.0)
+            }
+
+            #[cfg(not(feature = "simd"))]
+            #[inline]
+            fn $method(self, rhs: Mask) -> Mask {
+                Mask(core::array::from_fn(|i| self.0[i] $op rhs.0[i]))
+            }
+        }
+    };
+}
+
+mask_op!(BitAnd, bitand, &);
+mask_op!(BitOr, bitor, |);
+mask_op!(BitXor, bitxor, ^);
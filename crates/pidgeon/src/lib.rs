@@ -48,46 +48,331 @@
 //! }
 //! ```
 //!
+//! # Overview
+//!
+//! Beyond [`pid_compute`], the `std` feature brings:
+//!
+//! - **Controllers**: [`PidController`] and [`ThreadSafePidController`],
+//!   [`RampController`] for rate-of-change targets, [`MpcController`] for
+//!   dead-time-heavy plants, and LQR [`StateFeedbackController`]s designed
+//!   from a [`StateSpaceModel`], with a [`LuenbergerObserver`] for unmeasured
+//!   states.
+//! - **Many loops**: [`ControllerRegistry`] names loops and changes them
+//!   together through a [`RegistryTransaction`], [`ControllerBank`] steps
+//!   independent loops four at a time in SIMD lanes, and [`LoopScheduler`]
+//!   runs many loops from one thread at their own rates.
+//! - **Tuning**: [`StepTest`]s identify a [`FopdtModel`] or
+//!   [`SecondOrderModel`], [`TuningRule`]s turn it into gains, a
+//!   [`TuneRecord`] keeps the trail, and [`ShadowRunner`] tries a candidate
+//!   tune beside the live one.
+//! - **Operations**: each loop's [`EventLog`] of setpoint, gain, mode, and
+//!   saturation changes, wall-clock [`SetpointSchedule`]s, a
+//!   [`SensorWatchdog`] for stale inputs, and role-gated [`LoopNode`] reads
+//!   and writes for SCADA servers.
+//! - **Analysis**: [`Recording`]s that replay bit for bit and
+//!   [`StatisticsReport`]s with histograms.
+//! - **Simulation**: [`ThermalRoomPlant`] and [`DroneAltitudePlant`], with
+//!   seedable [`SimRng`] noise and [`GustGenerator`] disturbances.
+//!
 //! # Feature flags
 //!
 //! | Feature      | Default | Effect |
 //! |--------------|---------|--------|
-//! | `std`        | yes     | Enables [`PidController`], [`ThreadSafePidController`], and `Error` impl |
-//! | `debugging`  | no      | Streams PID telemetry via Iggy.rs (implies `std`) |
+//! | `std`        | yes     | Stateful controllers and everything built on them; see [Overview](#overview). Also implements `Error` for [`PidError`] |
+//! | `serde`      | no      | `Serialize`/`Deserialize` for [`ControllerConfig`] and its enums |
+//! | `debugging`  | no      | Streams PID telemetry via Iggy.rs and pluggable `DebugSink`s (implies `std`) |
+//! | `nats`       | no      | `NatsSink` publishing debug samples to NATS or JetStream (implies `debugging`) |
+//! | `otel`       | no      | `OtelSink` exporting loop metrics over OTLP (implies `debugging`) |
+//! | `remote-control` | no  | Signed `RemoteCommand`s changing setpoint, gains, and mode over the debug transport, with audit events (implies `debugging`) |
+//! | `opcua`      | no      | `OpcUaServer` exposing a [`ControllerRegistry`]'s loops to SCADA clients, with viewer/operator/admin write access (implies `std`) |
 //! | `benchmarks` | no      | Enables criterion benchmarks (implies `std`) |
-//! | `wasm`       | no      | Swaps `std::time` for `web_time` (implies `std`) |
+//! | `simd`       | no      | Steps [`ControllerBank`] lanes with explicit SIMD via [`wide`](https://docs.rs/wide) (implies `std`) |
+//! | `wasm`       | no      | Reads the clock through `web_time`, so statistics, the debug sampler, and timestamps work on `wasm32-unknown-unknown` (implies `std`) |
+//! | `critical-section` | no | `CriticalSectionPidController` for sharing a loop between ISRs and tasks without `std` |
+//! | `defmt`      | no      | `defmt::Format` for config/state/statistics and per-cycle `DebugFrame`s over RTT |
+//! | `embassy`    | no      | `TickerLoop` async driver over `embassy-time` (implies `embedded-hal`) |
+//! | `can`        | no      | `no_std` `CanCodec` packing telemetry and setpoint/gain/reset commands into classic CAN frames |
+//! | `socketcan`  | no      | `SocketCanBridge` streaming a controller over Linux SocketCAN (implies `std` and `can`) |
+//! | `serial`     | no      | `no_std` `SerialMessage` line protocol for telemetry and live gain changes over a UART |
+//! | `hil`        | no      | `no_std` `HilDevice` framed serial protocol for hardware-in-the-loop rigs; with `std`, a `HilHost` that plays the plant and compares the firmware against [`pid_compute`] |
+//! | `proptest`   | no      | [`proptest`](https://docs.rs/proptest) strategies for configs and inputs in [`testing`], alongside its invariant checkers (implies `std`) |
+//! | `units`      | no      | `no_std` `Quantity` and `TypedController` carrying setpoint, process value, and output units so mixing them fails to compile |
+//! | `heapless`   | no      | `no_std` `BoundedStatistics`: error indices, [`SetpointResponse`]s, and histograms in fixed-capacity [`heapless`](https://docs.rs/heapless) storage |
+//! | `embedded-hal` | no    | `Sensor`/`Actuator` adapters and a `no_std` `HalLoop` over `embedded-hal` 1.0 PWM |
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod bang_bang;
 mod compute;
 mod config;
 mod enums;
 mod error;
+mod feedforward;
+mod oversample;
+mod pipeline;
+mod response;
+mod scaling;
 mod state;
+mod traits;
+
+pub mod testing;
+
+#[cfg(feature = "std")]
+mod bank;
 
 #[cfg(feature = "std")]
 mod controller;
 
+#[cfg(feature = "std")]
+mod events;
+
+#[cfg(feature = "std")]
+mod lanes;
+
+#[cfg(feature = "std")]
+mod linalg;
+
 #[cfg(feature = "std")]
 mod thread_safe;
 
+#[cfg(feature = "std")]
+mod time;
+
+#[cfg(feature = "std")]
+mod mpc;
+
+#[cfg(feature = "std")]
+mod nodes;
+
+#[cfg(feature = "std")]
+mod observer;
+
+#[cfg(feature = "std")]
+mod plant;
+
+#[cfg(feature = "std")]
+mod ramp;
+
+#[cfg(feature = "std")]
+mod record;
+
+#[cfg(feature = "std")]
+mod registry;
+
+#[cfg(feature = "std")]
+mod report;
+
+#[cfg(feature = "std")]
+mod scheduler;
+
+#[cfg(feature = "std")]
+mod setpoint_schedule;
+
+#[cfg(feature = "std")]
+mod shadow;
+
+#[cfg(feature = "std")]
+mod state_space;
+
+#[cfg(feature = "std")]
+mod step_test;
+
+#[cfg(feature = "std")]
+mod transaction;
+
+#[cfg(feature = "std")]
+mod tune_record;
+
+#[cfg(feature = "std")]
+mod tuning;
+
+#[cfg(feature = "std")]
+mod watchdog;
+
 #[cfg(feature = "debugging")]
 mod debug;
 
-pub use compute::pid_compute;
-pub use config::{ControllerConfig, ControllerConfigBuilder};
-pub use enums::{AntiWindupMode, DerivativeMode};
+#[cfg(feature = "debugging")]
+mod sinks;
+
+#[cfg(feature = "nats")]
+mod nats;
+
+#[cfg(feature = "otel")]
+mod otel;
+
+#[cfg(feature = "remote-control")]
+mod remote;
+
+#[cfg(feature = "opcua")]
+mod opcua;
+
+#[cfg(feature = "embedded-hal")]
+mod hal;
+
+#[cfg(feature = "critical-section")]
+mod interrupt_safe;
+
+#[cfg(feature = "embassy")]
+mod embassy;
+
+#[cfg(feature = "defmt")]
+mod frame;
+
+#[cfg(feature = "can")]
+mod can;
+
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+mod socketcan;
+
+#[cfg(feature = "serial")]
+mod serial;
+
+#[cfg(feature = "hil")]
+mod hil;
+
+#[cfg(feature = "units")]
+pub mod units;
+
+#[cfg(feature = "heapless")]
+mod bounded_stats;
+
+pub use bang_bang::BangBangController;
+pub use compute::{pid_compute, pid_compute_with_feedforward, pid_compute_with_rate, pid_track};
+pub use config::{ControllerConfig, ControllerConfigBuilder, PidGains};
+pub use enums::{AntiWindupMode, DerivativeMode, Gain, ProportionalShape, SetpointLimitAction};
 pub use error::PidError;
+pub use feedforward::Feedforward;
+pub use oversample::Oversampler;
+pub use pipeline::{
+    Chain, FilteredInput, Limiter, LowPassFilter, Quantizer, RateLimiter, ShapedOutput, SignalStage,
+};
+pub use response::SetpointResponse;
+pub use scaling::Scaling;
 pub use state::PidState;
+pub use traits::Controller;
+
+#[cfg(feature = "std")]
+pub use bank::ControllerBank;
 
 #[cfg(feature = "std")]
 pub use controller::{ControllerStatistics, PidController};
 
+#[cfg(feature = "std")]
+pub use events::{ControllerEvent, EventKind, EventLog, OperatingMode};
+
 #[cfg(feature = "std")]
 pub use thread_safe::ThreadSafePidController;
 
+#[cfg(feature = "std")]
+pub use mpc::{MpcConfig, MpcConfigBuilder, MpcController};
+
+#[cfg(feature = "std")]
+pub use nodes::{AccessRole, LoopNode, NodeValue};
+
+#[cfg(feature = "std")]
+pub use observer::LuenbergerObserver;
+
+#[cfg(feature = "std")]
+pub use plant::{DroneAltitudePlant, GustGenerator, SimRng, ThermalRoomPlant};
+
+#[cfg(feature = "std")]
+pub use ramp::{RampController, RampMode};
+
+#[cfg(feature = "std")]
+pub use step_test::{
+    CancelToken, Excitation, StepTest, StepTestConfig, StepTestConfigBuilder, StepTestPhase,
+    StepTestProgress, StepTestStatus,
+};
+
+#[cfg(feature = "std")]
+pub use record::{RecordedStep, Recording, ReplayMismatch};
+
+#[cfg(feature = "std")]
+pub use registry::{ControllerRegistry, GroupMember};
+
+#[cfg(feature = "std")]
+pub use report::{Histogram, StatisticsReport};
+
+#[cfg(feature = "std")]
+pub use scheduler::{LoopScheduler, LoopTiming};
+
+#[cfg(feature = "std")]
+pub use setpoint_schedule::{ActiveSetpoint, ScheduleEntry, SetpointSchedule, Weekdays};
+
+#[cfg(feature = "std")]
+pub use shadow::{ShadowReport, ShadowRunner, ShadowStep};
+
+#[cfg(feature = "std")]
+pub use state_space::{
+    StateFeedbackConfig, StateFeedbackConfigBuilder, StateFeedbackController, StateSpaceModel,
+};
+
+#[cfg(feature = "std")]
+pub use transaction::RegistryTransaction;
+
+#[cfg(feature = "std")]
+pub use tune_record::TuneRecord;
+#[cfg(feature = "std")]
+pub use tuning::{FopdtModel, Identification, ResponsePoint, SecondOrderModel, TuningRule};
+
+#[cfg(feature = "std")]
+pub use watchdog::{SensorWatchdog, StaleFallback, StalenessEvent, WatchdogOutput};
+
+#[cfg(feature = "embedded-hal")]
+pub use hal::{Actuator, AdcSensor, HalLoop, HalLoopError, PwmActuator, Sensor};
+
+#[cfg(feature = "critical-section")]
+pub use interrupt_safe::{CriticalSectionPidController, CycleStatistics};
+
+#[cfg(feature = "embassy")]
+pub use embassy::TickerLoop;
+
+#[cfg(feature = "defmt")]
+pub use frame::DebugFrame;
+
+#[cfg(feature = "can")]
+pub use can::{CanCodec, CanFrame, CanMessage, CAN_ID_SPAN};
+
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+pub use socketcan::SocketCanBridge;
+
+#[cfg(feature = "serial")]
+pub use serial::{SerialMessage, SerialReader, SERIAL_LINE_MAX};
+
+#[cfg(feature = "hil")]
+pub use hil::{HilDecoder, HilDevice, HilFrame, HilMessage, HIL_FRAME_MAX};
+
+#[cfg(all(feature = "hil", feature = "std"))]
+pub use hil::{HilComparison, HilHost, HilRun, HilStep};
+
+#[cfg(feature = "units")]
+pub use units::{Quantity, TypedController, Unit};
+
+#[cfg(feature = "heapless")]
+pub use bounded_stats::{BoundedHistogram, BoundedStatistics};
+
+#[cfg(feature = "debugging")]
+pub use debug::{
+    BackpressurePolicy, CaptureConfig, CaptureTrigger, ConnectionState, ControllerDebugData,
+    ControllerDebugger, ControllerMode, ControllerSnapshot, DebugConfig, DebugEncoding,
+    DebuggerDiagnostics, Decimation, ReconnectPolicy, DEBUG_SCHEMA_VERSION,
+};
+
 #[cfg(feature = "debugging")]
-pub use debug::{ControllerDebugger, DebugConfig};
+pub use sinks::{DebugSink, FileFormat, FileSink, RingBufferSink, SharedDebugSink};
+
+#[cfg(feature = "nats")]
+pub use nats::NatsSink;
+
+#[cfg(feature = "otel")]
+pub use otel::OtelSink;
+
+#[cfg(feature = "remote-control")]
+pub use remote::{AuditEvent, CommandOutcome, RemoteAction, RemoteCommand, RemoteControlConfig};
+
+#[cfg(feature = "opcua")]
+pub use opcua::{OpcUaConfig, OpcUaServer};
 
 #[cfg(test)]
 mod tests;
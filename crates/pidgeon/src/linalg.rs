@@ -0,0 +1,122 @@
+//! Small dense matrix helpers for the model-based controllers. Matrices are
+//! row-major `Vec<Vec<f64>>` and only ever a handful of rows, so clarity
+//! wins over speed.
+
+pub(crate) type Matrix = Vec<Vec<f64>>;
+
+pub(crate) fn identity(n: usize) -> Matrix {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+pub(crate) fn transpose(a: &[Vec<f64>]) -> Matrix {
+    let cols = a.first().map_or(0, Vec::len);
+    (0..cols)
+        .map(|j| a.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+pub(crate) fn mul(a: &[Vec<f64>], b: &[Vec<f64>]) -> Matrix {
+    let cols = b.first().map_or(0, Vec::len);
+    a.iter()
+        .map(|row| {
+            (0..cols)
+                .map(|j| row.iter().zip(b).map(|(x, b_row)| x * b_row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn mul_vec(a: &[Vec<f64>], x: &[f64]) -> Vec<f64> {
+    a.iter().map(|row| dot(row, x)).collect()
+}
+
+pub(crate) fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+pub(crate) fn add(a: &[Vec<f64>], b: &[Vec<f64>]) -> Matrix {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x.iter().zip(y).map(|(x, y)| x + y).collect())
+        .collect()
+}
+
+pub(crate) fn scale(a: &[Vec<f64>], factor: f64) -> Matrix {
+    a.iter()
+        .map(|row| row.iter().map(|x| x * factor).collect())
+        .collect()
+}
+
+/// Largest absolute row sum.
+pub(crate) fn norm(a: &[Vec<f64>]) -> f64 {
+    a.iter()
+        .map(|row| row.iter().map(|x| x.abs()).sum::<f64>())
+        .fold(0.0, f64::max)
+}
+
+/// Solves `a·x = b` by Gaussian elimination with partial pivoting. A
+/// singular column leaves its unknown at zero.
+pub(crate) fn solve(mut a: Matrix, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        if a[col][col] == 0.0 {
+            continue;
+        }
+        let pivot_row = a[col].clone();
+        for row in col + 1..n {
+            let factor = a[row][col] / pivot_row[col];
+            for (value, pivot) in a[row].iter_mut().zip(&pivot_row).skip(col) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        if a[row][row] == 0.0 {
+            continue;
+        }
+        let rest: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    x
+}
+
+/// Inverse of `a`, or `None` if it is singular or close to it.
+pub(crate) fn inverse(a: &[Vec<f64>]) -> Option<Matrix> {
+    let n = a.len();
+    let size = norm(a);
+    let columns: Vec<Vec<f64>> = identity(n)
+        .into_iter()
+        .map(|unit| solve(a.to_vec(), unit))
+        .collect();
+    let inverse = transpose(&columns);
+    // A singular pivot leaves zeros behind rather than failing, so check
+    // the product instead
+    let residual = norm(&add(&mul(a, &inverse), &scale(&identity(n), -1.0)));
+    (size > 0.0 && residual.is_finite() && residual < 1e-6).then_some(inverse)
+}
+
+/// Matrix exponential `e^a` by scaling and squaring a Taylor series.
+pub(crate) fn expm(a: &[Vec<f64>]) -> Matrix {
+    let n = a.len();
+    let squarings = norm(a).max(1e-300).log2().ceil().max(0.0) as i32 + 1;
+    let scaled = scale(a, 0.5_f64.powi(squarings));
+    let mut result = identity(n);
+    let mut term = identity(n);
+    for k in 1..=16 {
+        term = scale(&mul(&term, &scaled), 1.0 / k as f64);
+        result = add(&result, &term);
+    }
+    for _ in 0..squarings {
+        result = mul(&result, &result);
+    }
+    result
+}
@@ -0,0 +1,448 @@
+use crate::error::PidError;
+use crate::linalg::solve;
+use crate::traits::Controller;
+use crate::tuning::FopdtModel;
+use std::collections::VecDeque;
+
+/// Validated configuration for an [`MpcController`].
+///
+/// Built with [`MpcConfig::builder`] from the [`FopdtModel`] the controller
+/// predicts with, typically one found by [`FopdtModel::identify`].
+///
+/// | Parameter            | Default                                   |
+/// |----------------------|-------------------------------------------|
+/// | `prediction_horizon` | dead time plus three time constants       |
+/// | `control_horizon`    | 3 moves                                   |
+/// | `move_suppression`   | 1.0                                       |
+/// | `setpoint`           | 0.0                                       |
+/// | `min_output`         | `-inf` (must be set)                      |
+/// | `max_output`         | `+inf` (must be set)                      |
+/// | `initial_output`     | none (the output starts at `0.0`)         |
+#[derive(Debug, Clone, PartialEq)]
+pub struct MpcConfig {
+    pub(crate) model: FopdtModel,
+    pub(crate) prediction_horizon: f64,
+    pub(crate) control_horizon: usize,
+    pub(crate) move_suppression: f64,
+    pub(crate) setpoint: f64,
+    pub(crate) min_output: f64,
+    pub(crate) max_output: f64,
+    pub(crate) initial_output: Option<f64>,
+}
+
+/// Builder for [`MpcConfig`]. Call [`build`](Self::build) to validate.
+#[derive(Debug, Clone)]
+pub struct MpcConfigBuilder {
+    model: FopdtModel,
+    prediction_horizon: Option<f64>,
+    control_horizon: usize,
+    move_suppression: f64,
+    setpoint: f64,
+    min_output: f64,
+    max_output: f64,
+    initial_output: Option<f64>,
+}
+
+impl MpcConfig {
+    /// Returns a builder that predicts with `model`.
+    pub fn builder(model: FopdtModel) -> MpcConfigBuilder {
+        MpcConfigBuilder {
+            model,
+            prediction_horizon: None,
+            control_horizon: 3,
+            move_suppression: 1.0,
+            setpoint: 0.0,
+            min_output: -f64::INFINITY,
+            max_output: f64::INFINITY,
+            initial_output: None,
+        }
+    }
+
+    /// Process model the controller predicts with.
+    pub fn model(&self) -> FopdtModel {
+        self.model
+    }
+
+    /// Seconds ahead the controller predicts.
+    pub fn prediction_horizon(&self) -> f64 {
+        self.prediction_horizon
+    }
+
+    /// Number of future output moves planned each step.
+    pub fn control_horizon(&self) -> usize {
+        self.control_horizon
+    }
+
+    /// Weight on output moves relative to tracking error.
+    pub fn move_suppression(&self) -> f64 {
+        self.move_suppression
+    }
+
+    /// Target process value.
+    pub fn setpoint(&self) -> f64 {
+        self.setpoint
+    }
+
+    /// Lower output clamp.
+    pub fn min_output(&self) -> f64 {
+        self.min_output
+    }
+
+    /// Upper output clamp.
+    pub fn max_output(&self) -> f64 {
+        self.max_output
+    }
+
+    /// Output held before the first step, if set.
+    pub fn initial_output(&self) -> Option<f64> {
+        self.initial_output
+    }
+}
+
+impl MpcConfigBuilder {
+    /// Seconds of future process value the controller weighs. It must reach
+    /// past the model's dead time, and should cover most of the settling
+    /// time: too short and the controller chases the near term and rings.
+    /// The cost of each step grows with `prediction_horizon / dt`.
+    pub fn with_prediction_horizon(mut self, seconds: f64) -> Self {
+        self.prediction_horizon = Some(seconds);
+        self
+    }
+
+    /// Number of future output moves planned each step; only the first is
+    /// applied. One move gives a gentle, nearly open-loop response; more
+    /// lets the controller plan an overdrive and pull back.
+    pub fn with_control_horizon(mut self, moves: usize) -> Self {
+        self.control_horizon = moves;
+        self
+    }
+
+    /// Weight on the size of output moves against tracking error. It is
+    /// scaled by the square of the model gain, so `1.0` means a move that
+    /// would shift the process value by one unit costs as much as one unit
+    /// of error for one sample. Raise it for a calmer output, lower it for
+    /// a faster response.
+    pub fn with_move_suppression(mut self, weight: f64) -> Self {
+        self.move_suppression = weight;
+        self
+    }
+
+    /// Sets the target process value.
+    pub fn with_setpoint(mut self, setpoint: f64) -> Self {
+        self.setpoint = setpoint;
+        self
+    }
+
+    /// Sets the output clamp range `[min, max]`.
+    ///
+    /// The controller plans its moves without the limits and clamps the
+    /// output it applies. The model is fed the clamped output, so a long
+    /// stretch at a limit does not wind anything up.
+    pub fn with_output_limits(mut self, min: f64, max: f64) -> Self {
+        self.min_output = min;
+        self.max_output = max;
+        self
+    }
+
+    /// Output the process is assumed to have settled at before the first
+    /// step, for a warm start. Without it the model assumes `0.0`.
+    pub fn with_initial_output(mut self, output: f64) -> Self {
+        self.initial_output = Some(output);
+        self
+    }
+
+    /// Validates and builds the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if:
+    /// - `prediction_horizon` is non-finite or does not reach past the
+    ///   model's dead time.
+    /// - `control_horizon` is zero.
+    /// - `move_suppression` is non-finite or negative.
+    /// - `setpoint` is non-finite.
+    /// - Output limits are non-finite or `min >= max`.
+    /// - `initial_output` is non-finite or outside the output limits.
+    pub fn build(self) -> Result<MpcConfig, PidError> {
+        let model = self.model;
+        let prediction_horizon = self
+            .prediction_horizon
+            .unwrap_or(model.dead_time + 3.0 * model.time_constant);
+        if !prediction_horizon.is_finite() || prediction_horizon <= model.dead_time {
+            return Err(PidError::InvalidParameter(
+                "prediction_horizon must be finite and longer than the dead time",
+            ));
+        }
+        if self.control_horizon == 0 {
+            return Err(PidError::InvalidParameter(
+                "control_horizon must be at least one move",
+            ));
+        }
+        if !self.move_suppression.is_finite() || self.move_suppression < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "move_suppression must be a finite non-negative number",
+            ));
+        }
+        if !self.setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        if !self.min_output.is_finite() || !self.max_output.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "output limits must be finite numbers",
+            ));
+        }
+        if self.min_output >= self.max_output {
+            return Err(PidError::InvalidParameter(
+                "min_output must be less than max_output",
+            ));
+        }
+        if let Some(output) = self.initial_output {
+            if !output.is_finite() || output < self.min_output || output > self.max_output {
+                return Err(PidError::InvalidParameter(
+                    "initial_output must be a finite number within the output limits",
+                ));
+            }
+        }
+
+        Ok(MpcConfig {
+            model,
+            prediction_horizon,
+            control_horizon: self.control_horizon,
+            move_suppression: self.move_suppression,
+            setpoint: self.setpoint,
+            min_output: self.min_output,
+            max_output: self.max_output,
+            initial_output: self.initial_output,
+        })
+    }
+}
+
+/// Model predictive controller over a first-order-plus-dead-time model.
+///
+/// Each step it runs its [`FopdtModel`] forward over the prediction horizon,
+/// picks the output moves that minimise squared tracking error plus
+/// weighted squared moves, applies the first, and repeats next step. This
+/// is dynamic matrix control without constraints, so the optimum is a fixed
+/// linear gain worked out once per `dt` and each step costs one pass over
+/// the horizon.
+///
+/// Because it predicts through the dead time instead of reacting to it,
+/// it stays stable with far more dead time than a PID tuned for the same
+/// speed. The difference between the measurement and the model's own
+/// output is treated as a constant disturbance, which removes steady-state
+/// offset even when the model is off.
+///
+/// ```
+/// use pidgeon::{FopdtModel, MpcConfig, MpcController};
+///
+/// // Heats 2 °C per % output, 60 s time constant, 30 s before anything moves
+/// let model = FopdtModel::new(2.0, 60.0, 30.0).unwrap();
+/// let config = MpcConfig::builder(model)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = MpcController::new(config);
+///
+/// let output = controller.compute(20.0, 1.0).unwrap();
+/// assert!(output > 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MpcController {
+    config: MpcConfig,
+    prediction: Option<Prediction>,
+    /// Output applied last step
+    output: f64,
+}
+
+/// The model discretized for one `dt`, with the gain that maps predicted
+/// error to the next move and the model's running state.
+#[derive(Debug, Clone)]
+struct Prediction {
+    dt: f64,
+    /// Per-step decay of the model output
+    decay: f64,
+    /// Per-step model output change per unit of input
+    input_gain: f64,
+    /// `gains[i]` weighs the predicted error `i + 1` steps ahead
+    gains: Vec<f64>,
+    /// Outputs still travelling through the dead time, oldest first
+    pending: VecDeque<f64>,
+    /// Model output, relative to zero input
+    model_output: f64,
+}
+
+impl MpcController {
+    /// Creates a controller from a validated [`MpcConfig`].
+    pub fn new(config: MpcConfig) -> Self {
+        Self {
+            output: config.initial_output.unwrap_or(0.0),
+            config,
+            prediction: None,
+        }
+    }
+
+    /// Runs one step and returns the clamped output.
+    ///
+    /// The first step, and any step whose `dt` differs from the last,
+    /// rebuilds the prediction and assumes the process had settled at the
+    /// current output; whatever the model then misses is picked up as
+    /// disturbance.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` is
+    /// non-finite or `dt` is non-finite / non-positive.
+    pub fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+
+        let output = self.output;
+        let prediction = match &mut self.prediction {
+            Some(prediction) if prediction.dt == dt => {
+                // Advance the model by the output applied last step
+                prediction.pending.push_back(output);
+                let applied = prediction.pending.pop_front().unwrap_or(output);
+                prediction.model_output =
+                    prediction.decay * prediction.model_output + prediction.input_gain * applied;
+                prediction
+            }
+            slot => slot.insert(Prediction::new(&self.config, dt, output)),
+        };
+
+        // Predicted error if the output were held from now on, with the
+        // model's miss carried forward as a constant disturbance
+        let disturbance = process_value - prediction.model_output;
+        let mut predicted = prediction.model_output;
+        let mut correction = 0.0;
+        for (i, gain) in prediction.gains.iter().enumerate() {
+            let input = prediction.pending.get(i).copied().unwrap_or(output);
+            predicted = prediction.decay * predicted + prediction.input_gain * input;
+            correction += gain * (self.config.setpoint - predicted - disturbance);
+        }
+
+        self.output = (output + correction).clamp(self.config.min_output, self.config.max_output);
+        Ok(self.output)
+    }
+
+    /// Forgets the model state and returns the output to its initial
+    /// value. The configuration is preserved.
+    pub fn reset(&mut self) {
+        self.prediction = None;
+        self.output = self.config.initial_output.unwrap_or(0.0);
+    }
+
+    /// Updates the setpoint at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        self.config.setpoint = setpoint;
+        Ok(())
+    }
+
+    /// Returns the current setpoint.
+    pub fn setpoint(&self) -> f64 {
+        self.config.setpoint
+    }
+
+    /// Returns the output applied last step.
+    pub fn output(&self) -> f64 {
+        self.output
+    }
+
+    /// Returns a reference to the current [`MpcConfig`].
+    pub fn config(&self) -> &MpcConfig {
+        &self.config
+    }
+}
+
+impl Controller for MpcController {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        MpcController::compute(self, process_value, dt)
+    }
+
+    fn setpoint(&self) -> f64 {
+        MpcController::setpoint(self)
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        MpcController::set_setpoint(self, setpoint)
+    }
+
+    fn reset(&mut self) {
+        MpcController::reset(self)
+    }
+}
+
+impl Prediction {
+    /// Discretizes the model for `dt` with the process settled at `output`.
+    fn new(config: &MpcConfig, dt: f64, output: f64) -> Self {
+        let model = config.model;
+        let decay = (-dt / model.time_constant).exp();
+        let input_gain = model.gain * (1.0 - decay);
+        let delay = (model.dead_time / dt).round() as usize;
+        let horizon = ((config.prediction_horizon / dt).ceil() as usize).max(delay + 1);
+        let moves = config.control_horizon.min(horizon);
+
+        // Step response: a move now first shows `delay + 1` steps later
+        let step_response = |steps: usize| {
+            if steps <= delay {
+                0.0
+            } else {
+                model.gain * (1.0 - decay.powi((steps - delay) as i32))
+            }
+        };
+
+        // Normal equations (GᵀG + λK²I)·z = e₀ of the least-squares plan over
+        // the dynamic matrix G[i][j] = step_response(i + 1 − j); the first
+        // move is then Gz·error, so Gz is all that needs keeping
+        let weight = config.move_suppression * model.gain * model.gain;
+        let mut normal = vec![vec![0.0; moves]; moves];
+        for (j, row) in normal.iter_mut().enumerate() {
+            for (k, cell) in row.iter_mut().enumerate() {
+                *cell = (j.max(k)..horizon)
+                    .map(|i| step_response(i + 1 - j) * step_response(i + 1 - k))
+                    .sum();
+            }
+            row[j] += weight;
+        }
+        let mut first = vec![0.0; moves];
+        first[0] = 1.0;
+        let plan = solve(normal, first);
+        let gains = (0..horizon)
+            .map(|i| {
+                plan.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j <= i)
+                    .map(|(j, z)| step_response(i + 1 - j) * z)
+                    .sum()
+            })
+            .collect();
+
+        Self {
+            dt,
+            decay,
+            input_gain,
+            gains,
+            pending: std::iter::repeat_n(output, delay).collect(),
+            model_output: model.gain * output,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::io;
+
+use async_nats::jetstream::{self, context::PublishAckFuture};
+
+use crate::debug::ControllerDebugData;
+use crate::sinks::DebugSink;
+
+/// Publishes debug samples to NATS, one subject per controller.
+///
+/// Each sample is encoded as JSON and published to
+/// `<subject_prefix>.<controller_id>`, so subscribers can follow one loop
+/// (`pidgeon.debug.oven`) or all of them (`pidgeon.debug.>`). Characters that
+/// NATS treats specially in a subject token (`.`, `*`, `>` and whitespace) are
+/// replaced with `_` in the controller ID.
+///
+/// With [`with_jetstream`](Self::with_jetstream) samples are published through
+/// JetStream instead of core NATS and persisted in a stream. Acknowledgements
+/// are collected on [`flush`](DebugSink::flush), which the debugger calls each
+/// time its queue drains, so publishing never waits on a round trip per sample.
+///
+/// The sink owns a small Tokio runtime. [`connect`](Self::connect) and every
+/// write block on it, so create the sink outside of any async context.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pidgeon::{DebugConfig, NatsSink};
+///
+/// let sink = NatsSink::connect("nats://127.0.0.1:4222", "pidgeon.debug")
+///     .unwrap()
+///     .with_jetstream("PIDGEON_DEBUG")
+///     .unwrap();
+///
+/// let debug_config = DebugConfig {
+///     controller_id: "oven".to_string(),
+///     ..Default::default()
+/// }
+/// .with_sink(sink);
+/// ```
+pub struct NatsSink {
+    runtime: tokio::runtime::Runtime,
+    client: async_nats::Client,
+    jetstream: Option<jetstream::Context>,
+    subject_prefix: String,
+    pending_acks: Vec<PublishAckFuture>,
+}
+
+impl NatsSink {
+    /// Connects to the NATS server(s) at `url` and publishes core NATS
+    /// messages under `subject_prefix`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runtime cannot be created or the connection fails.
+    pub fn connect(url: &str, subject_prefix: impl Into<String>) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("pidgeon-nats")
+            .enable_all()
+            .build()?;
+        let client = runtime
+            .block_on(async_nats::connect(url))
+            .map_err(io::Error::other)?;
+
+        Ok(NatsSink {
+            runtime,
+            client,
+            jetstream: None,
+            subject_prefix: subject_prefix.into(),
+            pending_acks: Vec::new(),
+        })
+    }
+
+    /// Publishes through JetStream, persisting samples in `stream_name`. The
+    /// stream is created with the subject filter `<subject_prefix>.>` if it
+    /// does not already exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream cannot be looked up or created.
+    pub fn with_jetstream(mut self, stream_name: &str) -> io::Result<Self> {
+        let context = jetstream::new(self.client.clone());
+        let config = jetstream::stream::Config {
+            name: stream_name.to_string(),
+            subjects: vec![format!("{}.>", self.subject_prefix)],
+            ..Default::default()
+        };
+        self.runtime
+            .block_on(context.get_or_create_stream(config))
+            .map_err(io::Error::other)?;
+
+        self.jetstream = Some(context);
+        Ok(self)
+    }
+
+    /// Subject that samples from `controller_id` are published to.
+    pub fn subject_for(&self, controller_id: &str) -> String {
+        let token: String = controller_id
+            .chars()
+            .map(|c| match c {
+                '.' | '*' | '>' => '_',
+                c if c.is_whitespace() => '_',
+                c => c,
+            })
+            .collect();
+        format!("{}.{}", self.subject_prefix, token)
+    }
+}
+
+impl DebugSink for NatsSink {
+    fn write(&mut self, data: &ControllerDebugData) -> io::Result<()> {
+        let subject = self.subject_for(&data.controller_id);
+        let payload = serde_json::to_vec(data).map_err(io::Error::other)?;
+
+        match &self.jetstream {
+            Some(context) => {
+                let ack = self
+                    .runtime
+                    .block_on(context.publish(subject, payload.into()))
+                    .map_err(io::Error::other)?;
+                self.pending_acks.push(ack);
+            }
+            None => {
+                self.runtime
+                    .block_on(self.client.publish(subject, payload.into()))
+                    .map_err(io::Error::other)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let acks = std::mem::take(&mut self.pending_acks);
+        self.runtime.block_on(async {
+            let mut first_error = None;
+            for ack in acks {
+                if let Err(e) = ack.await {
+                    first_error.get_or_insert(io::Error::other(e));
+                }
+            }
+            self.client.flush().await.map_err(io::Error::other)?;
+            first_error.map_or(Ok(()), Err)
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::error::PidError;
+use crate::events::OperatingMode;
+use crate::registry::ControllerRegistry;
+use crate::thread_safe::ThreadSafePidController;
+
+/// A value of a registered loop that a SCADA-facing server exposes as a
+/// node, such as the OPC UA server of the `opcua` feature.
+///
+/// Each loop of a [`ControllerRegistry`] becomes one node per variant,
+/// addressed by [`path`](Self::path). The setpoint and gains are writable by
+/// an [`AccessRole::Operator`] or above; the rest is read-only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopNode {
+    /// Most recent measurement.
+    ProcessValue,
+    /// Setpoint.
+    Setpoint,
+    /// Most recent clamped output.
+    Output,
+    /// Current [`OperatingMode`].
+    Mode,
+    /// Proportional gain.
+    Kp,
+    /// Integral gain.
+    Ki,
+    /// Derivative gain.
+    Kd,
+}
+
+impl LoopNode {
+    /// Every node of a loop, in browse order.
+    pub const ALL: [LoopNode; 7] = [
+        LoopNode::ProcessValue,
+        LoopNode::Setpoint,
+        LoopNode::Output,
+        LoopNode::Mode,
+        LoopNode::Kp,
+        LoopNode::Ki,
+        LoopNode::Kd,
+    ];
+
+    /// Browse name of the node.
+    pub fn name(self) -> &'static str {
+        match self {
+            LoopNode::ProcessValue => "ProcessValue",
+            LoopNode::Setpoint => "Setpoint",
+            LoopNode::Output => "Output",
+            LoopNode::Mode => "Mode",
+            LoopNode::Kp => "Kp",
+            LoopNode::Ki => "Ki",
+            LoopNode::Kd => "Kd",
+        }
+    }
+
+    /// Whether any role may write the node.
+    pub fn is_writable(self) -> bool {
+        matches!(
+            self,
+            LoopNode::Setpoint | LoopNode::Kp | LoopNode::Ki | LoopNode::Kd
+        )
+    }
+
+    /// `<loop>.<node>` address of this node of `loop_name`, the inverse of
+    /// [`parse_path`](Self::parse_path).
+    pub fn path(self, loop_name: &str) -> String {
+        format!("{}.{}", loop_name, self.name())
+    }
+
+    /// Splits a [`path`](Self::path) back into its loop name and node. Loop
+    /// names may themselves contain dots.
+    pub fn parse_path(path: &str) -> Option<(&str, LoopNode)> {
+        let (loop_name, node) = path.rsplit_once('.')?;
+        Some((loop_name, node.parse().ok()?))
+    }
+}
+
+impl fmt::Display for LoopNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LoopNode {
+    type Err = PidError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        LoopNode::ALL
+            .into_iter()
+            .find(|node| node.name() == name)
+            .ok_or(PidError::InvalidParameter("unknown loop node"))
+    }
+}
+
+/// What a client of a SCADA-facing server may do, matching pidgeoneer's
+/// viewer, operator, and admin roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccessRole {
+    /// Read every node.
+    Viewer,
+    /// Also write setpoints and gains.
+    Operator,
+    /// Everything an operator can do.
+    Admin,
+}
+
+impl AccessRole {
+    /// Whether this role may write `node`.
+    pub fn can_write(self, node: LoopNode) -> bool {
+        node.is_writable() && self >= AccessRole::Operator
+    }
+}
+
+/// Value of a [`LoopNode`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeValue {
+    /// A measurement, setpoint, output, or gain.
+    Number(f64),
+    /// The loop's operating mode.
+    Mode(OperatingMode),
+}
+
+impl ControllerRegistry {
+    /// Reads `node` of the loop registered under `name`. The process value
+    /// and output are `None` until the loop's first step.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if no loop is registered under
+    /// `name`, or [`PidError::MutexPoisoned`].
+    pub fn read_node(&self, name: &str, node: LoopNode) -> Result<Option<NodeValue>, PidError> {
+        let controller = self
+            .get(name)
+            .ok_or(PidError::InvalidParameter(
+                "no controller registered under that name",
+            ))?
+            .lock()?;
+        let running = !controller.state.first_run;
+        Ok(match node {
+            LoopNode::ProcessValue => {
+                running.then_some(NodeValue::Number(controller.state.prev_measurement))
+            }
+            LoopNode::Setpoint => Some(NodeValue::Number(controller.config.setpoint)),
+            LoopNode::Output => running.then_some(NodeValue::Number(controller.state.last_output)),
+            LoopNode::Mode => Some(NodeValue::Mode(controller.mode)),
+            LoopNode::Kp => Some(NodeValue::Number(controller.config.kp)),
+            LoopNode::Ki => Some(NodeValue::Number(controller.config.ki)),
+            LoopNode::Kd => Some(NodeValue::Number(controller.config.kd)),
+        })
+    }
+
+    /// Writes `value` to `node` of the loop registered under `name` on
+    /// behalf of a client with `role`, through
+    /// [`set_setpoint`](crate::PidController::set_setpoint) or the gain
+    /// setters, so setpoint and gain limits apply and the change is logged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if no loop is registered under
+    /// `name`, `role` may not write `node`, or the setter refuses `value`;
+    /// or [`PidError::MutexPoisoned`].
+    pub fn write_node(
+        &self,
+        name: &str,
+        node: LoopNode,
+        value: f64,
+        role: AccessRole,
+    ) -> Result<(), PidError> {
+        let controller = self.get(name).ok_or(PidError::InvalidParameter(
+            "no controller registered under that name",
+        ))?;
+        let set: fn(&ThreadSafePidController, f64) -> Result<(), PidError> = match node {
+            LoopNode::Setpoint => ThreadSafePidController::set_setpoint,
+            LoopNode::Kp => ThreadSafePidController::set_kp,
+            LoopNode::Ki => ThreadSafePidController::set_ki,
+            LoopNode::Kd => ThreadSafePidController::set_kd,
+            LoopNode::ProcessValue | LoopNode::Output | LoopNode::Mode => {
+                return Err(PidError::InvalidParameter("that loop node is read-only"))
+            }
+        };
+        if !role.can_write(node) {
+            return Err(PidError::InvalidParameter(
+                "writing loop nodes needs the operator role",
+            ));
+        }
+        set(controller, value)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::PidError;
+use crate::linalg::{self, Matrix};
+use crate::state_space::StateSpaceModel;
+
+/// Luenberger observer: estimates a process's unmeasured states, such as
+/// velocity from a position sensor, by running its [`StateSpaceModel`]
+/// alongside it and correcting the model with every measurement.
+///
+/// Each [`update`](Self::update) predicts the state from the last estimate
+/// and the output applied since, then pulls the prediction toward the new
+/// measurement. The correction gain places every pole of the estimation
+/// error at `−bandwidth` rad/s, so the estimate forgets a wrong start in
+/// about `4 / bandwidth` seconds. Faster than the loop it serves, but slow
+/// enough not to pass measurement noise straight through: three to ten
+/// times the closed-loop bandwidth is the usual range.
+///
+/// The estimate feeds either of two consumers: [`rate`](Self::rate) as the
+/// derivative source for [`PidController::compute_with_rate`](crate::PidController::compute_with_rate),
+/// or the full [`estimate`](Self::estimate) for a
+/// [`StateFeedbackController`](crate::StateFeedbackController), which can
+/// also own one through
+/// [`with_observer`](crate::StateFeedbackController::with_observer).
+///
+/// ```
+/// use pidgeon::{LuenbergerObserver, SecondOrderModel};
+///
+/// let model = SecondOrderModel::new(1.0, 2.0, 0.5).unwrap();
+/// let mut observer = LuenbergerObserver::new(model, 20.0).unwrap();
+///
+/// // Position readings only; the observer works out the velocity
+/// observer.update(0.0, 0.0, 0.01).unwrap();
+/// let state = observer.update(0.01, 0.0, 0.01).unwrap();
+/// assert_eq!(state.len(), 2);
+/// assert!(observer.rate() > 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LuenbergerObserver {
+    model: StateSpaceModel,
+    bandwidth: f64,
+    design: Option<Design>,
+    estimate: Vec<f64>,
+    /// Output applied over the last interval
+    input: f64,
+}
+
+/// The model discretized for one `dt` with its correction gain.
+#[derive(Debug, Clone)]
+struct Design {
+    dt: f64,
+    phi: Matrix,
+    gamma: Vec<f64>,
+    gain: Vec<f64>,
+}
+
+impl LuenbergerObserver {
+    /// Creates an observer for `model` whose estimation error decays at
+    /// `bandwidth` rad/s.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `bandwidth` is not finite
+    /// and positive, or the model's states cannot all be told apart from
+    /// the process value alone (it is not observable).
+    pub fn new(model: impl Into<StateSpaceModel>, bandwidth: f64) -> Result<Self, PidError> {
+        let model = model.into();
+        if !bandwidth.is_finite() || bandwidth <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "bandwidth must be a finite positive number",
+            ));
+        }
+        // Observable when C, CA, …, CAⁿ⁻¹ are independent
+        let a_transposed = linalg::transpose(model.a());
+        let mut rows = vec![model.c().to_vec()];
+        while rows.len() < model.order() {
+            let next = linalg::mul_vec(&a_transposed, &rows[rows.len() - 1]);
+            rows.push(next);
+        }
+        if linalg::inverse(&rows).is_none() {
+            return Err(PidError::InvalidParameter(
+                "model states are not observable from the process value",
+            ));
+        }
+        Ok(Self {
+            estimate: vec![0.0; model.order()],
+            model,
+            bandwidth,
+            design: None,
+            input: 0.0,
+        })
+    }
+
+    /// Takes a new measurement `dt` seconds after the previous one, with
+    /// `input` the controller output applied over that interval, and
+    /// returns the updated state estimate.
+    ///
+    /// The first update starts from the steady state that matches the
+    /// measurement, when the model has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` or `input`
+    /// is non-finite, or `dt` is non-finite / non-positive.
+    pub fn update(&mut self, process_value: f64, input: f64, dt: f64) -> Result<&[f64], PidError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+        if !input.is_finite() {
+            return Err(PidError::InvalidParameter("input must be a finite number"));
+        }
+        self.input = input;
+
+        let first = self.design.is_none();
+        let design = match &mut self.design {
+            Some(design) if design.dt == dt => design,
+            slot => slot.insert(Design::new(&self.model, self.bandwidth, dt)?),
+        };
+        let predicted: Vec<f64> = if first {
+            match self.model.steady_state() {
+                Some((state, _)) => state.iter().map(|x| x * process_value).collect(),
+                None => self.estimate.clone(),
+            }
+        } else {
+            linalg::mul_vec(&design.phi, &self.estimate)
+                .iter()
+                .zip(&design.gamma)
+                .map(|(x, g)| x + g * input)
+                .collect()
+        };
+
+        let innovation = process_value - linalg::dot(self.model.c(), &predicted);
+        self.estimate = predicted
+            .iter()
+            .zip(&design.gain)
+            .map(|(x, l)| x + l * innovation)
+            .collect();
+        Ok(&self.estimate)
+    }
+
+    /// Current state estimate.
+    pub fn estimate(&self) -> &[f64] {
+        &self.estimate
+    }
+
+    /// Estimated rate of change of the process value, `C·(A·x̂ + B·u)`.
+    /// For a model converted from a
+    /// [`SecondOrderModel`](crate::SecondOrderModel) that is the second
+    /// state.
+    pub fn rate(&self) -> f64 {
+        let derivative: Vec<f64> = linalg::mul_vec(self.model.a(), &self.estimate)
+            .iter()
+            .zip(self.model.b())
+            .map(|(x, b)| x + b * self.input)
+            .collect();
+        linalg::dot(self.model.c(), &derivative)
+    }
+
+    /// Estimated process value, `C·x̂`: the measurement with the noise the
+    /// model cannot explain filtered out.
+    pub fn process_value(&self) -> f64 {
+        linalg::dot(self.model.c(), &self.estimate)
+    }
+
+    /// Error-decay rate in rad/s.
+    pub fn bandwidth(&self) -> f64 {
+        self.bandwidth
+    }
+
+    /// Model the observer runs.
+    pub fn model(&self) -> &StateSpaceModel {
+        &self.model
+    }
+
+    /// Forgets the estimate; the next update starts over.
+    pub fn reset(&mut self) {
+        self.design = None;
+        self.estimate.fill(0.0);
+        self.input = 0.0;
+    }
+}
+
+impl Design {
+    /// Discretizes `model` for `dt` and places the poles of the estimation
+    /// error, `Φ − L·C·Φ`, all at `e^(−bandwidth·dt)`.
+    fn new(model: &StateSpaceModel, bandwidth: f64, dt: f64) -> Result<Self, PidError> {
+        let (phi, gamma) = model.discretize(dt);
+        let order = model.order();
+
+        // Ackermann's formula for the current-estimator form:
+        // L = p(Φ)·[CΦ; CΦ²; …; CΦⁿ]⁻¹·eₙ with p(z) = (z − λ)ⁿ
+        let pole = (-bandwidth * dt).exp();
+        let shifted = linalg::add(&phi, &linalg::scale(&linalg::identity(order), -pole));
+        let mut polynomial = linalg::identity(order);
+        for _ in 0..order {
+            polynomial = linalg::mul(&polynomial, &shifted);
+        }
+        let phi_transposed = linalg::transpose(&phi);
+        let mut row = model.c().to_vec();
+        let mut rows = Vec::with_capacity(order);
+        for _ in 0..order {
+            row = linalg::mul_vec(&phi_transposed, &row);
+            rows.push(row.clone());
+        }
+        let inverse = linalg::inverse(&rows).ok_or(PidError::InvalidParameter(
+            "model states are not observable at this sample time",
+        ))?;
+        let last_column: Vec<f64> = inverse.iter().map(|row| row[order - 1]).collect();
+        let gain = linalg::mul_vec(&polynomial, &last_column);
+
+        Ok(Self {
+            dt,
+            phi,
+            gamma,
+            gain,
+        })
+    }
+}
@@ -0,0 +1,615 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use opcua::server::address_space::{AccessLevel, AddressSpace, Variable};
+use opcua::server::authenticator::{
+    user_pass_security_policy_id, AuthManager, Password, UserToken,
+};
+use opcua::server::diagnostics::NamespaceMetadata;
+use opcua::server::node_manager::memory::{
+    InMemoryNodeManager, InMemoryNodeManagerBuilder, InMemoryNodeManagerImpl,
+};
+use opcua::server::node_manager::{
+    MonitoredItemRef, MonitoredItemUpdateRef, ParsedReadValueId, RequestContext, ServerContext,
+    SyncSampler, WriteNode,
+};
+use opcua::server::{CreateMonitoredItem, ServerBuilder, ServerEndpoint, ServerHandle};
+use opcua::sync::RwLock;
+use opcua::types::{
+    AttributeId, DataTypeId, DataValue, Error, MonitoringMode, NodeClass, NodeId, StatusCode,
+    TimestampsToReturn, UAString, UserTokenPolicy, UserTokenType, Variant,
+};
+
+use crate::error::PidError;
+use crate::events::OperatingMode;
+use crate::nodes::{AccessRole, LoopNode, NodeValue};
+use crate::registry::ControllerRegistry;
+
+/// Namespace of the loop nodes.
+const NAMESPACE_URI: &str = "urn:pidgeon:loops";
+
+/// Policy ID the server expects of anonymous logins.
+const ANONYMOUS_POLICY_ID: &str = "anonymous";
+
+/// Where and to whom an [`OpcUaServer`] serves.
+///
+/// No security policy is offered, so passwords cross the network in the
+/// clear; run the server on a control network or behind a tunnel.
+#[derive(Debug, Clone)]
+pub struct OpcUaConfig {
+    host: String,
+    port: u16,
+    pki_dir: PathBuf,
+    users: BTreeMap<String, (String, AccessRole)>,
+    anonymous: Option<AccessRole>,
+}
+
+impl OpcUaConfig {
+    /// Serves on `host:port` to nobody until users are added.
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        OpcUaConfig {
+            host: host.into(),
+            port,
+            pki_dir: PathBuf::from("pki"),
+            users: BTreeMap::new(),
+            anonymous: None,
+        }
+    }
+
+    /// Lets `username` log in with `password` as `role`.
+    pub fn with_user(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+        role: AccessRole,
+    ) -> Self {
+        self.users.insert(username.into(), (password.into(), role));
+        self
+    }
+
+    /// Lets clients connect without logging in, as `role`. Default: anonymous
+    /// clients are refused.
+    pub fn with_anonymous(mut self, role: AccessRole) -> Self {
+        self.anonymous = Some(role);
+        self
+    }
+
+    /// Keeps the server's certificate and trusted clients in `dir`. Default:
+    /// `pki`.
+    pub fn with_pki_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.pki_dir = dir.into();
+        self
+    }
+}
+
+/// Who may log in, and as which [`AccessRole`].
+#[derive(Debug)]
+pub(crate) struct Roles {
+    users: BTreeMap<String, (String, AccessRole)>,
+    anonymous: Option<AccessRole>,
+}
+
+impl Roles {
+    /// The logins of `config`.
+    pub(crate) fn new(config: &OpcUaConfig) -> Self {
+        Roles {
+            users: config.users.clone(),
+            anonymous: config.anonymous,
+        }
+    }
+
+    /// Role of the user a session logged in as, `None` if it has none.
+    pub(crate) fn role(&self, token: &UserToken) -> Option<AccessRole> {
+        if token.is_anonymous() {
+            self.anonymous
+        } else {
+            self.users.get(&token.0).map(|&(_, role)| role)
+        }
+    }
+}
+
+/// Checks logins against [`Roles`] and reports nodes a user's role may not
+/// write as read-only.
+struct RoleAuthenticator {
+    roles: Arc<Roles>,
+}
+
+#[async_trait]
+impl AuthManager for RoleAuthenticator {
+    async fn authenticate_anonymous_token(&self, _endpoint: &ServerEndpoint) -> Result<(), Error> {
+        match self.roles.anonymous {
+            Some(_) => Ok(()),
+            None => Err(Error::new(
+                StatusCode::BadIdentityTokenRejected,
+                "anonymous access is disabled",
+            )),
+        }
+    }
+
+    async fn authenticate_username_identity_token(
+        &self,
+        _endpoint: &ServerEndpoint,
+        username: &str,
+        password: &Password,
+    ) -> Result<UserToken, Error> {
+        match self.roles.users.get(username) {
+            Some((expected, _)) if password.get() == expected.as_str() => {
+                Ok(UserToken(username.to_string()))
+            }
+            _ => Err(Error::new(
+                StatusCode::BadUserAccessDenied,
+                "unknown user or wrong password",
+            )),
+        }
+    }
+
+    fn effective_user_access_level(
+        &self,
+        token: &UserToken,
+        user_access_level: AccessLevel,
+        node_id: &NodeId,
+    ) -> AccessLevel {
+        let writable = match (self.roles.role(token), loop_node(node_id)) {
+            (Some(role), Some((_, node))) => role.can_write(node),
+            _ => false,
+        };
+        if writable {
+            user_access_level
+        } else {
+            user_access_level - AccessLevel::CURRENT_WRITE
+        }
+    }
+
+    fn user_token_policies(&self, endpoint: &ServerEndpoint) -> Vec<UserTokenPolicy> {
+        let mut policies = vec![UserTokenPolicy {
+            policy_id: user_pass_security_policy_id(endpoint),
+            token_type: UserTokenType::UserName,
+            ..Default::default()
+        }];
+        if self.roles.anonymous.is_some() {
+            policies.push(UserTokenPolicy {
+                policy_id: UAString::from(ANONYMOUS_POLICY_ID),
+                token_type: UserTokenType::Anonymous,
+                ..Default::default()
+            });
+        }
+        policies
+    }
+}
+
+/// Loop name and node of a loop node ID
+fn loop_node(node_id: &NodeId) -> Option<(String, LoopNode)> {
+    let opcua::types::Identifier::String(path) = &node_id.identifier else {
+        return None;
+    };
+    let (loop_name, node) = LoopNode::parse_path(path.as_ref())?;
+    Some((loop_name.to_string(), node))
+}
+
+fn variant(value: Option<NodeValue>) -> Variant {
+    match value {
+        Some(NodeValue::Number(number)) => Variant::Double(number),
+        Some(NodeValue::Mode(mode)) => Variant::String(UAString::from(match mode {
+            OperatingMode::Automatic => "Automatic",
+            OperatingMode::Tracking => "Tracking",
+            OperatingMode::Interlocked => "Interlocked",
+        })),
+        None => Variant::Empty,
+    }
+}
+
+/// Serves the loop nodes of a [`ControllerRegistry`], reading and writing
+/// them through the registry as each request arrives.
+pub(crate) struct LoopNodeManager {
+    registry: ControllerRegistry,
+    roles: Arc<Roles>,
+    namespaces: Vec<NamespaceMetadata>,
+    samplers: SyncSampler,
+}
+
+impl LoopNodeManager {
+    /// Serves `registry` in the namespace with index `namespace`.
+    pub(crate) fn new(registry: ControllerRegistry, roles: Arc<Roles>, namespace: u16) -> Self {
+        LoopNodeManager {
+            registry,
+            roles,
+            namespaces: vec![NamespaceMetadata {
+                namespace_uri: NAMESPACE_URI.to_string(),
+                namespace_index: namespace,
+                ..Default::default()
+            }],
+            samplers: SyncSampler::new(),
+        }
+    }
+
+    /// Registers the loop namespace with the server and adds a folder per
+    /// loop to `address_space`.
+    fn build(
+        registry: ControllerRegistry,
+        roles: Arc<Roles>,
+        context: &ServerContext,
+        address_space: &mut AddressSpace,
+    ) -> Self {
+        let namespace = context
+            .type_tree
+            .write()
+            .namespaces_mut()
+            .add_namespace(NAMESPACE_URI);
+        address_space.add_namespace(NAMESPACE_URI, namespace);
+
+        let loops_folder = NodeId::new(namespace, "Loops");
+        address_space.add_folder(
+            &loops_folder,
+            "Loops",
+            "Loops",
+            &NodeId::objects_folder_id(),
+        );
+        for name in registry.names() {
+            let folder = NodeId::new(namespace, name.to_string());
+            address_space.add_folder(&folder, name.to_string(), name.to_string(), &loops_folder);
+            let variables = LoopNode::ALL
+                .into_iter()
+                .map(|node| {
+                    let node_id = NodeId::new(namespace, node.path(name));
+                    // Values are read from the registry on demand, so only
+                    // the data type is fixed here
+                    let data_type = match node {
+                        LoopNode::Mode => DataTypeId::String,
+                        _ => DataTypeId::Double,
+                    };
+                    let mut variable = Variable::new_data_value(
+                        &node_id,
+                        node.name(),
+                        node.name(),
+                        data_type,
+                        None,
+                        None,
+                        Variant::Empty,
+                    );
+                    if node.is_writable() {
+                        let access = AccessLevel::CURRENT_READ | AccessLevel::CURRENT_WRITE;
+                        variable.set_access_level(access);
+                        variable.set_user_access_level(access);
+                    }
+                    variable
+                })
+                .collect();
+            address_space.add_variables(variables, &folder);
+        }
+        LoopNodeManager::new(registry, roles, namespace)
+    }
+
+    /// Current value of a loop node.
+    fn read_value(&self, node_id: &NodeId) -> Result<DataValue, StatusCode> {
+        let (loop_name, node) = loop_node(node_id).ok_or(StatusCode::BadNodeIdUnknown)?;
+        let value = self
+            .registry
+            .read_node(&loop_name, node)
+            .map_err(|_| StatusCode::BadInternalError)?;
+        Ok(DataValue::new_now(variant(value)))
+    }
+
+    /// Writes `value` to a loop node on behalf of the session logged in as
+    /// `token`, with that user's role.
+    pub(crate) fn write_value(
+        &self,
+        token: &UserToken,
+        node_id: &NodeId,
+        value: &DataValue,
+    ) -> StatusCode {
+        let Some((loop_name, node)) = loop_node(node_id) else {
+            return StatusCode::BadNodeIdUnknown;
+        };
+        let Some(role) = self.roles.role(token).filter(|role| role.can_write(node)) else {
+            return StatusCode::BadUserAccessDenied;
+        };
+        let Some(Variant::Double(number)) = value.value else {
+            return StatusCode::BadTypeMismatch;
+        };
+        match self.registry.write_node(&loop_name, node, number, role) {
+            Ok(()) => StatusCode::Good,
+            Err(PidError::InvalidParameter(_)) => StatusCode::BadOutOfRange,
+            Err(PidError::MutexPoisoned) => StatusCode::BadInternalError,
+        }
+    }
+}
+
+#[async_trait]
+impl InMemoryNodeManagerImpl for LoopNodeManager {
+    async fn init(&self, _address_space: &mut AddressSpace, context: ServerContext) {
+        let interval = context
+            .info
+            .config
+            .limits
+            .subscriptions
+            .min_sampling_interval_ms;
+        self.samplers.run(
+            Duration::from_millis(interval as u64),
+            context.subscriptions.clone(),
+        );
+    }
+
+    fn name(&self) -> &str {
+        "pidgeon"
+    }
+
+    fn namespaces(&self) -> Vec<NamespaceMetadata> {
+        self.namespaces.clone()
+    }
+
+    async fn read_values(
+        &self,
+        context: &RequestContext,
+        address_space: &RwLock<AddressSpace>,
+        nodes: &[&ParsedReadValueId],
+        _max_age: f64,
+        _timestamps_to_return: TimestampsToReturn,
+    ) -> Vec<DataValue> {
+        let address_space = address_space.read();
+        nodes
+            .iter()
+            .map(|node| {
+                address_space
+                    .validate_node_read(context, node)
+                    .and_then(|_| self.read_value(&node.node_id))
+                    .unwrap_or_else(|status| DataValue {
+                        status: Some(status),
+                        ..Default::default()
+                    })
+            })
+            .collect()
+    }
+
+    async fn create_value_monitored_items(
+        &self,
+        context: &RequestContext,
+        address_space: &RwLock<AddressSpace>,
+        items: &mut [&mut &mut CreateMonitoredItem],
+    ) {
+        let to_read: Vec<_> = items.iter().map(|item| item.item_to_monitor()).collect();
+        let values = self
+            .read_values(
+                context,
+                address_space,
+                &to_read,
+                0.0,
+                TimestampsToReturn::Both,
+            )
+            .await;
+
+        for (value, item) in values.into_iter().zip(items.iter_mut()) {
+            let status = value.status();
+            item.set_initial_value(value);
+            item.set_status(if status == StatusCode::BadNodeIdUnknown {
+                status
+            } else {
+                StatusCode::Good
+            });
+            if status == StatusCode::BadNodeIdUnknown {
+                continue;
+            }
+
+            let node_id = item.item_to_monitor().node_id.clone();
+            let Some((loop_name, node)) = loop_node(&node_id) else {
+                continue;
+            };
+            let registry = self.registry.clone();
+            self.samplers.add_sampler(
+                node_id,
+                AttributeId::Value,
+                move || {
+                    Some(match registry.read_node(&loop_name, node) {
+                        Ok(value) => DataValue::new_now(variant(value)),
+                        Err(_) => DataValue {
+                            status: Some(StatusCode::BadInternalError),
+                            ..Default::default()
+                        },
+                    })
+                },
+                item.monitoring_mode(),
+                item.handle(),
+                Duration::from_millis(item.sampling_interval() as u64),
+            );
+        }
+    }
+
+    async fn modify_monitored_items(
+        &self,
+        _context: &RequestContext,
+        items: &[&MonitoredItemUpdateRef],
+    ) {
+        for item in items {
+            self.samplers.update_sampler(
+                item.node_id(),
+                item.attribute(),
+                item.handle(),
+                Duration::from_millis(item.update().revised_sampling_interval as u64),
+            );
+        }
+    }
+
+    async fn set_monitoring_mode(
+        &self,
+        _context: &RequestContext,
+        mode: MonitoringMode,
+        items: &[&MonitoredItemRef],
+    ) {
+        for item in items {
+            self.samplers
+                .set_sampler_mode(item.node_id(), item.attribute(), item.handle(), mode);
+        }
+    }
+
+    async fn delete_monitored_items(&self, _context: &RequestContext, items: &[&MonitoredItemRef]) {
+        for item in items {
+            self.samplers
+                .remove_sampler(item.node_id(), item.attribute(), item.handle());
+        }
+    }
+
+    async fn write(
+        &self,
+        context: &RequestContext,
+        address_space: &RwLock<AddressSpace>,
+        nodes_to_write: &mut [&mut WriteNode],
+    ) -> Result<(), StatusCode> {
+        let mut address_space = address_space.write();
+        let type_tree = context.type_tree.read();
+        for write in nodes_to_write {
+            let status =
+                match address_space.validate_node_write(context, write.value(), &*type_tree) {
+                    Err(status) => status,
+                    Ok(node)
+                        if node.node_class() != NodeClass::Variable
+                            || write.value().attribute_id != AttributeId::Value =>
+                    {
+                        StatusCode::BadNotWritable
+                    }
+                    // The role comes from the session's login, not from the
+                    // access level the client was shown
+                    Ok(_) => self.write_value(
+                        &context.token,
+                        &write.value().node_id,
+                        &write.value().value,
+                    ),
+                };
+            write.set_status(status);
+        }
+        Ok(())
+    }
+}
+
+/// Serves the loops of a [`ControllerRegistry`] over OPC UA, so SCADA and
+/// HMI clients can browse, trend, and adjust them.
+///
+/// Every loop registered when the server starts gets a folder under
+/// `Objects/Loops` holding one variable per [`LoopNode`], with the string
+/// node ID [`LoopNode::path`] in the `urn:pidgeon:loops` namespace. Reads go
+/// through [`ControllerRegistry::read_node`] and writes through
+/// [`ControllerRegistry::write_node`], so setpoint and gain limits apply and
+/// every change lands in the loop's event log.
+///
+/// Clients log in with a username and password from the [`OpcUaConfig`],
+/// each mapped to an [`AccessRole`]. A viewer only reads; an operator or
+/// admin may also write the setpoint and gains. Each write is made with the
+/// role of the user its session logged in as, and nodes a client may not
+/// write are reported read-only to it.
+///
+/// The server runs on its own thread and Tokio runtime, so start it outside
+/// of any async context.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pidgeon::{AccessRole, ControllerConfig, ControllerRegistry, OpcUaConfig, OpcUaServer};
+///
+/// let mut registry = ControllerRegistry::new();
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// registry.register_config("oven", config);
+///
+/// let opcua = OpcUaConfig::new("0.0.0.0", 4840)
+///     .with_user("scada", "hunter2", AccessRole::Viewer)
+///     .with_user("shift-lead", "correct horse", AccessRole::Operator);
+/// let server = OpcUaServer::start(&registry, opcua).unwrap();
+/// // ... run the loops ...
+/// server.stop();
+/// ```
+pub struct OpcUaServer {
+    handle: ServerHandle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OpcUaServer {
+    /// Builds the address space for the loops in `registry` and starts
+    /// serving it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runtime cannot be created or the server
+    /// cannot be built, for example because its certificate cannot be
+    /// created in the PKI directory.
+    pub fn start(registry: &ControllerRegistry, config: OpcUaConfig) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("pidgeon-opcua")
+            .enable_all()
+            .build()?;
+
+        let roles = Arc::new(Roles::new(&config));
+        let authenticator = RoleAuthenticator {
+            roles: Arc::clone(&roles),
+        };
+        let registry = registry.clone();
+        let loops = InMemoryNodeManagerBuilder::new(
+            move |context: ServerContext, address_space: &mut AddressSpace| {
+                LoopNodeManager::build(registry, roles, &context, address_space)
+            },
+        );
+        // Node managers start background samplers while the server is built
+        let _runtime = runtime.enter();
+        let (server, handle) = ServerBuilder::new()
+            .application_name("pidgeon")
+            .application_uri("urn:pidgeon")
+            .product_uri("urn:pidgeon")
+            .host(config.host)
+            .port(config.port)
+            .pki_dir(config.pki_dir)
+            .create_sample_keypair(true)
+            .with_authenticator(Arc::new(authenticator))
+            .discovery_urls(vec!["/".to_string()])
+            // Logins are offered by `RoleAuthenticator::user_token_policies`,
+            // not by user token IDs in the server config
+            .add_endpoint("none", ServerEndpoint::new_none("/", &[]))
+            .with_node_manager(loops)
+            .build()
+            .map_err(io::Error::other)?;
+        if handle
+            .node_managers()
+            .get_of_type::<InMemoryNodeManager<LoopNodeManager>>()
+            .is_none()
+        {
+            return Err(io::Error::other("OPC UA node manager missing"));
+        }
+
+        drop(_runtime);
+        let thread = thread::Builder::new()
+            .name("pidgeon-opcua".to_string())
+            .spawn(move || {
+                if let Err(e) = runtime.block_on(server.run()) {
+                    log::error!("OPC UA server stopped: {}", e);
+                }
+            })?;
+
+        Ok(OpcUaServer {
+            handle,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stops serving and waits for the server thread to finish.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.handle.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for OpcUaServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
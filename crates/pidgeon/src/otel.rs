@@ -0,0 +1,137 @@
+use std::io;
+
+use opentelemetry::metrics::{Counter, Gauge, Histogram, MeterProvider};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{MetricExporter, WithExportConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::Resource;
+
+use crate::debug::ControllerDebugData;
+use crate::sinks::DebugSink;
+use crate::time::Duration;
+
+/// Records debug samples as OpenTelemetry metrics.
+///
+/// Every sample updates the following instruments, each carrying a
+/// `controller.id` attribute so loops can be told apart in Grafana, Datadog,
+/// or any other OTLP backend:
+///
+/// | Instrument                    | Kind      | Value |
+/// |-------------------------------|-----------|-------|
+/// | `pidgeon.setpoint`            | gauge     | setpoint |
+/// | `pidgeon.process_value`       | gauge     | measurement |
+/// | `pidgeon.error`               | gauge     | setpoint minus measurement |
+/// | `pidgeon.output`              | gauge     | clamped output |
+/// | `pidgeon.p_term`, `.i_term`, `.d_term` | gauge | individual PID terms |
+/// | `pidgeon.abs_error`           | histogram | absolute error distribution |
+/// | `pidgeon.samples`             | counter   | samples recorded |
+///
+/// [`new`](Self::new) builds a dedicated OTLP/HTTP pipeline. Services that
+/// already configure OpenTelemetry should pass their own provider to
+/// [`with_provider`](Self::with_provider) instead, so the loop metrics share
+/// its resource attributes and export schedule.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pidgeon::{DebugConfig, OtelSink};
+/// use std::time::Duration;
+///
+/// let sink = OtelSink::new("http://127.0.0.1:4318/v1/metrics", "oven-service", Duration::from_secs(10))
+///     .unwrap();
+/// let debug_config = DebugConfig {
+///     controller_id: "oven".to_string(),
+///     ..Default::default()
+/// }
+/// .with_sink(sink);
+/// ```
+pub struct OtelSink {
+    provider: SdkMeterProvider,
+    setpoint: Gauge<f64>,
+    process_value: Gauge<f64>,
+    error: Gauge<f64>,
+    output: Gauge<f64>,
+    p_term: Gauge<f64>,
+    i_term: Gauge<f64>,
+    d_term: Gauge<f64>,
+    abs_error: Histogram<f64>,
+    samples: Counter<u64>,
+}
+
+impl OtelSink {
+    /// Exports to the OTLP/HTTP metrics `endpoint` every `export_interval`,
+    /// tagging the resource with `service_name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the exporter cannot be built (for example, an
+    /// invalid endpoint URL).
+    pub fn new(endpoint: &str, service_name: &str, export_interval: Duration) -> io::Result<Self> {
+        let exporter = MetricExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(io::Error::other)?;
+        let reader = PeriodicReader::builder(exporter)
+            .with_interval(export_interval)
+            .build();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(service_name.to_string())
+                    .build(),
+            )
+            .build();
+        Ok(Self::with_provider(provider))
+    }
+
+    /// Records into an existing meter provider.
+    pub fn with_provider(provider: SdkMeterProvider) -> Self {
+        let meter = provider.meter("pidgeon");
+        let gauge = |name: &'static str, description: &'static str| {
+            meter.f64_gauge(name).with_description(description).build()
+        };
+
+        OtelSink {
+            setpoint: gauge("pidgeon.setpoint", "Controller setpoint"),
+            process_value: gauge("pidgeon.process_value", "Measured process value"),
+            error: gauge("pidgeon.error", "Setpoint minus process value"),
+            output: gauge("pidgeon.output", "Clamped controller output"),
+            p_term: gauge("pidgeon.p_term", "Proportional contribution"),
+            i_term: gauge("pidgeon.i_term", "Integral contribution"),
+            d_term: gauge("pidgeon.d_term", "Derivative contribution"),
+            abs_error: meter
+                .f64_histogram("pidgeon.abs_error")
+                .with_description("Absolute control error")
+                .build(),
+            samples: meter
+                .u64_counter("pidgeon.samples")
+                .with_description("Debug samples recorded")
+                .build(),
+            provider,
+        }
+    }
+
+    /// The provider backing this sink, e.g. to force an export.
+    pub fn meter_provider(&self) -> &SdkMeterProvider {
+        &self.provider
+    }
+}
+
+impl DebugSink for OtelSink {
+    fn write(&mut self, data: &ControllerDebugData) -> io::Result<()> {
+        let attributes = [KeyValue::new("controller.id", data.controller_id.clone())];
+
+        self.setpoint.record(data.setpoint, &attributes);
+        self.process_value.record(data.process_value, &attributes);
+        self.error.record(data.error, &attributes);
+        self.output.record(data.output, &attributes);
+        self.p_term.record(data.p_term, &attributes);
+        self.i_term.record(data.i_term, &attributes);
+        self.d_term.record(data.d_term, &attributes);
+        self.abs_error.record(data.error.abs(), &attributes);
+        self.samples.add(1, &attributes);
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::PidError;
+use crate::pipeline::{LowPassFilter, SignalStage};
+
+/// Averages several raw readings into one process value per control period.
+///
+/// A converter sampling much faster than the loop runs sees noise the loop
+/// cannot use. Reading it once per period aliases that noise down to
+/// frequencies the derivative term amplifies; averaging every reading taken
+/// during the period instead cuts white noise by the square root of their
+/// number and notches out anything periodic in the block length. For noise
+/// concentrated near the converter's own Nyquist frequency, an anti-alias
+/// low-pass can run on every raw reading before it is averaged.
+///
+/// Readings go in through [`push`](Self::push), typically from an ADC
+/// interrupt, and the control step collects their mean with
+/// [`take`](Self::take); a `HalLoop` with
+/// `with_oversampling` does both itself. Where the readings arrive as a
+/// stream instead, such as DMA buffers, [`decimate`](Self::decimate) hands
+/// back one mean per [`samples`](Self::samples) readings. Non-finite
+/// readings are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::Oversampler;
+///
+/// // 8 readings per control period
+/// let mut oversampler = Oversampler::new(8).unwrap();
+/// let means: Vec<f64> = [20.0, 22.0, 19.0, 21.0, 20.5, 19.5, 20.0, 18.0]
+///     .into_iter()
+///     .filter_map(|raw| oversampler.decimate(raw))
+///     .collect();
+/// assert_eq!(means, [20.0]);
+///
+/// // Period-driven: the step takes whatever arrived
+/// oversampler.push(30.0);
+/// oversampler.push(31.0);
+/// assert_eq!(oversampler.take(), Some(30.5));
+/// assert_eq!(oversampler.take(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Oversampler {
+    samples: usize,
+    sum: f64,
+    count: usize,
+    /// Low-pass run on each reading, and the interval between readings
+    anti_alias: Option<(LowPassFilter, f64)>,
+}
+
+impl Oversampler {
+    /// Creates an oversampler averaging `samples` readings per period.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `samples` is zero.
+    pub fn new(samples: usize) -> Result<Self, PidError> {
+        if samples == 0 {
+            return Err(PidError::InvalidParameter(
+                "oversampling needs at least one sample per period",
+            ));
+        }
+        Ok(Oversampler {
+            samples,
+            sum: 0.0,
+            count: 0,
+            anti_alias: None,
+        })
+    }
+
+    /// Runs every reading through a first-order low-pass with
+    /// `time_constant` seconds before averaging, for readings taken
+    /// `sample_interval` seconds apart. A time constant of about a quarter
+    /// of the control period leaves the loop's bandwidth alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if either value is non-finite
+    /// or non-positive.
+    pub fn with_anti_alias(
+        mut self,
+        time_constant: f64,
+        sample_interval: f64,
+    ) -> Result<Self, PidError> {
+        if !sample_interval.is_finite() || sample_interval <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "oversampling sample_interval must be a finite positive number",
+            ));
+        }
+        self.anti_alias = Some((LowPassFilter::new(time_constant)?, sample_interval));
+        Ok(self)
+    }
+
+    /// Readings averaged per period.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Readings collected since the last [`take`](Self::take).
+    pub fn pending(&self) -> usize {
+        self.count
+    }
+
+    /// Adds one reading to the current period's average.
+    pub fn push(&mut self, raw: f64) {
+        if !raw.is_finite() {
+            return;
+        }
+        let value = match &mut self.anti_alias {
+            Some((filter, interval)) => filter.process(raw, *interval),
+            None => raw,
+        };
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Adds one reading, and returns the mean once
+    /// [`samples`](Self::samples) readings have been collected, starting
+    /// the next block.
+    pub fn decimate(&mut self, raw: f64) -> Option<f64> {
+        self.push(raw);
+        if self.count >= self.samples {
+            self.take()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mean of the readings collected so far, or `None` if there
+    /// are none, and starts a new block.
+    pub fn take(&mut self) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let mean = self.sum / self.count as f64;
+        self.sum = 0.0;
+        self.count = 0;
+        Some(mean)
+    }
+
+    /// Discards collected readings and the anti-alias filter's history.
+    pub fn reset(&mut self) {
+        self.sum = 0.0;
+        self.count = 0;
+        if let Some((filter, _)) = &mut self.anti_alias {
+            filter.reset();
+        }
+    }
+}
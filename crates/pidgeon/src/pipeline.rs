@@ -0,0 +1,412 @@
+//! Signal stages and the combinators that chain them around a controller.
+//!
+//! A loop body usually filters the measurement, runs the controller, then
+//! limits and quantizes the output before it reaches the actuator.
+//! [`Controller::filter_input`] and [`Controller::shape_output`] declare that
+//! path once; the result is itself a [`Controller`], so it drops into any
+//! code that runs one:
+//!
+//! ```
+//! use pidgeon::{
+//!     Controller, ControllerConfig, Limiter, LowPassFilter, PidController, Quantizer,
+//!     RateLimiter, SignalStage,
+//! };
+//!
+//! let config = ControllerConfig::builder()
+//!     .with_kp(3.0)
+//!     .with_ki(0.2)
+//!     .with_setpoint(60.0)
+//!     .with_output_limits(-100.0, 100.0)
+//!     .build()
+//!     .unwrap();
+//! let mut pump = PidController::new(config)
+//!     .filter_input(LowPassFilter::new(2.0).unwrap())
+//!     .shape_output(
+//!         Limiter::new(0.0, 100.0)
+//!             .unwrap()
+//!             .then(RateLimiter::new(20.0).unwrap())
+//!             .then(Quantizer::new(0.5).unwrap()),
+//!     );
+//!
+//! let speed = pump.compute(52.3, 0.1).unwrap();
+//! assert_eq!(speed % 0.5, 0.0);
+//! ```
+
+use crate::error::PidError;
+use crate::traits::Controller;
+
+/// One step of signal processing: a filter, limiter, quantizer, or anything
+/// else that turns a value into a value, sample by sample.
+///
+/// Closures taking `(value, dt)` are stages too.
+pub trait SignalStage {
+    /// Processes `value`, taken `dt` seconds after the previous one. `dt`
+    /// is finite and positive, and `value` finite.
+    fn process(&mut self, value: f64, dt: f64) -> f64;
+
+    /// Forgets any history, as on a controller reset.
+    fn reset(&mut self) {}
+
+    /// Runs `next` on this stage's result.
+    fn then<S: SignalStage>(self, next: S) -> Chain<Self, S>
+    where
+        Self: Sized,
+    {
+        Chain { first: self, next }
+    }
+}
+
+impl<F: FnMut(f64, f64) -> f64> SignalStage for F {
+    fn process(&mut self, value: f64, dt: f64) -> f64 {
+        self(value, dt)
+    }
+}
+
+/// Two stages one after the other, built by [`SignalStage::then`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A: SignalStage, B: SignalStage> SignalStage for Chain<A, B> {
+    fn process(&mut self, value: f64, dt: f64) -> f64 {
+        let value = self.first.process(value, dt);
+        self.next.process(value, dt)
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.next.reset();
+    }
+}
+
+/// First-order low-pass filter with a time constant in seconds. Starts at
+/// the first value it sees, so it adds no start-up transient.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LowPassFilter {
+    time_constant: f64,
+    value: Option<f64>,
+}
+
+impl LowPassFilter {
+    /// Creates a filter with `time_constant` seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `time_constant` is
+    /// non-finite or non-positive.
+    pub fn new(time_constant: f64) -> Result<Self, PidError> {
+        if !time_constant.is_finite() || time_constant <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "low-pass time_constant must be a finite positive number",
+            ));
+        }
+        Ok(Self {
+            time_constant,
+            value: None,
+        })
+    }
+
+    /// Time constant in seconds.
+    pub fn time_constant(&self) -> f64 {
+        self.time_constant
+    }
+}
+
+impl SignalStage for LowPassFilter {
+    fn process(&mut self, value: f64, dt: f64) -> f64 {
+        let filtered = match self.value {
+            None => value,
+            Some(prev) => prev + (value - prev) * dt / (self.time_constant + dt),
+        };
+        self.value = Some(filtered);
+        filtered
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// Clamps a value to `[min, max]`.
+///
+/// After a controller, it bounds what reaches the actuator without the
+/// controller knowing; prefer the config's output limits, which also stop
+/// the integral winding up, and use this for a tighter bound further down
+/// the path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Limiter {
+    min: f64,
+    max: f64,
+}
+
+impl Limiter {
+    /// Creates a limiter over `[min, max]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if either bound is non-finite
+    /// or `min >= max`.
+    pub fn new(min: f64, max: f64) -> Result<Self, PidError> {
+        if !min.is_finite() || !max.is_finite() || min >= max {
+            return Err(PidError::InvalidParameter(
+                "limiter bounds must be finite with min below max",
+            ));
+        }
+        Ok(Self { min, max })
+    }
+
+    /// Lower bound.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Upper bound.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+}
+
+impl SignalStage for Limiter {
+    fn process(&mut self, value: f64, _dt: f64) -> f64 {
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// Limits how fast a value may change, in units per second. The first
+/// value passes straight through.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RateLimiter {
+    max_rate: f64,
+    value: Option<f64>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `max_rate` units per second either way.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `max_rate` is non-finite
+    /// or non-positive.
+    pub fn new(max_rate: f64) -> Result<Self, PidError> {
+        if !max_rate.is_finite() || max_rate <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "max_rate must be a finite positive number",
+            ));
+        }
+        Ok(Self {
+            max_rate,
+            value: None,
+        })
+    }
+
+    /// Largest change per second.
+    pub fn max_rate(&self) -> f64 {
+        self.max_rate
+    }
+}
+
+impl SignalStage for RateLimiter {
+    fn process(&mut self, value: f64, dt: f64) -> f64 {
+        let limited = match self.value {
+            None => value,
+            Some(prev) => {
+                let step = self.max_rate * dt;
+                value.clamp(prev - step, prev + step)
+            }
+        };
+        self.value = Some(limited);
+        limited
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// Rounds a value to the nearest multiple of `step`, such as one DAC count
+/// or a valve positioner's resolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Quantizer {
+    step: f64,
+}
+
+impl Quantizer {
+    /// Creates a quantizer with resolution `step`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `step` is non-finite or
+    /// non-positive.
+    pub fn new(step: f64) -> Result<Self, PidError> {
+        if !step.is_finite() || step <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "quantizer step must be a finite positive number",
+            ));
+        }
+        Ok(Self { step })
+    }
+
+    /// Resolution.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+}
+
+impl SignalStage for Quantizer {
+    fn process(&mut self, value: f64, _dt: f64) -> f64 {
+        // `f64::round` needs std; ties go away from zero either way
+        let steps = value / self.step;
+        let whole = steps as i64 as f64;
+        let fraction = steps - whole;
+        let rounded = if fraction >= 0.5 {
+            whole + 1.0
+        } else if fraction <= -0.5 {
+            whole - 1.0
+        } else {
+            whole
+        };
+        rounded * self.step
+    }
+}
+
+/// A controller whose process value passes through a stage first, built by
+/// [`Controller::filter_input`].
+///
+/// Only the measurement is filtered; the setpoint goes to the controller
+/// unchanged.
+#[derive(Debug, Clone)]
+pub struct FilteredInput<C, S> {
+    controller: C,
+    stage: S,
+}
+
+impl<C, S> FilteredInput<C, S> {
+    pub(crate) fn new(controller: C, stage: S) -> Self {
+        Self { controller, stage }
+    }
+
+    /// The wrapped controller.
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    /// The wrapped controller, mutably.
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    /// The input stage.
+    pub fn stage(&self) -> &S {
+        &self.stage
+    }
+
+    /// Unwraps the controller and stage.
+    pub fn into_parts(self) -> (C, S) {
+        (self.controller, self.stage)
+    }
+}
+
+impl<C: Controller, S: SignalStage> Controller for FilteredInput<C, S> {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        // Check here so a bad sample never reaches the stage's history
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+        let filtered = self.stage.process(process_value, dt);
+        self.controller.compute(filtered, dt)
+    }
+
+    fn setpoint(&self) -> f64 {
+        self.controller.setpoint()
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        self.controller.set_setpoint(setpoint)
+    }
+
+    fn reset(&mut self) {
+        self.stage.reset();
+        self.controller.reset();
+    }
+
+    #[cfg(feature = "std")]
+    fn statistics(&self) -> Option<crate::ControllerStatistics> {
+        self.controller.statistics()
+    }
+}
+
+/// A controller whose output passes through a stage before it is returned,
+/// built by [`Controller::shape_output`].
+#[derive(Debug, Clone)]
+pub struct ShapedOutput<C, S> {
+    controller: C,
+    stage: S,
+}
+
+impl<C, S> ShapedOutput<C, S> {
+    pub(crate) fn new(controller: C, stage: S) -> Self {
+        Self { controller, stage }
+    }
+
+    /// The wrapped controller.
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    /// The wrapped controller, mutably.
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    /// The output stage.
+    pub fn stage(&self) -> &S {
+        &self.stage
+    }
+
+    /// Unwraps the controller and stage.
+    pub fn into_parts(self) -> (C, S) {
+        (self.controller, self.stage)
+    }
+}
+
+impl<C: Controller, S: SignalStage> Controller for ShapedOutput<C, S> {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        let output = self.controller.compute(process_value, dt)?;
+        Ok(self.stage.process(output, dt))
+    }
+
+    fn setpoint(&self) -> f64 {
+        self.controller.setpoint()
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        self.controller.set_setpoint(setpoint)
+    }
+
+    fn reset(&mut self) {
+        self.stage.reset();
+        self.controller.reset();
+    }
+
+    #[cfg(feature = "std")]
+    fn statistics(&self) -> Option<crate::ControllerStatistics> {
+        self.controller.statistics()
+    }
+}
@@ -0,0 +1,537 @@
+//! Simulated processes to close a loop around in examples, demos, and tests.
+//!
+//! Each plant also reports the disturbances acting on it as a sensor on the
+//! real process would, with a matching [`Feedforward`] to compensate them.
+//!
+//! Anything random in a simulation, such as sensor noise or wind gusts, draws
+//! from a [`SimRng`] seeded by the scenario, so a run can be repeated exactly.
+
+use crate::error::PidError;
+use crate::feedforward::Feedforward;
+
+/// A heated room: air, the walls and furniture it exchanges heat with, and a
+/// radiator that takes a few minutes to warm up.
+///
+/// The air responds in minutes and the walls in hours, and heat reaches the
+/// air only through the radiator's lag. That is enough to show the things a
+/// thermostat fights in practice: overshoot when the integral term is too
+/// eager, a slow tail as the walls catch up, and integral wind-up while the
+/// heater sits at a limit.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidController, ThermalRoomPlant};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(20.0)
+///     .with_ki(0.02)
+///     .with_output_limits(0.0, 100.0)
+///     .with_setpoint(21.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = PidController::new(config);
+/// let mut room = ThermalRoomPlant::new(16.0, 5.0);
+///
+/// for _ in 0..4 * 3600 {
+///     let output = controller.compute(room.temperature(), 1.0).unwrap();
+///     room.step(output, 1.0);
+/// }
+/// assert!((room.temperature() - 21.0).abs() < 0.2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalRoomPlant {
+    air: f64,
+    walls: f64,
+    /// Watts the radiator is currently giving off
+    radiator: f64,
+    outdoor: f64,
+    window_open: bool,
+    heater_power: f64,
+    /// Watts released straight into the air by people and appliances
+    heat_load: f64,
+}
+
+impl ThermalRoomPlant {
+    /// Heat capacity of the air and light furniture, J/K.
+    pub const AIR_CAPACITY: f64 = 300e3;
+    /// Heat capacity of the walls, floor, and heavy furniture, J/K.
+    pub const WALL_CAPACITY: f64 = 4e6;
+    /// Conductance between the air and the walls, W/K.
+    pub const AIR_TO_WALLS: f64 = 250.0;
+    /// Conductance from the walls to outdoors, W/K.
+    pub const WALLS_TO_OUTDOOR: f64 = 60.0;
+    /// Conductance from the air to outdoors through gaps and ventilation, W/K.
+    pub const INFILTRATION: f64 = 15.0;
+    /// Extra air-to-outdoor conductance while the window is open, W/K.
+    pub const OPEN_WINDOW: f64 = 200.0;
+    /// Seconds for the radiator to reach 63% of a change in heater output.
+    pub const RADIATOR_LAG: f64 = 180.0;
+    /// Rated heater power unless changed with [`with_heater_power`](Self::with_heater_power), W.
+    pub const DEFAULT_HEATER_POWER: f64 = 3000.0;
+
+    /// Longest step integrated at once; longer steps are split.
+    const MAX_SUBSTEP: f64 = 5.0;
+
+    /// Creates a room whose air and walls both start at `temperature`, with
+    /// the window shut and the heater off.
+    pub fn new(temperature: f64, outdoor_temperature: f64) -> Self {
+        Self {
+            air: temperature,
+            walls: temperature,
+            radiator: 0.0,
+            outdoor: outdoor_temperature,
+            window_open: false,
+            heater_power: Self::DEFAULT_HEATER_POWER,
+            heat_load: 0.0,
+        }
+    }
+
+    /// Sets the heater's rated power in watts. Negative values are treated
+    /// as zero.
+    pub fn with_heater_power(mut self, watts: f64) -> Self {
+        self.heater_power = watts.max(0.0);
+        self
+    }
+
+    /// Rated heater power, W.
+    pub fn heater_power(&self) -> f64 {
+        self.heater_power
+    }
+
+    /// Runs the heater at `output` percent of its rated power for `dt`
+    /// seconds and returns the new air temperature.
+    ///
+    /// `output` is clamped to 0–100. A non-positive or non-finite `dt`, or a
+    /// non-finite `output`, leaves the room unchanged.
+    pub fn step(&mut self, output: f64, dt: f64) -> f64 {
+        if !dt.is_finite() || dt <= 0.0 || !output.is_finite() {
+            return self.air;
+        }
+        let demand = output.clamp(0.0, 100.0) / 100.0 * self.heater_power;
+
+        let substeps = (dt / Self::MAX_SUBSTEP).ceil();
+        let h = dt / substeps;
+        let radiator_blend = 1.0 - (-h / Self::RADIATOR_LAG).exp();
+        let air_loss = if self.window_open {
+            Self::INFILTRATION + Self::OPEN_WINDOW
+        } else {
+            Self::INFILTRATION
+        };
+
+        for _ in 0..substeps as usize {
+            self.radiator += (demand - self.radiator) * radiator_blend;
+            let to_walls = Self::AIR_TO_WALLS * (self.air - self.walls);
+            let air_flow =
+                self.radiator + self.heat_load - to_walls - air_loss * (self.air - self.outdoor);
+            let wall_flow = to_walls - Self::WALLS_TO_OUTDOOR * (self.walls - self.outdoor);
+            self.air += air_flow / Self::AIR_CAPACITY * h;
+            self.walls += wall_flow / Self::WALL_CAPACITY * h;
+        }
+        self.air
+    }
+
+    /// Air temperature, the value a thermostat measures.
+    pub fn temperature(&self) -> f64 {
+        self.air
+    }
+
+    /// Temperature of the walls and heavy furniture.
+    pub fn wall_temperature(&self) -> f64 {
+        self.walls
+    }
+
+    /// Watts the radiator is currently giving off, which trails the heater
+    /// output by [`RADIATOR_LAG`](Self::RADIATOR_LAG).
+    pub fn radiator_power(&self) -> f64 {
+        self.radiator
+    }
+
+    /// Outdoor temperature.
+    pub fn outdoor_temperature(&self) -> f64 {
+        self.outdoor
+    }
+
+    /// Changes the outdoor temperature, e.g. for a heat wave or a cold snap.
+    pub fn set_outdoor_temperature(&mut self, temperature: f64) {
+        self.outdoor = temperature;
+    }
+
+    /// Whether the window is open.
+    pub fn window_open(&self) -> bool {
+        self.window_open
+    }
+
+    /// Opens or shuts the window.
+    pub fn set_window_open(&mut self, open: bool) {
+        self.window_open = open;
+    }
+
+    /// Internal heat load in watts: occupants, cooking, computers.
+    ///
+    /// This is the room's measured disturbance. A building would estimate it
+    /// from occupancy sensors or metered plug loads; feeding it to the
+    /// controller through [`heat_load_feedforward`](Self::heat_load_feedforward)
+    /// backs the heater off as the heat arrives instead of after the room
+    /// has overheated.
+    pub fn heat_load(&self) -> f64 {
+        self.heat_load
+    }
+
+    /// Changes the internal heat load, e.g. when a meeting starts. Negative
+    /// or non-finite values are treated as zero.
+    pub fn set_heat_load(&mut self, watts: f64) {
+        self.heat_load = if watts.is_finite() {
+            watts.max(0.0)
+        } else {
+            0.0
+        };
+    }
+
+    /// Compensator turning [`heat_load`](Self::heat_load) into heater output.
+    ///
+    /// The load reaches the air directly while the heater acts through the
+    /// radiator, so the compensator leads by
+    /// [`RADIATOR_LAG`](Self::RADIATOR_LAG) and settles at one percent less
+    /// output for every percent of the heater's power the load supplies.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the heater has no power.
+    pub fn heat_load_feedforward(&self) -> Result<Feedforward, PidError> {
+        if self.heater_power <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "heat load feedforward needs a heater with positive power",
+            ));
+        }
+        Feedforward::new(-100.0 / self.heater_power, Self::RADIATOR_LAG, 0.0)
+    }
+
+    /// Heater output in percent that holds the room at `temperature` once
+    /// the walls have settled, ignoring the 0–100 limit.
+    pub fn holding_output(&self, temperature: f64) -> f64 {
+        let walls_path = 1.0 / (1.0 / Self::AIR_TO_WALLS + 1.0 / Self::WALLS_TO_OUTDOOR);
+        let air_path = if self.window_open {
+            Self::INFILTRATION + Self::OPEN_WINDOW
+        } else {
+            Self::INFILTRATION
+        };
+        let watts = (walls_path + air_path) * (temperature - self.outdoor) - self.heat_load;
+        watts / self.heater_power * 100.0
+    }
+}
+
+/// A quadcopter holding altitude: motors that spin up in a tenth of a
+/// second, gravity, and quadratic air drag against a vertical wind.
+///
+/// The controller output is collective thrust in percent of the motors'
+/// combined [`MAX_THRUST`](Self::MAX_THRUST). Updrafts and downdrafts push
+/// the drone through drag on its speed relative to the air; an onboard
+/// wind estimate reports the load they put on it, so a [`Feedforward`]
+/// can trim the thrust before the altitude moves.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, DroneAltitudePlant, PidController};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(10.0)
+///     .with_ki(5.0)
+///     .with_kd(8.0)
+///     .with_output_limits(0.0, 100.0)
+///     .with_setpoint(10.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = PidController::new(config);
+/// let mut drone = DroneAltitudePlant::new(0.0);
+///
+/// for _ in 0..30 * 20 {
+///     let output = controller.compute(drone.altitude(), 0.05).unwrap();
+///     drone.step(output, 0.05);
+/// }
+/// assert!((drone.altitude() - 10.0).abs() < 0.1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroneAltitudePlant {
+    altitude: f64,
+    velocity: f64,
+    /// Thrust the motors are delivering, percent
+    thrust: f64,
+    /// Vertical wind, m/s, positive upward
+    wind: f64,
+    mass: f64,
+}
+
+impl DroneAltitudePlant {
+    /// Gravitational acceleration, m/s².
+    pub const GRAVITY: f64 = 9.81;
+    /// Combined thrust of the motors at 100% output, N.
+    pub const MAX_THRUST: f64 = 30.0;
+    /// Quadratic drag coefficient, N per (m/s)².
+    pub const DRAG: f64 = 0.3;
+    /// Seconds for the motors to reach 63% of a change in output.
+    pub const MOTOR_LAG: f64 = 0.1;
+    /// Take-off mass unless changed with [`with_mass`](Self::with_mass), kg.
+    pub const DEFAULT_MASS: f64 = 1.2;
+    /// Longest step integrated at once; longer steps are split.
+    const MAX_SUBSTEP: f64 = 0.05;
+
+    /// Creates a drone at rest at `altitude` metres, motors off, in still air.
+    pub fn new(altitude: f64) -> Self {
+        Self {
+            altitude: altitude.max(0.0),
+            velocity: 0.0,
+            thrust: 0.0,
+            wind: 0.0,
+            mass: Self::DEFAULT_MASS,
+        }
+    }
+
+    /// Sets the take-off mass in kilograms, e.g. to add a payload.
+    /// Non-positive or non-finite values are ignored.
+    pub fn with_mass(mut self, kg: f64) -> Self {
+        if kg.is_finite() && kg > 0.0 {
+            self.mass = kg;
+        }
+        self
+    }
+
+    /// Runs the motors at `output` percent for `dt` seconds and returns the
+    /// new altitude. The drone cannot sink below the ground.
+    ///
+    /// `output` is clamped to 0–100. A non-positive or non-finite `dt`, or a
+    /// non-finite `output`, leaves the drone unchanged.
+    pub fn step(&mut self, output: f64, dt: f64) -> f64 {
+        if !dt.is_finite() || dt <= 0.0 || !output.is_finite() {
+            return self.altitude;
+        }
+        let output = output.clamp(0.0, 100.0);
+
+        let substeps = (dt / Self::MAX_SUBSTEP).ceil();
+        let h = dt / substeps;
+        for _ in 0..substeps as usize {
+            self.thrust += (output - self.thrust) * h / Self::MOTOR_LAG;
+            let airspeed = self.velocity - self.wind;
+            let drag = Self::DRAG * airspeed.abs() * airspeed;
+            let force = self.thrust / 100.0 * Self::MAX_THRUST - self.mass * Self::GRAVITY - drag;
+            self.velocity += force / self.mass * h;
+            self.altitude += self.velocity * h;
+            if self.altitude < 0.0 {
+                self.altitude = 0.0;
+                self.velocity = 0.0;
+            }
+        }
+        self.altitude
+    }
+
+    /// Altitude above the ground, m, the value the controller measures.
+    pub fn altitude(&self) -> f64 {
+        self.altitude
+    }
+
+    /// Vertical speed, m/s, positive upward.
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Thrust the motors are delivering, in percent; it trails the output
+    /// by [`MOTOR_LAG`](Self::MOTOR_LAG).
+    pub fn thrust(&self) -> f64 {
+        self.thrust
+    }
+
+    /// Take-off mass, kg.
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+
+    /// Changes the vertical speed at once, the way a sudden gust would.
+    pub fn push(&mut self, velocity_change: f64) {
+        if velocity_change.is_finite() {
+            self.velocity += velocity_change;
+        }
+    }
+
+    /// Vertical wind, m/s, positive for an updraft.
+    pub fn wind(&self) -> f64 {
+        self.wind
+    }
+
+    /// Changes the vertical wind. Non-finite values are treated as still air.
+    pub fn set_wind(&mut self, speed: f64) {
+        self.wind = if speed.is_finite() { speed } else { 0.0 };
+    }
+
+    /// The drone's measured disturbance: the upward force, in newtons, the
+    /// wind puts on it while hovering.
+    ///
+    /// Flight controllers estimate this from the gap between the thrust
+    /// they command and the acceleration they see. Feeding it through
+    /// [`wind_feedforward`](Self::wind_feedforward) lets the controller trim
+    /// the thrust while the altitude is still on target.
+    pub fn wind_load(&self) -> f64 {
+        Self::DRAG * self.wind.abs() * self.wind
+    }
+
+    /// Compensator turning [`wind_load`](Self::wind_load) into thrust.
+    ///
+    /// The load acts on the airframe at once while thrust arrives through
+    /// the motors, so the compensator leads by
+    /// [`MOTOR_LAG`](Self::MOTOR_LAG) and settles at the output that
+    /// cancels the load.
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, DroneAltitudePlant, PidController};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(10.0)
+    ///     .with_ki(5.0)
+    ///     .with_kd(8.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .with_setpoint(0.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut drone = DroneAltitudePlant::new(0.0);
+    /// let mut controller =
+    ///     PidController::new(config).with_feedforward(drone.wind_feedforward());
+    ///
+    /// // A downdraft pressing on a drone resting on the pad
+    /// drone.set_wind(-2.0);
+    /// controller.set_disturbance(drone.wind_load()).unwrap();
+    /// let output = controller.compute(drone.altitude(), 0.05).unwrap();
+    /// assert!((output - 4.0).abs() < 1e-9);
+    /// ```
+    pub fn wind_feedforward(&self) -> Feedforward {
+        Feedforward::new(-100.0 / Self::MAX_THRUST, Self::MOTOR_LAG, 0.0)
+            .expect("drone constants make a valid compensator")
+    }
+
+    /// Output in percent that holds the drone still in the current wind.
+    pub fn hover_output(&self) -> f64 {
+        (self.mass * Self::GRAVITY - self.wind_load()) / Self::MAX_THRUST * 100.0
+    }
+}
+
+/// A small, seedable random number generator for simulations.
+///
+/// SplitMix64: fast, statistically sound for noise and disturbances, and the
+/// same sequence on every platform for a given seed, which is what makes a
+/// noisy run reproducible. It is not meant for anything security-related.
+///
+/// ```
+/// use pidgeon::SimRng;
+///
+/// let mut a = SimRng::new(42);
+/// let mut b = SimRng::new(42);
+/// let noise: Vec<f64> = (0..5).map(|_| a.gaussian()).collect();
+/// assert_eq!(noise, (0..5).map(|_| b.gaussian()).collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    /// Creates a generator whose sequence is fixed by `seed`.
+    pub fn new(seed: u64) -> Self {
+        SimRng { state: seed }
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform sample in `[0, 1)`.
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform sample in `[low, high)`.
+    pub fn range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.uniform()
+    }
+
+    /// Standard normal sample, by the Box-Muller transform.
+    pub fn gaussian(&mut self) -> f64 {
+        let u1 = 1.0 - self.uniform();
+        let u2 = self.uniform();
+        (-2.0 * u1.ln()).sqrt() * (core::f64::consts::TAU * u2).cos()
+    }
+
+    /// Gaussian noise with standard deviation `std_dev`.
+    pub fn noise(&mut self, std_dev: f64) -> f64 {
+        std_dev * self.gaussian()
+    }
+}
+
+/// Random wind gusts, or any other disturbance that arrives as sudden kicks
+/// at irregular times.
+///
+/// Gusts arrive as a Poisson process averaging one per `mean_interval`
+/// seconds, each up or down with equal odds and between half and one and a
+/// half times `strength`. Timing and size come from the [`SimRng`] passed to
+/// [`step`](Self::step), so the same seed gives the same weather.
+///
+/// ```
+/// use pidgeon::{GustGenerator, SimRng};
+///
+/// let mut rng = SimRng::new(7);
+/// let mut gusts = GustGenerator::new(5.0, 4.0).unwrap();
+/// let kicks: Vec<f64> = (0..1200).filter_map(|_| gusts.step(&mut rng, 0.05)).collect();
+/// assert!(!kicks.is_empty());
+/// assert!(kicks.iter().all(|kick| (2.0..6.0).contains(&kick.abs())));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GustGenerator {
+    mean_interval: f64,
+    strength: f64,
+}
+
+impl GustGenerator {
+    /// Creates a generator averaging one gust of about `strength` every
+    /// `mean_interval` seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `mean_interval` is not a
+    /// finite positive number or `strength` is negative or non-finite.
+    pub fn new(mean_interval: f64, strength: f64) -> Result<Self, PidError> {
+        if !mean_interval.is_finite() || mean_interval <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "gust mean_interval must be a finite positive number",
+            ));
+        }
+        if !strength.is_finite() || strength < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "gust strength must be a finite non-negative number",
+            ));
+        }
+        Ok(GustGenerator {
+            mean_interval,
+            strength,
+        })
+    }
+
+    /// Average seconds between gusts.
+    pub fn mean_interval(&self) -> f64 {
+        self.mean_interval
+    }
+
+    /// Typical gust size.
+    pub fn strength(&self) -> f64 {
+        self.strength
+    }
+
+    /// Advances `dt` seconds and returns the gust that arrived in that time,
+    /// if any.
+    pub fn step(&mut self, rng: &mut SimRng, dt: f64) -> Option<f64> {
+        let probability = 1.0 - (-dt.max(0.0) / self.mean_interval).exp();
+        if rng.uniform() >= probability {
+            return None;
+        }
+        let size = self.strength * rng.range(0.5, 1.5);
+        Some(if rng.uniform() < 0.5 { size } else { -size })
+    }
+}
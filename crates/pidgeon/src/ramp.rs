@@ -0,0 +1,299 @@
+use crate::config::ControllerConfig;
+use crate::controller::{ControllerStatistics, PidController};
+use crate::error::PidError;
+use crate::pipeline::{LowPassFilter, SignalStage};
+use crate::traits::Controller;
+
+/// What a [`RampController`] is controlling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RampMode {
+    /// Holding the process value at the absolute setpoint.
+    Absolute,
+    /// Driving the process value's rate of change to `rate` units per
+    /// second until it reaches `end_value`.
+    Ramping {
+        /// Target rate, signed towards `end_value`.
+        rate: f64,
+        /// Value at which control hands back to the absolute loop.
+        end_value: f64,
+    },
+}
+
+/// Controls how fast the process value moves rather than where it is, then
+/// hands back to ordinary setpoint control at the end of the move, as when
+/// a furnace must be heated at no more than 5 °C/min to its soak
+/// temperature.
+///
+/// It runs two PID loops over one output. The rate loop's process value is
+/// the measured rate, estimated as a low-pass filtered difference of
+/// successive samples, and its setpoint the target rate. The absolute loop
+/// holds the setpoint as usual. Whichever loop is not driving tracks the
+/// output (see [`PidController::track`]), so switching between them never
+/// bumps it: starting a ramp begins from the current output, and reaching
+/// the end value, or passing it, hands over to the absolute loop with the
+/// end value as its setpoint.
+///
+/// The rate loop needs its own tuning: its process gain is the output's
+/// effect on the rate, typically much smaller than on the value, and it
+/// needs integral action to keep up as the load changes along the ramp.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, RampController, RampMode};
+///
+/// let absolute = ControllerConfig::builder()
+///     .with_kp(4.0)
+///     .with_ki(0.02)
+///     .with_setpoint(20.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let rate = ControllerConfig::builder()
+///     .with_kp(200.0)
+///     .with_ki(10.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut furnace = RampController::new(absolute, rate, 10.0).unwrap();
+///
+/// // 5 °C/min up to an 850 °C soak
+/// furnace.start_ramp(5.0 / 60.0, 850.0, 20.0).unwrap();
+/// assert!(matches!(furnace.mode(), RampMode::Ramping { .. }));
+/// ```
+pub struct RampController {
+    absolute: PidController,
+    rate: PidController,
+    estimator: LowPassFilter,
+    handoff_band: f64,
+    mode: RampMode,
+    prev_value: Option<f64>,
+    measured_rate: Option<f64>,
+    /// Whether the rate loop has taken up the output since the ramp began
+    rate_seeded: bool,
+    output: f64,
+}
+
+impl RampController {
+    /// Creates a controller holding `absolute`'s setpoint, with `rate` as
+    /// the rate loop's tuning and `rate_filter` seconds as the time
+    /// constant of the rate estimate. The rate config's setpoint is
+    /// replaced by each ramp's rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the two configs have
+    /// different output limits, or `rate_filter` is non-finite or
+    /// non-positive.
+    pub fn new(
+        absolute: ControllerConfig,
+        rate: ControllerConfig,
+        rate_filter: f64,
+    ) -> Result<Self, PidError> {
+        if absolute.min_output() != rate.min_output() || absolute.max_output() != rate.max_output()
+        {
+            return Err(PidError::InvalidParameter(
+                "absolute and rate configs must share output limits",
+            ));
+        }
+        let estimator = LowPassFilter::new(rate_filter).map_err(|_| {
+            PidError::InvalidParameter("rate_filter must be a finite positive time constant")
+        })?;
+        let output = absolute.initial_output().unwrap_or(0.0);
+        Ok(Self {
+            absolute: PidController::new(absolute),
+            rate: PidController::new(rate),
+            estimator,
+            handoff_band: 0.0,
+            mode: RampMode::Absolute,
+            prev_value: None,
+            measured_rate: None,
+            rate_seeded: false,
+            output,
+        })
+    }
+
+    /// Hands over to the absolute loop once the process value is within
+    /// `band` of the end value, rather than only when it gets there, to
+    /// leave the absolute loop room to round off the approach. Default:
+    /// `0.0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `band` is non-finite or
+    /// negative.
+    pub fn set_handoff_band(&mut self, band: f64) -> Result<(), PidError> {
+        if !band.is_finite() || band < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "handoff band must be a finite non-negative number",
+            ));
+        }
+        self.handoff_band = band;
+        Ok(())
+    }
+
+    /// Starts moving from `process_value` towards `end_value` at `rate`
+    /// units per second; the sign of `rate` is ignored. Already within the
+    /// hand-off band, it just sets the absolute setpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if any argument is
+    /// non-finite or `rate` is zero.
+    pub fn start_ramp(
+        &mut self,
+        rate: f64,
+        end_value: f64,
+        process_value: f64,
+    ) -> Result<(), PidError> {
+        if !rate.is_finite() || rate == 0.0 {
+            return Err(PidError::InvalidParameter(
+                "ramp rate must be a finite non-zero number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+        self.absolute.set_setpoint(end_value)?;
+        let remaining = end_value - process_value;
+        if remaining.abs() <= self.handoff_band {
+            self.mode = RampMode::Absolute;
+            return Ok(());
+        }
+        let rate = rate.abs().copysign(remaining);
+        self.rate.set_setpoint(rate)?;
+        self.mode = RampMode::Ramping { rate, end_value };
+        self.rate_seeded = false;
+        Ok(())
+    }
+
+    /// What is being controlled now.
+    pub fn mode(&self) -> RampMode {
+        self.mode
+    }
+
+    /// Filtered rate of change of the process value, in units per second;
+    /// `None` until two samples have been seen.
+    pub fn measured_rate(&self) -> Option<f64> {
+        self.measured_rate
+    }
+
+    /// The absolute loop.
+    pub fn absolute(&self) -> &PidController {
+        &self.absolute
+    }
+
+    /// The rate loop.
+    pub fn rate_controller(&self) -> &PidController {
+        &self.rate
+    }
+
+    /// Runs one iteration `dt` seconds after the previous one and returns
+    /// the output of whichever loop is in control.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` is
+    /// non-finite or `dt` is non-finite / non-positive.
+    pub fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        // Check first so a bad sample never reaches the rate estimate
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+        if let Some(prev) = self.prev_value {
+            let raw = (process_value - prev) / dt;
+            self.measured_rate = Some(self.estimator.process(raw, dt));
+        }
+        self.prev_value = Some(process_value);
+
+        if let RampMode::Ramping { rate, end_value } = self.mode {
+            if self.reached(process_value, rate, end_value) {
+                self.mode = RampMode::Absolute;
+            }
+        }
+
+        let tracked_rate = self.measured_rate.unwrap_or(self.rate.setpoint());
+        self.output = match self.mode {
+            RampMode::Absolute => {
+                let output = self.absolute.compute(process_value, dt)?;
+                self.rate.track(tracked_rate, output, dt)?;
+                output
+            }
+            RampMode::Ramping { .. } => {
+                let output = match self.measured_rate {
+                    Some(measured) if self.rate_seeded => self.rate.compute(measured, dt)?,
+                    _ => self.rate.track(tracked_rate, self.output, dt)?,
+                };
+                self.rate_seeded = true;
+                self.absolute.track(process_value, output, dt)?;
+                output
+            }
+        };
+        Ok(self.output)
+    }
+
+    /// The absolute setpoint: the end value while ramping.
+    pub fn setpoint(&self) -> f64 {
+        self.absolute.setpoint()
+    }
+
+    /// Cancels any ramp and steps the absolute setpoint to `setpoint`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        self.absolute.set_setpoint(setpoint)?;
+        self.mode = RampMode::Absolute;
+        Ok(())
+    }
+
+    /// Returns both loops and the rate estimate to their initial state,
+    /// ending any ramp.
+    pub fn reset(&mut self) {
+        self.absolute.reset();
+        self.rate.reset();
+        self.estimator.reset();
+        self.mode = RampMode::Absolute;
+        self.prev_value = None;
+        self.measured_rate = None;
+        self.rate_seeded = false;
+        self.output = self.absolute.config().initial_output().unwrap_or(0.0);
+    }
+
+    fn reached(&self, process_value: f64, rate: f64, end_value: f64) -> bool {
+        let remaining = end_value - process_value;
+        remaining.abs() <= self.handoff_band || remaining * rate <= 0.0
+    }
+}
+
+impl Controller for RampController {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        RampController::compute(self, process_value, dt)
+    }
+
+    fn setpoint(&self) -> f64 {
+        RampController::setpoint(self)
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        RampController::set_setpoint(self, setpoint)
+    }
+
+    fn reset(&mut self) {
+        RampController::reset(self)
+    }
+
+    /// The absolute loop's statistics, which follow the process value
+    /// through ramps as well.
+    fn statistics(&self) -> Option<ControllerStatistics> {
+        Some(self.absolute.get_statistics())
+    }
+}
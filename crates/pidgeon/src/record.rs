@@ -0,0 +1,379 @@
+use core::fmt;
+
+use crate::compute::{
+    compute_step, pid_compute, pid_compute_with_feedforward, pid_compute_with_rate, pid_track,
+    StepInputs,
+};
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::state::PidState;
+
+/// One [`pid_compute`] call captured by a [`Recording`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedStep {
+    /// Configuration in force for this step, if it changed since the
+    /// previous one. Always present on the first step.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub config: Option<ControllerConfig>,
+    /// State the step started from, if it is not the previous step's result,
+    /// as after a reset. Always present on the first step.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub start: Option<PidState>,
+    /// Measured process value.
+    pub process_value: f64,
+    /// Rate of change passed to
+    /// [`compute_with_rate`](Recording::compute_with_rate), if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub rate: Option<f64>,
+    /// Feedforward term passed to
+    /// [`compute_with_feedforward`](Recording::compute_with_feedforward), if
+    /// any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub feedforward: Option<f64>,
+    /// Output followed through [`track`](Recording::track), if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub tracked_output: Option<f64>,
+    /// Time step in seconds.
+    pub dt: f64,
+    /// Setpoint minus process value.
+    pub error: f64,
+    /// Clamped output.
+    pub output: f64,
+    /// State after the step.
+    pub state: PidState,
+}
+
+/// A captured run of a controller, replayable against the current
+/// [`pid_compute`] to check that it still produces bit-identical results.
+///
+/// Record through [`compute`](Self::compute) in place of [`pid_compute`], or
+/// with [`PidController::start_recording`](crate::PidController::start_recording).
+/// Configuration changes and resets between steps are captured too, so a run
+/// with setpoint moves and retuning replays faithfully. With the `serde`
+/// feature a recording can be saved, for example as a fixture for a
+/// regression test that runs [`verify`](Self::verify); with `serde_json`,
+/// enable its `float_roundtrip` feature so every value reads back exactly.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidState, Recording};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_ki(0.5)
+///     .with_setpoint(10.0)
+///     .with_output_limits(-100.0, 100.0)
+///     .build()
+///     .unwrap();
+///
+/// let mut recording = Recording::new();
+/// let mut state = PidState::default();
+/// for process_value in [0.0, 2.5, 6.0, 9.0] {
+///     let (_output, next) = recording.compute(&config, &state, process_value, 0.1).unwrap();
+///     state = next;
+/// }
+///
+/// assert_eq!(recording.steps().len(), 4);
+/// assert_eq!(recording.verify(), Ok(()));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording {
+    pub(crate) steps: Vec<RecordedStep>,
+    /// Config and resulting state of the last step recorded in this process
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last: Option<(ControllerConfig, PidState)>,
+}
+
+impl Recording {
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs [`pid_compute`] and records the step. Failed steps are not
+    /// recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever [`pid_compute`] returns.
+    pub fn compute(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        dt: f64,
+    ) -> Result<(f64, PidState), PidError> {
+        let (output, next) = pid_compute(config, state, process_value, dt)?;
+        self.push(
+            config,
+            state,
+            process_value,
+            StepInputs::default(),
+            dt,
+            output,
+            &next,
+        );
+        Ok((output, next))
+    }
+
+    /// Runs [`pid_compute_with_rate`] and records the step, rate included.
+    /// Failed steps are not recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever [`pid_compute_with_rate`] returns.
+    pub fn compute_with_rate(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        rate: f64,
+        dt: f64,
+    ) -> Result<(f64, PidState), PidError> {
+        let (output, next) = pid_compute_with_rate(config, state, process_value, rate, dt)?;
+        let inputs = StepInputs {
+            rate: Some(rate),
+            ..StepInputs::default()
+        };
+        self.push(config, state, process_value, inputs, dt, output, &next);
+        Ok((output, next))
+    }
+
+    /// Runs [`pid_compute_with_feedforward`] and records the step,
+    /// feedforward term included. Failed steps are not recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever [`pid_compute_with_feedforward`] returns.
+    pub fn compute_with_feedforward(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        feedforward: f64,
+        dt: f64,
+    ) -> Result<(f64, PidState), PidError> {
+        let (output, next) =
+            pid_compute_with_feedforward(config, state, process_value, feedforward, dt)?;
+        let inputs = StepInputs {
+            feedforward: Some(feedforward),
+            ..StepInputs::default()
+        };
+        self.push(config, state, process_value, inputs, dt, output, &next);
+        Ok((output, next))
+    }
+
+    /// Runs [`pid_track`] and records the step, tracked output included.
+    /// Failed steps are not recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever [`pid_track`] returns.
+    pub fn track(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        tracked_output: f64,
+        dt: f64,
+    ) -> Result<(f64, PidState), PidError> {
+        let (output, next) = pid_track(config, state, process_value, tracked_output, dt)?;
+        let inputs = StepInputs {
+            tracked_output: Some(tracked_output),
+            ..StepInputs::default()
+        };
+        self.push(config, state, process_value, inputs, dt, output, &next);
+        Ok((output, next))
+    }
+
+    /// Runs and records a step with any mix of the optional inputs.
+    pub(crate) fn record(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        inputs: StepInputs,
+        dt: f64,
+    ) -> Result<(f64, PidState), PidError> {
+        let (output, next) = compute_step(config, state, process_value, inputs, dt)?;
+        self.push(config, state, process_value, inputs, dt, output, &next);
+        Ok((output, next))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        config: &ControllerConfig,
+        state: &PidState,
+        process_value: f64,
+        inputs: StepInputs,
+        dt: f64,
+        output: f64,
+        next: &PidState,
+    ) {
+        let (changed_config, start) = match &self.last {
+            Some((last_config, last_state)) => (
+                (last_config != config).then(|| config.clone()),
+                (last_state != state).then(|| state.clone()),
+            ),
+            None => (Some(config.clone()), Some(state.clone())),
+        };
+        self.steps.push(RecordedStep {
+            config: changed_config,
+            start,
+            process_value,
+            rate: inputs.rate,
+            feedforward: inputs.feedforward,
+            tracked_output: inputs.tracked_output,
+            dt,
+            error: config.setpoint - process_value,
+            output,
+            state: next.clone(),
+        });
+        self.last = Some((config.clone(), next.clone()));
+    }
+
+    /// The recorded steps, oldest first.
+    pub fn steps(&self) -> &[RecordedStep] {
+        &self.steps
+    }
+
+    /// Replays every step through the current [`pid_compute`] and checks
+    /// that the error, output, and state match the recording bit for bit.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`ReplayMismatch`].
+    pub fn verify(&self) -> Result<(), ReplayMismatch> {
+        let mut config: Option<&ControllerConfig> = None;
+        let mut state = PidState::default();
+        for (index, step) in self.steps.iter().enumerate() {
+            if let Some(changed) = &step.config {
+                config = Some(changed);
+            }
+            if let Some(start) = &step.start {
+                state = start.clone();
+            }
+            let config = config.ok_or(ReplayMismatch::Rejected {
+                step: index,
+                error: PidError::InvalidParameter("the first recorded step has no config"),
+            })?;
+            let inputs = StepInputs {
+                rate: step.rate,
+                feedforward: step.feedforward,
+                tracked_output: step.tracked_output,
+            };
+            let (output, next) = compute_step(config, &state, step.process_value, inputs, step.dt)
+                .map_err(|error| ReplayMismatch::Rejected { step: index, error })?;
+
+            let compare = |field, recorded: f64, replayed: f64| {
+                if recorded.to_bits() == replayed.to_bits() {
+                    Ok(())
+                } else {
+                    Err(ReplayMismatch::Differs {
+                        step: index,
+                        field,
+                        recorded,
+                        replayed,
+                    })
+                }
+            };
+            let flag = |first_run: bool| f64::from(u8::from(first_run));
+            compare("error", step.error, config.setpoint - step.process_value)?;
+            compare("output", step.output, output)?;
+            compare(
+                "integral_contribution",
+                step.state.integral_contribution,
+                next.integral_contribution,
+            )?;
+            compare("prev_error", step.state.prev_error, next.prev_error)?;
+            compare(
+                "prev_measurement",
+                step.state.prev_measurement,
+                next.prev_measurement,
+            )?;
+            compare(
+                "prev_filtered_derivative",
+                step.state.prev_filtered_derivative,
+                next.prev_filtered_derivative,
+            )?;
+            compare("last_output", step.state.last_output, next.last_output)?;
+            compare(
+                "filtered_setpoint",
+                step.state.filtered_setpoint,
+                next.filtered_setpoint,
+            )?;
+            compare(
+                "first_run",
+                flag(step.state.first_run),
+                flag(next.first_run),
+            )?;
+            state = next;
+        }
+        Ok(())
+    }
+}
+
+/// Where a replayed [`Recording`] first departed from what was recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayMismatch {
+    /// [`pid_compute`] rejected a step that succeeded when it was recorded.
+    Rejected {
+        /// Index of the step in [`Recording::steps`].
+        step: usize,
+        /// What [`pid_compute`] returned.
+        error: PidError,
+    },
+    /// A replayed value differs from the recorded one in at least one bit.
+    Differs {
+        /// Index of the step in [`Recording::steps`].
+        step: usize,
+        /// `error`, `output`, or the name of a [`PidState`] field. `first_run`
+        /// compares as `0` or `1`.
+        field: &'static str,
+        /// Value in the recording.
+        recorded: f64,
+        /// Value the replay produced.
+        replayed: f64,
+    },
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayMismatch::Rejected { step, error } => {
+                write!(f, "step {} was rejected on replay: {}", step, error)
+            }
+            ReplayMismatch::Differs {
+                step,
+                field,
+                recorded,
+                replayed,
+            } => write!(
+                f,
+                "step {}: {} was {:e} when recorded but {:e} on replay",
+                step, field, recorded, replayed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayMismatch {}
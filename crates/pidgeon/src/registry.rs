@@ -0,0 +1,541 @@
+use std::collections::BTreeMap;
+
+use crate::config::ControllerConfig;
+use crate::controller::ControllerStatistics;
+use crate::error::PidError;
+use crate::thread_safe::ThreadSafePidController;
+use crate::time::Instant;
+use crate::transaction::RegistryTransaction;
+
+/// One loop in a controller group, as passed to
+/// [`ControllerRegistry::define_group`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupMember {
+    /// Name the controller is registered under.
+    pub name: String,
+    /// Multiplier applied to the base setpoint of a
+    /// [scaled broadcast](ControllerRegistry::broadcast_scaled_setpoint).
+    pub ratio: f64,
+    /// Fastest the setpoint moves during a broadcast, in setpoint units per
+    /// second. `None` steps straight to the new setpoint.
+    pub ramp_rate: Option<f64>,
+}
+
+impl GroupMember {
+    /// A member with a ratio of 1 that steps to new setpoints.
+    pub fn new(name: impl Into<String>) -> Self {
+        GroupMember {
+            name: name.into(),
+            ratio: 1.0,
+            ramp_rate: None,
+        }
+    }
+
+    /// Sets the ratio applied to scaled broadcasts.
+    pub fn with_ratio(mut self, ratio: f64) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Ramps this member's setpoint at `rate` units per second.
+    pub fn with_ramp_rate(mut self, rate: f64) -> Self {
+        self.ramp_rate = Some(rate);
+        self
+    }
+}
+
+/// A setpoint change waiting for, or ramping since, its start instant
+#[derive(Clone)]
+struct GroupBroadcast {
+    at: Instant,
+    /// Final setpoint of each member, in group order
+    targets: Vec<f64>,
+    /// Setpoint of each member when the broadcast started
+    from: Option<Vec<f64>>,
+}
+
+/// A collection of named [`ThreadSafePidController`]s.
+///
+/// Applications that run many loops can register each controller under a
+/// unique name and then look them up, collect statistics, reset, or
+/// snapshot configuration in bulk. Names are kept in sorted order, so every
+/// bulk operation visits controllers in a deterministic sequence.
+///
+/// The registry hands out the same [`ThreadSafePidController`] handles it
+/// stores, so a loop can keep its own clone while the registry is used for
+/// bookkeeping elsewhere. It holds PID loops only, since its bulk
+/// operations reach into PID gains, configs, and statistics.
+///
+/// Loops that move together, such as the zones of a furnace, can be named as
+/// a group with [`define_group`](Self::define_group) and given a new
+/// setpoint in one operation; see
+/// [`broadcast_setpoint`](Self::broadcast_setpoint).
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, ControllerRegistry};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_output_limits(-10.0, 10.0)
+///     .build()
+///     .unwrap();
+///
+/// let mut registry = ControllerRegistry::new();
+/// let zone_a = registry.register_config("zone_a", config.clone());
+/// registry.register_config("zone_b", config);
+///
+/// zone_a.compute(0.5, 0.01).unwrap();
+///
+/// assert_eq!(registry.len(), 2);
+/// assert!(registry.get("zone_a").is_some());
+///
+/// // Snapshot every loop's configuration and restore it later.
+/// let snapshot = registry.dump_configs().unwrap();
+/// registry.restore_configs(&snapshot).unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct ControllerRegistry {
+    controllers: BTreeMap<String, ThreadSafePidController>,
+    groups: BTreeMap<String, Vec<GroupMember>>,
+    broadcasts: BTreeMap<String, GroupBroadcast>,
+}
+
+impl ControllerRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an existing controller handle under `name`.
+    ///
+    /// Returns the controller previously registered under the same name, if any.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        controller: ThreadSafePidController,
+    ) -> Option<ThreadSafePidController> {
+        self.controllers.insert(name.into(), controller)
+    }
+
+    /// Creates a new controller from `config`, registers it under `name`, and
+    /// returns a handle to it. Any controller previously registered under the
+    /// same name is replaced.
+    pub fn register_config(
+        &mut self,
+        name: impl Into<String>,
+        config: ControllerConfig,
+    ) -> ThreadSafePidController {
+        let controller = ThreadSafePidController::new(config);
+        self.controllers.insert(name.into(), controller.clone());
+        controller
+    }
+
+    /// Removes and returns the controller registered under `name`.
+    pub fn remove(&mut self, name: &str) -> Option<ThreadSafePidController> {
+        self.controllers.remove(name)
+    }
+
+    /// Returns the controller registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&ThreadSafePidController> {
+        self.controllers.get(name)
+    }
+
+    /// Returns `true` if a controller is registered under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.controllers.contains_key(name)
+    }
+
+    /// Number of registered controllers.
+    pub fn len(&self) -> usize {
+        self.controllers.len()
+    }
+
+    /// Returns `true` if no controllers are registered.
+    pub fn is_empty(&self) -> bool {
+        self.controllers.is_empty()
+    }
+
+    /// Iterates over registered names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.controllers.keys().map(String::as_str)
+    }
+
+    /// Iterates over `(name, controller)` pairs in sorted name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ThreadSafePidController)> {
+        self.controllers.iter().map(|(name, c)| (name.as_str(), c))
+    }
+
+    /// Collects a statistics snapshot from every registered controller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if any controller's mutex was poisoned.
+    pub fn statistics(&self) -> Result<BTreeMap<String, ControllerStatistics>, PidError> {
+        self.controllers
+            .iter()
+            .map(|(name, c)| Ok((name.clone(), c.get_statistics()?)))
+            .collect()
+    }
+
+    /// Resets state and statistics of every registered controller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if any controller's mutex was poisoned.
+    /// Controllers visited before the failure have already been reset.
+    pub fn reset_all(&self) -> Result<(), PidError> {
+        for controller in self.controllers.values() {
+            controller.reset()?;
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of every registered controller's configuration, keyed by name.
+    ///
+    /// With the `serde` feature the returned map can be serialized directly and
+    /// later fed back into [`restore_configs`](Self::restore_configs).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if any controller's mutex was poisoned.
+    pub fn dump_configs(&self) -> Result<BTreeMap<String, ControllerConfig>, PidError> {
+        self.controllers
+            .iter()
+            .map(|(name, c)| Ok((name.clone(), c.get_config()?)))
+            .collect()
+    }
+
+    /// Applies a configuration snapshot produced by [`dump_configs`](Self::dump_configs).
+    ///
+    /// Controllers that are already registered keep their state and statistics
+    /// and only have their configuration replaced. Names that are not yet
+    /// registered get a fresh controller. Registered controllers absent from
+    /// `configs` are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if any controller's mutex was poisoned.
+    pub fn restore_configs(
+        &mut self,
+        configs: &BTreeMap<String, ControllerConfig>,
+    ) -> Result<(), PidError> {
+        for (name, config) in configs {
+            match self.controllers.get(name) {
+                Some(controller) => controller.update_config(config.clone())?,
+                None => {
+                    self.register_config(name.clone(), config.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts staging gain and configuration changes to apply to several
+    /// controllers at once. See [`RegistryTransaction`].
+    pub fn transaction(&self) -> RegistryTransaction<'_> {
+        RegistryTransaction::new(self)
+    }
+
+    /// Names a group of registered controllers for
+    /// [`broadcast_setpoint`](Self::broadcast_setpoint) and
+    /// [`broadcast_scaled_setpoint`](Self::broadcast_scaled_setpoint).
+    ///
+    /// Redefining a group cancels any broadcast still pending or ramping on
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `members` is empty, names a
+    /// controller twice or one that is not registered, or has a ratio that
+    /// is not finite or a ramp rate that is not positive and finite.
+    pub fn define_group(
+        &mut self,
+        name: impl Into<String>,
+        members: Vec<GroupMember>,
+    ) -> Result<(), PidError> {
+        if members.is_empty() {
+            return Err(PidError::InvalidParameter(
+                "a group needs at least one member",
+            ));
+        }
+        for (i, member) in members.iter().enumerate() {
+            let controller =
+                self.controllers
+                    .get(&member.name)
+                    .ok_or(PidError::InvalidParameter(
+                        "group member is not a registered controller",
+                    ))?;
+            if members[..i].iter().any(|other| {
+                other.name == member.name
+                    || self.controllers[&other.name].same_controller(controller)
+            }) {
+                return Err(PidError::InvalidParameter(
+                    "group lists the same controller twice",
+                ));
+            }
+            if !member.ratio.is_finite() {
+                return Err(PidError::InvalidParameter(
+                    "group member ratio must be a finite number",
+                ));
+            }
+            if member
+                .ramp_rate
+                .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+            {
+                return Err(PidError::InvalidParameter(
+                    "group member ramp rate must be positive and finite",
+                ));
+            }
+        }
+        let name = name.into();
+        self.broadcasts.remove(&name);
+        self.groups.insert(name, members);
+        Ok(())
+    }
+
+    /// Removes a group, cancelling any broadcast on it. Its controllers stay
+    /// registered.
+    pub fn remove_group(&mut self, name: &str) -> Option<Vec<GroupMember>> {
+        self.broadcasts.remove(name);
+        self.groups.remove(name)
+    }
+
+    /// Returns the members of the group `name`.
+    pub fn group(&self, name: &str) -> Option<&[GroupMember]> {
+        self.groups.get(name).map(Vec::as_slice)
+    }
+
+    /// Iterates over group names in sorted order.
+    pub fn group_names(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    /// Schedules every member of `group` to move to `setpoint` at `at`.
+    ///
+    /// Nothing changes until [`update_groups`](Self::update_groups) is called
+    /// at or after `at`. That call moves every member in one step, holding
+    /// all of their locks at once, so no loop computes with a mix of old and
+    /// new setpoints. Members with a ramp rate then travel toward the new
+    /// setpoint at their own rate, measured from `at`, on each later
+    /// `update_groups`; their event logs record the whole ramp as a single
+    /// setpoint change. A new broadcast replaces one still pending or
+    /// ramping on the same group, ramping on from wherever it got to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the group does not exist or
+    /// `setpoint` is not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use pidgeon::{ControllerConfig, ControllerRegistry, GroupMember};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(1.0)
+    ///     .with_setpoint(20.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut registry = ControllerRegistry::new();
+    /// let front = registry.register_config("front", config.clone());
+    /// let back = registry.register_config("back", config);
+    /// registry
+    ///     .define_group(
+    ///         "furnace",
+    ///         vec![
+    ///             GroupMember::new("front").with_ramp_rate(10.0),
+    ///             GroupMember::new("back").with_ratio(0.9).with_ramp_rate(5.0),
+    ///         ],
+    ///     )
+    ///     .unwrap();
+    ///
+    /// // Heat to 400 at the front and 360 at the back, starting together
+    /// let start = Instant::now();
+    /// registry.broadcast_scaled_setpoint("furnace", 400.0, start).unwrap();
+    ///
+    /// registry.update_groups(start + Duration::from_secs(10)).unwrap();
+    /// assert_eq!(front.get_config().unwrap().setpoint(), 120.0);
+    /// assert_eq!(back.get_config().unwrap().setpoint(), 70.0);
+    ///
+    /// registry.update_groups(start + Duration::from_secs(100)).unwrap();
+    /// assert_eq!(front.get_config().unwrap().setpoint(), 400.0);
+    /// assert_eq!(back.get_config().unwrap().setpoint(), 360.0);
+    /// assert!(!registry.is_broadcasting("furnace"));
+    /// ```
+    pub fn broadcast_setpoint(
+        &mut self,
+        group: &str,
+        setpoint: f64,
+        at: Instant,
+    ) -> Result<(), PidError> {
+        self.schedule_broadcast(group, setpoint, at, |_| 1.0)
+    }
+
+    /// Like [`broadcast_setpoint`](Self::broadcast_setpoint), but moves each
+    /// member to `base` times its [ratio](GroupMember::ratio).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the group does not exist or
+    /// a scaled setpoint is not finite.
+    pub fn broadcast_scaled_setpoint(
+        &mut self,
+        group: &str,
+        base: f64,
+        at: Instant,
+    ) -> Result<(), PidError> {
+        self.schedule_broadcast(group, base, at, |member| member.ratio)
+    }
+
+    fn schedule_broadcast(
+        &mut self,
+        group: &str,
+        base: f64,
+        at: Instant,
+        ratio: impl Fn(&GroupMember) -> f64,
+    ) -> Result<(), PidError> {
+        let members = self
+            .groups
+            .get(group)
+            .ok_or(PidError::InvalidParameter("no group with that name"))?;
+        let targets: Vec<f64> = members.iter().map(|member| base * ratio(member)).collect();
+        if targets.iter().any(|target| !target.is_finite()) {
+            return Err(PidError::InvalidParameter(
+                "broadcast setpoint must be a finite number",
+            ));
+        }
+        self.broadcasts.insert(
+            group.to_string(),
+            GroupBroadcast {
+                at,
+                targets,
+                from: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Whether a broadcast on `group` is waiting for its instant or still
+    /// ramping.
+    pub fn is_broadcasting(&self, group: &str) -> bool {
+        self.broadcasts.contains_key(group)
+    }
+
+    /// Starts broadcasts whose instant has come and advances ramping ones to
+    /// `now`. Call it from a supervisory loop, at least as often as the
+    /// ramps should step.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a group's member is no
+    /// longer registered or rejects its target under its
+    /// [`setpoint_limits`](crate::ControllerConfig::setpoint_limits) (targets
+    /// outside clamping limits are clamped), and [`PidError::MutexPoisoned`] if a member's
+    /// mutex was poisoned. The failing broadcast is dropped without having
+    /// changed any of its members; the others still advance, and the first
+    /// error is returned.
+    pub fn update_groups(&mut self, now: Instant) -> Result<(), PidError> {
+        let mut result = Ok(());
+        let mut broadcasts = core::mem::take(&mut self.broadcasts);
+        broadcasts.retain(|group, broadcast| {
+            if broadcast.at > now {
+                return true;
+            }
+            match self.advance_broadcast(group, broadcast, now) {
+                Ok(done) => !done,
+                Err(e) => {
+                    if result.is_ok() {
+                        result = Err(e);
+                    }
+                    false
+                }
+            }
+        });
+        self.broadcasts = broadcasts;
+        result
+    }
+
+    /// Writes every member's setpoint for `now` under all of their locks,
+    /// and returns whether each has reached its target
+    fn advance_broadcast(
+        &self,
+        group: &str,
+        broadcast: &mut GroupBroadcast,
+        now: Instant,
+    ) -> Result<bool, PidError> {
+        let members = &self.groups[group];
+        let controllers = members
+            .iter()
+            .map(|member| {
+                self.controllers
+                    .get(&member.name)
+                    .ok_or(PidError::InvalidParameter(
+                        "group member is no longer a registered controller",
+                    ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, controller) in controllers.iter().enumerate() {
+            if controllers[..i]
+                .iter()
+                .any(|other| other.same_controller(controller))
+            {
+                return Err(PidError::InvalidParameter(
+                    "group members now share a controller",
+                ));
+            }
+        }
+
+        // Lock in name order, so overlapping groups can't deadlock
+        let mut order: Vec<usize> = (0..members.len()).collect();
+        order.sort_by(|&a, &b| members[a].name.cmp(&members[b].name));
+        let mut guards: Vec<_> = (0..members.len()).map(|_| None).collect();
+        for i in order {
+            guards[i] = Some(controllers[i].lock()?);
+        }
+        let mut guards: Vec<_> = guards.into_iter().flatten().collect();
+
+        // Hold each target to its loop's setpoint limits before anything
+        // moves, so a rejected target leaves the whole group untouched
+        if broadcast.from.is_none() {
+            broadcast.targets = guards
+                .iter_mut()
+                .zip(&broadcast.targets)
+                .map(|(controller, &target)| controller.limit_setpoint(target))
+                .collect::<Result<_, _>>()?;
+        }
+        let from = broadcast.from.get_or_insert_with(|| {
+            guards
+                .iter_mut()
+                .zip(&broadcast.targets)
+                .map(|(controller, &target)| {
+                    let from = controller.setpoint();
+                    controller.start_setpoint_ramp(target);
+                    from
+                })
+                .collect()
+        });
+        let elapsed = now.saturating_duration_since(broadcast.at).as_secs_f64();
+        let mut done = true;
+        for (((controller, member), &target), &from) in guards
+            .iter_mut()
+            .zip(members)
+            .zip(&broadcast.targets)
+            .zip(from.iter())
+        {
+            let setpoint = match member.ramp_rate {
+                Some(rate) if (target - from).abs() > rate * elapsed => {
+                    done = false;
+                    from + (target - from).signum() * rate * elapsed
+                }
+                _ => target,
+            };
+            controller.ramp_setpoint(setpoint);
+        }
+        Ok(done)
+    }
+}
@@ -0,0 +1,575 @@
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use hmac::{Hmac, Mac};
+use iggy::client::MessageClient;
+use iggy::consumer::Consumer;
+use iggy::identifier::Identifier;
+use iggy::messages::poll_messages::PollingStrategy;
+use iggy::messages::send_messages::Message;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::debug::{iggy_client, iggy_producer, ControllerMode, ControllerSnapshot, DebugConfig};
+use crate::time::{unix_millis, Duration};
+
+/// A runtime change requested by an operator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RemoteAction {
+    /// Move the setpoint
+    SetSetpoint {
+        /// New setpoint
+        value: f64,
+    },
+    /// Change any subset of the gains; all of them are applied or none
+    SetGains {
+        /// New proportional gain
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kp: Option<f64>,
+        /// New integral gain
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ki: Option<f64>,
+        /// New derivative gain
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kd: Option<f64>,
+    },
+    /// Switch the operating mode recorded by the debugger
+    SetMode {
+        /// New mode
+        mode: ControllerMode,
+    },
+    /// Publish a [`ControllerSnapshot`] on the snapshot topic
+    Snapshot,
+}
+
+/// A command addressed to one controller
+///
+/// Commands travel on [`RemoteControlConfig::command_topic`] as signed
+/// messages: the hex-encoded HMAC-SHA256 of the command's JSON, keyed with the
+/// shared secret, then a newline, then that JSON exactly as signed. The MAC is
+/// checked over those raw bytes before anything is decoded, so the signer is
+/// free to produce any valid JSON for the command. Use [`sign`](Self::sign)
+/// and [`from_signed`](Self::from_signed) rather than building messages by
+/// hand.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{RemoteAction, RemoteCommand};
+///
+/// let command = RemoteCommand::new("oven", "alice", RemoteAction::SetSetpoint { value: 180.0 });
+/// let message = command.sign(b"shared secret");
+/// assert_eq!(RemoteCommand::from_signed(&message, b"shared secret"), Ok(command));
+/// assert!(RemoteCommand::from_signed(&message, b"wrong secret").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteCommand {
+    /// Unique ID, used to reject replays and to correlate audit events
+    pub id: String,
+    /// Controller the command is addressed to
+    pub controller_id: String,
+    /// Operator or system that issued the command
+    pub issued_by: String,
+    /// Milliseconds since the Unix epoch when the command was issued
+    pub timestamp: u64,
+    /// The requested change
+    pub action: RemoteAction,
+}
+
+impl RemoteCommand {
+    /// Creates a command stamped with the current time
+    pub fn new(
+        controller_id: impl Into<String>,
+        issued_by: impl Into<String>,
+        action: RemoteAction,
+    ) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let timestamp = unix_millis();
+        RemoteCommand {
+            id: format!(
+                "{}-{}-{}",
+                timestamp,
+                std::process::id(),
+                NEXT_ID.fetch_add(1, Ordering::Relaxed)
+            ),
+            controller_id: controller_id.into(),
+            issued_by: issued_by.into(),
+            timestamp,
+            action,
+        }
+    }
+
+    /// Encodes the command as a message signed with the shared `secret`
+    pub fn sign(&self, secret: &[u8]) -> Vec<u8> {
+        let json = serde_json::to_vec(self).expect("commands always encode as JSON");
+        let mut message = hex(&hmac_sha256(secret, &json)).into_bytes();
+        message.push(b'\n');
+        message.extend_from_slice(&json);
+        message
+    }
+
+    /// Decodes a message from [`sign`](Self::sign), checking its signature
+    /// against `secret` in constant time first
+    ///
+    /// # Errors
+    ///
+    /// Returns `"invalid signature"` if the message is not signed with
+    /// `secret`, and `"malformed command"` if it is but does not hold a
+    /// command.
+    pub fn from_signed(message: &[u8], secret: &[u8]) -> Result<Self, &'static str> {
+        let (signature, json) = split_signed(message).ok_or("invalid signature")?;
+        let signature = unhex(signature).ok_or("invalid signature")?;
+        let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC takes keys of any length");
+        mac.update(json);
+        mac.verify_slice(&signature)
+            .map_err(|_| "invalid signature")?;
+        serde_json::from_slice(json).map_err(|_| "malformed command")
+    }
+}
+
+/// Signature and JSON halves of a signed message
+fn split_signed(message: &[u8]) -> Option<(&str, &[u8])> {
+    let newline = message.iter().position(|&b| b == b'\n')?;
+    let signature = std::str::from_utf8(&message[..newline]).ok()?;
+    Some((signature, &message[newline + 1..]))
+}
+
+/// Result of handling one command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CommandOutcome {
+    /// The change was applied to the controller
+    Applied,
+    /// The command was refused and the controller left unchanged
+    Rejected {
+        /// Why the command was refused
+        reason: String,
+    },
+}
+
+/// Record of a command received by a controller, published on
+/// [`RemoteControlConfig::audit_topic`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// Milliseconds since the Unix epoch when the command was handled
+    pub timestamp: u64,
+    /// Controller that handled the command
+    pub controller_id: String,
+    /// ID of the command, empty if it could not be decoded
+    pub command_id: String,
+    /// Issuer claimed by the command, empty if it could not be decoded
+    pub issued_by: String,
+    /// The requested change, `None` if the command could not be decoded
+    pub action: Option<RemoteAction>,
+    /// Whether the change was applied
+    pub outcome: CommandOutcome,
+}
+
+impl AuditEvent {
+    /// Audit record for `command` handled by `controller_id`
+    pub fn new(controller_id: &str, command: &RemoteCommand, outcome: CommandOutcome) -> Self {
+        AuditEvent {
+            timestamp: unix_millis(),
+            controller_id: controller_id.to_string(),
+            command_id: command.id.clone(),
+            issued_by: command.issued_by.clone(),
+            action: Some(command.action.clone()),
+            outcome,
+        }
+    }
+
+    fn undecodable(controller_id: &str, reason: String) -> Self {
+        AuditEvent {
+            timestamp: unix_millis(),
+            controller_id: controller_id.to_string(),
+            command_id: String::new(),
+            issued_by: String::new(),
+            action: None,
+            outcome: CommandOutcome::Rejected { reason },
+        }
+    }
+}
+
+/// Opt-in settings for accepting commands over the debug transport
+///
+/// When set on [`DebugConfig::with_remote_control`], the debugger subscribes to
+/// `command_topic` in the debug stream. Commands must be addressed to this
+/// controller, carry a valid signature for `secret`, be no older than
+/// `max_age`, and not repeat a recently seen ID. Valid commands are applied at
+/// the start of the next [`PidController::compute`](crate::PidController::compute);
+/// every command, applied or not, produces an [`AuditEvent`] on `audit_topic`.
+#[derive(Clone)]
+pub struct RemoteControlConfig {
+    /// Topic the debugger reads commands from
+    pub command_topic: String,
+    /// Topic audit events are published to
+    pub audit_topic: String,
+    /// Topic requested [`ControllerSnapshot`]s are published to
+    pub snapshot_topic: String,
+    /// Shared HMAC key that commands must be signed with
+    pub secret: Vec<u8>,
+    /// Commands issued longer ago than this are rejected
+    pub max_age: Duration,
+}
+
+impl RemoteControlConfig {
+    /// Accepts commands signed with `secret` on the default topics
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        RemoteControlConfig {
+            command_topic: "controller_commands".to_string(),
+            audit_topic: "controller_audit".to_string(),
+            snapshot_topic: "controller_snapshots".to_string(),
+            secret: secret.into(),
+            max_age: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets the command and audit topics
+    pub fn with_topics(
+        mut self,
+        command_topic: impl Into<String>,
+        audit_topic: impl Into<String>,
+    ) -> Self {
+        self.command_topic = command_topic.into();
+        self.audit_topic = audit_topic.into();
+        self
+    }
+
+    /// Sets the topic requested snapshots are published to
+    pub fn with_snapshot_topic(mut self, snapshot_topic: impl Into<String>) -> Self {
+        self.snapshot_topic = snapshot_topic.into();
+        self
+    }
+
+    /// Sets how old a command may be when it arrives
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+}
+
+impl std::fmt::Debug for RemoteControlConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteControlConfig")
+            .field("command_topic", &self.command_topic)
+            .field("audit_topic", &self.audit_topic)
+            .field("snapshot_topic", &self.snapshot_topic)
+            .field("secret", &"<redacted>")
+            .field("max_age", &self.max_age)
+            .finish()
+    }
+}
+
+/// What a [`CommandValidator`] made of one message
+#[derive(Debug, PartialEq)]
+pub(crate) enum Verdict {
+    /// Addressed to another controller, which audits it
+    Ignored,
+    /// Authentic, fresh, and not seen before
+    Accepted(RemoteCommand),
+    /// Refused, with the command as claimed if it could be decoded
+    Rejected(Option<RemoteCommand>, &'static str),
+}
+
+/// Checks that incoming commands are addressed, signed, fresh, and unique
+pub(crate) struct CommandValidator {
+    controller_id: String,
+    secret: Vec<u8>,
+    max_age: Duration,
+    seen: VecDeque<String>,
+}
+
+impl CommandValidator {
+    const SEEN_CAPACITY: usize = 256;
+
+    pub(crate) fn new(controller_id: &str, config: &RemoteControlConfig) -> Self {
+        CommandValidator {
+            controller_id: controller_id.to_string(),
+            secret: config.secret.clone(),
+            max_age: config.max_age,
+            seen: VecDeque::new(),
+        }
+    }
+
+    /// Checks the signed `message` as received at `now_ms`
+    ///
+    /// The signature is verified before the command is decoded. A message
+    /// that fails is decoded anyway, unauthenticated, only to tell whether it
+    /// is this controller's to reject: commands for other controllers share
+    /// the topic, possibly under other secrets, and are not ours to audit.
+    pub(crate) fn validate(&mut self, message: &[u8], now_ms: u64) -> Verdict {
+        let verified = if self.secret.is_empty() {
+            Err("no signing secret configured")
+        } else {
+            RemoteCommand::from_signed(message, &self.secret)
+        };
+        let command = match verified {
+            Ok(command) => command,
+            Err(reason) => {
+                let claimed = split_signed(message)
+                    .and_then(|(_, json)| serde_json::from_slice::<RemoteCommand>(json).ok());
+                return match claimed {
+                    Some(claimed) if claimed.controller_id != self.controller_id => {
+                        Verdict::Ignored
+                    }
+                    claimed => Verdict::Rejected(claimed, reason),
+                };
+            }
+        };
+
+        if command.controller_id != self.controller_id {
+            return Verdict::Ignored;
+        }
+        let max_age_ms = u64::try_from(self.max_age.as_millis()).unwrap_or(u64::MAX);
+        if now_ms.saturating_sub(command.timestamp) > max_age_ms {
+            return Verdict::Rejected(Some(command), "command expired");
+        }
+        if command.timestamp > now_ms.saturating_add(max_age_ms) {
+            return Verdict::Rejected(Some(command), "command timestamp is in the future");
+        }
+        if self.seen.contains(&command.id) {
+            return Verdict::Rejected(Some(command), "duplicate command id");
+        }
+        if self.seen.len() == Self::SEEN_CAPACITY {
+            self.seen.pop_front();
+        }
+        self.seen.push_back(command.id.clone());
+        Verdict::Accepted(command)
+    }
+}
+
+/// Messages the listener publishes on behalf of the controller
+enum Outgoing {
+    Audit(AuditEvent),
+    Snapshot(Box<ControllerSnapshot>),
+}
+
+/// The debugger's end of the command channel
+pub(crate) struct RemoteLink {
+    controller_id: String,
+    inbox: Arc<Mutex<VecDeque<RemoteCommand>>>,
+    outgoing: Sender<Outgoing>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RemoteLink {
+    /// Starts the listener thread for `remote` on the debug stream
+    pub(crate) fn spawn(config: &DebugConfig, remote: RemoteControlConfig) -> Self {
+        let inbox = Arc::new(Mutex::new(VecDeque::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (outgoing, outgoing_rx) = mpsc::channel();
+
+        let listener = Listener {
+            validator: CommandValidator::new(&config.controller_id, &remote),
+            config: config.clone(),
+            remote,
+            inbox: Arc::clone(&inbox),
+            outgoing: outgoing.clone(),
+            outgoing_rx,
+            stop: Arc::clone(&stop),
+        };
+        thread::spawn(move || listener.run());
+
+        RemoteLink {
+            controller_id: config.controller_id.clone(),
+            inbox,
+            outgoing,
+            stop,
+        }
+    }
+
+    /// Validated commands waiting to be applied, oldest first
+    pub(crate) fn take_commands(&self) -> Vec<RemoteCommand> {
+        let mut inbox = self.inbox.lock().unwrap_or_else(|e| e.into_inner());
+        inbox.drain(..).collect()
+    }
+
+    /// Reports how a command was handled
+    pub(crate) fn audit(&self, command: &RemoteCommand, outcome: CommandOutcome) {
+        let _ = self.outgoing.send(Outgoing::Audit(AuditEvent::new(
+            &self.controller_id,
+            command,
+            outcome,
+        )));
+    }
+
+    /// Publishes a requested snapshot
+    pub(crate) fn publish_snapshot(&self, snapshot: ControllerSnapshot) {
+        let _ = self.outgoing.send(Outgoing::Snapshot(Box::new(snapshot)));
+    }
+}
+
+impl Drop for RemoteLink {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Listener {
+    config: DebugConfig,
+    remote: RemoteControlConfig,
+    validator: CommandValidator,
+    inbox: Arc<Mutex<VecDeque<RemoteCommand>>>,
+    outgoing: Sender<Outgoing>,
+    outgoing_rx: Receiver<Outgoing>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Listener {
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    fn run(mut self) {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                error!("Failed to create tokio runtime for remote control: {}", e);
+                return;
+            }
+        };
+        let (Ok(stream), Ok(topic), Ok(consumer_id)) = (
+            Identifier::from_str(&self.config.stream_name),
+            Identifier::from_str(&self.remote.command_topic),
+            Identifier::named(&self.config.controller_id),
+        ) else {
+            error!("Invalid stream, command topic, or controller ID for remote control");
+            return;
+        };
+        let consumer = Consumer::new(consumer_id);
+
+        let mut failures = 0;
+        while !self.stop.load(Ordering::Relaxed) {
+            let connected = runtime.block_on(async {
+                let client = iggy_client(&self.config).await?;
+                let stream = &self.config.stream_name;
+                let audit = iggy_producer(&client, stream, &self.remote.audit_topic).await?;
+                let snapshots = iggy_producer(&client, stream, &self.remote.snapshot_topic).await?;
+                Some((client, audit, snapshots))
+            });
+            let Some((client, audit, snapshots)) = connected else {
+                failures += 1;
+                thread::sleep(self.config.reconnect.backoff_after(failures));
+                continue;
+            };
+            failures = 0;
+            info!(
+                "Accepting remote commands on topic '{}'",
+                self.remote.command_topic
+            );
+
+            while !self.stop.load(Ordering::Relaxed) {
+                let polled = runtime.block_on(client.poll_messages(
+                    &stream,
+                    &topic,
+                    None,
+                    &consumer,
+                    &PollingStrategy::next(),
+                    16,
+                    true,
+                ));
+                match polled {
+                    Ok(polled) => {
+                        for message in polled.messages {
+                            self.receive(&message.payload);
+                        }
+                    }
+                    // The topic is created by the first command producer
+                    Err(iggy::error::IggyError::TopicNameNotFound(..))
+                    | Err(iggy::error::IggyError::TopicIdNotFound(..)) => {}
+                    Err(e) => {
+                        warn!("Failed to poll remote commands, reconnecting: {}", e);
+                        break;
+                    }
+                }
+
+                let mut events = Vec::new();
+                let mut requested = Vec::new();
+                for outgoing in self.outgoing_rx.try_iter() {
+                    let (messages, payload) = match &outgoing {
+                        Outgoing::Audit(event) => (&mut events, serde_json::to_vec(event)),
+                        Outgoing::Snapshot(snapshot) => {
+                            (&mut requested, serde_json::to_vec(snapshot))
+                        }
+                    };
+                    if let Ok(payload) = payload {
+                        messages.push(Message::new(None, payload.into(), None));
+                    }
+                }
+                let published = runtime.block_on(async {
+                    if !events.is_empty() {
+                        audit.send(events).await?;
+                    }
+                    if !requested.is_empty() {
+                        snapshots.send(requested).await?;
+                    }
+                    Ok::<_, iggy::error::IggyError>(())
+                });
+                if let Err(e) = published {
+                    warn!(
+                        "Failed to publish audit events or snapshots, reconnecting: {}",
+                        e
+                    );
+                    break;
+                }
+
+                thread::sleep(Self::POLL_INTERVAL);
+            }
+        }
+    }
+
+    fn receive(&mut self, message: &[u8]) {
+        let controller_id = &self.config.controller_id;
+        let event = match self.validator.validate(message, unix_millis()) {
+            Verdict::Ignored => return,
+            Verdict::Accepted(command) => {
+                self.inbox
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push_back(command);
+                return;
+            }
+            Verdict::Rejected(Some(command), reason) => AuditEvent::new(
+                controller_id,
+                &command,
+                CommandOutcome::Rejected {
+                    reason: reason.to_string(),
+                },
+            ),
+            Verdict::Rejected(None, reason) => {
+                AuditEvent::undecodable(controller_id, reason.to_string())
+            }
+        };
+        let _ = self.outgoing.send(Outgoing::Audit(event));
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// HMAC-SHA256 of `message` under `key`
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,143 @@
+use crate::controller::ControllerStatistics;
+use crate::response::{bin_of, Bin, SetpointResponse};
+
+/// Seconds spent in each of a set of equal-width bins over `[low, high)`.
+///
+/// Every step adds its `dt` to the bin its value falls in, so the bins sum
+/// to the loop time covered rather than a sample count, and an irregular
+/// loop is not skewed towards its fast stretches.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Histogram {
+    /// Lower edge of the first bin.
+    pub low: f64,
+    /// Upper edge of the last bin.
+    pub high: f64,
+    /// Seconds spent in each bin, lowest first.
+    pub bins: Vec<f64>,
+    /// Seconds spent below `low`.
+    pub below: f64,
+    /// Seconds spent at or above `high`.
+    pub above: f64,
+}
+
+impl Histogram {
+    /// Bins in the histograms [`PidController`](crate::PidController)
+    /// statistics keep.
+    pub const BINS: usize = 10;
+
+    pub(crate) fn new(low: f64, high: f64, bins: usize) -> Self {
+        Histogram {
+            low,
+            high,
+            bins: vec![0.0; bins],
+            below: 0.0,
+            above: 0.0,
+        }
+    }
+
+    /// Width of each bin.
+    pub fn bin_width(&self) -> f64 {
+        (self.high - self.low) / self.bins.len() as f64
+    }
+
+    /// Seconds counted in every bin, including those outside the range.
+    pub fn total(&self) -> f64 {
+        self.below + self.above + self.bins.iter().sum::<f64>()
+    }
+
+    pub(crate) fn covers(&self, low: f64, high: f64) -> bool {
+        self.low == low && self.high == high
+    }
+
+    pub(crate) fn record(&mut self, value: f64, dt: f64) {
+        match bin_of(self.low, self.high, self.bins.len(), value) {
+            Bin::Below => self.below += dt,
+            Bin::At(bin) => self.bins[bin] += dt,
+            Bin::Above => self.above += dt,
+        }
+    }
+}
+
+/// Serializable summary of a [`ControllerStatistics`], from
+/// [`to_report`](ControllerStatistics::to_report).
+///
+/// Unlike the statistics themselves it never holds NaN or a placeholder:
+/// a time that has not happened yet is `None`, so the report survives a
+/// round trip through JSON and reads the same to a person as to a script.
+/// With the `serde` feature, write it out at shutdown or attach it to a
+/// bug report.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidController};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(0.5)
+///     .with_setpoint(10.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = PidController::new(config);
+///
+/// let mut level = 0.0;
+/// for _ in 0..200 {
+///     level += controller.compute(level, 0.1).unwrap() * 0.1;
+/// }
+///
+/// let report = controller.get_statistics().to_report();
+/// assert!(report.settling_time.is_some());
+/// assert_eq!(report.responses.len(), 1);
+/// assert!((report.error_histogram.total() - report.elapsed).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatisticsReport {
+    /// Loop time covered, in seconds since the last reset.
+    pub elapsed: f64,
+    /// Time-weighted mean absolute error.
+    pub average_error: f64,
+    /// Largest absolute error.
+    pub max_overshoot: f64,
+    /// Until the error first entered the settled threshold, if it has.
+    pub rise_time: Option<f64>,
+    /// Until the error entered the settled threshold for good, if it is
+    /// there now.
+    pub settling_time: Option<f64>,
+    /// Integral of the absolute error.
+    pub iae: f64,
+    /// Integral of the squared error.
+    pub ise: f64,
+    /// Integral of the absolute error weighted by time since the reset.
+    pub itae: f64,
+    /// Time-integral of the output's magnitude.
+    pub output_total: f64,
+    /// Times the output total has wrapped past its rollover limit.
+    pub output_total_rollovers: u64,
+    /// One entry per setpoint, oldest first.
+    pub responses: Vec<SetpointResponse>,
+    /// Time spent at each error.
+    pub error_histogram: Histogram,
+    /// Time spent at each output, with finite output limits.
+    pub output_histogram: Option<Histogram>,
+}
+
+impl ControllerStatistics {
+    /// Summarizes these statistics in a [`StatisticsReport`].
+    pub fn to_report(&self) -> StatisticsReport {
+        StatisticsReport {
+            elapsed: self.elapsed,
+            average_error: self.average_error,
+            max_overshoot: self.max_overshoot,
+            rise_time: (!self.rise_time.is_nan()).then_some(self.rise_time),
+            settling_time: self.settled.then_some(self.settling_time),
+            iae: self.iae,
+            ise: self.ise,
+            itae: self.itae,
+            output_total: self.output_total,
+            output_total_rollovers: self.output_total_rollovers,
+            responses: self.responses.clone(),
+            error_histogram: self.error_histogram.clone(),
+            output_histogram: self.output_histogram.clone(),
+        }
+    }
+}
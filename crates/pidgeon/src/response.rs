@@ -0,0 +1,100 @@
+/// How the process responded to one setpoint, from when it took effect
+/// until the next change or now.
+///
+/// Times are loop time in seconds since the setpoint took effect. Kept by
+/// `ControllerStatistics` with `std`, and by `BoundedStatistics` in
+/// fixed-capacity storage without it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetpointResponse {
+    /// Loop time since the last reset at which the setpoint took effect.
+    pub start: f64,
+    /// Setpoint before the change; equal to `setpoint` for the first
+    /// response after a reset.
+    pub from: f64,
+    /// The setpoint responded to.
+    pub setpoint: f64,
+    /// Until the error first entered the settled threshold, if it has.
+    pub rise_time: Option<f64>,
+    /// Until the error entered the settled threshold for good, if it is
+    /// there now.
+    pub settling_time: Option<f64>,
+    /// Furthest the process value went past the setpoint in the direction
+    /// it was moving, in process units.
+    pub max_overshoot: f64,
+    /// Integral of the absolute error over the response.
+    pub iae: f64,
+    /// Loop time the response has lasted.
+    pub duration: f64,
+}
+
+#[cfg(any(feature = "std", feature = "heapless"))]
+impl SetpointResponse {
+    /// Opens a response to `setpoint` at loop time `start`, following
+    /// `previous` if there was one. Also returns the direction of the step
+    /// (`1.0`, `-1.0`, or `0.0` with nowhere to go): the first setpoint's is
+    /// wherever the process starts.
+    pub(crate) fn open(
+        start: f64,
+        previous: Option<f64>,
+        setpoint: f64,
+        process_value: f64,
+    ) -> (Self, f64) {
+        let from = previous.unwrap_or(setpoint);
+        let step = if from != setpoint {
+            setpoint - from
+        } else {
+            setpoint - process_value
+        };
+        let direction = if step == 0.0 { 0.0 } else { step.signum() };
+        let response = SetpointResponse {
+            start,
+            from,
+            setpoint,
+            rise_time: None,
+            settling_time: None,
+            max_overshoot: 0.0,
+            iae: 0.0,
+            duration: 0.0,
+        };
+        (response, direction)
+    }
+
+    /// Accounts for a step of `dt` seconds ending at `process_value`.
+    pub(crate) fn update(&mut self, process_value: f64, direction: f64, threshold: f64, dt: f64) {
+        let error = self.setpoint - process_value;
+        self.duration += dt;
+        self.iae += error.abs() * dt;
+        self.max_overshoot = self
+            .max_overshoot
+            .max((process_value - self.setpoint) * direction);
+        if error.abs() <= threshold {
+            self.rise_time.get_or_insert(self.duration);
+            self.settling_time.get_or_insert(self.duration);
+        } else {
+            self.settling_time = None;
+        }
+    }
+}
+
+/// Where a value falls in a histogram of equal-width bins over `[low, high)`.
+#[cfg(any(feature = "std", feature = "heapless"))]
+pub(crate) enum Bin {
+    Below,
+    At(usize),
+    Above,
+}
+
+#[cfg(any(feature = "std", feature = "heapless"))]
+pub(crate) fn bin_of(low: f64, high: f64, bins: usize, value: f64) -> Bin {
+    if value < low {
+        Bin::Below
+    } else if value >= high {
+        Bin::Above
+    } else {
+        let bin = ((value - low) / ((high - low) / bins as f64)) as usize;
+        // Rounding can put a value just under `high` one past the end
+        Bin::At(bin.min(bins - 1))
+    }
+}
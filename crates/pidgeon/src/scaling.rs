@@ -0,0 +1,122 @@
+use crate::error::PidError;
+
+/// Linear map between a signal's raw counts, its engineering units, and
+/// percent of its range, as for a transmitter read through an ADC or a
+/// valve driven through a DAC.
+///
+/// Given to [`with_process_scaling`](crate::ControllerConfigBuilder::with_process_scaling)
+/// and [`with_output_scaling`](crate::ControllerConfigBuilder::with_output_scaling),
+/// the engineering range turns the gains into the industry's `%/%` terms,
+/// and the raw range lets [`PidController::compute_raw`](crate::PidController::compute_raw)
+/// take counts in and hand counts out. A raw range running downwards, such
+/// as a reverse-acting valve at full open on zero counts, is fine.
+///
+/// ```
+/// use pidgeon::Scaling;
+///
+/// // 4-20 mA on a 16-bit input, reading 0-150 °C
+/// let probe = Scaling::from_raw(13_107.0, 65_535.0, 0.0, 150.0).unwrap();
+/// assert_eq!(probe.to_engineering(39_321.0), 75.0);
+/// assert_eq!(probe.to_percent(75.0), 50.0);
+/// assert_eq!(probe.to_raw(150.0), 65_535.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Scaling {
+    raw_low: f64,
+    raw_high: f64,
+    low: f64,
+    high: f64,
+}
+
+impl Scaling {
+    /// Engineering range `low..high` with no separate raw form; raw values
+    /// are the engineering values.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if either bound is non-finite
+    /// or `low >= high`.
+    pub fn new(low: f64, high: f64) -> Result<Self, PidError> {
+        Self::from_raw(low, high, low, high)
+    }
+
+    /// Maps raw `raw_low` to engineering `low` and `raw_high` to `high`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if any bound is non-finite,
+    /// `low >= high`, or the raw bounds are equal.
+    pub fn from_raw(raw_low: f64, raw_high: f64, low: f64, high: f64) -> Result<Self, PidError> {
+        let scaling = Self {
+            raw_low,
+            raw_high,
+            low,
+            high,
+        };
+        scaling.check()?;
+        Ok(scaling)
+    }
+
+    /// Validates the bounds, including after deserialization.
+    pub(crate) fn check(&self) -> Result<(), PidError> {
+        if !self.low.is_finite() || !self.high.is_finite() || self.low >= self.high {
+            return Err(PidError::InvalidParameter(
+                "scaling range must be finite with low below high",
+            ));
+        }
+        if !self.raw_low.is_finite() || !self.raw_high.is_finite() || self.raw_low == self.raw_high
+        {
+            return Err(PidError::InvalidParameter(
+                "scaling raw range must be finite and non-empty",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Converts a raw reading into engineering units.
+    pub fn to_engineering(&self, raw: f64) -> f64 {
+        self.low + (raw - self.raw_low) * (self.high - self.low) / (self.raw_high - self.raw_low)
+    }
+
+    /// Converts engineering units into the raw value that represents them.
+    pub fn to_raw(&self, value: f64) -> f64 {
+        self.raw_low + (value - self.low) * (self.raw_high - self.raw_low) / (self.high - self.low)
+    }
+
+    /// Where `value` lies in the range, from 0 % at `low` to 100 % at `high`.
+    pub fn to_percent(&self, value: f64) -> f64 {
+        (value - self.low) * 100.0 / (self.high - self.low)
+    }
+
+    /// Engineering value at `percent` of the range.
+    pub fn from_percent(&self, percent: f64) -> f64 {
+        self.low + percent * (self.high - self.low) / 100.0
+    }
+
+    /// Bottom of the engineering range.
+    pub fn low(&self) -> f64 {
+        self.low
+    }
+
+    /// Top of the engineering range.
+    pub fn high(&self) -> f64 {
+        self.high
+    }
+
+    /// Width of the engineering range, `high - low`.
+    pub fn span(&self) -> f64 {
+        self.high - self.low
+    }
+
+    /// Raw value at the bottom of the range.
+    pub fn raw_low(&self) -> f64 {
+        self.raw_low
+    }
+
+    /// Raw value at the top of the range.
+    pub fn raw_high(&self) -> f64 {
+        self.raw_high
+    }
+}
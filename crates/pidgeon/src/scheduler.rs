@@ -0,0 +1,302 @@
+use crate::error::PidError;
+
+use crate::time::{Duration, Instant};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+type LoopTask = Box<dyn FnMut(f64) -> Result<(), PidError> + Send>;
+
+/// Execution-time accounting for a single scheduled loop.
+///
+/// Obtain via [`LoopScheduler::timing`] or [`LoopScheduler::timings`].
+#[derive(Debug, Clone, Default)]
+pub struct LoopTiming {
+    /// Number of times the loop body has run.
+    pub runs: u64,
+    /// Number of runs whose task returned an error.
+    pub errors: u64,
+    /// Number of deadlines skipped because the scheduler fell behind by more
+    /// than one period.
+    pub missed_deadlines: u64,
+    /// Wall-clock time spent in the most recent run.
+    pub last_exec_time: Duration,
+    /// Longest wall-clock time spent in a single run.
+    pub max_exec_time: Duration,
+    /// Total wall-clock time spent across all runs.
+    pub total_exec_time: Duration,
+}
+
+impl LoopTiming {
+    /// Mean execution time per run, or zero if the loop has not run yet.
+    pub fn average_exec_time(&self) -> Duration {
+        if self.runs == 0 {
+            Duration::ZERO
+        } else {
+            self.total_exec_time / self.runs as u32
+        }
+    }
+}
+
+struct ScheduledLoop {
+    name: String,
+    period: Duration,
+    phase: Option<Duration>,
+    task: LoopTask,
+    next_due: Option<Instant>,
+    last_run: Option<Instant>,
+    timing: LoopTiming,
+}
+
+/// Runs many control loops from one thread at independent rates.
+///
+/// Each loop has its own period and an optional phase offset relative to the
+/// scheduler's start time. Loops registered without an explicit phase are
+/// spread evenly across the shortest registered period, so a set of loops
+/// sharing a rate do not all fire in the same instant and contend for the CPU
+/// or a shared bus.
+///
+/// The task closure receives the elapsed time in seconds since its previous
+/// run (the nominal period on the first run), which can be passed directly as
+/// `dt` to [`PidController::compute`](crate::PidController::compute).
+///
+/// The scheduler can be driven by the caller with [`run_pending`](Self::run_pending),
+/// or left to block the current thread with [`run_until`](Self::run_until).
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, LoopScheduler, ThreadSafePidController};
+/// use std::time::{Duration, Instant};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_setpoint(10.0)
+///     .with_output_limits(-100.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let controller = ThreadSafePidController::new(config);
+///
+/// let mut scheduler = LoopScheduler::new();
+/// let handle = controller.clone();
+/// scheduler
+///     .add_loop("fast", Duration::from_millis(10), move |dt| {
+///         handle.compute(8.0, dt).map(|_| ())
+///     })
+///     .unwrap();
+///
+/// let start = Instant::now();
+/// scheduler.start(start);
+/// scheduler.run_pending(start + Duration::from_millis(35));
+///
+/// assert_eq!(scheduler.timing("fast").unwrap().runs, 1);
+/// ```
+#[derive(Default)]
+pub struct LoopScheduler {
+    loops: Vec<ScheduledLoop>,
+}
+
+impl LoopScheduler {
+    /// Creates an empty scheduler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a loop that runs every `period`. Its phase offset is chosen
+    /// automatically when the scheduler starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `period` is zero or a loop
+    /// with the same name is already registered.
+    pub fn add_loop<F>(
+        &mut self,
+        name: impl Into<String>,
+        period: Duration,
+        task: F,
+    ) -> Result<(), PidError>
+    where
+        F: FnMut(f64) -> Result<(), PidError> + Send + 'static,
+    {
+        self.push_loop(name.into(), period, None, Box::new(task))
+    }
+
+    /// Registers a loop that runs every `period`, first firing `phase` after
+    /// the scheduler starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `period` is zero, `phase` is
+    /// not shorter than `period`, or a loop with the same name is already
+    /// registered.
+    pub fn add_loop_with_phase<F>(
+        &mut self,
+        name: impl Into<String>,
+        period: Duration,
+        phase: Duration,
+        task: F,
+    ) -> Result<(), PidError>
+    where
+        F: FnMut(f64) -> Result<(), PidError> + Send + 'static,
+    {
+        if phase >= period {
+            return Err(PidError::InvalidParameter(
+                "phase offset must be shorter than the loop period",
+            ));
+        }
+        self.push_loop(name.into(), period, Some(phase), Box::new(task))
+    }
+
+    fn push_loop(
+        &mut self,
+        name: String,
+        period: Duration,
+        phase: Option<Duration>,
+        task: LoopTask,
+    ) -> Result<(), PidError> {
+        if period.is_zero() {
+            return Err(PidError::InvalidParameter(
+                "loop period must be greater than zero",
+            ));
+        }
+        if self.loops.iter().any(|l| l.name == name) {
+            return Err(PidError::InvalidParameter(
+                "a loop with this name is already registered",
+            ));
+        }
+        self.loops.push(ScheduledLoop {
+            name,
+            period,
+            phase,
+            task,
+            next_due: None,
+            last_run: None,
+            timing: LoopTiming::default(),
+        });
+        Ok(())
+    }
+
+    /// Number of registered loops.
+    pub fn len(&self) -> usize {
+        self.loops.len()
+    }
+
+    /// Returns `true` if no loops are registered.
+    pub fn is_empty(&self) -> bool {
+        self.loops.is_empty()
+    }
+
+    /// Anchors every loop's schedule at `now` and assigns phase offsets to
+    /// loops registered without one.
+    ///
+    /// Automatic offsets are `i * min_period / n` for the `n` loops without an
+    /// explicit phase, where `min_period` is the shortest period among them.
+    /// Calling `start` again restarts the schedule; timing statistics are kept.
+    pub fn start(&mut self, now: Instant) {
+        let auto: Vec<usize> = (0..self.loops.len())
+            .filter(|&i| self.loops[i].phase.is_none())
+            .collect();
+        let min_period = auto.iter().map(|&i| self.loops[i].period).min();
+
+        for l in self.loops.iter_mut() {
+            l.last_run = None;
+            if let Some(phase) = l.phase {
+                l.next_due = Some(now + phase);
+            }
+        }
+        if let Some(min_period) = min_period {
+            let slot = min_period / auto.len() as u32;
+            for (k, &i) in auto.iter().enumerate() {
+                self.loops[i].next_due = Some(now + slot * k as u32);
+            }
+        }
+    }
+
+    /// Runs every loop whose deadline is at or before `now`, in registration
+    /// order, and returns how many ran. Starts the scheduler at `now` if
+    /// [`start`](Self::start) has not been called.
+    ///
+    /// A loop that has fallen more than one period behind runs once and
+    /// skips the missed deadlines, which are counted in
+    /// [`LoopTiming::missed_deadlines`]. Its original phase is preserved.
+    pub fn run_pending(&mut self, now: Instant) -> usize {
+        if self.loops.iter().any(|l| l.next_due.is_none()) {
+            self.start(now);
+        }
+
+        let mut ran = 0;
+        for l in self.loops.iter_mut() {
+            let Some(due) = l.next_due else { continue };
+            if due > now {
+                continue;
+            }
+
+            let dt = match l.last_run {
+                Some(last) => now.duration_since(last).as_secs_f64(),
+                None => l.period.as_secs_f64(),
+            };
+
+            let exec_start = Instant::now();
+            let result = (l.task)(dt);
+            let exec_time = exec_start.elapsed();
+
+            l.timing.runs += 1;
+            if result.is_err() {
+                l.timing.errors += 1;
+            }
+            l.timing.last_exec_time = exec_time;
+            l.timing.total_exec_time += exec_time;
+            if exec_time > l.timing.max_exec_time {
+                l.timing.max_exec_time = exec_time;
+            }
+
+            let mut next = due + l.period;
+            while next <= now {
+                next += l.period;
+                l.timing.missed_deadlines += 1;
+            }
+            l.next_due = Some(next);
+            l.last_run = Some(now);
+            ran += 1;
+        }
+        ran
+    }
+
+    /// Earliest upcoming deadline across all loops, or `None` if the
+    /// scheduler has not started or has no loops.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.loops.iter().filter_map(|l| l.next_due).min()
+    }
+
+    /// Blocks the current thread, running loops on schedule until `stop` is set.
+    ///
+    /// Sleeps until the next deadline between runs. `stop` is checked once
+    /// per wake-up, so shutdown latency is bounded by the shortest period.
+    pub fn run_until(&mut self, stop: &AtomicBool) {
+        self.start(Instant::now());
+        while !stop.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            self.run_pending(now);
+            if let Some(deadline) = self.next_deadline() {
+                let now = Instant::now();
+                if deadline > now {
+                    std::thread::sleep(deadline - now);
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Returns the execution-time accounting for the loop named `name`.
+    pub fn timing(&self, name: &str) -> Option<&LoopTiming> {
+        self.loops
+            .iter()
+            .find(|l| l.name == name)
+            .map(|l| &l.timing)
+    }
+
+    /// Iterates over `(name, timing)` pairs in registration order.
+    pub fn timings(&self) -> impl Iterator<Item = (&str, &LoopTiming)> {
+        self.loops.iter().map(|l| (l.name.as_str(), &l.timing))
+    }
+}
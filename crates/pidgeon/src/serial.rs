@@ -0,0 +1,388 @@
+use core::fmt::{self, Write};
+use core::str::FromStr;
+
+use crate::config::ControllerConfig;
+use crate::enums::Gain;
+use crate::error::PidError;
+use crate::state::PidState;
+
+/// Longest line, newline included, that [`SerialMessage::encode`] writes and
+/// [`SerialReader`] accepts.
+pub const SERIAL_LINE_MAX: usize = 128;
+
+/// Controller telemetry and commands as newline-terminated ASCII lines.
+///
+/// Each line is a lowercase keyword followed by space-separated decimal
+/// values, so the protocol can be read and typed in a plain serial terminal
+/// as well as spoken by `pidgeon-cli serial`:
+///
+/// | Line | Message | Sent by |
+/// |------|---------|---------|
+/// | `tel <process value> <setpoint> <output> <error>` | [`Telemetry`](Self::Telemetry) | controller |
+/// | `gains <kp> <ki> <kd>` | [`Gains`](Self::Gains) | controller |
+/// | `nak` | [`Rejected`](Self::Rejected) | controller |
+/// | `sp <setpoint>` | [`SetSetpoint`](Self::SetSetpoint) | host |
+/// | `kp <value>`, `ki <value>`, `kd <value>` | [`SetGain`](Self::SetGain) | host |
+/// | `get` | [`GetGains`](Self::GetGains) | host |
+/// | `reset` | [`Reset`](Self::Reset) | host |
+///
+/// A controller answers every command with `gains`, or `nak` if it rejected
+/// it, so the host can confirm each change. A trailing `\r` is ignored.
+///
+/// # Examples
+///
+/// Firmware feeds received bytes to a [`SerialReader`], applies commands to
+/// the configuration and state it passes to [`pid_compute`](crate::pid_compute),
+/// and writes replies and telemetry back:
+///
+/// ```
+/// use pidgeon::{pid_compute, ControllerConfig, PidState, SerialMessage, SerialReader};
+///
+/// let mut config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut state = PidState::default();
+/// let mut reader = SerialReader::new();
+/// let mut line = [0u8; pidgeon::SERIAL_LINE_MAX];
+///
+/// // Bytes from the UART
+/// for &byte in b"kp 2.5\r\n" {
+///     if let Some(Ok(command)) = reader.push(byte) {
+///         let reply = match command.apply(&mut config, &mut state) {
+///             Ok(()) => SerialMessage::gains(&config),
+///             Err(_) => SerialMessage::Rejected,
+///         };
+///         let len = reply.encode(&mut line).unwrap();
+///         assert_eq!(&line[..len], b"gains 2.5 0 0\n");
+///     }
+/// }
+///
+/// let (_, next) = pid_compute(&config, &state, 45.0, 0.01).unwrap();
+/// state = next;
+/// let telemetry = SerialMessage::telemetry(&config, &state, 45.0);
+/// let len = telemetry.encode(&mut line).unwrap();
+/// assert_eq!(&line[..len], b"tel 45 50 12.5 5\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SerialMessage {
+    /// Telemetry for one cycle.
+    Telemetry {
+        /// Measured process value.
+        process_value: f64,
+        /// Setpoint in effect.
+        setpoint: f64,
+        /// Clamped controller output.
+        output: f64,
+        /// Error after deadband.
+        error: f64,
+    },
+    /// The gains in effect, sent in answer to a command.
+    Gains {
+        /// Proportional gain.
+        kp: f64,
+        /// Integral gain.
+        ki: f64,
+        /// Derivative gain.
+        kd: f64,
+    },
+    /// The last command was rejected and nothing changed.
+    Rejected,
+    /// Command: move the setpoint.
+    SetSetpoint {
+        /// New setpoint.
+        setpoint: f64,
+    },
+    /// Command: change one gain.
+    SetGain {
+        /// Which gain to change.
+        gain: Gain,
+        /// New value.
+        value: f64,
+    },
+    /// Command: report the gains without changing anything.
+    GetGains,
+    /// Command: clear the controller's state.
+    Reset,
+}
+
+impl SerialMessage {
+    /// `true` for messages sent to a controller rather than by it.
+    pub fn is_command(&self) -> bool {
+        matches!(
+            self,
+            SerialMessage::SetSetpoint { .. }
+                | SerialMessage::SetGain { .. }
+                | SerialMessage::GetGains
+                | SerialMessage::Reset
+        )
+    }
+
+    /// Builds the [`Telemetry`](Self::Telemetry) for one cycle, from the
+    /// configuration and the state returned by [`pid_compute`](crate::pid_compute).
+    pub fn telemetry(config: &ControllerConfig, state: &PidState, process_value: f64) -> Self {
+        SerialMessage::Telemetry {
+            process_value,
+            setpoint: config.setpoint,
+            output: state.last_output,
+            error: state.prev_error,
+        }
+    }
+
+    /// Builds the [`Gains`](Self::Gains) reply for `config`.
+    pub fn gains(config: &ControllerConfig) -> Self {
+        SerialMessage::Gains {
+            kp: config.kp,
+            ki: config.ki,
+            kd: config.kd,
+        }
+    }
+
+    /// Applies a command to a configuration and state driven by
+    /// [`pid_compute`](crate::pid_compute). [`Reset`](Self::Reset) clears
+    /// `state`; [`GetGains`](Self::GetGains) changes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a value is not finite, a
+    /// setpoint or gain is rejected by the config's
+    /// [`setpoint_limits`](ControllerConfig::setpoint_limits) or
+    /// [`gain_limits`](ControllerConfig::gain_limits), or `self` is not a
+    /// command. Nothing is changed in that case.
+    pub fn apply(
+        &self,
+        config: &mut ControllerConfig,
+        state: &mut PidState,
+    ) -> Result<(), PidError> {
+        match *self {
+            SerialMessage::SetSetpoint { setpoint } => {
+                config.setpoint = config.limit_setpoint(setpoint)?;
+            }
+            SerialMessage::SetGain { gain, value } => {
+                let value = config.check_gain(gain, value)?;
+                match gain {
+                    Gain::Kp => config.kp = value,
+                    Gain::Ki => config.ki = value,
+                    Gain::Kd => config.kd = value,
+                }
+            }
+            SerialMessage::Reset => *state = PidState::new(config),
+            SerialMessage::GetGains => {}
+            SerialMessage::Telemetry { .. }
+            | SerialMessage::Gains { .. }
+            | SerialMessage::Rejected => {
+                return Err(PidError::InvalidParameter(
+                    "serial replies cannot be applied as a command",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the message and a trailing newline into `buf`, returning the
+    /// number of bytes written. A buffer of [`SERIAL_LINE_MAX`] bytes always
+    /// fits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `buf` is too small.
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, PidError> {
+        let mut writer = SliceWriter { buf, len: 0 };
+        writeln!(writer, "{}", self)
+            .map_err(|_| PidError::InvalidParameter("buffer too small for the serial line"))?;
+        Ok(writer.len)
+    }
+}
+
+/// Formats the line without its trailing newline.
+impl fmt::Display for SerialMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SerialMessage::Telemetry {
+                process_value,
+                setpoint,
+                output,
+                error,
+            } => write!(
+                f,
+                "tel {} {} {} {}",
+                Number(process_value),
+                Number(setpoint),
+                Number(output),
+                Number(error)
+            ),
+            SerialMessage::Gains { kp, ki, kd } => {
+                write!(f, "gains {} {} {}", Number(kp), Number(ki), Number(kd))
+            }
+            SerialMessage::Rejected => f.write_str("nak"),
+            SerialMessage::SetSetpoint { setpoint } => write!(f, "sp {}", Number(setpoint)),
+            SerialMessage::SetGain { gain, value } => {
+                let keyword = match gain {
+                    Gain::Kp => "kp",
+                    Gain::Ki => "ki",
+                    Gain::Kd => "kd",
+                };
+                write!(f, "{} {}", keyword, Number(value))
+            }
+            SerialMessage::GetGains => f.write_str("get"),
+            SerialMessage::Reset => f.write_str("reset"),
+        }
+    }
+}
+
+/// Parses one line, with or without its line ending.
+impl FromStr for SerialMessage {
+    type Err = PidError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_ascii_whitespace();
+        let keyword = words
+            .next()
+            .ok_or(PidError::InvalidParameter("serial line is empty"))?;
+        let mut value = || {
+            let word = words
+                .next()
+                .ok_or(PidError::InvalidParameter("serial line is missing a value"))?;
+            let value: f64 = word.parse().map_err(|_| {
+                PidError::InvalidParameter("serial line has a value that is not a number")
+            })?;
+            finite(value)
+        };
+
+        let message = match keyword {
+            "tel" => SerialMessage::Telemetry {
+                process_value: value()?,
+                setpoint: value()?,
+                output: value()?,
+                error: value()?,
+            },
+            "gains" => SerialMessage::Gains {
+                kp: value()?,
+                ki: value()?,
+                kd: value()?,
+            },
+            "nak" => SerialMessage::Rejected,
+            "sp" => SerialMessage::SetSetpoint { setpoint: value()? },
+            "kp" | "ki" | "kd" => SerialMessage::SetGain {
+                gain: match keyword {
+                    "kp" => Gain::Kp,
+                    "ki" => Gain::Ki,
+                    _ => Gain::Kd,
+                },
+                value: value()?,
+            },
+            "get" => SerialMessage::GetGains,
+            "reset" => SerialMessage::Reset,
+            _ => {
+                return Err(PidError::InvalidParameter(
+                    "serial line starts with an unknown keyword",
+                ))
+            }
+        };
+        if words.next().is_some() {
+            return Err(PidError::InvalidParameter(
+                "serial line has too many values",
+            ));
+        }
+        Ok(message)
+    }
+}
+
+/// Assembles [`SerialMessage`]s from a byte stream, such as a UART receive
+/// interrupt or a host's serial port, without allocating.
+///
+/// Lines longer than [`SERIAL_LINE_MAX`] are dropped and reported as errors
+/// once their newline arrives, after which reading carries on normally.
+#[derive(Debug, Clone)]
+pub struct SerialReader {
+    buf: [u8; SERIAL_LINE_MAX],
+    len: usize,
+    overflowed: bool,
+}
+
+impl SerialReader {
+    /// Creates an empty reader.
+    pub const fn new() -> Self {
+        SerialReader {
+            buf: [0; SERIAL_LINE_MAX],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Adds one byte. Returns the parsed line, or why it could not be
+    /// parsed, when `byte` ends a line that is not blank.
+    pub fn push(&mut self, byte: u8) -> Option<Result<SerialMessage, PidError>> {
+        if byte != b'\n' {
+            if self.len < self.buf.len() {
+                self.buf[self.len] = byte;
+                self.len += 1;
+            } else {
+                self.overflowed = true;
+            }
+            return None;
+        }
+
+        let len = core::mem::take(&mut self.len);
+        if core::mem::take(&mut self.overflowed) {
+            return Some(Err(PidError::InvalidParameter("serial line is too long")));
+        }
+        match core::str::from_utf8(&self.buf[..len]) {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(line.parse()),
+            Err(_) => Some(Err(PidError::InvalidParameter("serial line is not ASCII"))),
+        }
+    }
+}
+
+impl Default for SerialReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn finite(value: f64) -> Result<f64, PidError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(PidError::InvalidParameter(
+            "serial line carries a non-finite value",
+        ))
+    }
+}
+
+/// Shortest text that parses back to the same value, in exponent notation
+/// when plain decimals would run long.
+struct Number(f64);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magnitude = self.0.abs();
+        if magnitude == 0.0 || (1e-6..1e9).contains(&magnitude) {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "{:e}", self.0)
+        }
+    }
+}
+
+/// `fmt::Write` into a byte slice, failing when it is full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+use core::ops::BitOr;
+
+use crate::error::PidError;
+use crate::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::traits::Controller;
+
+const DAY: i64 = 24 * 60 * 60;
+const WEEK: i64 = 7 * DAY;
+
+/// Days of the week a [`ScheduleEntry`] applies on. Combine with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    /// Monday.
+    pub const MONDAY: Self = Self(1);
+    /// Tuesday.
+    pub const TUESDAY: Self = Self(1 << 1);
+    /// Wednesday.
+    pub const WEDNESDAY: Self = Self(1 << 2);
+    /// Thursday.
+    pub const THURSDAY: Self = Self(1 << 3);
+    /// Friday.
+    pub const FRIDAY: Self = Self(1 << 4);
+    /// Saturday.
+    pub const SATURDAY: Self = Self(1 << 5);
+    /// Sunday.
+    pub const SUNDAY: Self = Self(1 << 6);
+    /// Monday to Friday.
+    pub const WORKDAYS: Self = Self(0b001_1111);
+    /// Saturday and Sunday.
+    pub const WEEKEND: Self = Self(0b110_0000);
+    /// All seven days.
+    pub const EVERY_DAY: Self = Self(0b111_1111);
+
+    /// Whether every day in `days` is included.
+    pub fn contains(self, days: Weekdays) -> bool {
+        self.0 & days.0 == days.0
+    }
+
+    /// Days from Monday (0) to Sunday (6) that are included.
+    fn indices(self) -> impl Iterator<Item = i64> {
+        (0..7).filter(move |day| self.0 & (1 << day) != 0)
+    }
+}
+
+impl BitOr for Weekdays {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// One change of setpoint in a [`SetpointSchedule`]: from `start` on each of
+/// `days`, the setpoint is `setpoint` until the next entry takes over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScheduleEntry {
+    /// Days the entry fires on.
+    pub days: Weekdays,
+    /// Seconds after local midnight.
+    pub start: u32,
+    /// Setpoint from then on.
+    pub setpoint: f64,
+}
+
+/// The setpoint a [`SetpointSchedule`] asks for at a given moment, and
+/// until when.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveSetpoint {
+    /// Setpoint to apply.
+    pub setpoint: f64,
+    /// Whether it comes from a hold rather than the schedule.
+    pub held: bool,
+    /// When it next changes by itself: the next schedule entry, or the end
+    /// of the hold. `None` for an indefinite hold.
+    pub until: Option<SystemTime>,
+}
+
+/// A manual override, with its expiry in Unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Hold {
+    setpoint: f64,
+    until: Option<i64>,
+}
+
+/// Weekly setpoint program driven by the wall clock, such as a thermostat
+/// that sets back overnight and at weekends.
+///
+/// Entries change the setpoint at a local time of day on chosen days; each
+/// one holds until the next fires, wrapping around the week, so the
+/// program's last change on Sunday night carries into Monday morning. A
+/// hold overrides the program, indefinitely, until a given time, or until
+/// the next scheduled change, the way a thermostat's temporary override
+/// does. [`apply`](Self::apply) pushes the active setpoint to any
+/// [`Controller`] whenever it changes; call it from the control loop.
+///
+/// Times are local through a fixed [`utc_offset`](Self::utc_offset);
+/// update it when daylight saving starts or ends. With the `serde` feature
+/// the whole schedule, hold included, can be saved and restored.
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use pidgeon::{SetpointSchedule, Weekdays};
+///
+/// let mut schedule = SetpointSchedule::new();
+/// schedule.add(Weekdays::WORKDAYS, 6, 30, 21.0).unwrap();
+/// schedule.add(Weekdays::WORKDAYS, 22, 0, 16.0).unwrap();
+/// schedule.add(Weekdays::WEEKEND, 8, 0, 21.0).unwrap();
+/// schedule.add(Weekdays::WEEKEND, 23, 0, 16.0).unwrap();
+///
+/// // Monday 5 January 2026, 07:00 UTC
+/// let monday_morning = UNIX_EPOCH + Duration::from_secs(1_767_596_400);
+/// assert_eq!(schedule.setpoint_at(monday_morning), Some(21.0));
+/// // 05:00: still Sunday night's setback
+/// let before_dawn = monday_morning - Duration::from_secs(2 * 3600);
+/// assert_eq!(schedule.setpoint_at(before_dawn), Some(16.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetpointSchedule {
+    entries: Vec<ScheduleEntry>,
+    /// Seconds local time is ahead of UTC
+    utc_offset: i32,
+    hold: Option<Hold>,
+}
+
+impl SetpointSchedule {
+    /// Creates an empty schedule on UTC.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry changing the setpoint to `setpoint` at `hour:minute`
+    /// local time on `days`. Where two entries fire at the same time, the
+    /// one added later wins.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `days` is empty, the time
+    /// is not a valid time of day, or `setpoint` is non-finite.
+    pub fn add(
+        &mut self,
+        days: Weekdays,
+        hour: u8,
+        minute: u8,
+        setpoint: f64,
+    ) -> Result<(), PidError> {
+        if days.0 == 0 {
+            return Err(PidError::InvalidParameter(
+                "schedule entry must apply on at least one day",
+            ));
+        }
+        if hour > 23 || minute > 59 {
+            return Err(PidError::InvalidParameter(
+                "schedule entry time must be a valid time of day",
+            ));
+        }
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        self.entries.push(ScheduleEntry {
+            days,
+            start: u32::from(hour) * 3600 + u32::from(minute) * 60,
+            setpoint,
+        });
+        Ok(())
+    }
+
+    /// Removes and returns the entry at `index`, in the order added.
+    pub fn remove(&mut self, index: usize) -> Option<ScheduleEntry> {
+        (index < self.entries.len()).then(|| self.entries.remove(index))
+    }
+
+    /// Removes every entry. A hold stays in place.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The entries, in the order added.
+    pub fn entries(&self) -> &[ScheduleEntry] {
+        &self.entries
+    }
+
+    /// Seconds local time is ahead of UTC.
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset
+    }
+
+    /// Sets the seconds local time is ahead of UTC, e.g. `3600` for CET.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `seconds` is more than 18
+    /// hours either way.
+    pub fn set_utc_offset(&mut self, seconds: i32) -> Result<(), PidError> {
+        if seconds.unsigned_abs() > 18 * 3600 {
+            return Err(PidError::InvalidParameter(
+                "utc_offset must be within 18 hours of UTC",
+            ));
+        }
+        self.utc_offset = seconds;
+        Ok(())
+    }
+
+    /// Overrides the schedule with `setpoint` until `until`, or
+    /// indefinitely for `None`. Replaces any earlier hold.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn hold(&mut self, setpoint: f64, until: Option<SystemTime>) -> Result<(), PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        self.hold = Some(Hold {
+            setpoint,
+            until: until.map(unix_seconds),
+        });
+        Ok(())
+    }
+
+    /// Overrides the schedule with `setpoint` until the next entry after
+    /// `now` fires; indefinitely if there are none.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn hold_until_next_change(
+        &mut self,
+        setpoint: f64,
+        now: SystemTime,
+    ) -> Result<(), PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        self.hold = Some(Hold {
+            setpoint,
+            until: self.scheduled(unix_seconds(now)).map(|(_, next)| next),
+        });
+        Ok(())
+    }
+
+    /// Ends any hold, handing the setpoint back to the schedule.
+    pub fn resume(&mut self) {
+        self.hold = None;
+    }
+
+    /// What the schedule asks for at `now`, or `None` if it has no entries
+    /// and no hold.
+    pub fn active(&self, now: SystemTime) -> Option<ActiveSetpoint> {
+        let now = unix_seconds(now);
+        if let Some(hold) = self
+            .hold
+            .filter(|hold| hold.until.is_none_or(|end| now < end))
+        {
+            return Some(ActiveSetpoint {
+                setpoint: hold.setpoint,
+                held: true,
+                until: hold.until.map(from_unix_seconds),
+            });
+        }
+        self.scheduled(now).map(|(setpoint, next)| ActiveSetpoint {
+            setpoint,
+            held: false,
+            until: Some(from_unix_seconds(next)),
+        })
+    }
+
+    /// The setpoint [`active`](Self::active) at `now`.
+    pub fn setpoint_at(&self, now: SystemTime) -> Option<f64> {
+        self.active(now).map(|active| active.setpoint)
+    }
+
+    /// Sets `controller`'s setpoint to the one active at `now` if it
+    /// differs, dropping a hold that has run out. Returns the new setpoint
+    /// when it changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns whatever the controller's
+    /// [`set_setpoint`](Controller::set_setpoint) returns.
+    pub fn apply<C: Controller + ?Sized>(
+        &mut self,
+        controller: &mut C,
+        now: SystemTime,
+    ) -> Result<Option<f64>, PidError> {
+        let seconds = unix_seconds(now);
+        if self
+            .hold
+            .is_some_and(|hold| hold.until.is_some_and(|end| seconds >= end))
+        {
+            self.hold = None;
+        }
+        match self.setpoint_at(now) {
+            Some(setpoint) if setpoint != controller.setpoint() => {
+                controller.set_setpoint(setpoint)?;
+                Ok(Some(setpoint))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Scheduled setpoint at `now` and the Unix time of the next change.
+    fn scheduled(&self, now: i64) -> Option<(f64, i64)> {
+        // Every change in the week, as seconds after local Monday midnight
+        let mut changes: Vec<(i64, f64)> = self
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .days
+                    .indices()
+                    .map(move |day| (day * DAY + i64::from(entry.start), entry.setpoint))
+            })
+            .collect();
+        // Stable, so a later entry at the same time sorts after
+        changes.sort_by_key(|&(at, _)| at);
+
+        let local = now + i64::from(self.utc_offset);
+        // 1 January 1970 was a Thursday
+        let week_start =
+            now - ((local.div_euclid(DAY) + 3).rem_euclid(7) * DAY + local.rem_euclid(DAY));
+        let into_week = now - week_start;
+
+        let current = changes.iter().rev().find(|&&(at, _)| at <= into_week);
+        let (_, setpoint) = current.or(changes.last())?;
+        let next = match changes.iter().find(|&&(at, _)| at > into_week) {
+            Some(&(at, _)) => week_start + at,
+            None => week_start + WEEK + changes[0].0,
+        };
+        Some((*setpoint, next))
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    }
+}
+
+fn from_unix_seconds(seconds: i64) -> SystemTime {
+    if seconds >= 0 {
+        UNIX_EPOCH + Duration::from_secs(seconds as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+    }
+}
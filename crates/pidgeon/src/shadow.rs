@@ -0,0 +1,282 @@
+use std::collections::VecDeque;
+
+use crate::config::PidGains;
+use crate::controller::PidController;
+use crate::error::PidError;
+use crate::tuning::FopdtModel;
+
+/// One step of a [`ShadowRunner`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShadowStep {
+    /// Seconds since the runner started, at the start of the step.
+    pub time: f64,
+    /// Seconds the step covered.
+    pub dt: f64,
+    /// Setpoint both controllers worked toward.
+    pub setpoint: f64,
+    /// Measured process value, fed to the live controller.
+    pub process_value: f64,
+    /// Process value the candidate was fed: the measurement plus the
+    /// model's prediction of what its outputs would have changed. Equal to
+    /// `process_value` without a model.
+    pub predicted_process_value: f64,
+    /// Output of the live controller, the one actuated.
+    pub live_output: f64,
+    /// Output of the candidate, discarded.
+    pub candidate_output: f64,
+}
+
+/// Comparison of a live and a candidate controller, from
+/// [`ShadowRunner::report`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShadowReport {
+    /// Steps compared.
+    pub steps: usize,
+    /// Loop time covered, in seconds.
+    pub elapsed: f64,
+    /// Integral of the live loop's absolute error.
+    pub live_iae: f64,
+    /// Integral of the absolute error the candidate is predicted to have
+    /// given, if the runner has a model.
+    pub candidate_iae: Option<f64>,
+    /// Fraction of the live IAE the candidate is predicted to remove:
+    /// positive if it would have done better, negative if worse. `None`
+    /// without a model or while the live IAE is zero.
+    pub iae_improvement: Option<f64>,
+    /// Time-weighted mean of candidate output minus live output.
+    pub mean_output_delta: f64,
+    /// Time-weighted root mean square of the output difference.
+    pub rms_output_delta: f64,
+    /// Largest absolute output difference.
+    pub max_output_delta: f64,
+}
+
+/// Runs a candidate controller in the shadow of a live one, for A/B tuning
+/// on a running process without ever actuating the candidate.
+///
+/// Every [`step`](Self::step) feeds the measurement to both controllers,
+/// records both outputs, and returns only the live one. Start the candidate
+/// as a [`fork`](PidController::fork) of the live controller with new
+/// gains, [`from_gains`](Self::from_gains), so the two differ in nothing
+/// else, and read the comparison from [`report`](Self::report).
+///
+/// Fed the same measurements, the candidate only shows what it would have
+/// output, not how the process would have answered. Give the runner a
+/// [`FopdtModel`] of the process, [`with_model`](Self::with_model), and it
+/// predicts that answer from the difference between the outputs, feeds the
+/// candidate the predicted process value instead, and reports the
+/// candidate's predicted IAE.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, FopdtModel, PidController, PidGains, ShadowRunner};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(0.5)
+///     .with_ki(0.01)
+///     .with_setpoint(50.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let model = FopdtModel::new(2.0, 20.0, 2.0).unwrap();
+/// let candidate = PidGains { kp: 0.5, ki: 0.025, kd: 0.0 };
+/// let mut shadow = ShadowRunner::from_gains(PidController::new(config), candidate)
+///     .unwrap()
+///     .with_model(model);
+///
+/// // The live loop runs a simulated process; only the live output reaches it
+/// let decay = (-1.0f64 / 20.0).exp();
+/// let mut outputs = vec![0.0; 2];
+/// let mut level = 0.0;
+/// for _ in 0..300 {
+///     outputs.push(shadow.step(level, 1.0).unwrap());
+///     let applied = outputs[outputs.len() - 3];
+///     level = decay * level + (1.0 - decay) * 2.0 * applied;
+/// }
+///
+/// let report = shadow.report();
+/// assert_eq!(report.steps, 300);
+/// assert!(report.iae_improvement.unwrap() > 0.0);
+/// let promoted = shadow.promote();
+/// assert_eq!(promoted.config().ki(), 0.025);
+/// ```
+pub struct ShadowRunner {
+    live: PidController,
+    candidate: PidController,
+    model: Option<FopdtModel>,
+    /// Output differences not yet through the model's dead time, with the
+    /// time each was made
+    pending: VecDeque<(f64, f64)>,
+    /// Predicted change in the process value had the candidate been in
+    /// control
+    deviation: f64,
+    elapsed: f64,
+    steps: Vec<ShadowStep>,
+}
+
+impl ShadowRunner {
+    /// Runs `candidate` in the shadow of `live`.
+    pub fn new(live: PidController, candidate: PidController) -> Self {
+        ShadowRunner {
+            live,
+            candidate,
+            model: None,
+            pending: VecDeque::new(),
+            deviation: 0.0,
+            elapsed: 0.0,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Runs a [`fork`](PidController::fork) of `live` with `gains` in its
+    /// shadow.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a gain is non-finite or
+    /// outside the live config's
+    /// [`gain_limits`](crate::ControllerConfig::gain_limits).
+    pub fn from_gains(live: PidController, gains: PidGains) -> Result<Self, PidError> {
+        let mut candidate = live.fork();
+        candidate.set_kp(gains.kp)?;
+        candidate.set_ki(gains.ki)?;
+        candidate.set_kd(gains.kd)?;
+        Ok(Self::new(live, candidate))
+    }
+
+    /// Predicts the process's answer to the candidate's outputs with
+    /// `model`. Default: none.
+    pub fn with_model(mut self, model: FopdtModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Runs both controllers on `process_value` and returns the live output,
+    /// the only one to actuate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` is
+    /// non-finite or `dt` is non-finite or non-positive. Neither controller
+    /// is stepped in that case.
+    pub fn step(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        let live_output = self.live.compute(process_value, dt)?;
+        let predicted_process_value = process_value + self.deviation;
+        let candidate_output = self.candidate.compute(predicted_process_value, dt)?;
+        self.steps.push(ShadowStep {
+            time: self.elapsed,
+            dt,
+            setpoint: self.live.setpoint(),
+            process_value,
+            predicted_process_value,
+            live_output,
+            candidate_output,
+        });
+        self.advance(candidate_output - live_output, dt);
+        Ok(live_output)
+    }
+
+    /// Moves the model's prediction on by `dt` after an output difference
+    /// of `delta`
+    fn advance(&mut self, delta: f64, dt: f64) {
+        if let Some(model) = self.model {
+            self.pending.push_back((self.elapsed, delta));
+            let due = self.elapsed - model.dead_time + 1e-9;
+            while self.pending.get(1).is_some_and(|&(time, _)| time <= due) {
+                self.pending.pop_front();
+            }
+            let applied = match self.pending.front() {
+                Some(&(time, delta)) if time <= due => delta,
+                _ => 0.0,
+            };
+            let decay = (-dt / model.time_constant).exp();
+            self.deviation = decay * self.deviation + model.gain * (1.0 - decay) * applied;
+        }
+        self.elapsed += dt;
+    }
+
+    /// Changes the setpoint of both controllers.
+    ///
+    /// # Errors
+    ///
+    /// Whatever [`PidController::set_setpoint`] returns for either; the
+    /// live controller is changed first.
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        self.live.set_setpoint(setpoint)?;
+        self.candidate.set_setpoint(setpoint)
+    }
+
+    /// The live controller.
+    pub fn live(&self) -> &PidController {
+        &self.live
+    }
+
+    /// The live controller, for changes only it should see, such as an
+    /// interlock.
+    pub fn live_mut(&mut self) -> &mut PidController {
+        &mut self.live
+    }
+
+    /// The candidate controller.
+    pub fn candidate(&self) -> &PidController {
+        &self.candidate
+    }
+
+    /// The candidate controller, to adjust it mid-run.
+    pub fn candidate_mut(&mut self) -> &mut PidController {
+        &mut self.candidate
+    }
+
+    /// Every step so far, oldest first.
+    pub fn steps(&self) -> &[ShadowStep] {
+        &self.steps
+    }
+
+    /// Compares the controllers over every step so far.
+    pub fn report(&self) -> ShadowReport {
+        let mut elapsed = 0.0;
+        let mut live_iae = 0.0;
+        let mut candidate_iae = 0.0;
+        let mut delta_sum = 0.0;
+        let mut delta_squares = 0.0;
+        let mut max_output_delta: f64 = 0.0;
+        for step in &self.steps {
+            let delta = step.candidate_output - step.live_output;
+            elapsed += step.dt;
+            live_iae += (step.setpoint - step.process_value).abs() * step.dt;
+            candidate_iae += (step.setpoint - step.predicted_process_value).abs() * step.dt;
+            delta_sum += delta * step.dt;
+            delta_squares += delta * delta * step.dt;
+            max_output_delta = max_output_delta.max(delta.abs());
+        }
+        let candidate_iae = self.model.map(|_| candidate_iae);
+        let mean = |sum: f64| if elapsed > 0.0 { sum / elapsed } else { 0.0 };
+        ShadowReport {
+            steps: self.steps.len(),
+            elapsed,
+            live_iae,
+            candidate_iae,
+            iae_improvement: candidate_iae
+                .filter(|_| live_iae > 0.0)
+                .map(|candidate_iae| (live_iae - candidate_iae) / live_iae),
+            mean_output_delta: mean(delta_sum),
+            rms_output_delta: mean(delta_squares).sqrt(),
+            max_output_delta,
+        }
+    }
+
+    /// Ends the run and returns the live controller with the candidate's
+    /// config, gains included. The live state carries on, so an integral
+    /// gain change is bumpless while a proportional one moves the output
+    /// by the change times the current error.
+    pub fn promote(mut self) -> PidController {
+        self.live.replace_config(self.candidate.config().clone());
+        self.live
+    }
+
+    /// Ends the run and returns the live controller unchanged.
+    pub fn into_live(self) -> PidController {
+        self.live
+    }
+}
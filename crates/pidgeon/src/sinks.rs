@@ -0,0 +1,441 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use crate::debug::{ControllerDebugData, ControllerMode};
+use crate::time::{Duration, Instant};
+
+/// Destination for debug samples produced by a
+/// [`ControllerDebugger`](crate::ControllerDebugger).
+///
+/// Sinks run on the debugger's background thread, never on the control
+/// loop's thread, so a slow sink delays telemetry but not control. Attach
+/// sinks with [`DebugConfig::with_sink`](crate::DebugConfig::with_sink).
+pub trait DebugSink: Send {
+    /// Records one sample.
+    fn write(&mut self, data: &ControllerDebugData) -> io::Result<()>;
+
+    /// Flushes any buffered samples. Called when the sample queue drains.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A sink shared between a [`DebugConfig`](crate::DebugConfig) and the
+/// debugger thread.
+pub type SharedDebugSink = Arc<Mutex<dyn DebugSink>>;
+
+/// On-disk format for [`FileSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// Comma-separated values with a header row at the top of every file.
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl FileFormat {
+    const CSV_HEADER: &'static str =
+        "schema_version,timestamp,controller_id,setpoint,process_value,error,output,p_term,i_term,d_term,dt,kp,ki,kd,saturated,mode";
+
+    fn write_header(self, out: &mut impl Write) -> io::Result<u64> {
+        match self {
+            FileFormat::Csv => {
+                writeln!(out, "{}", Self::CSV_HEADER)?;
+                Ok(Self::CSV_HEADER.len() as u64 + 1)
+            }
+            FileFormat::JsonLines => Ok(0),
+        }
+    }
+
+    fn encode(self, data: &ControllerDebugData) -> io::Result<String> {
+        match self {
+            FileFormat::Csv => Ok(format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                data.schema_version,
+                data.timestamp,
+                csv_escape(&data.controller_id),
+                data.setpoint,
+                data.process_value,
+                data.error,
+                data.output,
+                data.p_term,
+                data.i_term,
+                data.d_term,
+                data.dt,
+                data.kp,
+                data.ki,
+                data.kd,
+                data.saturated,
+                match data.mode {
+                    ControllerMode::Automatic => "automatic",
+                    ControllerMode::Manual => "manual",
+                },
+            )),
+            FileFormat::JsonLines => serde_json::to_string(data).map_err(io::Error::other),
+        }
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Appends debug samples to a CSV or JSON-lines file, rotating by size or age.
+///
+/// The active file is always `path`. On rotation it is renamed to
+/// `<stem>.1.<ext>`, any existing `<stem>.1.<ext>` becomes `<stem>.2.<ext>`,
+/// and so on; files beyond [`with_max_files`](Self::with_max_files) are
+/// deleted. With neither a size nor an age limit the file grows without bound.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pidgeon::{DebugConfig, FileFormat, FileSink};
+/// use std::time::Duration;
+///
+/// let sink = FileSink::new("runs/oven.csv", FileFormat::Csv)
+///     .unwrap()
+///     .with_max_bytes(10 * 1024 * 1024)
+///     .with_max_age(Duration::from_secs(3600))
+///     .with_max_files(24);
+///
+/// let debug_config = DebugConfig::default().with_sink(sink);
+/// ```
+pub struct FileSink {
+    path: PathBuf,
+    format: FileFormat,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    max_files: usize,
+    writer: BufWriter<File>,
+    bytes_written: u64,
+    opened_at: Instant,
+}
+
+impl FileSink {
+    /// Opens (or creates) `path` for appending. Parent directories are
+    /// created as needed. Default: no rotation limits, 5 rotated files kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from creating directories or opening the file.
+    pub fn new(path: impl Into<PathBuf>, format: FileFormat) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let (writer, bytes_written) = Self::open(&path, format)?;
+        Ok(FileSink {
+            path,
+            format,
+            max_bytes: None,
+            max_age: None,
+            max_files: 5,
+            writer,
+            bytes_written,
+            opened_at: Instant::now(),
+        })
+    }
+
+    /// Rotates once the active file reaches `max_bytes`.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Rotates once the active file has been open for `max_age`.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Number of rotated files to keep besides the active one. `0` discards
+    /// the old file on every rotation.
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Path of the active file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(path: &Path, format: FileFormat) -> io::Result<(BufWriter<File>, u64)> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let existing = file.metadata()?.len();
+        let mut writer = BufWriter::new(file);
+        let header = if existing == 0 {
+            format.write_header(&mut writer)?
+        } else {
+            0
+        };
+        Ok((writer, existing + header))
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name = match self.path.extension() {
+            Some(ext) => format!("{}.{}.{}", stem, index, ext.to_string_lossy()),
+            None => format!("{}.{}", stem, index),
+        };
+        self.path.with_file_name(name)
+    }
+
+    fn needs_rotation(&self) -> bool {
+        self.max_bytes.is_some_and(|max| self.bytes_written >= max)
+            || self
+                .max_age
+                .is_some_and(|max| self.opened_at.elapsed() >= max)
+    }
+
+    /// Closes the active file, shifts rotated files, and opens a fresh one.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error from flushing, renaming, or reopening.
+    pub fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let oldest = self.rotated_path(self.max_files);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        let (writer, bytes_written) = Self::open(&self.path, self.format)?;
+        self.writer = writer;
+        self.bytes_written = bytes_written;
+        self.opened_at = Instant::now();
+        Ok(())
+    }
+}
+
+impl DebugSink for FileSink {
+    fn write(&mut self, data: &ControllerDebugData) -> io::Result<()> {
+        if self.needs_rotation() {
+            self.rotate()?;
+        }
+        let line = self.format.encode(data)?;
+        writeln!(self.writer, "{}", line)?;
+        self.bytes_written += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// Keeps the most recent samples in memory for inspection.
+///
+/// `RingBufferSink` is a cheap, cloneable handle: attach one clone to a
+/// [`DebugConfig`](crate::DebugConfig) and keep another to query. Once
+/// `capacity` samples are held, each new sample evicts the oldest. Intended
+/// for integration tests that assert on term trajectories without an Iggy
+/// server.
+///
+/// Samples arrive from the debugger's background thread, so tests should call
+/// [`wait_for`](Self::wait_for) before asserting.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pidgeon::{ControllerConfig, DebugConfig, PidController, RingBufferSink};
+/// use std::time::Duration;
+///
+/// let samples = RingBufferSink::new(1000);
+/// let config = ControllerConfig::builder().with_kp(2.0).with_setpoint(10.0).build().unwrap();
+/// let mut pid = PidController::new(config)
+///     .with_debugging(DebugConfig::default().with_sink(samples.clone()));
+///
+/// for _ in 0..100 {
+///     pid.compute(5.0, 0.1).unwrap();
+/// }
+///
+/// assert!(samples.wait_for(100, Duration::from_secs(1)));
+/// assert!(samples.p_terms().iter().all(|&p| p == 10.0));
+/// ```
+#[derive(Clone)]
+pub struct RingBufferSink {
+    shared: Arc<RingBuffer>,
+}
+
+struct RingBuffer {
+    capacity: usize,
+    state: Mutex<RingState>,
+    updated: Condvar,
+}
+
+#[derive(Default)]
+struct RingState {
+    samples: VecDeque<ControllerDebugData>,
+    total: u64,
+}
+
+impl RingBufferSink {
+    /// Creates an empty buffer holding at most `capacity` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "RingBufferSink capacity must be non-zero");
+        RingBufferSink {
+            shared: Arc::new(RingBuffer {
+                capacity,
+                state: Mutex::new(RingState {
+                    samples: VecDeque::with_capacity(capacity),
+                    total: 0,
+                }),
+                updated: Condvar::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, RingState> {
+        // A panicking writer cannot leave the deque half-updated
+        self.shared
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Maximum number of samples retained.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Number of samples currently retained.
+    pub fn len(&self) -> usize {
+        self.state().samples.len()
+    }
+
+    /// Returns `true` if no samples are retained.
+    pub fn is_empty(&self) -> bool {
+        self.state().samples.is_empty()
+    }
+
+    /// Total samples received since creation or the last [`clear`](Self::clear),
+    /// including evicted ones.
+    pub fn total_received(&self) -> u64 {
+        self.state().total
+    }
+
+    /// Copies of the retained samples, oldest first.
+    pub fn samples(&self) -> Vec<ControllerDebugData> {
+        self.state().samples.iter().cloned().collect()
+    }
+
+    /// The most recent sample, if any.
+    pub fn latest(&self) -> Option<ControllerDebugData> {
+        self.state().samples.back().cloned()
+    }
+
+    /// The retained samples for which `predicate` holds, oldest first.
+    pub fn filter(
+        &self,
+        mut predicate: impl FnMut(&ControllerDebugData) -> bool,
+    ) -> Vec<ControllerDebugData> {
+        self.state()
+            .samples
+            .iter()
+            .filter(|sample| predicate(sample))
+            .cloned()
+            .collect()
+    }
+
+    /// Projects one field (or any derived value) across the retained samples,
+    /// oldest first.
+    pub fn series(&self, field: impl Fn(&ControllerDebugData) -> f64) -> Vec<f64> {
+        self.state().samples.iter().map(field).collect()
+    }
+
+    /// Proportional term trajectory, oldest first.
+    pub fn p_terms(&self) -> Vec<f64> {
+        self.series(|sample| sample.p_term)
+    }
+
+    /// Integral term trajectory, oldest first.
+    pub fn i_terms(&self) -> Vec<f64> {
+        self.series(|sample| sample.i_term)
+    }
+
+    /// Derivative term trajectory, oldest first.
+    pub fn d_terms(&self) -> Vec<f64> {
+        self.series(|sample| sample.d_term)
+    }
+
+    /// Output trajectory, oldest first.
+    pub fn outputs(&self) -> Vec<f64> {
+        self.series(|sample| sample.output)
+    }
+
+    /// Error trajectory, oldest first.
+    pub fn errors(&self) -> Vec<f64> {
+        self.series(|sample| sample.error)
+    }
+
+    /// Drops all retained samples and resets [`total_received`](Self::total_received).
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.samples.clear();
+        state.total = 0;
+    }
+
+    /// Blocks until at least `count` samples have been received in total
+    /// (see [`total_received`](Self::total_received)) or `timeout` elapses.
+    /// Returns `true` if the count was reached.
+    pub fn wait_for(&self, count: u64, timeout: Duration) -> bool {
+        let state = self.state();
+        let (state, _) = self
+            .shared
+            .updated
+            .wait_timeout_while(state, timeout, |state| state.total < count)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.total >= count
+    }
+}
+
+impl DebugSink for RingBufferSink {
+    fn write(&mut self, data: &ControllerDebugData) -> io::Result<()> {
+        let mut state = self.state();
+        if state.samples.len() == self.shared.capacity {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(data.clone());
+        state.total += 1;
+        drop(state);
+        self.shared.updated.notify_all();
+        Ok(())
+    }
+}
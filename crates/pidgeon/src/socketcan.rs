@@ -0,0 +1,268 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+
+use crate::can::{CanCodec, CanFrame};
+use crate::thread_safe::ThreadSafePidController;
+
+/// Streams a controller's state over Linux SocketCAN and applies the
+/// commands addressed to it.
+///
+/// Opens a raw CAN socket bound to one interface, with a kernel filter that
+/// lets only the [`CanCodec`]'s command identifiers through. The socket is
+/// non-blocking: [`publish`](Self::publish) sends the telemetry frames for a
+/// cycle and [`apply_commands`](Self::apply_commands) drains whatever
+/// commands have arrived, so both fit in an existing control loop without a
+/// thread of their own. The socket is also exposed through [`AsFd`] for use
+/// with `poll` or an async reactor.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pidgeon::{CanCodec, ControllerConfig, SocketCanBridge, ThreadSafePidController};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(2.0)
+///     .with_output_limits(-100.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let controller = ThreadSafePidController::new(config);
+/// let bridge = SocketCanBridge::open("can0", CanCodec::standard(0x180).unwrap()).unwrap();
+///
+/// // Each cycle: take operator commands, compute, report
+/// bridge.apply_commands(&controller).unwrap();
+/// let process_value = 42.0; // read from sensor
+/// let output = controller.compute(process_value, 0.01).unwrap();
+/// bridge.publish(&controller, process_value).unwrap();
+/// // apply `output` to actuator
+/// ```
+pub struct SocketCanBridge {
+    socket: OwnedFd,
+    codec: CanCodec,
+}
+
+impl SocketCanBridge {
+    /// Opens a raw CAN socket on `interface`, such as `can0` or `vcan0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interface does not exist or the socket cannot
+    /// be created, filtered, or bound.
+    pub fn open(interface: &str, codec: CanCodec) -> io::Result<Self> {
+        let name = CString::new(interface).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "interface name contains a NUL byte",
+            )
+        })?;
+        // SAFETY: `name` is a valid NUL-terminated string.
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: plain socket creation; the descriptor is owned below.
+        let fd = unsafe {
+            libc::socket(
+                libc::PF_CAN,
+                libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::CAN_RAW,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a freshly created descriptor that nothing else owns.
+        let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // Only command frames with the codec's identifier format, and no
+        // remote-transmission requests
+        let (flag, mask) = if codec.is_extended() {
+            (libc::CAN_EFF_FLAG, libc::CAN_EFF_MASK)
+        } else {
+            (0, libc::CAN_SFF_MASK)
+        };
+        let filters = codec.command_ids().map(|id| libc::can_filter {
+            can_id: id | flag,
+            can_mask: libc::CAN_EFF_FLAG | libc::CAN_RTR_FLAG | mask,
+        });
+        // SAFETY: `filters` outlives the call and its size is passed alongside.
+        let result = unsafe {
+            libc::setsockopt(
+                fd,
+                libc::SOL_CAN_RAW,
+                libc::CAN_RAW_FILTER,
+                filters.as_ptr().cast(),
+                mem::size_of_val(&filters) as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: all-zero is a valid `sockaddr_can`.
+        let mut address: libc::sockaddr_can = unsafe { mem::zeroed() };
+        address.can_family = libc::AF_CAN as libc::sa_family_t;
+        address.can_ifindex = index as libc::c_int;
+        // SAFETY: `address` is a valid `sockaddr_can` and its size is passed alongside.
+        let result = unsafe {
+            libc::bind(
+                fd,
+                (&address as *const libc::sockaddr_can).cast(),
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(SocketCanBridge { socket, codec })
+    }
+
+    /// The codec frames are encoded and decoded with.
+    pub fn codec(&self) -> &CanCodec {
+        &self.codec
+    }
+
+    /// Sends one frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame cannot be queued, including
+    /// [`io::ErrorKind::WouldBlock`] when the transmit queue is full.
+    pub fn send(&self, frame: &CanFrame) -> io::Result<()> {
+        // SAFETY: all-zero is a valid `can_frame`.
+        let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+        raw.can_id = if frame.extended {
+            frame.id | libc::CAN_EFF_FLAG
+        } else {
+            frame.id
+        };
+        raw.can_dlc = frame.len.min(8);
+        raw.data = frame.data;
+
+        // SAFETY: `raw` is a valid `can_frame` and its size is passed alongside.
+        let written = unsafe {
+            libc::write(
+                self.socket.as_raw_fd(),
+                (&raw as *const libc::can_frame).cast(),
+                mem::size_of::<libc::can_frame>(),
+            )
+        };
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if written as usize != mem::size_of::<libc::can_frame>() {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "incomplete CAN frame written",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Receives one command frame, or `None` if none is waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from the socket fails.
+    pub fn recv(&self) -> io::Result<Option<CanFrame>> {
+        // SAFETY: all-zero is a valid `can_frame`.
+        let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+        // SAFETY: `raw` is writable and its size is passed alongside.
+        let read = unsafe {
+            libc::read(
+                self.socket.as_raw_fd(),
+                (&mut raw as *mut libc::can_frame).cast(),
+                mem::size_of::<libc::can_frame>(),
+            )
+        };
+        if read < 0 {
+            let error = io::Error::last_os_error();
+            return match error.kind() {
+                io::ErrorKind::WouldBlock => Ok(None),
+                _ => Err(error),
+            };
+        }
+        if read as usize != mem::size_of::<libc::can_frame>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "incomplete CAN frame read",
+            ));
+        }
+
+        let extended = raw.can_id & libc::CAN_EFF_FLAG != 0;
+        Ok(Some(CanFrame {
+            id: if extended {
+                raw.can_id & libc::CAN_EFF_MASK
+            } else {
+                raw.can_id & libc::CAN_SFF_MASK
+            },
+            extended,
+            len: raw.can_dlc.min(8),
+            data: raw.data,
+        }))
+    }
+
+    /// Sends `controller`'s [`Status`](crate::CanMessage::Status) and
+    /// [`Target`](crate::CanMessage::Target) frames for the cycle that just
+    /// computed with `process_value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the controller's mutex was poisoned or a frame
+    /// cannot be sent.
+    pub fn publish(
+        &self,
+        controller: &ThreadSafePidController,
+        process_value: f64,
+    ) -> io::Result<()> {
+        let frames = controller
+            .can_telemetry(&self.codec, process_value)
+            .map_err(io::Error::other)?;
+        for frame in &frames {
+            self.send(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Applies every command frame waiting on the socket to `controller`, and
+    /// returns how many were applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] for a malformed frame or a
+    /// rejected value, after applying the frames before it; the frames after
+    /// it are applied on the next call. Also returns an error if reading
+    /// fails.
+    pub fn apply_commands(&self, controller: &ThreadSafePidController) -> io::Result<usize> {
+        let mut applied = 0;
+        while let Some(frame) = self.recv()? {
+            let Some(message) = self
+                .codec
+                .decode(&frame)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            else {
+                continue;
+            };
+            controller
+                .apply_can_message(&message)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+}
+
+impl AsFd for SocketCanBridge {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
+
+impl AsRawFd for SocketCanBridge {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
@@ -1,10 +1,16 @@
+use crate::config::ControllerConfig;
+
 /// Persistent state carried between [`pid_compute`](crate::pid_compute) invocations.
 ///
 /// This struct is the "memory" of the controller. Pass it into [`pid_compute`](crate::pid_compute)
 /// and receive an updated copy back alongside the control output. Start with
-/// [`PidState::default()`] for a fresh controller.
+/// [`PidState::new`] (or [`PidState::default()`] when the config has no
+/// [`initial_output`](ControllerConfig::initial_output)) for a fresh controller.
 ///
 /// All fields are public to support serialization, checkpointing, and testing.
+/// The struct is `#[non_exhaustive]`, so new state can be added without a
+/// breaking change: outside this crate, start from [`PidState::new`] or
+/// [`PidState::default()`] and assign the fields to change.
 ///
 /// # Examples
 ///
@@ -22,6 +28,9 @@
 /// assert!(!next_state.first_run);
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub struct PidState {
     /// Accumulated integral contribution with Ki baked in: `sum(Ki * error * dt)`.
     ///
@@ -40,12 +49,47 @@ pub struct PidState {
     pub prev_filtered_derivative: f64,
     /// The clamped output from the most recent computation.
     pub last_output: f64,
+    /// Setpoint after the [setpoint filter](ControllerConfig::setpoint_filter),
+    /// as used for the most recent error. Left unchanged when the config has
+    /// no filter.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub filtered_setpoint: f64,
     /// `true` before the first call to [`pid_compute`](crate::pid_compute). On the first
     /// run, the derivative term is zero (no previous measurement exists) and the
     /// controller returns P+I rather than P+I+D.
     pub first_run: bool,
 }
 
+impl PidState {
+    /// Fresh state for `config`, with the integral preloaded to its
+    /// [`initial_output`](ControllerConfig::initial_output) if it has one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pidgeon::{ControllerConfig, PidState, pid_compute};
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_kp(2.0)
+    ///     .with_ki(0.5)
+    ///     .with_setpoint(10.0)
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .with_initial_output(42.0)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // At the setpoint, the first output is the preloaded one
+    /// let (output, _) = pid_compute(&config, &PidState::new(&config), 10.0, 0.01).unwrap();
+    /// assert_eq!(output, 42.0);
+    /// ```
+    pub fn new(config: &ControllerConfig) -> Self {
+        PidState {
+            integral_contribution: config.initial_output.unwrap_or(0.0),
+            ..PidState::default()
+        }
+    }
+}
+
 impl Default for PidState {
     fn default() -> Self {
         PidState {
@@ -54,6 +98,7 @@ impl Default for PidState {
             prev_measurement: 0.0,
             prev_filtered_derivative: 0.0,
             last_output: 0.0,
+            filtered_setpoint: 0.0,
             first_run: true,
         }
     }
@@ -0,0 +1,599 @@
+use crate::error::PidError;
+use crate::linalg::{self, Matrix};
+use crate::observer::LuenbergerObserver;
+use crate::traits::Controller;
+use crate::tuning::SecondOrderModel;
+
+/// Continuous-time linear process model with one input,
+/// `ẋ = A·x + B·u`, `y = C·x`.
+///
+/// `x` holds the process's internal states, `u` is the controller output,
+/// and `y` is the process value. Build one by hand, or convert an
+/// identified [`SecondOrderModel`], whose states are the process value and
+/// its rate of change:
+///
+/// ```
+/// use pidgeon::{SecondOrderModel, StateSpaceModel};
+///
+/// let plant = SecondOrderModel::new(2.0, 1.5, 0.3).unwrap();
+/// let model = StateSpaceModel::from(plant);
+/// assert_eq!(model.order(), 2);
+///
+/// // Optimal gains for a 10 ms loop, weighting position only
+/// let gains = model.lqr(&[1.0, 0.0], 0.1, 0.01).unwrap();
+/// assert!(gains[0] > 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateSpaceModel {
+    a: Matrix,
+    b: Vec<f64>,
+    c: Vec<f64>,
+}
+
+impl StateSpaceModel {
+    /// Creates a model from the rows of `A`, and `B` and `C` as vectors.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `A` is empty or not square,
+    /// `B` or `C` does not have one entry per state, or any entry is not
+    /// finite.
+    pub fn new(a: Vec<Vec<f64>>, b: Vec<f64>, c: Vec<f64>) -> Result<Self, PidError> {
+        let order = a.len();
+        if order == 0 || a.iter().any(|row| row.len() != order) {
+            return Err(PidError::InvalidParameter(
+                "A must be a non-empty square matrix",
+            ));
+        }
+        if b.len() != order || c.len() != order {
+            return Err(PidError::InvalidParameter(
+                "B and C must have one entry per state",
+            ));
+        }
+        if !a
+            .iter()
+            .flatten()
+            .chain(&b)
+            .chain(&c)
+            .all(|v| v.is_finite())
+        {
+            return Err(PidError::InvalidParameter(
+                "model entries must be finite numbers",
+            ));
+        }
+        Ok(Self { a, b, c })
+    }
+
+    /// Number of states.
+    pub fn order(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Rows of the state matrix `A`.
+    pub fn a(&self) -> &[Vec<f64>] {
+        &self.a
+    }
+
+    /// Input vector `B`.
+    pub fn b(&self) -> &[f64] {
+        &self.b
+    }
+
+    /// Output vector `C`.
+    pub fn c(&self) -> &[f64] {
+        &self.c
+    }
+
+    /// Exact zero-order-hold discretization for a sample time of `dt`:
+    /// `x[k+1] = Φ·x[k] + Γ·u[k]`.
+    pub(crate) fn discretize(&self, dt: f64) -> (Matrix, Vec<f64>) {
+        // e^([A B; 0 0]·dt) holds Φ and Γ in its top rows
+        let order = self.order();
+        let mut augmented: Matrix = self
+            .a
+            .iter()
+            .zip(&self.b)
+            .map(|(row, b)| row.iter().chain([b]).map(|v| v * dt).collect())
+            .collect();
+        augmented.push(vec![0.0; order + 1]);
+        let exponential = linalg::expm(&augmented);
+        let phi = exponential[..order]
+            .iter()
+            .map(|row| row[..order].to_vec())
+            .collect();
+        let gamma = exponential[..order].iter().map(|row| row[order]).collect();
+        (phi, gamma)
+    }
+
+    /// State and output that hold the process value at 1 in steady state;
+    /// `None` if the model cannot hold a steady process value.
+    pub(crate) fn steady_state(&self) -> Option<(Vec<f64>, f64)> {
+        // [A B; C 0]·[x; u] = [0; 1]
+        let order = self.order();
+        let mut system: Matrix = self
+            .a
+            .iter()
+            .zip(&self.b)
+            .map(|(row, b)| row.iter().chain([b]).copied().collect())
+            .collect();
+        system.push(self.c.iter().chain([&0.0]).copied().collect());
+        let inverse = linalg::inverse(&system)?;
+        let solution: Vec<f64> = inverse.iter().map(|row| row[order]).collect();
+        Some((solution[..order].to_vec(), solution[order]))
+    }
+
+    /// Linear-quadratic regulator gains for a loop sampled every `dt`
+    /// seconds.
+    ///
+    /// Returns `K` minimising `Σ xᵀQx + R·u²` under `u = −K·x`, where `Q`
+    /// is diagonal with `state_weights` and `R` is `input_weight`. Raising
+    /// a state's weight makes the loop fight harder to keep it at zero;
+    /// raising `input_weight` makes it gentler on the actuator. The
+    /// discrete Riccati equation is solved by the doubling algorithm, which
+    /// converges in a few dozen iterations even at fast sample rates.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `state_weights` does not
+    /// have one finite non-negative entry per state, `input_weight` or `dt`
+    /// is not finite and positive, or no stabilizing gain exists (the
+    /// weighted states cannot all be steered by the input).
+    pub fn lqr(
+        &self,
+        state_weights: &[f64],
+        input_weight: f64,
+        dt: f64,
+    ) -> Result<Vec<f64>, PidError> {
+        check_weights(self, state_weights, input_weight)?;
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+
+        let order = self.order();
+        let (phi, gamma) = self.discretize(dt);
+        let weights: Matrix = (0..order)
+            .map(|i| {
+                (0..order)
+                    .map(|j| if i == j { state_weights[i] } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        let no_solution =
+            PidError::InvalidParameter("no stabilizing LQR gain exists for these weights");
+
+        // Structure-preserving doubling: H converges to the Riccati solution
+        let mut a = phi.clone();
+        let mut g: Matrix = gamma
+            .iter()
+            .map(|x| gamma.iter().map(|y| x * y / input_weight).collect())
+            .collect();
+        let mut h = weights;
+        let identity = linalg::identity(order);
+        let mut converged = false;
+        for _ in 0..100 {
+            let inverse = linalg::inverse(&linalg::add(&identity, &linalg::mul(&g, &h)))
+                .ok_or(no_solution.clone())?;
+            let a_transposed = linalg::transpose(&a);
+            let a_inverse = linalg::mul(&a, &inverse);
+            let next_h = linalg::add(
+                &h,
+                &linalg::mul(&linalg::mul(&a_transposed, &h), &linalg::mul(&inverse, &a)),
+            );
+            let next_g = linalg::add(
+                &g,
+                &linalg::mul(&linalg::mul(&a_inverse, &g), &a_transposed),
+            );
+            a = linalg::mul(&a_inverse, &a);
+            let change = linalg::norm(&linalg::add(&next_h, &linalg::scale(&h, -1.0)));
+            h = next_h;
+            g = next_g;
+            if !change.is_finite() {
+                return Err(no_solution);
+            }
+            if change <= 1e-12 * linalg::norm(&h) {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(no_solution);
+        }
+
+        // K = (R + ΓᵀPΓ)⁻¹·ΓᵀPΦ
+        let p_gamma = linalg::mul_vec(&h, &gamma);
+        let denominator = input_weight + linalg::dot(&gamma, &p_gamma);
+        let gains: Vec<f64> = linalg::mul_vec(&linalg::transpose(&phi), &p_gamma)
+            .into_iter()
+            .map(|v| v / denominator)
+            .collect();
+        if gains.iter().all(|v| v.is_finite()) {
+            Ok(gains)
+        } else {
+            Err(no_solution)
+        }
+    }
+}
+
+impl From<SecondOrderModel> for StateSpaceModel {
+    /// States are the process value and its rate of change.
+    fn from(model: SecondOrderModel) -> Self {
+        let w = model.natural_frequency;
+        Self {
+            a: vec![vec![0.0, 1.0], vec![-w * w, -2.0 * model.damping_ratio * w]],
+            b: vec![0.0, model.gain * w * w],
+            c: vec![1.0, 0.0],
+        }
+    }
+}
+
+fn check_weights(
+    model: &StateSpaceModel,
+    state_weights: &[f64],
+    input_weight: f64,
+) -> Result<(), PidError> {
+    if state_weights.len() != model.order()
+        || !state_weights.iter().all(|w| w.is_finite() && *w >= 0.0)
+    {
+        return Err(PidError::InvalidParameter(
+            "state_weights must have one finite non-negative entry per state",
+        ));
+    }
+    if !input_weight.is_finite() || input_weight <= 0.0 {
+        return Err(PidError::InvalidParameter(
+            "input_weight must be a finite positive number",
+        ));
+    }
+    Ok(())
+}
+
+/// Validated configuration for a [`StateFeedbackController`].
+///
+/// | Parameter        | Default                                        |
+/// |------------------|------------------------------------------------|
+/// | `state_weights`  | `C` squared, i.e. weight the process value only |
+/// | `input_weight`   | 1.0                                            |
+/// | `setpoint`       | 0.0                                            |
+/// | `min_output`     | `-inf` (must be set)                           |
+/// | `max_output`     | `+inf` (must be set)                           |
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateFeedbackConfig {
+    pub(crate) model: StateSpaceModel,
+    pub(crate) state_weights: Vec<f64>,
+    pub(crate) input_weight: f64,
+    pub(crate) setpoint: f64,
+    pub(crate) min_output: f64,
+    pub(crate) max_output: f64,
+    /// Steady state and output per unit of setpoint
+    pub(crate) reference_state: Vec<f64>,
+    pub(crate) reference_output: f64,
+}
+
+/// Builder for [`StateFeedbackConfig`]. Call [`build`](Self::build) to
+/// validate.
+#[derive(Debug, Clone)]
+pub struct StateFeedbackConfigBuilder {
+    model: StateSpaceModel,
+    state_weights: Option<Vec<f64>>,
+    input_weight: f64,
+    setpoint: f64,
+    min_output: f64,
+    max_output: f64,
+}
+
+impl StateFeedbackConfig {
+    /// Returns a builder that designs its gains from `model`.
+    pub fn builder(model: impl Into<StateSpaceModel>) -> StateFeedbackConfigBuilder {
+        StateFeedbackConfigBuilder {
+            model: model.into(),
+            state_weights: None,
+            input_weight: 1.0,
+            setpoint: 0.0,
+            min_output: -f64::INFINITY,
+            max_output: f64::INFINITY,
+        }
+    }
+
+    /// Process model the gains are designed from.
+    pub fn model(&self) -> &StateSpaceModel {
+        &self.model
+    }
+
+    /// Diagonal of the LQR state weight `Q`.
+    pub fn state_weights(&self) -> &[f64] {
+        &self.state_weights
+    }
+
+    /// LQR output weight `R`.
+    pub fn input_weight(&self) -> f64 {
+        self.input_weight
+    }
+
+    /// Target process value.
+    pub fn setpoint(&self) -> f64 {
+        self.setpoint
+    }
+
+    /// Lower output clamp.
+    pub fn min_output(&self) -> f64 {
+        self.min_output
+    }
+
+    /// Upper output clamp.
+    pub fn max_output(&self) -> f64 {
+        self.max_output
+    }
+}
+
+impl StateFeedbackConfigBuilder {
+    /// Diagonal of the LQR state weight `Q`, one entry per state. See
+    /// [`StateSpaceModel::lqr`].
+    pub fn with_state_weights(mut self, weights: Vec<f64>) -> Self {
+        self.state_weights = Some(weights);
+        self
+    }
+
+    /// LQR output weight `R`. See [`StateSpaceModel::lqr`].
+    pub fn with_input_weight(mut self, weight: f64) -> Self {
+        self.input_weight = weight;
+        self
+    }
+
+    /// Sets the target process value.
+    pub fn with_setpoint(mut self, setpoint: f64) -> Self {
+        self.setpoint = setpoint;
+        self
+    }
+
+    /// Sets the output clamp range `[min, max]`.
+    pub fn with_output_limits(mut self, min: f64, max: f64) -> Self {
+        self.min_output = min;
+        self.max_output = max;
+        self
+    }
+
+    /// Validates and builds the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if:
+    /// - `state_weights` does not have one finite non-negative entry per
+    ///   state, or `input_weight` is not finite and positive.
+    /// - `setpoint` is non-finite.
+    /// - Output limits are non-finite or `min >= max`.
+    /// - The model cannot hold a steady process value (for example, the
+    ///   input has no path to the output).
+    pub fn build(self) -> Result<StateFeedbackConfig, PidError> {
+        let state_weights = self
+            .state_weights
+            .unwrap_or_else(|| self.model.c.iter().map(|c| c * c).collect());
+        check_weights(&self.model, &state_weights, self.input_weight)?;
+        if !self.setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        if !self.min_output.is_finite() || !self.max_output.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "output limits must be finite numbers",
+            ));
+        }
+        if self.min_output >= self.max_output {
+            return Err(PidError::InvalidParameter(
+                "min_output must be less than max_output",
+            ));
+        }
+        let (reference_state, reference_output) = self.model.steady_state().ok_or(
+            PidError::InvalidParameter("model cannot hold a steady process value"),
+        )?;
+
+        Ok(StateFeedbackConfig {
+            model: self.model,
+            state_weights,
+            input_weight: self.input_weight,
+            setpoint: self.setpoint,
+            min_output: self.min_output,
+            max_output: self.max_output,
+            reference_state,
+            reference_output,
+        })
+    }
+}
+
+/// State-feedback controller with LQR gains, `u = uₛ − K·(x − xₛ)`.
+///
+/// `xₛ` and `uₛ` are the state and output that hold the process value at
+/// the setpoint according to the model. The gains come from
+/// [`StateSpaceModel::lqr`] and are recomputed whenever `dt` changes.
+///
+/// It needs every state, not just the process value: pass measured or
+/// estimated states to [`compute`](Self::compute), or attach a
+/// [`LuenbergerObserver`] with [`with_observer`](Self::with_observer) and
+/// run it from the process value alone, which also makes it a
+/// [`Controller`]. There is no integral action, so a model error or a
+/// constant load leaves an offset; that is the textbook baseline, which is
+/// the point when comparing against PID on the same model.
+///
+/// ```
+/// use pidgeon::{SecondOrderModel, StateFeedbackConfig, StateFeedbackController};
+///
+/// let plant = SecondOrderModel::new(1.0, 2.0, 0.2).unwrap();
+/// let config = StateFeedbackConfig::builder(plant)
+///     .with_state_weights(vec![10.0, 0.0])
+///     .with_setpoint(1.0)
+///     .with_output_limits(-10.0, 10.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = StateFeedbackController::new(config);
+///
+/// // Position 0, velocity 0: push toward the setpoint
+/// let output = controller.compute(&[0.0, 0.0], 0.01).unwrap();
+/// assert!(output > 0.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StateFeedbackController {
+    config: StateFeedbackConfig,
+    /// Gains and the `dt` they were designed for
+    design: Option<(f64, Vec<f64>)>,
+    observer: Option<LuenbergerObserver>,
+    output: f64,
+}
+
+impl StateFeedbackController {
+    /// Creates a controller from a validated [`StateFeedbackConfig`].
+    pub fn new(config: StateFeedbackConfig) -> Self {
+        Self {
+            config,
+            design: None,
+            observer: None,
+            output: 0.0,
+        }
+    }
+
+    /// Estimates the state with `observer` so the controller can run from
+    /// the process value alone through
+    /// [`compute_from_measurement`](Self::compute_from_measurement) or the
+    /// [`Controller`] trait. The observer's model should be the one the
+    /// gains are designed from, or at least have the same states.
+    pub fn with_observer(mut self, observer: LuenbergerObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Feeds `process_value` and the previous output to the attached
+    /// observer and computes the output from its estimate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if no observer is attached,
+    /// the observer's model has a different number of states, or the inputs
+    /// are rejected as by [`compute`](Self::compute).
+    pub fn compute_from_measurement(
+        &mut self,
+        process_value: f64,
+        dt: f64,
+    ) -> Result<f64, PidError> {
+        let Some(observer) = &mut self.observer else {
+            return Err(PidError::InvalidParameter(
+                "state feedback from a measurement needs an observer",
+            ));
+        };
+        let state = observer.update(process_value, self.output, dt)?.to_vec();
+        self.compute(&state, dt)
+    }
+
+    /// The attached observer, if any.
+    pub fn observer(&self) -> Option<&LuenbergerObserver> {
+        self.observer.as_ref()
+    }
+
+    /// Returns the clamped output for the current `state`, `dt` seconds
+    /// after the previous call.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `state` does not have one
+    /// finite entry per model state, `dt` is non-finite / non-positive, or
+    /// no LQR gain exists for this `dt`.
+    pub fn compute(&mut self, state: &[f64], dt: f64) -> Result<f64, PidError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if state.len() != self.config.model.order() || !state.iter().all(|x| x.is_finite()) {
+            return Err(PidError::InvalidParameter(
+                "state must have one finite entry per model state",
+            ));
+        }
+
+        let gains = match &mut self.design {
+            Some((design_dt, gains)) if *design_dt == dt => gains,
+            slot => {
+                let gains = self.config.model.lqr(
+                    &self.config.state_weights,
+                    self.config.input_weight,
+                    dt,
+                )?;
+                &mut slot.insert((dt, gains)).1
+            }
+        };
+
+        let setpoint = self.config.setpoint;
+        let correction: f64 = gains
+            .iter()
+            .zip(state.iter().zip(&self.config.reference_state))
+            .map(|(k, (x, reference))| k * (x - setpoint * reference))
+            .sum();
+        self.output = (setpoint * self.config.reference_output - correction)
+            .clamp(self.config.min_output, self.config.max_output);
+        Ok(self.output)
+    }
+
+    /// Gains `K` in use, or `None` before the first
+    /// [`compute`](Self::compute).
+    pub fn gains(&self) -> Option<&[f64]> {
+        self.design.as_ref().map(|(_, gains)| gains.as_slice())
+    }
+
+    /// Forgets the designed gains, the last output, and the observer's
+    /// estimate. The configuration is preserved.
+    pub fn reset(&mut self) {
+        self.design = None;
+        self.output = 0.0;
+        if let Some(observer) = &mut self.observer {
+            observer.reset();
+        }
+    }
+
+    /// Updates the setpoint at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        if !setpoint.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "setpoint must be a finite number",
+            ));
+        }
+        self.config.setpoint = setpoint;
+        Ok(())
+    }
+
+    /// Returns the current setpoint.
+    pub fn setpoint(&self) -> f64 {
+        self.config.setpoint
+    }
+
+    /// Returns the output from the last [`compute`](Self::compute).
+    pub fn output(&self) -> f64 {
+        self.output
+    }
+
+    /// Returns a reference to the current [`StateFeedbackConfig`].
+    pub fn config(&self) -> &StateFeedbackConfig {
+        &self.config
+    }
+}
+
+impl Controller for StateFeedbackController {
+    /// Runs [`compute_from_measurement`](Self::compute_from_measurement), so
+    /// it fails without an observer attached.
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        self.compute_from_measurement(process_value, dt)
+    }
+
+    fn setpoint(&self) -> f64 {
+        StateFeedbackController::setpoint(self)
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        StateFeedbackController::set_setpoint(self, setpoint)
+    }
+
+    fn reset(&mut self) {
+        StateFeedbackController::reset(self)
+    }
+}
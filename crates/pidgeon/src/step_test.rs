@@ -0,0 +1,739 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::controller::PidController;
+use crate::error::PidError;
+use crate::tune_record::TuneRecord;
+use crate::tuning::{FopdtModel, Identification, SecondOrderModel, TuningRule};
+
+/// Output move a [`StepTest`] applies on top of the held output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Excitation {
+    /// Moves the output by `amplitude` and holds it there.
+    Step {
+        /// Output change, either sign.
+        amplitude: f64,
+    },
+    /// Moves the output by `amplitude` for `width` seconds, then by
+    /// `-amplitude` for as long, then back. The process ends near where it
+    /// started, so a doublet suits processes that must not drift far, and
+    /// integrating ones.
+    Doublet {
+        /// Output change of the first pulse, either sign.
+        amplitude: f64,
+        /// Seconds each pulse lasts.
+        width: f64,
+    },
+}
+
+impl Excitation {
+    /// Offset from the held output `t` seconds after the excitation starts.
+    fn offset(&self, t: f64) -> f64 {
+        match *self {
+            Excitation::Step { amplitude } => amplitude,
+            Excitation::Doublet { amplitude, width } => {
+                if t < width {
+                    amplitude
+                } else if t < 2.0 * width {
+                    -amplitude
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Validated configuration for a [`StepTest`].
+///
+/// | Parameter         | Default                                         |
+/// |-------------------|-------------------------------------------------|
+/// | `baseline`        | `0.0` s (the excitation starts on the 2nd sample) |
+/// | `min_output`      | the controller's lower output limit             |
+/// | `max_output`      | the controller's upper output limit             |
+/// | `process_limits`  | none                                            |
+/// | `max_amplitude`   | none (only the output limits clip)              |
+/// | `max_deviation`   | none                                            |
+/// | `timeout`         | none                                            |
+/// | `safe_output`     | the output held before the excitation           |
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StepTestConfig {
+    excitation: Excitation,
+    duration: f64,
+    baseline: f64,
+    min_output: f64,
+    max_output: f64,
+    process_limits: Option<(f64, f64)>,
+    max_amplitude: Option<f64>,
+    max_deviation: Option<f64>,
+    timeout: Option<Duration>,
+    safe_output: Option<f64>,
+}
+
+/// Builder for [`StepTestConfig`]. Call [`build`](Self::build) to validate.
+#[derive(Debug, Clone)]
+pub struct StepTestConfigBuilder {
+    excitation: Excitation,
+    duration: f64,
+    baseline: f64,
+    min_output: f64,
+    max_output: f64,
+    process_limits: Option<(f64, f64)>,
+    max_amplitude: Option<f64>,
+    max_deviation: Option<f64>,
+    timeout: Option<Duration>,
+    safe_output: Option<f64>,
+}
+
+impl StepTestConfig {
+    /// Returns a builder for a test applying `excitation` and recording for
+    /// `duration` seconds after it starts. Allow the process time to settle:
+    /// three to five time constants past its dead time.
+    pub fn builder(excitation: Excitation, duration: f64) -> StepTestConfigBuilder {
+        StepTestConfigBuilder {
+            excitation,
+            duration,
+            baseline: 0.0,
+            min_output: -f64::INFINITY,
+            max_output: f64::INFINITY,
+            process_limits: None,
+            max_amplitude: None,
+            max_deviation: None,
+            timeout: None,
+            safe_output: None,
+        }
+    }
+
+    /// Output move applied.
+    pub fn excitation(&self) -> Excitation {
+        self.excitation
+    }
+
+    /// Seconds recorded once the excitation starts.
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Seconds the output is held and recorded before the excitation.
+    pub fn baseline(&self) -> f64 {
+        self.baseline
+    }
+
+    /// Lowest output the test may apply.
+    pub fn min_output(&self) -> f64 {
+        self.min_output
+    }
+
+    /// Highest output the test may apply.
+    pub fn max_output(&self) -> f64 {
+        self.max_output
+    }
+
+    /// Process values outside which the test aborts, if any.
+    pub fn process_limits(&self) -> Option<(f64, f64)> {
+        self.process_limits
+    }
+
+    /// Largest move away from the held output the test may apply, if any.
+    pub fn max_amplitude(&self) -> Option<f64> {
+        self.max_amplitude
+    }
+
+    /// Largest change in the process value from its first sample before
+    /// the test aborts, if any.
+    pub fn max_deviation(&self) -> Option<f64> {
+        self.max_deviation
+    }
+
+    /// Wall-clock time after which an unfinished test aborts, if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Output applied when the test stops early, if not the held output.
+    pub fn safe_output(&self) -> Option<f64> {
+        self.safe_output
+    }
+}
+
+impl StepTestConfigBuilder {
+    /// Seconds to hold and record the output before the excitation, so the
+    /// record shows the process at rest and the identification has a clean
+    /// starting point. Default: `0.0`.
+    pub fn with_baseline(mut self, seconds: f64) -> Self {
+        self.baseline = seconds;
+        self
+    }
+
+    /// Bounds on the output the test applies, inside the controller's own
+    /// limits. The excitation is clipped to them, so a step from near a
+    /// limit comes out smaller rather than driving the actuator past what
+    /// the process can safely take. Default: the controller's limits.
+    pub fn with_output_limits(mut self, min: f64, max: f64) -> Self {
+        self.min_output = min;
+        self.max_output = max;
+        self
+    }
+
+    /// Process values the test must stay within. The first sample outside
+    /// them aborts the test and puts the output back where it was held, or
+    /// at the [safe output](Self::with_safe_output). Default: none.
+    pub fn with_process_limits(mut self, min: f64, max: f64) -> Self {
+        self.process_limits = Some((min, max));
+        self
+    }
+
+    /// Caps how far the excitation may move the output from where it was
+    /// held, whatever the configured amplitude. On production equipment
+    /// this keeps a test set up for one operating point from slamming the
+    /// actuator at another. Default: none.
+    pub fn with_max_amplitude(mut self, amplitude: f64) -> Self {
+        self.max_amplitude = Some(amplitude);
+        self
+    }
+
+    /// Aborts the test once the process value has moved more than
+    /// `deviation` from its first sample, in either direction. Unlike
+    /// [`with_process_limits`](Self::with_process_limits) this needs no
+    /// knowledge of where the process will be when the test starts.
+    /// Default: none.
+    pub fn with_max_deviation(mut self, deviation: f64) -> Self {
+        self.max_deviation = Some(deviation);
+        self
+    }
+
+    /// Aborts the test if it has not completed `timeout` of wall-clock time
+    /// after its first sample, such as when the loop runs slower than
+    /// planned or stalls and resumes. Default: none.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Output to apply when the test stops early, for a process whose safe
+    /// state is not where it was held, such as a heater that should go off.
+    /// It is clamped to the controller's limits. Default: the held output.
+    pub fn with_safe_output(mut self, output: f64) -> Self {
+        self.safe_output = Some(output);
+        self
+    }
+
+    /// Validates the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if:
+    /// - The excitation amplitude is zero or non-finite, or a doublet's
+    ///   `width` is non-finite or non-positive.
+    /// - `duration` is non-finite or non-positive, or `baseline` non-finite
+    ///   or negative.
+    /// - The output or process limits are NaN or have `min >= max`.
+    /// - `max_amplitude` or `max_deviation` is non-finite or non-positive,
+    ///   `timeout` is zero, or `safe_output` is non-finite.
+    pub fn build(self) -> Result<StepTestConfig, PidError> {
+        let amplitude = match self.excitation {
+            Excitation::Step { amplitude } => amplitude,
+            Excitation::Doublet { amplitude, width } => {
+                if !width.is_finite() || width <= 0.0 {
+                    return Err(PidError::InvalidParameter(
+                        "doublet width must be a finite positive number",
+                    ));
+                }
+                amplitude
+            }
+        };
+        if !amplitude.is_finite() || amplitude == 0.0 {
+            return Err(PidError::InvalidParameter(
+                "excitation amplitude must be a finite nonzero number",
+            ));
+        }
+        if !self.duration.is_finite() || self.duration <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "step test duration must be a finite positive number",
+            ));
+        }
+        if !self.baseline.is_finite() || self.baseline < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "step test baseline must be a finite non-negative number",
+            ));
+        }
+        // Infinite output bounds fall back to the controller's limits
+        if self.min_output.is_nan() || self.max_output.is_nan() {
+            return Err(PidError::InvalidParameter(
+                "step test output limits must be numbers",
+            ));
+        }
+        if self.min_output >= self.max_output {
+            return Err(PidError::InvalidParameter(
+                "step test min_output must be less than max_output",
+            ));
+        }
+        if let Some((min, max)) = self.process_limits {
+            if min.is_nan() || max.is_nan() || min >= max {
+                return Err(PidError::InvalidParameter(
+                    "step test process limits must be numbers with min below max",
+                ));
+            }
+        }
+        if self
+            .max_amplitude
+            .is_some_and(|amplitude| !amplitude.is_finite() || amplitude <= 0.0)
+        {
+            return Err(PidError::InvalidParameter(
+                "step test max_amplitude must be a finite positive number",
+            ));
+        }
+        if self
+            .max_deviation
+            .is_some_and(|deviation| !deviation.is_finite() || deviation <= 0.0)
+        {
+            return Err(PidError::InvalidParameter(
+                "step test max_deviation must be a finite positive number",
+            ));
+        }
+        if self.timeout == Some(Duration::ZERO) {
+            return Err(PidError::InvalidParameter(
+                "step test timeout must be longer than zero",
+            ));
+        }
+        if self.safe_output.is_some_and(|output| !output.is_finite()) {
+            return Err(PidError::InvalidParameter(
+                "step test safe_output must be a finite number",
+            ));
+        }
+        Ok(StepTestConfig {
+            excitation: self.excitation,
+            duration: self.duration,
+            baseline: self.baseline,
+            min_output: self.min_output,
+            max_output: self.max_output,
+            process_limits: self.process_limits,
+            max_amplitude: self.max_amplitude,
+            max_deviation: self.max_deviation,
+            timeout: self.timeout,
+            safe_output: self.safe_output,
+        })
+    }
+}
+
+/// Where a [`StepTest`] is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepTestStatus {
+    /// Still applying the excitation and recording.
+    Running,
+    /// Recorded for the whole duration.
+    Complete,
+    /// Stopped early because the process value left the configured limits
+    /// or moved further than the maximum deviation.
+    Aborted {
+        /// The process value that was out of bounds.
+        process_value: f64,
+    },
+    /// Stopped early because the test ran past its timeout.
+    TimedOut,
+    /// Stopped early through the test's [`CancelToken`].
+    Cancelled,
+}
+
+/// Part of a [`StepTest`] the latest sample fell in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepTestPhase {
+    /// Holding the output and recording the process at rest.
+    Baseline,
+    /// Applying the excitation and recording the response.
+    Excitation,
+}
+
+/// How far a [`StepTest`] has got, from [`StepTest::progress`] or handed to
+/// the callback set with [`StepTest::with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StepTestProgress {
+    /// Part of the test the latest sample fell in.
+    pub phase: StepTestPhase,
+    /// Where the test is.
+    pub status: StepTestStatus,
+    /// Seconds recorded so far, baseline included.
+    pub elapsed: f64,
+    /// Share of the baseline plus duration recorded, 0 to 100.
+    pub percent: f64,
+    /// Model fitted to the record so far, refreshed every tenth of the
+    /// duration and once more when the test completes. `None` until the
+    /// process has responded enough to fit.
+    pub estimate: Option<Identification>,
+}
+
+/// Stops a [`StepTest`] from outside the loop running it, such as an
+/// operator's abort button or a shutdown handler.
+///
+/// Clones share one flag. Once [`cancel`](Self::cancel) is called, the next
+/// [`StepTest::step`] applies the test's safe output, by default the one it
+/// held, and ends the test with [`StepTestStatus::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every test holding a clone of this token to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+type ProgressCallback = Box<dyn FnMut(&StepTestProgress) + Send>;
+
+/// Runs an open-loop step or doublet test through a [`PidController`] and
+/// hands the record to [`FopdtModel::identify`] or
+/// [`SecondOrderModel::identify`].
+///
+/// Call [`step`](Self::step) in place of the controller's `compute` each
+/// cycle until the status leaves [`StepTestStatus::Running`]. The output the
+/// controller last applied is held, the excitation is added on top within
+/// the configured bounds, and the controller [`track`](PidController::track)s
+/// every output the test applies, so when the test ends the next `compute`
+/// takes over without a bump. If the process value leaves its limits or
+/// strays too far from where it started, the test runs past its timeout,
+/// or its [`CancelToken`] is cancelled, the test stops and puts the output
+/// back where it was held, or at the configured safe output.
+///
+/// Tests on slow processes run for minutes. [`progress`](Self::progress)
+/// reports the phase, percent complete, and a model fitted to the record so
+/// far; [`with_progress`](Self::with_progress) pushes the same report to a
+/// callback, or through a channel to another task, whenever it changes.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, Excitation, PidController, StepTest, StepTestConfig, StepTestStatus};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_ki(0.1)
+///     .with_setpoint(40.0)
+///     .with_output_limits(0.0, 100.0)
+///     .with_initial_output(30.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = PidController::new(config);
+/// let test = StepTestConfig::builder(Excitation::Step { amplitude: 10.0 }, 300.0)
+///     .with_baseline(20.0)
+///     .with_process_limits(0.0, 80.0)
+///     .build()
+///     .unwrap();
+/// let mut test = StepTest::new(test);
+///
+/// // A first-order process, 2 units per % of output with a 30 s lag
+/// let mut process_value = 40.0;
+/// while test.status() == StepTestStatus::Running {
+///     let output = test.step(&mut controller, process_value, 1.0).unwrap();
+///     process_value += (2.0 * (output - 30.0) + 40.0 - process_value) / 30.0;
+/// }
+///
+/// let found = test.identify().unwrap();
+/// assert!((found.model.gain - 2.0).abs() < 0.05);
+/// ```
+pub struct StepTest {
+    config: StepTestConfig,
+    status: StepTestStatus,
+    held_output: Option<f64>,
+    /// When the first sample was taken, for the timeout
+    started: Option<Instant>,
+    elapsed: f64,
+    outputs: Vec<f64>,
+    process_values: Vec<f64>,
+    cancel: Option<CancelToken>,
+    on_progress: Option<ProgressCallback>,
+    /// Phase and whole percent of the last report
+    reported: Option<(StepTestPhase, u32)>,
+    estimate: Option<Identification>,
+    /// Seconds into the excitation at which to refit the estimate
+    next_estimate: f64,
+}
+
+impl fmt::Debug for StepTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StepTest")
+            .field("config", &self.config)
+            .field("status", &self.status)
+            .field("held_output", &self.held_output)
+            .field("elapsed", &self.elapsed)
+            .field("samples", &self.outputs.len())
+            .field("cancel", &self.cancel)
+            .field("estimate", &self.estimate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StepTest {
+    /// Creates a test that starts on the first [`step`](Self::step).
+    pub fn new(config: StepTestConfig) -> Self {
+        Self {
+            status: StepTestStatus::Running,
+            held_output: None,
+            started: None,
+            elapsed: 0.0,
+            outputs: Vec::new(),
+            process_values: Vec::new(),
+            cancel: None,
+            on_progress: None,
+            reported: None,
+            estimate: None,
+            next_estimate: config.duration / 10.0,
+            config,
+        }
+    }
+
+    /// Stops the test on the next [`step`](Self::step) after `token` is
+    /// cancelled.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Calls `callback` from [`step`](Self::step) whenever the phase or the
+    /// whole percent complete changes, and once when the test ends.
+    ///
+    /// The callback runs inside the control loop, so it should only hand
+    /// the report on, for example by sending it down a channel to a task
+    /// that displays it.
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use pidgeon::{ControllerConfig, Excitation, PidController, StepTest, StepTestConfig};
+    ///
+    /// let (reports, received) = mpsc::channel();
+    /// let test = StepTestConfig::builder(Excitation::Step { amplitude: 5.0 }, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut test = StepTest::new(test).with_progress(move |progress| {
+    ///     let _ = reports.send(*progress);
+    /// });
+    ///
+    /// let config = ControllerConfig::builder()
+    ///     .with_output_limits(0.0, 100.0)
+    ///     .build()
+    ///     .unwrap();
+    /// let mut controller = PidController::new(config);
+    /// test.step(&mut controller, 20.0, 1.0).unwrap();
+    /// let first = received.try_recv().unwrap();
+    /// assert_eq!(first.percent, 1.0);
+    /// ```
+    pub fn with_progress(
+        mut self,
+        callback: impl FnMut(&StepTestProgress) + Send + 'static,
+    ) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Applies the next sample of the test through `controller` and returns
+    /// the output to apply.
+    ///
+    /// Samples should be evenly spaced; the identification uses their
+    /// average spacing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the test is no longer
+    /// running, `process_value` is non-finite, or `dt` is non-finite /
+    /// non-positive.
+    pub fn step(
+        &mut self,
+        controller: &mut PidController,
+        process_value: f64,
+        dt: f64,
+    ) -> Result<f64, PidError> {
+        if self.status != StepTestStatus::Running {
+            return Err(PidError::InvalidParameter("step test is no longer running"));
+        }
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        if !process_value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "process_value must be a finite number",
+            ));
+        }
+
+        let limits = controller.config();
+        let min_output = self.config.min_output.max(limits.min_output());
+        let max_output = self.config.max_output.min(limits.max_output());
+        let held = *self.held_output.get_or_insert_with(|| {
+            let state = controller.state();
+            if state.first_run {
+                limits.initial_output().unwrap_or(0.0)
+            } else {
+                state.last_output
+            }
+        });
+
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let first = self
+            .process_values
+            .first()
+            .copied()
+            .unwrap_or(process_value);
+        let stop = if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            Some(StepTestStatus::Cancelled)
+        } else if self
+            .config
+            .timeout
+            .is_some_and(|timeout| started.elapsed() > timeout)
+        {
+            Some(StepTestStatus::TimedOut)
+        } else if self
+            .config
+            .process_limits
+            .is_some_and(|(min, max)| process_value < min || process_value > max)
+            || self
+                .config
+                .max_deviation
+                .is_some_and(|deviation| (process_value - first).abs() > deviation)
+        {
+            Some(StepTestStatus::Aborted { process_value })
+        } else {
+            None
+        };
+        if let Some(status) = stop {
+            self.status = status;
+            self.report();
+            let safe = self.config.safe_output.unwrap_or(held);
+            return controller.track(process_value, safe, dt);
+        }
+
+        // The first sample always records the held output
+        let since_start = self.elapsed - self.config.baseline;
+        let output = if self.outputs.is_empty() || since_start < 0.0 {
+            held
+        } else {
+            let amplitude = self.config.max_amplitude.unwrap_or(f64::INFINITY);
+            let offset = self.config.excitation.offset(since_start);
+            (held + offset.clamp(-amplitude, amplitude)).clamp(min_output, max_output)
+        };
+        let output = controller.track(process_value, output, dt)?;
+        self.outputs.push(output);
+        self.process_values.push(process_value);
+        self.elapsed += dt;
+        if self.elapsed >= self.config.baseline + self.config.duration {
+            self.status = StepTestStatus::Complete;
+            self.estimate = self.identify().ok().or(self.estimate);
+        } else if self.elapsed - self.config.baseline >= self.next_estimate {
+            self.estimate = self.identify().ok().or(self.estimate);
+            self.next_estimate += self.config.duration / 10.0;
+        }
+        self.report();
+        Ok(output)
+    }
+
+    /// How far the test has got.
+    pub fn progress(&self) -> StepTestProgress {
+        let total = self.config.baseline + self.config.duration;
+        let phase = if self.elapsed < self.config.baseline {
+            StepTestPhase::Baseline
+        } else {
+            StepTestPhase::Excitation
+        };
+        StepTestProgress {
+            phase,
+            status: self.status,
+            elapsed: self.elapsed,
+            percent: (self.elapsed / total * 100.0).min(100.0),
+            estimate: self.estimate,
+        }
+    }
+
+    /// Hands the progress to the callback if it has moved on since the last
+    /// report, or the test has ended.
+    fn report(&mut self) {
+        if self.on_progress.is_none() {
+            return;
+        }
+        let progress = self.progress();
+        let step = (progress.phase, progress.percent as u32);
+        if progress.status == StepTestStatus::Running && self.reported == Some(step) {
+            return;
+        }
+        self.reported = Some(step);
+        if let Some(callback) = &mut self.on_progress {
+            callback(&progress);
+        }
+    }
+
+    /// Where the test is.
+    pub fn status(&self) -> StepTestStatus {
+        self.status
+    }
+
+    /// The configuration.
+    pub fn config(&self) -> &StepTestConfig {
+        &self.config
+    }
+
+    /// Outputs applied, one per sample.
+    pub fn outputs(&self) -> &[f64] {
+        &self.outputs
+    }
+
+    /// Process values recorded, one per sample alongside [`outputs`](Self::outputs).
+    pub fn process_values(&self) -> &[f64] {
+        &self.process_values
+    }
+
+    /// Average seconds between samples, `None` before the first.
+    pub fn sample_time(&self) -> Option<f64> {
+        (!self.outputs.is_empty()).then(|| self.elapsed / self.outputs.len() as f64)
+    }
+
+    /// Fits a first-order-plus-dead-time model to the record, which can go
+    /// straight to a [`TuningRule`](crate::TuningRule).
+    ///
+    /// # Errors
+    ///
+    /// Whatever [`FopdtModel::identify`] returns for the record, such as for
+    /// one too short or aborted before the process responded.
+    pub fn identify(&self) -> Result<Identification, PidError> {
+        FopdtModel::identify(&self.outputs, &self.process_values, self.dt()?)
+    }
+
+    /// Fits a second-order model to the record.
+    ///
+    /// # Errors
+    ///
+    /// Whatever [`SecondOrderModel::identify`] returns for the record.
+    pub fn identify_second_order(&self) -> Result<Identification<SecondOrderModel>, PidError> {
+        SecondOrderModel::identify(&self.outputs, &self.process_values, self.dt()?)
+    }
+
+    /// Identifies the record and applies `rule`, keeping the model, gains,
+    /// and a fingerprint of the record together in a [`TuneRecord`].
+    ///
+    /// # Errors
+    ///
+    /// Whatever [`TuneRecord::new`] returns for the record.
+    pub fn tune(&self, rule: TuningRule) -> Result<TuneRecord, PidError> {
+        TuneRecord::new(&self.outputs, &self.process_values, self.dt()?, rule)
+    }
+
+    fn dt(&self) -> Result<f64, PidError> {
+        self.sample_time().ok_or(PidError::InvalidParameter(
+            "step test has not recorded any samples",
+        ))
+    }
+}
@@ -0,0 +1,403 @@
+//! Invariant checkers for fuzzing controller configurations, and with the
+//! `proptest` feature, strategies that generate configs and inputs for them.
+//!
+//! Every config that builds should keep the output within its limits, never
+//! produce NaN or infinity from finite inputs, and never push the output the
+//! wrong way when the process value rises. [`check_run`] checks all three on
+//! every step of a run, so fuzzing your own configuration is one test:
+//!
+//! ```ignore
+//! use pidgeon::testing::{arb_inputs, check_run};
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn oven_config_holds_invariants(inputs in arb_inputs(1..500)) {
+//!         check_run(&oven_config(), &inputs)?;
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+use crate::compute::pid_compute;
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::state::PidState;
+
+#[cfg(feature = "proptest")]
+pub use strategies::*;
+
+/// One step of input to a controller under test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepInput {
+    /// Measured process value.
+    pub process_value: f64,
+    /// Time step in seconds.
+    pub dt: f64,
+    /// Amount added to the process value by [`check_monotone`]; its sign is
+    /// ignored.
+    pub bias: f64,
+}
+
+/// An invariant a step broke.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvariantViolation {
+    /// [`pid_compute`] rejected the step.
+    Rejected {
+        /// Index of the step in the run; `0` for single-step checks.
+        step: usize,
+        /// What [`pid_compute`] returned.
+        error: PidError,
+    },
+    /// The output left `[min_output, max_output]`.
+    OutputOutOfLimits {
+        /// Index of the step in the run; `0` for single-step checks.
+        step: usize,
+        /// The offending output.
+        output: f64,
+    },
+    /// The output or a [`PidState`] field became NaN or infinite.
+    NotFinite {
+        /// Index of the step in the run; `0` for single-step checks.
+        step: usize,
+        /// `output` or the name of the [`PidState`] field.
+        field: &'static str,
+        /// The offending value.
+        value: f64,
+    },
+    /// Raising the process value moved the output against the gains: up
+    /// when every gain is non-negative, or down when every gain is
+    /// non-positive.
+    NotMonotone {
+        /// Index of the step in the run; `0` for single-step checks.
+        step: usize,
+        /// Output at the unbiased process value.
+        output: f64,
+        /// Output at the higher process value.
+        biased_output: f64,
+    },
+}
+
+impl InvariantViolation {
+    fn at_step(mut self, index: usize) -> Self {
+        match &mut self {
+            InvariantViolation::Rejected { step, .. }
+            | InvariantViolation::OutputOutOfLimits { step, .. }
+            | InvariantViolation::NotFinite { step, .. }
+            | InvariantViolation::NotMonotone { step, .. } => *step = index,
+        }
+        self
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::Rejected { step, error } => {
+                write!(f, "step {} was rejected: {}", step, error)
+            }
+            InvariantViolation::OutputOutOfLimits { step, output } => {
+                write!(f, "step {}: output {} is outside the limits", step, output)
+            }
+            InvariantViolation::NotFinite { step, field, value } => {
+                write!(f, "step {}: {} became {}", step, field, value)
+            }
+            InvariantViolation::NotMonotone {
+                step,
+                output,
+                biased_output,
+            } => write!(
+                f,
+                "step {}: raising the process value moved the output from {} to {}",
+                step, output, biased_output
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvariantViolation {}
+
+fn compute(
+    config: &ControllerConfig,
+    state: &PidState,
+    process_value: f64,
+    dt: f64,
+) -> Result<(f64, PidState), InvariantViolation> {
+    pid_compute(config, state, process_value, dt)
+        .map_err(|error| InvariantViolation::Rejected { step: 0, error })
+}
+
+/// Checks that `output` lies within `config`'s output limits.
+///
+/// # Errors
+///
+/// Returns [`InvariantViolation::OutputOutOfLimits`] if it does not.
+pub fn check_output_within_limits(
+    config: &ControllerConfig,
+    output: f64,
+) -> Result<(), InvariantViolation> {
+    if output >= config.min_output && output <= config.max_output {
+        Ok(())
+    } else {
+        Err(InvariantViolation::OutputOutOfLimits { step: 0, output })
+    }
+}
+
+/// Checks that `output` and every number in `state` are finite.
+///
+/// # Errors
+///
+/// Returns [`InvariantViolation::NotFinite`] naming the first value that is
+/// NaN or infinite.
+pub fn check_finite(output: f64, state: &PidState) -> Result<(), InvariantViolation> {
+    let values = [
+        ("output", output),
+        ("integral_contribution", state.integral_contribution),
+        ("prev_error", state.prev_error),
+        ("prev_measurement", state.prev_measurement),
+        ("prev_filtered_derivative", state.prev_filtered_derivative),
+        ("last_output", state.last_output),
+        ("filtered_setpoint", state.filtered_setpoint),
+    ];
+    match values.into_iter().find(|(_, value)| !value.is_finite()) {
+        Some((field, value)) => Err(InvariantViolation::NotFinite {
+            step: 0,
+            field,
+            value,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that from `state`, a process value raised by `bias` gives an
+/// output no higher when every gain is non-negative, and no lower when every
+/// gain is non-positive. Configs mixing signs pass trivially.
+///
+/// This holds exactly, rounding included: every term of the output is
+/// monotone in the process value, and so are the deadband and the clamp.
+///
+/// # Errors
+///
+/// Returns [`InvariantViolation::NotMonotone`], or
+/// [`InvariantViolation::Rejected`] if either step is rejected.
+pub fn check_monotone(
+    config: &ControllerConfig,
+    state: &PidState,
+    process_value: f64,
+    bias: f64,
+    dt: f64,
+) -> Result<(), InvariantViolation> {
+    let (kp, ki, kd) = config.gains_for(false);
+    let (negative_kp, negative_ki, negative_kd) = config.gains_for(true);
+    let gains = [kp, ki, kd, negative_kp, negative_ki, negative_kd];
+    let direct = gains.iter().all(|&gain| gain >= 0.0);
+    let reverse = gains.iter().all(|&gain| gain <= 0.0);
+    let (output, _) = compute(config, state, process_value, dt)?;
+    let (biased_output, _) = compute(config, state, process_value + bias.abs(), dt)?;
+    if (direct && biased_output > output) || (reverse && biased_output < output) {
+        return Err(InvariantViolation::NotMonotone {
+            step: 0,
+            output,
+            biased_output,
+        });
+    }
+    Ok(())
+}
+
+/// Runs one step from `state`, checks every invariant on it, and returns the
+/// next state.
+///
+/// # Errors
+///
+/// Returns the first [`InvariantViolation`].
+pub fn check_step(
+    config: &ControllerConfig,
+    state: &PidState,
+    input: &StepInput,
+) -> Result<PidState, InvariantViolation> {
+    let (output, next) = compute(config, state, input.process_value, input.dt)?;
+    check_output_within_limits(config, output)?;
+    check_finite(output, &next)?;
+    check_monotone(config, state, input.process_value, input.bias, input.dt)?;
+    Ok(next)
+}
+
+/// Runs `inputs` through a controller started with [`PidState::new`] and
+/// checks every invariant on every step.
+///
+/// # Errors
+///
+/// Returns the first [`InvariantViolation`], with the index of its step.
+pub fn check_run(
+    config: &ControllerConfig,
+    inputs: &[StepInput],
+) -> Result<(), InvariantViolation> {
+    let mut state = PidState::new(config);
+    for (index, input) in inputs.iter().enumerate() {
+        state = check_step(config, &state, input).map_err(|e| e.at_step(index))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "proptest")]
+mod strategies {
+    use proptest::collection::{vec, SizeRange};
+    use proptest::prelude::*;
+
+    use super::StepInput;
+    use crate::config::ControllerConfig;
+    use crate::enums::{AntiWindupMode, DerivativeMode, ProportionalShape};
+    use crate::state::PidState;
+
+    /// Gains between -100 and 100, all of one sign about a third of the time
+    /// each way so [`check_monotone`](super::check_monotone) has work to do.
+    pub fn arb_gains() -> impl Strategy<Value = (f64, f64, f64)> {
+        let magnitudes = (0.0..100.0f64, 0.0..100.0f64, 0.0..100.0f64);
+        prop_oneof![
+            magnitudes.clone(),
+            magnitudes.prop_map(|(kp, ki, kd)| (-kp, -ki, -kd)),
+            (-100.0..100.0f64, -100.0..100.0f64, -100.0..100.0f64),
+        ]
+    }
+
+    /// Any of the anti-windup modes, with tracking times from 0.01 to 10 s.
+    pub fn arb_anti_windup_mode() -> impl Strategy<Value = AntiWindupMode> {
+        prop_oneof![
+            Just(AntiWindupMode::None),
+            Just(AntiWindupMode::Conditional),
+            (0.01..10.0f64)
+                .prop_map(|tracking_time| AntiWindupMode::BackCalculation { tracking_time }),
+        ]
+    }
+
+    /// Either derivative mode.
+    pub fn arb_derivative_mode() -> impl Strategy<Value = DerivativeMode> {
+        prop_oneof![
+            Just(DerivativeMode::OnMeasurement),
+            Just(DerivativeMode::OnError),
+        ]
+    }
+
+    /// Any proportional shape, with widths from 0.01 to 100.
+    pub fn arb_proportional_shape() -> impl Strategy<Value = ProportionalShape> {
+        prop_oneof![
+            Just(ProportionalShape::Linear),
+            (0.01..100.0f64)
+                .prop_map(|full_gain_error| ProportionalShape::ErrorSquared { full_gain_error }),
+            (0.01..100.0f64, 0.0..=1.0f64).prop_map(|(half_width, gain_ratio)| {
+                ProportionalShape::Gap {
+                    half_width,
+                    gain_ratio,
+                }
+            }),
+        ]
+    }
+
+    /// Valid configs over every option: gains from [`arb_gains`], limits
+    /// within ±2000, shapes from [`arb_proportional_shape`], setpoints
+    /// within ±1000, deadbands up to 10, filter coefficients from 0.1 to
+    /// 100, an initial output half the time, and derivative limits up to
+    /// ±1000 half the time.
+    pub fn arb_config() -> impl Strategy<Value = ControllerConfig> {
+        (
+            arb_gains(),
+            -1000.0..1000.0f64,
+            0.001..1000.0f64,
+            arb_anti_windup_mode(),
+            arb_derivative_mode(),
+            arb_proportional_shape(),
+            -1000.0..1000.0f64,
+            0.0..10.0f64,
+            0.1..100.0f64,
+            proptest::option::of(0.0..=1.0f64),
+            proptest::option::of((0.1..1000.0f64, 0.1..1000.0f64)),
+        )
+            .prop_map(
+                |(
+                    (kp, ki, kd),
+                    min_output,
+                    span,
+                    anti_windup_mode,
+                    derivative_mode,
+                    proportional_shape,
+                    setpoint,
+                    deadband,
+                    filter_coeff,
+                    initial,
+                    derivative_limits,
+                )| {
+                    let max_output = min_output + span;
+                    let mut builder = ControllerConfig::builder()
+                        .with_kp(kp)
+                        .with_ki(ki)
+                        .with_kd(kd)
+                        .with_output_limits(min_output, max_output)
+                        .with_anti_windup_mode(anti_windup_mode)
+                        .with_derivative_mode(derivative_mode)
+                        .with_proportional_shape(proportional_shape)
+                        .with_setpoint(setpoint)
+                        .with_deadband(deadband)
+                        .with_derivative_filter_coeff(filter_coeff);
+                    if let Some(fraction) = initial {
+                        builder = builder.with_initial_output(min_output + fraction * span);
+                    }
+                    if let Some((below, above)) = derivative_limits {
+                        builder = builder.with_derivative_limits(-below, above);
+                    }
+                    builder.build().expect("generated config is valid")
+                },
+            )
+    }
+
+    /// States a controller could be in partway through a run: finite values
+    /// within ±1000, first run or not.
+    pub fn arb_state() -> impl Strategy<Value = PidState> {
+        (
+            -1000.0..1000.0f64,
+            -1000.0..1000.0f64,
+            -1000.0..1000.0f64,
+            -1000.0..1000.0f64,
+            -1000.0..1000.0f64,
+            -1000.0..1000.0f64,
+            any::<bool>(),
+        )
+            .prop_map(
+                |(
+                    integral_contribution,
+                    prev_error,
+                    prev_measurement,
+                    prev_filtered_derivative,
+                    last_output,
+                    filtered_setpoint,
+                    first_run,
+                )| PidState {
+                    integral_contribution,
+                    prev_error,
+                    prev_measurement,
+                    prev_filtered_derivative,
+                    last_output,
+                    filtered_setpoint,
+                    first_run,
+                },
+            )
+    }
+
+    /// Process values within ±1000, time steps from 0.1 ms to 1 s, and
+    /// biases up to 100.
+    pub fn arb_input() -> impl Strategy<Value = StepInput> {
+        (-1000.0..1000.0f64, 0.0001..1.0f64, 0.0..100.0f64).prop_map(|(process_value, dt, bias)| {
+            StepInput {
+                process_value,
+                dt,
+                bias,
+            }
+        })
+    }
+
+    /// Runs of [`arb_input`]s, with a length in `len`.
+    pub fn arb_inputs(len: impl Into<SizeRange>) -> impl Strategy<Value = Vec<StepInput>> {
+        vec(arb_input(), len)
+    }
+}
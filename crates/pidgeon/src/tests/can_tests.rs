@@ -0,0 +1,143 @@
+use crate::*;
+
+fn config() -> ControllerConfig {
+    ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_can_codec_round_trips_every_message() {
+    let messages = [
+        CanMessage::Status {
+            process_value: 48.5,
+            output: 3.0,
+        },
+        CanMessage::Target {
+            setpoint: 50.0,
+            error: 1.5,
+        },
+        CanMessage::SetSetpoint { setpoint: -12.25 },
+        CanMessage::SetGain {
+            gain: Gain::Ki,
+            value: 0.125,
+        },
+        CanMessage::Reset,
+    ];
+    for codec in [
+        CanCodec::standard(0x180).unwrap(),
+        CanCodec::extended(0x18FF_0000).unwrap(),
+    ] {
+        for (offset, message) in messages.iter().enumerate() {
+            let frame = codec.encode(message);
+            assert_eq!(frame.id, codec.base_id() + offset as u32);
+            assert_eq!(frame.extended, codec.is_extended());
+            assert_eq!(codec.decode(&frame), Ok(Some(*message)));
+            assert_eq!(
+                message.is_command(),
+                codec.command_ids().contains(&frame.id)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_can_codec_ignores_and_rejects_frames() {
+    let codec = CanCodec::standard(0x180).unwrap();
+    let mut frame = codec.encode(&CanMessage::SetSetpoint { setpoint: 1.0 });
+
+    // Other nodes' traffic
+    frame.id = 0x17F;
+    assert_eq!(codec.decode(&frame), Ok(None));
+    frame.id = 0x185;
+    assert_eq!(codec.decode(&frame), Ok(None));
+    frame.id = 0x182;
+    frame.extended = true;
+    assert_eq!(codec.decode(&frame), Ok(None));
+    frame.extended = false;
+
+    frame.len = 3;
+    assert!(codec.decode(&frame).is_err());
+    frame.len = 4;
+    frame.data[..4].copy_from_slice(&f32::NAN.to_le_bytes());
+    assert!(codec.decode(&frame).is_err());
+
+    let mut gain = codec.encode(&CanMessage::SetGain {
+        gain: Gain::Kd,
+        value: 1.0,
+    });
+    gain.data[0] = 7;
+    assert!(codec.decode(&gain).is_err());
+
+    assert!(CanCodec::standard(0x7FB).is_ok());
+    assert!(CanCodec::standard(0x7FC).is_err());
+    assert!(CanCodec::extended(0x1FFF_FFFB).is_ok());
+    assert!(CanCodec::extended(0x1FFF_FFFC).is_err());
+}
+
+#[test]
+fn test_can_telemetry_reports_cycle() {
+    let codec = CanCodec::standard(0x180).unwrap();
+    let config = config();
+    let (output, state) = pid_compute(&config, &PidState::default(), 45.0, 0.01).unwrap();
+
+    let [status, target] = codec.telemetry(&config, &state, 45.0);
+    assert_eq!(
+        codec.decode(&status),
+        Ok(Some(CanMessage::Status {
+            process_value: 45.0,
+            output: output as f32,
+        }))
+    );
+    assert_eq!(
+        codec.decode(&target),
+        Ok(Some(CanMessage::Target {
+            setpoint: 50.0,
+            error: 5.0,
+        }))
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_can_commands_update_controller() {
+    let controller = ThreadSafePidController::new(config());
+    controller
+        .apply_can_message(&CanMessage::SetSetpoint { setpoint: 60.0 })
+        .unwrap();
+    controller
+        .apply_can_message(&CanMessage::SetGain {
+            gain: Gain::Kp,
+            value: 4.0,
+        })
+        .unwrap();
+    let config = controller.get_config().unwrap();
+    assert_eq!(config.setpoint(), 60.0);
+    assert_eq!(config.kp(), 4.0);
+
+    controller.compute(50.0, 0.01).unwrap();
+    controller.apply_can_message(&CanMessage::Reset).unwrap();
+    assert_eq!(controller.get_control_signal().unwrap(), 0.0);
+
+    assert!(controller
+        .apply_can_message(&CanMessage::Status {
+            process_value: 0.0,
+            output: 0.0,
+        })
+        .is_err());
+
+    let codec = CanCodec::standard(0x180).unwrap();
+    let [status, _] = controller.can_telemetry(&codec, 50.0).unwrap();
+    assert_eq!(status.id, 0x180);
+}
+
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+#[test]
+fn test_socketcan_bridge_requires_interface() {
+    let codec = CanCodec::standard(0x180).unwrap();
+    assert!(SocketCanBridge::open("pidgeon-none0", codec).is_err());
+    assert!(SocketCanBridge::open("can\0", codec).is_err());
+}
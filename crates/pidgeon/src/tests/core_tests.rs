@@ -119,6 +119,97 @@ fn test_first_run_returns_p_plus_i() {
     assert!((new_state.integral_contribution - 1.0).abs() < 1e-10);
 }
 
+#[test]
+fn test_initial_output_preloads_integral() {
+    let builder = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.5)
+        .with_setpoint(10.0)
+        .with_output_limits(0.0, 100.0);
+    assert!(builder
+        .clone()
+        .with_initial_output(f64::NAN)
+        .build()
+        .is_err());
+    assert!(builder.clone().with_initial_output(150.0).build().is_err());
+    assert_eq!(builder.clone().build().unwrap().initial_output(), None);
+    assert_eq!(
+        PidState::new(&builder.clone().build().unwrap()),
+        PidState::default()
+    );
+
+    let config = builder.with_initial_output(40.0).build().unwrap();
+    let state = PidState::new(&config);
+    assert_eq!(state.integral_contribution, 40.0);
+    assert!(state.first_run);
+
+    // P and I act on top of the preloaded output from the first step
+    let (output, state) = pid_compute(&config, &state, 10.0, 0.1).unwrap();
+    assert_eq!(output, 40.0);
+    let (output, _) = pid_compute(&config, &state, 9.0, 0.1).unwrap();
+    assert!((output - 42.05).abs() < 1e-10, "got {}", output);
+}
+
+#[test]
+fn test_invariant_checkers() {
+    use crate::testing::*;
+
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(1.0)
+        .with_kd(0.5)
+        .with_setpoint(10.0)
+        .with_output_limits(-5.0, 5.0)
+        .build()
+        .unwrap();
+    let inputs: [StepInput; 4] = [0.0, 4.0, 9.5, 12.0].map(|process_value| StepInput {
+        process_value,
+        dt: 0.1,
+        bias: 1.0,
+    });
+    assert_eq!(check_run(&config, &inputs), Ok(()));
+
+    assert!(check_output_within_limits(&config, 5.0).is_ok());
+    assert_eq!(
+        check_output_within_limits(&config, 5.5),
+        Err(InvariantViolation::OutputOutOfLimits {
+            step: 0,
+            output: 5.5
+        })
+    );
+
+    let state = PidState {
+        prev_filtered_derivative: f64::NAN,
+        ..PidState::default()
+    };
+    assert!(matches!(
+        check_finite(1.0, &state),
+        Err(InvariantViolation::NotFinite {
+            field: "prev_filtered_derivative",
+            ..
+        })
+    ));
+    assert!(check_finite(1.0, &PidState::default()).is_ok());
+
+    // Mixed signs pass trivially; a negative bias counts as positive
+    let mixed = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_kd(-3.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    assert!(check_monotone(&mixed, &PidState::default(), 0.0, -4.0, 0.1).is_ok());
+    assert!(check_monotone(&config, &PidState::default(), 0.0, -4.0, 0.1).is_ok());
+
+    // A rejected step is reported with its index
+    let mut bad = inputs;
+    bad[2].dt = 0.0;
+    assert!(matches!(
+        check_run(&config, &bad),
+        Err(InvariantViolation::Rejected { step: 2, .. })
+    ));
+}
+
 #[test]
 fn test_pure_compute_with_injected_state() {
     let config = ControllerConfig::builder()
@@ -138,6 +229,7 @@ fn test_pure_compute_with_injected_state() {
         prev_measurement: 5.0,
         prev_filtered_derivative: 0.0,
         last_output: 5.0,
+        filtered_setpoint: 0.0,
         first_run: false,
     };
 
@@ -195,6 +287,44 @@ fn test_derivative_kick_elimination() {
     );
 }
 
+#[test]
+fn test_derivative_limits_bound_each_direction() {
+    let builder = || {
+        ControllerConfig::builder()
+            .with_kp(0.0)
+            .with_kd(2.0)
+            .with_output_limits(-1000.0, 1000.0)
+            .with_derivative_filter_coeff(1000.0)
+    };
+    assert!(builder().with_derivative_limits(1.0, 5.0).build().is_err());
+    assert!(builder().with_derivative_limits(0.0, 0.0).build().is_err());
+    assert!(builder()
+        .with_derivative_limits(f64::NAN, 5.0)
+        .build()
+        .is_err());
+    let config = builder()
+        .with_derivative_limits(-3.0, f64::INFINITY)
+        .build()
+        .unwrap();
+    assert_eq!(config.derivative_limits(), Some((-3.0, f64::INFINITY)));
+
+    // A one-sample spike in the measurement: unbounded, the D term would
+    // swing the output by hundreds
+    let state = PidState::new(&config);
+    let (_, state) = pid_compute(&config, &state, 0.0, 0.01).unwrap();
+    let (spike, state) = pid_compute(&config, &state, 5.0, 0.01).unwrap();
+    assert!((-3.0..-2.0).contains(&spike), "got {}", spike);
+    // The filter holds the clamped sample, not the spike
+    assert!(state.prev_filtered_derivative * 2.0 >= -3.0);
+    let (recovery, _) = pid_compute(&config, &state, 0.0, 0.01).unwrap();
+    assert!(recovery > 0.0 && recovery < 1000.0);
+    assert!(
+        recovery > 100.0,
+        "the upward side is unbounded, got {}",
+        recovery
+    );
+}
+
 #[test]
 fn test_derivative_filter() {
     let config_filtered = ControllerConfig::builder()
@@ -262,3 +392,724 @@ fn test_derivative_filter() {
         var_u
     );
 }
+
+#[cfg(feature = "embedded-hal")]
+#[test]
+fn test_hal_loop_with_pwm_actuator() {
+    use embedded_hal::pwm::{ErrorType, SetDutyCycle};
+
+    struct MockPwm {
+        duty: u16,
+    }
+
+    impl ErrorType for MockPwm {
+        type Error = core::convert::Infallible;
+    }
+
+    impl SetDutyCycle for MockPwm {
+        fn max_duty_cycle(&self) -> u16 {
+            1000
+        }
+
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+            self.duty = duty;
+            Ok(())
+        }
+    }
+
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+
+    // 10-bit ADC spanning 0-100 units, reading 40.0
+    let sensor = AdcSensor::new(|| Ok::<u16, ()>(409), 1023, 0.0, 100.0).unwrap();
+    let pwm = PwmActuator::for_config(MockPwm { duty: 0 }, &config);
+    let mut hal_loop = HalLoop::new(config, sensor, pwm);
+
+    let output = hal_loop.step(0.01).unwrap();
+    let expected = 2.0 * (50.0 - 409.0 / 1023.0 * 100.0);
+    assert!((output - expected).abs() < 1e-9);
+
+    let (_, pwm) = hal_loop.release();
+    assert_eq!(pwm.release().duty, (expected * 10.0).round() as u16);
+
+    // Out-of-range outputs clamp to the duty range
+    let mut pwm = PwmActuator::new(MockPwm { duty: 0 }, -1.0, 1.0).unwrap();
+    pwm.write(5.0).unwrap();
+    assert_eq!(pwm.release().duty, 1000);
+    assert!(PwmActuator::new(MockPwm { duty: 0 }, 1.0, 1.0).is_err());
+
+    // Sensor errors do not advance the controller state
+    let config = ControllerConfig::builder()
+        .with_output_limits(-1.0, 1.0)
+        .build()
+        .unwrap();
+    let mut failing = HalLoop::new(
+        config,
+        || Err::<f64, &str>("bus fault"),
+        |_: f64| Ok::<(), ()>(()),
+    );
+    assert_eq!(failing.step(0.01), Err(HalLoopError::Sensor("bus fault")));
+    assert!(failing.state().first_run);
+}
+
+#[cfg(feature = "embedded-hal")]
+#[test]
+fn test_hal_loop_oversampling() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let mut reading = 0.0;
+    let sensor = move || {
+        reading += 1.0;
+        Ok::<f64, ()>(reading)
+    };
+    let mut hal_loop = HalLoop::new(config, sensor, |_: f64| Ok::<(), ()>(()))
+        .with_oversampling(Oversampler::new(4).unwrap());
+
+    // Readings 1-3 from `sample`, 4 from the step itself: mean 2.5
+    for _ in 0..3 {
+        hal_loop.sample().unwrap();
+    }
+    assert_eq!(hal_loop.step(0.01).unwrap(), 7.5);
+    assert_eq!(hal_loop.state().prev_measurement, 2.5);
+
+    // A step with nothing sampled uses its own reading
+    assert_eq!(hal_loop.step(0.01).unwrap(), 5.0);
+
+    // Reset discards readings taken for the next step
+    hal_loop.sample().unwrap();
+    hal_loop.reset();
+    assert_eq!(hal_loop.oversampler().unwrap().pending(), 0);
+    assert_eq!(hal_loop.step(0.01).unwrap(), 3.0);
+}
+
+#[cfg(feature = "critical-section")]
+#[test]
+fn test_critical_section_controller() {
+    static PID: CriticalSectionPidController = CriticalSectionPidController::new_uninit();
+
+    assert!(!PID.is_initialized());
+    assert!(PID.compute(1.0, 0.01).is_err());
+
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    PID.init(config);
+    assert!(PID.is_initialized());
+
+    assert_eq!(PID.compute(6.0, 0.01).unwrap(), 8.0);
+    assert_eq!(PID.compute(8.0, 0.01).unwrap(), 4.0);
+    assert_eq!(PID.get_control_signal().unwrap(), 4.0);
+
+    let stats = PID.statistics().unwrap();
+    assert_eq!(stats.cycles, 2);
+    assert_eq!(stats.average_error, 3.0);
+    assert_eq!(stats.max_error, 4.0);
+    assert_eq!(stats.last_output, 4.0);
+
+    assert!(PID.set_setpoint(f64::NAN).is_err());
+    PID.set_setpoint(12.0).unwrap();
+    assert_eq!(PID.compute(8.0, 0.01).unwrap(), 8.0);
+
+    assert!(PID.set_integral(f64::INFINITY).is_err());
+    PID.set_integral(5.0).unwrap();
+    assert_eq!(PID.compute(8.0, 0.01).unwrap(), 13.0);
+
+    PID.reset().unwrap();
+    assert_eq!(PID.statistics().unwrap(), CycleStatistics::default());
+    assert!(PID.state().unwrap().first_run);
+    assert_eq!(PID.state().unwrap().integral_contribution, 0.0);
+
+    // Opting out leaves the statistics untouched
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .with_statistics(false)
+        .build()
+        .unwrap();
+    PID.init(config);
+    assert_eq!(PID.compute(6.0, 0.01).unwrap(), 8.0);
+    assert_eq!(PID.statistics().unwrap(), CycleStatistics::default());
+}
+
+#[test]
+fn test_negative_gains_switch_bumplessly() {
+    // Cooling is four times as strong as heating
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.5)
+        .with_setpoint(20.0)
+        .with_output_limits(-50.0, 50.0)
+        .with_negative_gains(PidGains {
+            kp: 4.0,
+            ki: 2.0,
+            kd: 0.0,
+        })
+        .build()
+        .unwrap();
+    assert_eq!(config.negative_gains().unwrap().kp, 4.0);
+
+    // Warm up through the setpoint
+    let dt = 0.1;
+    let mut state = PidState::new(&config);
+    let mut outputs = [0.0; 60];
+    for (i, slot) in outputs.iter_mut().enumerate() {
+        let (output, next) = pid_compute(&config, &state, 19.0 + i as f64 * 0.05, dt).unwrap();
+        *slot = output;
+        state = next;
+    }
+
+    let crossing = outputs.iter().position(|&o| o < 0.0).unwrap();
+    assert!(crossing > 1);
+    for window in outputs.windows(2) {
+        // Each step moves P by at most 4.0 * 0.05 and I by at most
+        // 2.0 * 2.0 * dt; switching sets without rebasing the integral would
+        // add (4.0 - 1.0) * error, about 1.35, at the crossing
+        assert!((window[1] - window[0]).abs() < 0.6, "{window:?}");
+    }
+    // Past the crossing the negative set is in charge: 4x the proportional
+    // response to the same 0.05 step, plus 2.0 * error * dt of integral
+    let error = 20.0 - (19.0 + (crossing + 1) as f64 * 0.05);
+    let expected = outputs[crossing] + 4.0 * -0.05 + 2.0 * error * dt;
+    assert!((outputs[crossing + 1] - expected).abs() < 1e-9);
+
+    // Without the option, behaviour is the symmetric controller's
+    let symmetric = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.5)
+        .with_setpoint(20.0)
+        .with_output_limits(-50.0, 50.0)
+        .build()
+        .unwrap();
+    assert_eq!(symmetric.negative_gains(), None);
+
+    assert!(ControllerConfig::builder()
+        .with_negative_gains(PidGains {
+            kp: f64::NAN,
+            ki: 0.0,
+            kd: 0.0,
+        })
+        .with_output_limits(-1.0, 1.0)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_setpoint_filter_softens_steps() {
+    let builder = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_setpoint(0.0)
+        .with_output_limits(-100.0, 100.0);
+    let plain = builder.clone().build().unwrap();
+    let filtered = builder.with_setpoint_filter(1.0).build().unwrap();
+    assert_eq!(filtered.setpoint_filter(), Some(1.0));
+    assert_eq!(plain.setpoint_filter(), None);
+
+    // Settle at zero, then step the setpoint to 10
+    let dt = 0.1;
+    let mut plain_state = PidState::new(&plain);
+    let mut filtered_state = PidState::new(&filtered);
+    for _ in 0..5 {
+        plain_state = pid_compute(&plain, &plain_state, 0.0, dt).unwrap().1;
+        filtered_state = pid_compute(&filtered, &filtered_state, 0.0, dt).unwrap().1;
+    }
+    let mut plain = plain;
+    let mut filtered = filtered;
+    plain.setpoint = 10.0;
+    filtered.setpoint = 10.0;
+
+    // The plain loop kicks by the whole step; the filtered one by dt / (T + dt)
+    let (kick, _) = pid_compute(&plain, &plain_state, 0.0, dt).unwrap();
+    assert_eq!(kick, 20.0);
+    let (soft, next) = pid_compute(&filtered, &filtered_state, 0.0, dt).unwrap();
+    let lag = dt / (1.0 + dt) * 10.0;
+    assert!((soft - 2.0 * lag).abs() < 1e-12);
+    assert!((next.filtered_setpoint - lag).abs() < 1e-12);
+
+    // ...and converges on the setpoint
+    let mut state = next;
+    for _ in 0..200 {
+        state = pid_compute(&filtered, &state, 0.0, dt).unwrap().1;
+    }
+    assert!((state.filtered_setpoint - 10.0).abs() < 1e-6);
+
+    // The first run starts the filter from the process value
+    let (_, first) = pid_compute(&filtered, &PidState::new(&filtered), 4.0, dt).unwrap();
+    assert!((first.filtered_setpoint - (4.0 + lag * 0.6)).abs() < 1e-12);
+
+    for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(ControllerConfig::builder()
+            .with_output_limits(-1.0, 1.0)
+            .with_setpoint_filter(bad)
+            .build()
+            .is_err());
+    }
+}
+
+#[test]
+fn test_proportional_shapes() {
+    let p_at = |shape, error: f64| {
+        let config = ControllerConfig::builder()
+            .with_kp(2.0)
+            .with_setpoint(error)
+            .with_output_limits(-1000.0, 1000.0)
+            .with_proportional_shape(shape)
+            .build()
+            .unwrap();
+        pid_compute(&config, &PidState::new(&config), 0.0, 0.1)
+            .unwrap()
+            .0
+    };
+
+    let squared = ProportionalShape::ErrorSquared {
+        full_gain_error: 5.0,
+    };
+    assert_eq!(p_at(squared, 5.0), 10.0);
+    assert_eq!(p_at(squared, 1.0), 0.4);
+    assert_eq!(p_at(squared, -10.0), -40.0);
+
+    // Quarter gain inside ±2, full gain for the error beyond it
+    let gap = ProportionalShape::Gap {
+        half_width: 2.0,
+        gain_ratio: 0.25,
+    };
+    assert_eq!(p_at(gap, 1.0), 0.5);
+    assert_eq!(p_at(gap, 2.0), 1.0);
+    assert_eq!(p_at(gap, 3.0), 3.0);
+    assert_eq!(p_at(gap, -6.0), -9.0);
+    assert_eq!(p_at(ProportionalShape::Linear, 3.0), 6.0);
+
+    for bad in [
+        ProportionalShape::ErrorSquared {
+            full_gain_error: 0.0,
+        },
+        ProportionalShape::Gap {
+            half_width: f64::NAN,
+            gain_ratio: 0.5,
+        },
+        ProportionalShape::Gap {
+            half_width: 1.0,
+            gain_ratio: 1.5,
+        },
+    ] {
+        assert!(ControllerConfig::builder()
+            .with_output_limits(-1.0, 1.0)
+            .with_proportional_shape(bad)
+            .build()
+            .is_err());
+    }
+}
+
+#[test]
+fn test_supplied_rate_replaces_the_difference() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.5)
+        .with_ki(0.4)
+        .with_kd(0.8)
+        .with_setpoint(10.0)
+        .with_output_limits(-20.0, 20.0)
+        .build()
+        .unwrap();
+
+    // Handing in the same difference pid_compute takes gives the same bits
+    let dt = 0.05;
+    let (mut differenced, mut supplied) = (PidState::default(), PidState::default());
+    let (mut previous, mut process_value) = (0.0, 0.0);
+    for k in 0..40 {
+        process_value += (10.0 - process_value) * 0.1;
+        let rate = (process_value - previous) / dt;
+        let (a, next_a) = pid_compute(&config, &differenced, process_value, dt).unwrap();
+        let (b, next_b) =
+            pid_compute_with_rate(&config, &supplied, process_value, rate, dt).unwrap();
+        assert_eq!(a.to_bits(), b.to_bits(), "step {k}");
+        assert_eq!(next_a, next_b);
+        (differenced, supplied, previous) = (next_a, next_b, process_value);
+    }
+
+    // In OnError mode the rate still acts on the measurement, so a setpoint
+    // step does not kick
+    let on_error = ControllerConfig::builder()
+        .with_kp(0.0)
+        .with_kd(1.0)
+        .with_derivative_mode(DerivativeMode::OnError)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let (_, state) = pid_compute_with_rate(&on_error, &PidState::default(), 0.0, 0.0, dt).unwrap();
+    let moved = ControllerConfig::builder()
+        .with_kp(0.0)
+        .with_kd(1.0)
+        .with_derivative_mode(DerivativeMode::OnError)
+        .with_setpoint(50.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let (output, _) = pid_compute_with_rate(&moved, &state, 0.0, 0.0, dt).unwrap();
+    assert_eq!(output, 0.0);
+
+    assert!(pid_compute_with_rate(&config, &state, 0.0, f64::NAN, dt).is_err());
+}
+
+#[test]
+fn test_feedforward_adds_before_clamping() {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.5)
+        .with_setpoint(10.0)
+        .with_output_limits(0.0, 20.0)
+        .with_anti_windup(true)
+        .build()
+        .unwrap();
+    let dt = 0.1;
+
+    // Within the limits the term shifts the output and nothing else
+    let (plain, plain_state) = pid_compute(&config, &PidState::default(), 8.0, dt).unwrap();
+    let (shifted, shifted_state) =
+        pid_compute_with_feedforward(&config, &PidState::default(), 8.0, 5.0, dt).unwrap();
+    assert_eq!(shifted, plain + 5.0);
+    assert_eq!(
+        shifted_state.integral_contribution,
+        plain_state.integral_contribution
+    );
+
+    // Feedforward alone saturates the output, so the integral holds still
+    let (output, state) =
+        pid_compute_with_feedforward(&config, &shifted_state, 8.0, 30.0, dt).unwrap();
+    assert_eq!(output, 20.0);
+    assert_eq!(
+        state.integral_contribution,
+        shifted_state.integral_contribution
+    );
+
+    assert!(pid_compute_with_feedforward(&config, &state, 8.0, f64::INFINITY, dt).is_err());
+}
+
+#[test]
+fn test_feedforward_lead_lag_response() {
+    // Static: the term follows the disturbance exactly
+    let mut fixed = Feedforward::new(-2.0, 0.0, 0.0).unwrap();
+    assert_eq!(fixed.update(1.0, 0.5).unwrap(), -2.0);
+    assert_eq!(fixed.update(3.0, 0.5).unwrap(), -6.0);
+
+    // Lag: starts settled, then approaches a step from below
+    let mut lagged = Feedforward::new(1.5, 0.0, 10.0).unwrap();
+    assert_eq!(lagged.update(2.0, 1.0).unwrap(), 3.0);
+    let first = lagged.update(4.0, 1.0).unwrap();
+    assert!(first > 3.0 && first < 6.0, "{first}");
+    let mut previous = first;
+    for _ in 0..200 {
+        let term = lagged.update(4.0, 1.0).unwrap();
+        assert!(term >= previous && term <= 6.0);
+        previous = term;
+    }
+    assert!((previous - 6.0).abs() < 1e-6);
+
+    // Lead beyond the lag overshoots before settling
+    let mut leading = Feedforward::new(1.0, 20.0, 5.0).unwrap();
+    leading.update(0.0, 1.0).unwrap();
+    assert!(leading.update(1.0, 1.0).unwrap() > 1.0);
+    for _ in 0..200 {
+        leading.update(1.0, 1.0).unwrap();
+    }
+    assert!((leading.output() - 1.0).abs() < 1e-9);
+
+    leading.reset();
+    assert_eq!(leading.output(), 0.0);
+    assert_eq!(leading.update(2.0, 1.0).unwrap(), 2.0);
+
+    assert!(Feedforward::new(f64::NAN, 0.0, 0.0).is_err());
+    assert!(Feedforward::new(1.0, -1.0, 0.0).is_err());
+    assert!(Feedforward::new(1.0, 0.0, f64::INFINITY).is_err());
+    assert!(leading.update(f64::NAN, 1.0).is_err());
+    assert!(leading.update(1.0, 0.0).is_err());
+}
+
+#[test]
+fn test_tracking_back_calculates_the_integral() {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.3)
+        .with_kd(0.5)
+        .with_setpoint(20.0)
+        .with_output_limits(-50.0, 50.0)
+        .build()
+        .unwrap();
+    let dt = 0.1;
+
+    // The output follows, clamped to the limits
+    let mut state = PidState::default();
+    for (k, tracked) in [10.0, 12.0, 80.0, 15.0].into_iter().enumerate() {
+        let process_value = 15.0 + k as f64;
+        let (output, next) = pid_track(&config, &state, process_value, tracked, dt).unwrap();
+        assert_eq!(output, tracked.min(50.0));
+        assert_eq!(next.last_output, output);
+        state = next;
+    }
+
+    // P + I + D reproduces the tracked output, so taking over at the same
+    // process value moves it only by one step's I and the D filter's decay
+    let (output, next) = pid_compute(&config, &state, 18.0, dt).unwrap();
+    let expected = 15.0
+        + 0.3 * 2.0 * dt
+        + 0.5 * (next.prev_filtered_derivative - state.prev_filtered_derivative);
+    assert!((output - expected).abs() < 1e-9, "{output} vs {expected}");
+
+    // Gains for the tracked output's side are the ones set up
+    let asymmetric = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_negative_gains(PidGains {
+            kp: 4.0,
+            ki: 0.0,
+            kd: 0.0,
+        })
+        .with_setpoint(0.0)
+        .with_output_limits(-10.0, 10.0)
+        .build()
+        .unwrap();
+    let (_, state) = pid_track(&asymmetric, &PidState::default(), 1.0, -3.0, dt).unwrap();
+    assert_eq!(state.integral_contribution, -3.0 + 4.0);
+    let (output, _) = pid_compute(&asymmetric, &state, 1.0, dt).unwrap();
+    assert_eq!(output, -3.0);
+
+    assert!(pid_track(&config, &state, 18.0, f64::NAN, dt).is_err());
+}
+
+#[test]
+fn test_percent_gains_match_engineering_gains() {
+    // 0-400 °C transmitter, 0-50 kW heater: 1.5 %/% is 1.5 * 50 / 400 kW/°C
+    let process = Scaling::new(0.0, 400.0).unwrap();
+    let output = Scaling::new(0.0, 50.0).unwrap();
+    let percent = ControllerConfig::builder()
+        .with_kp(1.5)
+        .with_ki(0.1)
+        .with_kd(0.4)
+        .with_setpoint(250.0)
+        .with_process_scaling(process)
+        .with_output_scaling(output)
+        .build()
+        .unwrap();
+    let factor = 50.0 / 400.0;
+    let engineering = ControllerConfig::builder()
+        .with_kp(1.5 * factor)
+        .with_ki(0.1 * factor)
+        .with_kd(0.4 * factor)
+        .with_setpoint(250.0)
+        .with_output_limits(0.0, 50.0)
+        .build()
+        .unwrap();
+
+    // The output range doubles as the limits, and the gains read back as set
+    assert_eq!((percent.min_output(), percent.max_output()), (0.0, 50.0));
+    assert_eq!(percent.kp(), 1.5);
+    assert_eq!(percent.process_scaling(), Some(process));
+
+    let (mut a, mut b) = (PidState::default(), PidState::default());
+    for process_value in [200.0, 230.0, 245.0, 260.0, 251.0] {
+        let (out_a, next_a) = pid_compute(&percent, &a, process_value, 0.5).unwrap();
+        let (out_b, next_b) = pid_compute(&engineering, &b, process_value, 0.5).unwrap();
+        assert!((out_a - out_b).abs() < 1e-12, "{out_a} vs {out_b}");
+        (a, b) = (next_a, next_b);
+    }
+
+    // Explicit limits win over the output range
+    let narrowed = ControllerConfig::builder()
+        .with_output_scaling(output)
+        .with_output_limits(5.0, 40.0)
+        .build()
+        .unwrap();
+    assert_eq!((narrowed.min_output(), narrowed.max_output()), (5.0, 40.0));
+
+    // Conversions run both ways, including for a reversed raw range
+    let valve = Scaling::from_raw(4095.0, 0.0, 0.0, 100.0).unwrap();
+    assert_eq!(valve.to_raw(100.0), 0.0);
+    assert_eq!(valve.to_engineering(4095.0), 0.0);
+    assert_eq!(valve.from_percent(25.0), 25.0);
+    assert_eq!(process.to_percent(100.0), 25.0);
+
+    assert!(Scaling::new(10.0, 10.0).is_err());
+    assert!(Scaling::new(0.0, f64::INFINITY).is_err());
+    assert!(Scaling::from_raw(5.0, 5.0, 0.0, 1.0).is_err());
+}
+
+#[test]
+fn test_signal_stages() {
+    let mut filter = LowPassFilter::new(1.0).unwrap();
+    assert_eq!(filter.process(10.0, 0.5), 10.0);
+    assert_eq!(filter.process(16.0, 0.5), 12.0);
+    filter.reset();
+    assert_eq!(filter.process(-4.0, 0.5), -4.0);
+
+    let mut ramp = RateLimiter::new(2.0).unwrap();
+    assert_eq!(ramp.process(50.0, 0.1), 50.0);
+    assert_eq!(ramp.process(60.0, 0.5), 51.0);
+    assert_eq!(ramp.process(40.0, 1.0), 49.0);
+
+    let mut quantizer = Quantizer::new(0.25).unwrap();
+    assert_eq!(quantizer.process(1.12, 0.1), 1.0);
+    assert_eq!(quantizer.process(1.125, 0.1), 1.25);
+    assert_eq!(quantizer.process(-1.13, 0.1), -1.25);
+
+    // Chains run in order and reset together
+    let mut path = Limiter::new(0.0, 10.0)
+        .unwrap()
+        .then(|value: f64, _dt: f64| value * 2.0)
+        .then(RateLimiter::new(1.0).unwrap());
+    assert_eq!(path.process(50.0, 1.0), 20.0);
+    assert_eq!(path.process(0.0, 1.0), 19.0);
+    path.reset();
+    assert_eq!(path.process(-3.0, 1.0), 0.0);
+
+    assert!(LowPassFilter::new(0.0).is_err());
+    assert!(RateLimiter::new(f64::INFINITY).is_err());
+    assert!(Limiter::new(1.0, 1.0).is_err());
+    assert!(Quantizer::new(-0.1).is_err());
+}
+
+#[test]
+fn test_oversampler_averages_and_decimates() {
+    let mut oversampler = Oversampler::new(4).unwrap();
+    assert_eq!(oversampler.take(), None);
+
+    // Decimation hands back one mean per block
+    let mut means = [0.0; 2];
+    let mut blocks = 0;
+    for raw in [1.0, 2.0, 3.0, 6.0, 10.0, 10.0, f64::NAN, 12.0, 8.0] {
+        if let Some(mean) = oversampler.decimate(raw) {
+            means[blocks] = mean;
+            blocks += 1;
+        }
+    }
+    assert_eq!(means[..blocks], [3.0, 10.0]);
+    assert_eq!(oversampler.pending(), 0);
+
+    // Period-driven: whatever arrived, non-finite readings dropped
+    oversampler.push(5.0);
+    oversampler.push(f64::INFINITY);
+    oversampler.push(7.0);
+    assert_eq!(oversampler.pending(), 2);
+    assert_eq!(oversampler.take(), Some(6.0));
+
+    // Alternating noise at the sample rate averages out exactly, and the
+    // anti-alias filter smooths what is left of a step
+    let mut filtered = Oversampler::new(2)
+        .unwrap()
+        .with_anti_alias(0.001, 0.001)
+        .unwrap();
+    filtered.push(0.0);
+    filtered.push(10.0);
+    assert_eq!(filtered.take(), Some(2.5));
+    filtered.reset();
+    filtered.push(10.0);
+    assert_eq!(filtered.take(), Some(10.0));
+
+    assert!(Oversampler::new(0).is_err());
+    assert!(Oversampler::new(2)
+        .unwrap()
+        .with_anti_alias(0.01, 0.0)
+        .is_err());
+    assert!(Oversampler::new(2)
+        .unwrap()
+        .with_anti_alias(-1.0, 0.001)
+        .is_err());
+}
+
+#[test]
+fn test_bang_bang_hysteresis_and_min_switch_time() {
+    let mut heater = BangBangController::new(50.0, 2.0, 0.0, 1.0).unwrap();
+    assert_eq!(heater.output(), 0.0);
+    let readings = [48.0, 49.5, 50.9, 51.0, 50.5, 49.1, 49.0, 50.0];
+    let expected = [1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0];
+    for (pv, want) in readings.into_iter().zip(expected) {
+        assert_eq!(heater.compute(pv, 1.0).unwrap(), want, "at {pv}");
+    }
+
+    // Reverse acting, held at least 3 s in each state
+    let mut cooler = BangBangController::new(5.0, 1.0, 0.0, 100.0)
+        .unwrap()
+        .with_reverse_action()
+        .with_min_switch_time(3.0)
+        .unwrap();
+    let readings = [7.0, 4.0, 4.0, 4.0, 4.0, 6.0, 6.0];
+    let expected = [100.0, 100.0, 100.0, 0.0, 0.0, 0.0, 100.0];
+    for (pv, want) in readings.into_iter().zip(expected) {
+        assert_eq!(cooler.compute(pv, 1.0).unwrap(), want, "at {pv}");
+    }
+    assert!(cooler.is_on());
+    cooler.reset();
+    assert!(!cooler.is_on());
+    assert_eq!(Controller::compute(&mut cooler, 4.9, 1.0).unwrap(), 0.0);
+
+    assert!(heater.compute(f64::NAN, 1.0).is_err());
+    assert!(heater.set_setpoint(f64::INFINITY).is_err());
+    assert!(BangBangController::new(0.0, -1.0, 0.0, 1.0).is_err());
+    assert!(BangBangController::new(0.0, 1.0, 1.0, 1.0).is_err());
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn test_bounded_statistics_keep_recent_responses() {
+    let mut config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(1.0)
+        .with_output_limits(0.0, 10.0)
+        .build()
+        .unwrap();
+    let mut stats: BoundedStatistics<2, 4> = BoundedStatistics::new()
+        .with_settled_threshold(0.1)
+        .unwrap();
+
+    // Up to 1.0 with 0.2 of overshoot, then two more setpoints
+    for pv in [0.0, 0.5, 1.2, 1.0] {
+        stats.update(&config, pv, 5.0, 1.0);
+    }
+    config.setpoint = 2.0;
+    stats.update(&config, 1.0, 7.5, 1.0);
+    config.setpoint = 3.0;
+    stats.update(&config, 2.95, 12.0, 2.0);
+
+    assert_eq!(stats.elapsed(), 7.0);
+    assert_eq!(stats.rise_time(), Some(4.0));
+    assert_eq!(stats.settling_time(), Some(7.0));
+    assert_eq!(stats.max_error(), 1.0);
+    assert!((stats.iae() - 2.8).abs() < 1e-12);
+
+    // Only the two most recent responses fit
+    let responses: ([f64; 2], [f64; 2]) = {
+        let mut it = stats.responses();
+        let (a, b) = (it.next().unwrap(), it.next().unwrap());
+        assert!(it.next().is_none());
+        ([a.from, a.setpoint], [b.from, b.setpoint])
+    };
+    assert_eq!(responses, ([1.0, 2.0], [2.0, 3.0]));
+    let last = stats.responses().last().unwrap();
+    assert_eq!((last.start, last.rise_time), (5.0, Some(2.0)));
+
+    // Bins are 0.25 wide over ±0.5 error; the output sits above 10 for 2 s
+    let errors = stats.error_histogram();
+    assert_eq!((errors.low, errors.bin_width()), (-0.5, 0.25));
+    assert_eq!(errors.bins, [0.0, 1.0, 3.0, 0.0]);
+    assert_eq!((errors.below, errors.above), (0.0, 3.0));
+    let outputs = stats.output_histogram().unwrap();
+    assert_eq!(
+        (outputs.bins[2], outputs.bins[3], outputs.above),
+        (4.0, 1.0, 2.0)
+    );
+
+    stats.reset();
+    assert_eq!(stats.responses().count(), 0);
+    assert_eq!(stats.error_histogram().low, -0.5);
+    assert!(BoundedStatistics::<2, 4>::new()
+        .with_settled_threshold(0.0)
+        .is_err());
+}
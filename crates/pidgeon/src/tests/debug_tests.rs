@@ -0,0 +1,638 @@
+use crate::*;
+use std::fs;
+use std::path::PathBuf;
+
+fn sample(timestamp: u64) -> ControllerDebugData {
+    ControllerDebugData {
+        schema_version: DEBUG_SCHEMA_VERSION,
+        timestamp,
+        controller_id: "oven".to_string(),
+        setpoint: 100.0,
+        process_value: 90.0,
+        error: 10.0,
+        output: 20.0,
+        p_term: 20.0,
+        i_term: 0.5,
+        d_term: -0.5,
+        dt: 0.1,
+        kp: 2.0,
+        ki: 0.5,
+        kd: 0.1,
+        saturated: false,
+        mode: ControllerMode::Automatic,
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pidgeon_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_file_sink_csv_and_size_rotation() {
+    let dir = temp_dir("csv_rotation");
+    let path = dir.join("oven.csv");
+
+    let mut sink = FileSink::new(&path, FileFormat::Csv)
+        .unwrap()
+        .with_max_bytes(200)
+        .with_max_files(2);
+
+    for t in 0..20 {
+        sink.write(&sample(t)).unwrap();
+    }
+    sink.flush().unwrap();
+
+    let active = fs::read_to_string(&path).unwrap();
+    let mut lines = active.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "schema_version,timestamp,controller_id,setpoint,process_value,error,output,p_term,i_term,d_term,dt,kp,ki,kd,saturated,mode"
+    );
+    assert_eq!(lines.next().unwrap().split(',').count(), 16);
+
+    // Two rotated files kept, the third was discarded
+    assert!(dir.join("oven.1.csv").exists());
+    assert!(dir.join("oven.2.csv").exists());
+    assert!(!dir.join("oven.3.csv").exists());
+    for rotated in ["oven.1.csv", "oven.2.csv"] {
+        let contents = fs::read_to_string(dir.join(rotated)).unwrap();
+        assert!(contents.starts_with("schema_version,"));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_file_sink_jsonl_appends_across_reopen() {
+    let dir = temp_dir("jsonl");
+    let path = dir.join("run.jsonl");
+
+    {
+        let mut sink = FileSink::new(&path, FileFormat::JsonLines).unwrap();
+        sink.write(&sample(1)).unwrap();
+    }
+    {
+        let mut sink = FileSink::new(&path, FileFormat::JsonLines).unwrap();
+        sink.write(&sample(2)).unwrap();
+    }
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let timestamps: Vec<u64> = contents
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<ControllerDebugData>(line)
+                .unwrap()
+                .timestamp
+        })
+        .collect();
+    assert_eq!(timestamps, vec![1, 2]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_ring_buffer_sink_evicts_oldest_and_queries() {
+    let buffer = RingBufferSink::new(3);
+    let mut writer = buffer.clone();
+
+    for t in 0..5 {
+        let mut data = sample(t);
+        data.p_term = t as f64;
+        writer.write(&data).unwrap();
+    }
+
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.total_received(), 5);
+    assert_eq!(buffer.p_terms(), vec![2.0, 3.0, 4.0]);
+    assert_eq!(buffer.latest().unwrap().timestamp, 4);
+    assert_eq!(buffer.filter(|s| s.p_term >= 3.0).len(), 2);
+
+    buffer.clear();
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.total_received(), 0);
+}
+
+#[test]
+fn test_ring_buffer_sink_wait_for_other_thread() {
+    let buffer = RingBufferSink::new(16);
+    let mut writer = buffer.clone();
+
+    let handle = std::thread::spawn(move || {
+        for t in 0..10 {
+            writer.write(&sample(t)).unwrap();
+        }
+    });
+
+    assert!(buffer.wait_for(10, std::time::Duration::from_secs(5)));
+    handle.join().unwrap();
+    assert_eq!(buffer.outputs().len(), 10);
+    assert!(!buffer.wait_for(11, std::time::Duration::from_millis(10)));
+}
+
+#[cfg(feature = "otel")]
+#[test]
+fn test_otel_sink_records_gauges_per_controller() {
+    use opentelemetry_sdk::metrics::data::Gauge;
+    use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
+
+    let exporter = InMemoryMetricExporter::default();
+    let provider = SdkMeterProvider::builder()
+        .with_reader(PeriodicReader::builder(exporter.clone()).build())
+        .build();
+    let mut sink = OtelSink::with_provider(provider);
+
+    sink.write(&sample(1)).unwrap();
+    let mut other = sample(2);
+    other.controller_id = "fan".to_string();
+    other.output = -5.0;
+    sink.write(&other).unwrap();
+    sink.meter_provider().force_flush().unwrap();
+
+    let exported = exporter.get_finished_metrics().unwrap();
+    let metrics: Vec<_> = exported
+        .iter()
+        .flat_map(|rm| &rm.scope_metrics)
+        .flat_map(|sm| &sm.metrics)
+        .collect();
+    let output = metrics.iter().find(|m| m.name == "pidgeon.output").unwrap();
+    let gauge = output.data.as_any().downcast_ref::<Gauge<f64>>().unwrap();
+
+    let mut points: Vec<(String, f64)> = gauge
+        .data_points
+        .iter()
+        .map(|p| (p.attributes[0].value.to_string(), p.value))
+        .collect();
+    points.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        points,
+        vec![("fan".to_string(), -5.0), ("oven".to_string(), 20.0)]
+    );
+    assert!(metrics.iter().any(|m| m.name == "pidgeon.samples"));
+}
+
+#[test]
+fn test_debug_data_encodings_round_trip() {
+    let data = sample(42);
+
+    for encoding in [
+        DebugEncoding::Json,
+        DebugEncoding::MessagePack,
+        DebugEncoding::Cbor,
+    ] {
+        let bytes = data.encode(encoding).unwrap();
+        assert_eq!(ControllerDebugData::decode(&bytes, encoding).unwrap(), data);
+        assert_eq!(
+            DebugEncoding::from_content_type(encoding.content_type()),
+            Some(encoding)
+        );
+    }
+
+    let json = data.encode(DebugEncoding::Json).unwrap();
+    assert!(ControllerDebugData::decode(&json, DebugEncoding::Cbor).is_err());
+    assert_eq!(DebugEncoding::from_content_type("text/plain"), None);
+}
+
+#[test]
+fn test_debug_data_decodes_v1_payload() {
+    let v1 = r#"{"timestamp":7,"controller_id":"oven","setpoint":100.0,"process_value":90.0,
+        "error":10.0,"output":20.0,"p_term":20.0,"i_term":0.5,"d_term":-0.5}"#;
+
+    let data = ControllerDebugData::decode(v1.as_bytes(), DebugEncoding::Json).unwrap();
+    assert_eq!(data.schema_version, 1);
+    assert_eq!(data.timestamp, 7);
+    assert_eq!(data.kp, 0.0);
+    assert!(!data.saturated);
+    assert_eq!(data.mode, ControllerMode::Automatic);
+
+    let json = String::from_utf8(sample(1).encode(DebugEncoding::Json).unwrap()).unwrap();
+    assert!(json.contains(r#""schema_version":2"#));
+    assert!(json.contains(r#""mode":"automatic""#));
+}
+
+#[test]
+fn test_sample_queue_backpressure_policies() {
+    use crate::debug::SampleQueue;
+
+    let drain = |queue: &SampleQueue| {
+        std::iter::from_fn(|| queue.try_pop())
+            .map(|s| s.timestamp)
+            .collect::<Vec<_>>()
+    };
+
+    let cases = [
+        (BackpressurePolicy::DropOldest, vec![1, 2]),
+        (BackpressurePolicy::DropNewest, vec![0, 1]),
+        (BackpressurePolicy::CoalesceLatest, vec![0, 2]),
+        (
+            BackpressurePolicy::BlockWithTimeout(std::time::Duration::from_millis(5)),
+            vec![0, 1],
+        ),
+    ];
+    for (policy, expected) in cases {
+        let queue = SampleQueue::new(2, policy);
+        for t in 0..3 {
+            queue.push(sample(t));
+        }
+        let diagnostics = queue.diagnostics();
+        assert_eq!(diagnostics.dropped, 1, "{:?}", policy);
+        assert_eq!(diagnostics.queued, 2, "{:?}", policy);
+        assert_eq!(drain(&queue), expected, "{:?}", policy);
+    }
+}
+
+#[test]
+fn test_sample_queue_block_with_timeout_waits_for_consumer() {
+    use crate::debug::SampleQueue;
+    use std::sync::Arc;
+
+    let queue = Arc::new(SampleQueue::new(
+        1,
+        BackpressurePolicy::BlockWithTimeout(std::time::Duration::from_secs(5)),
+    ));
+    queue.push(sample(0));
+
+    let consumer = {
+        let queue = Arc::clone(&queue);
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            queue.pop().map(|s| s.timestamp)
+        })
+    };
+
+    // Blocks until the consumer frees a slot, then enqueues
+    queue.push(sample(1));
+    assert_eq!(consumer.join().unwrap(), Some(0));
+    assert_eq!(queue.diagnostics().dropped, 0);
+    assert_eq!(queue.try_pop().unwrap().timestamp, 1);
+
+    queue.close();
+    assert!(queue.pop().is_none());
+}
+
+#[test]
+fn test_reconnect_policy_backoff_grows_to_cap() {
+    use std::time::Duration;
+
+    let policy = ReconnectPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(1),
+        multiplier: 2.0,
+        outage_buffer: 10,
+    };
+    let delays: Vec<u128> = (1..=6)
+        .map(|failures| policy.backoff_after(failures).as_millis())
+        .collect();
+    assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    assert_eq!(policy.backoff_after(u32::MAX), Duration::from_secs(1));
+}
+
+#[test]
+fn test_debugger_reports_disconnected_when_iggy_unreachable() {
+    use std::time::{Duration, Instant};
+
+    let debugger = ControllerDebugger::new(DebugConfig {
+        iggy_url: "127.0.0.1:1".to_string(),
+        controller_id: "unreachable_iggy".to_string(),
+        ..Default::default()
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !matches!(
+        debugger.connection_state(),
+        ConnectionState::Disconnected { .. }
+    ) {
+        assert!(
+            Instant::now() < deadline,
+            "{:?}",
+            debugger.connection_state()
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        debugger.connection_state(),
+        ConnectionState::Disconnected { failures: 1 }
+    );
+}
+
+#[test]
+fn test_decimator_average_and_envelope() {
+    use crate::debug::Decimator;
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let interval = Some(Duration::from_millis(100));
+    let values = [5.0, 1.0, 9.0, 3.0];
+    let run = |decimation| {
+        let mut decimator = Decimator::new(decimation, interval, start);
+        let mut out = Vec::new();
+        for (i, pv) in values.iter().enumerate() {
+            let mut data = sample(i as u64);
+            data.process_value = *pv;
+            data.saturated = i == 1;
+            let now = start + Duration::from_millis(30 * (i as u64 + 1));
+            decimator.offer(data, now, |d| out.push(d));
+        }
+        out
+    };
+
+    // 30, 60, 90 ms stay in the window; 120 ms closes it
+    let averaged = run(Decimation::Average);
+    assert_eq!(averaged.len(), 1);
+    assert_eq!(averaged[0].process_value, 4.5);
+    assert_eq!(averaged[0].timestamp, 3);
+    assert!(averaged[0].saturated);
+
+    let envelope: Vec<f64> = run(Decimation::MinMaxEnvelope)
+        .iter()
+        .map(|d| d.process_value)
+        .collect();
+    assert_eq!(envelope, vec![1.0, 9.0]);
+}
+
+#[test]
+fn test_decimator_on_change_with_heartbeat() {
+    use crate::debug::Decimator;
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let mut decimator = Decimator::new(
+        Decimation::OnChange { threshold: 0.5 },
+        Some(Duration::from_secs(1)),
+        start,
+    );
+    let mut sent = Vec::new();
+    for (ms, pv) in [(0, 90.0), (10, 90.2), (20, 90.8), (30, 90.9), (1100, 90.9)] {
+        let mut data = sample(ms);
+        data.process_value = pv;
+        decimator.offer(data, start + Duration::from_millis(ms), |d| {
+            sent.push(d.timestamp)
+        });
+    }
+
+    // First sample, the 0.8 jump, then the heartbeat after a quiet second
+    assert_eq!(sent, vec![0, 20, 1100]);
+}
+
+#[cfg(feature = "remote-control")]
+#[test]
+fn test_hmac_sha256_rfc4231_vector() {
+    let mac = crate::remote::hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+    let hex: String = mac.iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(
+        hex,
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[cfg(feature = "remote-control")]
+#[test]
+fn test_command_validator_rejects_forged_stale_and_replayed() {
+    use crate::remote::{CommandValidator, Verdict};
+    use std::time::Duration;
+
+    let config = RemoteControlConfig::new("s3cret").with_max_age(Duration::from_secs(5));
+    let mut validator = CommandValidator::new("oven", &config);
+    let command = RemoteCommand::new("oven", "alice", RemoteAction::SetSetpoint { value: 180.0 });
+    let message = command.sign(b"s3cret");
+    let now = command.timestamp + 1_000;
+
+    assert_eq!(
+        validator.validate(&message, now),
+        Verdict::Accepted(command.clone())
+    );
+    assert_eq!(
+        validator.validate(&message, now),
+        Verdict::Rejected(Some(command), "duplicate command id")
+    );
+
+    let stale = RemoteCommand::new("oven", "alice", RemoteAction::SetSetpoint { value: 1.0 });
+    assert_eq!(
+        validator.validate(&stale.sign(b"s3cret"), stale.timestamp + 6_000),
+        Verdict::Rejected(Some(stale), "command expired")
+    );
+
+    let forged = RemoteCommand::new("oven", "mallory", RemoteAction::SetSetpoint { value: 1.0 });
+    let tampered = String::from_utf8(forged.sign(b"s3cret"))
+        .unwrap()
+        .replace("\"value\":1.0", "\"value\":999.0");
+    match validator.validate(tampered.as_bytes(), now) {
+        Verdict::Rejected(Some(claimed), "invalid signature") => {
+            assert_eq!(claimed.issued_by, "mallory");
+        }
+        verdict => panic!("tampered command got {:?}", verdict),
+    }
+    assert_eq!(
+        validator.validate(&forged.sign(b"guess"), now),
+        Verdict::Rejected(Some(forged), "invalid signature")
+    );
+    assert_eq!(
+        validator.validate(b"{\"id\":\"1\"}", now),
+        Verdict::Rejected(None, "invalid signature")
+    );
+
+    // Other controllers may use other secrets; theirs to audit, not ours
+    let other = RemoteCommand::new("fan", "alice", RemoteAction::SetSetpoint { value: 1.0 });
+    assert_eq!(
+        validator.validate(&other.sign(b"s3cret"), now),
+        Verdict::Ignored
+    );
+    assert_eq!(
+        validator.validate(&other.sign(b"fan secret"), now),
+        Verdict::Ignored
+    );
+}
+
+#[cfg(feature = "remote-control")]
+#[test]
+fn test_signed_command_verifies_over_the_bytes_as_sent() {
+    // Written by some other client: its own field order and spacing
+    let json = br#"{ "action": {"value": 2.5, "action": "set_setpoint"},
+        "timestamp": 1700000000000, "issued_by": "scada", "controller_id": "oven", "id": "c-1" }"#;
+    let signature: String = crate::remote::hmac_sha256(b"s3cret", json)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let message = [signature.as_bytes(), b"\n", json].concat();
+
+    let command = RemoteCommand::from_signed(&message, b"s3cret").unwrap();
+    assert_eq!(command.id, "c-1");
+    assert_eq!(command.timestamp, 1_700_000_000_000);
+    assert_eq!(command.action, RemoteAction::SetSetpoint { value: 2.5 });
+
+    let mut reformatted = message.clone();
+    reformatted.retain(|&b| b != b' ');
+    assert_eq!(
+        RemoteCommand::from_signed(&reformatted, b"s3cret"),
+        Err("invalid signature")
+    );
+
+    let signature: String = crate::remote::hmac_sha256(b"s3cret", b"null")
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let not_a_command = [signature.as_bytes(), b"\nnull"].concat();
+    assert_eq!(
+        RemoteCommand::from_signed(&not_a_command, b"s3cret"),
+        Err("malformed command")
+    );
+}
+
+#[cfg(feature = "remote-control")]
+#[test]
+fn test_apply_command_sets_gains_atomically() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.1)
+        .with_kd(0.01)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+
+    let result = controller.apply_command(&RemoteAction::SetGains {
+        kp: Some(2.0),
+        ki: Some(f64::NAN),
+        kd: None,
+    });
+    assert!(result.is_err());
+    assert_eq!(controller.config().kp, 1.0);
+
+    controller
+        .apply_command(&RemoteAction::SetGains {
+            kp: Some(2.0),
+            ki: None,
+            kd: Some(0.5),
+        })
+        .unwrap();
+    assert_eq!(
+        (
+            controller.config().kp,
+            controller.config().ki,
+            controller.config().kd
+        ),
+        (2.0, 0.1, 0.5)
+    );
+
+    controller
+        .apply_command(&RemoteAction::SetSetpoint { value: 42.0 })
+        .unwrap();
+    assert_eq!(controller.setpoint(), 42.0);
+
+    let json = serde_json::to_string(&RemoteAction::SetGains {
+        kp: Some(2.0),
+        ki: None,
+        kd: None,
+    })
+    .unwrap();
+    assert_eq!(json, r#"{"action":"set_gains","kp":2.0}"#);
+}
+
+#[test]
+fn test_debugger_shutdown_drains_queue_into_sinks() {
+    use std::time::Duration;
+
+    let ring = RingBufferSink::new(64);
+    let mut debugger = ControllerDebugger::new(
+        DebugConfig {
+            iggy_url: "127.0.0.1:1".to_string(),
+            controller_id: "shutdown_drain".to_string(),
+            ..Default::default()
+        }
+        .with_sink(ring.clone()),
+    );
+    for _ in 0..10 {
+        debugger.send_debug_data(100.0, 90.0, 10.0, 20.0, 20.0, 0.0, 0.0);
+    }
+
+    // Everything reached the sink; Iggy was unreachable, so not a clean flush
+    assert!(!debugger.shutdown(Duration::from_secs(10)));
+    assert_eq!(ring.len(), 10);
+    assert_eq!(debugger.connection_state(), ConnectionState::Closed);
+
+    debugger.send_debug_data(100.0, 90.0, 10.0, 20.0, 20.0, 0.0, 0.0);
+    assert_eq!(debugger.diagnostics().dropped, 1);
+    assert!(debugger.shutdown(Duration::from_secs(1)));
+
+    let _ = fs::remove_file("shutdown_drain_debug.log");
+}
+
+#[test]
+fn test_capture_emits_pre_and_post_trigger_window() {
+    use crate::debug::Capture;
+    use std::time::{Duration, Instant};
+
+    let config = CaptureConfig::new(Duration::from_millis(30), Duration::from_millis(50))
+        .with_trigger(CaptureTrigger::ErrorAbove(5.0))
+        .with_trigger(CaptureTrigger::ModeChange);
+    let mut capture = Capture::new(config, 100);
+    let start = Instant::now();
+
+    let mut captured = Vec::new();
+    let mut passed = Vec::new();
+    for t in (0..=200).step_by(10) {
+        let mut data = sample(t);
+        data.error = if t == 100 { 10.0 } else { 1.0 };
+        if t >= 190 {
+            data.mode = ControllerMode::Manual;
+        }
+        let now = start + Duration::from_millis(t);
+        if let Some(data) = capture.observe(data, now, |d| captured.push(d.timestamp)) {
+            passed.push(data.timestamp);
+        }
+    }
+
+    assert_eq!(
+        captured,
+        vec![70, 80, 90, 100, 110, 120, 130, 140, 150, 160, 170, 180, 190, 200]
+    );
+    // Pre-trigger history was handed to decimation before the trigger fired
+    assert_eq!(
+        passed,
+        vec![0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 160, 170, 180]
+    );
+    assert!(capture.is_capturing());
+}
+
+#[test]
+fn test_controller_snapshot_bundles_state_and_recent_samples() {
+    use std::time::Duration;
+
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.5)
+        .with_setpoint(50.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config).with_debugging(DebugConfig {
+        iggy_url: "127.0.0.1:1".to_string(),
+        controller_id: "snapshot_test".to_string(),
+        snapshot_history: 4,
+        ..Default::default()
+    });
+    assert!(PidController::new(controller.config().clone())
+        .snapshot()
+        .is_none());
+
+    for _ in 0..10 {
+        controller.compute(40.0, 0.1).unwrap();
+    }
+    let snapshot = controller.snapshot().unwrap();
+    assert_eq!(snapshot.controller_id, "snapshot_test");
+    assert_eq!(snapshot.config.kp, 2.0);
+    assert_eq!(snapshot.state, *controller.state());
+    assert_eq!(snapshot.recent_samples.len(), 4);
+    assert_eq!(snapshot.diagnostics.enqueued, 10);
+
+    // Never reached the setpoint, so rise time is NaN and must survive JSON
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let decoded: ControllerSnapshot = serde_json::from_str(&json).unwrap();
+    assert!(decoded.statistics.rise_time.is_nan());
+    assert_eq!(decoded.recent_samples, snapshot.recent_samples);
+    assert_eq!(decoded.state, snapshot.state);
+
+    controller.shutdown_debugger(Duration::from_secs(5));
+    let _ = fs::remove_file("snapshot_test_debug.log");
+}
@@ -0,0 +1,260 @@
+use crate::*;
+
+fn config() -> ControllerConfig {
+    ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.5)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap()
+}
+
+fn decode_all(bytes: &[u8]) -> Vec<Result<HilFrame, PidError>> {
+    let mut decoder = HilDecoder::new();
+    bytes
+        .iter()
+        .filter_map(|&byte| decoder.push(byte))
+        .collect()
+}
+
+#[test]
+fn test_hil_frames_round_trip_every_message() {
+    let messages = [
+        HilMessage::ProcessValue {
+            process_value: 48.5,
+            dt: 0.01,
+        },
+        HilMessage::Command { output: -3.25 },
+        HilMessage::Telemetry {
+            setpoint: 50.0,
+            process_value: 48.5,
+            output: 0.0,
+            error: 1.5,
+        },
+        HilMessage::Reset,
+    ];
+    for (seq, message) in messages.into_iter().enumerate() {
+        let frame = HilFrame {
+            seq: seq as u8,
+            message,
+        };
+        let mut buf = [0u8; HIL_FRAME_MAX];
+        let len = frame.encode(&mut buf).unwrap();
+        assert_eq!(buf[len - 1], 0, "frame must end with its delimiter");
+        assert!(
+            !buf[..len - 1].contains(&0),
+            "COBS must remove every zero from the body"
+        );
+        assert_eq!(decode_all(&buf[..len]), vec![Ok(frame)]);
+    }
+}
+
+#[test]
+fn test_hil_encode_rejects_small_buffers() {
+    let frame = HilFrame {
+        seq: 0,
+        message: HilMessage::Command { output: 1.0 },
+    };
+    assert!(frame.encode(&mut [0u8; 4]).is_err());
+}
+
+#[test]
+fn test_hil_decoder_rejects_corruption_and_resynchronizes() {
+    let frame = HilFrame {
+        seq: 3,
+        message: HilMessage::Command { output: 42.0 },
+    };
+    let mut buf = [0u8; HIL_FRAME_MAX];
+    let len = frame.encode(&mut buf).unwrap();
+
+    let mut corrupt = buf[..len].to_vec();
+    corrupt[3] ^= 0x10;
+    let mut stream = vec![0x55, 0x66];
+    stream.extend_from_slice(&[0]);
+    stream.extend_from_slice(&corrupt);
+    stream.extend_from_slice(&[0xAA; HIL_FRAME_MAX + 5]);
+    stream.push(0);
+    stream.extend_from_slice(&buf[..len]);
+
+    let results = decode_all(&stream);
+    assert_eq!(results.len(), 4);
+    assert!(results[..3].iter().all(|result| result.is_err()));
+    assert_eq!(results[3], Ok(frame));
+}
+
+#[test]
+fn test_hil_device_answers_with_telemetry_then_command() {
+    let config = config();
+    let mut state = PidState::default();
+    let mut device = HilDevice::new();
+    let mut rx = [0u8; HIL_FRAME_MAX];
+    let len = HilFrame {
+        seq: 9,
+        message: HilMessage::ProcessValue {
+            process_value: 45.0,
+            dt: 0.5,
+        },
+    }
+    .encode(&mut rx)
+    .unwrap();
+
+    let mut tx = [0u8; 2 * HIL_FRAME_MAX];
+    let replies: Vec<_> = rx[..len]
+        .iter()
+        .filter_map(|&byte| device.push(byte, &config, &mut state, &mut tx))
+        .collect();
+    let reply_len = replies[0].clone().unwrap();
+
+    let (expected, _) = pid_compute(&config, &PidState::default(), 45.0, 0.5).unwrap();
+    assert_eq!(
+        decode_all(&tx[..reply_len]),
+        vec![
+            Ok(HilFrame {
+                seq: 9,
+                message: HilMessage::Telemetry {
+                    setpoint: 50.0,
+                    process_value: 45.0,
+                    output: expected as f32,
+                    error: 5.0,
+                },
+            }),
+            Ok(HilFrame {
+                seq: 9,
+                message: HilMessage::Command {
+                    output: expected as f32,
+                },
+            }),
+        ]
+    );
+    assert_eq!(state.last_output, expected);
+
+    let len = HilFrame {
+        seq: 10,
+        message: HilMessage::Reset,
+    }
+    .encode(&mut rx)
+    .unwrap();
+    let replies: Vec<_> = rx[..len]
+        .iter()
+        .filter_map(|&byte| device.push(byte, &config, &mut state, &mut tx))
+        .collect();
+    assert_eq!(replies, vec![Ok(0)]);
+    assert_eq!(state, PidState::new(&config));
+}
+
+#[cfg(feature = "std")]
+mod host {
+    use super::config;
+    use crate::*;
+    use std::collections::VecDeque;
+    use std::io::{self, Read, Write};
+
+    /// A serial port with firmware on the other end
+    struct Loopback {
+        device: HilDevice,
+        config: ControllerConfig,
+        state: PidState,
+        rx: VecDeque<u8>,
+        /// Replies injected ahead of the device's own
+        noise: Vec<u8>,
+    }
+
+    impl Loopback {
+        fn new(config: ControllerConfig) -> Self {
+            Loopback {
+                device: HilDevice::new(),
+                state: PidState::new(&config),
+                config,
+                rx: VecDeque::new(),
+                noise: Vec::new(),
+            }
+        }
+    }
+
+    impl Write for Loopback {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut reply = [0u8; 2 * HIL_FRAME_MAX];
+            for &byte in buf {
+                if let Some(Ok(len)) =
+                    self.device
+                        .push(byte, &self.config, &mut self.state, &mut reply)
+                {
+                    self.rx.extend(self.noise.drain(..));
+                    self.rx.extend(&reply[..len]);
+                }
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for Loopback {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.rx.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_hil_host_closes_the_loop_and_matches_simulation() {
+        let config = config();
+        let mut host = HilHost::new(Loopback::new(config.clone()));
+        let mut room = ThermalRoomPlant::new(40.0, 10.0);
+
+        host.reset().unwrap();
+        let run = host
+            .run(room.temperature(), 0.5, 200, |output, dt| {
+                room.step(output, dt)
+            })
+            .unwrap();
+
+        assert_eq!(run.steps.len(), 200);
+        assert!(run.steps.iter().all(|step| step.setpoint == Some(50.0)));
+        let comparison = run.compare(&config).unwrap();
+        assert!(comparison.within(1e-4), "{:?}", comparison);
+        assert_eq!(host.skipped_frames(), 0);
+
+        let retuned = ControllerConfig::builder()
+            .with_kp(3.0)
+            .with_ki(0.5)
+            .with_setpoint(50.0)
+            .with_output_limits(0.0, 100.0)
+            .build()
+            .unwrap();
+        assert!(!run.compare(&retuned).unwrap().within(1e-4));
+    }
+
+    #[test]
+    fn test_hil_host_skips_stale_and_corrupt_frames() {
+        let mut port = Loopback::new(config());
+        let mut stale = [0u8; HIL_FRAME_MAX];
+        let len = HilFrame {
+            seq: 0,
+            message: HilMessage::Command { output: 99.0 },
+        }
+        .encode(&mut stale)
+        .unwrap();
+        port.noise.extend_from_slice(&stale[..len]);
+        port.noise.extend_from_slice(&[1, 2, 3, 0]);
+
+        let mut host = HilHost::new(port);
+        let step = host.exchange(45.0, 0.5).unwrap();
+        let (expected, _) = pid_compute(&config(), &PidState::default(), 45.0, 0.5).unwrap();
+        assert_eq!(step.output, f64::from(expected as f32));
+        assert_eq!(host.skipped_frames(), 2);
+    }
+
+    #[test]
+    fn test_hil_host_reports_a_silent_device() {
+        let mut host = HilHost::new(io::Cursor::new(Vec::new()));
+        let err = host.exchange(1.0, 0.1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            host.exchange(f64::NAN, 0.1).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
@@ -2,3 +2,24 @@ mod core_tests;
 
 #[cfg(feature = "std")]
 mod std_tests;
+
+#[cfg(feature = "debugging")]
+mod debug_tests;
+
+#[cfg(feature = "opcua")]
+mod opcua_tests;
+
+#[cfg(feature = "can")]
+mod can_tests;
+
+#[cfg(feature = "serial")]
+mod serial_tests;
+
+#[cfg(feature = "hil")]
+mod hil_tests;
+
+#[cfg(feature = "proptest")]
+mod proptest_tests;
+
+#[cfg(feature = "units")]
+mod units_tests;
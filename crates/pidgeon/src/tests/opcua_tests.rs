@@ -0,0 +1,85 @@
+use crate::opcua::{LoopNodeManager, Roles};
+use crate::*;
+use ::opcua::server::authenticator::UserToken;
+use ::opcua::server::ANONYMOUS_USER_TOKEN_ID;
+use ::opcua::types::{DataValue, NodeId, StatusCode, Variant};
+use std::sync::Arc;
+
+fn oven() -> ControllerRegistry {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(0.0, 100.0)
+        .with_gain_limits(Gain::Kp, 0.0, 5.0)
+        .build()
+        .unwrap();
+    let mut registry = ControllerRegistry::new();
+    registry.register_config("oven", config);
+    registry
+}
+
+#[test]
+fn test_opcua_writes_use_the_role_of_the_session_user() {
+    let config = OpcUaConfig::new("127.0.0.1", 4840)
+        .with_user("scada", "hunter2", AccessRole::Viewer)
+        .with_user("shift-lead", "correct horse", AccessRole::Operator);
+    let registry = oven();
+    let manager = LoopNodeManager::new(registry.clone(), Arc::new(Roles::new(&config)), 1);
+
+    let setpoint = NodeId::new(1, LoopNode::Setpoint.path("oven"));
+    let write = |user: &str, node_id: &NodeId, value: f64| {
+        manager.write_value(
+            &UserToken(user.to_string()),
+            node_id,
+            &DataValue::new_now(Variant::Double(value)),
+        )
+    };
+
+    assert_eq!(
+        write("scada", &setpoint, 20.0),
+        StatusCode::BadUserAccessDenied
+    );
+    // Anonymous sessions have no role unless the config grants one
+    assert_eq!(
+        write(ANONYMOUS_USER_TOKEN_ID, &setpoint, 20.0),
+        StatusCode::BadUserAccessDenied
+    );
+    assert_eq!(
+        registry.read_node("oven", LoopNode::Setpoint).unwrap(),
+        Some(NodeValue::Number(10.0))
+    );
+
+    assert_eq!(write("shift-lead", &setpoint, 20.0), StatusCode::Good);
+    assert_eq!(
+        registry.read_node("oven", LoopNode::Setpoint).unwrap(),
+        Some(NodeValue::Number(20.0))
+    );
+
+    // The operator's writes still go through the gain limits
+    let kp = NodeId::new(1, LoopNode::Kp.path("oven"));
+    assert_eq!(write("shift-lead", &kp, 9.0), StatusCode::BadOutOfRange);
+    let output = NodeId::new(1, LoopNode::Output.path("oven"));
+    assert_eq!(
+        write("shift-lead", &output, 1.0),
+        StatusCode::BadUserAccessDenied
+    );
+
+    let anonymous = Roles::new(&config.with_anonymous(AccessRole::Operator));
+    assert_eq!(
+        anonymous.role(&UserToken(ANONYMOUS_USER_TOKEN_ID.to_string())),
+        Some(AccessRole::Operator)
+    );
+    assert_eq!(anonymous.role(&UserToken("mallory".to_string())), None);
+}
+
+#[test]
+fn test_opcua_server_starts_and_stops() {
+    let pki_dir = std::env::temp_dir().join(format!("pidgeon_opcua_{}", std::process::id()));
+    let config = OpcUaConfig::new("127.0.0.1", 0)
+        .with_user("scada", "hunter2", AccessRole::Viewer)
+        .with_pki_dir(&pki_dir);
+
+    let server = OpcUaServer::start(&oven(), config).unwrap();
+    server.stop();
+    let _ = std::fs::remove_dir_all(pki_dir);
+}
@@ -0,0 +1,27 @@
+use crate::testing::*;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_every_config_holds_invariants(config in arb_config(), inputs in arb_inputs(1..200)) {
+        check_run(&config, &inputs)?;
+    }
+
+    #[test]
+    fn test_invariants_hold_from_any_state(
+        config in arb_config(),
+        state in arb_state(),
+        input in arb_input(),
+    ) {
+        check_step(&config, &state, &input)?;
+    }
+
+    #[test]
+    fn test_generated_configs_round_trip_through_the_builder(config in arb_config()) {
+        prop_assert!(config.min_output() < config.max_output());
+        if let Some(output) = config.initial_output() {
+            prop_assert!(output >= config.min_output() && output <= config.max_output());
+        }
+        prop_assert!(config.deadband() >= 0.0);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::*;
+
+fn config() -> ControllerConfig {
+    ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap()
+}
+
+fn encode(message: &SerialMessage) -> String {
+    let mut buf = [0u8; SERIAL_LINE_MAX];
+    let len = message.encode(&mut buf).unwrap();
+    String::from_utf8(buf[..len].to_vec()).unwrap()
+}
+
+#[test]
+fn test_serial_lines_round_trip_every_message() {
+    let messages = [
+        (
+            SerialMessage::Telemetry {
+                process_value: 48.5,
+                setpoint: 50.0,
+                output: -3.0,
+                error: 1.5,
+            },
+            "tel 48.5 50 -3 1.5\n",
+        ),
+        (
+            SerialMessage::Gains {
+                kp: 2.0,
+                ki: 0.125,
+                kd: 0.0,
+            },
+            "gains 2 0.125 0\n",
+        ),
+        (SerialMessage::Rejected, "nak\n"),
+        (
+            SerialMessage::SetSetpoint { setpoint: -12.25 },
+            "sp -12.25\n",
+        ),
+        (
+            SerialMessage::SetGain {
+                gain: Gain::Ki,
+                value: 0.1,
+            },
+            "ki 0.1\n",
+        ),
+        (SerialMessage::GetGains, "get\n"),
+        (SerialMessage::Reset, "reset\n"),
+    ];
+    for (message, line) in messages {
+        assert_eq!(encode(&message), line);
+        assert_eq!(line.parse::<SerialMessage>(), Ok(message));
+        assert_eq!(
+            message.is_command(),
+            !matches!(
+                message,
+                SerialMessage::Telemetry { .. }
+                    | SerialMessage::Gains { .. }
+                    | SerialMessage::Rejected
+            )
+        );
+    }
+}
+
+#[test]
+fn test_serial_numbers_stay_short_and_exact() {
+    let extremes = SerialMessage::Telemetry {
+        process_value: -f64::MAX,
+        setpoint: f64::MIN_POSITIVE,
+        output: 0.1 + 0.2,
+        error: -1.234_567_890_123_456_7e-300,
+    };
+    let line = encode(&extremes);
+    assert!(line.len() <= SERIAL_LINE_MAX, "{}", line);
+    assert_eq!(line.parse::<SerialMessage>(), Ok(extremes));
+    assert_eq!(
+        encode(&SerialMessage::SetSetpoint { setpoint: 2e9 }),
+        "sp 2e9\n"
+    );
+
+    let mut small = [0u8; 4];
+    assert!(SerialMessage::Reset.encode(&mut small).is_err());
+}
+
+#[test]
+fn test_serial_rejects_bad_lines() {
+    for line in [
+        "",
+        "kp",
+        "kp fast",
+        "kp NaN",
+        "sp inf",
+        "kp 1 2",
+        "KP 1",
+        "ku 1",
+        "tel 1 2 3",
+    ] {
+        assert!(line.parse::<SerialMessage>().is_err(), "{:?}", line);
+    }
+    assert_eq!(
+        "  kd\t0.5 \r\n".parse(),
+        Ok(SerialMessage::SetGain {
+            gain: Gain::Kd,
+            value: 0.5,
+        })
+    );
+}
+
+#[test]
+fn test_serial_reader_splits_lines_and_recovers() {
+    let mut reader = SerialReader::new();
+    let mut messages = Vec::new();
+    let mut stream = b"sp 1\r\n\r\nget\n".to_vec();
+    stream.extend(vec![b'9'; SERIAL_LINE_MAX + 10]);
+    stream.extend(b"\n\xff\nreset\n");
+    for byte in stream {
+        messages.extend(reader.push(byte));
+    }
+
+    assert_eq!(messages.len(), 5);
+    assert_eq!(
+        messages[0],
+        Ok(SerialMessage::SetSetpoint { setpoint: 1.0 })
+    );
+    assert_eq!(messages[1], Ok(SerialMessage::GetGains));
+    assert!(messages[2].is_err());
+    assert!(messages[3].is_err());
+    assert_eq!(messages[4], Ok(SerialMessage::Reset));
+}
+
+#[test]
+fn test_serial_commands_apply_to_config_and_state() {
+    let mut config = config();
+    let (_, mut state) = pid_compute(&config, &PidState::default(), 45.0, 0.01).unwrap();
+    assert_eq!(
+        SerialMessage::telemetry(&config, &state, 45.0),
+        SerialMessage::Telemetry {
+            process_value: 45.0,
+            setpoint: 50.0,
+            output: 10.0,
+            error: 5.0,
+        }
+    );
+
+    for line in ["sp 60", "kp 4", "ki 0.5", "kd 0.25", "get"] {
+        let command: SerialMessage = line.parse().unwrap();
+        command.apply(&mut config, &mut state).unwrap();
+    }
+    assert_eq!(config.setpoint(), 60.0);
+    assert_eq!(
+        SerialMessage::gains(&config),
+        SerialMessage::Gains {
+            kp: 4.0,
+            ki: 0.5,
+            kd: 0.25,
+        }
+    );
+
+    SerialMessage::Reset.apply(&mut config, &mut state).unwrap();
+    assert_eq!(state.last_output, 0.0);
+    assert!(SerialMessage::Rejected
+        .apply(&mut config, &mut state)
+        .is_err());
+    assert!(SerialMessage::SetGain {
+        gain: Gain::Kp,
+        value: f64::NAN,
+    }
+    .apply(&mut config, &mut state)
+    .is_err());
+    assert_eq!(config.kp(), 4.0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_serial_commands_update_controller() {
+    let mut controller = PidController::new(config());
+    controller
+        .apply_serial_message(&SerialMessage::SetGain {
+            gain: Gain::Kp,
+            value: 3.0,
+        })
+        .unwrap();
+    assert_eq!(controller.config().kp(), 3.0);
+
+    controller.compute(45.0, 0.01).unwrap();
+    controller
+        .apply_serial_message(&SerialMessage::Reset)
+        .unwrap();
+    assert_eq!(controller.state().last_output, 0.0);
+    assert!(controller
+        .apply_serial_message(&SerialMessage::Gains {
+            kp: 1.0,
+            ki: 0.0,
+            kd: 0.0,
+        })
+        .is_err());
+}
+
+#[cfg(feature = "critical-section")]
+#[test]
+fn test_serial_commands_update_critical_section_controller() {
+    let controller = CriticalSectionPidController::new(config());
+    controller
+        .apply_serial_message(&SerialMessage::SetSetpoint { setpoint: 70.0 })
+        .unwrap();
+    controller.compute(45.0, 0.01).unwrap();
+    assert_eq!(controller.config().unwrap().setpoint(), 70.0);
+
+    controller
+        .apply_serial_message(&SerialMessage::Reset)
+        .unwrap();
+    assert_eq!(controller.statistics().unwrap().cycles, 0);
+}
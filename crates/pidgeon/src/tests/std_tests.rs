@@ -373,4 +373,2965 @@ fn test_cached_output() {
         cached2,
         output2
     );
+
+    let state = controller.get_state().unwrap();
+    assert_eq!(state.last_output, output2);
+    assert_eq!(state.prev_measurement, 7.0);
+    assert!(!state.first_run);
+}
+
+#[test]
+fn test_controller_registry() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.5)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .expect("Invalid config");
+
+    let mut registry = ControllerRegistry::new();
+    assert!(registry.is_empty());
+
+    let loop_b = registry.register_config("loop_b", config.clone());
+    let loop_a = registry.register_config("loop_a", config.clone());
+    assert_eq!(registry.len(), 2);
+    assert_eq!(
+        registry.names().collect::<Vec<_>>(),
+        vec!["loop_a", "loop_b"]
+    );
+
+    loop_a.compute(5.0, 0.1).unwrap();
+    loop_b.compute(8.0, 0.1).unwrap();
+
+    // Handles returned by the registry share state with the stored controllers
+    let stored = registry
+        .get("loop_a")
+        .unwrap()
+        .get_control_signal()
+        .unwrap();
+    assert_eq!(stored, loop_a.get_control_signal().unwrap());
+
+    let stats = registry.statistics().unwrap();
+    assert_eq!(stats.len(), 2);
+    assert!((stats["loop_a"].average_error - 5.0).abs() < 1e-9);
+    assert!((stats["loop_b"].average_error - 2.0).abs() < 1e-9);
+
+    registry.reset_all().unwrap();
+    assert_eq!(loop_a.get_control_signal().unwrap(), 0.0);
+    assert_eq!(loop_b.get_control_signal().unwrap(), 0.0);
+
+    // Dump, mutate, and restore configs
+    let snapshot = registry.dump_configs().unwrap();
+    loop_a.set_kp(3.0).unwrap();
+    assert_eq!(loop_a.get_config().unwrap().kp(), 3.0);
+
+    let mut snapshot_with_new = snapshot.clone();
+    snapshot_with_new.insert("loop_c".to_string(), config);
+    registry.restore_configs(&snapshot_with_new).unwrap();
+
+    assert_eq!(loop_a.get_config().unwrap().kp(), 1.0);
+    assert!(registry.contains("loop_c"));
+
+    assert!(registry.remove("loop_b").is_some());
+    assert!(registry.get("loop_b").is_none());
+    assert_eq!(registry.len(), 2);
+}
+
+#[test]
+fn test_loop_nodes_read_and_write_by_role() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .with_gain_limits(Gain::Kp, 0.0, 5.0)
+        .build()
+        .unwrap();
+    let mut registry = ControllerRegistry::new();
+    let oven = registry.register_config("plant.oven", config);
+
+    // Paths round-trip, even through dotted loop names
+    let path = LoopNode::Kp.path("plant.oven");
+    assert_eq!(path, "plant.oven.Kp");
+    assert_eq!(
+        LoopNode::parse_path(&path),
+        Some(("plant.oven", LoopNode::Kp))
+    );
+    assert_eq!(LoopNode::parse_path("plant.oven.Bogus"), None);
+
+    // Measurements are empty until the first step
+    let read = |node| registry.read_node("plant.oven", node).unwrap();
+    assert_eq!(read(LoopNode::ProcessValue), None);
+    assert_eq!(read(LoopNode::Setpoint), Some(NodeValue::Number(10.0)));
+    assert_eq!(
+        read(LoopNode::Mode),
+        Some(NodeValue::Mode(OperatingMode::Automatic))
+    );
+    oven.compute(4.0, 0.1).unwrap();
+    assert_eq!(read(LoopNode::ProcessValue), Some(NodeValue::Number(4.0)));
+    assert_eq!(read(LoopNode::Output), Some(NodeValue::Number(6.0)));
+
+    // Viewers read only; operators and admins write setpoint and gains
+    assert!(!AccessRole::Viewer.can_write(LoopNode::Setpoint));
+    assert!(!AccessRole::Admin.can_write(LoopNode::Output));
+    assert!(registry
+        .write_node("plant.oven", LoopNode::Setpoint, 20.0, AccessRole::Viewer)
+        .is_err());
+    assert!(registry
+        .write_node("plant.oven", LoopNode::Output, 20.0, AccessRole::Admin)
+        .is_err());
+    registry
+        .write_node("plant.oven", LoopNode::Setpoint, 20.0, AccessRole::Operator)
+        .unwrap();
+    assert_eq!(read(LoopNode::Setpoint), Some(NodeValue::Number(20.0)));
+
+    // Writes go through the setters, so gain limits still apply
+    assert!(registry
+        .write_node("plant.oven", LoopNode::Kp, 9.0, AccessRole::Admin)
+        .is_err());
+    registry
+        .write_node("plant.oven", LoopNode::Kp, 2.0, AccessRole::Admin)
+        .unwrap();
+    assert_eq!(oven.get_config().unwrap().kp(), 2.0);
+    assert!(oven
+        .events()
+        .unwrap()
+        .iter()
+        .any(|event| matches!(event.kind, EventKind::GainRejected { gain: Gain::Kp, .. })));
+
+    assert!(registry.read_node("boiler", LoopNode::Kp).is_err());
+}
+
+#[test]
+fn test_registry_group_broadcast_is_scheduled_and_ramped() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(100.0)
+        .with_output_limits(-10.0, 10.0)
+        .build()
+        .unwrap();
+    let mut registry = ControllerRegistry::new();
+    let zone_a = registry.register_config("zone_a", config.clone());
+    let zone_b = registry.register_config("zone_b", config.clone());
+    let zone_c = registry.register_config("zone_c", config);
+    registry
+        .define_group(
+            "furnace",
+            vec![
+                GroupMember::new("zone_b").with_ramp_rate(2.0),
+                GroupMember::new("zone_a").with_ratio(0.5),
+                GroupMember::new("zone_c")
+                    .with_ratio(1.5)
+                    .with_ramp_rate(4.0),
+            ],
+        )
+        .unwrap();
+    assert_eq!(registry.group_names().collect::<Vec<_>>(), ["furnace"]);
+    let setpoints =
+        || [&zone_a, &zone_b, &zone_c].map(|zone| zone.get_config().unwrap().setpoint());
+
+    // Nothing moves before the coordinated instant
+    let start = std::time::Instant::now();
+    registry
+        .broadcast_scaled_setpoint("furnace", 80.0, start + Duration::from_secs(5))
+        .unwrap();
+    registry.update_groups(start).unwrap();
+    assert_eq!(setpoints(), [100.0, 100.0, 100.0]);
+    assert!(registry.is_broadcasting("furnace"));
+
+    // zone_a steps, zone_b ramps down at 2/s, zone_c ramps up at 4/s
+    registry
+        .update_groups(start + Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(setpoints(), [40.0, 100.0, 100.0]);
+    registry
+        .update_groups(start + Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(setpoints(), [40.0, 90.0, 120.0]);
+    registry
+        .update_groups(start + Duration::from_secs(30))
+        .unwrap();
+    assert_eq!(setpoints(), [40.0, 80.0, 120.0]);
+    assert!(!registry.is_broadcasting("furnace"));
+
+    // A ramp logs one setpoint change, not one per step
+    let changes: Vec<_> = zone_c
+        .events()
+        .unwrap()
+        .iter()
+        .map(|event| event.kind)
+        .collect();
+    assert_eq!(
+        changes,
+        [EventKind::SetpointChanged {
+            from: 100.0,
+            to: 120.0
+        }]
+    );
+
+    // A uniform broadcast ignores ratios
+    registry.broadcast_setpoint("furnace", 50.0, start).unwrap();
+    registry
+        .update_groups(start + Duration::from_secs(1000))
+        .unwrap();
+    assert_eq!(setpoints(), [50.0, 50.0, 50.0]);
+}
+
+#[test]
+fn test_registry_group_validation() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-10.0, 10.0)
+        .build()
+        .unwrap();
+    let mut registry = ControllerRegistry::new();
+    let zone = registry.register_config("zone", config);
+    registry.register("alias", zone.clone());
+
+    assert!(registry.define_group("empty", vec![]).is_err());
+    assert!(registry
+        .define_group("missing", vec![GroupMember::new("nope")])
+        .is_err());
+    assert!(registry
+        .define_group(
+            "twice",
+            vec![GroupMember::new("zone"), GroupMember::new("alias")]
+        )
+        .is_err());
+    assert!(registry
+        .define_group(
+            "bad_rate",
+            vec![GroupMember::new("zone").with_ramp_rate(0.0)]
+        )
+        .is_err());
+    assert!(registry
+        .define_group(
+            "bad_ratio",
+            vec![GroupMember::new("zone").with_ratio(f64::NAN)]
+        )
+        .is_err());
+    assert_eq!(registry.group_names().count(), 0);
+
+    registry
+        .define_group("zones", vec![GroupMember::new("zone")])
+        .unwrap();
+    let now = std::time::Instant::now();
+    assert!(registry.broadcast_setpoint("nope", 1.0, now).is_err());
+    assert!(registry
+        .broadcast_setpoint("zones", f64::INFINITY, now)
+        .is_err());
+    assert!(!registry.is_broadcasting("zones"));
+
+    // A member unregistered before the broadcast fires drops the broadcast
+    registry.broadcast_setpoint("zones", 20.0, now).unwrap();
+    registry.remove("zone");
+    assert!(registry.update_groups(now).is_err());
+    assert!(!registry.is_broadcasting("zones"));
+    assert_eq!(zone.get_config().unwrap().setpoint(), 10.0);
+
+    assert!(registry.remove_group("zones").is_some());
+    assert!(registry.group("zones").is_none());
+}
+
+#[test]
+fn test_registry_transaction_is_all_or_nothing() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(0.0, 100.0)
+        .with_initial_output(50.0)
+        .build()
+        .unwrap();
+    let mut registry = ControllerRegistry::new();
+    let a = registry.register_config("a", config.clone());
+    let b = registry.register_config("b", config.clone());
+    a.compute(5.0, 0.1).unwrap();
+    let state = a.get_state().unwrap();
+
+    // b's new limits exclude its initial output, so a is not touched either
+    let rejected = registry
+        .transaction()
+        .set_gains("a", 3.0, 0.2, 0.1)
+        .set_output_limits("b", 60.0, 100.0)
+        .commit();
+    assert!(matches!(rejected, Err(PidError::InvalidParameter(_))));
+    assert_eq!(a.get_config().unwrap(), config);
+    assert_eq!(b.get_config().unwrap(), config);
+
+    assert!(registry
+        .transaction()
+        .set_setpoint("a", 20.0)
+        .set_setpoint("missing", 20.0)
+        .commit()
+        .is_err());
+    registry.register("alias", a.clone());
+    assert!(registry
+        .transaction()
+        .set_setpoint("a", 20.0)
+        .set_setpoint("alias", 30.0)
+        .commit()
+        .is_err());
+    assert_eq!(a.get_config().unwrap().setpoint(), 10.0);
+
+    // Changes to one loop apply in the order they were staged
+    let replacement = ControllerConfig::builder()
+        .with_kp(9.0)
+        .with_output_limits(-1.0, 1.0)
+        .build()
+        .unwrap();
+    let transaction = registry
+        .transaction()
+        .set_gains("a", 3.0, 0.2, 0.1)
+        .set_setpoint("a", 20.0)
+        .set_config("b", replacement)
+        .set_setpoint("b", 0.5);
+    assert_eq!(transaction.len(), 2);
+    let previous = transaction.commit().unwrap();
+    assert_eq!(previous["a"], config);
+    assert_eq!(previous["b"], config);
+
+    let a_config = a.get_config().unwrap();
+    assert_eq!(
+        (a_config.kp(), a_config.ki(), a_config.kd()),
+        (3.0, 0.2, 0.1)
+    );
+    assert_eq!(a_config.setpoint(), 20.0);
+    assert_eq!(a_config.max_output(), 100.0);
+    let b_config = b.get_config().unwrap();
+    assert_eq!((b_config.kp(), b_config.setpoint()), (9.0, 0.5));
+    assert_eq!(a.get_state().unwrap(), state);
+    assert!(a.events().unwrap().iter().any(|event| event.kind
+        == EventKind::GainsChanged {
+            kp: 3.0,
+            ki: 0.2,
+            kd: 0.1
+        }));
+
+    registry.restore_configs(&previous).unwrap();
+    assert_eq!(a.get_config().unwrap(), config);
+    assert_eq!(b.get_config().unwrap(), config);
+}
+
+#[test]
+fn test_integral_preload() {
+    // A heater that needs 30% duty to hold temperature
+    let config = ControllerConfig::builder()
+        .with_kp(4.0)
+        .with_ki(0.2)
+        .with_setpoint(60.0)
+        .with_output_limits(0.0, 100.0)
+        .with_initial_output(30.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+    assert_eq!(controller.compute(60.0, 1.0).unwrap(), 30.0);
+    assert_eq!(controller.compute(59.0, 1.0).unwrap(), 34.2);
+
+    // Reset returns to the preload, not to zero
+    controller.reset();
+    assert_eq!(controller.state().integral_contribution, 30.0);
+
+    // Bumpless transfer from a manual output of 55 at an error of 2
+    assert!(controller.set_integral(f64::NAN).is_err());
+    controller.set_integral(55.0 - 4.0 * 2.0).unwrap();
+    assert_eq!(controller.compute(58.0, 1.0).unwrap(), 55.4);
+
+    let shared = ThreadSafePidController::new(controller.config().clone());
+    shared.set_integral(12.0).unwrap();
+    assert_eq!(shared.get_state().unwrap().integral_contribution, 12.0);
+}
+
+/// Runs a heater through a setpoint move, retuning, and a reset while
+/// recording
+fn recorded_heater_run() -> Recording {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.8)
+        .with_kd(0.3)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .with_deadband(0.1)
+        .with_anti_windup_mode(AntiWindupMode::BackCalculation { tracking_time: 0.5 })
+        .with_initial_output(20.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+    controller.start_recording();
+    let dt = 0.1;
+    let mut temperature = 20.0;
+    for step in 0..45 {
+        match step {
+            20 => controller.set_setpoint(60.0).unwrap(),
+            30 => controller.set_kp(3.0).unwrap(),
+            35 => controller.reset(),
+            _ => {}
+        }
+        let output = controller.compute(temperature, dt).unwrap();
+        temperature += (0.5 * output - (temperature - 20.0)) * dt / 5.0;
+    }
+    controller.take_recording().unwrap()
+}
+
+#[test]
+fn test_recording_replays_bit_identically() {
+    let recording = recorded_heater_run();
+    let steps = recording.steps();
+    assert_eq!(steps.len(), 45);
+    assert_eq!(recording.verify(), Ok(()));
+
+    // Only the first step and changes carry a config or starting state
+    let with_config: Vec<usize> = (0..45).filter(|&i| steps[i].config.is_some()).collect();
+    assert_eq!(with_config, vec![0, 20, 30]);
+    let with_start: Vec<usize> = (0..45).filter(|&i| steps[i].start.is_some()).collect();
+    assert_eq!(with_start, vec![0, 35]);
+    assert_eq!(
+        steps[35].start.as_ref().unwrap().integral_contribution,
+        20.0
+    );
+
+    // One bit off in the output is caught at that step
+    let mut tampered = recording.clone();
+    let output = &mut tampered.steps[12].output;
+    *output = f64::from_bits(output.to_bits() + 1);
+    match tampered.verify() {
+        Err(ReplayMismatch::Differs { step, field, .. }) => {
+            assert_eq!((step, field), (12, "output"));
+        }
+        other => panic!("expected a mismatch, got {:?}", other),
+    }
+    let mut tampered = recording.clone();
+    tampered.steps[40].dt = -1.0;
+    assert!(matches!(
+        tampered.verify(),
+        Err(ReplayMismatch::Rejected { step: 40, .. })
+    ));
+
+    let config = ControllerConfig::builder()
+        .with_output_limits(-1.0, 1.0)
+        .build()
+        .unwrap();
+    assert!(PidController::new(config).take_recording().is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_recorded_run_fixture_replays() {
+    // Captured before any later change to `pid_compute`; a refactor that
+    // changes a single bit of the numerics fails here
+    let fixture = include_str!("fixtures/heater_run.json");
+    let recording: Recording = serde_json::from_str(fixture).unwrap();
+    assert_eq!(recording.steps().len(), 45);
+    assert_eq!(recording.verify(), Ok(()));
+
+    let saved: Recording =
+        serde_json::from_str(&serde_json::to_string(&recorded_heater_run()).unwrap()).unwrap();
+    assert_eq!(saved.steps(), recording.steps());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_config_serde_roundtrip() {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.5)
+        .with_setpoint(42.0)
+        .with_output_limits(-10.0, 10.0)
+        .with_anti_windup_mode(AntiWindupMode::BackCalculation { tracking_time: 0.5 })
+        .build()
+        .expect("Invalid config");
+
+    let mut registry = ControllerRegistry::new();
+    registry.register_config("zone", config);
+
+    let json = serde_json::to_string(&registry.dump_configs().unwrap()).unwrap();
+    let restored: std::collections::BTreeMap<String, ControllerConfig> =
+        serde_json::from_str(&json).unwrap();
+    assert_eq!(restored["zone"].kp(), 2.0);
+    assert_eq!(restored["zone"].setpoint(), 42.0);
+    assert_eq!(
+        restored["zone"].anti_windup_mode(),
+        AntiWindupMode::BackCalculation { tracking_time: 0.5 }
+    );
+
+    assert!(!json.contains("initial_output"));
+
+    // Deserialization runs builder validation
+    let invalid = r#"{"kp": 1.0, "min_output": 5.0, "max_output": -5.0}"#;
+    assert!(serde_json::from_str::<ControllerConfig>(invalid).is_err());
+    let preloaded = r#"{"min_output": 0.0, "max_output": 1.0, "initial_output": 0.25}"#;
+    let config: ControllerConfig = serde_json::from_str(preloaded).unwrap();
+    assert_eq!(config.initial_output(), Some(0.25));
+    let outside = r#"{"min_output": 0.0, "max_output": 1.0, "initial_output": 2.0}"#;
+    assert!(serde_json::from_str::<ControllerConfig>(outside).is_err());
+}
+
+#[test]
+fn test_loop_scheduler_phase_staggering() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    let counts: Vec<Arc<AtomicUsize>> = (0..4).map(|_| Arc::new(AtomicUsize::new(0))).collect();
+    let mut scheduler = LoopScheduler::new();
+    for (i, count) in counts.iter().enumerate() {
+        let count = Arc::clone(count);
+        scheduler
+            .add_loop(
+                format!("loop_{}", i),
+                Duration::from_millis(100),
+                move |_| {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    // Duplicate names and zero periods are rejected
+    assert!(scheduler
+        .add_loop("loop_0", Duration::from_millis(10), |_| Ok(()))
+        .is_err());
+    assert!(scheduler
+        .add_loop("zero", Duration::ZERO, |_| Ok(()))
+        .is_err());
+    assert!(scheduler
+        .add_loop_with_phase(
+            "bad_phase",
+            Duration::from_millis(10),
+            Duration::from_millis(10),
+            |_| Ok(())
+        )
+        .is_err());
+
+    let t0 = Instant::now();
+    scheduler.start(t0);
+
+    // Four loops at 100 ms are offset by 0, 25, 50, 75 ms
+    assert_eq!(scheduler.run_pending(t0), 1);
+    assert_eq!(scheduler.run_pending(t0 + Duration::from_millis(30)), 1);
+    assert_eq!(scheduler.run_pending(t0 + Duration::from_millis(80)), 2);
+    assert_eq!(scheduler.run_pending(t0 + Duration::from_millis(90)), 0);
+    assert_eq!(
+        scheduler.next_deadline(),
+        Some(t0 + Duration::from_millis(100))
+    );
+
+    for count in &counts {
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[test]
+fn test_loop_scheduler_timing_accounting() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    let dts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&dts);
+
+    let mut scheduler = LoopScheduler::new();
+    scheduler
+        .add_loop_with_phase(
+            "pid",
+            Duration::from_millis(10),
+            Duration::from_millis(5),
+            move |dt| {
+                recorded.lock().unwrap().push(dt);
+                if dt > 0.015 {
+                    Err(PidError::InvalidParameter("late"))
+                } else {
+                    Ok(())
+                }
+            },
+        )
+        .unwrap();
+
+    let t0 = Instant::now();
+    scheduler.start(t0);
+    assert_eq!(scheduler.run_pending(t0), 0);
+    assert_eq!(scheduler.run_pending(t0 + Duration::from_millis(5)), 1);
+    assert_eq!(scheduler.run_pending(t0 + Duration::from_millis(15)), 1);
+    // Fall 3 periods behind: runs once, skips the missed deadlines
+    assert_eq!(scheduler.run_pending(t0 + Duration::from_millis(47)), 1);
+    assert_eq!(
+        scheduler.next_deadline(),
+        Some(t0 + Duration::from_millis(55))
+    );
+
+    let timing = scheduler.timing("pid").unwrap();
+    assert_eq!(timing.runs, 3);
+    assert_eq!(timing.errors, 1);
+    assert_eq!(timing.missed_deadlines, 2);
+    assert!(timing.max_exec_time >= timing.last_exec_time);
+    assert!(timing.total_exec_time >= timing.max_exec_time);
+
+    let dts = dts.lock().unwrap();
+    assert!((dts[0] - 0.010).abs() < 1e-9);
+    assert!((dts[1] - 0.010).abs() < 1e-9);
+    assert!((dts[2] - 0.032).abs() < 1e-9);
+}
+
+#[test]
+fn test_sensor_watchdog_fallbacks() {
+    use std::time::Instant;
+
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .expect("Invalid config");
+    let timeout = Duration::from_millis(100);
+    let t0 = Instant::now();
+    let ms = |n: u64| t0 + Duration::from_millis(n);
+
+    assert!(SensorWatchdog::new(Duration::ZERO, StaleFallback::HoldLastOutput, t0).is_err());
+    assert!(SensorWatchdog::new(timeout, StaleFallback::Failsafe(f64::NAN), t0).is_err());
+
+    // Hold: keeps last automatic output, resumes automatically
+    let mut controller = PidController::new(config.clone());
+    let mut watchdog = SensorWatchdog::new(timeout, StaleFallback::HoldLastOutput, t0).unwrap();
+    watchdog.record_measurement(4.0, t0).unwrap();
+    let fresh = watchdog.step(&mut controller, ms(10), 0.01).unwrap();
+    assert!(fresh.automatic);
+    assert_eq!(fresh.output, 6.0);
+    assert_eq!(fresh.event, None);
+
+    let stale = watchdog.step(&mut controller, ms(150), 0.01).unwrap();
+    assert!(!stale.automatic);
+    assert_eq!(stale.output, 6.0);
+    assert_eq!(
+        stale.event,
+        Some(StalenessEvent::BecameStale {
+            age: Duration::from_millis(150)
+        })
+    );
+    // Event is only emitted on the transition
+    assert_eq!(
+        watchdog.step(&mut controller, ms(160), 0.01).unwrap().event,
+        None
+    );
+
+    watchdog.record_measurement(7.0, ms(200)).unwrap();
+    let recovered = watchdog.step(&mut controller, ms(200), 0.01).unwrap();
+    assert!(recovered.automatic);
+    assert_eq!(
+        recovered.event,
+        Some(StalenessEvent::Recovered {
+            stale_for: Duration::from_millis(50)
+        })
+    );
+
+    // Failsafe: fixed output while stale
+    let mut controller = PidController::new(config.clone());
+    let mut watchdog = SensorWatchdog::new(timeout, StaleFallback::Failsafe(-5.0), t0).unwrap();
+    let before_first = watchdog.step(&mut controller, ms(50), 0.01).unwrap();
+    assert!(!before_first.automatic);
+    assert_eq!(before_first.event, None);
+    let stale = watchdog.step(&mut controller, ms(150), 0.01).unwrap();
+    assert_eq!(stale.output, -5.0);
+    assert!(matches!(
+        stale.event,
+        Some(StalenessEvent::BecameStale { .. })
+    ));
+
+    // Manual: latches until resume_auto
+    let controller = ThreadSafePidController::new(config);
+    let mut watchdog = SensorWatchdog::new(timeout, StaleFallback::Manual, t0).unwrap();
+    watchdog.record_measurement(4.0, t0).unwrap();
+    assert!(
+        watchdog
+            .step_shared(&controller, ms(10), 0.01)
+            .unwrap()
+            .automatic
+    );
+    let stale = watchdog.step_shared(&controller, ms(150), 0.01).unwrap();
+    assert!(watchdog.is_manual());
+    assert_eq!(stale.output, 6.0);
+    watchdog.set_manual_output(12.5).unwrap();
+
+    watchdog.record_measurement(9.0, ms(200)).unwrap();
+    let still_manual = watchdog.step_shared(&controller, ms(200), 0.01).unwrap();
+    assert!(!still_manual.automatic);
+    assert_eq!(still_manual.output, 12.5);
+
+    watchdog.resume_auto();
+    assert!(
+        watchdog
+            .step_shared(&controller, ms(210), 0.01)
+            .unwrap()
+            .automatic
+    );
+}
+
+/// Open-loop step response of `model`, starting at rest, sampled every `dt`
+fn step_test(model: &FopdtModel, dt: f64, steps: usize) -> (Vec<f64>, Vec<f64>) {
+    let decay = (-dt / model.time_constant).exp();
+    let delay = (model.dead_time / dt).round() as usize;
+    let outputs: Vec<f64> = (0..steps)
+        .map(|k| if k < 20 { 0.0 } else { 10.0 })
+        .collect();
+    let mut process_values = vec![25.0; steps];
+    for k in 1..steps {
+        let applied = if k > delay {
+            outputs[k - 1 - delay]
+        } else {
+            0.0
+        };
+        process_values[k] =
+            25.0 + decay * (process_values[k - 1] - 25.0) + model.gain * (1.0 - decay) * applied;
+    }
+    (outputs, process_values)
+}
+
+#[test]
+fn test_identify_fopdt_from_step_test() {
+    let truth = FopdtModel::new(2.0, 8.0, 1.5).unwrap();
+    let (outputs, process_values) = step_test(&truth, 0.1, 600);
+
+    let identified = FopdtModel::identify(&outputs, &process_values, 0.1).unwrap();
+    assert!(identified.fit > 0.99, "fit {}", identified.fit);
+    assert!((identified.model.gain - 2.0).abs() < 0.01);
+    assert!((identified.model.time_constant - 8.0).abs() < 0.05);
+    assert!((identified.model.dead_time - 1.5).abs() < 0.11);
+}
+
+#[test]
+fn test_identify_rejects_unexcited_data() {
+    let outputs = vec![5.0; 100];
+    let process_values = vec![20.0; 100];
+    assert!(FopdtModel::identify(&outputs, &process_values, 0.1).is_err());
+    assert!(FopdtModel::identify(&outputs[..5], &process_values[..5], 0.1).is_err());
+    assert!(FopdtModel::identify(&outputs, &process_values[..50], 0.1).is_err());
+}
+
+#[test]
+fn test_tuning_rules() {
+    let model = FopdtModel::new(2.0, 10.0, 2.0).unwrap();
+
+    // Ziegler–Nichols PID: Kc = 1.2τ/(Kθ), Ti = 2θ, Td = θ/2
+    let zn = TuningRule::ZieglerNicholsPid.gains(&model).unwrap();
+    assert!((zn.kp - 3.0).abs() < 1e-12);
+    assert!((zn.ki - 0.75).abs() < 1e-12);
+    assert!((zn.kd - 3.0).abs() < 1e-12);
+
+    // SIMC PI with τc = θ: Kc = τ/(K·2θ), Ti = min(τ, 8θ)
+    let simc = TuningRule::SimcPi.gains(&model).unwrap();
+    assert!((simc.kp - 1.25).abs() < 1e-12);
+    assert!((simc.ki - 0.125).abs() < 1e-12);
+    assert_eq!(simc.kd, 0.0);
+
+    // Rules built around dead time refuse models without it
+    let no_delay = FopdtModel::new(2.0, 10.0, 0.0).unwrap();
+    assert!(TuningRule::CohenCoonPid.gains(&no_delay).is_err());
+    assert!(TuningRule::LambdaPi.gains(&no_delay).is_ok());
+
+    for rule in TuningRule::ALL {
+        assert_eq!(rule.name().parse::<TuningRule>().unwrap(), rule);
+        let gains = rule.gains(&model).unwrap();
+        assert!(gains.kp > 0.0 && gains.ki > 0.0 && gains.kd >= 0.0);
+    }
+    assert!("pi-by-eye".parse::<TuningRule>().is_err());
+}
+
+#[test]
+fn test_simulated_response_settles_at_setpoint() {
+    let model = FopdtModel::new(2.0, 10.0, 2.0).unwrap();
+    for rule in [
+        TuningRule::SimcPi,
+        TuningRule::SimcPid,
+        TuningRule::LambdaPi,
+    ] {
+        let gains = rule.gains(&model).unwrap();
+        let config = ControllerConfig::builder()
+            .with_kp(gains.kp)
+            .with_ki(gains.ki)
+            .with_kd(gains.kd)
+            .with_setpoint(30.0)
+            .with_output_limits(-100.0, 100.0)
+            .build()
+            .unwrap();
+        let response = model.simulate(&config, 20.0, 0.05, 4000).unwrap();
+        let last = response.last().unwrap();
+        assert!(
+            (last.process_value - 30.0).abs() < 0.05,
+            "{} ended at {}",
+            rule,
+            last.process_value
+        );
+        // Output is only applied after the dead time
+        assert_eq!(response[30].process_value, 20.0);
+    }
+}
+
+/// Closes a loop around a FOPDT plant resting at 20 and returns the
+/// process values and outputs
+fn run_fopdt_loop<C: Controller + ?Sized>(
+    controller: &mut C,
+    plant: &FopdtModel,
+    dt: f64,
+    steps: usize,
+) -> (Vec<f64>, Vec<f64>) {
+    let decay = (-dt / plant.time_constant).exp();
+    let delay = (plant.dead_time / dt).round() as usize;
+    let mut pending: std::collections::VecDeque<f64> = std::iter::repeat_n(0.0, delay).collect();
+    let mut deviation = 0.0;
+    let (mut process_values, mut outputs) = (Vec::new(), Vec::new());
+    for _ in 0..steps {
+        let process_value = 20.0 + deviation;
+        let output = controller.compute(process_value, dt).unwrap();
+        process_values.push(process_value);
+        outputs.push(output);
+        pending.push_back(output);
+        let applied = pending.pop_front().unwrap();
+        deviation = decay * deviation + plant.gain * (1.0 - decay) * applied;
+    }
+    (process_values, outputs)
+}
+
+#[test]
+fn test_mpc_tracks_dead_time_plant_without_offset() {
+    let model = FopdtModel::new(2.0, 60.0, 30.0).unwrap();
+    let config = MpcConfig::builder(model)
+        .with_setpoint(30.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = MpcController::new(config);
+
+    // With a perfect model the step is taken without overshoot
+    let (process_values, outputs) = run_fopdt_loop(&mut controller, &model, 1.0, 600);
+    let peak = process_values.iter().copied().fold(f64::MIN, f64::max);
+    assert!(peak < 30.0 + 1e-6, "overshot to {peak}");
+    assert!((process_values[599] - 30.0).abs() < 1e-6);
+    assert!(outputs.iter().all(|&u| (0.0..=100.0).contains(&u)));
+
+    // A plant half again as sensitive as the model still settles on the
+    // setpoint, because the model's miss is fed back as disturbance
+    controller.reset();
+    let plant = FopdtModel::new(3.0, 60.0, 30.0).unwrap();
+    let (process_values, _) = run_fopdt_loop(&mut controller, &plant, 1.0, 1500);
+    assert!((process_values[1499] - 30.0).abs() < 1e-3);
+
+    // Same through the trait, after a setpoint change
+    let controller: &mut dyn Controller = &mut controller;
+    controller.reset();
+    controller.set_setpoint(25.0).unwrap();
+    assert_eq!(controller.setpoint(), 25.0);
+    assert!(controller.set_setpoint(f64::NAN).is_err());
+    let (process_values, _) = run_fopdt_loop(controller, &model, 1.0, 600);
+    assert!((process_values[599] - 25.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_mpc_config_validation() {
+    let model = FopdtModel::new(2.0, 60.0, 30.0).unwrap();
+    let builder = || MpcConfig::builder(model).with_output_limits(0.0, 100.0);
+
+    let config = builder().build().unwrap();
+    assert_eq!(config.prediction_horizon(), 210.0);
+    assert_eq!(config.control_horizon(), 3);
+    assert!(MpcConfig::builder(model).build().is_err());
+    assert!(builder().with_prediction_horizon(30.0).build().is_err());
+    assert!(builder()
+        .with_prediction_horizon(f64::INFINITY)
+        .build()
+        .is_err());
+    assert!(builder().with_control_horizon(0).build().is_err());
+    assert!(builder().with_move_suppression(-1.0).build().is_err());
+    assert!(builder().with_setpoint(f64::NAN).build().is_err());
+    assert!(builder().with_initial_output(101.0).build().is_err());
+
+    let mut controller = MpcController::new(builder().with_initial_output(40.0).build().unwrap());
+    assert!(controller.compute(f64::NAN, 1.0).is_err());
+    assert!(controller.compute(20.0, 0.0).is_err());
+    assert_eq!(controller.output(), 40.0);
+}
+
+/// Step response of a second-order model resting at 25, sampled every `dt`
+fn second_order_step_test(model: SecondOrderModel, dt: f64, steps: usize) -> (Vec<f64>, Vec<f64>) {
+    let (phi, gamma) = StateSpaceModel::from(model).discretize(dt);
+    let outputs: Vec<f64> = (0..steps)
+        .map(|k| if k < 20 { 0.0 } else { 10.0 })
+        .collect();
+    let mut state = [0.0, 0.0];
+    let process_values = outputs
+        .iter()
+        .map(|u| {
+            let process_value = 25.0 + state[0];
+            state = [
+                phi[0][0] * state[0] + phi[0][1] * state[1] + gamma[0] * u,
+                phi[1][0] * state[0] + phi[1][1] * state[1] + gamma[1] * u,
+            ];
+            process_value
+        })
+        .collect();
+    (outputs, process_values)
+}
+
+#[test]
+fn test_identify_second_order_from_step_test() {
+    for truth in [
+        SecondOrderModel::new(2.0, 1.5, 0.3).unwrap(),
+        SecondOrderModel::new(0.5, 0.4, 1.8).unwrap(),
+    ] {
+        let (outputs, process_values) = second_order_step_test(truth, 0.05, 800);
+        let identified = SecondOrderModel::identify(&outputs, &process_values, 0.05).unwrap();
+        assert!(identified.fit > 0.999, "fit {}", identified.fit);
+        let model = identified.model;
+        assert!((model.gain / truth.gain - 1.0).abs() < 1e-4);
+        assert!((model.natural_frequency / truth.natural_frequency - 1.0).abs() < 1e-4);
+        assert!((model.damping_ratio / truth.damping_ratio - 1.0).abs() < 1e-4);
+    }
+
+    let outputs = vec![5.0; 100];
+    assert!(SecondOrderModel::identify(&outputs, &vec![20.0; 100], 0.1).is_err());
+    assert!(SecondOrderModel::new(1.0, 0.0, 0.5).is_err());
+    assert!(SecondOrderModel::new(1.0, 1.0, -0.1).is_err());
+}
+
+#[test]
+fn test_lqr_matches_scalar_riccati_solution() {
+    // ẋ = −x + u sampled at dt: x[k+1] = a·x[k] + b·u[k]
+    let model = StateSpaceModel::new(vec![vec![-1.0]], vec![1.0], vec![1.0]).unwrap();
+    let (q, r, dt) = (4.0, 0.5, 0.1);
+    let a = f64::exp(-dt);
+    let b = 1.0 - a;
+
+    // p = q + a²p − (abp)²/(r + b²p), rearranged to a quadratic in p
+    let (qa, qb, qc) = (b * b, r * (1.0 - a * a) - q * b * b, -q * r);
+    let p = (-qb + (qb * qb - 4.0 * qa * qc).sqrt()) / (2.0 * qa);
+    let expected = a * b * p / (r + b * b * p);
+
+    let gains = model.lqr(&[q], r, dt).unwrap();
+    assert!(
+        (gains[0] - expected).abs() < 1e-9,
+        "{} vs {}",
+        gains[0],
+        expected
+    );
+
+    assert!(model.lqr(&[q, q], r, dt).is_err());
+    assert!(model.lqr(&[q], 0.0, dt).is_err());
+    assert!(model.lqr(&[q], r, 0.0).is_err());
+    assert!(StateSpaceModel::new(vec![vec![0.0, 1.0]], vec![0.0], vec![1.0]).is_err());
+}
+
+#[test]
+fn test_state_feedback_settles_second_order_plant() {
+    let plant = SecondOrderModel::new(2.0, 1.5, 0.1).unwrap();
+    let config = StateFeedbackConfig::builder(plant)
+        .with_state_weights(vec![20.0, 0.0])
+        .with_input_weight(0.1)
+        .with_setpoint(3.0)
+        .with_output_limits(-20.0, 20.0)
+        .build()
+        .unwrap();
+    let mut controller = StateFeedbackController::new(config);
+    assert!(controller.gains().is_none());
+
+    let dt = 0.01;
+    let (phi, gamma) = StateSpaceModel::from(plant).discretize(dt);
+    let mut state = [0.0, 0.0];
+    let mut peak: f64 = 0.0;
+    for _ in 0..1000 {
+        let u = controller.compute(&state, dt).unwrap();
+        state = [
+            phi[0][0] * state[0] + phi[0][1] * state[1] + gamma[0] * u,
+            phi[1][0] * state[0] + phi[1][1] * state[1] + gamma[1] * u,
+        ];
+        peak = peak.max(state[0]);
+    }
+    // The lightly damped plant would overshoot by 70% on its own
+    assert!((state[0] - 3.0).abs() < 1e-6, "settled at {}", state[0]);
+    assert!(peak < 3.0 * 1.2, "peaked at {peak}");
+    assert_eq!(controller.gains().unwrap().len(), 2);
+
+    assert!(controller.compute(&[0.0], dt).is_err());
+    assert!(controller.compute(&[0.0, f64::NAN], dt).is_err());
+    controller.reset();
+    assert!(controller.gains().is_none());
+
+    // No path from input to output, so nothing holds a setpoint
+    let unreachable = StateSpaceModel::new(vec![vec![-1.0]], vec![0.0], vec![1.0]).unwrap();
+    assert!(StateFeedbackConfig::builder(unreachable)
+        .with_output_limits(-1.0, 1.0)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn test_observer_recovers_velocity_from_position() {
+    let plant = SecondOrderModel::new(1.0, 2.0, 0.3).unwrap();
+    let dt = 0.01;
+    let (phi, gamma) = StateSpaceModel::from(plant).discretize(dt);
+    let mut observer = LuenbergerObserver::new(plant, 20.0).unwrap();
+
+    // The plant starts moving; the observer assumes it is at rest
+    let mut state = [1.0, -2.0];
+    let mut input = 0.0;
+    for k in 0..300 {
+        let estimate = observer.update(state[0], input, dt).unwrap().to_vec();
+        if k >= 200 {
+            assert!((estimate[0] - state[0]).abs() < 1e-6, "step {k}");
+            assert!((estimate[1] - state[1]).abs() < 1e-6, "step {k}");
+            assert!((observer.rate() - state[1]).abs() < 1e-6, "step {k}");
+        }
+        input = 5.0 * (0.5 * k as f64 * dt).sin();
+        state = [
+            phi[0][0] * state[0] + phi[0][1] * state[1] + gamma[0] * input,
+            phi[1][0] * state[0] + phi[1][1] * state[1] + gamma[1] * input,
+        ];
+    }
+    assert!((observer.process_value() - observer.estimate()[0]).abs() < 1e-12);
+
+    assert!(LuenbergerObserver::new(plant, 0.0).is_err());
+    assert!(observer.update(f64::NAN, 0.0, dt).is_err());
+    // Measuring only velocity of a double integrator leaves position unknown
+    let blind = StateSpaceModel::new(
+        vec![vec![0.0, 1.0], vec![0.0, 0.0]],
+        vec![0.0, 1.0],
+        vec![0.0, 1.0],
+    )
+    .unwrap();
+    assert!(LuenbergerObserver::new(blind, 10.0).is_err());
+}
+
+#[test]
+fn test_observer_drives_pid_derivative_and_state_feedback() {
+    let plant = SecondOrderModel::new(2.0, 1.5, 0.1).unwrap();
+    let dt = 0.01;
+    let (phi, gamma) = StateSpaceModel::from(plant).discretize(dt);
+    let run = |controller: &mut dyn FnMut(f64) -> f64| {
+        let mut state = [0.0, 0.0];
+        for _ in 0..1000 {
+            let u = controller(state[0]);
+            state = [
+                phi[0][0] * state[0] + phi[0][1] * state[1] + gamma[0] * u,
+                phi[1][0] * state[0] + phi[1][1] * state[1] + gamma[1] * u,
+            ];
+        }
+        state[0]
+    };
+
+    // PID taking its D term from the observer's rate, recorded for replay
+    let config = ControllerConfig::builder()
+        .with_kp(4.0)
+        .with_ki(2.0)
+        .with_kd(1.0)
+        .with_setpoint(3.0)
+        .with_output_limits(-20.0, 20.0)
+        .build()
+        .unwrap();
+    let mut pid = PidController::new(config);
+    pid.start_recording();
+    let mut observer = LuenbergerObserver::new(plant, 30.0).unwrap();
+    let mut output = 0.0;
+    let settled = run(&mut |position| {
+        observer.update(position, output, dt).unwrap();
+        output = pid
+            .compute_with_rate(position, observer.rate(), dt)
+            .unwrap();
+        output
+    });
+    assert!((settled - 3.0).abs() < 1e-3, "PID settled at {settled}");
+    let recording = pid.take_recording().unwrap();
+    assert!(recording.steps().iter().all(|step| step.rate.is_some()));
+    assert_eq!(recording.verify(), Ok(()));
+
+    // State feedback from the position alone, through the Controller trait
+    let config = StateFeedbackConfig::builder(plant)
+        .with_state_weights(vec![20.0, 0.0])
+        .with_input_weight(0.1)
+        .with_setpoint(3.0)
+        .with_output_limits(-20.0, 20.0)
+        .build()
+        .unwrap();
+    let mut blind = StateFeedbackController::new(config.clone());
+    assert!(Controller::compute(&mut blind, 0.0, dt).is_err());
+    let mut controller = StateFeedbackController::new(config)
+        .with_observer(LuenbergerObserver::new(plant, 30.0).unwrap());
+    let controller: &mut dyn Controller = &mut controller;
+    let settled = run(&mut |position| controller.compute(position, dt).unwrap());
+    assert!(
+        (settled - 3.0).abs() < 1e-6,
+        "state feedback settled at {settled}"
+    );
+}
+
+/// Configs covering every option, saturating some of the time
+fn bank_configs() -> Vec<ControllerConfig> {
+    let modes = [
+        AntiWindupMode::None,
+        AntiWindupMode::Conditional,
+        AntiWindupMode::BackCalculation { tracking_time: 0.3 },
+    ];
+    (0..11usize)
+        .map(|i| {
+            let mut builder = ControllerConfig::builder()
+                .with_kp(0.5 + i as f64 * 0.7)
+                .with_ki(if i % 4 == 3 { 0.0 } else { 0.2 * i as f64 })
+                .with_kd(if i.is_multiple_of(2) {
+                    0.05 * i as f64
+                } else {
+                    -0.1
+                })
+                .with_setpoint(10.0 * i as f64 - 30.0)
+                .with_output_limits(-20.0 - i as f64, 15.0 + 2.0 * i as f64)
+                .with_anti_windup_mode(modes[i % 3])
+                .with_derivative_mode(if i % 5 < 2 {
+                    DerivativeMode::OnError
+                } else {
+                    DerivativeMode::OnMeasurement
+                })
+                .with_deadband(if i % 3 == 1 { 0.5 } else { 0.0 })
+                .with_derivative_filter_coeff(1.0 + 3.0 * i as f64);
+            if i == 6 {
+                builder = builder.with_initial_output(4.0);
+            }
+            if i % 4 == 1 {
+                builder = builder.with_setpoint_filter(0.05 * i as f64);
+            }
+            if i % 5 == 3 {
+                builder = builder.with_proportional_shape(ProportionalShape::ErrorSquared {
+                    full_gain_error: 4.0 + i as f64,
+                });
+            } else if i % 5 == 4 {
+                builder = builder.with_proportional_shape(ProportionalShape::Gap {
+                    half_width: 2.0 * i as f64,
+                    gain_ratio: 0.1 * i as f64,
+                });
+            }
+            if i % 3 == 2 {
+                builder = builder.with_negative_gains(PidGains {
+                    kp: 3.0 + i as f64 * 0.2,
+                    ki: 0.05 * i as f64,
+                    kd: 0.02,
+                });
+            }
+            if i >= 5 {
+                builder =
+                    builder.with_derivative_limits(-1.0 - 0.2 * i as f64, 0.5 + 0.1 * i as f64);
+            }
+            if i % 4 == 2 {
+                builder = builder
+                    .with_process_scaling(Scaling::new(-50.0, 30.0 + 7.0 * i as f64).unwrap())
+                    .with_output_scaling(Scaling::new(-40.0, 35.0).unwrap());
+            }
+            builder.build().unwrap()
+        })
+        .collect()
+}
+
+fn assert_same_bits(label: &str, bank: f64, scalar: f64) {
+    assert_eq!(
+        bank.to_bits(),
+        scalar.to_bits(),
+        "{}: bank {} vs pid_compute {}",
+        label,
+        bank,
+        scalar
+    );
+}
+
+#[test]
+fn test_bank_matches_pid_compute_bit_for_bit() {
+    let mut configs = bank_configs();
+    let mut states: Vec<PidState> = configs.iter().map(PidState::new).collect();
+    let mut bank = ControllerBank::new();
+    for (index, config) in configs.iter().enumerate() {
+        assert_eq!(bank.push(config.clone()), index);
+    }
+    assert_eq!(bank.len(), 11);
+
+    let mut outputs = vec![0.0; bank.len()];
+    for step in 0..300 {
+        let t = step as f64 * 0.02;
+        let process_values: Vec<f64> = (0..bank.len())
+            .map(|i| 40.0 * (t + i as f64).sin() + 10.0 * i as f64 - 35.0)
+            .collect();
+        let dt = 0.01 + 0.005 * (step % 3) as f64;
+
+        match step {
+            100 => {
+                bank.set_setpoint(2, 55.5).unwrap();
+                configs[2].setpoint = 55.5;
+            }
+            150 => {
+                configs[7] = configs[0].clone();
+                bank.set_config(7, configs[0].clone()).unwrap();
+            }
+            200 => {
+                bank.reset(9).unwrap();
+                states[9] = PidState::new(&configs[9]);
+            }
+            250 => {
+                bank.reset_all();
+                states = configs.iter().map(PidState::new).collect();
+            }
+            _ => {}
+        }
+
+        bank.compute(&process_values, dt, &mut outputs).unwrap();
+        for i in 0..bank.len() {
+            let (output, next) =
+                pid_compute(&configs[i], &states[i], process_values[i], dt).unwrap();
+            let label = format!("step {} loop {}", step, i);
+            assert_same_bits(&label, outputs[i], output);
+            let banked = bank.state(i).unwrap();
+            assert_same_bits(
+                &label,
+                banked.integral_contribution,
+                next.integral_contribution,
+            );
+            assert_same_bits(&label, banked.prev_error, next.prev_error);
+            assert_same_bits(&label, banked.prev_measurement, next.prev_measurement);
+            assert_same_bits(
+                &label,
+                banked.prev_filtered_derivative,
+                next.prev_filtered_derivative,
+            );
+            assert_same_bits(&label, banked.last_output, next.last_output);
+            assert_same_bits(&label, banked.filtered_setpoint, next.filtered_setpoint);
+            assert_eq!(banked.first_run, next.first_run);
+            states[i] = next;
+        }
+    }
+    assert_eq!(bank.config(2).unwrap().setpoint(), 55.5);
+    assert!(bank.state(11).is_none());
+}
+
+#[test]
+fn test_bank_rejects_bad_input_without_stepping() {
+    let mut bank = ControllerBank::new();
+    for config in bank_configs().into_iter().take(5) {
+        bank.push(config);
+    }
+    let mut outputs = [0.0; 5];
+    let before: Vec<PidState> = (0..5).map(|i| bank.state(i).unwrap()).collect();
+
+    assert!(bank.compute(&[1.0; 4], 0.1, &mut outputs).is_err());
+    assert!(bank.compute(&[1.0; 5], 0.1, &mut [0.0; 6]).is_err());
+    assert!(bank.compute(&[1.0; 5], 0.0, &mut outputs).is_err());
+    assert!(bank
+        .compute(&[1.0, 2.0, f64::NAN, 4.0, 5.0], 0.1, &mut outputs)
+        .is_err());
+    for (i, state) in before.iter().enumerate() {
+        assert_eq!(&bank.state(i).unwrap(), state);
+    }
+
+    assert!(bank.set_setpoint(5, 1.0).is_err());
+    assert!(bank.set_setpoint(0, f64::INFINITY).is_err());
+    assert!(bank.reset(5).is_err());
+
+    let mut empty = ControllerBank::new();
+    assert!(empty.is_empty());
+    empty.compute(&[], 0.1, &mut []).unwrap();
+}
+
+#[test]
+fn test_thermal_room_settles_where_the_conductances_say() {
+    let mut room = ThermalRoomPlant::new(5.0, 5.0);
+    let output = room.holding_output(21.0);
+    assert!(output > 0.0 && output < 100.0);
+
+    // The walls take most of a day per time constant, so run for ten
+    for _ in 0..10 * 24 * 60 {
+        room.step(output, 60.0);
+    }
+    assert!((room.temperature() - 21.0).abs() < 0.01);
+    assert!(room.wall_temperature() < room.temperature());
+    assert!((room.radiator_power() - output * 30.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_sim_rng_repeats_per_seed() {
+    let mut a = SimRng::new(1);
+    let mut b = SimRng::new(1);
+    let mut c = SimRng::new(2);
+    let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+    assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+    assert_ne!(first, (0..8).map(|_| c.next_u64()).collect::<Vec<_>>());
+
+    let mut rng = SimRng::new(3);
+    let n = 20_000;
+    let samples: Vec<f64> = (0..n).map(|_| rng.noise(2.0)).collect();
+    let mean = samples.iter().sum::<f64>() / n as f64;
+    let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+    assert!(mean.abs() < 0.05);
+    assert!((variance.sqrt() - 2.0).abs() < 0.05);
+    assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.uniform())));
+}
+
+#[test]
+fn test_gusts_arrive_at_the_mean_rate() {
+    assert!(GustGenerator::new(0.0, 1.0).is_err());
+    assert!(GustGenerator::new(1.0, f64::NAN).is_err());
+
+    let mut gusts = GustGenerator::new(2.0, 1.0).unwrap();
+    let mut rng = SimRng::new(11);
+    // 2000 s at 10 Hz should see about 1000 gusts
+    let kicks: Vec<f64> = (0..20_000)
+        .filter_map(|_| gusts.step(&mut rng, 0.1))
+        .collect();
+    assert!((900..1100).contains(&kicks.len()));
+    assert!(kicks.iter().any(|&kick| kick > 0.0));
+    assert!(kicks.iter().any(|&kick| kick < 0.0));
+
+    // Same seed, same weather
+    let mut replay = SimRng::new(11);
+    let again: Vec<f64> = (0..20_000)
+        .filter_map(|_| gusts.step(&mut replay, 0.1))
+        .collect();
+    assert_eq!(kicks, again);
+}
+
+#[test]
+fn test_thermal_room_lags_and_leaks() {
+    let mut room = ThermalRoomPlant::new(20.0, 20.0);
+    // The radiator has to warm up before the air notices
+    room.step(100.0, 1.0);
+    assert!(room.temperature() - 20.0 < 1e-3);
+    assert!(room.radiator_power() < 0.01 * ThermalRoomPlant::DEFAULT_HEATER_POWER);
+
+    // Same number of seconds in one step or many
+    let mut coarse = ThermalRoomPlant::new(20.0, 0.0);
+    let mut fine = coarse.clone();
+    coarse.step(50.0, 600.0);
+    for _ in 0..600 {
+        fine.step(50.0, 1.0);
+    }
+    assert!((coarse.temperature() - fine.temperature()).abs() < 0.05);
+
+    // An open window loses heat faster
+    let mut shut = ThermalRoomPlant::new(20.0, 0.0);
+    let mut open = shut.clone();
+    open.set_window_open(true);
+    assert!(open.holding_output(20.0) > shut.holding_output(20.0));
+    shut.step(0.0, 600.0);
+    open.step(0.0, 600.0);
+    assert!(open.temperature() < shut.temperature() - 1.0);
+
+    // Bad input leaves the room alone
+    let before = open.clone();
+    open.step(f64::NAN, 1.0);
+    open.step(50.0, 0.0);
+    open.step(50.0, f64::INFINITY);
+    assert_eq!(open, before);
+}
+
+#[test]
+fn test_outdoor_feedforward_rejects_cold_snap() {
+    // Heater output per degree of outdoor temperature, from the plant's own
+    // steady state
+    let reference = ThermalRoomPlant::new(21.0, 5.0);
+    let warmer = ThermalRoomPlant::new(21.0, 6.0);
+    let gain = warmer.holding_output(21.0) - reference.holding_output(21.0);
+    // Infiltration passes a cold snap straight through; the share through
+    // the walls arrives with their time constant
+    let walls = ThermalRoomPlant::WALL_CAPACITY
+        / (ThermalRoomPlant::AIR_TO_WALLS + ThermalRoomPlant::WALLS_TO_OUTDOOR);
+    let walls_path =
+        1.0 / (1.0 / ThermalRoomPlant::AIR_TO_WALLS + 1.0 / ThermalRoomPlant::WALLS_TO_OUTDOOR);
+    let immediate = ThermalRoomPlant::INFILTRATION / (ThermalRoomPlant::INFILTRATION + walls_path);
+
+    let run = |feedforward: Option<Feedforward>| {
+        let config = ControllerConfig::builder()
+            .with_kp(20.0)
+            .with_ki(0.02)
+            .with_setpoint(21.0)
+            .with_output_limits(0.0, 100.0)
+            .with_initial_output(reference.holding_output(21.0))
+            .build()
+            .unwrap();
+        let mut controller = PidController::new(config);
+        if let Some(feedforward) = feedforward {
+            controller = controller.with_feedforward(feedforward);
+        }
+        controller.start_recording();
+        let mut room = ThermalRoomPlant::new(21.0, 5.0);
+        let mut worst: f64 = 0.0;
+        for k in 0..8 * 3600 {
+            // Once the radiator has warmed up, the weather turns
+            if k == 2 * 3600 {
+                room.set_outdoor_temperature(-5.0);
+            }
+            // Outdoor sensor read once a minute, as a deviation from 5 °C
+            if k % 60 == 0 {
+                controller
+                    .set_disturbance(room.outdoor_temperature() - 5.0)
+                    .unwrap();
+            }
+            let output = controller.compute(room.temperature(), 1.0).unwrap();
+            room.step(output, 1.0);
+            if k >= 2 * 3600 {
+                worst = worst.max((room.temperature() - 21.0).abs());
+            }
+        }
+        assert_eq!(controller.take_recording().unwrap().verify(), Ok(()));
+        worst
+    };
+
+    let feedback_only = run(None);
+    let static_only = run(Some(Feedforward::new(gain, 0.0, 0.0).unwrap()));
+    let with_feedforward = run(Some(
+        Feedforward::new(gain, immediate * walls, walls).unwrap(),
+    ));
+    assert!(
+        with_feedforward < 0.6 * feedback_only,
+        "{with_feedforward} vs {feedback_only}"
+    );
+    // Static compensation heats for the walls' share before it arrives
+    assert!(with_feedforward < static_only);
+
+    let mut controller = PidController::new(
+        ControllerConfig::builder()
+            .with_output_limits(0.0, 100.0)
+            .build()
+            .unwrap(),
+    );
+    assert!(controller.set_disturbance(f64::NAN).is_err());
+    // A compensator without a measurement contributes nothing
+    controller = controller.with_feedforward(Feedforward::new(1.0, 0.0, 0.0).unwrap());
+    assert_eq!(controller.compute(0.0, 1.0).unwrap(), 0.0);
+    assert!(controller.feedforward().is_some());
+}
+
+#[test]
+fn test_heat_load_feedforward_rejects_a_full_meeting_room() {
+    // A winter day, with the walls long settled
+    let mut settled = ThermalRoomPlant::new(21.0, -5.0);
+    let holding = settled.holding_output(21.0);
+    for _ in 0..10 * 24 * 60 {
+        settled.step(holding, 60.0);
+    }
+    let run = |feedforward: bool| {
+        let config = ControllerConfig::builder()
+            .with_kp(20.0)
+            .with_ki(0.02)
+            .with_setpoint(21.0)
+            .with_output_limits(0.0, 100.0)
+            .with_initial_output(holding)
+            .build()
+            .unwrap();
+        let mut room = settled.clone();
+        let mut controller = PidController::new(config);
+        if feedforward {
+            controller = controller.with_feedforward(room.heat_load_feedforward().unwrap());
+        }
+        let mut worst: f64 = 0.0;
+        let mut output = holding;
+        for k in 0..3 * 3600 {
+            // Five people and their laptops arrive over ten minutes
+            room.set_heat_load(600.0 * (k as f64 / 600.0).min(1.0));
+            controller.set_disturbance(room.heat_load()).unwrap();
+            output = controller.compute(room.temperature(), 1.0).unwrap();
+            room.step(output, 1.0);
+            worst = worst.max((room.temperature() - 21.0).abs());
+        }
+        (worst, output)
+    };
+
+    let (feedback_only, _) = run(false);
+    let (with_feedforward, output) = run(true);
+    assert!(
+        with_feedforward < 0.1 * feedback_only,
+        "{with_feedforward} vs {feedback_only}"
+    );
+    // The load now supplies 20% of the heater's power
+    let mut room = ThermalRoomPlant::new(21.0, -5.0);
+    room.set_heat_load(600.0);
+    assert!((room.holding_output(21.0) - (holding - 20.0)).abs() < 1e-9);
+    assert!((output - room.holding_output(21.0)).abs() < 1.0);
+
+    room.set_heat_load(-5.0);
+    assert_eq!(room.heat_load(), 0.0);
+    assert!(ThermalRoomPlant::new(21.0, 5.0)
+        .with_heater_power(0.0)
+        .heat_load_feedforward()
+        .is_err());
+}
+
+#[test]
+fn test_drone_wind_feedforward_holds_altitude_in_an_updraft() {
+    let run = |feedforward: bool| {
+        let config = ControllerConfig::builder()
+            .with_kp(10.0)
+            .with_ki(5.0)
+            .with_kd(8.0)
+            .with_setpoint(10.0)
+            .with_output_limits(0.0, 100.0)
+            .build()
+            .unwrap();
+        let mut drone = DroneAltitudePlant::new(0.0);
+        let mut controller = PidController::new(config);
+        if feedforward {
+            controller = controller.with_feedforward(drone.wind_feedforward());
+        }
+        let mut worst: f64 = 0.0;
+        for k in 0..60 * 20 {
+            // Settled at 10 m when it flies into a thermal
+            if k == 30 * 20 {
+                drone.set_wind(3.0);
+            }
+            controller.set_disturbance(drone.wind_load()).unwrap();
+            let output = controller.compute(drone.altitude(), 0.05).unwrap();
+            drone.step(output, 0.05);
+            if k >= 30 * 20 {
+                worst = worst.max((drone.altitude() - 10.0).abs());
+            }
+        }
+        (worst, drone)
+    };
+
+    let (feedback_only, _) = run(false);
+    let (with_feedforward, drone) = run(true);
+    assert!(
+        with_feedforward < 0.1 * feedback_only,
+        "{with_feedforward} vs {feedback_only}"
+    );
+    assert!((drone.altitude() - 10.0).abs() < 0.05);
+    assert!((drone.thrust() - drone.hover_output()).abs() < 0.1);
+    assert!(drone.hover_output() < DroneAltitudePlant::new(0.0).hover_output());
+
+    // One long step or many short ones, and never below the ground
+    let mut coarse = DroneAltitudePlant::new(5.0);
+    let mut fine = coarse.clone();
+    coarse.step(50.0, 1.0);
+    for _ in 0..20 {
+        fine.step(50.0, 0.05);
+    }
+    assert_eq!(coarse, fine);
+    coarse.step(0.0, 10.0);
+    assert_eq!((coarse.altitude(), coarse.velocity()), (0.0, 0.0));
+    let before = coarse.clone();
+    coarse.step(f64::NAN, 0.05);
+    coarse.step(50.0, 0.0);
+    assert_eq!(coarse, before);
+}
+
+#[test]
+fn test_standby_controller_takes_over_bumplessly() {
+    let config = ControllerConfig::builder()
+        .with_kp(20.0)
+        .with_ki(0.02)
+        .with_setpoint(21.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut room = ThermalRoomPlant::new(18.0, 5.0);
+    let mut standby = PidController::new(config.clone());
+    standby.start_recording();
+    let shared = ThreadSafePidController::new(config);
+
+    // An operator runs the heater by hand for an hour
+    let manual = 60.0;
+    for _ in 0..3600 {
+        let followed = standby.track(room.temperature(), manual, 1.0).unwrap();
+        assert_eq!(followed, manual);
+        shared.track(room.temperature(), manual, 1.0).unwrap();
+        room.step(manual, 1.0);
+    }
+
+    // Switched to automatic, the first output is where the operator left it
+    let first = standby.compute(room.temperature(), 1.0).unwrap();
+    assert!((first - manual).abs() < 0.1, "jumped to {first}");
+    assert_eq!(shared.compute(room.temperature(), 1.0).unwrap(), first);
+    room.step(first, 1.0);
+    for _ in 0..4 * 3600 {
+        let output = standby.compute(room.temperature(), 1.0).unwrap();
+        room.step(output, 1.0);
+    }
+    assert!((room.temperature() - 21.0).abs() < 0.2);
+
+    let recording = standby.take_recording().unwrap();
+    assert_eq!(recording.steps()[0].tracked_output, Some(manual));
+    assert_eq!(recording.verify(), Ok(()));
+}
+
+#[test]
+fn test_setpoint_schedule_follows_the_week() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let hours = |h: u64| Duration::from_secs(h * 3600);
+    // Monday 5 January 2026, 00:00 UTC
+    let monday = UNIX_EPOCH + Duration::from_secs(1_767_571_200);
+
+    let mut schedule = SetpointSchedule::new();
+    assert_eq!(schedule.active(monday), None);
+    schedule.add(Weekdays::WORKDAYS, 7, 0, 21.0).unwrap();
+    schedule.add(Weekdays::WORKDAYS, 22, 0, 17.0).unwrap();
+    schedule
+        .add(Weekdays::SATURDAY | Weekdays::SUNDAY, 9, 0, 20.0)
+        .unwrap();
+    // Same time as the weekday entry, added later: Friday evening runs warm
+    schedule.add(Weekdays::FRIDAY, 22, 0, 19.0).unwrap();
+    assert!(Weekdays::WEEKEND.contains(Weekdays::SUNDAY));
+    assert!(!Weekdays::WORKDAYS.contains(Weekdays::WEEKEND));
+
+    // Before Monday's first change, the weekend's last one still holds
+    let active = schedule.active(monday + hours(6)).unwrap();
+    assert_eq!(active.setpoint, 20.0);
+    assert!(!active.held);
+    assert_eq!(active.until, Some(monday + hours(7)));
+    assert_eq!(schedule.setpoint_at(monday + hours(7)), Some(21.0));
+    assert_eq!(schedule.setpoint_at(monday + hours(23)), Some(17.0));
+    assert_eq!(
+        schedule.setpoint_at(monday + hours(4 * 24 + 23)),
+        Some(19.0)
+    );
+    // Sunday evening: the next change wraps to next Monday
+    let sunday = schedule.active(monday + hours(6 * 24 + 20)).unwrap();
+    assert_eq!(sunday.setpoint, 20.0);
+    assert_eq!(sunday.until, Some(monday + hours(7 * 24 + 7)));
+
+    // An hour ahead of UTC, 07:00 local is 06:00 UTC
+    schedule.set_utc_offset(3600).unwrap();
+    assert_eq!(schedule.setpoint_at(monday + hours(6)), Some(21.0));
+    assert!(schedule.set_utc_offset(19 * 3600).is_err());
+    schedule.set_utc_offset(0).unwrap();
+
+    // Holds: until the next change, until a time, and indefinitely
+    schedule
+        .hold_until_next_change(23.0, monday + hours(8))
+        .unwrap();
+    let held = schedule.active(monday + hours(10)).unwrap();
+    assert!(held.held);
+    assert_eq!(held.setpoint, 23.0);
+    assert_eq!(held.until, Some(monday + hours(22)));
+    assert_eq!(schedule.setpoint_at(monday + hours(22)), Some(17.0));
+    schedule.hold(15.0, Some(monday + hours(12))).unwrap();
+    assert_eq!(schedule.setpoint_at(monday + hours(11)), Some(15.0));
+    assert_eq!(schedule.setpoint_at(monday + hours(12)), Some(21.0));
+    schedule.hold(18.0, None).unwrap();
+    assert_eq!(schedule.active(monday + hours(500)).unwrap().until, None);
+    schedule.resume();
+    assert_eq!(schedule.setpoint_at(monday + hours(8)), Some(21.0));
+
+    // Driving a controller: only changes are pushed, and an expired hold
+    // is dropped
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+    assert_eq!(
+        schedule.apply(&mut controller, monday + hours(8)).unwrap(),
+        Some(21.0)
+    );
+    assert_eq!(controller.setpoint(), 21.0);
+    assert_eq!(
+        schedule.apply(&mut controller, monday + hours(9)).unwrap(),
+        None
+    );
+    schedule.hold(19.5, Some(monday + hours(10))).unwrap();
+    let dynamic: &mut dyn Controller = &mut controller;
+    assert_eq!(
+        schedule.apply(dynamic, monday + hours(9)).unwrap(),
+        Some(19.5)
+    );
+    assert_eq!(
+        schedule.apply(&mut controller, monday + hours(10)).unwrap(),
+        Some(21.0)
+    );
+    assert!(!schedule.active(monday + hours(9)).unwrap().held);
+
+    assert!(schedule.add(Weekdays::EVERY_DAY, 24, 0, 20.0).is_err());
+    assert!(schedule.add(Weekdays::MONDAY, 7, 60, 20.0).is_err());
+    assert!(schedule.add(Weekdays::MONDAY, 7, 0, f64::NAN).is_err());
+    assert!(schedule.hold(f64::INFINITY, None).is_err());
+    let removed = schedule.remove(3).unwrap();
+    assert_eq!(removed.start, 22 * 3600);
+    assert!(schedule.remove(3).is_none());
+    assert_eq!(schedule.entries().len(), 3);
+    schedule.clear();
+    assert_eq!(schedule.active(monday), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_setpoint_schedule_serde_roundtrip() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let monday = UNIX_EPOCH + Duration::from_secs(1_767_571_200);
+    let mut schedule = SetpointSchedule::new();
+    schedule.set_utc_offset(-5 * 3600).unwrap();
+    schedule.add(Weekdays::EVERY_DAY, 6, 0, 20.5).unwrap();
+    schedule.add(Weekdays::EVERY_DAY, 23, 30, 16.0).unwrap();
+    schedule.hold_until_next_change(22.0, monday).unwrap();
+
+    let json = serde_json::to_string(&schedule).unwrap();
+    let restored: SetpointSchedule = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, schedule);
+    assert_eq!(restored.active(monday), schedule.active(monday));
+}
+
+#[test]
+fn test_output_totalizer_accumulates_and_rolls_over() {
+    // No P or I action: the output holds at the preloaded 40
+    let config = ControllerConfig::builder()
+        .with_kp(0.0)
+        .with_ki(0.0)
+        .with_output_limits(-100.0, 100.0)
+        .with_initial_output(40.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+    for _ in 0..100 {
+        assert_eq!(controller.compute(0.0, 0.5).unwrap(), 40.0);
+    }
+    let stats = controller.get_statistics();
+    assert_eq!(stats.output_total, 2000.0);
+    assert_eq!(stats.output_total_rollovers, 0);
+
+    // A rejected step adds nothing; a reset keeps the total
+    assert!(controller.compute(f64::NAN, 0.5).is_err());
+    controller.reset();
+    assert_eq!(controller.get_statistics().output_total, 2000.0);
+
+    // Wrapping at 750 keeps the remainder and counts each wrap
+    controller.set_totalizer_rollover(Some(750.0)).unwrap();
+    controller.compute(0.0, 0.5).unwrap();
+    let stats = controller.get_statistics();
+    assert_eq!(stats.output_total_rollovers, 2);
+    assert_eq!(stats.output_total, 520.0);
+    assert_eq!(
+        stats.output_total_rollovers as f64 * 750.0 + stats.output_total,
+        2020.0
+    );
+
+    controller.reset_totalizer();
+    assert_eq!(controller.get_statistics().output_total, 0.0);
+    assert_eq!(controller.get_statistics().output_total_rollovers, 0);
+    assert!(controller.set_totalizer_rollover(Some(0.0)).is_err());
+    assert!(controller.set_totalizer_rollover(Some(f64::NAN)).is_err());
+
+    // Usage counts whichever way the output drives, e.g. heating or cooling
+    let reverse = ThreadSafePidController::new(
+        ControllerConfig::builder()
+            .with_kp(0.0)
+            .with_ki(0.0)
+            .with_output_limits(-100.0, 100.0)
+            .with_initial_output(-25.0)
+            .build()
+            .unwrap(),
+    );
+    reverse.compute(0.0, 2.0).unwrap();
+    assert_eq!(reverse.get_statistics().unwrap().output_total, 50.0);
+    reverse.set_totalizer_rollover(None).unwrap();
+    reverse.reset_totalizer().unwrap();
+    assert_eq!(reverse.get_statistics().unwrap().output_total, 0.0);
+}
+
+#[test]
+fn test_compute_raw_keeps_counts_out_of_tuning() {
+    // 12-bit ADC over 0-150 °C in, 10-bit DAC over 0-100 % heater out
+    let adc = Scaling::from_raw(0.0, 4095.0, 0.0, 150.0).unwrap();
+    let dac = Scaling::from_raw(0.0, 1023.0, 0.0, 100.0).unwrap();
+    let config = ControllerConfig::builder()
+        .with_kp(4.0)
+        .with_ki(0.05)
+        .with_setpoint(90.0)
+        .with_process_scaling(adc)
+        .with_output_scaling(dac)
+        .build()
+        .unwrap();
+    let mut raw = PidController::new(config.clone());
+    let mut engineering = PidController::new(config.clone());
+
+    // First-order oven: 1.5 °C per % of heater at steady state, 60 s lag
+    let mut temperature = 20.0;
+    for _ in 0..4000 {
+        let counts = adc.to_raw(temperature).round();
+        let drive = raw.compute_raw(counts, 0.5).unwrap();
+        assert!((0.0..=1023.0).contains(&drive));
+        let expected = engineering
+            .compute(adc.to_engineering(counts), 0.5)
+            .unwrap();
+        assert!((dac.to_engineering(drive) - expected).abs() < 1e-9);
+        let heater = dac.to_engineering(drive.round());
+        temperature += (20.0 + 1.5 * heater - temperature) * 0.5 / 60.0;
+    }
+    assert!((temperature - 90.0).abs() < 0.1, "settled at {temperature}");
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("gain_scale"));
+        let restored: ControllerConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, config);
+        let bad =
+            r#"{"process_scaling": {"raw_low": 0.0, "raw_high": 1.0, "low": 5.0, "high": 5.0}}"#;
+        assert!(serde_json::from_str::<ControllerConfig>(bad).is_err());
+    }
+}
+
+#[test]
+fn test_composed_pipeline_matches_hand_wired_loop() {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.4)
+        .with_kd(0.1)
+        .with_setpoint(30.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let mut composed = PidController::new(config.clone())
+        .filter_input(LowPassFilter::new(0.3).unwrap())
+        .shape_output(
+            Limiter::new(0.0, 80.0)
+                .unwrap()
+                .then(RateLimiter::new(50.0).unwrap())
+                .then(Quantizer::new(0.5).unwrap()),
+        );
+
+    let mut controller = PidController::new(config);
+    let mut filter = LowPassFilter::new(0.3).unwrap();
+    let mut limiter = Limiter::new(0.0, 80.0).unwrap();
+    let mut ramp = RateLimiter::new(50.0).unwrap();
+    let mut quantizer = Quantizer::new(0.5).unwrap();
+
+    for k in 0..200 {
+        let measurement = 20.0 + (k as f64 * 0.3).sin() * 5.0 + k as f64 * 0.05;
+        let expected = {
+            let filtered = filter.process(measurement, 0.1);
+            let output = controller.compute(filtered, 0.1).unwrap();
+            let output = limiter.process(output, 0.1);
+            quantizer.process(ramp.process(output, 0.1), 0.1)
+        };
+        assert_eq!(composed.compute(measurement, 0.1).unwrap(), expected);
+    }
+
+    // A rejected sample leaves the filter's history alone
+    assert!(composed.compute(f64::NAN, 0.1).is_err());
+    assert!(composed.compute(25.0, 0.0).is_err());
+    let filtered = filter.process(25.0, 0.1);
+    let output = controller.compute(filtered, 0.1).unwrap();
+    let expected = quantizer.process(ramp.process(limiter.process(output, 0.1), 0.1), 0.1);
+    assert_eq!(composed.compute(25.0, 0.1).unwrap(), expected);
+
+    // Setpoint and reset go through to the controller and every stage
+    composed.set_setpoint(45.0).unwrap();
+    assert_eq!(composed.setpoint(), 45.0);
+    composed.reset();
+    let (inner, stages) = composed.into_parts();
+    assert_eq!(inner.controller().setpoint(), 45.0);
+    assert_eq!(inner.stage().clone().process(7.0, 0.1), 7.0);
+    let mut stages = stages;
+    assert_eq!(stages.process(42.3, 0.1), 42.5);
+}
+
+/// Rate loop for the furnace below: the heater moves the rate of rise by
+/// 0.01 °C/s per %.
+fn furnace_rate_config() -> ControllerConfig {
+    ControllerConfig::builder()
+        .with_kp(150.0)
+        .with_ki(30.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_ramp_controller_ramps_then_holds() {
+    // Furnace: 6 °C per % of heater over 20 °C ambient, 600 s time constant
+    let absolute = ControllerConfig::builder()
+        .with_kp(8.0)
+        .with_ki(0.05)
+        .with_setpoint(20.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut furnace = RampController::new(absolute, furnace_rate_config(), 2.0).unwrap();
+    let dt = 1.0;
+    let mut temperature = 20.0;
+    let step = |furnace: &mut RampController, temperature: &mut f64| {
+        let heater = furnace.compute(*temperature, dt).unwrap();
+        *temperature += (6.0 * heater - (*temperature - 20.0)) / 600.0 * dt;
+        heater
+    };
+
+    for _ in 0..60 {
+        step(&mut furnace, &mut temperature);
+    }
+    // Hand over 2 °C early so the absolute loop rounds off the approach
+    furnace.set_handoff_band(2.0).unwrap();
+    furnace.start_ramp(5.0 / 60.0, 400.0, temperature).unwrap();
+    assert_eq!(
+        furnace.mode(),
+        RampMode::Ramping {
+            rate: 5.0 / 60.0,
+            end_value: 400.0
+        }
+    );
+    assert_eq!(furnace.setpoint(), 400.0);
+
+    let mut previous = step(&mut furnace, &mut temperature);
+    let mut ramp = vec![temperature];
+    while furnace.mode() != RampMode::Absolute {
+        previous = step(&mut furnace, &mut temperature);
+        ramp.push(temperature);
+        assert!(ramp.len() < 10_000, "ramp never reached its end value");
+    }
+
+    // Once the rate loop has settled in, every ten minutes climb 50 °C
+    for window in ramp[300..].chunks_exact(600) {
+        let slope = (window[599] - window[0]) / 599.0 * 60.0;
+        assert!((slope - 5.0).abs() < 0.05, "{slope} °C/min");
+    }
+
+    // The absolute loop takes over from the tracked output, so the first
+    // step moves it by no more than P on one step's travel, and then holds
+    // the end value
+    let mut peak = temperature;
+    for k in 0..6000 {
+        let heater = step(&mut furnace, &mut temperature);
+        if k == 0 {
+            let bound = 8.0 * 5.0 / 60.0 * dt;
+            assert!((heater - previous).abs() <= bound, "{previous} -> {heater}");
+        }
+        previous = heater;
+        peak = peak.max(temperature);
+    }
+    assert!(peak - 400.0 < 0.1, "overshoot to {peak}");
+    assert!((temperature - 400.0).abs() < 0.01);
+    assert!(furnace.measured_rate().unwrap().abs() < 1e-4);
+
+    // Ramping down, and a setpoint change cancels a ramp
+    furnace.start_ramp(10.0 / 60.0, 300.0, temperature).unwrap();
+    assert!(matches!(furnace.mode(), RampMode::Ramping { rate, .. } if rate < 0.0));
+    furnace.set_setpoint(350.0).unwrap();
+    assert_eq!(furnace.mode(), RampMode::Absolute);
+    furnace.start_ramp(1.0, 351.5, 350.0).unwrap();
+    assert_eq!(furnace.mode(), RampMode::Absolute);
+    assert_eq!(furnace.setpoint(), 351.5);
+
+    assert!(furnace.compute(f64::NAN, dt).is_err());
+    assert!(furnace.start_ramp(0.0, 300.0, 350.0).is_err());
+    assert!(furnace.set_handoff_band(-1.0).is_err());
+    let mismatched = ControllerConfig::builder()
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    assert!(RampController::new(mismatched.clone(), furnace_rate_config(), 2.0).is_err());
+    assert!(RampController::new(furnace_rate_config(), furnace_rate_config(), 0.0).is_err());
+}
+
+#[test]
+fn test_step_test_identifies_and_hands_back() {
+    // Process: K = 1.5, τ = 40 s, θ = 6 s around a resting point of 50 at 20 %
+    let dt = 1.0;
+    let decay = (-dt / 40.0f64).exp();
+    let run = |test: &mut StepTest, controller: &mut PidController, pending: &mut Vec<f64>| {
+        let mut process_value = 50.0;
+        while test.status() == StepTestStatus::Running {
+            let output = test.step(controller, process_value, dt).unwrap();
+            pending.push(output);
+            let applied = pending[pending.len() - 7];
+            process_value =
+                50.0 + decay * (process_value - 50.0) + 1.5 * (1.0 - decay) * (applied - 20.0);
+        }
+        process_value
+    };
+    let config = ControllerConfig::builder()
+        .with_kp(0.5)
+        .with_ki(0.02)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .with_initial_output(20.0)
+        .build()
+        .unwrap();
+
+    // A step clipped by the test's bounds: 20 + 30 would pass 45
+    let mut controller = PidController::new(config.clone());
+    let mut test = StepTest::new(
+        StepTestConfig::builder(Excitation::Step { amplitude: 30.0 }, 300.0)
+            .with_baseline(30.0)
+            .with_output_limits(10.0, 45.0)
+            .build()
+            .unwrap(),
+    );
+    let mut pending = vec![20.0; 6];
+    let process_value = run(&mut test, &mut controller, &mut pending);
+    assert_eq!(test.status(), StepTestStatus::Complete);
+    assert_eq!(test.outputs().len(), 330);
+    assert!(test.outputs()[..30].iter().all(|&u| u == 20.0));
+    assert!(test.outputs()[30..].iter().all(|&u| u == 45.0));
+    assert_eq!(test.sample_time(), Some(dt));
+
+    let found = test.identify().unwrap();
+    assert!((found.model.gain - 1.5).abs() < 0.02, "{:?}", found.model);
+    assert!(
+        (found.model.time_constant - 40.0).abs() < 2.0,
+        "{:?}",
+        found.model
+    );
+    assert!(
+        (found.model.dead_time - 6.0).abs() <= 1.0,
+        "{:?}",
+        found.model
+    );
+    assert!(found.fit > 0.99);
+    assert!(TuningRule::SimcPi.gains(&found.model).is_ok());
+
+    // The controller tracked the test, so it picks up from the last output
+    let resumed = controller.compute(process_value, dt).unwrap();
+    assert!((resumed - 45.0).abs() < 1.0, "{resumed}");
+    assert!(test.step(&mut controller, process_value, dt).is_err());
+
+    // A doublet returns to the held output; leaving the process limits
+    // aborts and restores it
+    let mut controller = PidController::new(config);
+    let mut test = StepTest::new(
+        StepTestConfig::builder(
+            Excitation::Doublet {
+                amplitude: -15.0,
+                width: 60.0,
+            },
+            300.0,
+        )
+        .with_process_limits(35.0, 65.0)
+        .build()
+        .unwrap(),
+    );
+    let mut pending = vec![20.0; 6];
+    run(&mut test, &mut controller, &mut pending);
+    let StepTestStatus::Aborted { process_value } = test.status() else {
+        panic!("expected an abort, got {:?}", test.status());
+    };
+    assert!(process_value < 35.0);
+    assert_eq!(pending.last(), Some(&20.0));
+    assert!(test.outputs().iter().all(|&u| u == 20.0 || u == 5.0));
+
+    assert!(
+        StepTestConfig::builder(Excitation::Step { amplitude: 0.0 }, 10.0)
+            .build()
+            .is_err()
+    );
+    assert!(StepTestConfig::builder(
+        Excitation::Doublet {
+            amplitude: 1.0,
+            width: 0.0
+        },
+        10.0
+    )
+    .build()
+    .is_err());
+    assert!(
+        StepTestConfig::builder(Excitation::Step { amplitude: 1.0 }, 10.0)
+            .with_process_limits(5.0, 5.0)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_step_test_reports_progress_and_cancels() {
+    use std::sync::mpsc;
+
+    // Process: K = 2, τ = 30 s around a resting point of 40 at 30 %
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.1)
+        .with_setpoint(40.0)
+        .with_output_limits(0.0, 100.0)
+        .with_initial_output(30.0)
+        .build()
+        .unwrap();
+    let test_config = StepTestConfig::builder(Excitation::Step { amplitude: 10.0 }, 300.0)
+        .with_baseline(20.0)
+        .build()
+        .unwrap();
+    let run = |test: &mut StepTest, controller: &mut PidController| {
+        let mut process_value = 40.0;
+        while test.status() == StepTestStatus::Running {
+            let output = test.step(controller, process_value, 1.0).unwrap();
+            process_value += (2.0 * (output - 30.0) + 40.0 - process_value) / 30.0;
+        }
+    };
+
+    let (reports, received) = mpsc::channel();
+    let mut test = StepTest::new(test_config.clone()).with_progress(move |progress| {
+        reports.send(*progress).unwrap();
+    });
+    run(&mut test, &mut PidController::new(config.clone()));
+    let reports: Vec<StepTestProgress> = received.try_iter().collect();
+
+    // One report per whole percent from 0 to 100, the baseline first
+    assert_eq!(reports.len(), 101);
+    assert!(reports.windows(2).all(|w| w[0].percent < w[1].percent));
+    assert_eq!(reports[0].phase, StepTestPhase::Baseline);
+    let excitation = reports
+        .iter()
+        .position(|p| p.phase == StepTestPhase::Excitation)
+        .unwrap();
+    assert!(reports[..excitation]
+        .iter()
+        .all(|p| p.phase == StepTestPhase::Baseline && p.estimate.is_none()));
+    // Estimates appear while the test is still running and sharpen
+    let running = &reports[..reports.len() - 1];
+    let early = running.iter().find_map(|p| p.estimate).unwrap();
+    assert!(running.iter().all(|p| p.status == StepTestStatus::Running));
+    let last = reports.last().unwrap();
+    assert_eq!(last.status, StepTestStatus::Complete);
+    assert_eq!(last.percent, 100.0);
+    let last_estimate = last.estimate.unwrap();
+    assert_eq!(last_estimate, test.identify().unwrap());
+    assert!((last_estimate.model.gain - 2.0).abs() <= (early.model.gain - 2.0).abs());
+    assert_eq!(test.progress(), *last);
+
+    // Cancelled from another thread: the held output comes back
+    let token = CancelToken::new();
+    let mut test = StepTest::new(test_config).with_cancel_token(token.clone());
+    let mut controller = PidController::new(config);
+    for _ in 0..60 {
+        test.step(&mut controller, 40.0, 1.0).unwrap();
+    }
+    assert_eq!(test.outputs().last(), Some(&40.0));
+    std::thread::spawn(move || token.cancel()).join().unwrap();
+    assert_eq!(test.step(&mut controller, 40.0, 1.0).unwrap(), 30.0);
+    assert_eq!(test.status(), StepTestStatus::Cancelled);
+    assert_eq!(test.progress().status, StepTestStatus::Cancelled);
+    assert_eq!(test.outputs().len(), 60);
+    assert!(test.step(&mut controller, 40.0, 1.0).is_err());
+}
+
+#[test]
+fn test_step_test_constraints_abort_to_the_safe_output() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.1)
+        .with_setpoint(40.0)
+        .with_output_limits(0.0, 100.0)
+        .with_initial_output(30.0)
+        .build()
+        .unwrap();
+    // Process: K = 2, τ = 30 s around a resting point of 40 at 30 %
+    let run = |test: &mut StepTest, controller: &mut PidController| {
+        let mut process_value = 40.0;
+        let mut output = 0.0;
+        while test.status() == StepTestStatus::Running {
+            output = test.step(controller, process_value, 1.0).unwrap();
+            process_value += (2.0 * (output - 30.0) + 40.0 - process_value) / 30.0;
+        }
+        (output, process_value)
+    };
+
+    // The amplitude cap clips a step meant for another operating point, and
+    // the process wanders too far from where it started
+    let mut controller = PidController::new(config.clone());
+    let mut test = StepTest::new(
+        StepTestConfig::builder(Excitation::Step { amplitude: 40.0 }, 300.0)
+            .with_max_amplitude(8.0)
+            .with_max_deviation(10.0)
+            .with_safe_output(0.0)
+            .build()
+            .unwrap(),
+    );
+    let (output, process_value) = run(&mut test, &mut controller);
+    let StepTestStatus::Aborted {
+        process_value: stopped,
+    } = test.status()
+    else {
+        panic!("expected an abort, got {:?}", test.status());
+    };
+    assert!(stopped > 50.0 && stopped < 56.0);
+    assert!(test.outputs()[1..].iter().all(|&u| u == 38.0));
+    assert_eq!(output, 0.0);
+    // The controller picks up from the safe output
+    let resumed = controller.compute(process_value, 1.0).unwrap();
+    assert!(resumed < 5.0, "{resumed}");
+
+    // A stalled loop runs out of time; the held output comes back
+    let mut controller = PidController::new(config);
+    let mut test = StepTest::new(
+        StepTestConfig::builder(Excitation::Step { amplitude: 5.0 }, 300.0)
+            .with_timeout(Duration::from_millis(20))
+            .build()
+            .unwrap(),
+    );
+    test.step(&mut controller, 40.0, 1.0).unwrap();
+    assert_eq!(test.step(&mut controller, 40.0, 1.0).unwrap(), 35.0);
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(test.step(&mut controller, 40.0, 1.0).unwrap(), 30.0);
+    assert_eq!(test.status(), StepTestStatus::TimedOut);
+
+    let builder = || StepTestConfig::builder(Excitation::Step { amplitude: 1.0 }, 10.0);
+    assert!(builder().with_max_amplitude(0.0).build().is_err());
+    assert!(builder().with_max_deviation(f64::NAN).build().is_err());
+    assert!(builder().with_timeout(Duration::ZERO).build().is_err());
+    assert!(builder().with_safe_output(f64::INFINITY).build().is_err());
+    let built = builder().with_max_deviation(2.0).build().unwrap();
+    assert_eq!(built.max_deviation(), Some(2.0));
+    assert_eq!((built.max_amplitude(), built.timeout()), (None, None));
+}
+
+#[test]
+fn test_tune_record_traces_a_step_test_to_a_controller() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.1)
+        .with_setpoint(40.0)
+        .with_output_limits(0.0, 100.0)
+        .with_initial_output(30.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config.clone());
+    let mut test = StepTest::new(
+        StepTestConfig::builder(Excitation::Step { amplitude: 10.0 }, 300.0)
+            .with_baseline(20.0)
+            .build()
+            .unwrap(),
+    );
+    // Process: K = 2, τ = 30 s, θ = 3 s around a resting point of 40 at 30 %
+    let mut pending = vec![30.0; 3];
+    let mut process_value = 40.0;
+    while test.status() == StepTestStatus::Running {
+        pending.push(test.step(&mut controller, process_value, 1.0).unwrap());
+        let applied = pending[pending.len() - 4];
+        process_value += (2.0 * (applied - 30.0) + 40.0 - process_value) / 30.0;
+    }
+
+    let record = test.tune(TuningRule::SimcPi).unwrap();
+    assert_eq!(record.identification, test.identify().unwrap());
+    assert_eq!(
+        record.gains,
+        TuningRule::SimcPi
+            .gains(&record.identification.model)
+            .unwrap()
+    );
+    assert_eq!((record.samples, record.sample_time), (320, 1.0));
+    assert_eq!(record.fingerprint.len(), 16);
+    assert!(record.unix_time > 1.7e9);
+
+    // The fingerprint pins the exact data
+    let (outputs, process_values) = (test.outputs(), test.process_values());
+    assert!(record.matches(outputs, process_values, 1.0));
+    assert!(!record.matches(outputs, process_values, 2.0));
+    assert!(!record.matches(&outputs[1..], &process_values[1..], 1.0));
+    let mut nudged = process_values.to_vec();
+    nudged[100] = f64::from_bits(nudged[100].to_bits() + 1);
+    assert!(!record.matches(outputs, &nudged, 1.0));
+    assert_eq!(
+        TuneRecord::fingerprint(outputs, process_values, 1.0),
+        record.fingerprint
+    );
+
+    // A controller rebuilt from the record runs the recorded gains
+    let rebuilt = PidController::new(config.to_builder().with_tune(&record).build().unwrap());
+    assert_eq!(rebuilt.config().kp(), record.gains.kp);
+    assert_eq!(rebuilt.config().ki(), record.gains.ki);
+    assert_eq!(rebuilt.config().kd(), record.gains.kd);
+    assert_eq!(rebuilt.config().max_output(), 100.0);
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains("\"rule\":\"simc\""));
+        let loaded: TuneRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, record);
+    }
+
+    // Too short to identify, or a rule that needs dead time on a model
+    // without any
+    assert!(TuneRecord::new(&[1.0; 3], &[1.0; 3], 1.0, TuningRule::SimcPi).is_err());
+    let no_dead_time = Identification {
+        model: FopdtModel::new(2.0, 30.0, 0.0).unwrap(),
+        fit: 1.0,
+    };
+    assert!(TuneRecord::from_identification(
+        no_dead_time,
+        TuningRule::ZieglerNicholsPid,
+        outputs,
+        process_values,
+        1.0
+    )
+    .is_err());
+    assert!(TuneRecord::from_identification(
+        no_dead_time,
+        TuningRule::LambdaPi,
+        outputs,
+        &process_values[1..],
+        1.0
+    )
+    .is_err());
+}
+
+#[test]
+fn test_controllers_run_behind_the_common_trait() {
+    let pid = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.1)
+        .with_setpoint(10.0)
+        .with_output_limits(-50.0, 50.0)
+        .build()
+        .unwrap();
+    let mpc = MpcConfig::builder(FopdtModel::new(1.0, 5.0, 1.0).unwrap())
+        .with_setpoint(10.0)
+        .with_output_limits(-50.0, 50.0)
+        .build()
+        .unwrap();
+    let mut loops: Vec<Box<dyn Controller>> = vec![
+        Box::new(PidController::new(pid.clone())),
+        Box::new(MpcController::new(mpc)),
+        Box::new(BangBangController::new(10.0, 1.0, -50.0, 50.0).unwrap()),
+        Box::new(PidController::new(pid).shape_output(Quantizer::new(5.0).unwrap())),
+    ];
+    for controller in &mut loops {
+        assert!(controller.compute(8.0, 0.1).unwrap() > 0.0);
+        controller.set_setpoint(5.0).unwrap();
+        assert_eq!(controller.setpoint(), 5.0);
+        assert!(controller.compute(8.0, 0.1).unwrap() < 0.0);
+        controller.reset();
+    }
+
+    // Only the PID-based loops keep statistics
+    let kept: Vec<bool> = loops.iter().map(|c| c.statistics().is_some()).collect();
+    assert_eq!(kept, [true, false, false, true]);
+    assert_eq!(loops[0].statistics().unwrap().average_error, 0.0);
+}
+
+#[test]
+fn test_event_log_keeps_bounded_history() {
+    let config = ControllerConfig::builder()
+        .with_kp(10.0)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+
+    // Saturates high for 3 s, then comes off the limit
+    for _ in 0..3 {
+        controller.compute(20.0, 1.0).unwrap();
+    }
+    controller.compute(45.0, 1.0).unwrap();
+    controller.track(45.0, 30.0, 1.0).unwrap();
+    controller.compute(45.0, 1.0).unwrap();
+    controller.set_setpoint(50.0).unwrap(); // unchanged, not logged
+    controller.set_kp(5.0).unwrap();
+    assert!(controller.compute(f64::NAN, 1.0).is_err());
+    controller.reset();
+
+    let events: Vec<(f64, EventKind)> = controller
+        .events()
+        .iter()
+        .map(|event| (event.elapsed, event.kind))
+        .collect();
+    let automatic = OperatingMode::Automatic;
+    let tracking = OperatingMode::Tracking;
+    assert_eq!(
+        events,
+        [
+            (1.0, EventKind::SaturationStarted { output: 100.0 }),
+            (4.0, EventKind::SaturationEnded { duration: 3.0 }),
+            (
+                5.0,
+                EventKind::ModeChanged {
+                    from: automatic,
+                    to: tracking
+                }
+            ),
+            (
+                6.0,
+                EventKind::ModeChanged {
+                    from: tracking,
+                    to: automatic
+                }
+            ),
+            (
+                6.0,
+                EventKind::GainsChanged {
+                    kp: 5.0,
+                    ki: 0.0,
+                    kd: 0.0
+                }
+            ),
+            (6.0, EventKind::Reset),
+        ]
+    );
+    assert!(controller
+        .events()
+        .iter()
+        .all(|event| event.unix_time > 0.0));
+
+    // Shrinking keeps the newest and counts the rest
+    controller.set_event_capacity(2);
+    assert_eq!(controller.events().len(), 2);
+    assert_eq!(controller.events().dropped(), 4);
+    assert_eq!(controller.events().latest().unwrap().kind, EventKind::Reset);
+    controller.clear_events();
+    assert!(controller.events().is_empty());
+
+    // Whole-config updates through the shared handle are logged too
+    let shared = ThreadSafePidController::new(controller.config().clone());
+    let mut moved = controller.config().clone();
+    moved.setpoint = 60.0;
+    shared.update_config(moved).unwrap();
+    let log = shared.events().unwrap();
+    assert_eq!(
+        log.latest().unwrap().kind,
+        EventKind::SetpointChanged {
+            from: 50.0,
+            to: 60.0
+        }
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&log).unwrap();
+        assert_eq!(serde_json::from_str::<EventLog>(&json).unwrap(), log);
+    }
+}
+
+#[test]
+fn test_setpoint_limits_clamp_or_reject_with_events() {
+    let builder = || {
+        ControllerConfig::builder()
+            .with_kp(1.0)
+            .with_output_limits(0.0, 100.0)
+            .with_setpoint(150.0)
+            .with_setpoint_limits(20.0, 250.0)
+    };
+    assert!(builder().with_setpoint(300.0).build().is_err());
+    assert!(builder().with_setpoint_limits(250.0, 20.0).build().is_err());
+    assert!(builder()
+        .with_setpoint_limits(20.0, f64::INFINITY)
+        .build()
+        .is_err());
+
+    let mut clamping = PidController::new(builder().build().unwrap());
+    clamping.set_setpoint(400.0).unwrap();
+    assert_eq!(clamping.setpoint(), 250.0);
+    clamping.set_setpoint(100.0).unwrap();
+    assert!(clamping.set_setpoint(f64::NAN).is_err());
+    let kinds: Vec<EventKind> = clamping.events().iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            EventKind::SetpointClamped {
+                requested: 400.0,
+                to: 250.0
+            },
+            EventKind::SetpointChanged {
+                from: 150.0,
+                to: 250.0
+            },
+            EventKind::SetpointChanged {
+                from: 250.0,
+                to: 100.0
+            },
+        ]
+    );
+
+    let shared = ThreadSafePidController::new(
+        builder()
+            .with_setpoint_limit_action(SetpointLimitAction::Reject)
+            .build()
+            .unwrap(),
+    );
+    assert!(shared.set_setpoint(10.0).is_err());
+    assert_eq!(shared.get_config().unwrap().setpoint(), 150.0);
+    let mut registry = ControllerRegistry::new();
+    registry.register("oven", shared.clone());
+    registry
+        .define_group("ovens", vec![GroupMember::new("oven")])
+        .unwrap();
+    let now = std::time::Instant::now();
+    registry.broadcast_setpoint("ovens", 500.0, now).unwrap();
+    assert!(registry.update_groups(now).is_err());
+    assert!(!registry.is_broadcasting("ovens"));
+    assert_eq!(shared.get_config().unwrap().setpoint(), 150.0);
+    assert_eq!(
+        shared
+            .events()
+            .unwrap()
+            .iter()
+            .map(|event| event.kind)
+            .collect::<Vec<_>>(),
+        [
+            EventKind::SetpointRejected { requested: 10.0 },
+            EventKind::SetpointRejected { requested: 500.0 },
+        ]
+    );
+}
+
+#[test]
+fn test_gain_limits_reject_out_of_range_changes_with_events() {
+    let builder = || {
+        ControllerConfig::builder()
+            .with_kp(2.0)
+            .with_ki(0.5)
+            .with_output_limits(0.0, 100.0)
+            .with_gain_limits(Gain::Kp, 0.0, 10.0)
+            .with_gain_limits(Gain::Ki, 0.0, 2.0)
+    };
+    assert!(builder().with_kp(20.0).build().is_err());
+    assert!(builder()
+        .with_gain_limits(Gain::Kd, 1.0, 0.0)
+        .build()
+        .is_err());
+    assert!(builder()
+        .with_gain_limits(Gain::Kd, f64::NAN, 1.0)
+        .build()
+        .is_err());
+    let config = builder().build().unwrap();
+    assert_eq!(config.gain_limits(Gain::Kp), Some((0.0, 10.0)));
+    assert_eq!(config.gain_limits(Gain::Kd), None);
+    assert_eq!(config.check_gain(Gain::Kd, 1e6), Ok(1e6));
+
+    // The negative-side set is held to the same limits
+    let braking = |kp| PidGains {
+        kp,
+        ki: 0.5,
+        kd: 0.0,
+    };
+    assert!(builder()
+        .with_negative_gains(braking(20.0))
+        .build()
+        .is_err());
+    assert!(builder().with_negative_gains(braking(4.0)).build().is_ok());
+
+    let mut controller = PidController::new(config);
+    assert!(controller.set_negative_gains(Some(braking(20.0))).is_err());
+    assert_eq!(controller.config().negative_gains(), None);
+    controller.set_negative_gains(Some(braking(4.0))).unwrap();
+    assert_eq!(controller.config().negative_gains(), Some(braking(4.0)));
+    controller.set_negative_gains(None).unwrap();
+
+    // A missing decimal point on the dashboard
+    assert!(controller.set_kp(25.0).is_err());
+    assert!(controller.set_ki(f64::NAN).is_err());
+    controller.set_kp(2.5).unwrap();
+    assert!(controller.set_ki(-0.1).is_err());
+    assert_eq!(
+        (controller.config().kp(), controller.config().ki()),
+        (2.5, 0.5)
+    );
+    let kinds: Vec<EventKind> = controller.events().iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            EventKind::GainRejected {
+                gain: Gain::Kp,
+                requested: 20.0
+            },
+            EventKind::GainRejected {
+                gain: Gain::Kp,
+                requested: 25.0
+            },
+            EventKind::GainsChanged {
+                kp: 2.5,
+                ki: 0.5,
+                kd: 0.0
+            },
+            EventKind::GainRejected {
+                gain: Gain::Ki,
+                requested: -0.1
+            },
+        ]
+    );
+
+    #[cfg(feature = "serial")]
+    {
+        let mut config = builder().build().unwrap();
+        let mut state = PidState::new(&config);
+        let command = SerialMessage::SetGain {
+            gain: Gain::Kp,
+            value: 25.0,
+        };
+        assert!(command.apply(&mut config, &mut state).is_err());
+        assert_eq!(config.kp(), 2.0);
+    }
+
+    #[cfg(feature = "serde")]
+    {
+        let config = builder().build().unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: ControllerConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.gain_limits(Gain::Ki), Some((0.0, 2.0)));
+    }
+}
+
+#[test]
+fn test_fork_runs_identically_and_independently() {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(0.5)
+        .with_kd(0.1)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut live =
+        PidController::new(config).with_feedforward(Feedforward::new(0.5, 1.0, 2.0).unwrap());
+    live.start_recording();
+    for k in 0..20 {
+        live.set_disturbance(k as f64 * 0.1).unwrap();
+        live.compute(30.0 + k as f64, 0.1).unwrap();
+    }
+
+    let mut twin = live.fork();
+    assert_eq!(twin.state(), live.state());
+    assert_eq!(twin.events(), live.events());
+    for k in 0..50 {
+        let pv = 40.0 + (k as f64 * 0.3).sin();
+        twin.set_disturbance(2.0).unwrap();
+        live.set_disturbance(2.0).unwrap();
+        assert_eq!(
+            twin.compute(pv, 0.1).unwrap(),
+            live.compute(pv, 0.1).unwrap()
+        );
+    }
+    assert_eq!(twin.state(), live.state());
+    assert_eq!(
+        twin.get_statistics().average_error,
+        live.get_statistics().average_error
+    );
+
+    // A candidate tune evaluated in the shadow never touches the live loop
+    let mut candidate = live.fork();
+    candidate.set_ki(1.5).unwrap();
+    candidate.engage_interlock(0.0).unwrap();
+    candidate.compute(45.0, 0.1).unwrap();
+    let before = live.state().clone();
+    assert_eq!(live.config().ki(), 0.5);
+    assert_eq!(live.interlock(), None);
+    assert_eq!(live.take_recording().unwrap().steps().len(), 70);
+    assert_eq!(candidate.take_recording().unwrap().steps().len(), 71);
+    assert_eq!(*live.state(), before);
+
+    let shared = ThreadSafePidController::new(live.config().clone());
+    shared.compute(45.0, 0.1).unwrap();
+    let mut fork = shared.fork().unwrap();
+    fork.set_kp(5.0).unwrap();
+    assert_eq!(fork.state(), &shared.get_state().unwrap());
+    assert_eq!(shared.get_config().unwrap().kp(), 2.0);
+}
+
+#[test]
+fn test_shadow_runner_predicts_the_candidate_exactly_on_a_linear_process() {
+    let config = ControllerConfig::builder()
+        .with_kp(0.5)
+        .with_ki(0.01)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .with_gain_limits(Gain::Kp, 0.0, 5.0)
+        .build()
+        .unwrap();
+    let model = FopdtModel::new(2.0, 20.0, 3.0).unwrap();
+    let gains = PidGains {
+        kp: 1.5,
+        ki: 0.08,
+        kd: 0.0,
+    };
+    assert!(ShadowRunner::from_gains(
+        PidController::new(config.clone()),
+        PidGains { kp: 50.0, ..gains }
+    )
+    .is_err());
+    let mut shadow = ShadowRunner::from_gains(PidController::new(config.clone()), gains)
+        .unwrap()
+        .with_model(model);
+
+    let decay = (-1.0f64 / 20.0).exp();
+    let mut actuated = vec![0.0; 3];
+    let mut level = 0.0;
+    for _ in 0..400 {
+        actuated.push(shadow.step(level, 1.0).unwrap());
+        let applied = actuated[actuated.len() - 4];
+        level = decay * level + (1.0 - decay) * 2.0 * applied;
+    }
+
+    // The process is the model, so the prediction is what the candidate
+    // would really have done in control
+    let mut candidate_config = config.clone();
+    candidate_config.kp = gains.kp;
+    candidate_config.ki = gains.ki;
+    let truth = model.simulate(&candidate_config, 0.0, 1.0, 400).unwrap();
+    for (step, point) in shadow.steps().iter().zip(&truth) {
+        assert!((step.predicted_process_value - point.process_value).abs() < 1e-9);
+        assert!((step.candidate_output - point.output).abs() < 1e-9);
+    }
+    assert_eq!(
+        actuated[3..],
+        shadow
+            .steps()
+            .iter()
+            .map(|step| step.live_output)
+            .collect::<Vec<_>>()[..]
+    );
+
+    let report = shadow.report();
+    let true_iae: f64 = truth.iter().map(|p| (50.0 - p.process_value).abs()).sum();
+    assert_eq!(report.steps, 400);
+    assert_eq!(report.elapsed, 400.0);
+    assert!((report.candidate_iae.unwrap() - true_iae).abs() < 1e-6);
+    assert!(report.iae_improvement.unwrap() > 0.3);
+    assert!(report.max_output_delta >= report.rms_output_delta);
+    assert!(report.rms_output_delta >= report.mean_output_delta.abs());
+
+    let promoted = shadow.promote();
+    assert_eq!(
+        (promoted.config().kp(), promoted.config().ki()),
+        (1.5, 0.08)
+    );
+
+    // Without a model the candidate sees the measurements as they are
+    let mut blind = ShadowRunner::from_gains(PidController::new(config), gains).unwrap();
+    blind.step(40.0, 1.0).unwrap();
+    blind.set_setpoint(45.0).unwrap();
+    blind.step(41.0, 1.0).unwrap();
+    assert_eq!(blind.candidate().setpoint(), 45.0);
+    assert!(blind
+        .steps()
+        .iter()
+        .all(|step| step.predicted_process_value == step.process_value));
+    let report = blind.report();
+    assert_eq!((report.candidate_iae, report.iae_improvement), (None, None));
+    assert_eq!(report.live_iae, 14.0);
+    assert_eq!(blind.into_live().config().kp(), 0.5);
+}
+
+#[test]
+fn test_interlock_forces_output_and_resumes_bumplessly() {
+    let config = ControllerConfig::builder()
+        .with_kp(2.0)
+        .with_ki(1.0)
+        .with_setpoint(50.0)
+        .with_output_limits(10.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+    controller.compute(45.0, 0.1).unwrap();
+    controller.compute(46.0, 0.1).unwrap();
+    let integral = controller.state().integral_contribution;
+
+    assert!(controller.engage_interlock(f64::NAN).is_err());
+    assert_eq!(controller.interlock(), None);
+    // Below the output limits: the interlock wins
+    controller.engage_interlock(0.0).unwrap();
+    for pv in [40.0, 30.0, 20.0, 21.0] {
+        assert_eq!(controller.compute(pv, 0.1).unwrap(), 0.0);
+        assert_eq!(controller.state().integral_contribution, integral);
+    }
+    // Tracking requests don't override it either
+    assert_eq!(controller.track(21.0, 60.0, 0.1).unwrap(), 0.0);
+    assert_eq!(controller.interlock(), Some(0.0));
+
+    // Resumes from the limit nearest the forced output, moved only by
+    // this step's integral action
+    controller.release_interlock();
+    controller.release_interlock();
+    assert_eq!(controller.interlock(), None);
+    let resumed = controller.compute(21.0, 0.1).unwrap();
+    assert!(
+        (resumed - (10.0 + 1.0 * 29.0 * 0.1)).abs() < 1e-9,
+        "{}",
+        resumed
+    );
+
+    let modes: Vec<EventKind> = controller
+        .events()
+        .iter()
+        .map(|event| event.kind)
+        .filter(|kind| matches!(kind, EventKind::ModeChanged { .. }))
+        .collect();
+    assert_eq!(
+        modes,
+        [
+            EventKind::ModeChanged {
+                from: OperatingMode::Automatic,
+                to: OperatingMode::Interlocked
+            },
+            EventKind::ModeChanged {
+                from: OperatingMode::Interlocked,
+                to: OperatingMode::Automatic
+            },
+        ]
+    );
+
+    // The handle can trip it from another thread
+    let shared = ThreadSafePidController::new(controller.config().clone());
+    let watcher = shared.clone();
+    thread::spawn(move || watcher.engage_interlock(5.0).unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(shared.compute(0.0, 0.1).unwrap(), 5.0);
+    shared.release_interlock().unwrap();
+    assert_eq!(shared.interlock().unwrap(), None);
+}
+
+#[test]
+fn test_interlock_outside_the_limits_resumes_from_the_nearest_limit() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.5)
+        .with_setpoint(50.0)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+    controller.compute(45.0, 1.0).unwrap();
+
+    // A purge forces the actuator past its normal range
+    controller.engage_interlock(150.0).unwrap();
+    assert_eq!(controller.compute(60.0, 1.0).unwrap(), 150.0);
+    assert_eq!(controller.compute(60.0, 1.0).unwrap(), 150.0);
+
+    // Released, the loop picks up at 100 and moves only by one second of
+    // integral action (0.5 × -10 × 1 s), rather than from 150
+    controller.release_interlock();
+    let resumed = controller.compute(60.0, 1.0).unwrap();
+    assert!((resumed - 95.0).abs() < 1e-9, "{}", resumed);
+    let next = controller.compute(60.0, 1.0).unwrap();
+    assert!((next - 90.0).abs() < 1e-9, "{}", next);
+
+    // Limits narrowed while engaged are the ones it resumes inside
+    controller.engage_interlock(-20.0).unwrap();
+    controller.compute(40.0, 1.0).unwrap();
+    controller.set_output_limits(10.0, 80.0);
+    assert_eq!(controller.compute(40.0, 1.0).unwrap(), -20.0);
+    controller.release_interlock();
+    let resumed = controller.compute(40.0, 1.0).unwrap();
+    assert!((resumed - 15.0).abs() < 1e-9, "{}", resumed);
+}
+
+#[test]
+fn test_statistics_run_on_loop_time() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+
+    // An hour of process time in a few microseconds of wall time
+    let errors = [4.0, 1.5, 0.0, 1.0, 0.0, 0.0];
+    let dts = [600.0, 600.0, 600.0, 300.0, 300.0, 1200.0];
+    for (error, dt) in errors.iter().zip(dts) {
+        controller.compute(10.0 - error, dt).unwrap();
+    }
+    assert!(controller.compute(f64::NAN, 1e6).is_err());
+
+    let stats = controller.get_statistics();
+    assert_eq!(stats.rise_time, 1800.0);
+    assert_eq!(stats.settling_time, 2400.0);
+    // (4·600 + 1.5·600 + 1·300) / 3600
+    assert_eq!(stats.average_error, 1.0);
+    assert_eq!(stats.max_overshoot, 4.0);
+
+    // Until it settles, settling time is the loop time so far
+    controller.reset();
+    controller.compute(5.0, 30.0).unwrap();
+    let stats = controller.get_statistics();
+    assert!(stats.rise_time.is_nan());
+    assert_eq!(stats.settling_time, 30.0);
+}
+
+#[test]
+fn test_statistics_report_covers_each_setpoint() {
+    let config = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_setpoint(1.0)
+        .with_output_limits(0.0, 10.0)
+        .build()
+        .unwrap();
+    let mut controller = PidController::new(config);
+
+    // Step up from 0, overshooting by 0.2, then settle
+    for pv in [0.0, 0.5, 1.2, 1.0, 1.0] {
+        controller.compute(pv, 1.0).unwrap();
+    }
+    // Step down to 0.5, never getting there
+    controller.set_setpoint(0.5).unwrap();
+    for pv in [1.0, 0.8] {
+        controller.compute(pv, 2.0).unwrap();
+    }
+
+    let report = controller.get_statistics().to_report();
+    assert_eq!(report.elapsed, 9.0);
+    assert_eq!(report.rise_time, Some(4.0));
+    assert_eq!(report.settling_time, None);
+    assert!((report.iae - (1.0 + 0.5 + 0.2 + 1.0 + 0.6)).abs() < 1e-12);
+    assert!((report.ise - (1.0 + 0.25 + 0.04 + 0.5 + 0.18)).abs() < 1e-12);
+
+    let [up, down] = &report.responses[..] else {
+        panic!("expected two responses, got {:?}", report.responses);
+    };
+    assert_eq!((up.start, up.from, up.setpoint), (0.0, 1.0, 1.0));
+    assert_eq!((up.rise_time, up.settling_time), (Some(4.0), Some(4.0)));
+    assert!((up.max_overshoot - 0.2).abs() < 1e-12);
+    assert_eq!((down.start, down.from, down.setpoint), (5.0, 1.0, 0.5));
+    assert_eq!(
+        (down.rise_time, down.duration, down.max_overshoot),
+        (None, 4.0, 0.0)
+    );
+
+    // Bins are one settled threshold (0.05) wide and count seconds
+    let errors = &report.error_histogram;
+    assert_eq!((errors.low, errors.high), (-0.25, 0.25));
+    assert_eq!((errors.bins[1], errors.bins[5]), (1.0, 2.0));
+    assert_eq!((errors.below, errors.above), (4.0, 2.0));
+    let outputs = report.output_histogram.as_ref().unwrap();
+    assert_eq!(outputs.bin_width(), 1.0);
+    assert_eq!(outputs.total(), 9.0);
+
+    // A fresh start clears the responses and histograms
+    controller.reset();
+    let stats = controller.get_statistics();
+    assert!(stats.responses.is_empty());
+    assert_eq!(stats.error_histogram.total(), 0.0);
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            serde_json::from_str::<StatisticsReport>(&json).unwrap(),
+            report
+        );
+    }
+}
+
+#[test]
+fn test_statistics_opt_out_skips_bookkeeping() {
+    let builder = ControllerConfig::builder()
+        .with_kp(1.0)
+        .with_ki(0.5)
+        .with_setpoint(10.0)
+        .with_output_limits(-100.0, 100.0);
+    let mut kept = PidController::new(builder.clone().build().unwrap());
+    let mut lean = PidController::new(builder.with_statistics(false).build().unwrap());
+    assert!(!lean.config().statistics());
+
+    // Same outputs, no statistics
+    for pv in [0.0, 4.0, 8.0, 11.0] {
+        assert_eq!(
+            kept.compute(pv, 0.1).unwrap(),
+            lean.compute(pv, 0.1).unwrap()
+        );
+    }
+    let stats = lean.get_statistics();
+    assert_eq!(
+        (stats.elapsed, stats.iae, stats.output_total),
+        (0.0, 0.0, 0.0)
+    );
+    assert!(stats.responses.is_empty());
+    assert!(Controller::statistics(&lean).is_none());
+    assert!(Controller::statistics(&kept).is_some());
+
+    #[cfg(feature = "serde")]
+    {
+        // Only the non-default value is written
+        let json = serde_json::to_value(lean.config()).unwrap();
+        assert_eq!(json["statistics"], false);
+        assert!(serde_json::to_value(kept.config())
+            .unwrap()
+            .get("statistics")
+            .is_none());
+    }
 }
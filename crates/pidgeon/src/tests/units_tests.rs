@@ -0,0 +1,76 @@
+use crate::units::{Celsius, Fahrenheit, Kelvin, Newton, Percent};
+use crate::*;
+
+/// Bare `pid_compute` loop, so the wrapper is exercised without `std`.
+struct Loop {
+    config: ControllerConfig,
+    state: PidState,
+}
+
+impl Controller for Loop {
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError> {
+        let (output, state) = pid_compute(&self.config, &self.state, process_value, dt)?;
+        self.state = state;
+        Ok(output)
+    }
+
+    fn setpoint(&self) -> f64 {
+        self.config.setpoint()
+    }
+
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError> {
+        self.config = ControllerConfig::builder()
+            .with_kp(self.config.kp())
+            .with_output_limits(self.config.min_output(), self.config.max_output())
+            .with_setpoint(setpoint)
+            .build()?;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.state = PidState::default();
+    }
+}
+
+#[test]
+fn test_typed_controller_carries_units_through() {
+    let config = ControllerConfig::builder()
+        .with_kp(4.0)
+        .with_output_limits(-200.0, 200.0)
+        .build()
+        .unwrap();
+    let mut arm = TypedController::<_, Celsius, Newton>::new(Loop {
+        state: PidState::new(&config),
+        config,
+    });
+    arm.set_setpoint(Quantity::from(Quantity::<Kelvin>::new(313.15)))
+        .unwrap();
+    assert!((arm.setpoint().value() - 40.0).abs() < 1e-9);
+
+    let force: Quantity<Newton> = arm
+        .compute(Quantity::<Fahrenheit>::new(86.0).into(), 0.1)
+        .unwrap();
+    assert!((force.value() - 40.0).abs() < 1e-9);
+    assert!(arm.set_setpoint(Quantity::new(f64::NAN)).is_err());
+    assert!(arm.compute(Quantity::new(20.0), 0.0).is_err());
+
+    arm.reset();
+    assert_eq!(arm.into_inner().state, PidState::default());
+}
+
+#[test]
+fn test_quantity_arithmetic_and_display() {
+    let a = Quantity::<Percent>::new(40.0);
+    let b = Quantity::<Percent>::new(15.5);
+    assert_eq!((a + b).value(), 55.5);
+    assert_eq!((a - b).value(), 24.5);
+    assert_eq!((-a * 2.0 / 4.0).value(), -20.0);
+    assert!(b < a);
+    assert_eq!(Quantity::<Percent>::default().value(), 0.0);
+
+    let boiling: Quantity<Fahrenheit> = Quantity::<Celsius>::new(100.0).into();
+    assert_eq!(boiling.value(), 212.0);
+    assert_eq!(format!("{boiling}"), "212 °F");
+    assert_eq!(format!("{:.1}", a), "40.0 %");
+    assert_eq!(format!("{:?}", b), "15.5 %");
+}
@@ -1,20 +1,27 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 
-use crate::config::ControllerConfig;
+use crate::config::{ControllerConfig, PidGains};
 use crate::controller::{ControllerStatistics, PidController};
 use crate::error::PidError;
+use crate::events::EventLog;
+use crate::record::Recording;
+use crate::state::PidState;
 
 #[cfg(feature = "debugging")]
-use crate::controller::StatisticsTracker;
+use crate::debug::{ControllerDebugger, ControllerSnapshot, DebugConfig, DebuggerDiagnostics};
 
-#[cfg(feature = "debugging")]
-use crate::debug::{ControllerDebugger, DebugConfig};
+#[cfg(feature = "remote-control")]
+use crate::remote::RemoteAction;
+
+#[cfg(feature = "can")]
+use crate::can::{CanCodec, CanFrame, CanMessage};
 
 /// Thread-safe PID controller backed by `Arc<Mutex<PidController>>`.
 ///
 /// All methods take `&self` (not `&mut self`), so a single instance can be
 /// shared across threads via [`Clone`]. Cloning produces a new handle to the
-/// *same* underlying controller, not an independent copy.
+/// *same* underlying controller, not an independent copy; use
+/// [`fork`](Self::fork) for that.
 ///
 /// # Examples
 ///
@@ -73,6 +80,21 @@ impl ThreadSafePidController {
         controller.compute(process_value, dt)
     }
 
+    /// Follows an externally applied output for one iteration. See
+    /// [`PidController::track`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned, or
+    /// [`PidError::InvalidParameter`] if inputs are invalid.
+    pub fn track(&self, process_value: f64, tracked_output: f64, dt: f64) -> Result<f64, PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.track(process_value, tracked_output, dt)
+    }
+
     /// Resets controller state and statistics. See [`PidController::reset`].
     ///
     /// # Errors
@@ -87,6 +109,16 @@ impl ThreadSafePidController {
         Ok(())
     }
 
+    /// Locks the controller, for operations spanning several controllers
+    pub(crate) fn lock(&self) -> Result<MutexGuard<'_, PidController>, PidError> {
+        self.controller.lock().map_err(|_| PidError::MutexPoisoned)
+    }
+
+    /// Whether both handles refer to the same controller
+    pub(crate) fn same_controller(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.controller, &other.controller)
+    }
+
     /// Returns the most recent clamped control output.
     ///
     /// # Errors
@@ -100,6 +132,20 @@ impl ThreadSafePidController {
         Ok(controller.state.last_output)
     }
 
+    /// Returns a copy of the controller's internal state, including the last
+    /// measurement, error, and output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn get_state(&self) -> Result<PidState, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.state.clone())
+    }
+
     /// Updates the setpoint. See [`PidController::set_setpoint`].
     ///
     /// # Errors
@@ -113,6 +159,90 @@ impl ThreadSafePidController {
         controller.set_setpoint(setpoint)
     }
 
+    /// Forces the output to `safe_output`. See
+    /// [`PidController::engage_interlock`]. Safe to call from the thread
+    /// watching the interlock condition while another runs the loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] or [`PidError::InvalidParameter`].
+    pub fn engage_interlock(&self, safe_output: f64) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.engage_interlock(safe_output)
+    }
+
+    /// Releases an engaged interlock. See
+    /// [`PidController::release_interlock`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn release_interlock(&self) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.release_interlock();
+        Ok(())
+    }
+
+    /// The output forced by an engaged interlock, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn interlock(&self) -> Result<Option<f64>, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.interlock())
+    }
+
+    /// Starts capturing every compute. See [`PidController::start_recording`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn start_recording(&self) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.start_recording();
+        Ok(())
+    }
+
+    /// Stops recording and returns what was captured. See
+    /// [`PidController::take_recording`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn take_recording(&self) -> Result<Option<Recording>, PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.take_recording())
+    }
+
+    /// Overwrites the integral term. See [`PidController::set_integral`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] or [`PidError::InvalidParameter`].
+    pub fn set_integral(&self, value: f64) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.set_integral(value)
+    }
+
     /// Replaces the entire configuration. State and statistics are preserved.
     ///
     /// # Errors
@@ -123,10 +253,38 @@ impl ThreadSafePidController {
             .controller
             .lock()
             .map_err(|_| PidError::MutexPoisoned)?;
-        controller.config = config;
+        controller.replace_config(config);
         Ok(())
     }
 
+    /// Returns a copy of the current configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn get_config(&self) -> Result<ControllerConfig, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.config.clone())
+    }
+
+    /// Returns an independent [`PidController`] copied from the shared one;
+    /// see [`PidController::fork`]. The shared controller is unaffected by
+    /// anything done to the fork.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn fork(&self) -> Result<PidController, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.fork())
+    }
+
     /// Returns a snapshot of performance statistics.
     ///
     /// # Errors
@@ -140,6 +298,145 @@ impl ThreadSafePidController {
         Ok(controller.get_statistics())
     }
 
+    /// Returns a copy of the event log. See [`PidController::events`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn events(&self) -> Result<EventLog, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.events().clone())
+    }
+
+    /// Sets the totalizer's rollover limit. See
+    /// [`PidController::set_totalizer_rollover`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned, or
+    /// [`PidError::InvalidParameter`] if `limit` is invalid.
+    pub fn set_totalizer_rollover(&self, limit: Option<f64>) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.set_totalizer_rollover(limit)
+    }
+
+    /// Clears the output totalizer. See [`PidController::reset_totalizer`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    pub fn reset_totalizer(&self) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.reset_totalizer();
+        Ok(())
+    }
+
+    /// Returns the debugger's queue counters, or `None` if no debugger is
+    /// attached. Only available with the `debugging` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    #[cfg(feature = "debugging")]
+    pub fn get_debug_diagnostics(&self) -> Result<Option<DebuggerDiagnostics>, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.debug_diagnostics())
+    }
+
+    /// Captures config, state, statistics, and the debugger's recent samples,
+    /// or `None` if no debugger is attached. Only available with the
+    /// `debugging` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    #[cfg(feature = "debugging")]
+    pub fn snapshot(&self) -> Result<Option<ControllerSnapshot>, PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.snapshot())
+    }
+
+    /// Flushes queued debug samples and stops the debugger, waiting at most
+    /// `timeout`. Returns `true` if every sample was delivered, or if no
+    /// debugger is attached. Only available with the `debugging` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    #[cfg(feature = "debugging")]
+    pub fn shutdown_debugger(&self, timeout: crate::time::Duration) -> Result<bool, PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(controller.shutdown_debugger(timeout))
+    }
+
+    /// Applies an operator command to the controller. Only available with the
+    /// `remote-control` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] or [`PidError::InvalidParameter`].
+    #[cfg(feature = "remote-control")]
+    pub fn apply_command(&self, action: &RemoteAction) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.apply_command(action)
+    }
+
+    /// Applies a command received over CAN. See
+    /// [`PidController::apply_can_message`]. Only available with the `can`
+    /// feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] or [`PidError::InvalidParameter`].
+    #[cfg(feature = "can")]
+    pub fn apply_can_message(&self, message: &CanMessage) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.apply_can_message(message)
+    }
+
+    /// Encodes the latest cycle's telemetry frames. See
+    /// [`CanCodec::telemetry`]. Only available with the `can` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] if the mutex was poisoned.
+    #[cfg(feature = "can")]
+    pub fn can_telemetry(
+        &self,
+        codec: &CanCodec,
+        process_value: f64,
+    ) -> Result<[CanFrame; 2], PidError> {
+        let controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        Ok(codec.telemetry(&controller.config, &controller.state, process_value))
+    }
+
     /// Updates the proportional gain at runtime.
     ///
     /// # Errors
@@ -179,6 +476,20 @@ impl ThreadSafePidController {
         controller.set_kd(kd)
     }
 
+    /// Replaces the negative-side gains at runtime. See
+    /// [`PidController::set_negative_gains`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::MutexPoisoned`] or [`PidError::InvalidParameter`].
+    pub fn set_negative_gains(&self, gains: Option<PidGains>) -> Result<(), PidError> {
+        let mut controller = self
+            .controller
+            .lock()
+            .map_err(|_| PidError::MutexPoisoned)?;
+        controller.set_negative_gains(gains)
+    }
+
     /// Updates the output clamp range at runtime.
     ///
     /// # Errors
@@ -225,16 +536,16 @@ impl ThreadSafePidController {
         let pid_controller = PidController {
             config: lock.config.clone(),
             state: lock.state.clone(),
-            stats: StatisticsTracker {
-                start_time: lock.stats.start_time,
-                error_sum: lock.stats.error_sum,
-                error_count: lock.stats.error_count,
-                max_error: lock.stats.max_error,
-                reached_setpoint: lock.stats.reached_setpoint,
-                rise_time: lock.stats.rise_time,
-                settle_time: lock.stats.settle_time,
-                settled_threshold: lock.stats.settled_threshold,
-            },
+            stats: lock.stats.clone(),
+            recording: lock.recording.clone(),
+            feedforward: lock.feedforward.clone(),
+            disturbance: lock.disturbance,
+            events: lock.events.clone(),
+            mode: lock.mode,
+            interlock: lock.interlock,
+            resume_integral: lock.resume_integral,
+            run_time: lock.run_time,
+            saturated_since: lock.saturated_since,
             debugger: Some(ControllerDebugger::new(debug_config)),
         };
 
@@ -0,0 +1,20 @@
+//! The crate's clock. Everything that reads the time goes through here.
+//!
+//! `std::time::Instant::now()` and `SystemTime::now()` panic on
+//! `wasm32-unknown-unknown`. With the `wasm` feature the same types come from
+//! `web_time` instead, which reads `performance.now()` and `Date.now()` in the
+//! browser and is `std::time` on every other target.
+
+#[cfg(feature = "wasm")]
+pub(crate) use web_time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(not(feature = "wasm"))]
+pub(crate) use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Milliseconds since the Unix epoch, or `0` if the clock is set earlier.
+#[cfg(feature = "debugging")]
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
@@ -0,0 +1,93 @@
+use crate::error::PidError;
+use crate::pipeline::{FilteredInput, ShapedOutput, SignalStage};
+
+/// A feedback controller that turns a measured process value into an output,
+/// one sample at a time.
+///
+/// [`PidController`](crate::PidController),
+/// [`MpcController`](crate::MpcController),
+/// [`BangBangController`](crate::BangBangController), and the other
+/// controller types implement it, so a loop can be written once and handed
+/// any of them:
+///
+/// ```
+/// use pidgeon::{Controller, ControllerConfig, FopdtModel, MpcConfig, MpcController, PidController};
+///
+/// fn run(controller: &mut impl Controller, process_value: f64) -> f64 {
+///     controller.compute(process_value, 1.0).unwrap()
+/// }
+///
+/// let pid = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_ki(0.1)
+///     .with_output_limits(0.0, 100.0)
+///     .with_setpoint(50.0)
+///     .build()
+///     .unwrap();
+/// let model = FopdtModel::new(2.0, 30.0, 10.0).unwrap();
+/// let mpc = MpcConfig::builder(model)
+///     .with_output_limits(0.0, 100.0)
+///     .with_setpoint(50.0)
+///     .build()
+///     .unwrap();
+///
+/// assert!(run(&mut PidController::new(pid), 40.0) > 0.0);
+/// assert!(run(&mut MpcController::new(mpc), 40.0) > 0.0);
+/// ```
+///
+/// Within the crate, only the [`FilteredInput`] and [`ShapedOutput`]
+/// combinators are generic over it. The loop runners (`HalLoop`,
+/// `TickerLoop`), `ControllerRegistry`, and the debug and remote-control
+/// paths work on PID configs and state directly, so they take PID
+/// controllers only; run other kinds from your own loop.
+pub trait Controller {
+    /// Runs one iteration `dt` seconds after the previous one and returns
+    /// the output to apply.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `process_value` is
+    /// non-finite or `dt` is non-finite / non-positive.
+    fn compute(&mut self, process_value: f64, dt: f64) -> Result<f64, PidError>;
+
+    /// Returns the current setpoint.
+    fn setpoint(&self) -> f64;
+
+    /// Updates the setpoint at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    fn set_setpoint(&mut self, setpoint: f64) -> Result<(), PidError>;
+
+    /// Returns the controller to its initial state, keeping its
+    /// configuration.
+    fn reset(&mut self);
+
+    /// Performance statistics, for controllers that keep them. Defaults to
+    /// `None`.
+    #[cfg(feature = "std")]
+    fn statistics(&self) -> Option<crate::ControllerStatistics> {
+        None
+    }
+
+    /// Passes every process value through `stage`, such as a
+    /// [`LowPassFilter`](crate::LowPassFilter), before this controller sees
+    /// it. Invalid samples are rejected before they reach the stage.
+    fn filter_input<S: SignalStage>(self, stage: S) -> FilteredInput<Self, S>
+    where
+        Self: Sized,
+    {
+        FilteredInput::new(self, stage)
+    }
+
+    /// Passes every output through `stage`, such as a
+    /// [`Limiter`](crate::Limiter) or [`Quantizer`](crate::Quantizer),
+    /// before it is returned. Resetting resets the stage too.
+    fn shape_output<S: SignalStage>(self, stage: S) -> ShapedOutput<Self, S>
+    where
+        Self: Sized,
+    {
+        ShapedOutput::new(self, stage)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use crate::config::ControllerConfig;
+use crate::error::PidError;
+use crate::registry::ControllerRegistry;
+
+/// One change staged in a [`RegistryTransaction`]
+#[derive(Debug, Clone)]
+enum StagedChange {
+    Gains { kp: f64, ki: f64, kd: f64 },
+    Setpoint(f64),
+    OutputLimits { min: f64, max: f64 },
+    Config(Box<ControllerConfig>),
+}
+
+/// Gain and configuration changes across several loops of a
+/// [`ControllerRegistry`], applied all together or not at all.
+///
+/// Obtained from [`ControllerRegistry::transaction`]. Changes are only
+/// collected until [`commit`](Self::commit), which locks every loop
+/// involved, applies each loop's changes in the order they were staged to a
+/// copy of its configuration, and validates the results exactly as
+/// [`ControllerConfigBuilder::build`](crate::ControllerConfigBuilder::build)
+/// would. Only if every loop's new configuration is valid are they written,
+/// while the locks are still held, so no loop ever computes with half of a
+/// coordinated retune and a rejected transaction leaves every loop as it
+/// was. Dropping a transaction without committing discards it.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, ControllerRegistry};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut registry = ControllerRegistry::new();
+/// let inner = registry.register_config("inner", config.clone());
+/// let outer = registry.register_config("outer", config);
+///
+/// // Rejected: the outer loop's limits are inverted, so neither loop changes
+/// let result = registry
+///     .transaction()
+///     .set_gains("inner", 4.0, 0.5, 0.0)
+///     .set_output_limits("outer", 100.0, 0.0)
+///     .commit();
+/// assert!(result.is_err());
+/// assert_eq!(inner.get_config().unwrap().kp(), 1.0);
+///
+/// let previous = registry
+///     .transaction()
+///     .set_gains("inner", 4.0, 0.5, 0.0)
+///     .set_gains("outer", 0.8, 0.05, 0.0)
+///     .commit()
+///     .unwrap();
+/// assert_eq!(inner.get_config().unwrap().kp(), 4.0);
+/// assert_eq!(outer.get_config().unwrap().ki(), 0.05);
+///
+/// // Undo the retune
+/// registry.restore_configs(&previous).unwrap();
+/// assert_eq!(inner.get_config().unwrap().kp(), 1.0);
+/// ```
+#[must_use = "a transaction does nothing until it is committed"]
+pub struct RegistryTransaction<'a> {
+    registry: &'a ControllerRegistry,
+    changes: BTreeMap<String, Vec<StagedChange>>,
+}
+
+impl<'a> RegistryTransaction<'a> {
+    pub(crate) fn new(registry: &'a ControllerRegistry) -> Self {
+        RegistryTransaction {
+            registry,
+            changes: BTreeMap::new(),
+        }
+    }
+
+    fn stage(mut self, name: impl Into<String>, change: StagedChange) -> Self {
+        self.changes.entry(name.into()).or_default().push(change);
+        self
+    }
+
+    /// Stages new proportional, integral, and derivative gains for `name`.
+    pub fn set_gains(self, name: impl Into<String>, kp: f64, ki: f64, kd: f64) -> Self {
+        self.stage(name, StagedChange::Gains { kp, ki, kd })
+    }
+
+    /// Stages a new setpoint for `name`.
+    pub fn set_setpoint(self, name: impl Into<String>, setpoint: f64) -> Self {
+        self.stage(name, StagedChange::Setpoint(setpoint))
+    }
+
+    /// Stages new output limits for `name`.
+    pub fn set_output_limits(self, name: impl Into<String>, min: f64, max: f64) -> Self {
+        self.stage(name, StagedChange::OutputLimits { min, max })
+    }
+
+    /// Stages a whole configuration for `name`, replacing everything staged
+    /// for it before.
+    pub fn set_config(self, name: impl Into<String>, config: ControllerConfig) -> Self {
+        self.stage(name, StagedChange::Config(Box::new(config)))
+    }
+
+    /// Number of loops with staged changes.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns `true` if nothing is staged.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Validates every staged change and, if all pass, applies them.
+    ///
+    /// Returns each changed loop's configuration from before the commit,
+    /// ready for [`ControllerRegistry::restore_configs`] should the retune
+    /// need undoing later. Controller state is kept; the changes are
+    /// recorded in each loop's event log as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if a staged name is not
+    /// registered, two names refer to the same controller, or a loop's
+    /// resulting configuration fails validation, and
+    /// [`PidError::MutexPoisoned`] if a loop's mutex was poisoned. No loop is
+    /// changed in any of these cases.
+    pub fn commit(self) -> Result<BTreeMap<String, ControllerConfig>, PidError> {
+        let controllers = self
+            .changes
+            .keys()
+            .map(|name| {
+                self.registry.get(name).ok_or(PidError::InvalidParameter(
+                    "transaction names a controller that is not registered",
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, controller) in controllers.iter().enumerate() {
+            if controllers[..i]
+                .iter()
+                .any(|other| other.same_controller(controller))
+            {
+                return Err(PidError::InvalidParameter(
+                    "transaction reaches one controller under two names",
+                ));
+            }
+        }
+
+        // Names are sorted, so every transaction locks in the same order
+        let mut guards = controllers
+            .iter()
+            .map(|controller| controller.lock())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut staged = Vec::with_capacity(guards.len());
+        for (guard, changes) in guards.iter().zip(self.changes.values()) {
+            let mut builder = guard.config().to_builder();
+            for change in changes {
+                builder = match change {
+                    StagedChange::Gains { kp, ki, kd } => {
+                        builder.with_kp(*kp).with_ki(*ki).with_kd(*kd)
+                    }
+                    StagedChange::Setpoint(setpoint) => builder.with_setpoint(*setpoint),
+                    StagedChange::OutputLimits { min, max } => {
+                        builder.with_output_limits(*min, *max)
+                    }
+                    StagedChange::Config(config) => config.to_builder(),
+                };
+            }
+            staged.push(builder.build()?);
+        }
+
+        let mut previous = BTreeMap::new();
+        for ((guard, config), name) in guards.iter_mut().zip(staged).zip(self.changes.keys()) {
+            previous.insert(name.clone(), guard.config().clone());
+            guard.replace_config(config);
+        }
+        Ok(previous)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::config::PidGains;
+use crate::error::PidError;
+use crate::time::{SystemTime, UNIX_EPOCH};
+use crate::tuning::{FopdtModel, Identification, TuningRule};
+
+/// A tune and where it came from: the model identified from a record, the
+/// rule applied to it, the gains that resulted, a fingerprint of the data,
+/// and when it was made.
+///
+/// Serialize it next to the controller's configuration to keep tunes
+/// traceable. [`matches`](Self::matches) tells whether a record on disk is
+/// the one a tune was made from, and
+/// [`ControllerConfigBuilder::with_tune`](crate::ControllerConfigBuilder::with_tune)
+/// builds a controller with its gains.
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidController, TuneRecord, TuningRule};
+///
+/// // A step test of a process with gain 2, a 20 s lag, and 4 s dead time
+/// let outputs: Vec<f64> = (0..200).map(|k| if k < 10 { 0.0 } else { 10.0 }).collect();
+/// let decay = (-1.0f64 / 20.0).exp();
+/// let mut process_values = vec![0.0];
+/// for k in 0..199usize {
+///     let applied = outputs[k.saturating_sub(4)];
+///     process_values.push(decay * process_values[k] + (1.0 - decay) * 2.0 * applied);
+/// }
+///
+/// let record = TuneRecord::new(&outputs, &process_values, 1.0, TuningRule::SimcPi).unwrap();
+/// assert!((record.identification.model.gain - 2.0).abs() < 0.01);
+/// assert!(record.matches(&outputs, &process_values, 1.0));
+///
+/// let config = ControllerConfig::builder()
+///     .with_tune(&record)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let controller = PidController::new(config);
+/// assert_eq!(controller.config().kp(), record.gains.kp);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuneRecord {
+    /// The model identified from the record, and how well it fits.
+    pub identification: Identification,
+    /// The rule that turned the model into gains.
+    pub rule: TuningRule,
+    /// The gains the rule gave.
+    pub gains: PidGains,
+    /// [`fingerprint`](Self::fingerprint) of the record the model was
+    /// identified from.
+    pub fingerprint: String,
+    /// Samples in the record.
+    pub samples: usize,
+    /// Seconds between samples.
+    pub sample_time: f64,
+    /// Seconds since the Unix epoch when the tune was made.
+    pub unix_time: f64,
+}
+
+impl TuneRecord {
+    /// Identifies a [`FopdtModel`] from `outputs` and `process_values`
+    /// sampled every `dt` seconds and applies `rule` to it.
+    ///
+    /// # Errors
+    ///
+    /// Whatever [`FopdtModel::identify`] or [`TuningRule::gains`] returns.
+    pub fn new(
+        outputs: &[f64],
+        process_values: &[f64],
+        dt: f64,
+        rule: TuningRule,
+    ) -> Result<Self, PidError> {
+        let identification = FopdtModel::identify(outputs, process_values, dt)?;
+        Self::from_identification(identification, rule, outputs, process_values, dt)
+    }
+
+    /// Applies `rule` to a model already identified from `outputs` and
+    /// `process_values`, recording the data it came from.
+    ///
+    /// # Errors
+    ///
+    /// Whatever [`TuningRule::gains`] returns, or
+    /// [`PidError::InvalidParameter`] if the slices differ in length or `dt`
+    /// is non-finite or non-positive.
+    pub fn from_identification(
+        identification: Identification,
+        rule: TuningRule,
+        outputs: &[f64],
+        process_values: &[f64],
+        dt: f64,
+    ) -> Result<Self, PidError> {
+        if outputs.len() != process_values.len() {
+            return Err(PidError::InvalidParameter(
+                "outputs and process_values must have the same length",
+            ));
+        }
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        let gains = rule.gains(&identification.model)?;
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        Ok(Self {
+            identification,
+            rule,
+            gains,
+            fingerprint: Self::fingerprint(outputs, process_values, dt),
+            samples: outputs.len(),
+            sample_time: dt,
+            unix_time,
+        })
+    }
+
+    /// Fingerprint of a record: 16 hex digits of a 64-bit FNV-1a hash over
+    /// the sample time and every sample's exact bits.
+    ///
+    /// Any change to the data, down to the last bit of one sample, changes
+    /// it. It identifies a record; it does not protect one from tampering.
+    pub fn fingerprint(outputs: &[f64], process_values: &[f64], dt: f64) -> String {
+        const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        let samples = outputs
+            .iter()
+            .zip(process_values)
+            .flat_map(|(u, y)| [*u, *y]);
+        let hash = core::iter::once(dt)
+            .chain(samples)
+            .flat_map(|value| value.to_bits().to_le_bytes())
+            .fold(OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            });
+        format!("{hash:016x}")
+    }
+
+    /// Whether `outputs` and `process_values` sampled every `dt` seconds
+    /// are the record this tune was made from.
+    pub fn matches(&self, outputs: &[f64], process_values: &[f64], dt: f64) -> bool {
+        outputs.len() == self.samples
+            && process_values.len() == self.samples
+            && dt == self.sample_time
+            && Self::fingerprint(outputs, process_values, dt) == self.fingerprint
+    }
+}
@@ -0,0 +1,528 @@
+use crate::compute::pid_compute;
+use crate::config::{ControllerConfig, PidGains};
+use crate::error::PidError;
+use crate::state::PidState;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// First-order-plus-dead-time process model, `K·e^(−θs) / (τs + 1)`.
+///
+/// A step of `Δu` in the controller output moves the process value by
+/// `gain · Δu`. Nothing happens for `dead_time` seconds, then the process
+/// value reaches 63% of the change `time_constant` seconds later. Most
+/// thermal, flow, and level processes are close enough to this shape for the
+/// [`TuningRule`]s to work from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FopdtModel {
+    /// Steady-state change in process value per unit of output (`K`).
+    pub gain: f64,
+    /// Seconds to reach 63% of the final change once it starts (`τ`).
+    pub time_constant: f64,
+    /// Seconds before the process value starts to respond (`θ`).
+    pub dead_time: f64,
+}
+
+/// Second-order process model, `K·ωn² / (s² + 2ζωn·s + ωn²)`.
+///
+/// The shape of a mass on a spring, a motor driving an inertia, or two lags
+/// in series: the process value responds with a rate of its own, and
+/// overshoots by itself when `damping_ratio` is below 1. It has no dead
+/// time. [`StateSpaceModel`](crate::StateSpaceModel) converts from it with
+/// the process value and its rate as the states.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecondOrderModel {
+    /// Steady-state change in process value per unit of output (`K`).
+    pub gain: f64,
+    /// Undamped natural frequency in radians per second (`ωn`).
+    pub natural_frequency: f64,
+    /// Damping ratio (`ζ`): below 1 rings, 1 is critically damped, above 1
+    /// is two real lags.
+    pub damping_ratio: f64,
+}
+
+/// A model fitted by [`FopdtModel::identify`] or
+/// [`SecondOrderModel::identify`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Identification<M = FopdtModel> {
+    /// The best-fitting model.
+    pub model: M,
+    /// Fraction of the process value's variance the model reproduces when
+    /// driven by the recorded outputs, from 0 to 1. Below about 0.8 the
+    /// suggested gains deserve suspicion.
+    pub fit: f64,
+}
+
+/// One step of a simulated closed-loop response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResponsePoint {
+    /// Seconds since the start of the simulation.
+    pub time: f64,
+    /// Simulated process value.
+    pub process_value: f64,
+    /// Controller output applied at this step.
+    pub output: f64,
+}
+
+impl FopdtModel {
+    /// Creates a model, checking that `gain` is finite and nonzero,
+    /// `time_constant` finite and positive, and `dead_time` finite and not
+    /// negative.
+    pub fn new(gain: f64, time_constant: f64, dead_time: f64) -> Result<Self, PidError> {
+        if !gain.is_finite() || gain == 0.0 {
+            return Err(PidError::InvalidParameter(
+                "gain must be a finite nonzero number",
+            ));
+        }
+        if !time_constant.is_finite() || time_constant <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "time_constant must be a finite positive number",
+            ));
+        }
+        if !dead_time.is_finite() || dead_time < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dead_time must be a finite non-negative number",
+            ));
+        }
+        Ok(Self {
+            gain,
+            time_constant,
+            dead_time,
+        })
+    }
+
+    /// Fits a model to recorded controller outputs and process values.
+    ///
+    /// `outputs[k]` and `process_values[k]` are sampled together every `dt`
+    /// seconds. The data may come from an open-loop step test or from normal
+    /// closed-loop operation, as long as the output moves: a few setpoint
+    /// changes are enough. Each candidate dead time up to a third of the
+    /// record is tried with a least-squares fit of the discrete model
+    /// `y[k+1] = a·y[k] + b·u[k−d] + c`, and the best fit wins. The output
+    /// is assumed to have held its first value before the record starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the slices differ in length
+    /// or hold fewer than 10 samples, `dt` or any sample is not finite, or no
+    /// stable first-order model fits (typically because the output barely
+    /// changed).
+    pub fn identify(
+        outputs: &[f64],
+        process_values: &[f64],
+        dt: f64,
+    ) -> Result<Identification, PidError> {
+        check_record(outputs, process_values, dt)?;
+
+        let n = outputs.len();
+        let mut best: Option<(f64, f64, f64, usize, f64)> = None;
+        for delay in 0..=n / 3 {
+            // Before the record starts the output is assumed to have held its
+            // first value, so every candidate is scored on the same rows
+            let rows = (0..n - 1).map(|k| {
+                (
+                    [process_values[k], outputs[k.saturating_sub(delay)], 1.0],
+                    process_values[k + 1],
+                )
+            });
+            let Some([a, b, c]) = least_squares(rows.clone()) else {
+                continue;
+            };
+            if !(a > 0.0 && a < 1.0) || b == 0.0 {
+                continue;
+            }
+            let sse: f64 = rows
+                .map(|(x, y)| {
+                    let residual = y - (a * x[0] + b * x[1] + c * x[2]);
+                    residual * residual
+                })
+                .sum();
+            let mse = sse / (n - 1) as f64;
+            if best.is_none_or(|(.., best_mse)| mse < best_mse) {
+                best = Some((a, b, c, delay, mse));
+            }
+        }
+        let Some((a, b, c, delay, _)) = best else {
+            return Err(PidError::InvalidParameter(
+                "no stable first-order model fits; the output must change during the record",
+            ));
+        };
+
+        // Score the model by running it on the recorded outputs alone, which
+        // is far stricter than its one-step-ahead predictions
+        let mean = process_values.iter().sum::<f64>() / n as f64;
+        let total: f64 = process_values.iter().map(|y| (y - mean).powi(2)).sum();
+        let mut simulated = process_values[0];
+        let mut residual = 0.0;
+        for k in 0..n - 1 {
+            simulated = a * simulated + b * outputs[k.saturating_sub(delay)] + c;
+            residual += (process_values[k + 1] - simulated).powi(2);
+        }
+        let fit = if total > 0.0 {
+            (1.0 - residual / total).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let model = FopdtModel::new(b / (1.0 - a), -dt / a.ln(), delay as f64 * dt)?;
+        Ok(Identification { model, fit })
+    }
+
+    /// Simulates `config` controlling this process.
+    ///
+    /// The process starts at rest at `initial_process_value` with zero output,
+    /// and the controller drives it toward `config`'s setpoint for `steps`
+    /// steps of `dt` seconds. Useful for previewing the response of gains
+    /// from a [`TuningRule`] before trying them on the real process.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `dt` or
+    /// `initial_process_value` is not finite, or `dt` is not positive.
+    pub fn simulate(
+        &self,
+        config: &ControllerConfig,
+        initial_process_value: f64,
+        dt: f64,
+        steps: usize,
+    ) -> Result<Vec<ResponsePoint>, PidError> {
+        if !dt.is_finite() || dt <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "dt must be a finite positive number",
+            ));
+        }
+        let decay = (-dt / self.time_constant).exp();
+        let delay = (self.dead_time / dt).round() as usize;
+        let mut pending: VecDeque<f64> = std::iter::repeat_n(0.0, delay).collect();
+        let mut deviation = 0.0;
+        let mut state = PidState::default();
+        let mut response = Vec::with_capacity(steps);
+        for step in 0..steps {
+            let process_value = initial_process_value + deviation;
+            let (output, next_state) = pid_compute(config, &state, process_value, dt)?;
+            state = next_state;
+            response.push(ResponsePoint {
+                time: step as f64 * dt,
+                process_value,
+                output,
+            });
+            pending.push_back(output);
+            let applied = pending.pop_front().unwrap_or(output);
+            deviation = decay * deviation + self.gain * (1.0 - decay) * applied;
+        }
+        Ok(response)
+    }
+}
+
+impl SecondOrderModel {
+    /// Creates a model, checking that `gain` is finite and nonzero,
+    /// `natural_frequency` finite and positive, and `damping_ratio` finite
+    /// and not negative.
+    pub fn new(gain: f64, natural_frequency: f64, damping_ratio: f64) -> Result<Self, PidError> {
+        if !gain.is_finite() || gain == 0.0 {
+            return Err(PidError::InvalidParameter(
+                "gain must be a finite nonzero number",
+            ));
+        }
+        if !natural_frequency.is_finite() || natural_frequency <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "natural_frequency must be a finite positive number",
+            ));
+        }
+        if !damping_ratio.is_finite() || damping_ratio < 0.0 {
+            return Err(PidError::InvalidParameter(
+                "damping_ratio must be a finite non-negative number",
+            ));
+        }
+        Ok(Self {
+            gain,
+            natural_frequency,
+            damping_ratio,
+        })
+    }
+
+    /// Fits a model to recorded controller outputs and process values.
+    ///
+    /// Takes the same records as [`FopdtModel::identify`], but fits the
+    /// discrete model `y[k+1] = a₁·y[k] + a₂·y[k−1] + b₁·u[k] + b₂·u[k−1] + c`
+    /// by least squares and maps its poles back to `ωn` and `ζ`. There is no
+    /// dead-time search, so use it on plants that respond within a sample or
+    /// two.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the slices differ in length
+    /// or hold fewer than 10 samples, `dt` or any sample is not finite, or no
+    /// stable second-order model fits (the output barely changed, or the
+    /// poles have no continuous-time equivalent).
+    pub fn identify(
+        outputs: &[f64],
+        process_values: &[f64],
+        dt: f64,
+    ) -> Result<Identification<SecondOrderModel>, PidError> {
+        check_record(outputs, process_values, dt)?;
+
+        let n = outputs.len();
+        let rows = (1..n - 1).map(|k| {
+            (
+                [
+                    process_values[k],
+                    process_values[k - 1],
+                    outputs[k],
+                    outputs[k - 1],
+                    1.0,
+                ],
+                process_values[k + 1],
+            )
+        });
+        let no_fit = PidError::InvalidParameter(
+            "no stable second-order model fits; the output must change during the record",
+        );
+        let [a1, a2, b1, b2, c] = least_squares(rows).ok_or(no_fit.clone())?;
+
+        // Poles of z² − a₁z − a₂, mapped through s = ln(z)/dt
+        let discriminant = a1 * a1 + 4.0 * a2;
+        let (natural_frequency, damping_ratio) = if discriminant >= 0.0 {
+            let root = discriminant.sqrt();
+            let (z1, z2) = ((a1 + root) / 2.0, (a1 - root) / 2.0);
+            if !(z1 > 0.0 && z1 < 1.0 && z2 > 0.0 && z2 < 1.0) {
+                return Err(no_fit);
+            }
+            let (s1, s2) = (z1.ln() / dt, z2.ln() / dt);
+            let natural_frequency = (s1 * s2).sqrt();
+            (natural_frequency, -(s1 + s2) / (2.0 * natural_frequency))
+        } else {
+            let radius = (-a2).sqrt();
+            if radius >= 1.0 {
+                return Err(no_fit);
+            }
+            let decay = radius.ln() / dt;
+            let frequency = (-discriminant).sqrt().atan2(a1) / dt;
+            let natural_frequency = decay.hypot(frequency);
+            (natural_frequency, -decay / natural_frequency)
+        };
+
+        // Score the model by running it on the recorded outputs alone
+        let mean = process_values.iter().sum::<f64>() / n as f64;
+        let total: f64 = process_values.iter().map(|y| (y - mean).powi(2)).sum();
+        let (mut previous, mut simulated) = (process_values[0], process_values[1]);
+        let mut residual = 0.0;
+        for k in 1..n - 1 {
+            let next = a1 * simulated + a2 * previous + b1 * outputs[k] + b2 * outputs[k - 1] + c;
+            (previous, simulated) = (simulated, next);
+            residual += (process_values[k + 1] - simulated).powi(2);
+        }
+        let fit = if total > 0.0 {
+            (1.0 - residual / total).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let model = SecondOrderModel::new(
+            (b1 + b2) / (1.0 - a1 - a2),
+            natural_frequency,
+            damping_ratio,
+        )?;
+        Ok(Identification { model, fit })
+    }
+}
+
+/// Checks a record of outputs and process values before fitting a model
+/// to it.
+fn check_record(outputs: &[f64], process_values: &[f64], dt: f64) -> Result<(), PidError> {
+    if outputs.len() != process_values.len() {
+        return Err(PidError::InvalidParameter(
+            "outputs and process_values must be the same length",
+        ));
+    }
+    if outputs.len() < 10 {
+        return Err(PidError::InvalidParameter(
+            "at least 10 samples are needed to identify a model",
+        ));
+    }
+    if !dt.is_finite() || dt <= 0.0 {
+        return Err(PidError::InvalidParameter(
+            "dt must be a finite positive number",
+        ));
+    }
+    if !outputs.iter().chain(process_values).all(|v| v.is_finite()) {
+        return Err(PidError::InvalidParameter("samples must be finite"));
+    }
+    Ok(())
+}
+
+/// Solves the normal equations of an `N`-parameter linear least-squares
+/// problem; `None` if they are singular.
+fn least_squares<const N: usize>(rows: impl Iterator<Item = ([f64; N], f64)>) -> Option<[f64; N]> {
+    let mut ata = [[0.0; N]; N];
+    let mut atb = [0.0; N];
+    for (x, y) in rows {
+        for i in 0..N {
+            for j in 0..N {
+                ata[i][j] += x[i] * x[j];
+            }
+            atb[i] += x[i] * y;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting
+    let scale = ata.iter().flatten().fold(0.0_f64, |m, v| m.max(v.abs()));
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&a, &b| ata[a][col].abs().total_cmp(&ata[b][col].abs()))?;
+        if ata[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        ata.swap(col, pivot);
+        atb.swap(col, pivot);
+        for row in col + 1..N {
+            let factor = ata[row][col] / ata[col][col];
+            let pivot_row = ata[col];
+            for (value, pivot) in ata[row].iter_mut().zip(pivot_row).skip(col) {
+                *value -= factor * pivot;
+            }
+            atb[row] -= factor * atb[col];
+        }
+    }
+    let mut solution = [0.0; N];
+    for row in (0..N).rev() {
+        let known: f64 = (row + 1..N).map(|k| ata[row][k] * solution[k]).sum();
+        solution[row] = (atb[row] - known) / ata[row][row];
+    }
+    solution.iter().all(|v| v.is_finite()).then_some(solution)
+}
+
+/// Classic rules mapping an [`FopdtModel`] to PID gains.
+///
+/// The Ziegler–Nichols and Cohen–Coon rules aim for a quarter-decay response
+/// and are aggressive; SIMC and lambda tuning trade some speed for
+/// robustness and are the better starting point for most loops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum TuningRule {
+    /// Ziegler–Nichols reaction-curve PI.
+    #[cfg_attr(feature = "serde", serde(rename = "zn-pi"))]
+    ZieglerNicholsPi,
+    /// Ziegler–Nichols reaction-curve PID.
+    #[cfg_attr(feature = "serde", serde(rename = "zn-pid"))]
+    ZieglerNicholsPid,
+    /// Cohen–Coon PI.
+    CohenCoonPi,
+    /// Cohen–Coon PID.
+    CohenCoonPid,
+    /// Skogestad's SIMC PI with closed-loop time constant `τc = θ`.
+    #[cfg_attr(feature = "serde", serde(rename = "simc"))]
+    SimcPi,
+    /// Skogestad's improved SIMC PID with derivative time `θ/3`.
+    SimcPid,
+    /// Lambda (IMC) PI with closed-loop time constant `λ = max(τ, 3θ)`.
+    #[cfg_attr(feature = "serde", serde(rename = "lambda"))]
+    LambdaPi,
+}
+
+impl TuningRule {
+    /// Every rule, from most to least aggressive.
+    pub const ALL: [TuningRule; 7] = [
+        TuningRule::ZieglerNicholsPid,
+        TuningRule::ZieglerNicholsPi,
+        TuningRule::CohenCoonPid,
+        TuningRule::CohenCoonPi,
+        TuningRule::SimcPid,
+        TuningRule::SimcPi,
+        TuningRule::LambdaPi,
+    ];
+
+    /// Short name, as accepted by [`FromStr`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            TuningRule::ZieglerNicholsPi => "zn-pi",
+            TuningRule::ZieglerNicholsPid => "zn-pid",
+            TuningRule::CohenCoonPi => "cohen-coon-pi",
+            TuningRule::CohenCoonPid => "cohen-coon-pid",
+            TuningRule::SimcPi => "simc",
+            TuningRule::SimcPid => "simc-pid",
+            TuningRule::LambdaPi => "lambda",
+        }
+    }
+
+    /// Gains for `model`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if the model has no dead time
+    /// and the rule needs some: every rule except SIMC PI and lambda, which
+    /// fall back to `τc = τ/10` and `λ = τ`.
+    pub fn gains(&self, model: &FopdtModel) -> Result<PidGains, PidError> {
+        let FopdtModel {
+            gain: k,
+            time_constant: tau,
+            dead_time: theta,
+        } = *model;
+        let needs_dead_time = !matches!(self, TuningRule::SimcPi | TuningRule::LambdaPi);
+        if needs_dead_time && theta <= 0.0 {
+            return Err(PidError::InvalidParameter(
+                "this tuning rule needs a model with dead time",
+            ));
+        }
+        let r = theta / tau;
+        let gains = match self {
+            TuningRule::ZieglerNicholsPi => {
+                PidGains::from_standard(0.9 * tau / (k * theta), 3.33 * theta, 0.0)
+            }
+            TuningRule::ZieglerNicholsPid => {
+                PidGains::from_standard(1.2 * tau / (k * theta), 2.0 * theta, 0.5 * theta)
+            }
+            TuningRule::CohenCoonPi => PidGains::from_standard(
+                (0.9 + r / 12.0) / (k * r),
+                theta * (30.0 + 3.0 * r) / (9.0 + 20.0 * r),
+                0.0,
+            ),
+            TuningRule::CohenCoonPid => PidGains::from_standard(
+                (4.0 / 3.0 + r / 4.0) / (k * r),
+                theta * (32.0 + 6.0 * r) / (13.0 + 8.0 * r),
+                4.0 * theta / (11.0 + 2.0 * r),
+            ),
+            TuningRule::SimcPi => {
+                let tau_c = if theta > 0.0 { theta } else { tau / 10.0 };
+                PidGains::from_standard(
+                    tau / (k * (tau_c + theta)),
+                    tau.min(4.0 * (tau_c + theta)),
+                    0.0,
+                )
+            }
+            TuningRule::SimcPid => {
+                // Series-form gains, converted to parallel form below
+                let td = theta / 3.0;
+                let kc = (tau + td) / (k * 2.0 * theta);
+                let ti = (tau + td).min(8.0 * theta);
+                PidGains::from_standard(kc * (1.0 + td / ti), ti + td, ti * td / (ti + td))
+            }
+            TuningRule::LambdaPi => {
+                let lambda = tau.max(3.0 * theta);
+                PidGains::from_standard(tau / (k * (lambda + theta)), tau, 0.0)
+            }
+        };
+        Ok(gains)
+    }
+}
+
+impl fmt::Display for TuningRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TuningRule {
+    type Err = PidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TuningRule::ALL
+            .into_iter()
+            .find(|rule| rule.name() == s)
+            .ok_or(PidError::InvalidParameter("unknown tuning rule"))
+    }
+}
@@ -0,0 +1,322 @@
+//! Units of measure for a controller's setpoint, process value, and output,
+//! checked at compile time.
+//!
+//! A [`Quantity`] is an `f64` tagged with a zero-sized [`Unit`] type, and a
+//! [`TypedController`] wraps any [`Controller`] so it only accepts process
+//! values and setpoints in one unit and hands back outputs in another. A
+//! loop written against `TypedController<_, Celsius, Percent>` cannot be fed
+//! Fahrenheit or have its output applied as newtons:
+//!
+//! ```compile_fail
+//! use pidgeon::units::{Celsius, Fahrenheit, Percent};
+//! use pidgeon::{ControllerConfig, PidController, Quantity, TypedController};
+//!
+//! let pid = PidController::new(ControllerConfig::builder().build().unwrap());
+//! let mut oven = TypedController::<_, Celsius, Percent>::new(pid);
+//! oven.compute(Quantity::<Fahrenheit>::new(350.0), 1.0).unwrap();
+//! ```
+//!
+//! Converting is explicit, with `From` between the temperature scales, and
+//! [`unit!`](crate::unit) declares units of your own. Values stay plain
+//! `f64` underneath, so the wrapper costs nothing at run time.
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::error::PidError;
+use crate::traits::Controller;
+
+/// A unit of measure. Implemented by zero-sized marker types, usually
+/// declared with [`unit!`](crate::unit).
+pub trait Unit {
+    /// Symbol printed after values, e.g. `"°C"`.
+    const SYMBOL: &'static str;
+}
+
+/// Declares a zero-sized [`Unit`] type with the given symbol.
+///
+/// ```
+/// use pidgeon::{unit, Quantity};
+///
+/// unit!(
+///     /// Pounds per square inch.
+///     pub Psi, "psi"
+/// );
+///
+/// assert_eq!(Quantity::<Psi>::new(14.7).to_string(), "14.7 psi");
+/// ```
+#[macro_export]
+macro_rules! unit {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $symbol:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        $vis struct $name;
+
+        impl $crate::units::Unit for $name {
+            const SYMBOL: &'static str = $symbol;
+        }
+    };
+}
+
+unit!(
+    /// Degrees Celsius.
+    pub Celsius, "°C"
+);
+unit!(
+    /// Degrees Fahrenheit.
+    pub Fahrenheit, "°F"
+);
+unit!(
+    /// Kelvin.
+    pub Kelvin, "K"
+);
+unit!(
+    /// Percent, as of an actuator's range.
+    pub Percent, "%"
+);
+unit!(
+    /// Newtons.
+    pub Newton, "N"
+);
+unit!(
+    /// Meters.
+    pub Meter, "m"
+);
+unit!(
+    /// Meters per second.
+    pub MeterPerSecond, "m/s"
+);
+unit!(
+    /// Revolutions per minute.
+    pub Rpm, "rpm"
+);
+unit!(
+    /// Volts.
+    pub Volt, "V"
+);
+unit!(
+    /// Amperes.
+    pub Ampere, "A"
+);
+unit!(
+    /// Watts.
+    pub Watt, "W"
+);
+unit!(
+    /// Bar.
+    pub Bar, "bar"
+);
+
+/// An `f64` in unit `U`. Adding, subtracting, and comparing only work
+/// between quantities of the same unit; scaling by a plain `f64` keeps the
+/// unit.
+pub struct Quantity<U> {
+    value: f64,
+    unit: PhantomData<U>,
+}
+
+impl<U> Quantity<U> {
+    /// Tags `value` with unit `U`.
+    pub const fn new(value: f64) -> Self {
+        Self {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    /// The bare number.
+    pub const fn value(self) -> f64 {
+        self.value
+    }
+}
+
+impl<U> Clone for Quantity<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U> Copy for Quantity<U> {}
+
+impl<U> PartialEq for Quantity<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<U> PartialOrd for Quantity<U> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<U> Default for Quantity<U> {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl<U: Unit> fmt::Debug for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}", self.value, U::SYMBOL)
+    }
+}
+
+impl<U: Unit> fmt::Display for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)?;
+        write!(f, " {}", U::SYMBOL)
+    }
+}
+
+impl<U> Add for Quantity<U> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.value + rhs.value)
+    }
+}
+
+impl<U> Sub for Quantity<U> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.value - rhs.value)
+    }
+}
+
+impl<U> Neg for Quantity<U> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.value)
+    }
+}
+
+impl<U> Mul<f64> for Quantity<U> {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self::new(self.value * rhs)
+    }
+}
+
+impl<U> Div<f64> for Quantity<U> {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self {
+        Self::new(self.value / rhs)
+    }
+}
+
+impl From<Quantity<Fahrenheit>> for Quantity<Celsius> {
+    fn from(t: Quantity<Fahrenheit>) -> Self {
+        Self::new((t.value - 32.0) * 5.0 / 9.0)
+    }
+}
+
+impl From<Quantity<Celsius>> for Quantity<Fahrenheit> {
+    fn from(t: Quantity<Celsius>) -> Self {
+        Self::new(t.value * 9.0 / 5.0 + 32.0)
+    }
+}
+
+impl From<Quantity<Kelvin>> for Quantity<Celsius> {
+    fn from(t: Quantity<Kelvin>) -> Self {
+        Self::new(t.value - 273.15)
+    }
+}
+
+impl From<Quantity<Celsius>> for Quantity<Kelvin> {
+    fn from(t: Quantity<Celsius>) -> Self {
+        Self::new(t.value + 273.15)
+    }
+}
+
+/// A [`Controller`] whose process value and setpoint are in unit `Pv` and
+/// whose output is in unit `Out`.
+///
+/// The gains and limits inside stay plain numbers in those units; the
+/// wrapper only guards the boundary, which is where units get mixed up.
+///
+/// ```
+/// use pidgeon::units::{Celsius, Fahrenheit, Percent};
+/// use pidgeon::{ControllerConfig, PidController, Quantity, TypedController};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(5.0)
+///     .with_output_limits(0.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut oven = TypedController::<_, Celsius, Percent>::new(PidController::new(config));
+/// oven.set_setpoint(Quantity::new(180.0)).unwrap();
+///
+/// // A Fahrenheit probe has to be converted on the way in
+/// let probe = Quantity::<Fahrenheit>::new(338.0);
+/// let heater: Quantity<Percent> = oven.compute(probe.into(), 1.0).unwrap();
+/// assert_eq!(heater.value(), 50.0);
+/// ```
+pub struct TypedController<C, Pv, Out> {
+    inner: C,
+    units: PhantomData<(Pv, Out)>,
+}
+
+impl<C: Controller, Pv, Out> TypedController<C, Pv, Out> {
+    /// Wraps `controller`, whose numbers are in `Pv` and `Out`.
+    pub fn new(controller: C) -> Self {
+        Self {
+            inner: controller,
+            units: PhantomData,
+        }
+    }
+
+    /// Runs one iteration `dt` seconds after the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Whatever the wrapped controller's [`Controller::compute`] returns.
+    pub fn compute(
+        &mut self,
+        process_value: Quantity<Pv>,
+        dt: f64,
+    ) -> Result<Quantity<Out>, PidError> {
+        self.inner
+            .compute(process_value.value(), dt)
+            .map(Quantity::new)
+    }
+
+    /// Returns the current setpoint.
+    pub fn setpoint(&self) -> Quantity<Pv> {
+        Quantity::new(self.inner.setpoint())
+    }
+
+    /// Updates the setpoint at runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `setpoint` is non-finite.
+    pub fn set_setpoint(&mut self, setpoint: Quantity<Pv>) -> Result<(), PidError> {
+        self.inner.set_setpoint(setpoint.value())
+    }
+
+    /// Returns the wrapped controller to its initial state.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// The wrapped controller, for anything beyond the typed boundary.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// The wrapped controller, mutably.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Unwraps the controller.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
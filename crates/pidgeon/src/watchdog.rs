@@ -0,0 +1,280 @@
+use crate::controller::PidController;
+use crate::error::PidError;
+use crate::thread_safe::ThreadSafePidController;
+
+use crate::time::{Duration, Instant};
+
+/// What a [`SensorWatchdog`] drives the output to while the measurement is stale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StaleFallback {
+    /// Keep applying the last output computed from a fresh measurement.
+    /// Automatic control resumes as soon as a fresh measurement arrives.
+    HoldLastOutput,
+    /// Apply a fixed, known-safe output. Automatic control resumes as soon as
+    /// a fresh measurement arrives.
+    Failsafe(f64),
+    /// Switch to manual mode. The output starts at the last computed value and
+    /// can be changed with [`SensorWatchdog::set_manual_output`]. Manual mode
+    /// latches: automatic control only resumes after
+    /// [`SensorWatchdog::resume_auto`], even if measurements recover.
+    Manual,
+}
+
+/// Diagnostics event emitted by [`SensorWatchdog`] on freshness transitions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StalenessEvent {
+    /// No measurement arrived within the timeout; the fallback is now active.
+    BecameStale {
+        /// Age of the newest measurement (or time since the watchdog started
+        /// if none was ever recorded).
+        age: Duration,
+    },
+    /// A fresh measurement arrived after a stale period.
+    Recovered {
+        /// How long the measurement was stale.
+        stale_for: Duration,
+    },
+}
+
+/// Result of a single [`SensorWatchdog`] step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchdogOutput {
+    /// The output to apply to the actuator.
+    pub output: f64,
+    /// `true` if `output` came from the controller, `false` if it came from
+    /// the fallback.
+    pub automatic: bool,
+    /// Freshness transition that happened on this step, if any.
+    pub event: Option<StalenessEvent>,
+}
+
+/// Tracks measurement freshness for one loop and substitutes a fallback
+/// output when the sensor goes quiet.
+///
+/// Record each new sensor reading with
+/// [`record_measurement`](Self::record_measurement), then call
+/// [`step`](Self::step) at the loop rate. While the newest reading is younger
+/// than the timeout, `step` runs the controller on it. Once it is older, the
+/// controller is not run and the configured [`StaleFallback`] supplies the
+/// output instead. Each transition into or out of the stale state is reported
+/// once as a [`StalenessEvent`]. Until the first measurement arrives, `step`
+/// returns the fallback output without running the controller.
+///
+/// # Examples
+///
+/// ```
+/// use pidgeon::{ControllerConfig, PidController, SensorWatchdog, StaleFallback};
+/// use std::time::{Duration, Instant};
+///
+/// let config = ControllerConfig::builder()
+///     .with_kp(1.0)
+///     .with_setpoint(10.0)
+///     .with_output_limits(-100.0, 100.0)
+///     .build()
+///     .unwrap();
+/// let mut controller = PidController::new(config);
+///
+/// let t0 = Instant::now();
+/// let mut watchdog =
+///     SensorWatchdog::new(Duration::from_millis(100), StaleFallback::Failsafe(0.0), t0).unwrap();
+///
+/// watchdog.record_measurement(8.0, t0).unwrap();
+/// let step = watchdog.step(&mut controller, t0, 0.01).unwrap();
+/// assert!(step.automatic);
+///
+/// // Sensor goes quiet for longer than the timeout
+/// let later = t0 + Duration::from_millis(250);
+/// let step = watchdog.step(&mut controller, later, 0.01).unwrap();
+/// assert!(!step.automatic);
+/// assert_eq!(step.output, 0.0);
+/// assert!(step.event.is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SensorWatchdog {
+    timeout: Duration,
+    fallback: StaleFallback,
+    started: Instant,
+    last_measurement: Option<(f64, Instant)>,
+    stale_since: Option<Instant>,
+    manual_active: bool,
+    manual_output: f64,
+    last_auto_output: f64,
+}
+
+impl SensorWatchdog {
+    /// Creates a watchdog that declares the measurement stale once it is older
+    /// than `timeout`. `now` marks the start of the grace period before the
+    /// first measurement arrives.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `timeout` is zero or a
+    /// [`StaleFallback::Failsafe`] value is non-finite.
+    pub fn new(timeout: Duration, fallback: StaleFallback, now: Instant) -> Result<Self, PidError> {
+        if timeout.is_zero() {
+            return Err(PidError::InvalidParameter(
+                "staleness timeout must be greater than zero",
+            ));
+        }
+        if let StaleFallback::Failsafe(value) = fallback {
+            if !value.is_finite() {
+                return Err(PidError::InvalidParameter(
+                    "failsafe output must be a finite number",
+                ));
+            }
+        }
+        Ok(SensorWatchdog {
+            timeout,
+            fallback,
+            started: now,
+            last_measurement: None,
+            stale_since: None,
+            manual_active: false,
+            manual_output: 0.0,
+            last_auto_output: 0.0,
+        })
+    }
+
+    /// Records a new sensor reading taken at `at`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `value` is non-finite. A
+    /// non-finite reading does not refresh the timeout.
+    pub fn record_measurement(&mut self, value: f64, at: Instant) -> Result<(), PidError> {
+        if !value.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "measurement must be a finite number",
+            ));
+        }
+        self.last_measurement = Some((value, at));
+        Ok(())
+    }
+
+    /// Age of the newest measurement at `now`, or time since the watchdog
+    /// started if no measurement has been recorded.
+    pub fn age(&self, now: Instant) -> Duration {
+        let since = self.last_measurement.map_or(self.started, |(_, at)| at);
+        now.saturating_duration_since(since)
+    }
+
+    /// Returns `true` if the newest measurement is older than the timeout at `now`.
+    pub fn is_stale(&self, now: Instant) -> bool {
+        self.age(now) > self.timeout
+    }
+
+    /// Returns `true` while a latched [`StaleFallback::Manual`] fallback is active.
+    pub fn is_manual(&self) -> bool {
+        self.manual_active
+    }
+
+    /// Sets the output applied while in manual mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PidError::InvalidParameter`] if `output` is non-finite.
+    pub fn set_manual_output(&mut self, output: f64) -> Result<(), PidError> {
+        if !output.is_finite() {
+            return Err(PidError::InvalidParameter(
+                "manual output must be a finite number",
+            ));
+        }
+        self.manual_output = output;
+        Ok(())
+    }
+
+    /// Leaves manual mode so the next fresh measurement resumes automatic control.
+    pub fn resume_auto(&mut self) {
+        self.manual_active = false;
+    }
+
+    /// The configured fallback.
+    pub fn fallback(&self) -> StaleFallback {
+        self.fallback
+    }
+
+    /// Runs one loop iteration against a [`PidController`].
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors from [`PidController::compute`].
+    pub fn step(
+        &mut self,
+        controller: &mut PidController,
+        now: Instant,
+        dt: f64,
+    ) -> Result<WatchdogOutput, PidError> {
+        self.step_with(now, |pv| controller.compute(pv, dt))
+    }
+
+    /// Runs one loop iteration against a [`ThreadSafePidController`].
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors from [`ThreadSafePidController::compute`].
+    pub fn step_shared(
+        &mut self,
+        controller: &ThreadSafePidController,
+        now: Instant,
+        dt: f64,
+    ) -> Result<WatchdogOutput, PidError> {
+        self.step_with(now, |pv| controller.compute(pv, dt))
+    }
+
+    fn step_with<F>(&mut self, now: Instant, compute: F) -> Result<WatchdogOutput, PidError>
+    where
+        F: FnOnce(f64) -> Result<f64, PidError>,
+    {
+        let stale = self.is_stale(now);
+        let mut event = None;
+
+        match (stale, self.stale_since) {
+            (true, None) => {
+                self.stale_since = Some(now);
+                event = Some(StalenessEvent::BecameStale { age: self.age(now) });
+                if self.fallback == StaleFallback::Manual {
+                    self.manual_active = true;
+                    self.manual_output = self.last_auto_output;
+                }
+            }
+            (false, Some(since)) => {
+                self.stale_since = None;
+                event = Some(StalenessEvent::Recovered {
+                    stale_for: now.saturating_duration_since(since),
+                });
+            }
+            _ => {}
+        }
+
+        if self.manual_active {
+            return Ok(WatchdogOutput {
+                output: self.manual_output,
+                automatic: false,
+                event,
+            });
+        }
+
+        match (stale, self.last_measurement) {
+            (false, Some((pv, _))) => {
+                let output = compute(pv)?;
+                self.last_auto_output = output;
+                Ok(WatchdogOutput {
+                    output,
+                    automatic: true,
+                    event,
+                })
+            }
+            _ => {
+                let output = match self.fallback {
+                    StaleFallback::Failsafe(value) => value,
+                    StaleFallback::HoldLastOutput | StaleFallback::Manual => self.last_auto_output,
+                };
+                Ok(WatchdogOutput {
+                    output,
+                    automatic: false,
+                    event,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use crate::alerts::AlertEngine;
+use crate::models::{PidControllerData, Role};
+use crate::storage::{HistoryQuery, Storage};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use log::*;
+use pidgeon_grpc::proto::pidgeon_client::PidgeonClient;
+use pidgeon_grpc::proto::{
+    AutotuneRequest, ControllerState, GetStateRequest, ListControllersRequest, SetGainsRequest,
+    SetSetpointRequest,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::transport::{Channel, Endpoint};
+
+/// Samples an autotune fits to when the request sets no limit
+const DEFAULT_AUTOTUNE_SAMPLES: usize = 10_000;
+/// Fewest samples worth fitting a model to
+const MIN_AUTOTUNE_SAMPLES: usize = 20;
+
+/// Backs the `/api/admin` endpoints
+///
+/// Loops are managed through a `pidgeon-grpc` server, which serves the
+/// `ControllerRegistry` of the process running them; Pidgeoneer connects
+/// on first use, so the registry may come up after the dashboard. Alert
+/// silences are kept by the [`AlertEngine`], and autotune fits to the
+/// history in [`Storage`].
+pub struct Admin {
+    registry: Option<PidgeonClient<Channel>>,
+    storage: Arc<Storage>,
+    alerts: Arc<AlertEngine>,
+}
+
+impl Admin {
+    /// Manage the registry served at `registry`, if given
+    pub fn new(
+        registry: Option<&str>,
+        storage: Arc<Storage>,
+        alerts: Arc<AlertEngine>,
+    ) -> Result<Self, String> {
+        let registry = registry
+            .map(|url| {
+                Endpoint::from_shared(url.to_string())
+                    .map(|endpoint| PidgeonClient::new(endpoint.connect_lazy()))
+                    .map_err(|e| format!("invalid registry URL '{}': {}", url, e))
+            })
+            .transpose()?;
+        Ok(Self {
+            registry,
+            storage,
+            alerts,
+        })
+    }
+
+    /// A registry client, if `role` reaches `needed` and a registry is
+    /// configured
+    fn registry(&self, role: Role, needed: Role) -> Result<PidgeonClient<Channel>, Response> {
+        require(role, needed)?;
+        self.registry.clone().ok_or_else(|| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "no controller registry; start the server with --registry",
+            )
+                .into_response()
+        })
+    }
+}
+
+/// Refuse the request unless `role` reaches `needed`
+fn require(role: Role, needed: Role) -> Result<(), Response> {
+    if role < needed {
+        let message = match needed {
+            Role::Viewer => "requires a session",
+            Role::Operator => "requires the operator role",
+            Role::Admin => "requires the admin role",
+        };
+        return Err((StatusCode::FORBIDDEN, message).into_response());
+    }
+    Ok(())
+}
+
+/// An error from the registry as an HTTP response
+fn registry_error(status: tonic::Status) -> Response {
+    let code = match status.code() {
+        tonic::Code::NotFound => StatusCode::NOT_FOUND,
+        tonic::Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        _ => {
+            error!("Controller registry request failed: {}", status);
+            StatusCode::BAD_GATEWAY
+        }
+    };
+    (code, status.message().to_string()).into_response()
+}
+
+/// Answer with `result`, or the response it failed with
+fn respond<T: Serialize>(result: Result<T, Response>) -> Response {
+    match result {
+        Ok(body) => Json(body).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Statistics in a [`LoopState`]
+#[derive(Debug, Clone, Serialize)]
+pub struct LoopStatistics {
+    pub average_error: f64,
+    pub max_overshoot: f64,
+    pub settling_time: f64,
+    pub rise_time: f64,
+}
+
+/// A registered loop's config and latest values
+#[derive(Debug, Clone, Serialize)]
+pub struct LoopState {
+    pub controller_id: String,
+    pub setpoint: f64,
+    pub process_value: f64,
+    pub error: f64,
+    pub output: f64,
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub min_output: f64,
+    pub max_output: f64,
+    pub deadband: f64,
+    pub statistics: Option<LoopStatistics>,
+}
+
+impl From<ControllerState> for LoopState {
+    fn from(state: ControllerState) -> Self {
+        let gains = state.gains.unwrap_or_default();
+        LoopState {
+            controller_id: state.controller_id,
+            setpoint: state.setpoint,
+            process_value: state.process_value,
+            error: state.error,
+            output: state.output,
+            kp: gains.kp,
+            ki: gains.ki,
+            kd: gains.kd,
+            min_output: state.min_output,
+            max_output: state.max_output,
+            deadband: state.deadband,
+            statistics: state.statistics.map(|stats| LoopStatistics {
+                average_error: stats.average_error,
+                max_overshoot: stats.max_overshoot,
+                settling_time: stats.settling_time,
+                rise_time: stats.rise_time,
+            }),
+        }
+    }
+}
+
+/// Body of `PATCH /api/admin/loops/:id/config`; absent fields are kept
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConfigUpdate {
+    pub kp: Option<f64>,
+    pub ki: Option<f64>,
+    pub kd: Option<f64>,
+    pub setpoint: Option<f64>,
+}
+
+/// Body of `POST /api/admin/loops/:id/autotune`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AutotuneParams {
+    /// Tuning rule, as pidgeon names them; the registry's default if absent
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Earliest sample to fit to, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub from: Option<u64>,
+    /// Latest sample to fit to, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub to: Option<u64>,
+    /// Most recent samples to fit to
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Apply the gains to the loop, instead of only reporting them
+    #[serde(default)]
+    pub apply: bool,
+}
+
+/// Response of `POST /api/admin/loops/:id/autotune`
+#[derive(Debug, Clone, Serialize)]
+pub struct AutotuneResult {
+    /// Samples the model was fitted to
+    pub samples: usize,
+    /// Sample interval used, in seconds
+    pub dt: f64,
+    pub gain: f64,
+    pub time_constant: f64,
+    pub dead_time: f64,
+    /// Coefficient of determination of the fit
+    pub fit: f64,
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    /// Whether the gains were applied to the loop
+    pub applied: bool,
+}
+
+/// Body of `POST /api/admin/silences`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SilenceRequest {
+    /// Only silence this rule; every rule when absent
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Only silence this controller; every controller when absent
+    #[serde(default)]
+    pub controller_id: Option<String>,
+    /// How long the silence lasts
+    pub duration_secs: f64,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Sample interval of a recorded history: the median `dt` the controllers
+/// reported, or else the median gap between timestamps
+fn sample_interval(samples: &[PidControllerData]) -> Option<f64> {
+    let mut intervals: Vec<f64> = samples
+        .iter()
+        .map(|sample| sample.dt)
+        .filter(|dt| dt.is_finite() && *dt > 0.0)
+        .collect();
+    if intervals.is_empty() {
+        intervals = samples
+            .windows(2)
+            .map(|pair| pair[1].timestamp.saturating_sub(pair[0].timestamp) as f64 / 1000.0)
+            .filter(|dt| *dt > 0.0)
+            .collect();
+    }
+    intervals.sort_by(f64::total_cmp);
+    intervals.get(intervals.len() / 2).copied()
+}
+
+/// `GET /api/admin/loops` — every registered loop's config and values
+pub async fn loops_handler(admin: Arc<Admin>, Extension(role): Extension<Role>) -> Response {
+    let result = async {
+        let mut registry = admin.registry(role, Role::Viewer)?;
+        let ids = registry
+            .list_controllers(ListControllersRequest {})
+            .await
+            .map_err(registry_error)?
+            .into_inner()
+            .controller_ids;
+        let mut loops = Vec::with_capacity(ids.len());
+        for controller_id in ids {
+            match registry.get_state(GetStateRequest { controller_id }).await {
+                Ok(state) => loops.push(LoopState::from(state.into_inner())),
+                // Removed between the listing and now
+                Err(status) if status.code() == tonic::Code::NotFound => {}
+                Err(status) => return Err(registry_error(status)),
+            }
+        }
+        Ok::<_, Response>(loops)
+    };
+    respond(result.await)
+}
+
+/// `GET /api/admin/loops/:id/config` — one loop's config and values
+pub async fn config_handler(
+    admin: Arc<Admin>,
+    Extension(role): Extension<Role>,
+    Path(controller_id): Path<String>,
+) -> Response {
+    let result = async {
+        let state = admin
+            .registry(role, Role::Viewer)?
+            .get_state(GetStateRequest { controller_id })
+            .await
+            .map_err(registry_error)?;
+        Ok::<_, Response>(LoopState::from(state.into_inner()))
+    };
+    respond(result.await)
+}
+
+/// `PATCH /api/admin/loops/:id/config` — change gains and setpoint; admins
+/// only
+///
+/// The gains are applied together, then the setpoint, and the loop's state
+/// after both is returned.
+pub async fn update_config_handler(
+    admin: Arc<Admin>,
+    Extension(role): Extension<Role>,
+    Path(controller_id): Path<String>,
+    Json(update): Json<ConfigUpdate>,
+) -> Response {
+    let result = async {
+        let mut registry = admin.registry(role, Role::Admin)?;
+        let values = [update.kp, update.ki, update.kd, update.setpoint];
+        if values.iter().all(Option::is_none) {
+            return Err((StatusCode::BAD_REQUEST, "nothing to change").into_response());
+        }
+        if values.iter().flatten().any(|value| !value.is_finite()) {
+            return Err((StatusCode::BAD_REQUEST, "values must be finite").into_response());
+        }
+
+        let mut state = None;
+        if update.kp.is_some() || update.ki.is_some() || update.kd.is_some() {
+            let request = SetGainsRequest {
+                controller_id: controller_id.clone(),
+                kp: update.kp,
+                ki: update.ki,
+                kd: update.kd,
+            };
+            state = Some(registry.set_gains(request).await.map_err(registry_error)?);
+        }
+        if let Some(setpoint) = update.setpoint {
+            let request = SetSetpointRequest {
+                controller_id: controller_id.clone(),
+                setpoint,
+            };
+            state = Some(
+                registry
+                    .set_setpoint(request)
+                    .await
+                    .map_err(registry_error)?,
+            );
+        }
+        info!(
+            "Admin changed the config of {}: {:?}",
+            controller_id, update
+        );
+        Ok::<_, Response>(state.map(|state| LoopState::from(state.into_inner())))
+    };
+    respond(result.await)
+}
+
+/// `POST /api/admin/loops/:id/autotune` — fit a model to the loop's stored
+/// history and derive gains, optionally applying them; admins only
+pub async fn autotune_handler(
+    admin: Arc<Admin>,
+    Extension(role): Extension<Role>,
+    Path(controller_id): Path<String>,
+    Json(params): Json<AutotuneParams>,
+) -> Response {
+    let result = async {
+        let mut registry = admin.registry(role, Role::Admin)?;
+        let query = HistoryQuery {
+            controller_id: Some(controller_id.clone()),
+            from: params.from,
+            to: params.to,
+            limit: Some(params.limit.unwrap_or(DEFAULT_AUTOTUNE_SAMPLES)),
+        };
+        let samples = admin.storage.history(&query).await.map_err(|e| {
+            error!("History query failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        })?;
+        if samples.len() < MIN_AUTOTUNE_SAMPLES {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "{} stored samples from {}; at least {} are needed",
+                    samples.len(),
+                    controller_id,
+                    MIN_AUTOTUNE_SAMPLES
+                ),
+            )
+                .into_response());
+        }
+        let dt = sample_interval(&samples).ok_or_else(|| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "the stored samples have no usable interval",
+            )
+                .into_response()
+        })?;
+
+        let request = AutotuneRequest {
+            controller_id: controller_id.clone(),
+            outputs: samples.iter().map(|sample| sample.output).collect(),
+            process_values: samples.iter().map(|sample| sample.process_value).collect(),
+            dt,
+            rule: params.rule.clone().unwrap_or_default(),
+            apply: params.apply,
+        };
+        let response = registry
+            .autotune(request)
+            .await
+            .map_err(registry_error)?
+            .into_inner();
+        let model = response.model.unwrap_or_default();
+        let gains = response.gains.unwrap_or_default();
+        if response.applied {
+            info!(
+                "Admin applied autotuned gains to {}: kp {} ki {} kd {}",
+                controller_id, gains.kp, gains.ki, gains.kd
+            );
+        }
+        Ok::<_, Response>(AutotuneResult {
+            samples: samples.len(),
+            dt,
+            gain: model.gain,
+            time_constant: model.time_constant,
+            dead_time: model.dead_time,
+            fit: response.fit,
+            kp: gains.kp,
+            ki: gains.ki,
+            kd: gains.kd,
+            applied: response.applied,
+        })
+    };
+    respond(result.await)
+}
+
+/// `GET /api/admin/silences` — alert silences still in force
+pub async fn silences_handler(admin: Arc<Admin>) -> Response {
+    Json(admin.alerts.silences()).into_response()
+}
+
+/// `POST /api/admin/silences` — hold back alert notifications for a while;
+/// operators and admins
+pub async fn silence_handler(
+    admin: Arc<Admin>,
+    Extension(role): Extension<Role>,
+    Json(request): Json<SilenceRequest>,
+) -> Response {
+    if let Err(response) = require(role, Role::Operator) {
+        return response;
+    }
+    let duration = match Duration::try_from_secs_f64(request.duration_secs) {
+        Ok(duration) if !duration.is_zero() => duration,
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "duration_secs must be a positive number",
+            )
+                .into_response()
+        }
+    };
+    let silence = admin.alerts.silence(
+        request.rule,
+        request.controller_id,
+        duration,
+        request.reason,
+    );
+    (StatusCode::CREATED, Json(silence)).into_response()
+}
+
+/// `DELETE /api/admin/silences/:id` — lift a silence early; operators and
+/// admins
+pub async fn unsilence_handler(
+    admin: Arc<Admin>,
+    Extension(role): Extension<Role>,
+    Path(id): Path<u64>,
+) -> Response {
+    if let Err(response) = require(role, Role::Operator) {
+        return response;
+    }
+    if admin.alerts.unsilence(id) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        (StatusCode::NOT_FOUND, format!("no silence {}", id)).into_response()
+    }
+}
@@ -0,0 +1,493 @@
+use crate::models::{Alarm, PidControllerData};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use lettre::message::Mailbox;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// How often rules are evaluated
+const EVALUATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A condition watched on every controller it applies to
+///
+/// Rules are loaded from the JSON file given to `--alert-rules`:
+///
+/// ```json
+/// [
+///   { "name": "oven error", "controller_id": "oven",
+///     "kind": "error_above", "threshold": 5.0, "for_secs": 30,
+///     "notify": [{ "type": "slack", "webhook_url": "https://hooks.slack.com/..." }] },
+///   { "name": "loop silent", "kind": "silent", "for_secs": 10,
+///     "notify": [{ "type": "webhook", "url": "https://ops.example.com/hook" }] }
+/// ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRule {
+    /// Shown in the UI and in notifications
+    pub name: String,
+    /// Only watch this controller; every controller when absent
+    #[serde(default)]
+    pub controller_id: Option<String>,
+    /// What to watch for
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+    /// Where to send raise and clear events
+    #[serde(default)]
+    pub notify: Vec<Notifier>,
+}
+
+/// The condition an [`AlertRule`] fires on
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// `|error|` stayed above `threshold` for `for_secs`
+    ErrorAbove {
+        /// Absolute error that counts as excessive
+        threshold: f64,
+        /// How long the error must stay above the threshold
+        for_secs: f64,
+    },
+    /// No sample arrived for `for_secs`
+    Silent {
+        /// Longest acceptable gap between samples
+        for_secs: f64,
+    },
+    /// More than `fraction` of the samples in the last `window_secs` were saturated
+    SaturationAbove {
+        /// Saturated share of samples, from 0 to 1
+        fraction: f64,
+        /// Window the share is measured over
+        window_secs: f64,
+    },
+}
+
+/// A destination for alert events
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notifier {
+    /// POST the [`AlertEvent`] as JSON
+    Webhook {
+        /// Endpoint to POST to
+        url: String,
+    },
+    /// POST a message to a Slack incoming webhook
+    Slack {
+        /// Incoming webhook URL
+        webhook_url: String,
+    },
+    /// Send an e-mail over SMTP
+    Email {
+        /// `smtp://` or `smtps://` URL, with credentials if needed
+        smtp_url: String,
+        /// Sender address
+        from: String,
+        /// Recipient addresses
+        to: Vec<String>,
+    },
+}
+
+/// Whether an alarm started or stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    /// The condition became true
+    Firing,
+    /// The condition is no longer true
+    Resolved,
+}
+
+/// Payload sent to webhooks when an alarm changes state
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertEvent {
+    /// New state of the alarm
+    pub state: AlertState,
+    /// The alarm that changed
+    pub alarm: Alarm,
+}
+
+impl AlertEvent {
+    fn summary(&self) -> String {
+        let state = match self.state {
+            AlertState::Firing => "🔥 FIRING",
+            AlertState::Resolved => "✅ RESOLVED",
+        };
+        format!(
+            "{} [{}] {}: {}",
+            state, self.alarm.controller_id, self.alarm.rule, self.alarm.message
+        )
+    }
+}
+
+/// Load rules from a JSON file
+pub fn load_rules(path: &Path) -> Result<Vec<AlertRule>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("invalid rules in {}: {}", path.display(), e))
+}
+
+/// Per-rule, per-controller evaluation state
+#[derive(Default)]
+struct RuleState {
+    /// When `|error|` first exceeded the threshold, for `ErrorAbove`
+    above_since: Option<Instant>,
+    /// Recent samples' saturation, for `SaturationAbove`
+    window: VecDeque<(Instant, bool)>,
+    /// The alarm, while firing
+    alarm: Option<Alarm>,
+}
+
+/// A window during which matching alarms are not notified
+///
+/// Silenced alarms are still raised, cleared, and listed at `/api/alarms`;
+/// only their notifications are held back.
+#[derive(Debug, Clone, Serialize)]
+pub struct Silence {
+    /// Handle for lifting the silence early
+    pub id: u64,
+    /// Only silence this rule; every rule when absent
+    pub rule: Option<String>,
+    /// Only silence this controller; every controller when absent
+    pub controller_id: Option<String>,
+    /// Milliseconds since the Unix epoch when the silence ends
+    pub until: u64,
+    /// Why, for whoever looks later
+    pub reason: Option<String>,
+}
+
+impl Silence {
+    fn covers(&self, alarm: &Alarm) -> bool {
+        self.rule.as_deref().is_none_or(|rule| rule == alarm.rule)
+            && self
+                .controller_id
+                .as_deref()
+                .is_none_or(|id| id == alarm.controller_id)
+    }
+}
+
+#[derive(Default)]
+struct EngineState {
+    last_seen: HashMap<String, Instant>,
+    rules: HashMap<(usize, String), RuleState>,
+    silences: Vec<Silence>,
+    next_silence: u64,
+}
+
+/// Evaluates [`AlertRule`]s against the ingested stream
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    state: Mutex<EngineState>,
+    http: reqwest::Client,
+}
+
+impl AlertEngine {
+    /// Create an engine for `rules`
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules,
+            state: Mutex::new(EngineState::default()),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Alarms currently firing, oldest first
+    pub fn active(&self) -> Vec<Alarm> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut alarms: Vec<Alarm> = state
+            .rules
+            .values()
+            .filter_map(|rule| rule.alarm.clone())
+            .collect();
+        alarms.sort_by_key(|alarm| alarm.since);
+        alarms
+    }
+
+    /// Hold back notifications for alarms matching `rule` and
+    /// `controller_id` (either may be `None` for all) for `duration`
+    pub fn silence(
+        &self,
+        rule: Option<String>,
+        controller_id: Option<String>,
+        duration: Duration,
+        reason: Option<String>,
+    ) -> Silence {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.next_silence += 1;
+        let silence = Silence {
+            id: state.next_silence,
+            rule,
+            controller_id,
+            until: epoch_millis().saturating_add(duration.as_millis() as u64),
+            reason,
+        };
+        info!(
+            "Silenced alerts for rule {} on controller {} until {}",
+            silence.rule.as_deref().unwrap_or("*"),
+            silence.controller_id.as_deref().unwrap_or("*"),
+            silence.until
+        );
+        state.silences.push(silence.clone());
+        silence
+    }
+
+    /// Silences still in force
+    pub fn silences(&self) -> Vec<Silence> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = epoch_millis();
+        state.silences.retain(|silence| silence.until > now);
+        state.silences.clone()
+    }
+
+    /// Lift a silence early, returning whether it existed
+    pub fn unsilence(&self, id: u64) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let before = state.silences.len();
+        state.silences.retain(|silence| silence.id != id);
+        state.silences.len() < before
+    }
+
+    fn is_silenced(&self, alarm: &Alarm) -> bool {
+        let now = epoch_millis();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .silences
+            .iter()
+            .any(|silence| silence.until > now && silence.covers(alarm))
+    }
+
+    /// Evaluate rules as samples arrive and once per second
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        if self.rules.is_empty() {
+            return;
+        }
+        info!("Evaluating {} alert rules", self.rules.len());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(EVALUATION_INTERVAL);
+            loop {
+                tokio::select! {
+                    sample = samples.recv() => match sample {
+                        Ok(sample) => self.observe(&sample, Instant::now()),
+                        Err(broadcast::error::RecvError::Lagged(missed)) => {
+                            warn!("Alert engine fell behind; skipped {} samples", missed);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = interval.tick() => {
+                        for (rule, event) in self.evaluate(Instant::now()) {
+                            if self.is_silenced(&event.alarm) {
+                                debug!("Alarm {} for {} is silenced", event.alarm.rule, event.alarm.controller_id);
+                                continue;
+                            }
+                            self.notify(&self.rules[rule], event);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn applies_to(rule: &AlertRule, controller_id: &str) -> bool {
+        rule.controller_id
+            .as_deref()
+            .is_none_or(|id| id == controller_id)
+    }
+
+    fn observe(&self, sample: &PidControllerData, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_seen.insert(sample.controller_id.clone(), now);
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !Self::applies_to(rule, &sample.controller_id) {
+                continue;
+            }
+            let rule_state = state
+                .rules
+                .entry((index, sample.controller_id.clone()))
+                .or_default();
+            match rule.condition {
+                AlertCondition::ErrorAbove { threshold, .. } => {
+                    if sample.error.abs() > threshold {
+                        rule_state.above_since.get_or_insert(now);
+                    } else {
+                        rule_state.above_since = None;
+                    }
+                }
+                AlertCondition::SaturationAbove { window_secs, .. } => {
+                    rule_state.window.push_back((now, sample.saturated));
+                    trim_window(&mut rule_state.window, now, window_secs);
+                }
+                AlertCondition::Silent { .. } => {}
+            }
+        }
+    }
+
+    /// Raise and clear alarms, returning each event with its rule's index
+    fn evaluate(&self, now: Instant) -> Vec<(usize, AlertEvent)> {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let EngineState {
+            last_seen, rules, ..
+        } = &mut *guard;
+        let mut events = Vec::new();
+
+        for ((index, controller_id), rule_state) in rules.iter_mut() {
+            let rule = &self.rules[*index];
+            let message = match rule.condition {
+                AlertCondition::ErrorAbove {
+                    threshold,
+                    for_secs,
+                } => rule_state
+                    .above_since
+                    .filter(|since| now.duration_since(*since).as_secs_f64() >= for_secs)
+                    .map(|since| {
+                        format!(
+                            "|error| above {} for {:.0}s",
+                            threshold,
+                            now.duration_since(since).as_secs_f64()
+                        )
+                    }),
+                AlertCondition::Silent { for_secs } => last_seen
+                    .get(controller_id)
+                    .map(|seen| now.duration_since(*seen).as_secs_f64())
+                    .filter(|silent| *silent >= for_secs)
+                    .map(|silent| format!("no samples for {:.0}s", silent)),
+                AlertCondition::SaturationAbove {
+                    fraction,
+                    window_secs,
+                } => {
+                    trim_window(&mut rule_state.window, now, window_secs);
+                    let total = rule_state.window.len();
+                    let saturated = rule_state.window.iter().filter(|(_, s)| *s).count();
+                    let duty = saturated as f64 / total.max(1) as f64;
+                    (total > 0 && duty > fraction).then(|| {
+                        format!(
+                            "saturated {:.0}% of the last {}s",
+                            duty * 100.0,
+                            window_secs
+                        )
+                    })
+                }
+            };
+
+            match (message, rule_state.alarm.take()) {
+                (Some(message), None) => {
+                    let raised = Alarm {
+                        rule: rule.name.clone(),
+                        controller_id: controller_id.clone(),
+                        message,
+                        since: epoch_millis(),
+                    };
+                    warn!("Alarm raised: {} for {}", raised.rule, raised.controller_id);
+                    rule_state.alarm = Some(raised.clone());
+                    events.push((
+                        *index,
+                        AlertEvent {
+                            state: AlertState::Firing,
+                            alarm: raised,
+                        },
+                    ));
+                }
+                (Some(message), Some(mut alarm)) => {
+                    alarm.message = message;
+                    rule_state.alarm = Some(alarm);
+                }
+                (None, Some(cleared)) => {
+                    info!(
+                        "Alarm cleared: {} for {}",
+                        cleared.rule, cleared.controller_id
+                    );
+                    events.push((
+                        *index,
+                        AlertEvent {
+                            state: AlertState::Resolved,
+                            alarm: cleared,
+                        },
+                    ));
+                }
+                (None, None) => {}
+            }
+        }
+        events
+    }
+
+    /// Send `event` to every notifier of `rule` in the background
+    fn notify(&self, rule: &AlertRule, event: AlertEvent) {
+        for notifier in rule.notify.clone() {
+            let http = self.http.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                if let Err(e) = send(&http, &notifier, &event).await {
+                    error!("Failed to send alert notification: {}", e);
+                }
+            });
+        }
+    }
+}
+
+fn trim_window(window: &mut VecDeque<(Instant, bool)>, now: Instant, window_secs: f64) {
+    while window
+        .front()
+        .is_some_and(|(at, _)| now.duration_since(*at).as_secs_f64() > window_secs)
+    {
+        window.pop_front();
+    }
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+async fn send(
+    http: &reqwest::Client,
+    notifier: &Notifier,
+    event: &AlertEvent,
+) -> Result<(), String> {
+    match notifier {
+        Notifier::Webhook { url } => {
+            http.post(url)
+                .json(event)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| e.to_string())?;
+        }
+        Notifier::Slack { webhook_url } => {
+            http.post(webhook_url)
+                .json(&serde_json::json!({ "text": event.summary() }))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| e.to_string())?;
+        }
+        Notifier::Email { smtp_url, from, to } => {
+            let from: Mailbox = from.parse().map_err(|e| format!("invalid sender: {}", e))?;
+            let mut message = Message::builder().from(from).subject(event.summary());
+            for recipient in to {
+                let recipient: Mailbox = recipient
+                    .parse()
+                    .map_err(|e| format!("invalid recipient {}: {}", recipient, e))?;
+                message = message.to(recipient);
+            }
+            let body = serde_json::to_string_pretty(event).map_err(|e| e.to_string())?;
+            let message = message.body(body).map_err(|e| e.to_string())?;
+            AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+                .map_err(|e| e.to_string())?
+                .build()
+                .send(message)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// `GET /api/alarms` — alarms currently firing, oldest first
+pub async fn alarms_handler(engine: Arc<AlertEngine>) -> Response {
+    Json(engine.active()).into_response()
+}
@@ -1,14 +1,43 @@
-use crate::models::PidControllerData;
+use crate::models::{
+    Alarm, CommandLog, CommandStatus, ControllerPresence, ErrorSpectrum, FleetEntry,
+    GainSuggestion, LoopTiming, PidControllerData, Presence, Role, SessionInfo, StepResponse,
+    Subscription, TuningRequest, TuningSuggestions,
+};
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
     components::{Route, Router, Routes},
+    hooks::use_navigate,
     StaticSegment,
 };
+use std::cmp::Ordering;
 
 #[cfg(feature = "hydrate")]
 const MAX_CHART_POINTS: usize = 300;
 
+/// Most recent step responses listed under the metrics
+const RESPONSES_SHOWN: usize = 5;
+
+/// Most recent remote commands listed in the tuning panel
+const COMMANDS_SHOWN: usize = 10;
+
+/// Line colors of the tuning assistant's predicted responses
+const SUGGESTION_COLORS: [&str; 7] = [
+    "#3b82f6", "#22c55e", "#f59e0b", "#ef4444", "#a855f7", "#14b8a6", "#ec4899",
+];
+
+/// Identified models fitting worse than this get a warning
+const POOR_FIT: f64 = 0.5;
+
+/// Fleet health scores at or above this are shown green
+const HEALTHY_FROM: f64 = 80.0;
+
+/// Fleet health scores below this are shown red and counted as needing attention
+const UNHEALTHY_BELOW: f64 = 50.0;
+
+#[cfg(feature = "hydrate")]
+const STATUS_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
 pub fn shell(options: LeptosOptions) -> impl IntoView {
     view! {
         <!DOCTYPE html>
@@ -18,6 +47,8 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                 <meta name="viewport" content="width=device-width, initial-scale=1.0"/>
                 <title>Pidgeoneer - PID Controller Dashboard</title>
                 <script src="https://cdn.jsdelivr.net/npm/chart.js"></script>
+                <script src="https://cdn.jsdelivr.net/npm/hammerjs"></script>
+                <script src="https://cdn.jsdelivr.net/npm/chartjs-plugin-zoom"></script>
                 <AutoReload options=options.clone() />
                 <HydrationScripts options/>
                 <MetaTags/>
@@ -47,6 +78,86 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                         color: #fff;
                     }
 
+                    .nav-link {
+                        margin-left: 16px;
+                        color: #3b82f6;
+                        font-size: 0.8rem;
+                        text-decoration: none;
+                    }
+
+                    .controller-select {
+                        margin-left: auto;
+                        margin-right: 12px;
+                        background: #0f1117;
+                        color: #ccc;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 6px;
+                        padding: 4px 8px;
+                        font-size: 0.8rem;
+                    }
+
+                    .role {
+                        margin-left: 12px;
+                        font-size: 0.75rem;
+                        color: #888;
+                    }
+
+                    .logout {
+                        margin-left: 8px;
+                        background: none;
+                        color: #3b82f6;
+                        border: none;
+                        font-size: 0.75rem;
+                        cursor: pointer;
+                    }
+
+                    .login {
+                        display: flex;
+                        justify-content: center;
+                        padding: 96px 24px;
+                        color: #888;
+                    }
+
+                    .login form {
+                        display: flex;
+                        flex-direction: column;
+                        gap: 12px;
+                        width: 320px;
+                        background: #1a1d28;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 8px;
+                        padding: 24px;
+                    }
+
+                    .login h2 {
+                        font-size: 1.1rem;
+                        font-weight: 600;
+                        color: #fff;
+                    }
+
+                    .login input {
+                        background: #0f1117;
+                        color: #e0e0e0;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 6px;
+                        padding: 8px 10px;
+                    }
+
+                    .login button {
+                        background: #3b82f6;
+                        color: #fff;
+                        border: none;
+                        border-radius: 6px;
+                        padding: 8px 10px;
+                        font-weight: 600;
+                        cursor: pointer;
+                    }
+
+                    .login-error {
+                        font-size: 0.8rem;
+                        color: #ef4444;
+                    }
+
                     .status {
                         padding: 4px 12px;
                         border-radius: 12px;
@@ -58,6 +169,52 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                     .connected { background: #22c55e; color: #fff; }
                     .disconnected { background: #f59e0b; color: #1a1a2e; }
 
+                    .presence:empty { display: none; }
+
+                    .presence {
+                        display: flex;
+                        flex-wrap: wrap;
+                        gap: 16px;
+                        padding: 12px 24px 0;
+                        font-size: 0.8rem;
+                        color: #ccc;
+                    }
+
+                    .presence-item {
+                        display: flex;
+                        align-items: center;
+                        gap: 6px;
+                    }
+
+                    .presence-dot {
+                        width: 8px;
+                        height: 8px;
+                        border-radius: 50%;
+                    }
+                    .presence-dot.online { background: #22c55e; }
+                    .presence-dot.stale { background: #f59e0b; }
+                    .presence-dot.offline { background: #6b7280; }
+
+                    .alarms:empty { display: none; }
+
+                    .alarms {
+                        display: flex;
+                        flex-direction: column;
+                        gap: 6px;
+                        padding: 12px 24px 0;
+                    }
+
+                    .alarm {
+                        background: rgba(239, 68, 68, 0.12);
+                        border: 1px solid #ef4444;
+                        border-radius: 6px;
+                        padding: 8px 12px;
+                        font-size: 0.8rem;
+                        color: #fca5a5;
+                    }
+
+                    .alarm strong { color: #fff; }
+
                     .intro {
                         padding: 20px 24px 8px;
                     }
@@ -124,6 +281,298 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                         color: #fff;
                     }
 
+                    .loop-context {
+                        display: flex;
+                        flex-wrap: wrap;
+                        gap: 8px 24px;
+                        padding: 0 24px 16px;
+                        font-size: 0.75rem;
+                        color: #888;
+                    }
+
+                    .loop-context strong {
+                        color: #ccc;
+                        font-family: 'SF Mono', 'Fira Code', 'Consolas', monospace;
+                        font-weight: 500;
+                    }
+
+                    .loop-context .saturated { color: #f59e0b; }
+
+                    .tuning {
+                        margin: 0 24px 16px;
+                        background: #1a1d28;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 8px;
+                        padding: 16px;
+                    }
+
+                    .tuning-form {
+                        display: flex;
+                        flex-wrap: wrap;
+                        align-items: flex-end;
+                        gap: 12px;
+                        margin-top: 12px;
+                    }
+
+                    .tuning-form label {
+                        display: flex;
+                        flex-direction: column;
+                        gap: 4px;
+                        font-size: 0.7rem;
+                        color: #888;
+                    }
+
+                    .tuning-form input {
+                        width: 110px;
+                        background: #0f1117;
+                        color: #e0e0e0;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 6px;
+                        padding: 6px 8px;
+                    }
+
+                    .tuning-form button {
+                        background: #2a2d3a;
+                        color: #e0e0e0;
+                        border: none;
+                        border-radius: 6px;
+                        padding: 7px 12px;
+                        cursor: pointer;
+                    }
+
+                    .tuning-form button.send { background: #3b82f6; color: #fff; font-weight: 600; }
+                    .tuning-form button:disabled { opacity: 0.4; cursor: default; }
+
+                    .tuning-note {
+                        margin-top: 8px;
+                        font-size: 0.75rem;
+                        color: #999;
+                    }
+
+                    .audit-log {
+                        width: 100%;
+                        margin-top: 12px;
+                        border-collapse: collapse;
+                        font-size: 0.75rem;
+                        color: #ccc;
+                    }
+
+                    .audit-log td { padding: 3px 8px 3px 0; }
+                    .audit-log .pending { color: #f59e0b; }
+                    .audit-log .applied { color: #22c55e; }
+                    .audit-log .rejected { color: #ef4444; }
+
+                    .responses {
+                        padding: 0 24px 16px;
+                        overflow-x: auto;
+                    }
+
+                    .responses table {
+                        width: 100%;
+                        border-collapse: collapse;
+                        font-size: 0.75rem;
+                    }
+
+                    .responses th {
+                        text-align: left;
+                        color: #888;
+                        font-weight: 500;
+                        padding: 4px 8px;
+                        border-bottom: 1px solid #333;
+                    }
+
+                    .responses td {
+                        color: #ccc;
+                        font-family: 'SF Mono', 'Fira Code', 'Consolas', monospace;
+                        padding: 4px 8px;
+                    }
+
+                    .fleet {
+                        padding: 16px 24px 24px;
+                        overflow-x: auto;
+                    }
+
+                    .fleet-summary {
+                        font-size: 0.8rem;
+                        color: #888;
+                        margin-bottom: 12px;
+                    }
+
+                    .fleet table {
+                        width: 100%;
+                        border-collapse: collapse;
+                        font-size: 0.8rem;
+                    }
+
+                    .fleet th {
+                        text-align: left;
+                        color: #888;
+                        font-weight: 500;
+                        padding: 6px 8px;
+                        border-bottom: 1px solid #333;
+                        cursor: pointer;
+                        user-select: none;
+                    }
+
+                    .fleet th:hover { color: #ccc; }
+
+                    .fleet td {
+                        color: #ccc;
+                        font-family: 'SF Mono', 'Fira Code', 'Consolas', monospace;
+                        padding: 6px 8px;
+                        border-bottom: 1px solid #1a1d28;
+                    }
+
+                    .fleet tbody tr { cursor: pointer; }
+                    .fleet tbody tr:hover { background: #1a1d28; }
+                    .fleet .healthy { color: #22c55e; }
+                    .fleet .degraded { color: #f59e0b; }
+                    .fleet .unhealthy { color: #ef4444; }
+
+                    .timing {
+                        display: grid;
+                        grid-template-columns: repeat(auto-fill, minmax(320px, 1fr));
+                        gap: 12px;
+                        padding: 0 24px 16px;
+                    }
+
+                    .timing-card {
+                        background: #1a1d28;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 8px;
+                        padding: 12px 16px;
+                    }
+
+                    .timing-card h3 {
+                        font-size: 0.8rem;
+                        font-weight: 600;
+                        color: #ccc;
+                    }
+
+                    .timing-stats {
+                        display: flex;
+                        flex-wrap: wrap;
+                        gap: 4px 16px;
+                        font-size: 0.7rem;
+                        color: #888;
+                    }
+
+                    .timing-stats strong {
+                        color: #ccc;
+                        font-family: 'SF Mono', 'Fira Code', 'Consolas', monospace;
+                        font-weight: 500;
+                    }
+
+                    .timing-stats .missed { color: #ef4444; }
+
+                    .histogram {
+                        display: flex;
+                        align-items: flex-end;
+                        gap: 2px;
+                        height: 80px;
+                        margin-top: 8px;
+                    }
+
+                    .histogram-bar {
+                        flex: 1;
+                        min-height: 1px;
+                        background: #3b82f6;
+                    }
+
+                    .histogram-range {
+                        display: flex;
+                        justify-content: space-between;
+                        font-size: 0.65rem;
+                        color: #555;
+                    }
+
+                    .spectrum {
+                        margin: 0 24px 16px;
+                    }
+
+                    .assistant {
+                        margin: 0 24px 16px;
+                    }
+
+                    .assistant .chart-header button {
+                        background: #3b82f6;
+                        color: #fff;
+                        border: none;
+                        border-radius: 6px;
+                        padding: 5px 12px;
+                        font-size: 0.75rem;
+                        cursor: pointer;
+                    }
+
+                    .assistant .chart-header button:disabled { opacity: 0.4; cursor: default; }
+
+                    .assistant svg {
+                        display: block;
+                        width: 100%;
+                        height: 160px;
+                        margin-top: 8px;
+                    }
+
+                    .assistant polyline {
+                        fill: none;
+                        stroke-width: 1.5;
+                    }
+
+                    .assistant line.setpoint {
+                        stroke: #555;
+                        stroke-width: 1;
+                    }
+
+                    .assistant table {
+                        width: 100%;
+                        margin-top: 8px;
+                        border-collapse: collapse;
+                        font-size: 0.75rem;
+                    }
+
+                    .assistant th {
+                        text-align: left;
+                        color: #888;
+                        font-weight: 500;
+                        padding: 4px 8px;
+                        border-bottom: 1px solid #333;
+                    }
+
+                    .assistant td {
+                        color: #ccc;
+                        font-family: 'SF Mono', 'Fira Code', 'Consolas', monospace;
+                        padding: 4px 8px;
+                    }
+
+                    .assistant td button {
+                        background: #2a2d3a;
+                        color: #e0e0e0;
+                        border: none;
+                        border-radius: 4px;
+                        padding: 2px 10px;
+                        cursor: pointer;
+                    }
+
+                    .swatch {
+                        display: inline-block;
+                        width: 10px;
+                        height: 10px;
+                        margin-right: 6px;
+                        border-radius: 2px;
+                    }
+
+                    .spectrum svg {
+                        display: block;
+                        width: 100%;
+                        height: 140px;
+                    }
+
+                    .spectrum polyline {
+                        fill: none;
+                        stroke: #f59e0b;
+                        stroke-width: 1.5;
+                    }
+
                     .charts {
                         padding: 0 24px 24px;
                         display: flex;
@@ -131,6 +580,27 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                         gap: 12px;
                     }
 
+                    .chart-controls {
+                        display: flex;
+                        align-items: center;
+                        gap: 8px;
+                    }
+
+                    .chart-controls button {
+                        background: #1a1d28;
+                        color: #ccc;
+                        border: 1px solid #2a2d3a;
+                        border-radius: 6px;
+                        padding: 4px 12px;
+                        font-size: 0.75rem;
+                        cursor: pointer;
+                    }
+
+                    .chart-controls button:hover { border-color: #3b82f6; }
+                    .chart-controls button.paused { background: #f59e0b; color: #1a1a2e; border-color: #f59e0b; }
+
+                    .chart-controls .chart-hint { margin-left: auto; }
+
                     .chart-panel {
                         background: #1a1d28;
                         border-radius: 8px;
@@ -244,28 +714,69 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
     }
 }
 
+/// Whether the browser may see the dashboard
+///
+/// Only the browser build leaves `Checking`; the server always renders it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(not(feature = "hydrate"), allow(dead_code))]
+enum SessionState {
+    /// Waiting for `/api/session`
+    Checking,
+    /// The server wants a token
+    SignedOut,
+    /// Signed in, or the server runs without authentication
+    SignedIn(SessionInfo),
+}
+
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
 
     let (pid_data, set_pid_data) = signal(Vec::<PidControllerData>::new());
     let (connected, set_connected) = signal(false);
+    let (subscription, set_subscription) = signal(Subscription::default());
+    let (session, set_session) = signal(SessionState::Checking);
 
     #[cfg(feature = "hydrate")]
     {
-        use crate::iggy_client::IggyClient;
-
-        let set_connected_clone = set_connected.clone();
-
-        let on_open = move || {
-            set_connected_clone.set(true);
-        };
+        use crate::iggy_client::{fetch_session, load_history, IggyClient};
+
+        leptos::task::spawn_local(async move {
+            let state = match fetch_session().await {
+                Ok(Some(info)) => SessionState::SignedIn(info),
+                Ok(None) => SessionState::SignedOut,
+                Err(e) => {
+                    log::error!("Failed to check session: {}", e);
+                    SessionState::SignedOut
+                }
+            };
+            set_session.set(state);
+        });
 
-        let on_close = move || {
-            set_connected.set(false);
-        };
+        // The WebSocket is only opened once signed in
+        let iggy_client = StoredValue::new_local(None::<IggyClient>);
 
-        let _iggy_client = IggyClient::new(set_pid_data, on_open, on_close);
+        // Start over from stored history whenever the subscription changes
+        leptos::prelude::Effect::new(move |_| {
+            let current = subscription.get();
+            if !matches!(session.get(), SessionState::SignedIn(_)) {
+                return;
+            }
+            iggy_client.update_value(|client| {
+                client
+                    .get_or_insert_with(|| {
+                        IggyClient::new(
+                            set_pid_data,
+                            subscription,
+                            move || set_connected.set(true),
+                            move || set_connected.set(false),
+                        )
+                    })
+                    .subscribe(&current);
+            });
+            set_pid_data.set(Vec::new());
+            load_history(set_pid_data, &current);
+        });
     }
 
     #[cfg(not(feature = "hydrate"))]
@@ -281,11 +792,27 @@ pub fn App() -> impl IntoView {
         <Router>
             <main>
                 <Routes fallback=|| "Page not found.".into_view()>
-                    <Route path=StaticSegment("") view=move || view! {
-                        <HomePage
-                            pid_data=pid_data
-                            connected=connected
-                        />
+                    <Route path=StaticSegment("") view=move || match session.get() {
+                        SessionState::SignedIn(info) => view! {
+                            <HomePage
+                                pid_data=pid_data
+                                connected=connected
+                                subscription=subscription
+                                set_subscription=set_subscription
+                                session=info
+                            />
+                        }
+                            .into_any(),
+                        state => view! { <SessionGate state=state set_session=set_session/> }
+                            .into_any(),
+                    }/>
+                    <Route path=StaticSegment("fleet") view=move || match session.get() {
+                        SessionState::SignedIn(info) => view! {
+                            <FleetPage set_subscription=set_subscription session=info/>
+                        }
+                            .into_any(),
+                        state => view! { <SessionGate state=state set_session=set_session/> }
+                            .into_any(),
                     }/>
                 </Routes>
             </main>
@@ -293,17 +820,194 @@ pub fn App() -> impl IntoView {
     }
 }
 
+/// What to show until the session is signed in
+#[component]
+fn SessionGate(state: SessionState, set_session: WriteSignal<SessionState>) -> impl IntoView {
+    match state {
+        SessionState::SignedOut => view! { <LoginPage set_session=set_session/> }.into_any(),
+        _ => view! {
+            <div class="login"><p>"Connecting\u{2026}"</p></div>
+        }
+        .into_any(),
+    }
+}
+
+/// Role and sign-out button for the header, when authentication is enabled
+#[component]
+fn SessionControls(session: SessionInfo) -> impl IntoView {
+    session.auth_enabled.then(|| {
+        let role = match session.role {
+            Role::Viewer => "Viewer",
+            Role::Operator => "Operator",
+            Role::Admin => "Admin",
+        };
+        view! {
+            <span class="role">{role}</span>
+            <button class="logout" on:click=move |_| sign_out()>"Sign out"</button>
+        }
+    })
+}
+
+#[component]
+fn LoginPage(set_session: WriteSignal<SessionState>) -> impl IntoView {
+    let (token, set_token) = signal(String::new());
+    let (login_error, set_login_error) = signal(None::<String>);
+
+    let submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        #[cfg(feature = "hydrate")]
+        {
+            let token = token.get_untracked();
+            leptos::task::spawn_local(async move {
+                match crate::iggy_client::login(token).await {
+                    Ok(Some(info)) => set_session.set(SessionState::SignedIn(info)),
+                    Ok(None) => set_login_error.set(Some("That token was not accepted.".into())),
+                    Err(e) => set_login_error.set(Some(e)),
+                }
+            });
+        }
+        #[cfg(not(feature = "hydrate"))]
+        {
+            let _ = set_session;
+            let _ = set_login_error;
+        }
+    };
+
+    view! {
+        <div class="login">
+            <form on:submit=submit>
+                <h2>"Sign in to Pidgeoneer"</h2>
+                <input
+                    type="password"
+                    placeholder="API token"
+                    autocomplete="current-password"
+                    prop:value=token
+                    on:input=move |ev| set_token.set(event_target_value(&ev))
+                />
+                <button type="submit">"Sign in"</button>
+                {move || login_error.get().map(|e| view! { <p class="login-error">{e}</p> })}
+            </form>
+        </div>
+    }
+}
+
 #[component]
 fn HomePage(
     pid_data: ReadSignal<Vec<PidControllerData>>,
     connected: ReadSignal<bool>,
+    subscription: ReadSignal<Subscription>,
+    set_subscription: WriteSignal<Subscription>,
+    session: SessionInfo,
 ) -> impl IntoView {
+    // While paused, samples keep arriving but the charts hold still
+    let (paused, set_paused) = signal(false);
+
+    // Alarms raised by the server's alert rules
+    let (alarms, set_alarms) = signal(Vec::<Alarm>::new());
+    // Whether each controller known to the server is still sending
+    let (presence, set_presence) = signal(Vec::<ControllerPresence>::new());
+    // Every controller seen so far, kept when the subscription narrows
+    let (controllers, set_controllers) = signal(std::collections::BTreeSet::<String>::new());
+    // Step responses computed by the server for the selected controller
+    let (responses, set_responses) = signal(Vec::<StepResponse>::new());
+    // Loop-rate histograms for the selected controller, or every controller
+    let (timings, set_timings) = signal(Vec::<LoopTiming>::new());
+    // Spectrum of the selected controller's error
+    let (spectrum, set_spectrum) = signal(None::<ErrorSpectrum>);
+    // Gains picked in the tuning assistant, for the tuning panel to load
+    let (proposed, set_proposed) = signal(None::<GainSuggestion>);
+    #[cfg(feature = "hydrate")]
+    {
+        let refresh = move || {
+            leptos::task::spawn_local(async move {
+                match crate::iggy_client::fetch_alarms().await {
+                    Ok(active) => set_alarms.set(active),
+                    Err(e) => log::error!("Failed to fetch alarms: {}", e),
+                }
+                match crate::iggy_client::fetch_controllers().await {
+                    Ok(known) => {
+                        let unseen: Vec<String> = {
+                            let seen = controllers.read_untracked();
+                            known
+                                .iter()
+                                .map(|c| &c.controller_id)
+                                .filter(|id| !seen.contains(*id))
+                                .cloned()
+                                .collect()
+                        };
+                        if !unseen.is_empty() {
+                            set_controllers.update(|seen| seen.extend(unseen));
+                        }
+                        set_presence.set(known);
+                    }
+                    Err(e) => log::error!("Failed to fetch controller presence: {}", e),
+                }
+                let controller_id = subscription.get_untracked().controller_ids.first().cloned();
+                match crate::iggy_client::fetch_statistics(controller_id.clone()).await {
+                    Ok(recent) => set_responses.set(recent),
+                    Err(e) => log::error!("Failed to fetch response statistics: {}", e),
+                }
+                match &controller_id {
+                    Some(controller_id) => {
+                        match crate::iggy_client::fetch_spectrum(controller_id).await {
+                            Ok(latest) => set_spectrum.set(latest),
+                            Err(e) => log::error!("Failed to fetch error spectrum: {}", e),
+                        }
+                    }
+                    None => set_spectrum.set(None),
+                }
+                match crate::iggy_client::fetch_timing(controller_id).await {
+                    Ok(timing) => set_timings.set(timing),
+                    Err(e) => log::error!("Failed to fetch loop timing: {}", e),
+                }
+            })
+        };
+        refresh();
+        if let Ok(handle) = set_interval_with_handle(refresh, STATUS_POLL_INTERVAL) {
+            on_cleanup(move || handle.clear());
+        }
+    }
+    #[cfg(not(feature = "hydrate"))]
+    let _ = (
+        set_alarms,
+        set_presence,
+        set_responses,
+        set_timings,
+        set_spectrum,
+    );
+
+    Effect::new(move |_| {
+        let data = pid_data.get();
+        let unseen: Vec<String> = {
+            let known = controllers.read_untracked();
+            data.iter()
+                .map(|d| &d.controller_id)
+                .filter(|id| !known.contains(*id))
+                .cloned()
+                .collect()
+        };
+        if !unseen.is_empty() {
+            set_controllers.update(|known| known.extend(unseen));
+        }
+    });
+    let selected_controller = move || {
+        subscription
+            .get()
+            .controller_ids
+            .first()
+            .cloned()
+            .unwrap_or_default()
+    };
+
     // Set up chart update effect (client-side only)
     #[cfg(feature = "hydrate")]
     {
-        setup_chart_functions();
+        setup_chart_functions(set_paused);
 
         leptos::prelude::Effect::new(move |_| {
+            if paused.get() {
+                return;
+            }
             let data = pid_data.get();
             if !data.is_empty() {
                 update_all_charts(&data);
@@ -311,14 +1015,86 @@ fn HomePage(
         });
     }
 
+    let can_tune = session.role >= Role::Operator;
+
     view! {
         <header>
             <h1>"Pidgeoneer"</h1>
+            <a class="nav-link" href="/fleet">"Fleet"</a>
+            <select
+                class="controller-select"
+                on:change=move |ev| {
+                    let controller_id = event_target_value(&ev);
+                    set_subscription.set(Subscription {
+                        controller_ids: if controller_id.is_empty() {
+                            Vec::new()
+                        } else {
+                            vec![controller_id]
+                        },
+                        fields: Vec::new(),
+                    });
+                }
+            >
+                <option value="" selected=move || selected_controller().is_empty()>"All controllers"</option>
+                {move || {
+                    controllers
+                        .get()
+                        .into_iter()
+                        .map(|id| {
+                            let is_selected = id == selected_controller();
+                            let label = id.clone();
+                            view! { <option value=id selected=is_selected>{label}</option> }
+                        })
+                        .collect_view()
+                }}
+            </select>
             <div class={move || if connected.get() { "status connected" } else { "status disconnected" }}>
                 {move || if connected.get() { "Connected" } else { "Disconnected" }}
             </div>
+            <SessionControls session=session/>
         </header>
 
+        // ── Controller Presence ──
+        <div class="presence">
+            {move || {
+                presence
+                    .get()
+                    .into_iter()
+                    .map(|controller| {
+                        let (class, label) = match controller.presence {
+                            Presence::Online => ("presence-dot online", "online"),
+                            Presence::Stale => ("presence-dot stale", "stale"),
+                            Presence::Offline => ("presence-dot offline", "offline"),
+                        };
+                        view! {
+                            <span class="presence-item" title=label>
+                                <span class=class></span>
+                                {controller.controller_id}
+                            </span>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+
+        // ── Active Alarms ──
+        <div class="alarms">
+            {move || {
+                alarms
+                    .get()
+                    .into_iter()
+                    .map(|alarm| {
+                        view! {
+                            <div class="alarm">
+                                <strong>{alarm.rule}</strong>
+                                " \u{00B7} "{alarm.controller_id}" \u{2014} "{alarm.message}
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+
         // ── Intro / Context ──
         <div class="intro">
             <h2>"HVAC Temperature Control Simulation"</h2>
@@ -387,8 +1163,206 @@ fn HomePage(
             }}
         </div>
 
+        // ── Loop Context (schema v2+) ──
+        <div class="loop-context">
+            {move || {
+                let data = pid_data.get();
+                match data.last().filter(|d| d.has_loop_context()) {
+                    Some(d) => {
+                        let (saturation_class, saturation) = if d.saturated {
+                            ("saturated", "Saturated")
+                        } else {
+                            ("", "Within limits")
+                        };
+                        view! {
+                            <span>"Controller "<strong>{d.controller_id.clone()}</strong></span>
+                            <span>"Gains "<strong>{format!("Kp {:.3} \u{00B7} Ki {:.3} \u{00B7} Kd {:.3}", d.kp, d.ki, d.kd)}</strong></span>
+                            <span>"dt "<strong>{format!("{:.1} ms", d.dt * 1000.0)}</strong></span>
+                            <span>"Mode "<strong>{d.mode.clone()}</strong></span>
+                            <span>"Output "<strong class=saturation_class>{saturation}</strong></span>
+                        }
+                            .into_any()
+                    }
+                    None => view! {
+                        <span>"Gains, dt, mode, and saturation appear once the controller sends schema v2 samples."</span>
+                    }
+                        .into_any(),
+                }
+            }}
+        </div>
+
+        // ── Step Responses ──
+        <div class="responses">
+            {move || {
+                let recent = responses.get();
+                if recent.is_empty() {
+                    return None;
+                }
+                let seconds = |value: Option<f64>| value.map_or("--".to_string(), |s| format!("{:.2} s", s));
+                let rows = recent
+                    .into_iter()
+                    .rev()
+                    .take(RESPONSES_SHOWN)
+                    .map(|r| {
+                        view! {
+                            <tr>
+                                <td>{r.controller_id}</td>
+                                <td>{format!("{:.1} \u{2192} {:.1}", r.from_setpoint, r.setpoint)}</td>
+                                <td>{seconds(r.rise_time)}</td>
+                                <td>{format!("{:.2}", r.max_overshoot)}</td>
+                                <td>{seconds(r.settling_time)}</td>
+                                <td>{format!("{:.2}", r.iae)}</td>
+                                <td>{format!("{:.0}%", r.saturation_duty * 100.0)}</td>
+                                <td>{if r.complete { "" } else { "in progress" }}</td>
+                            </tr>
+                        }
+                    })
+                    .collect_view();
+                Some(view! {
+                    <table>
+                        <thead>
+                            <tr>
+                                <th>"Controller"</th>
+                                <th>"Setpoint"</th>
+                                <th>"Rise"</th>
+                                <th>"Overshoot"</th>
+                                <th>"Settling"</th>
+                                <th>"IAE"</th>
+                                <th>"Saturated"</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>{rows}</tbody>
+                    </table>
+                })
+            }}
+        </div>
+
+        // ── Loop Timing ──
+        <div class="timing">
+            {move || {
+                timings
+                    .get()
+                    .into_iter()
+                    .map(|timing| {
+                        let millis = |seconds: f64| format!("{:.2} ms", seconds * 1000.0);
+                        let tallest = timing.histogram.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+                        let bars = timing
+                            .histogram
+                            .iter()
+                            .map(|bucket| {
+                                let height = format!("height: {:.0}%", bucket.count as f64 * 100.0 / tallest as f64);
+                                let title = format!(
+                                    "{:.2}\u{2013}{:.2} ms: {}",
+                                    bucket.from * 1000.0,
+                                    bucket.to * 1000.0,
+                                    bucket.count
+                                );
+                                view! { <div class="histogram-bar" style=height title=title></div> }
+                            })
+                            .collect_view();
+                        let missed_class = if timing.missed_deadlines > 0 { "missed" } else { "" };
+                        view! {
+                            <div class="timing-card">
+                                <h3>{format!("Loop timing \u{00B7} {}", timing.controller_id)}</h3>
+                                <div class="timing-stats">
+                                    <span>"Rate "<strong>{format!("{:.1} Hz", 1.0 / timing.nominal_dt)}</strong></span>
+                                    <span>"dt "<strong>{millis(timing.nominal_dt)}</strong></span>
+                                    <span>"Jitter "<strong>{millis(timing.jitter)}</strong></span>
+                                    <span>"Missed deadlines "<strong class=missed_class>{timing.missed_deadlines}</strong></span>
+                                </div>
+                                <div class="histogram">{bars}</div>
+                                <div class="histogram-range">
+                                    <span>{millis(timing.min_dt)}</span>
+                                    <span>{millis(timing.max_dt)}</span>
+                                </div>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+        </div>
+
+        // ── Error Spectrum ──
+        <div class="chart-panel spectrum">
+            <div class="chart-header">
+                <h2>"Error Spectrum"</h2>
+                <span class="chart-hint">
+                    {move || spectrum.get().map(|s| format!("Peak at {:.3} Hz", s.peak_frequency))}
+                </span>
+            </div>
+            <p class="chart-desc">
+                "Frequency content of the recent error. A sharp peak means the loop is oscillating at that "
+                "frequency; a raised floor toward the right edge is measurement noise the D-term amplifies."
+            </p>
+            {move || match spectrum.get() {
+                Some(spectrum) => {
+                    let tallest = spectrum
+                        .bins
+                        .iter()
+                        .map(|bin| bin.amplitude)
+                        .fold(0.0, f64::max)
+                        .max(f64::MIN_POSITIVE);
+                    let points = spectrum
+                        .bins
+                        .iter()
+                        .enumerate()
+                        .map(|(i, bin)| format!("{},{:.2}", i, 100.0 - bin.amplitude / tallest * 100.0))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    let view_box = format!("0 0 {} 100", spectrum.bins.len().saturating_sub(1).max(1));
+                    let lowest = spectrum.bins.first().map_or(0.0, |bin| bin.frequency);
+                    view! {
+                        <svg viewBox=view_box preserveAspectRatio="none">
+                            <polyline points=points vector-effect="non-scaling-stroke"></polyline>
+                        </svg>
+                        <div class="histogram-range">
+                            <span>{format!("{:.3} Hz", lowest)}</span>
+                            <span>{format!("{:.1} Hz (Nyquist)", spectrum.sample_rate / 2.0)}</span>
+                        </div>
+                    }
+                        .into_any()
+                }
+                None if selected_controller().is_empty() => view! {
+                    <p class="chart-hint">"Select a controller to see its error spectrum."</p>
+                }
+                    .into_any(),
+                None => view! { <p class="chart-hint">"Waiting for enough samples\u{2026}"</p> }.into_any(),
+            }}
+        </div>
+
+        // ── Tuning Assistant ──
+        <TuningAssistant
+            controller_id=Signal::derive(selected_controller)
+            can_tune=can_tune
+            set_proposed=set_proposed
+        />
+
+        // ── Remote Tuning ──
+        <TuningPanel
+            pid_data=pid_data
+            controller_id=Signal::derive(selected_controller)
+            can_tune=can_tune
+            proposed=proposed
+        />
+
         // ── Charts ──
         <div class="charts">
+            <div class="chart-controls">
+                <button
+                    class=move || if paused.get() { "paused" } else { "" }
+                    on:click=move |_| {
+                        if paused.get_untracked() {
+                            reset_chart_zoom();
+                        }
+                        set_paused.update(|paused| *paused = !*paused);
+                    }
+                >
+                    {move || if paused.get() { "Resume live" } else { "Pause" }}
+                </button>
+                <button on:click=move |_| reset_chart_zoom()>"Reset zoom"</button>
+                <span class="chart-hint">"Scroll or pinch to zoom, drag to pan. Zooming pauses the live view."</span>
+            </div>
             <div class="chart-panel">
                 <div class="chart-header">
                     <h2>"Process Value & Setpoint"</h2>
@@ -449,11 +1423,636 @@ fn HomePage(
     }
 }
 
+/// Column the fleet table is sorted by
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FleetColumn {
+    Controller,
+    Status,
+    Error,
+    Saturation,
+    Health,
+}
+
+/// Every controller in one sortable table; click a row to open its dashboard
+#[component]
+fn FleetPage(set_subscription: WriteSignal<Subscription>, session: SessionInfo) -> impl IntoView {
+    let (fleet, set_fleet) = signal(Vec::<FleetEntry>::new());
+    // Lowest health first, so the loops needing attention are on top
+    let (sort, set_sort) = signal((FleetColumn::Health, false));
+
+    #[cfg(feature = "hydrate")]
+    {
+        let refresh = move || {
+            leptos::task::spawn_local(async move {
+                match crate::iggy_client::fetch_fleet().await {
+                    Ok(entries) => set_fleet.set(entries),
+                    Err(e) => log::error!("Failed to fetch fleet overview: {}", e),
+                }
+            })
+        };
+        refresh();
+        if let Ok(handle) = set_interval_with_handle(refresh, STATUS_POLL_INTERVAL) {
+            on_cleanup(move || handle.clear());
+        }
+    }
+    #[cfg(not(feature = "hydrate"))]
+    let _ = set_fleet;
+
+    let sort_by = move |column: FleetColumn| {
+        set_sort.update(|(current, descending)| {
+            if *current == column {
+                *descending = !*descending;
+            } else {
+                *current = column;
+                *descending = false;
+            }
+        })
+    };
+    let heading = move |column: FleetColumn, label: &'static str| {
+        view! {
+            <th on:click=move |_| sort_by(column)>
+                {label}
+                {move || {
+                    let (current, descending) = sort.get();
+                    (current == column).then_some(if descending { " \u{25BC}" } else { " \u{25B2}" })
+                }}
+            </th>
+        }
+    };
+
+    let navigate = StoredValue::new_local(use_navigate());
+    let open = move |controller_id: String| {
+        set_subscription.set(Subscription {
+            controller_ids: vec![controller_id],
+            fields: Vec::new(),
+        });
+        navigate.with_value(|navigate| navigate("/", Default::default()));
+    };
+
+    let sorted = move || {
+        let (column, descending) = sort.get();
+        let mut entries = fleet.get();
+        entries.sort_by(|a, b| {
+            let ordering = match column {
+                FleetColumn::Controller => a.controller_id.cmp(&b.controller_id),
+                FleetColumn::Status => presence_rank(a.presence).cmp(&presence_rank(b.presence)),
+                FleetColumn::Error => compare_optional(a.mean_abs_error, b.mean_abs_error),
+                FleetColumn::Saturation => compare_optional(a.saturation_duty, b.saturation_duty),
+                FleetColumn::Health => a.health.total_cmp(&b.health),
+            };
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        entries
+    };
+
+    view! {
+        <header>
+            <h1>"Pidgeoneer"</h1>
+            <a class="nav-link" href="/">"Dashboard"</a>
+            <SessionControls session=session/>
+        </header>
+
+        <div class="fleet">
+            <p class="fleet-summary">
+                {move || {
+                    let entries = fleet.get();
+                    let online = entries.iter().filter(|e| e.presence == Presence::Online).count();
+                    let attention = entries.iter().filter(|e| e.health < UNHEALTHY_BELOW).count();
+                    format!(
+                        "{} controllers \u{00B7} {} online \u{00B7} {} need attention",
+                        entries.len(),
+                        online,
+                        attention
+                    )
+                }}
+            </p>
+            <table>
+                <thead>
+                    <tr>
+                        {heading(FleetColumn::Status, "Status")}
+                        {heading(FleetColumn::Controller, "Controller")}
+                        <th>"Setpoint"</th>
+                        <th>"Process value"</th>
+                        <th>"Error"</th>
+                        {heading(FleetColumn::Error, "Mean |error|")}
+                        {heading(FleetColumn::Saturation, "Saturated")}
+                        <th>"Alarms"</th>
+                        {heading(FleetColumn::Health, "Health")}
+                    </tr>
+                </thead>
+                <tbody>
+                    {move || {
+                        let value = |value: Option<f64>, precision: usize| {
+                            value.map_or("--".to_string(), |v| format!("{:.*}", precision, v))
+                        };
+                        sorted()
+                            .into_iter()
+                            .map(|entry| {
+                                let (dot, status) = match entry.presence {
+                                    Presence::Online => ("presence-dot online", "online"),
+                                    Presence::Stale => ("presence-dot stale", "stale"),
+                                    Presence::Offline => ("presence-dot offline", "offline"),
+                                };
+                                let health_class = if entry.health >= HEALTHY_FROM {
+                                    "healthy"
+                                } else if entry.health >= UNHEALTHY_BELOW {
+                                    "degraded"
+                                } else {
+                                    "unhealthy"
+                                };
+                                let controller_id = entry.controller_id.clone();
+                                view! {
+                                    <tr on:click=move |_| open(controller_id.clone())>
+                                        <td>
+                                            <span class="presence-item">
+                                                <span class=dot></span>
+                                                {status}
+                                            </span>
+                                        </td>
+                                        <td>{entry.controller_id}</td>
+                                        <td>{value(entry.setpoint, 2)}</td>
+                                        <td>{value(entry.process_value, 2)}</td>
+                                        <td>{value(entry.error, 2)}</td>
+                                        <td>{value(entry.mean_abs_error, 3)}</td>
+                                        <td>{value(entry.saturation_duty.map(|d| d * 100.0), 0)}"%"</td>
+                                        <td>{entry.alarms}</td>
+                                        <td class=health_class>{format!("{:.0}", entry.health)}</td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+/// Order in which presence sorts, healthiest first
+fn presence_rank(presence: Presence) -> u8 {
+    match presence {
+        Presence::Online => 0,
+        Presence::Stale => 1,
+        Presence::Offline => 2,
+    }
+}
+
+/// Compare optional values, with missing ones first
+fn compare_optional(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+/// Identifies the selected controller's process from its recorded history and
+/// previews the gains each tuning rule proposes for it
+#[component]
+fn TuningAssistant(
+    controller_id: Signal<String>,
+    can_tune: bool,
+    set_proposed: WriteSignal<Option<GainSuggestion>>,
+) -> impl IntoView {
+    let (analysis, set_analysis) = signal(None::<Result<TuningSuggestions, String>>);
+    let (running, set_running) = signal(false);
+
+    // A result only describes the controller it was computed for
+    Effect::new(move |_| {
+        controller_id.track();
+        set_analysis.set(None);
+    });
+
+    let analyze = move |_| {
+        #[cfg(feature = "hydrate")]
+        {
+            let id = controller_id.get_untracked();
+            set_running.set(true);
+            leptos::task::spawn_local(async move {
+                set_analysis.set(Some(crate::iggy_client::fetch_suggestions(&id).await));
+                set_running.set(false);
+            });
+        }
+    };
+    #[cfg(not(feature = "hydrate"))]
+    let _ = set_running;
+
+    let results = move || match analysis.get() {
+        None => None,
+        Some(Err(e)) => Some(view! { <p class="tuning-note">{e}</p> }.into_any()),
+        Some(Ok(analysis)) => {
+            let candidates: Vec<(&'static str, GainSuggestion)> = analysis
+                .current
+                .into_iter()
+                .map(|current| ("#888", current))
+                .chain(
+                    SUGGESTION_COLORS
+                        .iter()
+                        .copied()
+                        .cycle()
+                        .zip(analysis.suggestions),
+                )
+                .collect();
+            let duration = candidates
+                .iter()
+                .filter_map(|(_, s)| s.response.last().map(|(time, _)| *time))
+                .fold(f64::MIN_POSITIVE, f64::max);
+            let highest = candidates
+                .iter()
+                .flat_map(|(_, s)| s.response.iter().map(|(_, value)| *value))
+                .fold(1.2, f64::max);
+            let lines = candidates
+                .iter()
+                .map(|(color, s)| {
+                    let points = s
+                        .response
+                        .iter()
+                        .map(|(time, value)| {
+                            format!(
+                                "{:.2},{:.2}",
+                                time / duration * 100.0,
+                                100.0 - value / highest * 100.0
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    let dashed = if s.rule == "current" { "4 3" } else { "" };
+                    view! {
+                        <polyline
+                            points=points
+                            stroke=*color
+                            stroke-dasharray=dashed
+                            vector-effect="non-scaling-stroke"
+                        ></polyline>
+                    }
+                })
+                .collect_view();
+            let setpoint = format!("{:.2}", 100.0 - 100.0 / highest);
+            let rows = candidates
+                .into_iter()
+                .map(|(color, s)| {
+                    let settling = s.settling_time.map_or("never".to_string(), |t| format!("{:.2} s", t));
+                    let use_button = (can_tune && s.rule != "current").then(|| {
+                        let proposal = s.clone();
+                        view! {
+                            <button on:click=move |_| set_proposed.set(Some(proposal.clone()))>"Use"</button>
+                        }
+                    });
+                    view! {
+                        <tr>
+                            <td><span class="swatch" style=format!("background: {}", color)></span>{s.rule.clone()}</td>
+                            <td>{format!("{:.4}", s.kp)}</td>
+                            <td>{format!("{:.4}", s.ki)}</td>
+                            <td>{format!("{:.4}", s.kd)}</td>
+                            <td>{format!("{:.0}%", s.overshoot * 100.0)}</td>
+                            <td>{settling}</td>
+                            <td>{use_button}</td>
+                        </tr>
+                    }
+                })
+                .collect_view();
+            let model = analysis.model;
+            Some(
+                view! {
+                    <div class="timing-stats">
+                        <span>"Gain "<strong>{format!("{:.3}", model.gain)}</strong></span>
+                        <span>"Time constant "<strong>{format!("{:.2} s", model.time_constant)}</strong></span>
+                        <span>"Dead time "<strong>{format!("{:.2} s", model.dead_time)}</strong></span>
+                        <span>"Fit "<strong>{format!("{:.0}%", analysis.fit * 100.0)}</strong></span>
+                        <span>"From "<strong>{analysis.samples}</strong>" samples"</span>
+                    </div>
+                    {(analysis.fit < POOR_FIT).then(|| view! {
+                        <p class="tuning-note">
+                            "The model explains little of the recording. Step the setpoint or output, "
+                            "let the process settle, and analyze again."
+                        </p>
+                    })}
+                    <svg viewBox="0 0 100 100" preserveAspectRatio="none">
+                        <line class="setpoint" x1="0" x2="100" y1=setpoint.clone() y2=setpoint vector-effect="non-scaling-stroke"></line>
+                        {lines}
+                    </svg>
+                    <div class="histogram-range">
+                        <span>"0 s"</span>
+                        <span>{format!("{:.1} s after a unit setpoint step", duration)}</span>
+                    </div>
+                    <table>
+                        <thead>
+                            <tr>
+                                <th>"Rule"</th>
+                                <th>"Kp"</th>
+                                <th>"Ki"</th>
+                                <th>"Kd"</th>
+                                <th>"Overshoot"</th>
+                                <th>"Settling"</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>{rows}</tbody>
+                    </table>
+                }
+                    .into_any(),
+            )
+        }
+    };
+
+    view! {
+        <div class="chart-panel assistant">
+            <div class="chart-header">
+                <h2>"Tuning Assistant"</h2>
+                <button
+                    disabled=move || running.get() || controller_id.get().is_empty()
+                    on:click=analyze
+                >
+                    {move || if running.get() { "Analyzing\u{2026}" } else { "Suggest gains" }}
+                </button>
+            </div>
+            <p class="chart-desc">
+                "Fits a first-order-plus-dead-time model to the selected controller's recorded output and "
+                "process value, then previews the gains each tuning rule proposes. Dashed is the current tuning."
+            </p>
+            {move || {
+                if controller_id.get().is_empty() {
+                    Some(view! { <p class="chart-hint">"Select a controller to analyze its history."</p> }.into_any())
+                } else {
+                    results()
+                }
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn TuningPanel(
+    pid_data: ReadSignal<Vec<PidControllerData>>,
+    controller_id: Signal<String>,
+    can_tune: bool,
+    proposed: ReadSignal<Option<GainSuggestion>>,
+) -> impl IntoView {
+    let (log, set_log) = signal(CommandLog::default());
+    let (setpoint, set_setpoint) = signal(String::new());
+    let (kp, set_kp) = signal(String::new());
+    let (ki, set_ki) = signal(String::new());
+    let (kd, set_kd) = signal(String::new());
+    let (note, set_note) = signal(None::<String>);
+
+    #[cfg(feature = "hydrate")]
+    {
+        let refresh = move || {
+            leptos::task::spawn_local(async move {
+                match crate::iggy_client::fetch_commands().await {
+                    Ok(commands) => set_log.set(commands),
+                    Err(e) => log::error!("Failed to fetch remote commands: {}", e),
+                }
+            })
+        };
+        refresh();
+        if let Ok(handle) = set_interval_with_handle(refresh, STATUS_POLL_INTERVAL) {
+            on_cleanup(move || handle.clear());
+        }
+    }
+
+    // Start from the controller's current values
+    let load_current = move |_| {
+        let id = controller_id.get_untracked();
+        let data = pid_data.get_untracked();
+        if let Some(d) = data.iter().rev().find(|d| d.controller_id == id) {
+            set_setpoint.set(d.setpoint.to_string());
+            set_kp.set(d.kp.to_string());
+            set_ki.set(d.ki.to_string());
+            set_kd.set(d.kd.to_string());
+        }
+    };
+
+    // Fill in gains picked in the tuning assistant, for review before sending
+    Effect::new(move |_| {
+        if let Some(gains) = proposed.get() {
+            set_kp.set(gains.kp.to_string());
+            set_ki.set(gains.ki.to_string());
+            set_kd.set(gains.kd.to_string());
+            set_note.set(Some(format!(
+                "Loaded the {} gains; review them and Send",
+                gains.rule
+            )));
+        }
+    });
+
+    let send = move |_| {
+        let request = parse_optional("setpoint", &setpoint.get_untracked()).and_then(|setpoint| {
+            Ok(TuningRequest {
+                controller_id: controller_id.get_untracked(),
+                setpoint,
+                kp: parse_optional("Kp", &kp.get_untracked())?,
+                ki: parse_optional("Ki", &ki.get_untracked())?,
+                kd: parse_optional("Kd", &kd.get_untracked())?,
+            })
+        });
+        match request {
+            Ok(request) => submit_tuning(request, set_log, set_note),
+            Err(e) => set_note.set(Some(e)),
+        }
+    };
+
+    let tuning_field =
+        move |label: &'static str, value: ReadSignal<String>, set_value: WriteSignal<String>| {
+            view! {
+                <label>
+                    {label}
+                    <input
+                        type="number"
+                        step="any"
+                        placeholder="unchanged"
+                        disabled=!can_tune
+                        prop:value=value
+                        on:input=move |ev| set_value.set(event_target_value(&ev))
+                    />
+                </label>
+            }
+        };
+
+    view! {
+        <Show when=move || log.get().enabled>
+            <div class="tuning">
+                <div class="chart-header">
+                    <h2>"Remote Tuning"</h2>
+                    <span class="chart-hint">
+                        {move || {
+                            if !can_tune {
+                                "Operators only".to_string()
+                            } else if controller_id.get().is_empty() {
+                                "Pick a controller in the header to tune it".to_string()
+                            } else {
+                                format!("Signed commands to {}", controller_id.get())
+                            }
+                        }}
+                    </span>
+                </div>
+                <div class="tuning-form">
+                    {tuning_field("Setpoint", setpoint, set_setpoint)}
+                    {tuning_field("Kp", kp, set_kp)}
+                    {tuning_field("Ki", ki, set_ki)}
+                    {tuning_field("Kd", kd, set_kd)}
+                    <button
+                        disabled=move || !can_tune || controller_id.get().is_empty()
+                        on:click=load_current
+                    >
+                        "Load current"
+                    </button>
+                    <button
+                        class="send"
+                        disabled=move || !can_tune || controller_id.get().is_empty()
+                        on:click=send
+                    >
+                        "Send"
+                    </button>
+                </div>
+                {move || note.get().map(|note| view! { <p class="tuning-note">{note}</p> })}
+                <table class="audit-log">
+                    <tbody>
+                        {move || {
+                            log.get()
+                                .commands
+                                .into_iter()
+                                .take(COMMANDS_SHOWN)
+                                .map(|command| {
+                                    let (class, status) = match &command.status {
+                                        CommandStatus::Pending => ("pending", "pending".to_string()),
+                                        CommandStatus::Applied => ("applied", "applied".to_string()),
+                                        CommandStatus::Rejected { reason } => {
+                                            ("rejected", format!("rejected: {}", reason))
+                                        }
+                                    };
+                                    view! {
+                                        <tr>
+                                            <td>{clock_time(command.issued_at)}</td>
+                                            <td>{command.controller_id}</td>
+                                            <td>{command.action}</td>
+                                            <td>{command.issued_by}</td>
+                                            <td class=class>{status}</td>
+                                        </tr>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                    </tbody>
+                </table>
+            </div>
+        </Show>
+    }
+}
+
+/// Parse a tuning field, treating an empty field as "leave unchanged"
+fn parse_optional(name: &str, text: &str) -> Result<Option<f64>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(Some(value)),
+        _ => Err(format!("{} must be a number", name)),
+    }
+}
+
+/// `HH:MM:SS` UTC of a millisecond epoch timestamp
+fn clock_time(millis: u64) -> String {
+    let secs = (millis / 1000) % 86_400;
+    format!("{:02}:{:02}:{:02}Z", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Confirm `request` with the operator, then send it and refresh the log
+fn submit_tuning(
+    request: TuningRequest,
+    set_log: WriteSignal<CommandLog>,
+    set_note: WriteSignal<Option<String>>,
+) {
+    #[cfg(feature = "hydrate")]
+    {
+        let changes: Vec<String> = [
+            ("setpoint", request.setpoint),
+            ("Kp", request.kp),
+            ("Ki", request.ki),
+            ("Kd", request.kd),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| format!("{} = {}", name, value)))
+        .collect();
+        if changes.is_empty() {
+            set_note.set(Some("Enter at least one value to change.".into()));
+            return;
+        }
+        let question = format!(
+            "Send to {}?\n\n{}",
+            request.controller_id,
+            changes.join("\n")
+        );
+        let confirmed = web_sys::window()
+            .and_then(|window| window.confirm_with_message(&question).ok())
+            .unwrap_or(false);
+        if !confirmed {
+            return;
+        }
+        leptos::task::spawn_local(async move {
+            match crate::iggy_client::send_tuning(&request).await {
+                Ok(issued) => {
+                    set_note.set(Some(format!(
+                        "Sent {} command(s); waiting for the controller to apply them.",
+                        issued.len()
+                    )));
+                    if let Ok(commands) = crate::iggy_client::fetch_commands().await {
+                        set_log.set(commands);
+                    }
+                }
+                Err(e) => set_note.set(Some(e)),
+            }
+        });
+    }
+    #[cfg(not(feature = "hydrate"))]
+    let _ = (request, set_log, set_note);
+}
+
+/// End the session and return to the login page
+fn sign_out() {
+    #[cfg(feature = "hydrate")]
+    leptos::task::spawn_local(async {
+        if let Err(e) = crate::iggy_client::logout().await {
+            log::error!("Failed to sign out: {}", e);
+        }
+        if let Some(window) = web_sys::window() {
+            let _ = window.location().reload();
+        }
+    });
+}
+
+/// Reset every chart to show the full buffered window
+fn reset_chart_zoom() {
+    #[cfg(feature = "hydrate")]
+    let _ = js_sys::eval("window.__pidgeoneerResetZoom && window.__pidgeoneerResetZoom()");
+}
+
 /// Register a global JS function that creates/updates all charts.
 /// Called once at startup. The function handles lazy chart creation.
+/// Panning or zooming any chart sets `set_paused` so live updates don't
+/// yank the view away.
 #[cfg(feature = "hydrate")]
-fn setup_chart_functions() {
+fn setup_chart_functions(set_paused: WriteSignal<bool>) {
+    use wasm_bindgen::prelude::*;
+
+    let pause = Closure::<dyn Fn()>::new(move || set_paused.set(true));
+    if let Some(window) = web_sys::window() {
+        let _ = js_sys::Reflect::set(&window, &"__pidgeoneerPause".into(), pause.as_ref());
+    }
+    pause.forget();
+
     let js = r#"
+window.__pidgeoneerResetZoom = function() {
+    if (!window.__charts) return;
+    for (var id in window.__charts) {
+        if (window.__charts[id].resetZoom) window.__charts[id].resetZoom('none');
+    }
+};
+
 window.__pidgeoneerUpdate = function(labels, pv, sp, error, output, pTerm, iTerm, dTerm) {
     if (typeof Chart === 'undefined') return;
     if (!window.__charts) window.__charts = {};
@@ -461,6 +2060,21 @@ window.__pidgeoneerUpdate = function(labels, pv, sp, error, output, pTerm, iTerm
     var gridColor = 'rgba(255,255,255,0.06)';
     var tickColor = '#666';
 
+    function pauseLive() {
+        if (window.__pidgeoneerPause) window.__pidgeoneerPause();
+        return true;
+    }
+
+    var zoomOptions = {
+        pan: { enabled: true, mode: 'x', onPanStart: pauseLive },
+        zoom: {
+            wheel: { enabled: true },
+            pinch: { enabled: true },
+            mode: 'x',
+            onZoomStart: pauseLive
+        }
+    };
+
     function ensure(id, cfg) {
         var el = document.getElementById(id);
         if (!el) return null;
@@ -507,7 +2121,8 @@ window.__pidgeoneerUpdate = function(labels, pv, sp, error, output, pTerm, iTerm
             animation: false,
             interaction: { mode: 'index', intersect: false },
             plugins: {
-                legend: { labels: { color: '#ccc', usePointStyle: true, pointStyle: 'line' } }
+                legend: { labels: { color: '#ccc', usePointStyle: true, pointStyle: 'line' } },
+                zoom: zoomOptions
             },
             scales: {
                 x: baseScales.x,
@@ -543,7 +2158,8 @@ window.__pidgeoneerUpdate = function(labels, pv, sp, error, output, pTerm, iTerm
             animation: false,
             interaction: { mode: 'index', intersect: false },
             plugins: {
-                legend: { labels: { color: '#ccc', usePointStyle: true, pointStyle: 'line' } }
+                legend: { labels: { color: '#ccc', usePointStyle: true, pointStyle: 'line' } },
+                zoom: zoomOptions
             },
             scales: {
                 x: baseScales.x,
@@ -574,7 +2190,8 @@ window.__pidgeoneerUpdate = function(labels, pv, sp, error, output, pTerm, iTerm
             animation: false,
             interaction: { mode: 'index', intersect: false },
             plugins: {
-                legend: { labels: { color: '#ccc', usePointStyle: true, pointStyle: 'line' } }
+                legend: { labels: { color: '#ccc', usePointStyle: true, pointStyle: 'line' } },
+                zoom: zoomOptions
             },
             scales: {
                 x: baseScales.x,
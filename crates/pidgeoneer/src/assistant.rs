@@ -0,0 +1,193 @@
+use crate::models::{GainSuggestion, PidControllerData, ProcessModel, TuningSuggestions};
+use crate::storage::{HistoryQuery, Storage};
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use pidgeon::{ControllerConfig, FopdtModel, PidGains, TuningRule};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Newest recorded samples a model is identified from
+const MAX_SAMPLES: usize = 5_000;
+/// Predicted responses run for this many of the model's `τ + θ`
+const HORIZON: f64 = 10.0;
+/// Most simulation steps per predicted response
+const MAX_STEPS: usize = 20_000;
+/// Points of each predicted response sent to the browser
+const RESPONSE_POINTS: usize = 200;
+/// Band around the setpoint a response has settled into
+const SETTLING_BAND: f64 = 0.02;
+/// Output limits wide enough that the preview never saturates
+const UNCONSTRAINED: f64 = 1e9;
+
+/// Which recording to analyze
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SuggestionQuery {
+    pub controller_id: Option<String>,
+    /// Earliest timestamp to use
+    pub from: Option<u64>,
+    /// Latest timestamp to use
+    pub to: Option<u64>,
+}
+
+/// Identify a process model from `samples` and propose gains for it
+///
+/// Fits a first-order-plus-dead-time model to the recorded output and
+/// process value with [`FopdtModel::identify`], assuming evenly spaced
+/// samples at the median `dt` (or, before schema v2, the median timestamp
+/// step). Every [`TuningRule`] the model supports then proposes gains, and
+/// each is previewed, along with the controller's current gains when the
+/// samples carry them, by simulating a unit setpoint step on the model.
+pub fn suggest(
+    controller_id: &str,
+    samples: &[PidControllerData],
+) -> Result<TuningSuggestions, String> {
+    let dt = sample_period(samples).ok_or("the recording has no usable sample period")?;
+    let outputs: Vec<f64> = samples.iter().map(|s| s.output).collect();
+    let process_values: Vec<f64> = samples.iter().map(|s| s.process_value).collect();
+    let identification = FopdtModel::identify(&outputs, &process_values, dt)
+        .map_err(|e| format!("could not identify a process model: {}", e))?;
+    let model = identification.model;
+
+    let suggestions = TuningRule::ALL
+        .iter()
+        .filter_map(|rule| {
+            let gains = rule.gains(&model).ok()?;
+            preview(&model, rule.name(), gains, dt)
+        })
+        .collect();
+    let current = samples
+        .last()
+        .filter(|latest| latest.has_loop_context())
+        .and_then(|latest| {
+            let gains = PidGains {
+                kp: latest.kp,
+                ki: latest.ki,
+                kd: latest.kd,
+            };
+            preview(&model, "current", gains, dt)
+        });
+
+    Ok(TuningSuggestions {
+        controller_id: controller_id.to_string(),
+        samples: samples.len(),
+        dt,
+        model: ProcessModel {
+            gain: model.gain,
+            time_constant: model.time_constant,
+            dead_time: model.dead_time,
+        },
+        fit: identification.fit,
+        current,
+        suggestions,
+    })
+}
+
+/// Median `dt` of `samples`, falling back to the median timestamp step
+fn sample_period(samples: &[PidControllerData]) -> Option<f64> {
+    let mut steps: Vec<f64> = samples
+        .iter()
+        .map(|s| s.dt)
+        .filter(|dt| *dt > 0.0)
+        .collect();
+    if steps.len() < samples.len() / 2 {
+        steps = samples
+            .windows(2)
+            .map(|pair| pair[1].timestamp.saturating_sub(pair[0].timestamp) as f64 / 1000.0)
+            .filter(|step| *step > 0.0)
+            .collect();
+    }
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort_by(f64::total_cmp);
+    Some(steps[steps.len() / 2])
+}
+
+/// Simulate `gains` following a unit setpoint step, or `None` if the
+/// response diverges
+fn preview(model: &FopdtModel, rule: &str, gains: PidGains, dt: f64) -> Option<GainSuggestion> {
+    let horizon = HORIZON * (model.time_constant + model.dead_time);
+    let dt = dt.max(horizon / MAX_STEPS as f64);
+    let steps = (horizon / dt).ceil() as usize;
+    let config = ControllerConfig::builder()
+        .with_kp(gains.kp)
+        .with_ki(gains.ki)
+        .with_kd(gains.kd)
+        .with_setpoint(1.0)
+        .with_output_limits(-UNCONSTRAINED, UNCONSTRAINED)
+        .build()
+        .ok()?;
+    let response = model.simulate(&config, 0.0, dt, steps).ok()?;
+    if response
+        .iter()
+        .any(|point| !point.process_value.is_finite())
+    {
+        return None;
+    }
+
+    let peak = response
+        .iter()
+        .map(|point| point.process_value)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let settling_time = match response
+        .iter()
+        .rposition(|point| (point.process_value - 1.0).abs() > SETTLING_BAND)
+    {
+        None => Some(0.0),
+        Some(last) if last + 1 < response.len() => Some(response[last + 1].time),
+        Some(_) => None,
+    };
+    let stride = response.len().div_ceil(RESPONSE_POINTS).max(1);
+
+    Some(GainSuggestion {
+        rule: rule.to_string(),
+        kp: gains.kp,
+        ki: gains.ki,
+        kd: gains.kd,
+        overshoot: (peak - 1.0).max(0.0),
+        settling_time,
+        response: response
+            .iter()
+            .step_by(stride)
+            .map(|point| (point.time, point.process_value))
+            .collect(),
+    })
+}
+
+/// `GET /api/suggestions?controller_id=&from=&to=` — gains proposed from
+/// recorded data, with predicted step responses
+pub async fn suggestions_handler(
+    storage: Arc<Storage>,
+    Query(query): Query<SuggestionQuery>,
+) -> Response {
+    let Some(controller_id) = query.controller_id else {
+        return (StatusCode::BAD_REQUEST, "controller_id is required").into_response();
+    };
+    let history = HistoryQuery {
+        controller_id: Some(controller_id.clone()),
+        from: query.from,
+        to: query.to,
+        limit: Some(MAX_SAMPLES),
+    };
+    let samples = match storage.history(&history).await {
+        Ok(samples) => samples,
+        Err(e) => {
+            error!("History query failed: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    // Identification fits a model for every dead time up to a third of the
+    // recording; keep it off the async workers
+    match tokio::task::spawn_blocking(move || suggest(&controller_id, &samples)).await {
+        Ok(Ok(suggestions)) => Json(suggestions).into_response(),
+        Ok(Err(reason)) => (StatusCode::UNPROCESSABLE_ENTITY, reason).into_response(),
+        Err(e) => {
+            error!("Tuning analysis failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::config::ApiToken;
+use crate::models::{LoginRequest, Role, SessionInfo};
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use log::*;
+use rand::RngCore;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cookie holding the browser's session id
+pub const SESSION_COOKIE: &str = "pidgeoneer_session";
+
+struct Session {
+    role: Role,
+    expires: Instant,
+}
+
+/// Token authentication and browser sessions
+///
+/// Browsers exchange a token for a session cookie at `POST /api/login`;
+/// scripts may instead send `Authorization: Bearer <token>` on every request.
+/// With no tokens configured, authentication is disabled and every request
+/// is treated as a viewer, or as an admin only if
+/// [`with_insecure_no_auth`](Self::with_insecure_no_auth) says so.
+pub struct Auth {
+    tokens: Vec<ApiToken>,
+    /// Role of every request while no tokens are configured
+    open_role: Role,
+    session_ttl: Duration,
+    secure_cookies: bool,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Auth {
+    /// Create an authenticator accepting `tokens`, with sessions that expire
+    /// after `session_ttl` of inactivity
+    pub fn new(tokens: Vec<ApiToken>, session_ttl: Duration) -> Self {
+        if tokens.is_empty() {
+            warn!("⚠️ No API tokens configured; anyone who can reach the dashboard can watch it");
+        }
+        Self {
+            tokens,
+            open_role: Role::Viewer,
+            session_ttl,
+            secure_cookies: false,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Mark session cookies `Secure` so browsers only send them over HTTPS
+    pub fn with_secure_cookies(mut self, secure_cookies: bool) -> Self {
+        self.secure_cookies = secure_cookies;
+        self
+    }
+
+    /// With no tokens configured, treat every request as an admin rather
+    /// than a viewer
+    pub fn with_insecure_no_auth(mut self, insecure_no_auth: bool) -> Self {
+        if insecure_no_auth && self.tokens.is_empty() {
+            warn!("⚠️ --insecure-no-auth: anyone who can reach the dashboard is an admin");
+            self.open_role = Role::Admin;
+        }
+        self
+    }
+
+    /// Whether requests must carry a token or session
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// Role granted by `token`, if it is one of the configured tokens
+    pub fn role_for_token(&self, token: &str) -> Option<Role> {
+        self.tokens
+            .iter()
+            .filter(|api_token| constant_time_eq(api_token.token.as_bytes(), token.as_bytes()))
+            .map(|api_token| api_token.role)
+            .max()
+    }
+
+    /// Role of the request's session cookie or bearer token
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<Role> {
+        if !self.is_enabled() {
+            return Some(self.open_role);
+        }
+        if let Some(token) = bearer_token(headers) {
+            return self.role_for_token(token);
+        }
+        session_id(headers).and_then(|id| self.session_role(id))
+    }
+
+    /// Start a session for `role` and return its id
+    fn create_session(&self, role: Role) -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let id: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|_, session| session.expires > now);
+        sessions.insert(
+            id.clone(),
+            Session {
+                role,
+                expires: now + self.session_ttl,
+            },
+        );
+        id
+    }
+
+    /// Role of a live session, extending its expiry
+    fn session_role(&self, id: &str) -> Option<Role> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        match sessions.get_mut(id) {
+            Some(session) if session.expires > now => {
+                session.expires = now + self.session_ttl;
+                Some(session.role)
+            }
+            Some(_) => {
+                sessions.remove(id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn end_session(&self, id: &str) {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+    }
+
+    /// `Set-Cookie` value for session `id`, valid for `max_age_secs`
+    fn session_cookie(&self, id: &str, max_age_secs: u64) -> HeaderValue {
+        let cookie = format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}{}",
+            SESSION_COOKIE,
+            id,
+            max_age_secs,
+            if self.secure_cookies { "; Secure" } else { "" }
+        );
+        HeaderValue::from_str(&cookie).expect("session cookie is valid ASCII")
+    }
+
+    /// `Set-Cookie` value extending the request's session cookie, if the
+    /// request was authenticated by one
+    ///
+    /// Sessions expire after `session_ttl` of inactivity, so the cookie's
+    /// Max-Age is renewed along with them.
+    fn refreshed_session_cookie(&self, headers: &HeaderMap) -> Option<HeaderValue> {
+        if !self.is_enabled() || bearer_token(headers).is_some() {
+            return None;
+        }
+        let id = session_id(headers)?;
+        Some(self.session_cookie(id, self.session_ttl.as_secs()))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn session_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, id)| id)
+}
+
+/// Middleware rejecting unauthenticated requests with 401
+///
+/// Authenticated requests carry their [`Role`] as a request extension, and
+/// requests authenticated by a session cookie get it back with a renewed
+/// Max-Age.
+pub async fn require_auth(auth: Arc<Auth>, mut request: Request, next: Next) -> Response {
+    let Some(role) = auth.authenticate(request.headers()) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let cookie = auth.refreshed_session_cookie(request.headers());
+    request.extensions_mut().insert(role);
+    let mut response = next.run(request).await;
+    if let Some(cookie) = cookie {
+        response
+            .headers_mut()
+            .entry(header::SET_COOKIE)
+            .or_insert(cookie);
+    }
+    response
+}
+
+/// `POST /api/login` — exchange a token for a session cookie
+///
+/// With authentication disabled there is nothing to exchange, and the
+/// response reports the role every request already has.
+pub async fn login_handler(auth: Arc<Auth>, Json(login): Json<LoginRequest>) -> Response {
+    if !auth.is_enabled() {
+        return Json(SessionInfo {
+            role: auth.open_role,
+            auth_enabled: false,
+        })
+        .into_response();
+    }
+    let Some(role) = auth.role_for_token(&login.token) else {
+        warn!("Rejected login with an unknown token");
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let id = auth.create_session(role);
+    info!("Started {:?} session", role);
+    let info = SessionInfo {
+        role,
+        auth_enabled: auth.is_enabled(),
+    };
+    let mut response = Json(info).into_response();
+    response.headers_mut().insert(
+        header::SET_COOKIE,
+        auth.session_cookie(&id, auth.session_ttl.as_secs()),
+    );
+    response
+}
+
+/// `POST /api/logout` — end the session and clear its cookie
+pub async fn logout_handler(auth: Arc<Auth>, headers: HeaderMap) -> Response {
+    if let Some(id) = session_id(&headers) {
+        auth.end_session(id);
+    }
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    response
+        .headers_mut()
+        .insert(header::SET_COOKIE, auth.session_cookie("", 0));
+    response
+}
+
+/// `GET /api/session` — role of the current session
+pub async fn session_handler(auth: Arc<Auth>, Extension(role): Extension<Role>) -> Response {
+    Json(SessionInfo {
+        role,
+        auth_enabled: auth.is_enabled(),
+    })
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn session_info(response: Response) -> SessionInfo {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_login_reports_auth_state_and_sessions_renew_their_cookie() {
+        // Without tokens there is no session to start, only the open role
+        let open =
+            Arc::new(Auth::new(Vec::new(), Duration::from_secs(60)).with_insecure_no_auth(true));
+        let login = LoginRequest {
+            token: "anything".to_string(),
+        };
+        let response = login_handler(open, Json(login)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+        let info = session_info(response).await;
+        assert_eq!(info.role, Role::Admin);
+        assert!(!info.auth_enabled);
+
+        let tokens = vec![ApiToken {
+            role: Role::Operator,
+            token: "s3cret".to_string(),
+        }];
+        let auth = Arc::new(Auth::new(tokens, Duration::from_secs(60)));
+        let login = LoginRequest {
+            token: "s3cret".to_string(),
+        };
+        let response = login_handler(auth.clone(), Json(login)).await;
+        let cookie = response.headers()[header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(cookie.contains("Max-Age=60"));
+        let info = session_info(response).await;
+        assert_eq!(info.role, Role::Operator);
+        assert!(info.auth_enabled);
+
+        // Each request on the session hands the cookie back with a fresh Max-Age
+        let session = cookie.split(';').next().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_str(session).unwrap());
+        assert_eq!(auth.authenticate(&headers), Some(Role::Operator));
+        let refreshed = auth.refreshed_session_cookie(&headers).unwrap();
+        assert_eq!(refreshed.to_str().unwrap(), cookie);
+
+        // Bearer tokens have no cookie to renew
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer s3cret"),
+        );
+        assert!(auth.refreshed_session_cookie(&headers).is_none());
+    }
+}
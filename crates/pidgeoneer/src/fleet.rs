@@ -0,0 +1,167 @@
+use crate::alerts::AlertEngine;
+use crate::models::{ControllerPresence, FleetEntry, PidControllerData, Presence};
+use crate::presence::PresenceTracker;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Recent samples per controller the summary is computed over
+const WINDOW: usize = 200;
+/// Mean `|error|`, relative to the setpoint, that costs the full error penalty
+const RELATIVE_ERROR_LIMIT: f64 = 0.1;
+
+/// Recent samples from one controller
+struct ControllerWindow {
+    latest: PidControllerData,
+    /// `|error|` and saturation of the last [`WINDOW`] samples
+    recent: VecDeque<(f64, bool)>,
+}
+
+impl ControllerWindow {
+    fn mean_abs_error(&self) -> f64 {
+        self.recent.iter().map(|(error, _)| error).sum::<f64>() / self.recent.len() as f64
+    }
+
+    fn saturation_duty(&self) -> f64 {
+        let saturated = self
+            .recent
+            .iter()
+            .filter(|(_, saturated)| *saturated)
+            .count();
+        saturated as f64 / self.recent.len() as f64
+    }
+}
+
+/// Summarizes every controller for the fleet overview
+///
+/// Keeps the latest sample and a short window of errors and saturation per
+/// controller; presence and alarms come from the [`PresenceTracker`] and
+/// [`AlertEngine`] when the summary is taken.
+pub struct FleetTracker {
+    controllers: Mutex<HashMap<String, ControllerWindow>>,
+}
+
+impl Default for FleetTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FleetTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self {
+            controllers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a sample from the ingest stream
+    pub fn observe(&self, sample: &PidControllerData) {
+        let mut controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let window = controllers
+            .entry(sample.controller_id.clone())
+            .or_insert_with(|| ControllerWindow {
+                latest: sample.clone(),
+                recent: VecDeque::with_capacity(WINDOW),
+            });
+        if window.recent.len() == WINDOW {
+            window.recent.pop_front();
+        }
+        window
+            .recent
+            .push_back((sample.error.abs(), sample.saturated));
+        window.latest = sample.clone();
+    }
+
+    /// Record every broadcast sample in a background task
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        tokio::spawn(async move {
+            loop {
+                match samples.recv().await {
+                    Ok(sample) => self.observe(&sample),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Fleet overview fell behind; skipped {} samples", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// One entry per controller in `presence`, sorted by id
+    pub fn summary(
+        &self,
+        presence: Vec<ControllerPresence>,
+        alerts: &AlertEngine,
+    ) -> Vec<FleetEntry> {
+        let active = alerts.active();
+        let controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        presence
+            .into_iter()
+            .map(|controller| {
+                let window = controllers.get(&controller.controller_id);
+                let alarms = active
+                    .iter()
+                    .filter(|alarm| alarm.controller_id == controller.controller_id)
+                    .count();
+                let mean_abs_error = window.map(ControllerWindow::mean_abs_error);
+                let saturation_duty = window.map(ControllerWindow::saturation_duty);
+                let relative_error = mean_abs_error
+                    .zip(window)
+                    .map(|(error, w)| error / w.latest.setpoint.abs().max(1.0));
+                FleetEntry {
+                    health: health_score(
+                        controller.presence,
+                        relative_error,
+                        saturation_duty,
+                        alarms,
+                    ),
+                    setpoint: window.map(|w| w.latest.setpoint),
+                    process_value: window.map(|w| w.latest.process_value),
+                    error: window.map(|w| w.latest.error),
+                    output: window.map(|w| w.latest.output),
+                    mean_abs_error,
+                    saturation_duty,
+                    alarms,
+                    controller_id: controller.controller_id,
+                    presence: controller.presence,
+                    last_seen: controller.last_seen,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Score a controller from 0 (needs attention) to 100 (healthy)
+///
+/// Tracking error relative to the setpoint costs up to 40 points, saturation
+/// duty up to 40, and any firing alarm 20. A stale controller's score is
+/// halved and an offline one scores 0.
+fn health_score(
+    presence: Presence,
+    relative_error: Option<f64>,
+    saturation_duty: Option<f64>,
+    alarms: usize,
+) -> f64 {
+    let error_penalty = relative_error.map_or(0.0, |e| (e / RELATIVE_ERROR_LIMIT).min(1.0)) * 40.0;
+    let saturation_penalty = saturation_duty.unwrap_or(0.0) * 40.0;
+    let alarm_penalty = if alarms > 0 { 20.0 } else { 0.0 };
+    let score = (100.0 - error_penalty - saturation_penalty - alarm_penalty).max(0.0);
+    match presence {
+        Presence::Online => score,
+        Presence::Stale => score / 2.0,
+        Presence::Offline => 0.0,
+    }
+}
+
+/// `GET /api/fleet` — status, error, saturation, and health of every controller
+pub async fn fleet_handler(
+    fleet: Arc<FleetTracker>,
+    presence: Arc<PresenceTracker>,
+    alerts: Arc<AlertEngine>,
+) -> Response {
+    Json(fleet.summary(presence.snapshot(), &alerts)).into_response()
+}
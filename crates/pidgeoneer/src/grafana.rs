@@ -0,0 +1,247 @@
+use crate::models::PidControllerData;
+use crate::storage::{HistoryQuery, Storage};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Fields that can be charted, in search order
+const FIELDS: &[&str] = &[
+    "setpoint",
+    "process_value",
+    "error",
+    "output",
+    "p_term",
+    "i_term",
+    "d_term",
+    "dt",
+    "kp",
+    "ki",
+    "kd",
+    "saturated",
+];
+
+/// Points returned per target when Grafana does not say
+const DEFAULT_MAX_POINTS: usize = 1_000;
+/// Samples read per target before downsampling
+const SAMPLES_PER_TARGET: usize = 50_000;
+
+/// Body of `POST /search`
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchRequest {
+    /// Text typed into the metric picker
+    #[serde(default)]
+    pub target: String,
+}
+
+/// Body of `POST /query`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    /// Time range of the panel
+    pub range: TimeRange,
+    /// Series to return
+    pub targets: Vec<QueryTarget>,
+    /// Width of the panel in points
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+}
+
+/// Panel time range as RFC 3339 UTC timestamps
+#[derive(Debug, Deserialize)]
+pub struct TimeRange {
+    pub from: String,
+    pub to: String,
+}
+
+/// One series requested by a panel
+#[derive(Debug, Deserialize)]
+pub struct QueryTarget {
+    /// `<controller_id>.<field>`
+    pub target: String,
+}
+
+/// One series in a `POST /query` response
+#[derive(Debug, Serialize)]
+pub struct TimeSeries {
+    pub target: String,
+    /// `[value, milliseconds since the Unix epoch]` pairs
+    pub datapoints: Vec<(f64, u64)>,
+}
+
+/// Value of `field` in `sample`, if it is a chartable field
+fn field_value(sample: &PidControllerData, field: &str) -> Option<f64> {
+    Some(match field {
+        "setpoint" => sample.setpoint,
+        "process_value" => sample.process_value,
+        "error" => sample.error,
+        "output" => sample.output,
+        "p_term" => sample.p_term,
+        "i_term" => sample.i_term,
+        "d_term" => sample.d_term,
+        "dt" => sample.dt,
+        "kp" => sample.kp,
+        "ki" => sample.ki,
+        "kd" => sample.kd,
+        "saturated" => f64::from(u8::from(sample.saturated)),
+        _ => return None,
+    })
+}
+
+/// Average `points` into at most `max_points` equal-width time buckets
+fn downsample(points: Vec<(f64, u64)>, from: u64, to: u64, max_points: usize) -> Vec<(f64, u64)> {
+    if points.len() <= max_points || max_points == 0 || to <= from {
+        return points;
+    }
+    let width = (to - from).div_ceil(max_points as u64).max(1);
+    let mut buckets: Vec<(f64, u64)> = Vec::with_capacity(max_points);
+    let mut current: Option<(u64, f64, usize)> = None;
+    for (value, timestamp) in points {
+        let bucket = (timestamp.saturating_sub(from)) / width;
+        match &mut current {
+            Some((index, sum, count)) if *index == bucket => {
+                *sum += value;
+                *count += 1;
+            }
+            _ => {
+                if let Some((index, sum, count)) = current.take() {
+                    buckets.push((sum / count as f64, from + index * width));
+                }
+                current = Some((bucket, value, 1));
+            }
+        }
+    }
+    if let Some((index, sum, count)) = current {
+        buckets.push((sum / count as f64, from + index * width));
+    }
+    buckets
+}
+
+/// Milliseconds since the Unix epoch of an RFC 3339 UTC timestamp such as
+/// `2024-05-01T12:30:00.000Z`
+fn parse_rfc3339_millis(timestamp: &str) -> Option<u64> {
+    let timestamp = timestamp.strip_suffix('Z')?;
+    let (date, time) = timestamp.split_once('T')?;
+
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    let millis = if fraction.is_empty() {
+        0
+    } else {
+        let digits: String = fraction.chars().chain("00".chars()).take(3).collect();
+        digits.parse::<i64>().ok()?
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second;
+    u64::try_from(secs * 1_000 + millis).ok()
+}
+
+/// `GET /api/grafana/` — health check for Grafana's JSON datasource
+///
+/// Point the datasource's URL at `/api/grafana`; targets are named
+/// `<controller_id>.<field>`, e.g. `oven.error`.
+pub async fn health_handler() -> StatusCode {
+    StatusCode::OK
+}
+
+/// `POST /api/grafana/search` — `<controller_id>.<field>` targets matching the typed text
+pub async fn search_handler(
+    storage: Arc<Storage>,
+    request: Option<Json<SearchRequest>>,
+) -> Response {
+    let filter = request.map(|Json(r)| r.target).unwrap_or_default();
+    let controllers = match storage.last_seen().await {
+        Ok(controllers) => controllers,
+        Err(e) => {
+            error!("Grafana search failed: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let targets: Vec<String> = controllers
+        .iter()
+        .flat_map(|(controller_id, _)| {
+            FIELDS
+                .iter()
+                .map(move |field| format!("{}.{}", controller_id, field))
+        })
+        .filter(|target| target.contains(&filter))
+        .collect();
+    Json(targets).into_response()
+}
+
+/// `POST /api/grafana/query` — downsampled history for each target
+pub async fn query_handler(storage: Arc<Storage>, Json(request): Json<QueryRequest>) -> Response {
+    let (Some(from), Some(to)) = (
+        parse_rfc3339_millis(&request.range.from),
+        parse_rfc3339_millis(&request.range.to),
+    ) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "range must be RFC 3339 UTC timestamps",
+        )
+            .into_response();
+    };
+    let max_points = request.max_data_points.unwrap_or(DEFAULT_MAX_POINTS);
+
+    let mut series = Vec::with_capacity(request.targets.len());
+    for QueryTarget { target } in request.targets {
+        // Controller ids may contain dots; the field never does
+        let Some((controller_id, field)) = target.rsplit_once('.') else {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unknown target '{}'", target),
+            )
+                .into_response();
+        };
+        if !FIELDS.contains(&field) {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unknown field '{}'", field),
+            )
+                .into_response();
+        }
+        let query = HistoryQuery {
+            controller_id: Some(controller_id.to_string()),
+            from: Some(from),
+            to: Some(to),
+            limit: Some(SAMPLES_PER_TARGET),
+        };
+        let samples = match storage.history(&query).await {
+            Ok(samples) => samples,
+            Err(e) => {
+                error!("Grafana query failed: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        };
+        let points = samples
+            .iter()
+            .filter_map(|sample| Some((field_value(sample, field)?, sample.timestamp)))
+            .collect();
+        series.push(TimeSeries {
+            datapoints: downsample(points, from, to, max_points),
+            target,
+        });
+    }
+    Json(series).into_response()
+}
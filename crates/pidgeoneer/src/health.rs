@@ -0,0 +1,230 @@
+use crate::storage::Storage;
+use crate::websocket::WebSocketState;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long `/healthz` waits for the database before calling it down
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What one ingest source has reported about itself
+struct SourceState {
+    name: String,
+    connected: bool,
+    messages: u64,
+    last_message: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// Connectivity and traffic of every ingest source, for `/healthz`
+///
+/// Each source started with [`start_ingest`](crate::ingest::start_ingest)
+/// gets a [`SourceHealth`] handle through its `Ingest`, and reports
+/// connecting, failing, and every sample it decodes, whether or not the
+/// ingest guard later rejects it.
+pub struct IngestHealth {
+    started: Instant,
+    sources: Mutex<Vec<SourceState>>,
+}
+
+impl Default for IngestHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IngestHealth {
+    /// Create a tracker with no sources
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            sources: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a source, named as it is displayed, which starts disconnected
+    pub fn register(self: &Arc<Self>, name: String) -> SourceHealth {
+        let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        sources.push(SourceState {
+            name,
+            connected: false,
+            messages: 0,
+            last_message: None,
+            last_error: None,
+        });
+        SourceHealth {
+            health: self.clone(),
+            index: sources.len() - 1,
+        }
+    }
+
+    fn update(&self, index: usize, f: impl FnOnce(&mut SourceState)) {
+        let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut sources[index]);
+    }
+
+    /// Every source's state, in the order they were registered
+    pub fn sources(&self) -> Vec<SourceReport> {
+        let sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        sources
+            .iter()
+            .map(|source| SourceReport {
+                source: source.name.clone(),
+                connected: source.connected,
+                messages: source.messages,
+                last_message_age_secs: source.last_message.map(|at| at.elapsed().as_secs_f64()),
+                last_error: source.last_error.clone(),
+            })
+            .collect()
+    }
+}
+
+/// One source's slot in an [`IngestHealth`]
+#[derive(Clone)]
+pub struct SourceHealth {
+    health: Arc<IngestHealth>,
+    index: usize,
+}
+
+impl SourceHealth {
+    /// The source is connected and consuming
+    pub fn connected(&self) {
+        self.health.update(self.index, |source| {
+            source.connected = true;
+            source.last_error = None;
+        });
+    }
+
+    /// The source lost or could not make its connection
+    pub fn disconnected(&self, error: impl fmt::Display) {
+        let error = error.to_string();
+        self.health.update(self.index, |source| {
+            source.connected = false;
+            source.last_error = Some(error);
+        });
+    }
+
+    /// A sample arrived
+    pub fn received(&self) {
+        self.health.update(self.index, |source| {
+            source.messages += 1;
+            source.last_message = Some(Instant::now());
+        });
+    }
+}
+
+/// Overall verdict of a [`HealthReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// The database answers and every source is connected
+    Ok,
+    /// Serving, but some sources are disconnected
+    Degraded,
+    /// The database is down, or no source is connected
+    Unhealthy,
+}
+
+/// One source in a [`HealthReport`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceReport {
+    /// The source URL, without its password
+    pub source: String,
+    pub connected: bool,
+    /// Samples decoded since the server started
+    pub messages: u64,
+    /// Seconds since the last message, or `None` if there has been none
+    pub last_message_age_secs: Option<f64>,
+    /// Why the source last disconnected, until it reconnects
+    pub last_error: Option<String>,
+}
+
+/// Database reachability in a [`HealthReport`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseReport {
+    pub ok: bool,
+    /// Round trip of a trivial query
+    pub latency_ms: f64,
+    pub error: Option<String>,
+}
+
+/// Connected clients in a [`HealthReport`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientReport {
+    /// Open WebSocket sessions
+    pub websocket: usize,
+    /// Receivers of the sample broadcast: sessions plus server-side consumers
+    pub subscribers: usize,
+}
+
+/// Body of `GET /healthz`
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub uptime_secs: f64,
+    pub database: DatabaseReport,
+    pub sources: Vec<SourceReport>,
+    pub clients: ClientReport,
+}
+
+impl HealthReport {
+    /// Judge the database and sources
+    pub fn status(database: &DatabaseReport, sources: &[SourceReport]) -> HealthStatus {
+        let connected = sources.iter().filter(|source| source.connected).count();
+        if !database.ok || (!sources.is_empty() && connected == 0) {
+            HealthStatus::Unhealthy
+        } else if connected < sources.len() {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Ok
+        }
+    }
+}
+
+/// `GET /healthz` — ingest connectivity and freshness, database status, and
+/// client counts as JSON
+///
+/// Answers 200 while the service is usable (`ok` or `degraded`) and 503 when
+/// it is `unhealthy`, so orchestrators and uptime monitors can probe it
+/// without parsing the body. It needs no session, and reveals no credentials.
+pub async fn healthz_handler(
+    health: Arc<IngestHealth>,
+    storage: Arc<Storage>,
+    ws_state: Arc<WebSocketState>,
+) -> Response {
+    let start = Instant::now();
+    let error = match tokio::time::timeout(DATABASE_TIMEOUT, storage.ping()).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("no answer within {:?}", DATABASE_TIMEOUT)),
+    };
+    if let Some(e) = &error {
+        warn!("Health check: database is down: {}", e);
+    }
+    let database = DatabaseReport {
+        ok: error.is_none(),
+        latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        error,
+    };
+    let sources = health.sources();
+    let report = HealthReport {
+        status: HealthReport::status(&database, &sources),
+        uptime_secs: health.started.elapsed().as_secs_f64(),
+        database,
+        sources,
+        clients: ClientReport {
+            websocket: ws_state.client_count(),
+            subscribers: ws_state.sender().receiver_count(),
+        },
+    };
+    let code = match report.status {
+        HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+        HealthStatus::Ok | HealthStatus::Degraded => StatusCode::OK,
+    };
+    (code, Json(report)).into_response()
+}
@@ -1,4 +1,4 @@
-use crate::models::PidControllerData;
+use crate::models::{PidControllerData, Subscription};
 use leptos::prelude::*;
 use log::*;
 
@@ -6,8 +6,16 @@ use log::*;
 #[cfg(feature = "hydrate")]
 mod client_impl {
     use super::*;
+    use crate::models::{
+        Alarm, ClientMessage, CommandLog, CommandRecord, ControllerPresence, ErrorSpectrum,
+        FleetEntry, LoginRequest, LoopTiming, SessionInfo, StepResponse, TuningRequest,
+        TuningSuggestions, JSON_PROTOCOL, MSGPACK_PROTOCOL,
+    };
     use wasm_bindgen::prelude::*;
-    use web_sys::{CloseEvent, ErrorEvent, MessageEvent, WebSocket};
+    use web_sys::{BinaryType, CloseEvent, ErrorEvent, MessageEvent, WebSocket};
+
+    /// Samples kept in the browser
+    const MAX_BUFFERED_SAMPLES: usize = 1000;
 
     /// Client for connecting to Iggy server via WebSocket
     #[derive(Clone)]
@@ -17,8 +25,11 @@ mod client_impl {
 
     impl IggyClient {
         /// Create a new IggyClient and set up WebSocket handlers
+        ///
+        /// The current `subscription` is sent as soon as the socket opens.
         pub fn new(
             pid_data: WriteSignal<Vec<PidControllerData>>,
+            subscription: ReadSignal<Subscription>,
             on_open: impl Fn() + 'static,
             on_close: impl Fn() + 'static,
         ) -> Self {
@@ -37,33 +48,36 @@ mod client_impl {
                 format!("{}//{}/ws", protocol, host)
             };
 
+            // Prefer compact MessagePack frames; the server falls back to JSON
+            // text for clients that don't ask for it
             info!("Connecting to WebSocket at {}", ws_url);
-            let connection = WebSocket::new(&ws_url).expect("Failed to create WebSocket");
+            let protocols = js_sys::Array::of2(
+                &JsValue::from(MSGPACK_PROTOCOL),
+                &JsValue::from(JSON_PROTOCOL),
+            );
+            let connection = WebSocket::new_with_str_sequence(&ws_url, &protocols)
+                .expect("Failed to create WebSocket");
+            connection.set_binary_type(BinaryType::Arraybuffer);
 
             // Set up message handler
             let pid_data_clone = pid_data;
             let onmessage_callback = Closure::<dyn FnMut(_)>::new(move |e: MessageEvent| {
-                if let Ok(txt) = e.data().dyn_into::<js_sys::JsString>() {
-                    let txt_str = String::from(txt);
-                    match serde_json::from_str::<PidControllerData>(&txt_str) {
-                        Ok(data) => {
-                            info!("Received PID data for controller: {}", data.controller_id);
-
-                            // Update the signal with the new data (chronological order)
-                            pid_data_clone.update(|data_vec| {
-                                data_vec.push(data);
-
-                                // Limit the size of the data vector to prevent memory issues
-                                if data_vec.len() > 1000 {
-                                    data_vec.drain(..data_vec.len() - 1000);
-                                }
-                            });
-                        }
-                        Err(e) => {
-                            error!("Failed to parse message as PidControllerData: {}", e);
-                            info!("Raw message: {}", txt_str);
-                        }
+                match decode_sample(&e.data()) {
+                    Some(Ok(data)) => {
+                        info!("Received PID data for controller: {}", data.controller_id);
+
+                        // Update the signal with the new data (chronological order)
+                        pid_data_clone.update(|data_vec| {
+                            data_vec.push(data);
+
+                            // Limit the size of the data vector to prevent memory issues
+                            if data_vec.len() > MAX_BUFFERED_SAMPLES {
+                                data_vec.drain(..data_vec.len() - MAX_BUFFERED_SAMPLES);
+                            }
+                        });
                     }
+                    Some(Err(e)) => error!("Failed to parse message as PidControllerData: {}", e),
+                    None => warn!("Ignoring WebSocket frame of unexpected type"),
                 }
             });
             connection.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
@@ -71,8 +85,13 @@ mod client_impl {
 
             // Set up open handler
             let on_open_clone = Box::new(on_open);
+            let open_connection = connection.clone();
             let onopen_callback = Closure::<dyn FnMut()>::new(move || {
-                info!("WebSocket connection opened");
+                info!(
+                    "WebSocket connection opened, protocol {:?}",
+                    open_connection.protocol()
+                );
+                send_subscription(&open_connection, &subscription.get_untracked());
                 on_open_clone();
             });
             connection.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
@@ -123,6 +142,236 @@ mod client_impl {
 
             Self { connection }
         }
+
+        /// Ask the server to forward only what `subscription` selects
+        ///
+        /// Ignored until the socket is open; the open handler sends the
+        /// subscription current at that time.
+        pub fn subscribe(&self, subscription: &Subscription) {
+            if self.connection.ready_state() == WebSocket::OPEN {
+                send_subscription(&self.connection, subscription);
+            }
+        }
+    }
+
+    /// Decode a sample from a MessagePack binary frame or a JSON text frame
+    fn decode_sample(frame: &JsValue) -> Option<Result<PidControllerData, String>> {
+        if let Some(buffer) = frame.dyn_ref::<js_sys::ArrayBuffer>() {
+            let bytes = js_sys::Uint8Array::new(buffer).to_vec();
+            return Some(rmp_serde::from_slice(&bytes).map_err(|e| e.to_string()));
+        }
+        let text = String::from(frame.dyn_ref::<js_sys::JsString>()?);
+        Some(serde_json::from_str(&text).map_err(|e| format!("{} in {}", e, text)))
+    }
+
+    fn send_subscription(connection: &WebSocket, subscription: &Subscription) {
+        let message = ClientMessage::Subscribe(subscription.clone());
+        match serde_json::to_string(&message) {
+            Ok(json) => {
+                if let Err(e) = connection.send_with_str(&json) {
+                    error!("Failed to send subscription: {:?}", e);
+                }
+            }
+            Err(e) => error!("Failed to encode subscription: {}", e),
+        }
+    }
+
+    /// Prepend the server's stored history to `pid_data`
+    ///
+    /// Only the subscribed controller's history is requested when the
+    /// subscription names exactly one. Live samples that arrived while the
+    /// request was in flight are kept after the history.
+    pub fn load_history(
+        pid_data: WriteSignal<Vec<PidControllerData>>,
+        subscription: &Subscription,
+    ) {
+        let controller_id = match subscription.controller_ids.as_slice() {
+            [controller_id] => Some(controller_id.clone()),
+            _ => None,
+        };
+        leptos::task::spawn_local(async move {
+            match fetch_history(controller_id.as_deref()).await {
+                Ok(mut history) => {
+                    info!("Loaded {} stored samples", history.len());
+                    pid_data.update(|data_vec| {
+                        let newest = history.last().map(|d| d.timestamp);
+                        history.extend(data_vec.drain(..).filter(|d| Some(d.timestamp) > newest));
+                        if history.len() > MAX_BUFFERED_SAMPLES {
+                            history.drain(..history.len() - MAX_BUFFERED_SAMPLES);
+                        }
+                        *data_vec = history;
+                    });
+                }
+                Err(e) => error!("Failed to load history: {}", e),
+            }
+        });
+    }
+
+    async fn fetch_history(controller_id: Option<&str>) -> Result<Vec<PidControllerData>, String> {
+        let mut url = format!("/api/history?limit={}", MAX_BUFFERED_SAMPLES);
+        if let Some(controller_id) = controller_id {
+            let controller_id = String::from(js_sys::encode_uri_component(controller_id));
+            url.push_str(&format!("&controller_id={}", controller_id));
+        }
+        match send_request("GET", &url, None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Alarms currently firing on the server
+    pub async fn fetch_alarms() -> Result<Vec<Alarm>, String> {
+        match send_request("GET", "/api/alarms", None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Every controller the server knows of and whether it is still sending
+    pub async fn fetch_controllers() -> Result<Vec<ControllerPresence>, String> {
+        match send_request("GET", "/api/controllers", None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Status, error, saturation, and health of every controller
+    pub async fn fetch_fleet() -> Result<Vec<FleetEntry>, String> {
+        match send_request("GET", "/api/fleet", None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Recent step responses computed by the server, oldest first
+    pub async fn fetch_statistics(
+        controller_id: Option<String>,
+    ) -> Result<Vec<StepResponse>, String> {
+        let mut url = "/api/statistics".to_string();
+        if let Some(controller_id) = controller_id {
+            let controller_id = String::from(js_sys::encode_uri_component(&controller_id));
+            url.push_str(&format!("?controller_id={}", controller_id));
+        }
+        match send_request("GET", &url, None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// `dt` histograms and missed deadlines, for one controller or all of them
+    pub async fn fetch_timing(controller_id: Option<String>) -> Result<Vec<LoopTiming>, String> {
+        let mut url = "/api/timing".to_string();
+        if let Some(controller_id) = controller_id {
+            let controller_id = String::from(js_sys::encode_uri_component(&controller_id));
+            url.push_str(&format!("?controller_id={}", controller_id));
+        }
+        match send_request("GET", &url, None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Spectrum of a controller's recent error; `None` until it has enough samples
+    pub async fn fetch_spectrum(controller_id: &str) -> Result<Option<ErrorSpectrum>, String> {
+        let controller_id = String::from(js_sys::encode_uri_component(controller_id));
+        let url = format!("/api/spectrum?controller_id={}", controller_id);
+        match send_request("GET", &url, None).await? {
+            (200, body) => serde_json::from_str(&body)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+            (404, _) => Ok(None),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Identify a controller's process from its recorded history and propose gains
+    pub async fn fetch_suggestions(controller_id: &str) -> Result<TuningSuggestions, String> {
+        let controller_id = String::from(js_sys::encode_uri_component(controller_id));
+        let url = format!("/api/suggestions?controller_id={}", controller_id);
+        match send_request("GET", &url, None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, body) if !body.is_empty() => Err(format!("HTTP {}: {}", status, body)),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Whether remote tuning is enabled, and recent commands
+    pub async fn fetch_commands() -> Result<CommandLog, String> {
+        match send_request("GET", "/api/commands", None).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Ask the server to sign and publish `request`
+    pub async fn send_tuning(request: &TuningRequest) -> Result<Vec<CommandRecord>, String> {
+        let body = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        match send_request("POST", "/api/commands", Some(body)).await? {
+            (200, body) => serde_json::from_str(&body).map_err(|e| e.to_string()),
+            (status, body) if !body.is_empty() => Err(format!("HTTP {}: {}", status, body)),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// The current session, or `None` if the browser must sign in
+    pub async fn fetch_session() -> Result<Option<SessionInfo>, String> {
+        match send_request("GET", "/api/session", None).await? {
+            (200, body) => serde_json::from_str(&body)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+            (401, _) => Ok(None),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// Exchange `token` for a session cookie, or `None` if it was refused
+    pub async fn login(token: String) -> Result<Option<SessionInfo>, String> {
+        let body = serde_json::to_string(&LoginRequest { token }).map_err(|e| e.to_string())?;
+        match send_request("POST", "/api/login", Some(body)).await? {
+            (200, body) => serde_json::from_str(&body)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+            (401, _) => Ok(None),
+            (status, _) => Err(format!("HTTP {}", status)),
+        }
+    }
+
+    /// End the current session
+    pub async fn logout() -> Result<(), String> {
+        send_request("POST", "/api/logout", None).await.map(|_| ())
+    }
+
+    /// Send a same-origin request and return its status and body
+    async fn send_request(
+        method: &str,
+        url: &str,
+        json_body: Option<String>,
+    ) -> Result<(u16, String), String> {
+        use wasm_bindgen_futures::JsFuture;
+
+        let js_error = |e: JsValue| format!("{:?}", e);
+        let window = web_sys::window().ok_or("no global `window` exists")?;
+        let init = web_sys::RequestInit::new();
+        init.set_method(method);
+        if let Some(body) = json_body {
+            let headers = web_sys::Headers::new().map_err(js_error)?;
+            headers
+                .set("Content-Type", "application/json")
+                .map_err(js_error)?;
+            init.set_headers(&headers);
+            init.set_body(&JsValue::from_str(&body));
+        }
+        let response: web_sys::Response =
+            JsFuture::from(window.fetch_with_str_and_init(url, &init))
+                .await
+                .and_then(|response| response.dyn_into())
+                .map_err(js_error)?;
+        let body = JsFuture::from(response.text().map_err(js_error)?)
+            .await
+            .map_err(js_error)?
+            .as_string()
+            .unwrap_or_default();
+        Ok((response.status(), body))
     }
 }
 
@@ -139,18 +388,26 @@ mod server_impl {
         /// Create a new placeholder IggyClient for server-side
         pub fn new(
             _pid_data: WriteSignal<Vec<PidControllerData>>,
+            _subscription: ReadSignal<Subscription>,
             _on_open: impl Fn() + 'static,
             _on_close: impl Fn() + 'static,
         ) -> Self {
             info!("Creating placeholder IggyClient for server-side");
             Self
         }
+
+        /// Subscriptions only apply in the browser
+        pub fn subscribe(&self, _subscription: &Subscription) {}
     }
 }
 
 // Re-export the appropriate implementation based on the feature flag
 #[cfg(feature = "hydrate")]
-pub use client_impl::IggyClient;
+pub use client_impl::{
+    fetch_alarms, fetch_commands, fetch_controllers, fetch_fleet, fetch_session, fetch_spectrum,
+    fetch_statistics, fetch_suggestions, fetch_timing, load_history, login, logout, send_tuning,
+    IggyClient,
+};
 
 #[cfg(not(feature = "hydrate"))]
 pub use server_impl::IggyClient;
@@ -0,0 +1,253 @@
+use crate::config::{IggySource, Source};
+use crate::health::{IngestHealth, SourceHealth};
+use crate::models::PidControllerData;
+use crate::validation::IngestGuard;
+use crate::websocket::WebSocketState;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use iggy::client::{Client, MessageClient, UserClient};
+use iggy::models::header::{HeaderKey, HeaderValue};
+use log::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Where ingested samples go
+///
+/// Every source hands its decoded samples to [`publish`](Self::publish),
+/// which screens them with the [`IngestGuard`], then queues them for storage
+/// and broadcasts them to browsers and the server-side consumers (alerts,
+/// presence, statistics, metrics). Sources also report their connection
+/// state through it, for `/healthz`.
+#[derive(Clone)]
+pub struct Ingest {
+    state: Arc<WebSocketState>,
+    history: mpsc::Sender<PidControllerData>,
+    guard: Arc<IngestGuard>,
+    health: Arc<IngestHealth>,
+    /// Set by [`start_ingest`] for the source it hands this to
+    source: Option<SourceHealth>,
+}
+
+impl Ingest {
+    /// Publish to `state`'s broadcast channel and queue on `history`,
+    /// dropping samples `guard` rejects, and track sources in `health`
+    pub fn new(
+        state: Arc<WebSocketState>,
+        history: mpsc::Sender<PidControllerData>,
+        guard: Arc<IngestGuard>,
+        health: Arc<IngestHealth>,
+    ) -> Self {
+        Self {
+            state,
+            history,
+            guard,
+            health,
+            source: None,
+        }
+    }
+
+    /// The source is connected and consuming
+    pub fn connected(&self) {
+        if let Some(source) = &self.source {
+            source.connected();
+        }
+    }
+
+    /// The source lost or could not make its connection
+    pub fn disconnected(&self, error: impl fmt::Display) {
+        if let Some(source) = &self.source {
+            source.disconnected(error);
+        }
+    }
+
+    /// Validate, store, then broadcast a decoded sample
+    pub fn publish(&self, sample: PidControllerData) {
+        if let Some(source) = &self.source {
+            source.received();
+        }
+        if self.guard.admit(&sample).is_err() {
+            return;
+        }
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.history.try_send(sample.clone()) {
+            warn!("History writer is behind; dropping sample");
+        }
+        let _ = self.state.sender().send(sample);
+    }
+}
+
+/// A broker or bus that samples can be ingested from
+///
+/// Implement this to add a transport; [`start_ingest`] runs each configured
+/// source in its own task.
+pub trait IngestSource: fmt::Display + Send + 'static {
+    /// Consume samples, handing each to `ingest`, until the source gives up
+    fn run(self: Box<Self>, ingest: Ingest) -> BoxFuture<'static, ()>;
+}
+
+impl From<Source> for Box<dyn IngestSource> {
+    fn from(source: Source) -> Self {
+        match source {
+            Source::Iggy(source) => Box::new(source),
+            Source::Mqtt(source) => Box::new(source),
+        }
+    }
+}
+
+/// Run `source` in a background task, registering it with the ingest's
+/// health tracker
+pub fn start_ingest(source: Box<dyn IngestSource>, mut ingest: Ingest) {
+    info!("Starting ingest from {}", source);
+    ingest.source = Some(ingest.health.register(source.to_string()));
+    tokio::spawn(source.run(ingest));
+}
+
+/// Decode a Pidgeon debug payload using its content type.
+///
+/// Producers that send none fall back to JSON, then MessagePack, then CBOR.
+pub fn decode_payload(
+    content_type: Option<&str>,
+    payload: &[u8],
+) -> Result<PidControllerData, String> {
+    match content_type {
+        Some("application/json") => serde_json::from_slice(payload).map_err(|e| e.to_string()),
+        Some("application/msgpack") | Some("application/x-msgpack") => {
+            rmp_serde::from_slice(payload).map_err(|e| e.to_string())
+        }
+        Some("application/cbor") => ciborium::from_reader(payload).map_err(|e| e.to_string()),
+        Some(other) => Err(format!("unsupported content type '{}'", other)),
+        None => serde_json::from_slice(payload)
+            .or_else(|_| rmp_serde::from_slice(payload))
+            .or_else(|_| ciborium::from_reader(payload))
+            .map_err(|_| "payload is not JSON, MessagePack, or CBOR".to_string()),
+    }
+}
+
+/// The `content-type` header of an Iggy message
+fn content_type(headers: Option<&HashMap<HeaderKey, HeaderValue>>) -> Option<&str> {
+    headers
+        .and_then(|headers| {
+            headers
+                .iter()
+                .find(|(key, _)| key.as_str().eq_ignore_ascii_case("content-type"))
+        })
+        .and_then(|(_, value)| value.as_str().ok())
+}
+
+impl IngestSource for IggySource {
+    fn run(self: Box<Self>, ingest: Ingest) -> BoxFuture<'static, ()> {
+        let source = *self;
+        async move {
+            // Create Iggy client
+            let connection_string = source.connection_string();
+            let client =
+                match iggy::clients::client::IggyClient::from_connection_string(&connection_string)
+                {
+                    Ok(client) => {
+                        match client.connect().await {
+                            Ok(_) => {
+                                info!("✅ Connected to Iggy server at {}", source.address);
+
+                                // Login with the source's credentials
+                                if let Err(e) =
+                                    client.login_user(&source.username, &source.password).await
+                                {
+                                    error!("Failed to login to Iggy: {}", e);
+                                    ingest.disconnected(format!("login failed: {}", e));
+                                    return;
+                                }
+
+                                client
+                            }
+                            Err(e) => {
+                                error!("Failed to connect to Iggy server: {}", e);
+                                ingest.disconnected(&e);
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("❌ Failed to create Iggy client: {}", e);
+                        ingest.disconnected(&e);
+                        return;
+                    }
+                };
+
+            // Create a consumer
+            let (stream_name, topic_name) = match (
+                iggy::identifier::Identifier::from_str(&source.stream),
+                iggy::identifier::Identifier::from_str(&source.topic),
+            ) {
+                (Ok(stream), Ok(topic)) => (stream, topic),
+                (Err(e), _) | (_, Err(e)) => {
+                    error!("❌ Invalid stream or topic in {}: {}", source, e);
+                    ingest.disconnected(format!("invalid stream or topic: {}", e));
+                    return;
+                }
+            };
+
+            let consumer = iggy::consumer::Consumer {
+                kind: iggy::consumer::ConsumerKind::from_code(1).unwrap(),
+                id: iggy::identifier::Identifier::numeric(1).unwrap(),
+            };
+
+            // Start consuming messages
+            info!("Starting message consumption loop");
+            ingest.connected();
+            loop {
+                // Poll for messages
+                match client
+                    .poll_messages(
+                        &stream_name,
+                        &topic_name,
+                        None,
+                        &consumer,
+                        &iggy::messages::poll_messages::PollingStrategy::next(),
+                        1,
+                        true,
+                    )
+                    .await
+                {
+                    Ok(polled_messages) => {
+                        ingest.connected();
+                        // The messages is a PolledMessages struct, not an iterator
+                        // We need to access messages field which is a Vec<Message>
+                        for message in polled_messages.messages {
+                            // Decode the message according to its content type
+                            match decode_payload(
+                                content_type(message.headers.as_ref()),
+                                &message.payload,
+                            ) {
+                                Ok(pid_data) => {
+                                    info!(
+                                        "📥 Received PID data from controller: {}",
+                                        pid_data.controller_id
+                                    );
+
+                                    ingest.publish(pid_data);
+                                }
+                                Err(e) => {
+                                    error!("Failed to parse message as PidControllerData: {}", e);
+                                    debug!("Raw message: {:?}", message.payload);
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("Error polling for messages: {}", e);
+                        ingest.disconnected(format!("polling failed: {}", e));
+                        // Add a short delay to prevent CPU spinning on repeated errors
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+
+                // Small delay between polling attempts
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+        .boxed()
+    }
+}
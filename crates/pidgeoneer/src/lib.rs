@@ -1,7 +1,43 @@
+#[cfg(feature = "admin")]
+pub mod admin;
+#[cfg(feature = "ssr")]
+pub mod alerts;
 pub mod app;
+#[cfg(feature = "ssr")]
+pub mod assistant;
+#[cfg(feature = "ssr")]
+pub mod auth;
+#[cfg(feature = "ssr")]
+pub mod config;
+#[cfg(feature = "ssr")]
+pub mod fleet;
+#[cfg(feature = "ssr")]
+pub mod grafana;
+#[cfg(feature = "ssr")]
+pub mod health;
 pub mod iggy_client;
+#[cfg(feature = "ssr")]
+pub mod ingest;
+#[cfg(feature = "ssr")]
+pub mod metrics;
 pub mod models;
 #[cfg(feature = "ssr")]
+pub mod mqtt;
+#[cfg(feature = "ssr")]
+pub mod presence;
+#[cfg(feature = "ssr")]
+pub mod spectrum;
+#[cfg(feature = "ssr")]
+pub mod statistics;
+#[cfg(feature = "ssr")]
+pub mod storage;
+#[cfg(feature = "ssr")]
+pub mod timing;
+#[cfg(feature = "ssr")]
+pub mod tuning;
+#[cfg(feature = "ssr")]
+pub mod validation;
+#[cfg(feature = "ssr")]
 pub mod websocket;
 
 #[cfg(feature = "hydrate")]
@@ -1,13 +1,44 @@
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() {
-    use axum::{extract::WebSocketUpgrade, routing::get, Router};
+    #[cfg(feature = "admin")]
+    use axum::routing::delete;
+    use axum::{
+        extract::WebSocketUpgrade,
+        middleware,
+        routing::{get, post},
+        Router,
+    };
+    use clap::Parser;
     use leptos::logging::log;
     use leptos::prelude::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
+    #[cfg(feature = "admin")]
+    use pidgeoneer::admin::{
+        autotune_handler, config_handler, loops_handler, silence_handler, silences_handler,
+        unsilence_handler, update_config_handler, Admin,
+    };
+    use pidgeoneer::alerts::{alarms_handler, load_rules, AlertEngine};
     use pidgeoneer::app::*;
-    use pidgeoneer::websocket::{start_iggy_consumer, ws_handler, WebSocketState};
+    use pidgeoneer::assistant::suggestions_handler;
+    use pidgeoneer::auth::{login_handler, logout_handler, require_auth, session_handler, Auth};
+    use pidgeoneer::config::ServerConfig;
+    use pidgeoneer::fleet::{fleet_handler, FleetTracker};
+    use pidgeoneer::grafana::{health_handler, query_handler, search_handler};
+    use pidgeoneer::health::{healthz_handler, IngestHealth};
+    use pidgeoneer::ingest::{start_ingest, Ingest};
+    use pidgeoneer::metrics::{metrics_handler, Metrics};
+    use pidgeoneer::models::{JSON_PROTOCOL, MSGPACK_PROTOCOL};
+    use pidgeoneer::presence::{controllers_handler, PresenceTracker};
+    use pidgeoneer::spectrum::{spectrum_handler, SpectrumAnalyzer};
+    use pidgeoneer::statistics::{statistics_handler, ResponseStatistics};
+    use pidgeoneer::storage::{history_handler, rollups_handler, Storage};
+    use pidgeoneer::timing::{timing_handler, TimingTracker};
+    use pidgeoneer::tuning::{commands_handler, issue_handler, Tuner};
+    use pidgeoneer::validation::{quarantine_handler, IngestGuard};
+    use pidgeoneer::websocket::{ws_handler, WebSocketState};
     use std::sync::Arc;
+    use std::time::Duration;
 
     // Set up logging
     #[cfg(feature = "ssr")]
@@ -19,23 +50,280 @@ async fn main() {
             .init();
     }
 
+    let config = ServerConfig::parse();
     let conf = get_configuration(None).unwrap();
-    let addr = conf.leptos_options.site_addr;
+    let addr = config.listen.unwrap_or(conf.leptos_options.site_addr);
     let leptos_options = conf.leptos_options;
     // Generate the list of routes in your Leptos App
     let routes = generate_route_list(App);
 
-    // Create WebSocketState and Iggy consumer
-    let ws_state = Arc::new(WebSocketState::new());
-    start_iggy_consumer(ws_state.clone());
+    // Open the history database
+    let storage = Storage::connect(&config.database_url)
+        .await
+        .expect("failed to open history database");
+    let history = storage.clone().spawn_writer();
+    storage.clone().spawn_pruner(config.retention_policy());
+    let last_seen = storage.last_seen().await.unwrap_or_else(|e| {
+        log::warn!("Failed to load controller last-seen times: {}", e);
+        Vec::new()
+    });
+    let storage = Arc::new(storage);
 
-    let app = Router::new()
+    // Relay signed tuning commands when a secret is configured
+    let tuner = config.command_secret.map(|secret| {
+        let source = config
+            .command_source
+            .or_else(|| config.sources.iter().find_map(|s| s.as_iggy()).cloned())
+            .expect("remote commands need --command-source or an iggy:// --source");
+        Tuner::start(
+            secret.0.into_bytes(),
+            source,
+            config.command_topic,
+            config.audit_topic,
+        )
+    });
+
+    // Create WebSocketState and one ingest task per source
+    let ws_state = Arc::new(WebSocketState::new().with_keepalive(
+        Duration::from_secs(config.ws_ping_secs),
+        Duration::from_secs(config.ws_timeout_secs),
+    ));
+    let guard = Arc::new(IngestGuard::new(Some(config.max_sample_rate)));
+    let health = Arc::new(IngestHealth::new());
+    let ingest = Ingest::new(ws_state.clone(), history, guard.clone(), health.clone());
+    for source in config.sources {
+        start_ingest(source.into(), ingest.clone());
+    }
+
+    // Evaluate alert rules against the same stream the browsers see
+    let rules = match &config.alert_rules {
+        Some(path) => load_rules(path).expect("failed to load alert rules"),
+        None => Vec::new(),
+    };
+    let alerts = Arc::new(AlertEngine::new(rules));
+    alerts.clone().spawn(ws_state.sender().subscribe());
+
+    // Track which controllers are still sending samples
+    let presence = Arc::new(PresenceTracker::new(
+        Duration::from_secs(config.stale_after_secs),
+        Duration::from_secs(config.offline_after_secs),
+    ));
+    presence.seed(last_seen);
+    presence.clone().spawn(ws_state.sender().subscribe());
+
+    // Rise time, overshoot, and settling for every setpoint change
+    let statistics = Arc::new(ResponseStatistics::new(config.settled_threshold));
+    statistics.clone().spawn(ws_state.sender().subscribe());
+
+    // Loop-rate histograms and missed deadlines from each sample's dt
+    let timing = Arc::new(TimingTracker::new());
+    timing.clone().spawn(ws_state.sender().subscribe());
+
+    // Frequency content of each controller's error signal
+    let spectrum = Arc::new(SpectrumAnalyzer::new());
+    spectrum.clone().spawn(ws_state.sender().subscribe());
+
+    // Error, saturation, and health of every controller at a glance
+    let fleet = Arc::new(FleetTracker::new());
+    fleet.clone().spawn(ws_state.sender().subscribe());
+
+    // Prometheus metrics for existing ops dashboards
+    let metrics = Arc::new(Metrics::new());
+    metrics.clone().spawn(ws_state.sender().subscribe());
+
+    let tls = config.tls_cert.zip(config.tls_key);
+    let auth = Arc::new(
+        Auth::new(config.tokens, Duration::from_secs(config.session_ttl_secs))
+            .with_insecure_no_auth(config.insecure_no_auth)
+            .with_secure_cookies(tls.is_some()),
+    );
+
+    let healthz = {
+        let storage = storage.clone();
+        let ws_state = ws_state.clone();
+        move || healthz_handler(health.clone(), storage.clone(), ws_state.clone())
+    };
+
+    // Fleet operations for scripts, against the registry of the process
+    // running the loops
+    #[cfg(feature = "admin")]
+    let admin = Arc::new(
+        Admin::new(config.registry.as_deref(), storage.clone(), alerts.clone())
+            .expect("failed to set up the admin API"),
+    );
+
+    // Everything here requires a session or bearer token
+    let protected = Router::new()
+        .route("/api/grafana", get(health_handler))
+        .route("/api/grafana/", get(health_handler))
+        .route(
+            "/api/grafana/search",
+            post({
+                let storage = storage.clone();
+                move |request| search_handler(storage.clone(), request)
+            }),
+        )
+        .route(
+            "/api/grafana/query",
+            post({
+                let storage = storage.clone();
+                move |request| query_handler(storage.clone(), request)
+            }),
+        )
+        .route(
+            "/api/rollups",
+            get({
+                let storage = storage.clone();
+                move |query| rollups_handler(storage.clone(), query)
+            }),
+        )
+        .route(
+            "/api/suggestions",
+            get({
+                let storage = storage.clone();
+                move |query| suggestions_handler(storage.clone(), query)
+            }),
+        )
+        .route(
+            "/api/history",
+            get(move |query| history_handler(storage.clone(), query)),
+        )
+        .route(
+            "/metrics",
+            get({
+                let ws_state = ws_state.clone();
+                let alerts = alerts.clone();
+                let guard = guard.clone();
+                move || {
+                    metrics_handler(
+                        metrics.clone(),
+                        ws_state.clone(),
+                        alerts.clone(),
+                        guard.clone(),
+                    )
+                }
+            }),
+        )
+        .route(
+            "/api/quarantine",
+            get(move || quarantine_handler(guard.clone())),
+        )
+        .route(
+            "/api/fleet",
+            get({
+                let presence = presence.clone();
+                let alerts = alerts.clone();
+                move || fleet_handler(fleet.clone(), presence.clone(), alerts.clone())
+            }),
+        )
+        .route("/api/alarms", get(move || alarms_handler(alerts.clone())))
+        .route(
+            "/api/controllers",
+            get(move || controllers_handler(presence.clone())),
+        )
+        .route(
+            "/api/commands",
+            get({
+                let tuner = tuner.clone();
+                move || commands_handler(tuner.clone())
+            })
+            .post(move |role, request| issue_handler(tuner.clone(), role, request)),
+        )
+        .route(
+            "/api/statistics",
+            get(move |query| statistics_handler(statistics.clone(), query)),
+        )
+        .route(
+            "/api/spectrum",
+            get(move |query| spectrum_handler(spectrum.clone(), query)),
+        )
+        .route(
+            "/api/timing",
+            get(move |query| timing_handler(timing.clone(), query)),
+        )
+        .route(
+            "/api/session",
+            get({
+                let auth = auth.clone();
+                move |role| session_handler(auth.clone(), role)
+            }),
+        )
         .route(
             "/ws",
             get(move |ws: WebSocketUpgrade| async move {
-                ws.on_upgrade(move |socket| ws_handler(socket, ws_state.clone()))
+                ws.protocols([MSGPACK_PROTOCOL, JSON_PROTOCOL])
+                    .on_upgrade(move |socket| ws_handler(socket, ws_state.clone()))
             }),
         )
+        .route_layer(middleware::from_fn({
+            let auth = auth.clone();
+            move |request, next| require_auth(auth.clone(), request, next)
+        }));
+
+    #[cfg(feature = "admin")]
+    let protected = protected.merge(
+        Router::new()
+            .route(
+                "/api/admin/loops",
+                get({
+                    let admin = admin.clone();
+                    move |role| loops_handler(admin.clone(), role)
+                }),
+            )
+            .route(
+                "/api/admin/loops/:id/config",
+                get({
+                    let admin = admin.clone();
+                    move |role, id| config_handler(admin.clone(), role, id)
+                })
+                .patch({
+                    let admin = admin.clone();
+                    move |role, id, update| update_config_handler(admin.clone(), role, id, update)
+                }),
+            )
+            .route(
+                "/api/admin/loops/:id/autotune",
+                post({
+                    let admin = admin.clone();
+                    move |role, id, params| autotune_handler(admin.clone(), role, id, params)
+                }),
+            )
+            .route(
+                "/api/admin/silences",
+                get({
+                    let admin = admin.clone();
+                    move || silences_handler(admin.clone())
+                })
+                .post({
+                    let admin = admin.clone();
+                    move |role, request| silence_handler(admin.clone(), role, request)
+                }),
+            )
+            .route(
+                "/api/admin/silences/:id",
+                delete(move |role, id| unsilence_handler(admin.clone(), role, id)),
+            )
+            .route_layer(middleware::from_fn({
+                let auth = auth.clone();
+                move |request, next| require_auth(auth.clone(), request, next)
+            })),
+    );
+
+    let app = Router::new()
+        .merge(protected)
+        // Probed by orchestrators and uptime monitors, which hold no token
+        .route("/healthz", get(healthz))
+        .route(
+            "/api/login",
+            post({
+                let auth = auth.clone();
+                move |login| login_handler(auth.clone(), login)
+            }),
+        )
+        .route(
+            "/api/logout",
+            post(move |headers| logout_handler(auth.clone(), headers)),
+        )
         .leptos_routes(&leptos_options, routes, {
             let leptos_options = leptos_options.clone();
             move || shell(leptos_options.clone())
@@ -43,13 +331,26 @@ async fn main() {
         .fallback(leptos_axum::file_and_error_handler(shell))
         .with_state(leptos_options);
 
-    // run our app with hyper
-    // `axum::Server` is a re-export of `hyper::Server`
-    log!("listening on http://{}", &addr);
-    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    // run our app with hyper, over rustls when a certificate is configured
+    match tls {
+        Some((cert, key)) => {
+            let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&cert, &key)
+                .await
+                .expect("failed to load TLS certificate and key");
+            log!("listening on https://{}", &addr);
+            axum_server::bind_rustls(addr, tls_config)
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        }
+        None => {
+            log!("listening on http://{}", &addr);
+            let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+            axum::serve(listener, app.into_make_service())
+                .await
+                .unwrap();
+        }
+    }
 }
 
 #[cfg(not(feature = "ssr"))]
@@ -0,0 +1,230 @@
+use crate::alerts::AlertEngine;
+use crate::models::PidControllerData;
+use crate::validation::IngestGuard;
+use crate::websocket::WebSocketState;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use log::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Content type of the Prometheus text exposition format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Latest values from one controller
+#[derive(Default)]
+struct ControllerMetrics {
+    samples: u64,
+    error: f64,
+    output: f64,
+    setpoint: f64,
+    process_value: f64,
+    saturated: bool,
+    /// Server time of the last sample, in seconds since the Unix epoch
+    last_seen: f64,
+}
+
+/// Counters and gauges exported at `GET /metrics`
+///
+/// Rendered by hand in the Prometheus text format; there are few enough
+/// series that a client library would add more than it saves.
+pub struct Metrics {
+    controllers: Mutex<BTreeMap<String, ControllerMetrics>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Name, type, help text, and value of a per-controller metric
+type PerController = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&ControllerMetrics) -> f64,
+);
+
+impl Metrics {
+    /// Create an empty set of metrics
+    pub fn new() -> Self {
+        Self {
+            controllers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record a sample from the ingest stream
+    pub fn observe(&self, sample: &PidControllerData) {
+        let mut controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let controller = controllers.entry(sample.controller_id.clone()).or_default();
+        controller.samples += 1;
+        controller.error = sample.error;
+        controller.output = sample.output;
+        controller.setpoint = sample.setpoint;
+        controller.process_value = sample.process_value;
+        controller.saturated = sample.saturated;
+        controller.last_seen = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+    }
+
+    /// Record every broadcast sample in a background task
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        tokio::spawn(async move {
+            loop {
+                match samples.recv().await {
+                    Ok(sample) => self.observe(&sample),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Metrics fell behind; skipped {} samples", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Render every metric in the Prometheus text format
+    pub fn render(&self, ws_clients: usize, alerts: &AlertEngine, guard: &IngestGuard) -> String {
+        let mut out = String::new();
+        let controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+
+        let per_controller: [PerController; 7] = [
+            (
+                "pidgeoneer_samples_received_total",
+                "counter",
+                "Samples ingested from the controller",
+                |c| c.samples as f64,
+            ),
+            (
+                "pidgeoneer_controller_error",
+                "gauge",
+                "Last reported error (setpoint minus process value)",
+                |c| c.error,
+            ),
+            (
+                "pidgeoneer_controller_output",
+                "gauge",
+                "Last reported controller output",
+                |c| c.output,
+            ),
+            (
+                "pidgeoneer_controller_setpoint",
+                "gauge",
+                "Last reported setpoint",
+                |c| c.setpoint,
+            ),
+            (
+                "pidgeoneer_controller_process_value",
+                "gauge",
+                "Last reported process value",
+                |c| c.process_value,
+            ),
+            (
+                "pidgeoneer_controller_saturated",
+                "gauge",
+                "1 if the last reported output was clamped to its limits",
+                |c| if c.saturated { 1.0 } else { 0.0 },
+            ),
+            (
+                "pidgeoneer_controller_last_sample_timestamp_seconds",
+                "gauge",
+                "Server time the last sample arrived, in seconds since the Unix epoch",
+                |c| c.last_seen,
+            ),
+        ];
+        for (name, kind, help, value) in per_controller {
+            help_and_type(&mut out, name, kind, help);
+            for (controller_id, controller) in controllers.iter() {
+                let _ = writeln!(
+                    out,
+                    "{}{{controller_id=\"{}\"}} {}",
+                    name,
+                    escape(controller_id),
+                    format_value(value(controller))
+                );
+            }
+        }
+
+        help_and_type(
+            &mut out,
+            "pidgeoneer_websocket_clients",
+            "gauge",
+            "Browser sessions currently connected",
+        );
+        let _ = writeln!(out, "pidgeoneer_websocket_clients {}", ws_clients);
+
+        help_and_type(
+            &mut out,
+            "pidgeoneer_samples_rejected_total",
+            "counter",
+            "Samples dropped by ingest validation or rate limiting, by reason",
+        );
+        for (controller_id, reason, count) in guard.rejected_counts() {
+            let _ = writeln!(
+                out,
+                "pidgeoneer_samples_rejected_total{{controller_id=\"{}\",reason=\"{}\"}} {}",
+                escape(&controller_id),
+                reason,
+                count
+            );
+        }
+
+        help_and_type(
+            &mut out,
+            "pidgeoneer_alert_firing",
+            "gauge",
+            "1 for each alert rule currently firing, by rule and controller",
+        );
+        for alarm in alerts.active() {
+            let _ = writeln!(
+                out,
+                "pidgeoneer_alert_firing{{rule=\"{}\",controller_id=\"{}\"}} 1",
+                escape(&alarm.rule),
+                escape(&alarm.controller_id)
+            );
+        }
+        out
+    }
+}
+
+fn help_and_type(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value as the text format requires
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Prometheus spells non-finite values `NaN`, `+Inf`, and `-Inf`
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// `GET /metrics` — Prometheus scrape endpoint
+pub async fn metrics_handler(
+    metrics: Arc<Metrics>,
+    ws_state: Arc<WebSocketState>,
+    alerts: Arc<AlertEngine>,
+    guard: Arc<IngestGuard>,
+) -> Response {
+    (
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        metrics.render(ws_state.client_count(), &alerts, &guard),
+    )
+        .into_response()
+}
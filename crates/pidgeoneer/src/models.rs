@@ -1,17 +1,411 @@
 use serde::{Deserialize, Serialize};
 
+fn default_schema_version() -> u32 {
+    1
+}
+
+fn default_mode() -> String {
+    "automatic".to_string()
+}
+
 /// Data structure representing PID controller data sent by the backend
+///
+/// Mirrors pidgeon's `ControllerDebugData`. Fields added in schema version 2
+/// default when older producers omit them, as do fields left out by a
+/// [`Subscription`].
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PidControllerData {
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     pub timestamp: u64,
     pub controller_id: String,
     #[serde(default)]
     pub setpoint: f64,
     #[serde(default)]
     pub process_value: f64,
+    #[serde(default)]
     pub error: f64,
+    #[serde(default)]
     pub output: f64,
+    #[serde(default)]
     pub p_term: f64,
+    #[serde(default)]
     pub i_term: f64,
+    #[serde(default)]
     pub d_term: f64,
+    #[serde(default)]
+    pub dt: f64,
+    #[serde(default)]
+    pub kp: f64,
+    #[serde(default)]
+    pub ki: f64,
+    #[serde(default)]
+    pub kd: f64,
+    #[serde(default)]
+    pub saturated: bool,
+    #[serde(default = "default_mode")]
+    pub mode: String,
+}
+
+impl PidControllerData {
+    /// Whether the sample carries gains, dt, saturation, and mode
+    pub fn has_loop_context(&self) -> bool {
+        self.schema_version >= 2
+    }
+}
+
+/// Fields sent regardless of a [`Subscription`]'s field list
+pub const ALWAYS_SENT_FIELDS: &[&str] = &["schema_version", "timestamp", "controller_id"];
+
+/// WebSocket subprotocol for samples as MessagePack maps in binary frames
+pub const MSGPACK_PROTOCOL: &str = "pidgeoneer.msgpack";
+
+/// WebSocket subprotocol for samples as JSON text frames, also used when a
+/// client asks for no subprotocol
+pub const JSON_PROTOCOL: &str = "pidgeoneer.json";
+
+/// Selects which samples a WebSocket session receives
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Controllers to forward; empty forwards every controller
+    #[serde(default)]
+    pub controller_ids: Vec<String>,
+    /// `PidControllerData` fields to forward; empty forwards every field
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+impl Subscription {
+    /// Whether `data` comes from a subscribed controller
+    pub fn matches(&self, data: &PidControllerData) -> bool {
+        self.controller_ids.is_empty() || self.controller_ids.contains(&data.controller_id)
+    }
+
+    /// Whether `field` should be forwarded
+    pub fn includes_field(&self, field: &str) -> bool {
+        self.fields.is_empty()
+            || ALWAYS_SENT_FIELDS.contains(&field)
+            || self.fields.iter().any(|f| f == field)
+    }
+}
+
+/// Message sent by the browser over the WebSocket
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Replace the session's subscription
+    Subscribe(Subscription),
+}
+
+/// What a dashboard session is allowed to do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Watch live data and history
+    Viewer,
+    /// Everything a viewer can do, plus send commands to controllers and
+    /// silence alerts
+    Operator,
+    /// Everything an operator can do, plus change controller configs and run
+    /// autotune through the admin API
+    Admin,
+}
+
+/// Response of `GET /api/session`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// Role granted to the current session
+    pub role: Role,
+    /// `false` when the server runs without tokens and everyone shares one
+    /// role: viewer, or admin with `--insecure-no-auth`
+    pub auth_enabled: bool,
+}
+
+/// Body of `POST /api/login`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoginRequest {
+    /// One of the tokens configured on the server
+    pub token: String,
+}
+
+/// An alert rule currently firing for one controller
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Alarm {
+    /// Name of the rule that fired
+    pub rule: String,
+    /// Controller the rule fired for
+    pub controller_id: String,
+    /// Human-readable description of the condition
+    pub message: String,
+    /// Milliseconds since the Unix epoch when the alarm was raised
+    pub since: u64,
+}
+
+/// Liveness of a controller, judged by how recently it sent a sample
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Presence {
+    /// Sent a sample within the stale threshold
+    Online,
+    /// Quiet for longer than the stale threshold
+    Stale,
+    /// Quiet for longer than the offline threshold
+    Offline,
+}
+
+/// Entry of `GET /api/controllers`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControllerPresence {
+    pub controller_id: String,
+    pub presence: Presence,
+    /// Milliseconds since the Unix epoch when the last sample arrived
+    pub last_seen: u64,
+}
+
+/// Entry of `GET /api/fleet`: one controller at a glance
+///
+/// Values are `None` for controllers known only from storage that have not
+/// sent a sample since the server started.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetEntry {
+    pub controller_id: String,
+    pub presence: Presence,
+    /// Milliseconds since the Unix epoch when the last sample arrived
+    pub last_seen: u64,
+    pub setpoint: Option<f64>,
+    pub process_value: Option<f64>,
+    pub error: Option<f64>,
+    pub output: Option<f64>,
+    /// Mean `|error|` over the last 200 samples
+    pub mean_abs_error: Option<f64>,
+    /// Fraction of the last 200 samples with a saturated output
+    pub saturation_duty: Option<f64>,
+    /// Alarms currently firing for the controller
+    pub alarms: usize,
+    /// 0 (needs attention) to 100 (healthy), from error, saturation, alarms,
+    /// and presence
+    pub health: f64,
+}
+
+/// How a controller responded to one setpoint change
+///
+/// Computed server-side from the ingested stream, mirroring pidgeon's
+/// `ControllerStatistics` for loops the dashboard only sees remotely. Times
+/// are seconds since the change, measured on the controller's clock.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepResponse {
+    pub controller_id: String,
+    /// Milliseconds since the Unix epoch of the first sample at the new setpoint
+    pub started_at: u64,
+    /// Setpoint before the change; equal to `setpoint` for the first response seen
+    pub from_setpoint: f64,
+    pub setpoint: f64,
+    /// Mean `|error|` across the response
+    pub average_error: f64,
+    /// Furthest the process value went past the setpoint, in process units
+    pub max_overshoot: f64,
+    /// Until `|error|` first entered the settled band; `None` if it never has
+    pub rise_time: Option<f64>,
+    /// Until `|error|` entered the settled band for good; `None` while outside it
+    pub settling_time: Option<f64>,
+    /// Integral of `|error|` over time
+    pub iae: f64,
+    /// Fraction of samples with a saturated output
+    pub saturation_duty: f64,
+    /// Samples seen at this setpoint
+    pub samples: u64,
+    /// Whether a later setpoint change ended this response
+    pub complete: bool,
+}
+
+/// Entry of `GET /api/timing`: how regularly a controller's loop runs
+///
+/// Durations are seconds, taken from the samples' `dt` over the last 1000
+/// samples; the counters cover everything since the server started.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoopTiming {
+    pub controller_id: String,
+    /// Samples with a `dt` seen
+    pub samples: u64,
+    /// Median `dt`, the loop's intended period
+    pub nominal_dt: f64,
+    pub mean_dt: f64,
+    pub min_dt: f64,
+    pub max_dt: f64,
+    /// Standard deviation of `dt`
+    pub jitter: f64,
+    /// Steps that took more than 1.5 times the nominal period
+    pub missed_deadlines: u64,
+    /// Equal-width `dt` bins from `min_dt` to `max_dt`
+    pub histogram: Vec<TimingBucket>,
+}
+
+/// One bar of a [`LoopTiming`] histogram
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimingBucket {
+    /// Lower bound of the bin, in seconds
+    pub from: f64,
+    /// Upper bound of the bin, in seconds
+    pub to: f64,
+    pub count: u64,
+}
+
+/// Response of `GET /api/spectrum`: frequency content of a controller's error
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorSpectrum {
+    pub controller_id: String,
+    /// Samples transformed, a power of two
+    pub samples: usize,
+    /// Assumed sampling rate in Hz, from the median `dt`
+    pub sample_rate: f64,
+    /// Frequency of the strongest component, in Hz
+    pub peak_frequency: f64,
+    /// From the lowest nonzero frequency up to Nyquist
+    pub bins: Vec<SpectrumBin>,
+}
+
+/// One frequency of an [`ErrorSpectrum`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpectrumBin {
+    /// Hz
+    pub frequency: f64,
+    /// Amplitude of the component, in the error's units
+    pub amplitude: f64,
+}
+
+/// Response of `GET /api/suggestions`: gains proposed from recorded data
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TuningSuggestions {
+    pub controller_id: String,
+    /// Recorded samples the model was identified from
+    pub samples: usize,
+    /// Seconds between samples assumed by the identification
+    pub dt: f64,
+    pub model: ProcessModel,
+    /// How much of the recorded process value the model reproduces, 0 to 1
+    pub fit: f64,
+    /// The controller's gains at the end of the recording, previewed the
+    /// same way; absent before schema v2
+    pub current: Option<GainSuggestion>,
+    /// One per tuning rule that applies to the model
+    pub suggestions: Vec<GainSuggestion>,
+}
+
+/// First-order-plus-dead-time process identified from a recording
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProcessModel {
+    /// Change in process value per unit of output, at steady state
+    pub gain: f64,
+    /// Seconds
+    pub time_constant: f64,
+    /// Seconds
+    pub dead_time: f64,
+}
+
+/// Gains from one tuning rule and their predicted response
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GainSuggestion {
+    /// Rule name such as `simc` or `zn-pid`, or `current`
+    pub rule: String,
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    /// Peak overshoot of the predicted response, as a fraction of the step
+    pub overshoot: f64,
+    /// Seconds until the predicted response stays within 2% of the step,
+    /// if it does
+    pub settling_time: Option<f64>,
+    /// `(seconds, process value)` after a unit setpoint step
+    pub response: Vec<(f64, f64)>,
+}
+
+/// Body of `POST /api/commands`: changes to send to one controller
+///
+/// Each present field becomes a signed pidgeon `RemoteCommand`; the gains are
+/// sent together so the controller applies all of them or none.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TuningRequest {
+    pub controller_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setpoint: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kp: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ki: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kd: Option<f64>,
+}
+
+/// Where a remote command stands, according to the controller's audit events
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CommandStatus {
+    /// Published, with no audit event from the controller yet
+    Pending,
+    /// The controller applied the change
+    Applied,
+    /// The controller, or the broker, refused the change
+    Rejected { reason: String },
+}
+
+/// Audit log entry for one remote command
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CommandRecord {
+    pub command_id: String,
+    pub controller_id: String,
+    pub issued_by: String,
+    /// Milliseconds since the Unix epoch when the command was issued or,
+    /// for commands issued elsewhere, first audited
+    pub issued_at: u64,
+    /// Human-readable description of the requested change
+    pub action: String,
+    #[serde(flatten)]
+    pub status: CommandStatus,
+    /// Milliseconds since the Unix epoch when the controller handled it
+    pub resolved_at: Option<u64>,
+}
+
+/// Response of `GET /api/commands`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandLog {
+    /// Whether the server was started with a command secret
+    pub enabled: bool,
+    /// Recent commands, newest first
+    pub commands: Vec<CommandRecord>,
+}
+
+/// Summary of one controller's samples over a fixed-width time bucket
+///
+/// Written when retention prunes raw samples, so long-term trends survive.
+/// Unsuffixed values are averages over the bucket.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub controller_id: String,
+    /// Milliseconds since the Unix epoch at the start of the bucket
+    pub bucket_start: u64,
+    pub bucket_secs: u64,
+    /// Raw samples summarized
+    pub samples: u64,
+    pub setpoint: f64,
+    pub process_value: f64,
+    pub process_value_min: f64,
+    pub process_value_max: f64,
+    pub error: f64,
+    pub error_min: f64,
+    pub error_max: f64,
+    pub output: f64,
+    pub output_min: f64,
+    pub output_max: f64,
+    /// Fraction of samples with a saturated output
+    pub saturated_fraction: f64,
+}
+
+/// A sample the ingest guard refused to forward
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuarantinedSample {
+    /// Server time the sample arrived, in milliseconds since the Unix epoch
+    pub received_at: u64,
+    /// Why it was rejected, e.g. `non_finite` or `out_of_order`
+    pub reason: String,
+    pub sample: PidControllerData,
 }
@@ -0,0 +1,71 @@
+use crate::config::MqttSource;
+use crate::ingest::{decode_payload, Ingest, IngestSource};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use log::*;
+use rand::Rng;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::time::Duration;
+
+/// Requests buffered between the client handle and the event loop
+const REQUEST_CAPACITY: usize = 16;
+/// Interval between keepalive pings
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+/// Wait before reconnecting after a connection error
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Largest accepted payload
+const MAX_PACKET_SIZE: usize = 1024 * 1024;
+
+impl IngestSource for MqttSource {
+    /// Subscribe to the topic filter and decode every publish on it
+    ///
+    /// MQTT 3.1.1 carries no content type, so payloads are decoded by trying
+    /// JSON, then MessagePack, then CBOR. The subscription is renewed on
+    /// every reconnect.
+    fn run(self: Box<Self>, ingest: Ingest) -> BoxFuture<'static, ()> {
+        let source = *self;
+        async move {
+            let client_id = format!("pidgeoneer-{:08x}", rand::thread_rng().gen::<u32>());
+            let mut options = MqttOptions::new(client_id, &source.host, source.port);
+            options.set_keep_alive(KEEP_ALIVE);
+            options.set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE);
+            if let Some((user, password)) = &source.credentials {
+                options.set_credentials(user, password);
+            }
+            let (client, mut event_loop) = AsyncClient::new(options, REQUEST_CAPACITY);
+
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        info!("✅ Connected to MQTT broker at {}", source);
+                        ingest.connected();
+                        if let Err(e) = client.try_subscribe(&source.topic, QoS::AtMostOnce) {
+                            error!("Failed to subscribe to {}: {}", source.topic, e);
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        match decode_payload(None, &publish.payload) {
+                            Ok(pid_data) => {
+                                debug!(
+                                    "📥 Received PID data from controller {} on {}",
+                                    pid_data.controller_id, publish.topic
+                                );
+                                ingest.publish(pid_data);
+                            }
+                            Err(e) => {
+                                error!("Failed to parse MQTT message on {}: {}", publish.topic, e);
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        error!("MQTT connection to {} failed: {}", source, e);
+                        ingest.disconnected(&e);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+}
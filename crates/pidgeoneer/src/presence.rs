@@ -0,0 +1,105 @@
+use crate::models::{ControllerPresence, PidControllerData, Presence};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Tracks when each controller last sent a sample
+///
+/// Arrival times are taken from the server's clock, so a controller with a
+/// skewed clock is still judged correctly. Controllers seeded from storage
+/// use their newest stored sample's timestamp.
+pub struct PresenceTracker {
+    stale_after: Duration,
+    offline_after: Duration,
+    last_seen: Mutex<HashMap<String, u64>>,
+}
+
+impl PresenceTracker {
+    /// Create a tracker that marks controllers stale after `stale_after` and
+    /// offline after `offline_after` without a sample
+    pub fn new(stale_after: Duration, offline_after: Duration) -> Self {
+        Self {
+            stale_after,
+            offline_after,
+            last_seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remember controllers that sent samples before the server started
+    pub fn seed(&self, last_seen: impl IntoIterator<Item = (String, u64)>) {
+        let mut known = self.last_seen.lock().unwrap_or_else(|e| e.into_inner());
+        for (controller_id, timestamp) in last_seen {
+            let seen = known.entry(controller_id).or_insert(timestamp);
+            *seen = (*seen).max(timestamp);
+        }
+    }
+
+    /// Record a sample from `controller_id` arriving now
+    pub fn observe(&self, controller_id: &str) {
+        let now = epoch_millis();
+        let mut known = self.last_seen.lock().unwrap_or_else(|e| e.into_inner());
+        match known.get_mut(controller_id) {
+            Some(seen) => *seen = now,
+            None => {
+                info!("Controller {} is online", controller_id);
+                known.insert(controller_id.to_string(), now);
+            }
+        }
+    }
+
+    /// Every known controller's presence, sorted by id
+    pub fn snapshot(&self) -> Vec<ControllerPresence> {
+        let now = epoch_millis();
+        let known = self.last_seen.lock().unwrap_or_else(|e| e.into_inner());
+        let mut controllers: Vec<ControllerPresence> = known
+            .iter()
+            .map(|(controller_id, last_seen)| ControllerPresence {
+                controller_id: controller_id.clone(),
+                presence: self.presence(now.saturating_sub(*last_seen)),
+                last_seen: *last_seen,
+            })
+            .collect();
+        controllers.sort_by(|a, b| a.controller_id.cmp(&b.controller_id));
+        controllers
+    }
+
+    fn presence(&self, quiet_millis: u64) -> Presence {
+        let quiet = Duration::from_millis(quiet_millis);
+        if quiet >= self.offline_after {
+            Presence::Offline
+        } else if quiet >= self.stale_after {
+            Presence::Stale
+        } else {
+            Presence::Online
+        }
+    }
+
+    /// Record every broadcast sample in a background task
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        tokio::spawn(async move {
+            loop {
+                match samples.recv().await {
+                    Ok(sample) => self.observe(&sample.controller_id),
+                    // Missed samples only delay the next observation
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// `GET /api/controllers` — every known controller and its presence
+pub async fn controllers_handler(tracker: Arc<PresenceTracker>) -> Response {
+    Json(tracker.snapshot()).into_response()
+}
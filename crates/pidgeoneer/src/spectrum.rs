@@ -0,0 +1,191 @@
+use crate::models::{ErrorSpectrum, PidControllerData, SpectrumBin};
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Error samples kept per controller; a power of two
+const WINDOW: usize = 512;
+/// Fewest samples worth transforming
+const MIN_SAMPLES: usize = 64;
+
+/// Which controller's spectrum to compute
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SpectrumQuery {
+    pub controller_id: Option<String>,
+}
+
+/// One recent sample: its `dt`, arrival timestamp, and error
+type ErrorSample = (f64, u64, f64);
+
+/// Frequency content of each controller's recent error signal
+///
+/// Oscillation shows up as a peak at the loop's ringing frequency and sensor
+/// noise as a raised floor toward Nyquist, both easier to spot here than in
+/// the time domain. The spectrum is computed on request with a Hann-windowed
+/// FFT over the newest power-of-two samples, assuming they are evenly spaced
+/// at the median `dt` (or, before schema v2, the median timestamp step).
+pub struct SpectrumAnalyzer {
+    controllers: Mutex<HashMap<String, VecDeque<ErrorSample>>>,
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectrumAnalyzer {
+    /// Create an analyzer with no history
+    pub fn new() -> Self {
+        Self {
+            controllers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a sample's error
+    pub fn observe(&self, sample: &PidControllerData) {
+        let mut controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let recent = controllers.entry(sample.controller_id.clone()).or_default();
+        if recent.len() == WINDOW {
+            recent.pop_front();
+        }
+        recent.push_back((sample.dt, sample.timestamp, sample.error));
+    }
+
+    /// Spectrum of `controller_id`'s error, or `None` without enough samples
+    pub fn spectrum(&self, controller_id: &str) -> Option<ErrorSpectrum> {
+        let recent: Vec<ErrorSample> = {
+            let controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+            controllers.get(controller_id)?.iter().copied().collect()
+        };
+        if recent.len() < MIN_SAMPLES {
+            return None;
+        }
+        let n = 1 << recent.len().ilog2();
+        let recent = &recent[recent.len() - n..];
+
+        let mut steps: Vec<f64> = recent
+            .iter()
+            .map(|(dt, _, _)| *dt)
+            .filter(|dt| *dt > 0.0)
+            .collect();
+        if steps.len() < n / 2 {
+            steps = recent
+                .windows(2)
+                .map(|pair| pair[1].1.saturating_sub(pair[0].1) as f64 / 1000.0)
+                .filter(|step| *step > 0.0)
+                .collect();
+        }
+        if steps.is_empty() {
+            return None;
+        }
+        steps.sort_by(f64::total_cmp);
+        let sample_rate = 1.0 / steps[steps.len() / 2];
+
+        let mean = recent.iter().map(|(_, _, error)| error).sum::<f64>() / n as f64;
+        let window: Vec<f64> = (0..n)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / n as f64).cos())
+            .collect();
+        let mut signal: Vec<(f64, f64)> = recent
+            .iter()
+            .zip(&window)
+            .map(|((_, _, error), w)| ((error - mean) * w, 0.0))
+            .collect();
+        fft(&mut signal);
+
+        // Scale so a sinusoid of amplitude A shows as A
+        let gain = 2.0 / window.iter().sum::<f64>();
+        let bins: Vec<SpectrumBin> = (1..=n / 2)
+            .map(|k| SpectrumBin {
+                frequency: k as f64 * sample_rate / n as f64,
+                amplitude: signal[k].0.hypot(signal[k].1) * gain,
+            })
+            .collect();
+        let peak_frequency = bins
+            .iter()
+            .max_by(|a, b| a.amplitude.total_cmp(&b.amplitude))
+            .map_or(0.0, |bin| bin.frequency);
+
+        Some(ErrorSpectrum {
+            controller_id: controller_id.to_string(),
+            samples: n,
+            sample_rate,
+            peak_frequency,
+            bins,
+        })
+    }
+
+    /// Record every broadcast sample in a background task
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        tokio::spawn(async move {
+            loop {
+                match samples.recv().await {
+                    Ok(sample) => self.observe(&sample),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Spectrum analyzer fell behind; skipped {} samples", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// In-place iterative radix-2 FFT of `(re, im)` pairs; the length must be a
+/// power of two
+fn fft(data: &mut [(f64, f64)]) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (re, im) = data[start + k + len / 2];
+                let twiddled = (re * cos - im * sin, re * sin + im * cos);
+                let even = data[start + k];
+                data[start + k] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                data[start + k + len / 2] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// `GET /api/spectrum?controller_id=` — amplitude spectrum of the recent error
+pub async fn spectrum_handler(
+    analyzer: Arc<SpectrumAnalyzer>,
+    Query(query): Query<SpectrumQuery>,
+) -> Response {
+    let Some(controller_id) = query.controller_id else {
+        return (StatusCode::BAD_REQUEST, "controller_id is required").into_response();
+    };
+    match analyzer.spectrum(&controller_id) {
+        Some(spectrum) => Json(spectrum).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("not enough samples from '{}' yet", controller_id),
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,182 @@
+use crate::models::{PidControllerData, StepResponse};
+use axum::extract::Query;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Completed responses kept per controller
+const RESPONSES_KEPT: usize = 20;
+
+/// Setpoint changes smaller than this are treated as noise
+const SETPOINT_EPSILON: f64 = 1e-9;
+
+/// Filters for a statistics query
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StatisticsQuery {
+    /// Only return responses from this controller
+    pub controller_id: Option<String>,
+}
+
+/// Running totals for the response in progress
+struct Episode {
+    response: StepResponse,
+    /// Sign of the error at the change; the process value overshoots when
+    /// the error takes the opposite sign
+    direction: f64,
+    error_sum: f64,
+    saturated: u64,
+}
+
+impl Episode {
+    fn start(sample: &PidControllerData, from_setpoint: f64) -> Self {
+        Self {
+            response: StepResponse {
+                controller_id: sample.controller_id.clone(),
+                started_at: sample.timestamp,
+                from_setpoint,
+                setpoint: sample.setpoint,
+                average_error: 0.0,
+                max_overshoot: 0.0,
+                rise_time: None,
+                settling_time: None,
+                iae: 0.0,
+                saturation_duty: 0.0,
+                samples: 0,
+                complete: false,
+            },
+            direction: sample.error.signum(),
+            error_sum: 0.0,
+            saturated: 0,
+        }
+    }
+
+    fn update(&mut self, sample: &PidControllerData, settled_threshold: f64) {
+        let response = &mut self.response;
+        let error = sample.error.abs();
+        let elapsed = sample.timestamp.saturating_sub(response.started_at) as f64 / 1000.0;
+
+        response.samples += 1;
+        self.error_sum += error;
+        response.average_error = self.error_sum / response.samples as f64;
+        response.iae += error * sample.dt.max(0.0);
+        if sample.saturated {
+            self.saturated += 1;
+        }
+        response.saturation_duty = self.saturated as f64 / response.samples as f64;
+        response.max_overshoot = response.max_overshoot.max(-sample.error * self.direction);
+
+        if error <= settled_threshold {
+            response.rise_time.get_or_insert(elapsed);
+            response.settling_time.get_or_insert(elapsed);
+        } else {
+            response.settling_time = None;
+        }
+    }
+}
+
+/// One controller's current and recent responses
+struct ControllerResponses {
+    current: Episode,
+    completed: VecDeque<StepResponse>,
+}
+
+/// Computes [`StepResponse`] statistics for every controller
+///
+/// Each setpoint change starts a new response; the first sample from a
+/// controller starts one too, so loops that never change setpoint still
+/// report how they settled.
+pub struct ResponseStatistics {
+    settled_threshold: f64,
+    controllers: Mutex<HashMap<String, ControllerResponses>>,
+}
+
+impl ResponseStatistics {
+    /// Create a tracker that considers a loop settled once `|error|` is
+    /// within `settled_threshold`, as pidgeon's `with_settled_threshold` does
+    pub fn new(settled_threshold: f64) -> Self {
+        Self {
+            settled_threshold,
+            controllers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fold a sample into its controller's current response
+    pub fn observe(&self, sample: &PidControllerData) {
+        let mut controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let responses = controllers
+            .entry(sample.controller_id.clone())
+            .or_insert_with(|| ControllerResponses {
+                current: Episode::start(sample, sample.setpoint),
+                completed: VecDeque::new(),
+            });
+
+        let previous = responses.current.response.setpoint;
+        if (sample.setpoint - previous).abs() > SETPOINT_EPSILON {
+            debug!(
+                "Controller {} setpoint changed from {} to {}",
+                sample.controller_id, previous, sample.setpoint
+            );
+            let mut finished =
+                std::mem::replace(&mut responses.current, Episode::start(sample, previous))
+                    .response;
+            finished.complete = true;
+            if responses.completed.len() == RESPONSES_KEPT {
+                responses.completed.pop_front();
+            }
+            responses.completed.push_back(finished);
+        }
+        responses.current.update(sample, self.settled_threshold);
+    }
+
+    /// Recent responses, oldest first, for one controller or all of them
+    pub fn responses(&self, controller_id: Option<&str>) -> Vec<StepResponse> {
+        let controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let mut responses: Vec<StepResponse> = controllers
+            .iter()
+            .filter(|(id, _)| controller_id.is_none_or(|wanted| wanted == id.as_str()))
+            .flat_map(|(_, responses)| {
+                responses
+                    .completed
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(responses.current.response.clone()))
+            })
+            .collect();
+        responses.sort_by(|a, b| {
+            a.started_at
+                .cmp(&b.started_at)
+                .then_with(|| a.controller_id.cmp(&b.controller_id))
+        });
+        responses
+    }
+
+    /// Fold every broadcast sample in a background task
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        tokio::spawn(async move {
+            loop {
+                match samples.recv().await {
+                    Ok(sample) => self.observe(&sample),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!(
+                            "Response statistics fell behind; skipped {} samples",
+                            missed
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// `GET /api/statistics?controller_id=` — recent step responses, oldest first
+pub async fn statistics_handler(
+    statistics: Arc<ResponseStatistics>,
+    Query(query): Query<StatisticsQuery>,
+) -> Response {
+    Json(statistics.responses(query.controller_id.as_deref())).into_response()
+}
@@ -0,0 +1,431 @@
+use crate::models::{PidControllerData, Rollup};
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use serde::Deserialize;
+use sqlx::Row;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Database backend selected at compile time
+#[cfg(feature = "postgres")]
+type Db = sqlx::Postgres;
+#[cfg(not(feature = "postgres"))]
+type Db = sqlx::Sqlite;
+
+/// Database used when `PIDGEONEER_DATABASE_URL` is not set
+#[cfg(feature = "postgres")]
+pub const DEFAULT_DATABASE_URL: &str = "postgres://pidgeoneer@localhost/pidgeoneer";
+#[cfg(not(feature = "postgres"))]
+pub const DEFAULT_DATABASE_URL: &str = "sqlite://pidgeoneer.db?mode=rwc";
+
+/// Samples buffered between the Iggy consumer and the database writer
+const WRITE_QUEUE: usize = 10_000;
+/// Samples written per transaction
+const WRITE_BATCH: usize = 500;
+/// Samples returned by `/api/history` when no limit is given
+const DEFAULT_HISTORY_LIMIT: usize = 1_000;
+/// Upper bound on samples returned by a single `/api/history` request
+const MAX_HISTORY_LIMIT: usize = 50_000;
+/// How often the retention policy is applied
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+// Column types are shared by SQLite and Postgres
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS samples (
+        controller_id TEXT NOT NULL,
+        timestamp BIGINT NOT NULL,
+        schema_version BIGINT NOT NULL,
+        setpoint DOUBLE PRECISION NOT NULL,
+        process_value DOUBLE PRECISION NOT NULL,
+        error DOUBLE PRECISION NOT NULL,
+        output DOUBLE PRECISION NOT NULL,
+        p_term DOUBLE PRECISION NOT NULL,
+        i_term DOUBLE PRECISION NOT NULL,
+        d_term DOUBLE PRECISION NOT NULL,
+        dt DOUBLE PRECISION NOT NULL,
+        kp DOUBLE PRECISION NOT NULL,
+        ki DOUBLE PRECISION NOT NULL,
+        kd DOUBLE PRECISION NOT NULL,
+        saturated BOOLEAN NOT NULL,
+        mode TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS samples_controller_time ON samples (controller_id, timestamp)",
+    "CREATE INDEX IF NOT EXISTS samples_time ON samples (timestamp)",
+    "CREATE TABLE IF NOT EXISTS rollups (
+        controller_id TEXT NOT NULL,
+        bucket_start BIGINT NOT NULL,
+        bucket_secs BIGINT NOT NULL,
+        samples BIGINT NOT NULL,
+        setpoint DOUBLE PRECISION NOT NULL,
+        process_value DOUBLE PRECISION NOT NULL,
+        process_value_min DOUBLE PRECISION NOT NULL,
+        process_value_max DOUBLE PRECISION NOT NULL,
+        error DOUBLE PRECISION NOT NULL,
+        error_min DOUBLE PRECISION NOT NULL,
+        error_max DOUBLE PRECISION NOT NULL,
+        output DOUBLE PRECISION NOT NULL,
+        output_min DOUBLE PRECISION NOT NULL,
+        output_max DOUBLE PRECISION NOT NULL,
+        saturated_fraction DOUBLE PRECISION NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS rollups_controller_time ON rollups (controller_id, bucket_start)",
+];
+
+const INSERT: &str = "INSERT INTO samples (
+        controller_id, timestamp, schema_version, setpoint, process_value, error, output,
+        p_term, i_term, d_term, dt, kp, ki, kd, saturated, mode
+    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)";
+
+const SELECT: &str = "SELECT
+        controller_id, timestamp, schema_version, setpoint, process_value, error, output,
+        p_term, i_term, d_term, dt, kp, ki, kd, saturated, mode
+    FROM samples
+    WHERE ($1 IS NULL OR controller_id = $1) AND timestamp >= $2 AND timestamp <= $3
+    ORDER BY timestamp DESC
+    LIMIT $4";
+
+// Buckets are aligned to multiples of $2 milliseconds since the epoch
+const ROLLUP: &str = "INSERT INTO rollups
+    SELECT controller_id, (timestamp / $2) * $2, $3, COUNT(*),
+        AVG(setpoint),
+        AVG(process_value), MIN(process_value), MAX(process_value),
+        AVG(error), MIN(error), MAX(error),
+        AVG(output), MIN(output), MAX(output),
+        AVG(CASE WHEN saturated THEN 1.0 ELSE 0.0 END)
+    FROM samples
+    WHERE timestamp < $1
+    GROUP BY controller_id, timestamp / $2";
+
+const SELECT_ROLLUPS: &str = "SELECT
+        controller_id, bucket_start, bucket_secs, samples, setpoint,
+        process_value, process_value_min, process_value_max,
+        error, error_min, error_max, output, output_min, output_max, saturated_fraction
+    FROM rollups
+    WHERE ($1 IS NULL OR controller_id = $1) AND bucket_start >= $2 AND bucket_start <= $3
+    ORDER BY bucket_start DESC
+    LIMIT $4";
+
+/// How long stored samples are kept
+///
+/// Samples older than `max_age`, and the oldest samples beyond
+/// `max_samples`, are deleted by [`Storage::spawn_pruner`]. With a
+/// `rollup_interval`, they are first averaged into per-controller buckets
+/// that are kept for `rollup_max_age`, or forever.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    /// Delete samples older than this
+    pub max_age: Option<Duration>,
+    /// Keep at most this many samples across all controllers
+    pub max_samples: Option<u64>,
+    /// Width of the rollup buckets pruned samples are summarized into
+    pub rollup_interval: Option<Duration>,
+    /// Delete rollups older than this
+    pub rollup_max_age: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Whether anything is ever pruned
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_samples.is_some() || self.rollup_max_age.is_some()
+    }
+}
+
+/// Filters for a history query
+///
+/// Deserialized from the `/api/history` query string. Timestamps are
+/// milliseconds since the Unix epoch and both bounds are inclusive.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    /// Only return samples from this controller
+    pub controller_id: Option<String>,
+    /// Earliest timestamp to return
+    pub from: Option<u64>,
+    /// Latest timestamp to return
+    pub to: Option<u64>,
+    /// Maximum number of samples; the most recent ones are kept
+    pub limit: Option<usize>,
+}
+
+/// Persistent history of ingested controller samples
+///
+/// Backed by SQLite, or by Postgres with the `postgres` feature.
+#[derive(Debug, Clone)]
+pub struct Storage {
+    pool: sqlx::Pool<Db>,
+}
+
+impl Storage {
+    /// Connect to the database at `url` and create the schema if needed
+    pub async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        let pool = sqlx::pool::PoolOptions::<Db>::new()
+            .max_connections(4)
+            .connect(url)
+            .await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        info!("✅ Opened history database at {}", url);
+        Ok(Self { pool })
+    }
+
+    /// Run a trivial query, to check the database is reachable
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Write samples in a single transaction
+    pub async fn insert(&self, samples: &[PidControllerData]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for sample in samples {
+            sqlx::query(INSERT)
+                .bind(&sample.controller_id)
+                .bind(db_timestamp(sample.timestamp))
+                .bind(i64::from(sample.schema_version))
+                .bind(sample.setpoint)
+                .bind(sample.process_value)
+                .bind(sample.error)
+                .bind(sample.output)
+                .bind(sample.p_term)
+                .bind(sample.i_term)
+                .bind(sample.d_term)
+                .bind(sample.dt)
+                .bind(sample.kp)
+                .bind(sample.ki)
+                .bind(sample.kd)
+                .bind(sample.saturated)
+                .bind(&sample.mode)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Stored samples matching `query`, oldest first
+    pub async fn history(
+        &self,
+        query: &HistoryQuery,
+    ) -> Result<Vec<PidControllerData>, sqlx::Error> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(MAX_HISTORY_LIMIT);
+        let rows = sqlx::query(SELECT)
+            .bind(query.controller_id.as_deref())
+            .bind(query.from.map_or(0, db_timestamp))
+            .bind(query.to.map_or(i64::MAX, db_timestamp))
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut samples = rows
+            .iter()
+            .map(sample_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        samples.reverse();
+        Ok(samples)
+    }
+
+    /// Timestamp of each controller's newest stored sample
+    pub async fn last_seen(&self) -> Result<Vec<(String, u64)>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT controller_id, MAX(timestamp) AS last_seen FROM samples GROUP BY controller_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                let last_seen: i64 = row.try_get("last_seen")?;
+                Ok((row.try_get("controller_id")?, last_seen as u64))
+            })
+            .collect()
+    }
+
+    /// Apply `policy` once, returning the number of samples deleted
+    pub async fn prune(&self, policy: &RetentionPolicy, now: u64) -> Result<u64, sqlx::Error> {
+        let mut cutoff = policy.max_age.map_or(0, |age| {
+            db_timestamp(now).saturating_sub(age.as_millis() as i64)
+        });
+        if let Some(max_samples) = policy.max_samples {
+            let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM samples")
+                .fetch_one(&self.pool)
+                .await?;
+            let excess = count - max_samples.min(i64::MAX as u64) as i64;
+            if excess > 0 {
+                // Everything older than the first sample we keep goes
+                let oldest_kept: i64 = sqlx::query_scalar(
+                    "SELECT timestamp FROM samples ORDER BY timestamp ASC LIMIT 1 OFFSET $1",
+                )
+                .bind(excess)
+                .fetch_one(&self.pool)
+                .await?;
+                cutoff = cutoff.max(oldest_kept);
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let mut deleted = 0;
+        if cutoff > 0 {
+            if let Some(interval) = policy.rollup_interval {
+                // Only roll up whole buckets so none is summarized twice
+                let width = (interval.as_millis() as i64).max(1);
+                cutoff = cutoff / width * width;
+                sqlx::query(ROLLUP)
+                    .bind(cutoff)
+                    .bind(width)
+                    .bind(interval.as_secs() as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            deleted = sqlx::query("DELETE FROM samples WHERE timestamp < $1")
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        if let Some(age) = policy.rollup_max_age {
+            sqlx::query("DELETE FROM rollups WHERE bucket_start < $1")
+                .bind(db_timestamp(now).saturating_sub(age.as_millis() as i64))
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Spawn a task that applies `policy` every minute
+    pub fn spawn_pruner(self, policy: RetentionPolicy) {
+        if !policy.is_enabled() {
+            return;
+        }
+        info!("Pruning history with {:?}", policy);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis() as u64);
+                match self.prune(&policy, now).await {
+                    Ok(0) => {}
+                    Ok(deleted) => info!("Pruned {} samples from history", deleted),
+                    Err(e) => error!("Failed to prune history: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Stored rollups matching `query`, oldest first
+    pub async fn rollups(&self, query: &HistoryQuery) -> Result<Vec<Rollup>, sqlx::Error> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .min(MAX_HISTORY_LIMIT);
+        let rows = sqlx::query(SELECT_ROLLUPS)
+            .bind(query.controller_id.as_deref())
+            .bind(query.from.map_or(0, db_timestamp))
+            .bind(query.to.map_or(i64::MAX, db_timestamp))
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut rollups = rows
+            .iter()
+            .map(rollup_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        rollups.reverse();
+        Ok(rollups)
+    }
+
+    /// Spawn a task that writes samples in batches as they arrive
+    ///
+    /// The returned sender is bounded; callers should use `try_send` so a slow
+    /// database never stalls the live stream.
+    pub fn spawn_writer(self) -> mpsc::Sender<PidControllerData> {
+        let (tx, mut rx) = mpsc::channel(WRITE_QUEUE);
+        tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(WRITE_BATCH);
+            while rx.recv_many(&mut batch, WRITE_BATCH).await > 0 {
+                if let Err(e) = self.insert(&batch).await {
+                    error!("Failed to store {} samples: {}", batch.len(), e);
+                }
+                batch.clear();
+            }
+        });
+        tx
+    }
+}
+
+/// Timestamps are stored as `BIGINT`, which both backends treat as signed
+fn db_timestamp(millis: u64) -> i64 {
+    millis.min(i64::MAX as u64) as i64
+}
+
+fn sample_from_row(row: &<Db as sqlx::Database>::Row) -> Result<PidControllerData, sqlx::Error> {
+    Ok(PidControllerData {
+        schema_version: row.try_get::<i64, _>("schema_version")? as u32,
+        timestamp: row.try_get::<i64, _>("timestamp")? as u64,
+        controller_id: row.try_get("controller_id")?,
+        setpoint: row.try_get("setpoint")?,
+        process_value: row.try_get("process_value")?,
+        error: row.try_get("error")?,
+        output: row.try_get("output")?,
+        p_term: row.try_get("p_term")?,
+        i_term: row.try_get("i_term")?,
+        d_term: row.try_get("d_term")?,
+        dt: row.try_get("dt")?,
+        kp: row.try_get("kp")?,
+        ki: row.try_get("ki")?,
+        kd: row.try_get("kd")?,
+        saturated: row.try_get("saturated")?,
+        mode: row.try_get("mode")?,
+    })
+}
+
+fn rollup_from_row(row: &<Db as sqlx::Database>::Row) -> Result<Rollup, sqlx::Error> {
+    Ok(Rollup {
+        controller_id: row.try_get("controller_id")?,
+        bucket_start: row.try_get::<i64, _>("bucket_start")? as u64,
+        bucket_secs: row.try_get::<i64, _>("bucket_secs")? as u64,
+        samples: row.try_get::<i64, _>("samples")? as u64,
+        setpoint: row.try_get("setpoint")?,
+        process_value: row.try_get("process_value")?,
+        process_value_min: row.try_get("process_value_min")?,
+        process_value_max: row.try_get("process_value_max")?,
+        error: row.try_get("error")?,
+        error_min: row.try_get("error_min")?,
+        error_max: row.try_get("error_max")?,
+        output: row.try_get("output")?,
+        output_min: row.try_get("output_min")?,
+        output_max: row.try_get("output_max")?,
+        saturated_fraction: row.try_get("saturated_fraction")?,
+    })
+}
+
+/// `GET /api/history` — stored samples as a JSON array, oldest first
+pub async fn history_handler(storage: Arc<Storage>, Query(query): Query<HistoryQuery>) -> Response {
+    match storage.history(&query).await {
+        Ok(samples) => Json(samples).into_response(),
+        Err(e) => {
+            error!("History query failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+/// `GET /api/rollups` — downsampled history of pruned samples, oldest first
+///
+/// Takes the same query parameters as `/api/history`.
+pub async fn rollups_handler(storage: Arc<Storage>, Query(query): Query<HistoryQuery>) -> Response {
+    match storage.rollups(&query).await {
+        Ok(rollups) => Json(rollups).into_response(),
+        Err(e) => {
+            error!("Rollup query failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::models::{LoopTiming, PidControllerData, TimingBucket};
+use axum::extract::Query;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Recent `dt` values per controller the histogram is built from
+const WINDOW: usize = 1_000;
+/// Samples between recomputing the nominal period
+const NOMINAL_REFRESH: u64 = 100;
+/// A step longer than this many nominal periods counts as a missed deadline
+const MISSED_DEADLINE_FACTOR: f64 = 1.5;
+/// Bars in each histogram
+const HISTOGRAM_BINS: usize = 20;
+
+/// Filters for a timing query
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TimingQuery {
+    /// Only return this controller's timing
+    pub controller_id: Option<String>,
+}
+
+/// `dt` history of one controller
+#[derive(Default)]
+struct ControllerTiming {
+    samples: u64,
+    recent: VecDeque<f64>,
+    /// Median `dt` of the window, once it has [`NOMINAL_REFRESH`] samples
+    nominal: Option<f64>,
+    missed_deadlines: u64,
+}
+
+impl ControllerTiming {
+    fn observe(&mut self, dt: f64) {
+        if self
+            .nominal
+            .is_some_and(|nominal| dt > nominal * MISSED_DEADLINE_FACTOR)
+        {
+            self.missed_deadlines += 1;
+        }
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(dt);
+        self.samples += 1;
+        if self.samples.is_multiple_of(NOMINAL_REFRESH) {
+            self.nominal = Some(median(&self.recent));
+        }
+    }
+
+    fn summary(&self, controller_id: &str) -> LoopTiming {
+        let count = self.recent.len() as f64;
+        let mean = self.recent.iter().sum::<f64>() / count;
+        let variance = self
+            .recent
+            .iter()
+            .map(|dt| (dt - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        let (min, max) = self
+            .recent
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), dt| {
+                (min.min(*dt), max.max(*dt))
+            });
+
+        let width = (max - min) / HISTOGRAM_BINS as f64;
+        let mut histogram: Vec<TimingBucket> = if width > 0.0 {
+            (0..HISTOGRAM_BINS)
+                .map(|bin| TimingBucket {
+                    from: min + bin as f64 * width,
+                    to: min + (bin + 1) as f64 * width,
+                    count: 0,
+                })
+                .collect()
+        } else {
+            vec![TimingBucket {
+                from: min,
+                to: max,
+                count: 0,
+            }]
+        };
+        for dt in &self.recent {
+            let bin = if width > 0.0 {
+                (((dt - min) / width) as usize).min(HISTOGRAM_BINS - 1)
+            } else {
+                0
+            };
+            histogram[bin].count += 1;
+        }
+
+        LoopTiming {
+            controller_id: controller_id.to_string(),
+            samples: self.samples,
+            nominal_dt: self.nominal.unwrap_or_else(|| median(&self.recent)),
+            mean_dt: mean,
+            min_dt: min,
+            max_dt: max,
+            jitter: variance.sqrt(),
+            missed_deadlines: self.missed_deadlines,
+            histogram,
+        }
+    }
+}
+
+fn median(values: &VecDeque<f64>) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+/// Loop-rate histograms and missed deadlines from each sample's `dt`
+///
+/// Timing problems look like tuning problems on a chart: a loop that runs
+/// late sees bigger errors and acts on stale derivatives. The nominal period
+/// is the median `dt` of recent samples, and a step taking more than 1.5
+/// times that counts as a missed deadline. Samples before schema v2 carry no
+/// `dt` and are skipped.
+pub struct TimingTracker {
+    controllers: Mutex<HashMap<String, ControllerTiming>>,
+}
+
+impl Default for TimingTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimingTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self {
+            controllers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a sample's `dt`
+    pub fn observe(&self, sample: &PidControllerData) {
+        if !sample.has_loop_context() || sample.dt <= 0.0 {
+            return;
+        }
+        let mut controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        controllers
+            .entry(sample.controller_id.clone())
+            .or_default()
+            .observe(sample.dt);
+    }
+
+    /// Timing of `controller_id`, or of every controller, sorted by id
+    pub fn summaries(&self, controller_id: Option<&str>) -> Vec<LoopTiming> {
+        let controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let mut summaries: Vec<LoopTiming> = controllers
+            .iter()
+            .filter(|(id, _)| controller_id.is_none_or(|wanted| wanted == id.as_str()))
+            .map(|(id, timing)| timing.summary(id))
+            .collect();
+        summaries.sort_by(|a, b| a.controller_id.cmp(&b.controller_id));
+        summaries
+    }
+
+    /// Record every broadcast sample in a background task
+    pub fn spawn(self: Arc<Self>, mut samples: broadcast::Receiver<PidControllerData>) {
+        tokio::spawn(async move {
+            loop {
+                match samples.recv().await {
+                    Ok(sample) => self.observe(&sample),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Loop timing fell behind; skipped {} samples", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+/// `GET /api/timing?controller_id=` — `dt` histogram and missed deadlines
+pub async fn timing_handler(
+    timing: Arc<TimingTracker>,
+    Query(query): Query<TimingQuery>,
+) -> Response {
+    Json(timing.summaries(query.controller_id.as_deref())).into_response()
+}
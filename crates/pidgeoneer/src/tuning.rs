@@ -0,0 +1,372 @@
+use crate::config::IggySource;
+use crate::models::{CommandLog, CommandRecord, CommandStatus, Role, TuningRequest};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use iggy::client::{Client, MessageClient, UserClient};
+use iggy::clients::client::IggyClient;
+use iggy::clients::producer::IggyProducer;
+use iggy::consumer::Consumer;
+use iggy::identifier::Identifier;
+use iggy::messages::poll_messages::PollingStrategy;
+use iggy::messages::send_messages::{Message, Partitioning};
+use iggy::utils::duration::IggyDuration;
+use log::*;
+use pidgeon::{AuditEvent, CommandOutcome, RemoteAction, RemoteCommand};
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Commands kept in the audit log
+const LOG_SIZE: usize = 200;
+/// Commands waiting to be published
+const COMMAND_QUEUE: usize = 64;
+/// How often the audit topic is polled
+const AUDIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Wait before reconnecting to the broker
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// Issuer recorded on commands sent from the dashboard
+const ISSUER: &str = "pidgeoneer";
+
+/// Publishes signed tuning commands and follows the controllers' audit events
+///
+/// Commands go to `command_topic` in the command source's stream, where
+/// controllers built with pidgeon's `remote-control` feature pick them up.
+/// Their audit events on `audit_topic` mark each command applied or rejected.
+pub struct Tuner {
+    secret: Vec<u8>,
+    /// Signed messages waiting to be published, with their command IDs
+    outbox: mpsc::Sender<(String, Vec<u8>)>,
+    log: Mutex<VecDeque<CommandRecord>>,
+}
+
+impl Tuner {
+    /// Sign commands with `secret` and relay them through `source`
+    pub fn start(
+        secret: Vec<u8>,
+        source: IggySource,
+        command_topic: String,
+        audit_topic: String,
+    ) -> Arc<Self> {
+        let (outbox, commands) = mpsc::channel(COMMAND_QUEUE);
+        let tuner = Arc::new(Self {
+            secret,
+            outbox,
+            log: Mutex::new(VecDeque::new()),
+        });
+        info!(
+            "🎛️ Sending remote commands to {}/{} on {}",
+            source.stream, command_topic, source.address
+        );
+        tokio::spawn(
+            tuner
+                .clone()
+                .run(source, command_topic, audit_topic, commands),
+        );
+        tuner
+    }
+
+    /// Sign and queue the commands making up `request`
+    pub fn issue(&self, request: TuningRequest) -> Result<Vec<CommandRecord>, String> {
+        if request.controller_id.is_empty() {
+            return Err("controller_id is required".to_string());
+        }
+        let mut actions = Vec::new();
+        if let Some(value) = request.setpoint {
+            actions.push(RemoteAction::SetSetpoint { value });
+        }
+        if request.kp.is_some() || request.ki.is_some() || request.kd.is_some() {
+            actions.push(RemoteAction::SetGains {
+                kp: request.kp,
+                ki: request.ki,
+                kd: request.kd,
+            });
+        }
+        if actions.is_empty() {
+            return Err("nothing to change".to_string());
+        }
+
+        let mut issued = Vec::new();
+        for action in actions {
+            let command = RemoteCommand::new(&request.controller_id, ISSUER, action);
+            let record = CommandRecord {
+                command_id: command.id.clone(),
+                controller_id: command.controller_id.clone(),
+                issued_by: command.issued_by.clone(),
+                issued_at: command.timestamp,
+                action: describe(&command.action),
+                status: CommandStatus::Pending,
+                resolved_at: None,
+            };
+            let message = command.sign(&self.secret);
+            self.outbox
+                .try_send((command.id, message))
+                .map_err(|_| "command queue is full".to_string())?;
+            info!(
+                "Issued command {} to {}: {}",
+                record.command_id, record.controller_id, record.action
+            );
+            self.record(record.clone());
+            issued.push(record);
+        }
+        Ok(issued)
+    }
+
+    /// Recent commands, newest first
+    pub fn log(&self) -> Vec<CommandRecord> {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.iter().rev().cloned().collect()
+    }
+
+    fn record(&self, record: CommandRecord) {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        if log.len() == LOG_SIZE {
+            log.pop_front();
+        }
+        log.push_back(record);
+    }
+
+    fn resolve(&self, command_id: &str, status: CommandStatus, resolved_at: u64) {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(record) = log.iter_mut().rev().find(|r| r.command_id == command_id) {
+            record.status = status;
+            record.resolved_at = Some(resolved_at);
+        }
+    }
+
+    /// Fold a controller's audit event into the log
+    fn audit(&self, event: AuditEvent) {
+        let status = match event.outcome {
+            CommandOutcome::Applied => CommandStatus::Applied,
+            CommandOutcome::Rejected { reason } => CommandStatus::Rejected { reason },
+        };
+        info!(
+            "Controller {} reported command {} as {:?}",
+            event.controller_id, event.command_id, status
+        );
+
+        let known = {
+            let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+            log.iter().any(|r| r.command_id == event.command_id)
+        };
+        if known {
+            self.resolve(&event.command_id, status, event.timestamp);
+        } else {
+            // Issued by another dashboard or tool sharing the topic
+            self.record(CommandRecord {
+                command_id: event.command_id,
+                controller_id: event.controller_id,
+                issued_by: event.issued_by,
+                issued_at: event.timestamp,
+                action: event.action.as_ref().map_or_else(String::new, describe),
+                status,
+                resolved_at: Some(event.timestamp),
+            });
+        }
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        source: IggySource,
+        command_topic: String,
+        audit_topic: String,
+        mut commands: mpsc::Receiver<(String, Vec<u8>)>,
+    ) {
+        let (Ok(stream), Ok(audit), Ok(consumer_id)) = (
+            Identifier::from_str(&source.stream),
+            Identifier::from_str(&audit_topic),
+            Identifier::named("pidgeoneer-audit"),
+        ) else {
+            error!("❌ Invalid stream or audit topic for remote commands");
+            return;
+        };
+        let consumer = Consumer::new(consumer_id);
+
+        loop {
+            let Some((client, producer)) = connect(&source, &command_topic).await else {
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            };
+            let mut interval = tokio::time::interval(AUDIT_POLL_INTERVAL);
+            loop {
+                tokio::select! {
+                    command = commands.recv() => {
+                        let Some((command_id, signed)) = command else { return };
+                        let message = Message::new(None, signed.into(), None);
+                        if let Err(e) = producer.send(vec![message]).await {
+                            error!("❌ Failed to publish command {}: {}", command_id, e);
+                            self.resolve(
+                                &command_id,
+                                CommandStatus::Rejected {
+                                    reason: format!("not published: {}", e),
+                                },
+                                epoch_millis(),
+                            );
+                            break;
+                        }
+                    }
+                    _ = interval.tick() => {
+                        let polled = client
+                            .poll_messages(
+                                &stream,
+                                &audit,
+                                None,
+                                &consumer,
+                                &PollingStrategy::next(),
+                                16,
+                                true,
+                            )
+                            .await;
+                        match polled {
+                            Ok(polled) => {
+                                for message in polled.messages {
+                                    match serde_json::from_slice::<AuditEvent>(&message.payload) {
+                                        Ok(event) => self.audit(event),
+                                        Err(e) => warn!("Ignoring malformed audit event: {}", e),
+                                    }
+                                }
+                            }
+                            // Controllers create the audit topic when they start listening
+                            Err(iggy::error::IggyError::TopicNameNotFound(..))
+                            | Err(iggy::error::IggyError::TopicIdNotFound(..)) => {}
+                            Err(e) => {
+                                error!("Error polling audit events: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+}
+
+/// Log in to `source` and build a producer for `command_topic`
+async fn connect(source: &IggySource, command_topic: &str) -> Option<(IggyClient, IggyProducer)> {
+    let client = match IggyClient::from_connection_string(&source.connection_string()) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("❌ Failed to create Iggy client for commands: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = client.connect().await {
+        error!("Failed to connect to Iggy server for commands: {}", e);
+        return None;
+    }
+    if let Err(e) = client.login_user(&source.username, &source.password).await {
+        error!("Failed to login to Iggy for commands: {}", e);
+        return None;
+    }
+
+    let mut producer = match client.producer(&source.stream, command_topic) {
+        Ok(builder) => builder
+            .send_interval(IggyDuration::from_str("1ms").unwrap())
+            .partitioning(Partitioning::balanced())
+            .build(),
+        Err(e) => {
+            error!("❌ Failed to create command producer: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = producer.init().await {
+        error!("❌ Failed to initialize command producer: {}", e);
+        return None;
+    }
+    Some((client, producer))
+}
+
+/// Short description of `action` for the audit log
+fn describe(action: &RemoteAction) -> String {
+    match action {
+        RemoteAction::SetSetpoint { value } => format!("setpoint = {}", value),
+        RemoteAction::SetGains { kp, ki, kd } => {
+            let gains: Vec<String> = [("kp", kp), ("ki", ki), ("kd", kd)]
+                .into_iter()
+                .filter_map(|(name, gain)| gain.map(|gain| format!("{} = {}", name, gain)))
+                .collect();
+            gains.join(", ")
+        }
+        RemoteAction::SetMode { mode } => format!("mode = {:?}", mode),
+        RemoteAction::Snapshot => "snapshot".to_string(),
+    }
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// `GET /api/commands` — whether tuning is enabled, and the audit log
+pub async fn commands_handler(tuner: Option<Arc<Tuner>>) -> Response {
+    Json(CommandLog {
+        enabled: tuner.is_some(),
+        commands: tuner.map(|tuner| tuner.log()).unwrap_or_default(),
+    })
+    .into_response()
+}
+
+/// `POST /api/commands` — send a [`TuningRequest`]; operators only
+pub async fn issue_handler(
+    tuner: Option<Arc<Tuner>>,
+    Extension(role): Extension<Role>,
+    Json(request): Json<TuningRequest>,
+) -> Response {
+    if role < Role::Operator {
+        return (StatusCode::FORBIDDEN, "tuning requires the operator role").into_response();
+    }
+    let Some(tuner) = tuner else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "remote commands are disabled; start the server with --command-secret",
+        )
+            .into_response();
+    };
+    match tuner.issue(request) {
+        Ok(issued) => Json(issued).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Auth;
+    use crate::config::ServerConfig;
+    use axum::http::HeaderMap;
+    use clap::Parser;
+
+    #[tokio::test]
+    async fn test_tuning_is_refused_without_tokens() {
+        // The server will not start a tuner that nobody has to log in to use
+        let parsed = ServerConfig::try_parse_from(["pidgeoneer", "--command-secret", "s3cret"]);
+        assert!(parsed.is_err());
+
+        // And a request that slips through without tokens is only a viewer
+        let (outbox, mut commands) = mpsc::channel(COMMAND_QUEUE);
+        let tuner = Arc::new(Tuner {
+            secret: b"s3cret".to_vec(),
+            outbox,
+            log: Mutex::new(VecDeque::new()),
+        });
+        let auth = Auth::new(Vec::new(), Duration::from_secs(60));
+        let role = auth.authenticate(&HeaderMap::new()).unwrap();
+        let request = TuningRequest {
+            controller_id: "oven".to_string(),
+            setpoint: Some(250.0),
+            kp: Some(40.0),
+            ..TuningRequest::default()
+        };
+        let response = issue_handler(Some(tuner.clone()), Extension(role), Json(request)).await;
+        assert!(matches!(
+            response.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ));
+        assert!(commands.try_recv().is_err());
+        assert!(tuner.log().is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::models::{PidControllerData, QuarantinedSample};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use log::*;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Rejected samples kept for inspection
+const QUARANTINE_SIZE: usize = 100;
+/// A controller silent for this long may restart its clock
+const RESTART_AFTER: Duration = Duration::from_secs(5);
+
+/// Why a sample was not forwarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rejection {
+    /// `controller_id` was empty
+    MissingControllerId,
+    /// `schema_version` is newer than this server understands
+    UnsupportedSchema,
+    /// A numeric field was NaN or infinite
+    NonFinite,
+    /// `timestamp` went backwards
+    OutOfOrder,
+    /// The controller exceeded its sample rate
+    RateLimited,
+}
+
+impl Rejection {
+    /// Label used in logs and metrics
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rejection::MissingControllerId => "missing_controller_id",
+            Rejection::UnsupportedSchema => "unsupported_schema",
+            Rejection::NonFinite => "non_finite",
+            Rejection::OutOfOrder => "out_of_order",
+            Rejection::RateLimited => "rate_limited",
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Per-controller validation state
+struct ControllerGuard {
+    last_timestamp: u64,
+    last_accepted: Instant,
+    /// Token bucket holding up to one second of samples
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Screens ingested samples before they are stored or broadcast
+///
+/// Rejects samples with no controller id, an unknown schema version,
+/// non-finite numbers, or a timestamp earlier than the controller's last
+/// one, and rate-limits each controller with a token bucket. Rejected
+/// samples, apart from rate-limited ones, are quarantined for inspection at
+/// `GET /api/quarantine`.
+pub struct IngestGuard {
+    max_rate: Option<f64>,
+    controllers: Mutex<HashMap<String, ControllerGuard>>,
+    rejected: Mutex<BTreeMap<(String, Rejection), u64>>,
+    quarantine: Mutex<VecDeque<QuarantinedSample>>,
+}
+
+impl IngestGuard {
+    /// Create a guard allowing each controller `max_rate` samples per second;
+    /// `None` disables rate limiting
+    pub fn new(max_rate: Option<f64>) -> Self {
+        Self {
+            max_rate: max_rate.filter(|rate| *rate > 0.0),
+            controllers: Mutex::new(HashMap::new()),
+            rejected: Mutex::new(BTreeMap::new()),
+            quarantine: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Accept `sample`, or record and quarantine why it was rejected
+    pub fn admit(&self, sample: &PidControllerData) -> Result<(), Rejection> {
+        let result = self.check(sample, Instant::now());
+        if let Err(rejection) = result {
+            let mut rejected = self.rejected.lock().unwrap_or_else(|e| e.into_inner());
+            let count = rejected
+                .entry((sample.controller_id.clone(), rejection))
+                .or_default();
+            *count += 1;
+            // Log the first rejection of each kind, then every thousandth
+            if *count % 1000 == 1 {
+                warn!(
+                    "Rejected {} sample(s) from controller '{}': {}",
+                    count, sample.controller_id, rejection
+                );
+            }
+            drop(rejected);
+
+            if rejection != Rejection::RateLimited {
+                let mut quarantine = self.quarantine.lock().unwrap_or_else(|e| e.into_inner());
+                if quarantine.len() == QUARANTINE_SIZE {
+                    quarantine.pop_front();
+                }
+                quarantine.push_back(QuarantinedSample {
+                    received_at: epoch_millis(),
+                    reason: rejection.to_string(),
+                    sample: sample.clone(),
+                });
+            }
+        }
+        result
+    }
+
+    fn check(&self, sample: &PidControllerData, now: Instant) -> Result<(), Rejection> {
+        if sample.controller_id.is_empty() {
+            return Err(Rejection::MissingControllerId);
+        }
+        if sample.schema_version == 0 || sample.schema_version > pidgeon::DEBUG_SCHEMA_VERSION {
+            return Err(Rejection::UnsupportedSchema);
+        }
+        let numbers = [
+            sample.setpoint,
+            sample.process_value,
+            sample.error,
+            sample.output,
+            sample.p_term,
+            sample.i_term,
+            sample.d_term,
+            sample.dt,
+            sample.kp,
+            sample.ki,
+            sample.kd,
+        ];
+        if !numbers.iter().all(|n| n.is_finite()) {
+            return Err(Rejection::NonFinite);
+        }
+
+        let mut controllers = self.controllers.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(guard) = controllers.get_mut(&sample.controller_id) else {
+            controllers.insert(
+                sample.controller_id.clone(),
+                ControllerGuard {
+                    last_timestamp: sample.timestamp,
+                    last_accepted: now,
+                    tokens: self.max_rate.map_or(0.0, |rate| rate - 1.0),
+                    refilled: now,
+                },
+            );
+            return Ok(());
+        };
+
+        if sample.timestamp < guard.last_timestamp
+            && now.duration_since(guard.last_accepted) < RESTART_AFTER
+        {
+            return Err(Rejection::OutOfOrder);
+        }
+        if let Some(rate) = self.max_rate {
+            let elapsed = now.duration_since(guard.refilled).as_secs_f64();
+            guard.tokens = (guard.tokens + elapsed * rate).min(rate);
+            guard.refilled = now;
+            if guard.tokens < 1.0 {
+                return Err(Rejection::RateLimited);
+            }
+            guard.tokens -= 1.0;
+        }
+        guard.last_timestamp = sample.timestamp;
+        guard.last_accepted = now;
+        Ok(())
+    }
+
+    /// Samples rejected so far, by controller and reason
+    pub fn rejected_counts(&self) -> Vec<(String, Rejection, u64)> {
+        let rejected = self.rejected.lock().unwrap_or_else(|e| e.into_inner());
+        rejected
+            .iter()
+            .map(|((controller_id, rejection), count)| (controller_id.clone(), *rejection, *count))
+            .collect()
+    }
+
+    /// Recently quarantined samples, newest first
+    pub fn quarantined(&self) -> Vec<QuarantinedSample> {
+        let quarantine = self.quarantine.lock().unwrap_or_else(|e| e.into_inner());
+        quarantine.iter().rev().cloned().collect()
+    }
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// `GET /api/quarantine` — recently rejected samples, newest first
+pub async fn quarantine_handler(guard: Arc<IngestGuard>) -> Response {
+    Json(guard.quarantined()).into_response()
+}
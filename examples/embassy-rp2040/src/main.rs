@@ -0,0 +1,81 @@
+//! Heater control on a Raspberry Pi Pico (RP2040) with embassy.
+//!
+//! - GPIO26 / ADC0: temperature sensor producing 0-3.3 V for 0-100 degC
+//! - GPIO15 / PWM7B: heater driver (MOSFET gate), 10 kHz PWM
+//!
+//! The PID loop runs at 100 Hz from an `embassy-time` ticker. Build and flash
+//! with `cargo run --release` (requires `probe-rs` and a debug probe).
+
+#![no_std]
+#![no_main]
+
+use defmt::{error, info};
+use embassy_executor::Spawner;
+use embassy_rp::adc::{self, Adc, Channel};
+use embassy_rp::bind_interrupts;
+use embassy_rp::gpio::Pull;
+use embassy_rp::pwm::{self, Pwm};
+use embassy_time::Duration;
+use pidgeon::{AdcSensor, ControllerConfig, HalLoop, PwmActuator, TickerLoop};
+use {defmt_rtt as _, panic_probe as _};
+
+bind_interrupts!(struct Irqs {
+    ADC_IRQ_FIFO => adc::InterruptHandler;
+});
+
+const LOOP_PERIOD: Duration = Duration::from_millis(10);
+const TARGET_TEMPERATURE: f64 = 45.0;
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let p = embassy_rp::init(Default::default());
+
+    // Sensor: 12-bit ADC scaled to degrees Celsius
+    let mut adc = Adc::new(p.ADC, Irqs, adc::Config::default());
+    let mut channel = Channel::new_pin(p.PIN_26, Pull::None);
+    let sensor = AdcSensor::new(move || adc.blocking_read(&mut channel), 4095, 0.0, 100.0).unwrap();
+
+    // Actuator: 125 MHz / 12_500 = 10 kHz PWM on channel B of slice 7
+    let mut pwm_config = pwm::Config::default();
+    pwm_config.top = 12_500;
+    let pwm = Pwm::new_output_b(p.PWM_SLICE7, p.PIN_15, pwm_config);
+    let (_, heater) = pwm.split();
+
+    let config = ControllerConfig::builder()
+        .with_kp(8.0)
+        .with_ki(0.4)
+        .with_kd(1.5)
+        .with_setpoint(TARGET_TEMPERATURE)
+        .with_output_limits(0.0, 100.0)
+        .build()
+        .unwrap();
+
+    let actuator = PwmActuator::for_config(heater.unwrap(), &config);
+    let mut pid = TickerLoop::new(HalLoop::new(config, sensor, actuator), LOOP_PERIOD);
+
+    info!(
+        "pidgeon: heater loop at {} Hz, target {} degC",
+        1.0 / pid.dt(),
+        TARGET_TEMPERATURE
+    );
+
+    let mut cycles: u32 = 0;
+    loop {
+        match pid.tick().await {
+            Ok(output) => {
+                cycles = cycles.wrapping_add(1);
+                // Report once per second
+                if cycles.is_multiple_of(100) {
+                    let state = pid.hal_loop().state();
+                    info!(
+                        "output={}% integral={}",
+                        output, state.integral_contribution
+                    );
+                }
+            }
+            Err(e) => {
+                error!("control step failed: {}", defmt::Debug2Format(&e));
+            }
+        }
+    }
+}